@@ -0,0 +1,110 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::time::Duration;
+
+use cheetah_string::CheetahString;
+use clap::Args;
+use clap::ValueEnum;
+use rocketmq_common::common::message::message_single::Message;
+
+/// Options shared by every benchmark binary.
+#[derive(Args, Debug, Clone)]
+pub struct BenchArgs {
+    /// Name server address list, eg: '192.168.0.1:9876;192.168.0.2:9876'
+    #[arg(short, long, default_value = "127.0.0.1:9876")]
+    pub namesrv_addr: String,
+
+    /// Topic to benchmark against
+    #[arg(short, long, default_value = "BenchmarkTest")]
+    pub topic: String,
+
+    /// Number of concurrent sending tasks
+    #[arg(short = 'w', long, default_value_t = 64)]
+    pub concurrency: usize,
+
+    /// Stop after this many seconds, 0 runs until interrupted
+    #[arg(short, long, default_value_t = 0)]
+    pub duration: u64,
+
+    /// Seconds between two progress reports
+    #[arg(long, default_value_t = 1)]
+    pub report_interval: u64,
+}
+
+impl BenchArgs {
+    pub fn duration(&self) -> Option<Duration> {
+        (self.duration > 0).then(|| Duration::from_secs(self.duration))
+    }
+
+    pub fn report_interval(&self) -> Duration {
+        Duration::from_secs(self.report_interval.max(1))
+    }
+}
+
+/// How the producer benchmark hands messages to the broker.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendMode {
+    /// Wait for every send result before sending the next message
+    Sync,
+    /// Send with a callback, keeping up to `concurrency` requests in flight
+    Async,
+    /// Fire and forget, only the client side cost is measured
+    Oneway,
+}
+
+/// Shape of the messages the producer benchmarks send.
+#[derive(Args, Debug, Clone)]
+pub struct MessageArgs {
+    /// Message body size in bytes
+    #[arg(short = 's', long, default_value_t = 128)]
+    pub message_size: usize,
+
+    /// Number of distinct tags to rotate through, 0 sends untagged messages
+    #[arg(short = 'c', long, default_value_t = 0)]
+    pub tag_count: usize,
+
+    /// Attach a unique key to every message
+    #[arg(short, long)]
+    pub keys: bool,
+
+    /// Stop after this many messages in total, 0 means no limit
+    #[arg(short = 'm', long, default_value_t = 0)]
+    pub message_count: u64,
+}
+
+impl MessageArgs {
+    /// Builds the `sequence`-th message of the run.
+    pub fn build(&self, topic: &str, body: &[u8], sequence: u64) -> Message {
+        let mut message = Message::new(topic, body);
+        if self.tag_count > 0 {
+            message.set_tags(CheetahString::from_string(format!(
+                "tag{}",
+                sequence % self.tag_count as u64
+            )));
+        }
+        if self.keys {
+            message.set_keys(CheetahString::from_string(format!("key{sequence}")));
+        }
+        message
+    }
+
+    pub fn body(&self) -> Vec<u8> {
+        // A repeating alphabet keeps bodies readable when inspecting the store.
+        (b'a'..=b'z').cycle().take(self.message_size).collect()
+    }
+}
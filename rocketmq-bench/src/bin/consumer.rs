@@ -0,0 +1,118 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use clap::Parser;
+use rocketmq_bench::BenchArgs;
+use rocketmq_bench::BenchStats;
+use rocketmq_client_rust::consumer::default_mq_push_consumer::DefaultMQPushConsumer;
+use rocketmq_client_rust::consumer::listener::consume_concurrently_context::ConsumeConcurrentlyContext;
+use rocketmq_client_rust::consumer::listener::consume_concurrently_status::ConsumeConcurrentlyStatus;
+use rocketmq_client_rust::consumer::listener::message_listener_concurrently::MessageListenerConcurrently;
+use rocketmq_client_rust::consumer::mq_push_consumer::MQPushConsumer;
+use rocketmq_common::common::message::message_ext::MessageExt;
+use rocketmq_common::TimeUtils::get_current_millis;
+use rocketmq_error::RocketMQResult;
+use rocketmq_rust::rocketmq;
+
+/// Measures push consumer throughput and end-to-end delay.
+#[derive(Parser, Debug)]
+#[command(name = "consumer", about = "RocketMQ push consumer benchmark")]
+struct Args {
+    #[command(flatten)]
+    bench: BenchArgs,
+
+    /// Consumer group
+    #[arg(short, long, default_value = "benchmark_consumer")]
+    group: String,
+
+    /// Subscription expression
+    #[arg(short, long, default_value = "*")]
+    expression: String,
+}
+
+/// Records how long each message took from the producer (born timestamp) to the listener, and
+/// separately from the broker (store timestamp) to the listener.
+struct BenchListener {
+    born_to_consume: Arc<BenchStats>,
+    store_to_consume: Arc<BenchStats>,
+}
+
+impl MessageListenerConcurrently for BenchListener {
+    fn consume_message(
+        &self,
+        msgs: &[&MessageExt],
+        _context: &ConsumeConcurrentlyContext,
+    ) -> RocketMQResult<ConsumeConcurrentlyStatus> {
+        let now = get_current_millis() as i64;
+        for msg in msgs {
+            self.born_to_consume
+                .record_success(delay_since(now, msg.born_timestamp()));
+            self.store_to_consume
+                .record_success(delay_since(now, msg.store_timestamp()));
+        }
+        Ok(ConsumeConcurrentlyStatus::ConsumeSuccess)
+    }
+}
+
+fn delay_since(now: i64, timestamp: i64) -> Duration {
+    // Clocks of producer, broker and consumer hosts may disagree, never report a negative delay.
+    Duration::from_millis(now.saturating_sub(timestamp).max(0) as u64)
+}
+
+#[rocketmq::main]
+async fn main() -> RocketMQResult<()> {
+    rocketmq_common::log::init_logger_with_level(rocketmq_common::log::Level::WARN);
+    let args = Args::parse();
+    println!("{args:?}");
+
+    let born_to_consume = Arc::new(BenchStats::new("Consume"));
+    let store_to_consume = Arc::new(BenchStats::new("Store to consume"));
+    let concurrency = args.bench.concurrency.max(1) as u32;
+    let mut consumer = DefaultMQPushConsumer::builder()
+        .consumer_group(args.group.clone())
+        .name_server_addr(args.bench.namesrv_addr.clone())
+        .consume_thread_min(concurrency)
+        .consume_thread_max(concurrency)
+        .build();
+    consumer.subscribe(args.bench.topic.as_str(), args.expression.as_str())?;
+    consumer.register_message_listener_concurrently(BenchListener {
+        born_to_consume: born_to_consume.clone(),
+        store_to_consume: store_to_consume.clone(),
+    });
+    consumer.start().await?;
+
+    let reporter = born_to_consume.spawn_reporter(args.bench.report_interval());
+    match args.bench.duration() {
+        Some(duration) => {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = tokio::time::sleep(duration) => {}
+            }
+        }
+        None => {
+            let _ = tokio::signal::ctrl_c().await;
+        }
+    }
+    reporter.abort();
+    println!("{}", born_to_consume.summary());
+    println!("{}", store_to_consume.summary());
+    consumer.shutdown().await;
+    Ok(())
+}
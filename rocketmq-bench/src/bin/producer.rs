@@ -0,0 +1,143 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::sync::atomic::AtomicU64;
+use std::sync::Arc;
+use std::time::Instant;
+
+use clap::Parser;
+use parking_lot::Mutex;
+use rocketmq_bench::BenchArgs;
+use rocketmq_bench::BenchStats;
+use rocketmq_bench::MessageArgs;
+use rocketmq_bench::SendMode;
+use rocketmq_bench::StopSignal;
+use rocketmq_client_rust::producer::default_mq_producer::DefaultMQProducer;
+use rocketmq_client_rust::producer::mq_producer::MQProducer;
+use rocketmq_client_rust::producer::send_status::SendStatus;
+use rocketmq_error::RocketMQResult;
+use rocketmq_rust::rocketmq;
+use tokio::sync::Semaphore;
+
+/// Measures producer throughput and send latency.
+#[derive(Parser, Debug)]
+#[command(name = "producer", about = "RocketMQ producer benchmark")]
+struct Args {
+    #[command(flatten)]
+    bench: BenchArgs,
+
+    #[command(flatten)]
+    message: MessageArgs,
+
+    /// Producer group
+    #[arg(short, long, default_value = "benchmark_producer")]
+    group: String,
+
+    /// How messages are sent
+    #[arg(long, value_enum, default_value_t = SendMode::Sync)]
+    mode: SendMode,
+
+    /// Send timeout in milliseconds
+    #[arg(long, default_value_t = 3000)]
+    send_timeout: u32,
+}
+
+#[rocketmq::main]
+async fn main() -> RocketMQResult<()> {
+    rocketmq_common::log::init_logger_with_level(rocketmq_common::log::Level::WARN);
+    let args = Args::parse();
+    println!("{args:?}");
+
+    let mut producer = DefaultMQProducer::builder()
+        .producer_group(args.group.clone())
+        .name_server_addr(args.bench.namesrv_addr.clone())
+        .send_msg_timeout(args.send_timeout)
+        .build();
+    producer.start().await?;
+
+    let stats = Arc::new(BenchStats::new("Send"));
+    let stop = StopSignal::new(args.bench.duration(), args.message.message_count);
+    let reporter = stats.spawn_reporter(args.bench.report_interval());
+    let body: Arc<[u8]> = args.message.body().into();
+    let sequence = Arc::new(AtomicU64::new(0));
+    // Bounds the number of async sends waiting for a response.
+    let in_flight = Arc::new(Semaphore::new(args.bench.concurrency));
+
+    let mut workers = Vec::with_capacity(args.bench.concurrency);
+    for _ in 0..args.bench.concurrency {
+        let mut producer = producer.clone();
+        let stats = stats.clone();
+        let stop = stop.clone();
+        let body = body.clone();
+        let sequence = sequence.clone();
+        let in_flight = in_flight.clone();
+        let topic = args.bench.topic.clone();
+        let message_args = args.message.clone();
+        let mode = args.mode;
+        workers.push(tokio::spawn(async move {
+            while let Some(seq) = stop.next_message(&sequence) {
+                let message = message_args.build(&topic, &body, seq);
+                let begin = Instant::now();
+                match mode {
+                    SendMode::Sync => match producer.send(message).await {
+                        Ok(result) if result.send_status == SendStatus::SendOk => {
+                            stats.record_success(begin.elapsed())
+                        }
+                        _ => stats.record_failure(),
+                    },
+                    SendMode::Async => {
+                        let Ok(permit) = in_flight.clone().acquire_owned().await else {
+                            break;
+                        };
+                        // The callback is `Fn`, so the permit is moved out on first call.
+                        let permit = Mutex::new(Some(permit));
+                        let callback_stats = stats.clone();
+                        let sent = producer
+                            .send_with_callback(message, move |result, _error| {
+                                match result {
+                                    Some(result) if result.send_status == SendStatus::SendOk => {
+                                        callback_stats.record_success(begin.elapsed())
+                                    }
+                                    _ => callback_stats.record_failure(),
+                                }
+                                permit.lock().take();
+                            })
+                            .await;
+                        if sent.is_err() {
+                            stats.record_failure();
+                        }
+                    }
+                    SendMode::Oneway => match producer.send_oneway(message).await {
+                        Ok(()) => stats.record_success(begin.elapsed()),
+                        Err(_) => stats.record_failure(),
+                    },
+                }
+            }
+        }));
+    }
+    for worker in workers {
+        let _ = worker.await;
+    }
+    if args.mode == SendMode::Async {
+        // Wait for the callbacks of the last requests before summarizing.
+        let _ = in_flight.acquire_many(args.bench.concurrency as u32).await;
+    }
+    reporter.abort();
+    println!("{}", stats.summary());
+    producer.shutdown().await;
+    Ok(())
+}
@@ -0,0 +1,165 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::any::Any;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Instant;
+
+use clap::Parser;
+use rocketmq_bench::BenchArgs;
+use rocketmq_bench::BenchStats;
+use rocketmq_bench::MessageArgs;
+use rocketmq_bench::StopSignal;
+use rocketmq_client_rust::producer::local_transaction_state::LocalTransactionState;
+use rocketmq_client_rust::producer::mq_producer::MQProducer;
+use rocketmq_client_rust::producer::send_status::SendStatus;
+use rocketmq_client_rust::producer::transaction_listener::TransactionListener;
+use rocketmq_client_rust::producer::transaction_mq_producer::TransactionMQProducer;
+use rocketmq_common::common::message::message_ext::MessageExt;
+use rocketmq_common::common::message::message_single::Message;
+use rocketmq_error::RocketMQResult;
+use rocketmq_rust::rocketmq;
+
+/// Measures half message send plus local transaction latency.
+#[derive(Parser, Debug)]
+#[command(
+    name = "transaction-producer",
+    about = "RocketMQ transaction producer benchmark"
+)]
+struct Args {
+    #[command(flatten)]
+    bench: BenchArgs,
+
+    #[command(flatten)]
+    message: MessageArgs,
+
+    /// Producer group
+    #[arg(short, long, default_value = "benchmark_transaction_producer")]
+    group: String,
+
+    /// Percentage of local transactions that roll back
+    #[arg(long, default_value_t = 0)]
+    rollback_percent: u64,
+
+    /// Percentage of local transactions left unknown, to be committed by the broker check
+    #[arg(long, default_value_t = 0)]
+    unknown_percent: u64,
+}
+
+/// Decides every local transaction from a running counter so the configured shares of rollback
+/// and unknown states are met exactly; unknown transactions are committed when checked.
+struct BenchTransactionListener {
+    rollback_percent: u64,
+    unknown_percent: u64,
+    executed: AtomicU64,
+    checked: Arc<AtomicU64>,
+}
+
+impl TransactionListener for BenchTransactionListener {
+    fn execute_local_transaction(
+        &self,
+        _msg: &Message,
+        _arg: Option<&(dyn Any + Send + Sync)>,
+    ) -> LocalTransactionState {
+        let slot = self.executed.fetch_add(1, Ordering::Relaxed) % 100;
+        if slot < self.rollback_percent {
+            LocalTransactionState::RollbackMessage
+        } else if slot < self.rollback_percent + self.unknown_percent {
+            LocalTransactionState::Unknown
+        } else {
+            LocalTransactionState::CommitMessage
+        }
+    }
+
+    fn check_local_transaction(&self, _msg: &MessageExt) -> LocalTransactionState {
+        self.checked.fetch_add(1, Ordering::Relaxed);
+        LocalTransactionState::CommitMessage
+    }
+}
+
+#[rocketmq::main]
+async fn main() -> RocketMQResult<()> {
+    rocketmq_common::log::init_logger_with_level(rocketmq_common::log::Level::WARN);
+    let args = Args::parse();
+    println!("{args:?}");
+
+    let checked = Arc::new(AtomicU64::new(0));
+    let mut producer = TransactionMQProducer::builder()
+        .producer_group(args.group.clone())
+        .name_server_addr(args.bench.namesrv_addr.clone())
+        .topics(vec![args.bench.topic.clone()])
+        .transaction_listener(BenchTransactionListener {
+            rollback_percent: args.rollback_percent.min(100),
+            unknown_percent: args
+                .unknown_percent
+                .min(100 - args.rollback_percent.min(100)),
+            executed: AtomicU64::new(0),
+            checked: checked.clone(),
+        })
+        .build();
+    producer.start().await?;
+
+    let stats = Arc::new(BenchStats::new("Transaction send"));
+    let stop = StopSignal::new(args.bench.duration(), args.message.message_count);
+    let reporter = stats.spawn_reporter(args.bench.report_interval());
+    let body: Arc<[u8]> = args.message.body().into();
+    let sequence = Arc::new(AtomicU64::new(0));
+
+    let mut workers = Vec::with_capacity(args.bench.concurrency);
+    for _ in 0..args.bench.concurrency {
+        let mut producer = producer.clone();
+        let stats = stats.clone();
+        let stop = stop.clone();
+        let body = body.clone();
+        let sequence = sequence.clone();
+        let topic = args.bench.topic.clone();
+        let message_args = args.message.clone();
+        workers.push(tokio::spawn(async move {
+            while let Some(seq) = stop.next_message(&sequence) {
+                let message = message_args.build(&topic, &body, seq);
+                let begin = Instant::now();
+                match producer
+                    .send_message_in_transaction::<()>(message, None)
+                    .await
+                {
+                    Ok(result)
+                        if result
+                            .send_result
+                            .as_ref()
+                            .is_some_and(|result| result.send_status == SendStatus::SendOk) =>
+                    {
+                        stats.record_success(begin.elapsed())
+                    }
+                    _ => stats.record_failure(),
+                }
+            }
+        }));
+    }
+    for worker in workers {
+        let _ = worker.await;
+    }
+    reporter.abort();
+    println!("{}", stats.summary());
+    println!(
+        "transaction checks answered: {}",
+        checked.load(Ordering::Relaxed)
+    );
+    producer.shutdown().await;
+    Ok(())
+}
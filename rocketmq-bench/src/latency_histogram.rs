@@ -0,0 +1,173 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+/// Values below `SUB_BUCKET_COUNT` get an exact bucket each. Above that, every power of two is
+/// split into `SUB_BUCKET_HALF` buckets, which bounds the relative error of a percentile to
+/// 1 / `SUB_BUCKET_HALF`.
+const SUB_BUCKET_BITS: u32 = 5;
+const SUB_BUCKET_COUNT: u64 = 1 << SUB_BUCKET_BITS;
+const SUB_BUCKET_HALF: u64 = SUB_BUCKET_COUNT >> 1;
+const BUCKET_COUNT: usize =
+    (SUB_BUCKET_COUNT + (64 - SUB_BUCKET_BITS as u64 + 1) * SUB_BUCKET_HALF) as usize;
+
+/// Log-linear histogram of latencies in microseconds.
+///
+/// Recording is a couple of relaxed atomic increments, so one histogram can be shared by all
+/// sending tasks.
+pub struct LatencyHistogram {
+    buckets: Box<[AtomicU64]>,
+    count: AtomicU64,
+    sum: AtomicU64,
+    max: AtomicU64,
+}
+
+impl LatencyHistogram {
+    pub fn new() -> Self {
+        LatencyHistogram {
+            buckets: (0..BUCKET_COUNT).map(|_| AtomicU64::new(0)).collect(),
+            count: AtomicU64::new(0),
+            sum: AtomicU64::new(0),
+            max: AtomicU64::new(0),
+        }
+    }
+
+    #[inline]
+    pub fn record(&self, latency: Duration) {
+        self.record_micros(latency.as_micros().min(u64::MAX as u128) as u64);
+    }
+
+    pub fn record_micros(&self, micros: u64) {
+        self.buckets[bucket_index(micros)].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum.fetch_add(micros, Ordering::Relaxed);
+        self.max.fetch_max(micros, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    #[inline]
+    pub fn max_micros(&self) -> u64 {
+        self.max.load(Ordering::Relaxed)
+    }
+
+    pub fn mean_micros(&self) -> f64 {
+        let count = self.count();
+        if count == 0 {
+            return 0.0;
+        }
+        self.sum.load(Ordering::Relaxed) as f64 / count as f64
+    }
+
+    /// Returns the latency at `percentile` (0-100], reported as the upper bound of the bucket it
+    /// falls into, or 0 if nothing has been recorded.
+    pub fn percentile_micros(&self, percentile: f64) -> u64 {
+        let count = self.count();
+        if count == 0 {
+            return 0;
+        }
+        let rank = ((percentile / 100.0) * count as f64)
+            .ceil()
+            .clamp(1.0, count as f64) as u64;
+        let mut seen = 0;
+        for (index, bucket) in self.buckets.iter().enumerate() {
+            seen += bucket.load(Ordering::Relaxed);
+            if seen >= rank {
+                return bucket_upper_bound(index).min(self.max_micros());
+            }
+        }
+        self.max_micros()
+    }
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[inline]
+fn bucket_index(value: u64) -> usize {
+    if value < SUB_BUCKET_COUNT {
+        return value as usize;
+    }
+    let shift = (63 - value.leading_zeros()) - (SUB_BUCKET_BITS - 1);
+    (SUB_BUCKET_COUNT + (shift as u64 - 1) * SUB_BUCKET_HALF + ((value >> shift) - SUB_BUCKET_HALF))
+        as usize
+}
+
+#[inline]
+fn bucket_upper_bound(index: usize) -> u64 {
+    let index = index as u64;
+    if index < SUB_BUCKET_COUNT {
+        return index;
+    }
+    let offset = index - SUB_BUCKET_COUNT;
+    let shift = offset / SUB_BUCKET_HALF + 1;
+    let sub_bucket = offset % SUB_BUCKET_HALF + SUB_BUCKET_HALF;
+    ((((sub_bucket + 1) as u128) << shift) - 1).min(u64::MAX as u128) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_bounds_cover_their_values() {
+        for value in (0..100_000u64).chain([u64::MAX / 3, u64::MAX]) {
+            let index = bucket_index(value);
+            assert!(index < BUCKET_COUNT);
+            assert!(bucket_upper_bound(index) >= value);
+            if index > 0 {
+                assert!(bucket_upper_bound(index - 1) < value);
+            }
+        }
+    }
+
+    #[test]
+    fn percentiles_of_uniform_latencies() {
+        let histogram = LatencyHistogram::new();
+        for micros in 1..=10_000 {
+            histogram.record_micros(micros);
+        }
+        assert_eq!(histogram.count(), 10_000);
+        assert_eq!(histogram.max_micros(), 10_000);
+        assert!((histogram.mean_micros() - 5_000.5).abs() < f64::EPSILON);
+
+        for (percentile, expected) in [(50.0, 5_000u64), (99.0, 9_900), (100.0, 10_000)] {
+            let actual = histogram.percentile_micros(percentile);
+            let error = actual.abs_diff(expected) as f64 / expected as f64;
+            assert!(
+                error <= 1.0 / SUB_BUCKET_HALF as f64,
+                "p{percentile}: {actual}"
+            );
+        }
+    }
+
+    #[test]
+    fn empty_histogram_reports_zero() {
+        let histogram = LatencyHistogram::new();
+        assert_eq!(histogram.percentile_micros(99.0), 0);
+        assert_eq!(histogram.mean_micros(), 0.0);
+    }
+}
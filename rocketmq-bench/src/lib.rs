@@ -0,0 +1,30 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Shared pieces of the benchmark binaries: command line options, a lock-free latency histogram
+//! and the periodic TPS/latency reporter.
+
+pub use self::args::BenchArgs;
+pub use self::args::MessageArgs;
+pub use self::args::SendMode;
+pub use self::latency_histogram::LatencyHistogram;
+pub use self::stats::BenchStats;
+pub use self::stats::StopSignal;
+
+mod args;
+mod latency_histogram;
+mod stats;
@@ -0,0 +1,234 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use tokio::task::JoinHandle;
+
+use crate::LatencyHistogram;
+
+const REPORTED_PERCENTILES: [f64; 5] = [50.0, 90.0, 99.0, 99.9, 99.99];
+
+/// Counters of one benchmark run.
+///
+/// `latency` holds the request latency for producers and the born-to-consume delay for
+/// consumers; failures are requests that returned an error or a non-OK status.
+pub struct BenchStats {
+    label: &'static str,
+    succeeded: AtomicU64,
+    failed: AtomicU64,
+    latency: LatencyHistogram,
+    started: Instant,
+}
+
+impl BenchStats {
+    pub fn new(label: &'static str) -> Self {
+        BenchStats {
+            label,
+            succeeded: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+            latency: LatencyHistogram::new(),
+            started: Instant::now(),
+        }
+    }
+
+    #[inline]
+    pub fn record_success(&self, latency: Duration) {
+        self.succeeded.fetch_add(1, Ordering::Relaxed);
+        self.latency.record(latency);
+    }
+
+    #[inline]
+    pub fn record_failure(&self) {
+        self.failed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn succeeded(&self) -> u64 {
+        self.succeeded.load(Ordering::Relaxed)
+    }
+
+    #[inline]
+    pub fn failed(&self) -> u64 {
+        self.failed.load(Ordering::Relaxed)
+    }
+
+    #[inline]
+    pub fn latency(&self) -> &LatencyHistogram {
+        &self.latency
+    }
+
+    /// Prints throughput and latency every `interval` until the returned task is aborted.
+    pub fn spawn_reporter(self: &Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        let stats = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.tick().await;
+            let mut last_succeeded = 0;
+            let mut last_failed = 0;
+            let mut last_tick = Instant::now();
+            loop {
+                ticker.tick().await;
+                let succeeded = stats.succeeded();
+                let failed = stats.failed();
+                let elapsed = last_tick.elapsed().as_secs_f64();
+                last_tick = Instant::now();
+                println!(
+                    "{} TPS: {:.0} | Failed TPS: {:.0} | Avg RT(ms): {:.3} | Max RT(ms): {:.3} | \
+                     Total: {} | Total Failed: {}",
+                    stats.label,
+                    (succeeded - last_succeeded) as f64 / elapsed,
+                    (failed - last_failed) as f64 / elapsed,
+                    stats.latency.mean_micros() / 1000.0,
+                    stats.latency.max_micros() as f64 / 1000.0,
+                    succeeded,
+                    failed
+                );
+                last_succeeded = succeeded;
+                last_failed = failed;
+            }
+        })
+    }
+
+    /// Summary of the whole run, printed when a benchmark exits.
+    pub fn summary(&self) -> String {
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let mut summary = format!(
+            "[{}] elapsed: {:.1}s | succeeded: {} | failed: {} | avg TPS: {:.0} | avg RT(ms): \
+             {:.3}",
+            self.label,
+            elapsed,
+            self.succeeded(),
+            self.failed(),
+            self.succeeded() as f64 / elapsed.max(f64::MIN_POSITIVE),
+            self.latency.mean_micros() / 1000.0
+        );
+        for percentile in REPORTED_PERCENTILES {
+            summary.push_str(&format!(
+                " | p{}(ms): {:.3}",
+                percentile,
+                self.latency.percentile_micros(percentile) as f64 / 1000.0
+            ));
+        }
+        summary.push_str(&format!(
+            " | max(ms): {:.3}",
+            self.latency.max_micros() as f64 / 1000.0
+        ));
+        summary
+    }
+}
+
+/// Tells the sending tasks when to stop: on Ctrl-C, once the configured duration has passed or
+/// when the message budget is used up.
+#[derive(Clone)]
+pub struct StopSignal {
+    stopped: Arc<AtomicBool>,
+    remaining: Option<Arc<AtomicU64>>,
+}
+
+impl StopSignal {
+    pub fn new(duration: Option<Duration>, message_count: u64) -> Self {
+        let signal = StopSignal {
+            stopped: Arc::new(AtomicBool::new(false)),
+            remaining: (message_count > 0).then(|| Arc::new(AtomicU64::new(message_count))),
+        };
+        let stopper = signal.clone();
+        tokio::spawn(async move {
+            match duration {
+                Some(duration) => {
+                    tokio::select! {
+                        _ = tokio::signal::ctrl_c() => {}
+                        _ = tokio::time::sleep(duration) => {}
+                    }
+                }
+                None => {
+                    let _ = tokio::signal::ctrl_c().await;
+                }
+            }
+            stopper.stop();
+        });
+        signal
+    }
+
+    #[inline]
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::Release);
+    }
+
+    #[inline]
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Acquire)
+    }
+
+    /// Claims the next message slot, returning its sequence number, or `None` once the run
+    /// should end.
+    pub fn next_message(&self, sequence: &AtomicU64) -> Option<u64> {
+        if self.is_stopped() {
+            return None;
+        }
+        if let Some(remaining) = &self.remaining {
+            let claimed = remaining
+                .fetch_update(Ordering::AcqRel, Ordering::Acquire, |left| {
+                    left.checked_sub(1)
+                })
+                .is_ok();
+            if !claimed {
+                self.stop();
+                return None;
+            }
+        }
+        Some(sequence.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// Resolves once the run has been stopped.
+    pub async fn wait(&self) {
+        while !self.is_stopped() {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn message_budget_stops_the_run() {
+        let signal = StopSignal::new(None, 3);
+        let sequence = AtomicU64::new(0);
+        assert_eq!(signal.next_message(&sequence), Some(0));
+        assert_eq!(signal.next_message(&sequence), Some(1));
+        assert_eq!(signal.next_message(&sequence), Some(2));
+        assert_eq!(signal.next_message(&sequence), None);
+        assert!(signal.is_stopped());
+    }
+
+    #[test]
+    fn summary_reports_percentiles() {
+        let stats = BenchStats::new("Send");
+        stats.record_success(Duration::from_millis(2));
+        stats.record_failure();
+        let summary = stats.summary();
+        assert!(summary.contains("succeeded: 1 | failed: 1"), "{summary}");
+        assert!(summary.contains("p99(ms): 2.000"), "{summary}");
+    }
+}
@@ -0,0 +1,258 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::time::Instant;
+
+use cheetah_string::CheetahString;
+use parking_lot::RwLock;
+use rocketmq_common::common::broker::broker_config::BrokerConfig;
+use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::code::response_code::RemotingSysResponseCode;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use tracing::info;
+use tracing::warn;
+
+/// Target of the audit events, so they can be routed apart from the rest of the broker log.
+pub(crate) const AUDIT_TARGET: &str = "rocketmq_broker::audit";
+
+const ANY_REQUEST_CODE: &str = "*";
+
+/// The share of the requests of each request code that is audited.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct SampleRates {
+    code_rates: HashMap<i32, f64>,
+    default_rate: f64,
+}
+
+impl SampleRates {
+    /// Parses `code:rate` pairs separated by `,`, with `*` in place of the code for the rate of
+    /// the codes not listed.
+    pub fn parse(rates: &str) -> Result<Self, String> {
+        let mut sample_rates = SampleRates::default();
+        for pair in rates
+            .split(',')
+            .map(str::trim)
+            .filter(|pair| !pair.is_empty())
+        {
+            let (code, rate) = pair
+                .split_once(':')
+                .ok_or_else(|| format!("sample rate `{pair}` is not a code:rate pair"))?;
+            let rate = rate
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|rate| (0.0..=1.0).contains(rate))
+                .ok_or_else(|| format!("sample rate of `{pair}` is not between 0 and 1"))?;
+            match code.trim() {
+                ANY_REQUEST_CODE => sample_rates.default_rate = rate,
+                code => {
+                    let code = code
+                        .parse::<i32>()
+                        .map_err(|_| format!("request code of `{pair}` is not a number"))?;
+                    sample_rates.code_rates.insert(code, rate);
+                }
+            }
+        }
+        Ok(sample_rates)
+    }
+
+    pub fn rate(&self, request_code: i32) -> f64 {
+        self.code_rates
+            .get(&request_code)
+            .copied()
+            .unwrap_or(self.default_rate)
+    }
+}
+
+/// Metadata of a sampled request, logged once the request is answered.
+#[derive(Debug)]
+pub(crate) struct AuditRecord {
+    request_code: i32,
+    group: Option<CheetahString>,
+    topic: Option<CheetahString>,
+    remote_addr: SocketAddr,
+    start: Instant,
+}
+
+/// Logs the metadata of a sample of the requests served by the broker: request code, group,
+/// topic, source address, latency and response code.
+///
+/// Sampling per request code helps to find the client abusing a request without turning on the
+/// debug log of the whole broker. Auditing and the sample rates can be changed at runtime.
+pub(crate) struct RequestAuditor {
+    enable: AtomicBool,
+    sample_rates: RwLock<SampleRates>,
+}
+
+impl RequestAuditor {
+    pub fn new(broker_config: &BrokerConfig) -> Self {
+        let sample_rates = SampleRates::parse(broker_config.request_audit_sample_rates.as_str())
+            .unwrap_or_else(|e| {
+                warn!(
+                    "invalid requestAuditSampleRates, no request is audited: {}",
+                    e
+                );
+                SampleRates::default()
+            });
+        Self {
+            enable: AtomicBool::new(broker_config.enable_request_audit),
+            sample_rates: RwLock::new(sample_rates),
+        }
+    }
+
+    #[inline]
+    pub fn is_enable(&self) -> bool {
+        self.enable.load(Ordering::Relaxed)
+    }
+
+    pub fn set_enable(&self, enable: bool) {
+        self.enable.store(enable, Ordering::Relaxed);
+    }
+
+    /// Replaces the sample rates, which are kept when `rates` does not parse.
+    pub fn update_sample_rates(&self, rates: &str) -> Result<(), String> {
+        *self.sample_rates.write() = SampleRates::parse(rates)?;
+        Ok(())
+    }
+
+    /// Returns the record to finish once `request` is answered if the request is sampled.
+    pub fn sample(
+        &self,
+        request: &RemotingCommand,
+        remote_addr: SocketAddr,
+    ) -> Option<AuditRecord> {
+        if !self.is_enable() {
+            return None;
+        }
+        let request_code = request.code();
+        let rate = self.sample_rates.read().rate(request_code);
+        if rate <= 0.0 || (rate < 1.0 && rand::random::<f64>() >= rate) {
+            return None;
+        }
+        let (group, topic) = group_and_topic(request);
+        Some(AuditRecord {
+            request_code,
+            group,
+            topic,
+            remote_addr,
+            start: Instant::now(),
+        })
+    }
+
+    /// Logs `record` with the outcome of its request.
+    pub fn finish(
+        &self,
+        record: AuditRecord,
+        result: &rocketmq_error::RocketMQResult<Option<RemotingCommand>>,
+    ) {
+        // oneway requests are not answered
+        let response_code = match result {
+            Ok(response) => response.as_ref().map_or(-1, RemotingCommand::code),
+            Err(e) => e
+                .response_code_remark()
+                .map_or(RemotingSysResponseCode::SystemError as i32, |(code, _)| {
+                    code
+                }),
+        };
+        info!(
+            target: AUDIT_TARGET,
+            request_code = record.request_code,
+            group = record.group.as_ref().map_or("", CheetahString::as_str),
+            topic = record.topic.as_ref().map_or("", CheetahString::as_str),
+            remote_addr = %record.remote_addr,
+            latency_ms = record.start.elapsed().as_millis() as u64,
+            response_code,
+            "request audit"
+        );
+    }
+}
+
+/// Reads the group and topic of `request` from its header fields.
+fn group_and_topic(request: &RemotingCommand) -> (Option<CheetahString>, Option<CheetahString>) {
+    let Some(fields) = request.get_ext_fields() else {
+        return (None, None);
+    };
+    let field = |keys: &[&str]| keys.iter().find_map(|key| fields.get(*key).cloned());
+    match RequestCode::from(request.code()) {
+        // the compact send header names its fields by letters
+        RequestCode::SendMessageV2
+        | RequestCode::SendBatchMessage
+        | RequestCode::SendReplyMessageV2 => (field(&["a"]), field(&["b"])),
+        _ => (
+            field(&["consumerGroup", "producerGroup", "group"]),
+            field(&["topic"]),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_rates_are_parsed_per_request_code() {
+        let rates = SampleRates::parse("310:1, 11:0.5,*:0.01").unwrap();
+        assert_eq!(rates.rate(310), 1.0);
+        assert_eq!(rates.rate(11), 0.5);
+        assert_eq!(rates.rate(34), 0.01);
+        assert_eq!(SampleRates::parse("").unwrap().rate(310), 0.0);
+
+        assert!(SampleRates::parse("310").is_err());
+        assert!(SampleRates::parse("310:2").is_err());
+        assert!(SampleRates::parse("send:1").is_err());
+    }
+
+    #[test]
+    fn only_requests_of_sampled_codes_are_audited() {
+        let broker_config = BrokerConfig {
+            enable_request_audit: true,
+            request_audit_sample_rates: CheetahString::from_static_str("310:1,*:0"),
+            ..BrokerConfig::default()
+        };
+        let auditor = RequestAuditor::new(&broker_config);
+        let remote_addr = "127.0.0.1:10911".parse().unwrap();
+
+        let mut fields = HashMap::new();
+        fields.insert(
+            CheetahString::from_static_str("a"),
+            CheetahString::from_static_str("producer_group"),
+        );
+        fields.insert(
+            CheetahString::from_static_str("b"),
+            CheetahString::from_static_str("TopicA"),
+        );
+        let send = RemotingCommand::create_remoting_command(RequestCode::SendMessageV2)
+            .set_ext_fields(fields);
+        let record = auditor.sample(&send, remote_addr).unwrap();
+        assert_eq!(record.group.unwrap().as_str(), "producer_group");
+        assert_eq!(record.topic.unwrap().as_str(), "TopicA");
+
+        let pull = RemotingCommand::create_remoting_command(RequestCode::PullMessage);
+        assert!(auditor.sample(&pull, remote_addr).is_none());
+
+        auditor.update_sample_rates("*:1").unwrap();
+        assert!(auditor.sample(&pull, remote_addr).is_some());
+        assert!(auditor.update_sample_rates("*:x").is_err());
+        assert!(auditor.sample(&pull, remote_addr).is_some());
+
+        auditor.set_enable(false);
+        assert!(auditor.sample(&send, remote_addr).is_none());
+    }
+}
@@ -15,6 +15,7 @@
  * limitations under the License.
  */
 
+use std::path::Path;
 use std::path::PathBuf;
 
 use clap::Parser;
@@ -23,16 +24,26 @@ use rocketmq_broker::Builder;
 use rocketmq_common::common::broker::broker_config::BrokerConfig;
 use rocketmq_common::EnvUtils::EnvUtils;
 use rocketmq_common::ParseConfigFile;
+use rocketmq_common::ParseConfigFile::PropertiesConfig;
 use rocketmq_error::RocketMQResult;
 use rocketmq_rust::rocketmq;
 use rocketmq_store::config::message_store_config::MessageStoreConfig;
 use tracing::info;
+use tracing::warn;
 
 #[rocketmq::main]
 async fn main() -> RocketMQResult<()> {
     // init logger
     rocketmq_common::log::init_logger_with_level(rocketmq_common::log::Level::INFO);
-    let (broker_config, message_store_config) = parse_config_file().unwrap_or_default();
+    let args = Args::parse();
+    let (broker_config, message_store_config) = match args.config_file {
+        Some(ref config_file) => parse_config_file(config_file)?,
+        None => parse_default_config_file().unwrap_or_default(),
+    };
+    if args.print_config_item {
+        print_config_items(&broker_config, &message_store_config);
+        return Ok(());
+    }
     // boot strap broker
     Builder::new()
         .set_broker_config(broker_config)
@@ -43,24 +54,48 @@ async fn main() -> RocketMQResult<()> {
     Ok(())
 }
 
-fn parse_config_file() -> RocketMQResult<(BrokerConfig, MessageStoreConfig)> {
-    let args = Args::parse();
+/// Java `broker.conf`/`.properties` files use the Java property keys, anything else is read as
+/// a structured (TOML, YAML, JSON) config.
+fn parse_config_file(config_file: &Path) -> RocketMQResult<(BrokerConfig, MessageStoreConfig)> {
+    let is_properties = matches!(
+        config_file
+            .extension()
+            .and_then(|extension| extension.to_str()),
+        Some("conf" | "properties")
+    );
+    if !is_properties {
+        return Ok((
+            ParseConfigFile::parse_config_file::<BrokerConfig>(config_file.to_path_buf())?,
+            ParseConfigFile::parse_config_file::<MessageStoreConfig>(config_file.to_path_buf())?,
+        ));
+    }
+    let mut properties = PropertiesConfig::load(config_file)?;
+    let broker_config = properties.apply::<BrokerConfig>()?;
+    let message_store_config = properties.apply::<MessageStoreConfig>()?;
+    for key in properties.unknown_keys() {
+        warn!(
+            "Unknown config item `{}` in {}, ignored",
+            key,
+            config_file.display()
+        );
+    }
+    Ok((broker_config, message_store_config))
+}
+
+fn parse_default_config_file() -> RocketMQResult<(BrokerConfig, MessageStoreConfig)> {
     let home = EnvUtils::get_rocketmq_home();
     info!("Rocketmq(Rust) home: {}", home);
-    let config = if let Some(ref config_file) = args.config_file {
-        let config_file = PathBuf::from(config_file);
-        Ok((
-            ParseConfigFile::parse_config_file::<BrokerConfig>(config_file.clone())?,
-            ParseConfigFile::parse_config_file::<MessageStoreConfig>(config_file)?,
-        ))
-    } else {
-        let path_buf = PathBuf::from(home.as_str())
-            .join("conf")
-            .join("broker.toml");
-        Ok((
-            ParseConfigFile::parse_config_file::<BrokerConfig>(path_buf.clone())?,
-            ParseConfigFile::parse_config_file::<MessageStoreConfig>(path_buf)?,
-        ))
-    };
-    config
+    let path_buf = PathBuf::from(home.as_str())
+        .join("conf")
+        .join("broker.toml");
+    parse_config_file(&path_buf)
+}
+
+fn print_config_items(broker_config: &BrokerConfig, message_store_config: &MessageStoreConfig) {
+    for (key, value) in ParseConfigFile::config_to_properties(broker_config)
+        .into_iter()
+        .chain(ParseConfigFile::config_to_properties(message_store_config))
+    {
+        println!("{key}={value}");
+    }
 }
@@ -0,0 +1,56 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::path::PathBuf;
+
+use clap::Parser;
+use rocketmq_broker::command::Args;
+use rocketmq_broker::BrokerContainer;
+use rocketmq_broker::BrokerContainerConfig;
+use rocketmq_common::common::server::config::ServerConfig;
+use rocketmq_common::EnvUtils::EnvUtils;
+use rocketmq_common::ParseConfigFile;
+use rocketmq_error::RocketMQResult;
+use rocketmq_rust::rocketmq;
+use tracing::info;
+
+#[rocketmq::main]
+async fn main() -> RocketMQResult<()> {
+    // init logger
+    rocketmq_common::log::init_logger_with_level(rocketmq_common::log::Level::INFO);
+    let (container_config, server_config) = parse_config_file().unwrap_or_default();
+    // boot strap broker container
+    BrokerContainer::new(container_config, server_config)
+        .boot()
+        .await;
+    Ok(())
+}
+
+fn parse_config_file() -> RocketMQResult<(BrokerContainerConfig, ServerConfig)> {
+    let args = Args::parse();
+    let home = EnvUtils::get_rocketmq_home();
+    info!("Rocketmq(Rust) home: {}", home);
+    let config_file = match args.config_file {
+        Some(config_file) => config_file,
+        None => PathBuf::from(home.as_str())
+            .join("conf")
+            .join("broker-container.toml"),
+    };
+    Ok((
+        ParseConfigFile::parse_config_file::<BrokerContainerConfig>(config_file.clone())?,
+        ParseConfigFile::parse_config_file::<ServerConfig>(config_file)?,
+    ))
+}
@@ -17,3 +17,5 @@
 
 pub mod broker_hook;
 pub mod broker_pre_online_service;
+pub(crate) mod broker_readiness;
+pub(crate) mod broker_startup_check;
@@ -14,16 +14,258 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
-use tracing::warn;
+use std::collections::HashMap;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
 
-pub struct BrokerPreOnlineService;
+use cheetah_string::CheetahString;
+use rocketmq_common::common::mix_all;
+use rocketmq_rust::ArcMut;
+use rocketmq_store::base::message_store::MessageStore;
+use tracing::error;
+use tracing::info;
+
+use crate::broker_runtime::BrokerRuntimeInner;
+
+/// Keeps an isolated broker out of service until it has caught up with the other members of its
+/// broker group, then registers it to the name server.
+pub struct BrokerPreOnlineService<MS> {
+    broker_runtime_inner: ArcMut<BrokerRuntimeInner<MS>>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl<MS: MessageStore> BrokerPreOnlineService<MS> {
+    pub fn new(broker_runtime_inner: ArcMut<BrokerRuntimeInner<MS>>) -> Self {
+        Self {
+            broker_runtime_inner,
+            stopped: Arc::new(AtomicBool::new(false)),
+        }
+    }
 
-impl BrokerPreOnlineService {
     pub fn start(&mut self) {
-        warn!("BrokerPreOnlineService started not implemented");
+        let broker_runtime_inner = self.broker_runtime_inner.clone();
+        let stopped = self.stopped.clone();
+        tokio::spawn(async move {
+            info!("BrokerPreOnlineService service started");
+            while !stopped.load(Ordering::Acquire) && !broker_runtime_inner.is_shutdown() {
+                if !broker_runtime_inner.is_isolated().load(Ordering::Acquire) {
+                    info!(
+                        "broker {} is online",
+                        get_broker_addr(&broker_runtime_inner)
+                    );
+                    break;
+                }
+                if Self::prepare_for_broker_online(&broker_runtime_inner, &stopped).await {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+            info!("BrokerPreOnlineService service end");
+        });
     }
 
     pub fn shutdown(&mut self) {
-        warn!("BrokerPreOnlineService shutdown not implemented");
+        self.stopped.store(true, Ordering::Release);
+    }
+
+    async fn prepare_for_broker_online(
+        broker_runtime_inner: &ArcMut<BrokerRuntimeInner<MS>>,
+        stopped: &AtomicBool,
+    ) -> bool {
+        let broker_config = broker_runtime_inner.broker_config();
+        let broker_member_group = match broker_runtime_inner
+            .broker_outer_api()
+            .sync_broker_member_group(
+                &broker_config.broker_identity.broker_cluster_name,
+                &broker_config.broker_identity.broker_name,
+                broker_config.compatible_with_old_name_srv,
+            )
+            .await
+        {
+            Ok(broker_member_group) => broker_member_group,
+            Err(e) => {
+                error!(
+                    "syncBrokerMemberGroup from namesrv error, start service failed: {}",
+                    e
+                );
+                return false;
+            }
+        };
+
+        let broker_id = broker_config.broker_identity.broker_id;
+        let broker_addrs = match broker_member_group {
+            Some(group) if !group.broker_addrs.is_empty() => group.broker_addrs,
+            _ => {
+                info!("no other broker online, will start service directly");
+                BrokerRuntimeInner::start_service(
+                    broker_runtime_inner.clone(),
+                    broker_id,
+                    get_broker_addr(broker_runtime_inner),
+                )
+                .await;
+                return true;
+            }
+        };
+
+        let min_broker_id = get_min_broker_id(&broker_addrs, broker_id);
+        if broker_id == mix_all::MASTER_ID {
+            Self::prepare_for_master_online(broker_runtime_inner, &broker_addrs, stopped).await
+        } else if min_broker_id == mix_all::MASTER_ID {
+            Self::prepare_for_slave_online(broker_runtime_inner, &broker_addrs).await
+        } else {
+            info!("no master online, start service directly");
+            let min_broker_addr = broker_addrs
+                .get(&min_broker_id)
+                .cloned()
+                .unwrap_or_else(|| get_broker_addr(broker_runtime_inner));
+            BrokerRuntimeInner::start_service(
+                broker_runtime_inner.clone(),
+                min_broker_id,
+                min_broker_addr,
+            )
+            .await;
+            true
+        }
+    }
+
+    /// A restarted master takes back the offsets that the acting master committed while it was
+    /// away before serving again.
+    async fn prepare_for_master_online(
+        broker_runtime_inner: &ArcMut<BrokerRuntimeInner<MS>>,
+        broker_addrs: &HashMap<u64, CheetahString>,
+        stopped: &AtomicBool,
+    ) -> bool {
+        let mut broker_ids = broker_addrs
+            .keys()
+            .copied()
+            .filter(|broker_id| *broker_id != mix_all::MASTER_ID)
+            .collect::<Vec<_>>();
+        broker_ids.sort_unstable();
+        if let Some(min_slave_id) = broker_ids.first() {
+            if stopped.load(Ordering::Acquire) {
+                return false;
+            }
+            let slave_addr = &broker_addrs[min_slave_id];
+            if !Self::sync_metadata(broker_runtime_inner, slave_addr).await {
+                return false;
+            }
+        }
+        info!("master preOnline complete, start service");
+        BrokerRuntimeInner::start_service(
+            broker_runtime_inner.clone(),
+            mix_all::MASTER_ID,
+            get_broker_addr(broker_runtime_inner),
+        )
+        .await;
+        true
+    }
+
+    /// A slave takes the offsets of its master before serving, so consumers reading from it do
+    /// not start over from stale offsets.
+    async fn prepare_for_slave_online(
+        broker_runtime_inner: &ArcMut<BrokerRuntimeInner<MS>>,
+        broker_addrs: &HashMap<u64, CheetahString>,
+    ) -> bool {
+        let master_addr = &broker_addrs[&mix_all::MASTER_ID];
+        if !Self::sync_metadata(broker_runtime_inner, master_addr).await {
+            return false;
+        }
+        info!("slave preOnline complete, start service");
+        BrokerRuntimeInner::start_service(
+            broker_runtime_inner.clone(),
+            mix_all::MASTER_ID,
+            master_addr.clone(),
+        )
+        .await;
+        true
+    }
+
+    /// Applies the consumer and delay offsets of the broker at `broker_addr` whose data version is
+    /// not older than the local one. Returns `false` when they cannot be fetched.
+    async fn sync_metadata(
+        broker_runtime_inner: &ArcMut<BrokerRuntimeInner<MS>>,
+        broker_addr: &CheetahString,
+    ) -> bool {
+        info!("Get metadata from {}", broker_addr);
+        let broker_outer_api = broker_runtime_inner.broker_outer_api();
+        let delay_offset = match broker_outer_api.get_all_delay_offset(broker_addr).await {
+            Ok(delay_offset) => delay_offset,
+            Err(e) => {
+                error!("Get metadata from {} error: {}", broker_addr, e);
+                return false;
+            }
+        };
+        let consumer_offset = match broker_outer_api.get_all_consumer_offset(broker_addr).await {
+            Ok(consumer_offset) => consumer_offset,
+            Err(e) => {
+                error!("Get metadata from {} error: {}", broker_addr, e);
+                return false;
+            }
+        };
+
+        if !consumer_offset.is_empty()
+            && broker_runtime_inner
+                .consumer_offset_manager()
+                .sync_consumer_offset(consumer_offset.as_str())
+        {
+            info!(
+                "{}'s consumerOffset data version is not older than the local one, {}'s \
+                 consumerOffset will be used.",
+                broker_addr, broker_addr
+            );
+        }
+
+        if !delay_offset.is_empty()
+            && broker_runtime_inner
+                .schedule_message_service()
+                .sync_delay_offset(delay_offset.as_str())
+        {
+            info!(
+                "{}'s scheduleMessageService data version is not older than the local one, {}'s \
+                 delayOffset will be used.",
+                broker_addr, broker_addr
+            );
+        }
+        true
+    }
+}
+
+fn get_broker_addr<MS: MessageStore>(
+    broker_runtime_inner: &ArcMut<BrokerRuntimeInner<MS>>,
+) -> CheetahString {
+    broker_runtime_inner.get_broker_addr().clone()
+}
+
+/// Returns the smallest broker id in the group other than `self_broker_id`, or `self_broker_id`
+/// itself when it is alone.
+fn get_min_broker_id(broker_addrs: &HashMap<u64, CheetahString>, self_broker_id: u64) -> u64 {
+    broker_addrs
+        .keys()
+        .copied()
+        .filter(|broker_id| *broker_id != self_broker_id)
+        .min()
+        .unwrap_or(self_broker_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn min_broker_id_excludes_self() {
+        let mut broker_addrs = HashMap::new();
+        broker_addrs.insert(0, CheetahString::from_static_str("127.0.0.1:10911"));
+        broker_addrs.insert(2, CheetahString::from_static_str("127.0.0.1:10921"));
+        assert_eq!(get_min_broker_id(&broker_addrs, 0), 2);
+        assert_eq!(get_min_broker_id(&broker_addrs, 2), 0);
+    }
+
+    #[test]
+    fn min_broker_id_alone() {
+        let mut broker_addrs = HashMap::new();
+        broker_addrs.insert(1, CheetahString::from_static_str("127.0.0.1:10911"));
+        assert_eq!(get_min_broker_id(&broker_addrs, 1), 1);
     }
 }
@@ -0,0 +1,76 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+
+/// Startup steps a broker goes through before it is ready to serve.
+pub(crate) const STORE_RECOVERY: &str = "store recovery";
+pub(crate) const NAME_SERVER_REGISTRATION: &str = "name server registration";
+pub(crate) const PRE_ONLINE_SYNC: &str = "pre-online sync";
+
+/// Tracks the startup of a broker for readiness probes. The broker is ready once its store is
+/// recovered, it registered to the name servers and, when it started isolated, it caught up with
+/// its broker group.
+#[derive(Default)]
+pub(crate) struct BrokerReadiness {
+    recovered: AtomicBool,
+    registered: AtomicBool,
+}
+
+impl BrokerReadiness {
+    pub fn mark_recovered(&self) {
+        self.recovered.store(true, Ordering::Release);
+    }
+
+    pub fn mark_registered(&self) {
+        self.registered.store(true, Ordering::Release);
+    }
+
+    /// Returns the startup steps not done yet, the broker is ready when there is none.
+    /// `is_isolated` tells whether the broker still waits for the pre-online sync.
+    pub fn pending_steps(&self, is_isolated: bool) -> Vec<&'static str> {
+        let mut pending_steps = Vec::new();
+        if !self.recovered.load(Ordering::Acquire) {
+            pending_steps.push(STORE_RECOVERY);
+        }
+        if !self.registered.load(Ordering::Acquire) {
+            pending_steps.push(NAME_SERVER_REGISTRATION);
+        }
+        if is_isolated {
+            pending_steps.push(PRE_ONLINE_SYNC);
+        }
+        pending_steps
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn broker_is_ready_once_every_step_is_done() {
+        let readiness = BrokerReadiness::default();
+        assert_eq!(
+            readiness.pending_steps(true),
+            vec![STORE_RECOVERY, NAME_SERVER_REGISTRATION, PRE_ONLINE_SYNC]
+        );
+        readiness.mark_recovered();
+        readiness.mark_registered();
+        assert_eq!(readiness.pending_steps(true), vec![PRE_ONLINE_SYNC]);
+        assert!(readiness.pending_steps(false).is_empty());
+    }
+}
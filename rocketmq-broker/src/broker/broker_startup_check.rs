@@ -0,0 +1,273 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::net::TcpListener;
+use std::path::Path;
+
+use rocketmq_common::common::broker::broker_config::BrokerConfig;
+use rocketmq_common::common::broker::broker_role::BrokerRole;
+use rocketmq_common::common::mix_all;
+use rocketmq_common::common::server::config::ServerConfig;
+use rocketmq_common::utils::network_util::NetworkUtil;
+use rocketmq_store::config::message_store_config::MessageStoreConfig;
+
+const PROBE_FILE_NAME: &str = ".startup_check";
+
+/// Checks the environment and the configs of a broker before anything is loaded, so that a
+/// broker that cannot work refuses to start with the reasons instead of failing later on.
+///
+/// Returns the problems found, the broker can start when there is none.
+pub(crate) fn check_startup(
+    broker_config: &BrokerConfig,
+    message_store_config: &MessageStoreConfig,
+    server_config: &ServerConfig,
+) -> Vec<String> {
+    check_startup_binding_with(
+        broker_config,
+        message_store_config,
+        server_config,
+        |bind_address, port| TcpListener::bind((bind_address, port)).map(drop),
+    )
+}
+
+/// [`check_startup`] with `bind` probing whether a port can be bound.
+fn check_startup_binding_with(
+    broker_config: &BrokerConfig,
+    message_store_config: &MessageStoreConfig,
+    server_config: &ServerConfig,
+    bind: impl Fn(&str, u16) -> std::io::Result<()>,
+) -> Vec<String> {
+    let mut problems = Vec::new();
+    check_addresses(broker_config, &mut problems);
+    check_role(broker_config, message_store_config, &mut problems);
+    check_ports(broker_config, server_config, bind, &mut problems);
+    for store_path in [
+        broker_config.store_path_root_dir.as_str(),
+        message_store_config.store_path_root_dir.as_str(),
+    ] {
+        if let Err(e) = check_writable(Path::new(store_path)) {
+            problems.push(format!("store path {store_path} is not writable: {e}"));
+        }
+    }
+    problems.dedup();
+    problems
+}
+
+fn check_addresses(broker_config: &BrokerConfig, problems: &mut Vec<String>) {
+    let broker_ips = std::iter::once(("brokerIP1", &broker_config.broker_ip1)).chain(
+        broker_config
+            .broker_ip2
+            .iter()
+            .filter(|ip| !ip.is_empty())
+            .map(|ip| ("brokerIP2", ip)),
+    );
+    for (name, ip) in broker_ips {
+        if let Err(e) = NetworkUtil::resolve_host_port(ip.as_str(), broker_config.listen_port) {
+            problems.push(format!("{name} {ip} is not resolvable: {e}"));
+        }
+    }
+    let namesrv_addrs = broker_config
+        .namesrv_addr
+        .as_ref()
+        .map_or("", |addr| addr.as_str());
+    for namesrv_addr in namesrv_addrs.split(';').map(str::trim) {
+        if namesrv_addr.is_empty() {
+            continue;
+        }
+        if let Err(e) = NetworkUtil::resolve_socket_addr(namesrv_addr) {
+            problems.push(format!(
+                "name server address {namesrv_addr} is illegal: {e}"
+            ));
+        }
+    }
+}
+
+fn check_role(
+    broker_config: &BrokerConfig,
+    message_store_config: &MessageStoreConfig,
+    problems: &mut Vec<String>,
+) {
+    // the members of a dledger group elect their master, ids are not bound to roles
+    if !message_store_config.enable_dledger_commit_log
+        && message_store_config.broker_role == BrokerRole::Slave
+        && broker_config.broker_identity.broker_id == mix_all::MASTER_ID
+    {
+        problems.push("the brokerId of a slave must be > 0".to_string());
+    }
+}
+
+fn check_ports(
+    broker_config: &BrokerConfig,
+    server_config: &ServerConfig,
+    bind: impl Fn(&str, u16) -> std::io::Result<()>,
+    problems: &mut Vec<String>,
+) {
+    let bind_address = server_config.bind_address.as_str();
+    let listen_port = server_config.listen_port;
+    let mut ports = vec![("listenPort", bind_address, listen_port)];
+    // the fast remoting server listens two ports below the main one
+    match listen_port.checked_sub(2) {
+        Some(fast_port) if fast_port > 0 => {
+            ports.push(("fast remoting port", bind_address, fast_port))
+        }
+        _ => problems.push(format!(
+            "listenPort {listen_port} leaves no port for the fast remoting server"
+        )),
+    }
+    if broker_config.enable_http_admin {
+        ports.push((
+            "httpAdminListenPort",
+            broker_config.http_admin_bind_address.as_str(),
+            broker_config.http_admin_listen_port,
+        ));
+    }
+    for (name, bind_address, port) in ports {
+        let Ok(port) = u16::try_from(port) else {
+            problems.push(format!("{name} {port} is not a valid port"));
+            continue;
+        };
+        if let Err(e) = bind(bind_address, port) {
+            problems.push(format!(
+                "{name} {port} can not be bound on {bind_address}: {e}"
+            ));
+        }
+    }
+}
+
+fn check_writable(dir: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let probe = dir.join(PROBE_FILE_NAME);
+    let result = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .open(&probe)
+        .and_then(|mut file| file.write_all(b"ok"));
+    let _ = fs::remove_file(&probe);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use cheetah_string::CheetahString;
+
+    use super::*;
+
+    fn configs(store_dir: &Path) -> (BrokerConfig, MessageStoreConfig, ServerConfig) {
+        let store_dir = CheetahString::from_string(store_dir.to_string_lossy().into_owned());
+        let broker_config = BrokerConfig {
+            broker_ip1: CheetahString::from_static_str("127.0.0.1"),
+            broker_ip2: None,
+            namesrv_addr: Some(CheetahString::from_static_str("127.0.0.1:9876")),
+            store_path_root_dir: store_dir.clone(),
+            ..BrokerConfig::default()
+        };
+        let message_store_config = MessageStoreConfig {
+            store_path_root_dir: store_dir,
+            ..MessageStoreConfig::default()
+        };
+        let server_config = ServerConfig {
+            listen_port: 0,
+            bind_address: "127.0.0.1".to_string(),
+            ..ServerConfig::default()
+        };
+        (broker_config, message_store_config, server_config)
+    }
+
+    #[test]
+    fn a_sound_broker_passes_the_check() {
+        let store_dir = tempfile::tempdir().unwrap();
+        let (mut broker_config, message_store_config, mut server_config) =
+            configs(store_dir.path());
+        server_config.listen_port = 10911;
+        // whether the ports are free depends on the machine, every port can be bound here
+        let check = |broker_config: &BrokerConfig| {
+            check_startup_binding_with(
+                broker_config,
+                &message_store_config,
+                &server_config,
+                |_, _| Ok(()),
+            )
+        };
+        let problems = check(&broker_config);
+        assert!(problems.is_empty(), "{problems:?}");
+
+        broker_config.broker_ip1 = CheetahString::from_static_str("localhost");
+        let problems = check(&broker_config);
+        assert!(problems.is_empty(), "{problems:?}");
+        assert!(!store_dir.path().join(PROBE_FILE_NAME).exists());
+    }
+
+    #[test]
+    fn http_admin_is_checked_on_its_bind_address() {
+        let store_dir = tempfile::tempdir().unwrap();
+        let (mut broker_config, message_store_config, mut server_config) =
+            configs(store_dir.path());
+        server_config.listen_port = 10911;
+        broker_config.enable_http_admin = true;
+        let bound = std::cell::RefCell::new(Vec::new());
+        let problems = check_startup_binding_with(
+            &broker_config,
+            &message_store_config,
+            &server_config,
+            |bind_address, port| {
+                bound.borrow_mut().push((bind_address.to_string(), port));
+                Ok(())
+            },
+        );
+        assert!(problems.is_empty(), "{problems:?}");
+        assert!(bound.borrow().contains(&("127.0.0.1".to_string(), 10919)));
+    }
+
+    #[test]
+    fn every_problem_is_reported() {
+        let store_dir = tempfile::tempdir().unwrap();
+        let (mut broker_config, mut message_store_config, mut server_config) =
+            configs(store_dir.path());
+        broker_config.broker_ip1 = CheetahString::from_static_str("no such host:1");
+        broker_config.namesrv_addr =
+            Some(CheetahString::from_static_str("127.0.0.1:9876;127.0.0.1"));
+        message_store_config.broker_role = BrokerRole::Slave;
+        let busy = TcpListener::bind("127.0.0.1:0").unwrap();
+        server_config.listen_port = busy.local_addr().unwrap().port() as u32;
+
+        let problems = check_startup(&broker_config, &message_store_config, &server_config);
+        assert!(
+            problems.iter().any(|p| p.starts_with("brokerIP1")),
+            "{problems:?}"
+        );
+        assert!(
+            problems
+                .iter()
+                .any(|p| p.contains("name server address 127.0.0.1 ")),
+            "{problems:?}"
+        );
+        assert!(problems.iter().any(|p| p.contains("slave")), "{problems:?}");
+        assert!(
+            problems.iter().any(|p| p.starts_with("listenPort")),
+            "{problems:?}"
+        );
+
+        server_config.listen_port = 70000;
+        let problems = check_startup(&broker_config, &message_store_config, &server_config);
+        assert!(problems
+            .iter()
+            .any(|p| p.contains("70000 is not a valid port")));
+    }
+}
@@ -14,12 +14,16 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use std::future::Future;
 use std::sync::Arc;
 
 use rocketmq_common::common::broker::broker_config::BrokerConfig;
 use rocketmq_common::common::server::config::ServerConfig;
+use rocketmq_remoting::auth::authentication_provider::AuthenticationProvider;
 use rocketmq_rust::wait_for_signal;
 use rocketmq_store::config::message_store_config::MessageStoreConfig;
+use rocketmq_store::hook::put_message_hook::BoxedPutMessageHook;
+use rocketmq_store::hook::put_message_hook::PutMessageHook;
 use tracing::error;
 use tracing::info;
 
@@ -30,7 +34,16 @@ pub struct BrokerBootstrap {
 }
 
 impl BrokerBootstrap {
-    pub async fn boot(mut self) {
+    pub async fn boot(self) {
+        self.boot_until(async {
+            wait_for_signal().await;
+            info!("Broker Received signal, initiating shutdown...");
+        })
+        .await;
+    }
+
+    /// Runs the broker until `shutdown` completes instead of waiting for a process signal.
+    pub async fn boot_until(mut self, shutdown: impl Future<Output = ()>) {
         if !self.initialize().await {
             error!("initialize fail");
             return;
@@ -38,7 +51,11 @@ impl BrokerBootstrap {
         let (shutdown_tx, shutdown_rx) = tokio::sync::broadcast::channel(1);
         self.broker_runtime.shutdown_rx = Some(shutdown_rx);
 
-        tokio::join!(self.start(), wait_for_signal_inner(shutdown_tx));
+        tokio::join!(self.start(), async move {
+            shutdown.await;
+            // Send shutdown signal to all tasks
+            let _ = shutdown_tx.send(());
+        });
     }
 
     async fn initialize(&mut self) -> bool {
@@ -50,20 +67,12 @@ impl BrokerBootstrap {
     }
 }
 
-async fn wait_for_signal_inner(shutdown_tx: tokio::sync::broadcast::Sender<()>) {
-    tokio::select! {
-        _ = wait_for_signal() => {
-            info!("Broker Received signal, initiating shutdown...");
-        }
-    }
-    // Send shutdown signal to all tasks
-    let _ = shutdown_tx.send(());
-}
-
 pub struct Builder {
     broker_config: BrokerConfig,
     message_store_config: MessageStoreConfig,
     server_config: ServerConfig,
+    put_message_hooks: Vec<BoxedPutMessageHook>,
+    authentication_provider: Option<Arc<dyn AuthenticationProvider>>,
 }
 
 impl Builder {
@@ -72,6 +81,8 @@ impl Builder {
             broker_config: Default::default(),
             message_store_config: MessageStoreConfig::default(),
             server_config: Default::default(),
+            put_message_hooks: Vec::new(),
+            authentication_provider: None,
         }
     }
 
@@ -90,14 +101,42 @@ impl Builder {
         self
     }
 
+    /// Adds a hook run on every message before it is stored, returning a result from it rejects
+    /// the message. Hooks run in the order they are added, after the built-in checks of the
+    /// store status, the message size and the delay level.
+    pub fn add_put_message_hook(
+        mut self,
+        put_message_hook: impl PutMessageHook + Send + Sync + 'static,
+    ) -> Self {
+        self.put_message_hooks.push(Box::new(put_message_hook));
+        self
+    }
+
+    /// Authenticates every request the broker receives with `authentication_provider`, requests
+    /// it rejects are answered with `NO_PERMISSION`. The requests the broker sends itself carry
+    /// the inner credentials of its config, the ones of the other brokers of the group have to be
+    /// accepted by the provider as well.
+    pub fn set_authentication_provider(
+        mut self,
+        authentication_provider: impl AuthenticationProvider,
+    ) -> Self {
+        self.authentication_provider = Some(Arc::new(authentication_provider));
+        self
+    }
+
     pub fn build(self) -> BrokerBootstrap {
-        BrokerBootstrap {
-            broker_runtime: BrokerRuntime::new(
-                Arc::new(self.broker_config),
-                Arc::new(self.message_store_config),
-                Arc::new(self.server_config),
-            ),
+        let mut broker_runtime = BrokerRuntime::new(
+            Arc::new(self.broker_config),
+            Arc::new(self.message_store_config),
+            Arc::new(self.server_config),
+        );
+        for put_message_hook in self.put_message_hooks {
+            broker_runtime.add_put_message_hook(put_message_hook);
+        }
+        if let Some(authentication_provider) = self.authentication_provider {
+            broker_runtime.set_authentication_provider(authentication_provider);
         }
+        BrokerBootstrap { broker_runtime }
     }
 }
 
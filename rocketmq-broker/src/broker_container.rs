@@ -0,0 +1,368 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+pub mod broker_container_config;
+pub(crate) mod broker_container_processor;
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use cheetah_string::CheetahString;
+use rocketmq_common::common::broker::broker_config::BrokerConfig;
+use rocketmq_common::common::broker::broker_role::BrokerRole;
+use rocketmq_common::common::mix_all;
+use rocketmq_common::common::server::config::ServerConfig;
+use rocketmq_common::ParseConfigFile;
+use rocketmq_common::ParseConfigFile::PropertiesConfig;
+use rocketmq_error::RocketMQResult;
+use rocketmq_error::RocketmqError;
+use rocketmq_remoting::remoting_server::server::RocketMQServer;
+use rocketmq_rust::wait_for_signal;
+use rocketmq_store::config::message_store_config::MessageStoreConfig;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tracing::error;
+use tracing::info;
+use tracing::warn;
+
+use crate::broker_container::broker_container_config::BrokerContainerConfig;
+use crate::broker_container::broker_container_processor::BrokerContainerProcessor;
+use crate::broker_runtime::BrokerRuntime;
+use crate::broker_runtime_group::BrokerRuntimeGroup;
+
+/// Cluster name, broker name and broker id of a broker hosted by the container.
+type BrokerKey = (CheetahString, CheetahString, u64);
+
+struct ContainedBroker {
+    listen_port: u32,
+    shutdown_tx: broadcast::Sender<()>,
+    handle: JoinHandle<()>,
+}
+
+/// Hosts several brokers, masters of some broker groups and slaves of others, in one process.
+///
+/// The brokers are managed through the container admin server, which accepts `ADD_BROKER` and
+/// `REMOVE_BROKER` requests. Like the sub servers of the Java container, the remoting servers of
+/// the brokers listen on their own ports but share the network and processor runtimes of the
+/// container, and the brokers share its scheduled and store runtimes; the runtime settings in
+/// the config of a hosted broker are ignored.
+#[derive(Clone)]
+pub struct BrokerContainer {
+    container_config: Arc<BrokerContainerConfig>,
+    server_config: Arc<ServerConfig>,
+    runtime_group: Arc<BrokerRuntimeGroup>,
+    brokers: Arc<tokio::sync::Mutex<HashMap<BrokerKey, ContainedBroker>>>,
+}
+
+impl BrokerContainer {
+    pub fn new(container_config: BrokerContainerConfig, server_config: ServerConfig) -> Self {
+        let runtime_group = BrokerRuntimeGroup::with_threads(
+            container_config.network_runtime_threads,
+            container_config.processor_runtime_threads,
+            container_config.store_runtime_threads,
+            container_config.flush_on_dedicated_thread,
+        );
+        BrokerContainer {
+            container_config: Arc::new(container_config),
+            server_config: Arc::new(server_config),
+            runtime_group: Arc::new(runtime_group),
+            brokers: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn container_config(&self) -> &BrokerContainerConfig {
+        &self.container_config
+    }
+
+    pub async fn boot(self) {
+        let mut server_config = self.server_config.as_ref().clone();
+        server_config.listen_port = self.container_config.listen_port;
+        let mut server = RocketMQServer::new(Arc::new(server_config));
+        if let Some(runtime) = self.runtime_group.processor_handle() {
+            server = server.with_connection_runtime(runtime);
+        }
+        let processor = BrokerContainerProcessor::new(self.clone());
+        self.runtime_group
+            .spawn_network(async move { server.run(processor, None).await });
+
+        for config_path in self.container_config.broker_config_paths() {
+            if let Err(e) = self.add_broker_from_file(config_path).await {
+                error!("add broker from {} failed: {}", config_path, e);
+            }
+        }
+        info!(
+            "Rocketmq BrokerContainer({}:{} ----Rust) start success",
+            self.container_config.broker_container_ip, self.container_config.listen_port
+        );
+
+        wait_for_signal().await;
+        info!("BrokerContainer received signal, shutting down brokers...");
+        self.shutdown().await;
+        info!("BrokerContainer shutdown complete");
+    }
+
+    async fn add_broker_from_file(&self, config_path: &str) -> RocketMQResult<()> {
+        let (broker_config, message_store_config) = parse_broker_config_file(config_path)?;
+        self.add_broker(broker_config, message_store_config).await
+    }
+
+    /// Initializes and starts a broker inside the container.
+    pub async fn add_broker(
+        &self,
+        broker_config: BrokerConfig,
+        message_store_config: MessageStoreConfig,
+    ) -> RocketMQResult<()> {
+        let mut brokers = self.brokers.lock().await;
+        let (broker_config, message_store_config) = prepare_broker_config(
+            &self.container_config,
+            broker_config,
+            message_store_config,
+            brokers
+                .values()
+                .map(|broker| broker.listen_port)
+                .collect::<Vec<_>>()
+                .as_slice(),
+        )?;
+        let key = broker_key(&broker_config);
+        if brokers.contains_key(&key) {
+            return Err(RocketmqError::IllegalArgument(format!(
+                "broker {} already exists in the container",
+                broker_config.broker_identity.get_canonical_name()
+            )));
+        }
+
+        let mut server_config = self.server_config.as_ref().clone();
+        server_config.listen_port = broker_config.listen_port;
+        let listen_port = broker_config.listen_port;
+        let canonical_name = broker_config.broker_identity.get_canonical_name();
+        let mut broker_runtime = BrokerRuntime::with_runtime_group(
+            Arc::new(broker_config),
+            Arc::new(message_store_config),
+            Arc::new(server_config),
+            self.runtime_group.share(),
+        );
+        if !broker_runtime.initialize().await {
+            broker_runtime.shutdown().await;
+            return Err(RocketmqError::ConfigError(format!(
+                "initialize broker {canonical_name} failed"
+            )));
+        }
+
+        let (shutdown_tx, shutdown_rx) = broadcast::channel(1);
+        broker_runtime.shutdown_rx = Some(shutdown_rx);
+        let handle = tokio::spawn(async move { broker_runtime.start().await });
+        info!("add broker {} to the container", canonical_name);
+        brokers.insert(
+            key,
+            ContainedBroker {
+                listen_port,
+                shutdown_tx,
+                handle,
+            },
+        );
+        Ok(())
+    }
+
+    /// Shuts down a broker hosted by the container and removes it.
+    pub async fn remove_broker(
+        &self,
+        broker_cluster_name: &CheetahString,
+        broker_name: &CheetahString,
+        broker_id: u64,
+    ) -> RocketMQResult<()> {
+        let key = (broker_cluster_name.clone(), broker_name.clone(), broker_id);
+        let broker = self.brokers.lock().await.remove(&key).ok_or_else(|| {
+            RocketmqError::IllegalArgument(format!(
+                "broker {broker_cluster_name}_{broker_name}_{broker_id} does not exist in the \
+                 container"
+            ))
+        })?;
+        shutdown_broker(broker).await;
+        info!(
+            "remove broker {}_{}_{} from the container",
+            broker_cluster_name, broker_name, broker_id
+        );
+        Ok(())
+    }
+
+    pub async fn shutdown(&self) {
+        let brokers = std::mem::take(&mut *self.brokers.lock().await);
+        for (_, broker) in brokers {
+            shutdown_broker(broker).await;
+        }
+    }
+}
+
+async fn shutdown_broker(broker: ContainedBroker) {
+    let _ = broker.shutdown_tx.send(());
+    if let Err(e) = broker.handle.await {
+        warn!("broker task in the container exited abnormally: {}", e);
+    }
+}
+
+/// Reads the config of a broker to host. Java `broker.conf`/`.properties` files use the Java
+/// property keys, anything else is read as a structured (TOML, YAML, JSON) config.
+pub(crate) fn parse_broker_config_file(
+    config_path: &str,
+) -> RocketMQResult<(BrokerConfig, MessageStoreConfig)> {
+    let config_file = Path::new(config_path);
+    let is_properties = matches!(
+        config_file
+            .extension()
+            .and_then(|extension| extension.to_str()),
+        Some("conf" | "properties")
+    );
+    if is_properties {
+        return apply_broker_properties(PropertiesConfig::load(config_file)?, config_path);
+    }
+    let config_file = PathBuf::from(config_path);
+    Ok((
+        ParseConfigFile::parse_config_file::<BrokerConfig>(config_file.clone())?,
+        ParseConfigFile::parse_config_file::<MessageStoreConfig>(config_file)?,
+    ))
+}
+
+/// Parses the `key=value` broker config that Java's mqadmin sends in the body of `ADD_BROKER`.
+pub(crate) fn parse_broker_config_properties(
+    content: &str,
+) -> RocketMQResult<(BrokerConfig, MessageStoreConfig)> {
+    apply_broker_properties(PropertiesConfig::parse(content)?, "the addBroker request")
+}
+
+fn apply_broker_properties(
+    mut properties: PropertiesConfig,
+    source: &str,
+) -> RocketMQResult<(BrokerConfig, MessageStoreConfig)> {
+    let broker_config = properties.apply::<BrokerConfig>()?;
+    let message_store_config = properties.apply::<MessageStoreConfig>()?;
+    for key in properties.unknown_keys() {
+        warn!("Unknown config item `{}` in {}, ignored", key, source);
+    }
+    Ok((broker_config, message_store_config))
+}
+
+fn broker_key(broker_config: &BrokerConfig) -> BrokerKey {
+    (
+        broker_config.broker_identity.broker_cluster_name.clone(),
+        broker_config.broker_identity.broker_name.clone(),
+        broker_config.broker_identity.broker_id,
+    )
+}
+
+/// Adapts the config of a broker to run inside the container and checks that it does not
+/// collide with the ports already in use.
+fn prepare_broker_config(
+    container_config: &BrokerContainerConfig,
+    mut broker_config: BrokerConfig,
+    mut message_store_config: MessageStoreConfig,
+    used_ports: &[u32],
+) -> RocketMQResult<(BrokerConfig, MessageStoreConfig)> {
+    let listen_port = broker_config.listen_port;
+    if listen_port == container_config.listen_port || used_ports.contains(&listen_port) {
+        return Err(RocketmqError::IllegalArgument(format!(
+            "listen port {listen_port} of broker {} is already in use in the container",
+            broker_config.broker_identity.get_canonical_name()
+        )));
+    }
+    broker_config.broker_identity.is_in_broker_container = true;
+    broker_config.is_in_broker_container = true;
+    if broker_config.namesrv_addr.is_none() {
+        broker_config.namesrv_addr = container_config.namesrv_addr.clone();
+    }
+    if !message_store_config.enable_dledger_commit_log {
+        if broker_config.broker_identity.broker_id == mix_all::MASTER_ID {
+            if message_store_config.broker_role == BrokerRole::Slave {
+                message_store_config.broker_role = BrokerRole::AsyncMaster;
+            }
+        } else {
+            message_store_config.broker_role = BrokerRole::Slave;
+        }
+    }
+    message_store_config.ha_listen_port = listen_port as usize + 1;
+    Ok((broker_config, message_store_config))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prepare_broker_config_marks_broker_in_container() {
+        let container_config = BrokerContainerConfig::default();
+        let mut broker_config = BrokerConfig::default();
+        broker_config.broker_identity.broker_id = 1;
+        broker_config.namesrv_addr = None;
+        let (broker_config, message_store_config) = prepare_broker_config(
+            &container_config,
+            broker_config,
+            MessageStoreConfig::default(),
+            &[],
+        )
+        .unwrap();
+        assert!(broker_config.broker_identity.is_in_broker_container);
+        assert!(broker_config.is_in_broker_container);
+        assert_eq!(broker_config.namesrv_addr, container_config.namesrv_addr);
+        assert_eq!(message_store_config.broker_role, BrokerRole::Slave);
+        assert_eq!(
+            message_store_config.ha_listen_port,
+            broker_config.listen_port as usize + 1
+        );
+    }
+
+    #[test]
+    fn parse_broker_config_from_properties() {
+        let content = concat!(
+            "brokerClusterName=DefaultCluster\nbrokerName=broker-b\nbrokerId=1\n",
+            "listenPort=10921\nbrokerRole=SLAVE\n",
+        );
+        let (broker_config, message_store_config) =
+            parse_broker_config_properties(content).unwrap();
+        assert_eq!(broker_config.listen_port, 10921);
+        assert_eq!(broker_config.broker_identity.broker_name, "broker-b");
+        assert_eq!(broker_config.broker_identity.broker_id, 1);
+        assert_eq!(message_store_config.broker_role, BrokerRole::Slave);
+
+        assert!(parse_broker_config_properties("[brokerIdentity]\nbrokerId = 1\n").is_err());
+    }
+
+    #[test]
+    fn prepare_broker_config_rejects_used_port() {
+        let container_config = BrokerContainerConfig::default();
+        let broker_config = BrokerConfig::default();
+        let used_port = broker_config.listen_port;
+        assert!(prepare_broker_config(
+            &container_config,
+            broker_config,
+            MessageStoreConfig::default(),
+            &[used_port],
+        )
+        .is_err());
+
+        let broker_config = BrokerConfig {
+            listen_port: container_config.listen_port,
+            ..Default::default()
+        };
+        assert!(prepare_broker_config(
+            &container_config,
+            broker_config,
+            MessageStoreConfig::default(),
+            &[],
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,82 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use cheetah_string::CheetahString;
+use rocketmq_common::common::broker::broker_config::NAMESRV_ADDR;
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BrokerContainerConfig {
+    pub namesrv_addr: Option<CheetahString>,
+    pub broker_container_ip: CheetahString,
+    /// Port of the container admin server that serves `ADD_BROKER`/`REMOVE_BROKER`.
+    pub listen_port: u32,
+    /// Config files of the brokers to start with the container, separated by `:`.
+    pub broker_config_paths: Option<String>,
+    // Runtimes shared by the hosted brokers, with the meaning of the same broker config items.
+    pub network_runtime_threads: usize,
+    pub processor_runtime_threads: usize,
+    pub store_runtime_threads: usize,
+    pub flush_on_dedicated_thread: bool,
+}
+
+impl Default for BrokerContainerConfig {
+    fn default() -> Self {
+        BrokerContainerConfig {
+            namesrv_addr: NAMESRV_ADDR.clone().map(|addr| addr.into()),
+            broker_container_ip: CheetahString::from_static_str("0.0.0.0"),
+            listen_port: 10811,
+            broker_config_paths: None,
+            network_runtime_threads: 0,
+            processor_runtime_threads: 0,
+            store_runtime_threads: 0,
+            flush_on_dedicated_thread: false,
+        }
+    }
+}
+
+impl BrokerContainerConfig {
+    pub fn broker_config_paths(&self) -> Vec<&str> {
+        self.broker_config_paths
+            .as_deref()
+            .map(|paths| {
+                paths
+                    .split(':')
+                    .map(str::trim)
+                    .filter(|path| !path.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn broker_config_paths_split() {
+        let mut config = BrokerContainerConfig::default();
+        assert!(config.broker_config_paths().is_empty());
+        config.broker_config_paths = Some("/conf/broker-a.toml: /conf/broker-b-s.toml:".into());
+        assert_eq!(
+            config.broker_config_paths(),
+            vec!["/conf/broker-a.toml", "/conf/broker-b-s.toml"]
+        );
+    }
+}
@@ -0,0 +1,153 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use rocketmq_error::RocketMQResult;
+use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::code::response_code::ResponseCode;
+use rocketmq_remoting::net::channel::Channel;
+use rocketmq_remoting::protocol::header::broker::add_broker_request_header::AddBrokerRequestHeader;
+use rocketmq_remoting::protocol::header::broker::remove_broker_request_header::RemoveBrokerRequestHeader;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
+use rocketmq_remoting::runtime::processor::RequestProcessor;
+use tracing::error;
+use tracing::info;
+use tracing::warn;
+
+use crate::broker_container::parse_broker_config_file;
+use crate::broker_container::parse_broker_config_properties;
+use crate::broker_container::BrokerContainer;
+
+/// Serves the admin requests of the container server.
+#[derive(Clone)]
+pub(crate) struct BrokerContainerProcessor {
+    broker_container: BrokerContainer,
+}
+
+impl BrokerContainerProcessor {
+    pub fn new(broker_container: BrokerContainer) -> Self {
+        BrokerContainerProcessor { broker_container }
+    }
+
+    async fn add_broker(&self, request: RemotingCommand) -> RemotingCommand {
+        let request_header = match request.decode_command_custom_header::<AddBrokerRequestHeader>()
+        {
+            Ok(request_header) => request_header,
+            Err(e) => {
+                return RemotingCommand::create_response_command_with_code_remark(
+                    ResponseCode::SystemError,
+                    e.to_string(),
+                )
+            }
+        };
+        let configs = match request_header.config_path.as_ref() {
+            Some(config_path) if !config_path.is_empty() => {
+                info!("addBroker called, config path: {}", config_path);
+                parse_broker_config_file(config_path.as_str())
+            }
+            _ => match request.get_body() {
+                Some(body) => parse_broker_config_properties(&String::from_utf8_lossy(body)),
+                None => {
+                    return RemotingCommand::create_response_command_with_code_remark(
+                        ResponseCode::SystemError,
+                        "addBroker needs a config path or a broker config in the body",
+                    )
+                }
+            },
+        };
+        let result = match configs {
+            Ok((broker_config, message_store_config)) => {
+                self.broker_container
+                    .add_broker(broker_config, message_store_config)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(()) => RemotingCommand::create_response_command(),
+            Err(e) => {
+                error!("addBroker failed: {}", e);
+                RemotingCommand::create_response_command_with_code_remark(
+                    ResponseCode::SystemError,
+                    e.to_string(),
+                )
+            }
+        }
+    }
+
+    async fn remove_broker(&self, request: RemotingCommand) -> RemotingCommand {
+        let request_header =
+            match request.decode_command_custom_header::<RemoveBrokerRequestHeader>() {
+                Ok(request_header) => request_header,
+                Err(e) => {
+                    return RemotingCommand::create_response_command_with_code_remark(
+                        ResponseCode::SystemError,
+                        e.to_string(),
+                    )
+                }
+            };
+        info!(
+            "removeBroker called, broker: {}_{}_{}",
+            request_header.broker_cluster_name,
+            request_header.broker_name,
+            request_header.broker_id
+        );
+        match self
+            .broker_container
+            .remove_broker(
+                &request_header.broker_cluster_name,
+                &request_header.broker_name,
+                request_header.broker_id,
+            )
+            .await
+        {
+            Ok(()) => RemotingCommand::create_response_command(),
+            Err(e) => {
+                warn!("removeBroker failed: {}", e);
+                RemotingCommand::create_response_command_with_code_remark(
+                    ResponseCode::SystemError,
+                    e.to_string(),
+                )
+            }
+        }
+    }
+}
+
+impl RequestProcessor for BrokerContainerProcessor {
+    async fn process_request(
+        &mut self,
+        _channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        request: RemotingCommand,
+    ) -> RocketMQResult<Option<RemotingCommand>> {
+        let request_code = RequestCode::from(request.code());
+        let response = match request_code {
+            RequestCode::AddBroker => self.add_broker(request).await,
+            RequestCode::RemoveBroker => self.remove_broker(request).await,
+            _ => {
+                warn!(
+                    "request type {:?} not supported by the container",
+                    request_code
+                );
+                RemotingCommand::create_response_command_with_code_remark(
+                    ResponseCode::RequestCodeNotSupported,
+                    format!(" request type {} not supported", request_code.to_i32()),
+                )
+            }
+        };
+        Ok(Some(response))
+    }
+}
@@ -15,6 +15,10 @@
  * limitations under the License.
  */
 use std::collections::HashMap;
+use std::future::Future;
+use std::net::IpAddr;
+use std::net::Ipv4Addr;
+use std::net::Ipv6Addr;
 use std::net::SocketAddr;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::AtomicU64;
@@ -30,32 +34,48 @@ use rocketmq_common::common::constant::PermName;
 use rocketmq_common::common::mix_all;
 use rocketmq_common::common::server::config::ServerConfig;
 use rocketmq_common::common::statistics::state_getter::StateGetter;
+use rocketmq_common::utils::network_util::NetworkUtil;
 use rocketmq_common::TimeUtils::get_current_millis;
 use rocketmq_common::UtilAll::compute_next_morning_time_millis;
+use rocketmq_error::RocketMQResult;
+use rocketmq_remoting::auth::acl_client_rpc_hook::AclClientRpcHook;
+use rocketmq_remoting::auth::acl_client_rpc_hook::SessionCredentials;
+use rocketmq_remoting::auth::authentication_provider::AuthenticationProvider;
 use rocketmq_remoting::base::channel_event_listener::ChannelEventListener;
 use rocketmq_remoting::protocol::body::broker_body::broker_member_group::BrokerMemberGroup;
 use rocketmq_remoting::protocol::body::topic_info_wrapper::topic_config_wrapper::TopicConfigAndMappingSerializeWrapper;
 use rocketmq_remoting::protocol::body::topic_info_wrapper::topic_config_wrapper::TopicConfigSerializeWrapper;
+use rocketmq_remoting::protocol::header::namesrv::brokerid_change_request_header::NotifyMinBrokerIdChangeRequestHeader;
 use rocketmq_remoting::protocol::namespace_util::NamespaceUtil;
+use rocketmq_remoting::protocol::namesrv::RegisterBrokerResult;
 use rocketmq_remoting::protocol::static_topic::topic_queue_mapping_detail::TopicQueueMappingDetail;
 use rocketmq_remoting::protocol::DataVersion;
 use rocketmq_remoting::remoting_server::server::RocketMQServer;
 use rocketmq_remoting::runtime::config::client_config::TokioClientConfig;
-use rocketmq_runtime::RocketMQRuntime;
+use rocketmq_remoting::runtime::RPCHook;
+use rocketmq_rust::wait_for_signal;
 use rocketmq_rust::ArcMut;
 use rocketmq_store::base::commit_log_dispatcher::CommitLogDispatcher;
 use rocketmq_store::base::message_store::MessageStore;
 use rocketmq_store::base::store_enum::StoreType;
 use rocketmq_store::config::message_store_config::MessageStoreConfig;
+use rocketmq_store::hook::delay_level_check_hook::DelayLevelCheckHook;
+use rocketmq_store::hook::message_size_check_hook::MessageSizeCheckHook;
+use rocketmq_store::hook::put_message_hook::BoxedPutMessageHook;
 use rocketmq_store::message_store::local_file_message_store::LocalFileMessageStore;
 use rocketmq_store::stats::broker_stats::BrokerStats;
 use rocketmq_store::stats::broker_stats_manager::BrokerStatsManager;
 use rocketmq_store::timer::timer_message_store::TimerMessageStore;
+use tracing::error;
 use tracing::info;
 use tracing::warn;
 
+use crate::audit::request_auditor::RequestAuditor;
 use crate::broker::broker_hook::BrokerShutdownHook;
 use crate::broker::broker_pre_online_service::BrokerPreOnlineService;
+use crate::broker::broker_readiness::BrokerReadiness;
+use crate::broker::broker_startup_check;
+use crate::broker_runtime_group::BrokerRuntimeGroup;
 use crate::client::client_housekeeping_service::ClientHousekeepingService;
 use crate::client::consumer_ids_change_listener::ConsumerIdsChangeListener;
 use crate::client::default_consumer_ids_change_listener::DefaultConsumerIdsChangeListener;
@@ -66,13 +86,18 @@ use crate::client::rebalance::rebalance_lock_manager::RebalanceLockManager;
 use crate::coldctr::cold_data_cg_ctr_service::ColdDataCgCtrService;
 use crate::coldctr::cold_data_pull_request_hold_service::ColdDataPullRequestHoldService;
 use crate::controller::replicas_manager::ReplicasManager;
+use crate::dedup::send_dedup_table::SendDedupTable;
+use crate::dledger::dledger_role_change_handler::DLedgerRoleChangeHandler;
 use crate::failover::escape_bridge::EscapeBridge;
 use crate::filter::commit_log_dispatcher_calc_bit_map::CommitLogDispatcherCalcBitMap;
 use crate::filter::manager::consumer_filter_manager::ConsumerFilterManager;
+use crate::flow_control::flow_controller::FlowController;
 use crate::hook::batch_check_before_put_message::BatchCheckBeforePutMessageHook;
 use crate::hook::check_before_put_message::CheckBeforePutMessageHook;
 use crate::hook::schedule_message_hook::ScheduleMessageHook;
+use crate::http_admin::http_admin_server::HttpAdminServer;
 use crate::latency::broker_fast_failure::BrokerFastFailure;
+use crate::load_balance::message_request_mode_manager::MessageRequestModeManager;
 use crate::long_polling::long_polling_service::pull_request_hold_service::PullRequestHoldService;
 use crate::long_polling::notify_message_arriving_listener::NotifyMessageArrivingListener;
 use crate::offset::manager::broadcast_offset_manager::BroadcastOffsetManager;
@@ -90,6 +115,7 @@ use crate::processor::end_transaction_processor::EndTransactionProcessor;
 use crate::processor::notification_processor::NotificationProcessor;
 use crate::processor::pop_inflight_message_counter::PopInflightMessageCounter;
 use crate::processor::pop_message_processor::PopMessageProcessor;
+use crate::processor::processor_service::response_body_compression::CpuLoadSampler;
 use crate::processor::pull_message_processor::PullMessageProcessor;
 use crate::processor::query_assignment_processor::QueryAssignmentProcessor;
 use crate::processor::query_message_processor::QueryMessageProcessor;
@@ -114,13 +140,22 @@ pub(crate) struct BrokerRuntime {
     #[cfg(feature = "local_file_store")]
     transactional_message_service:
         Option<ArcMut<DefaultTransactionalMessageService<LocalFileMessageStore>>>,
-    broker_runtime: Option<RocketMQRuntime>,
+    // scheduled tasks and the dedicated network/processor/store/flush runtimes, owned by the
+    // container for the brokers it hosts
+    runtime_group: BrokerRuntimeGroup,
     shutdown_hook: Option<BrokerShutdownHook>,
     consumer_ids_change_listener: Arc<Box<dyn ConsumerIdsChangeListener + Send + Sync + 'static>>,
     topic_queue_mapping_clean_service: TopicQueueMappingCleanService,
-    broker_pre_online_service: BrokerPreOnlineService,
+    #[cfg(feature = "local_file_store")]
+    broker_pre_online_service: BrokerPreOnlineService<LocalFileMessageStore>,
+    // stops the remoting servers of this broker without stopping the process
+    server_shutdown_tx: tokio::sync::broadcast::Sender<()>,
     // receiver for shutdown signal
     pub(crate) shutdown_rx: Option<tokio::sync::broadcast::Receiver<()>>,
+    // hooks of the embedder, run after the built-in checks and before messages are scheduled
+    put_message_hooks: Vec<BoxedPutMessageHook>,
+    // authenticates the requests of the remoting servers when set by the embedder
+    authentication_provider: Option<Arc<dyn AuthenticationProvider>>,
 }
 
 impl BrokerRuntime {
@@ -129,11 +164,37 @@ impl BrokerRuntime {
         message_store_config: Arc<MessageStoreConfig>,
         server_config: Arc<ServerConfig>,
     ) -> Self {
-        let broker_address = format!("{}:{}", broker_config.broker_ip1, broker_config.listen_port);
-        let store_host = broker_address
-            .parse::<SocketAddr>()
-            .expect("parse store_host failed");
-        let runtime = RocketMQRuntime::new_multi(10, "broker-thread");
+        let runtime_group = BrokerRuntimeGroup::new(&broker_config);
+        Self::with_runtime_group(
+            broker_config,
+            message_store_config,
+            server_config,
+            runtime_group,
+        )
+    }
+
+    /// Creates a broker running on the given runtimes instead of creating its own.
+    pub(crate) fn with_runtime_group(
+        broker_config: Arc<BrokerConfig>,
+        message_store_config: Arc<MessageStoreConfig>,
+        server_config: Arc<ServerConfig>,
+        runtime_group: BrokerRuntimeGroup,
+    ) -> Self {
+        let broker_address = broker_config.get_broker_addr();
+        let store_host = NetworkUtil::resolve_host_port(
+            broker_config.broker_ip1.as_str(),
+            broker_config.listen_port,
+        )
+        .unwrap_or_else(|e| {
+            error!(
+                "Resolve store host failed, the broker will refuse to start: {}",
+                e
+            );
+            SocketAddr::new(
+                IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+                broker_config.listen_port as u16,
+            )
+        });
         let broker_outer_api = BrokerOuterAPI::new(Arc::new(TokioClientConfig::default()));
 
         let topic_queue_mapping_manager = TopicQueueMappingManager::new(broker_config.clone());
@@ -157,6 +218,12 @@ impl BrokerRuntime {
         let should_start_time = Arc::new(AtomicU64::new(0));
         let pop_inflight_message_counter =
             PopInflightMessageCounter::new(should_start_time.clone());
+        let flow_controller = FlowController::new(&broker_config);
+        let send_dedup_table = Arc::new(SendDedupTable::new(&broker_config));
+        let cpu_load_sampler = Arc::new(CpuLoadSampler::default());
+        let request_auditor = Arc::new(RequestAuditor::new(&broker_config));
+        let consumer_offset_manager = ConsumerOffsetManager::new(broker_config.clone(), None);
+        let consumer_filter_manager = ConsumerFilterManager::new(broker_config.clone());
 
         let mut inner = ArcMut::new(BrokerRuntimeInner::<LocalFileMessageStore> {
             shutdown: Arc::new(AtomicBool::new(false)),
@@ -167,9 +234,9 @@ impl BrokerRuntime {
             server_config,
             topic_config_manager: None,
             topic_queue_mapping_manager,
-            consumer_offset_manager: Default::default(),
+            consumer_offset_manager,
             subscription_group_manager: None,
-            consumer_filter_manager: Some(Default::default()),
+            consumer_filter_manager: Some(consumer_filter_manager),
             consumer_order_info_manager: None,
             message_store: None,
             broker_stats: None,
@@ -195,6 +262,11 @@ impl BrokerRuntime {
             pop_inflight_message_counter,
             replicas_manager: None,
             broker_fast_failure: BrokerFastFailure,
+            flow_controller,
+            send_dedup_table,
+            cpu_load_sampler,
+            request_auditor,
+            readiness: BrokerReadiness::default(),
             cold_data_pull_request_hold_service: None,
             cold_data_cg_ctr_service: None,
             is_schedule_service_start: Arc::new(Default::default()),
@@ -229,19 +301,38 @@ impl BrokerRuntime {
             Some(ArcMut::new(ScheduleMessageService::new(inner.clone())));
         inner.client_housekeeping_service =
             Some(Arc::new(ClientHousekeepingService::new(inner.clone())));
+        let broker_pre_online_service = BrokerPreOnlineService::new(inner.clone());
 
         Self {
             inner,
             transactional_message_service: None,
-            broker_runtime: Some(runtime),
+            runtime_group,
             shutdown_hook: None,
             consumer_ids_change_listener,
             topic_queue_mapping_clean_service: TopicQueueMappingCleanService,
-            broker_pre_online_service: BrokerPreOnlineService,
+            broker_pre_online_service,
+            server_shutdown_tx: tokio::sync::broadcast::channel(1).0,
             shutdown_rx: None,
+            put_message_hooks: Vec::new(),
+            authentication_provider: None,
         }
     }
 
+    /// Adds a hook that can reject messages before they are stored, it has to be added before
+    /// the broker is initialized.
+    pub(crate) fn add_put_message_hook(&mut self, put_message_hook: BoxedPutMessageHook) {
+        self.put_message_hooks.push(put_message_hook);
+    }
+
+    /// Authenticates the requests of the remoting servers with `authentication_provider`, it has
+    /// to be set before the broker is started.
+    pub(crate) fn set_authentication_provider(
+        &mut self,
+        authentication_provider: Arc<dyn AuthenticationProvider>,
+    ) {
+        self.authentication_provider = Some(authentication_provider);
+    }
+
     pub(crate) fn broker_config(&self) -> &BrokerConfig {
         self.inner.broker_config()
     }
@@ -255,9 +346,7 @@ impl BrokerRuntime {
 
         self.inner.broker_outer_api.shutdown();
 
-        if let Some(runtime) = self.broker_runtime.take() {
-            runtime.shutdown();
-        }
+        self.runtime_group.shutdown();
 
         if let Some(client_housekeeping_service) = self.inner.client_housekeeping_service.take() {
             client_housekeeping_service.shutdown();
@@ -291,23 +380,27 @@ impl BrokerRuntime {
             .await;
     }
 
+    /// Stops the broker in an order that loses no data: requests are turned away first, held
+    /// pulls are answered, metadata is persisted and the message store flushed before the broker
+    /// leaves the name servers and closes its connections. The store keeps its abort file unless
+    /// it shut down cleanly, so an interrupted stop is recovered on the next start.
     pub(crate) async fn shutdown_basic_service(&mut self) {
+        // the request processors answer SYSTEM_BUSY from now on
         self.inner.shutdown.store(true, Ordering::SeqCst);
 
-        self.unregister_broker().await;
-
         if let Some(hook) = self.shutdown_hook.as_ref() {
             hook.before_shutdown();
         }
 
-        if let Some(broker_stats_manager) = self.inner.broker_stats_manager.as_ref() {
-            broker_stats_manager.shutdown();
-        }
-
+        // wakes the held pull requests while the store can still serve them
         if let Some(pull_request_hold_service) = self.inner.pull_request_hold_service.as_mut() {
             pull_request_hold_service.shutdown();
         }
 
+        if let Some(broker_stats_manager) = self.inner.broker_stats_manager.as_ref() {
+            broker_stats_manager.shutdown();
+        }
+
         if let Some(pop_message_processor) = self.inner.pop_message_processor.as_mut() {
             pop_message_processor.shutdown();
         }
@@ -325,29 +418,13 @@ impl BrokerRuntime {
         }
         self.consumer_ids_change_listener.shutdown();
         self.topic_queue_mapping_clean_service.shutdown();
+        self.broker_pre_online_service.shutdown();
         if let Some(timer_message_store) = self.inner.timer_message_store.as_mut() {
             timer_message_store.shutdown();
         }
 
-        self.inner.broadcast_offset_manager.shutdown();
-
-        if let Some(message_store) = self.inner.message_store.as_mut() {
-            message_store.shutdown();
-        }
-
-        if let Some(replicas_manager) = self.inner.replicas_manager.as_mut() {
-            replicas_manager.shutdown();
-        }
-
         self.inner.broker_fast_failure.shutdown();
 
-        if let Some(consumer_filter_manager) = self.inner.consumer_filter_manager.as_ref() {
-            consumer_filter_manager.persist();
-        }
-        if let Some(consumer_order_info_manager) = self.inner.consumer_order_info_manager.as_ref() {
-            consumer_order_info_manager.persist();
-        }
-
         if let Some(schedule_message_service) = self.inner.schedule_message_service.as_mut() {
             schedule_message_service.persist();
             schedule_message_service.shutdown();
@@ -369,10 +446,6 @@ impl BrokerRuntime {
             topic_route_info_manager.shutdown();
         }
 
-        if let Some(topic_route_info_manager) = self.inner.topic_route_info_manager.as_mut() {
-            topic_route_info_manager.shutdown();
-        }
-
         if let Some(cold_data_pull_request_hold_service) =
             self.inner.cold_data_pull_request_hold_service.as_mut()
         {
@@ -383,6 +456,10 @@ impl BrokerRuntime {
             cold_data_cg_ctr_service.shutdown();
         }
 
+        self.inner.consumer_offset_manager.persist();
+        self.inner.consumer_offset_manager.stop();
+        self.inner.broadcast_offset_manager.shutdown();
+
         if let Some(topic_config_manager) = self.inner.topic_config_manager.as_mut() {
             topic_config_manager.persist();
             topic_config_manager.stop();
@@ -393,13 +470,42 @@ impl BrokerRuntime {
             subscription_group_manager.stop();
         }
 
-        self.inner.consumer_offset_manager.persist();
-        self.inner.consumer_offset_manager.stop();
+        if let Some(consumer_filter_manager) = self.inner.consumer_filter_manager.as_ref() {
+            consumer_filter_manager.persist();
+        }
+        if let Some(consumer_order_info_manager) = self.inner.consumer_order_info_manager.as_ref() {
+            consumer_order_info_manager.shutdown();
+            consumer_order_info_manager.persist();
+        }
+        info!("[Broker shutdown] consumer offsets and metadata persisted");
+
+        if let Some(message_store) = self.inner.message_store.as_mut() {
+            message_store.shutdown();
+        }
+
+        if let Some(replicas_manager) = self.inner.replicas_manager.as_mut() {
+            replicas_manager.shutdown();
+        }
+
+        self.unregister_broker().await;
+
+        let _ = self.server_shutdown_tx.send(());
     }
 }
 
 impl BrokerRuntime {
     pub(crate) async fn initialize(&mut self) -> bool {
+        let problems = broker_startup_check::check_startup(
+            &self.inner.broker_config,
+            &self.inner.message_store_config,
+            &self.inner.server_config,
+        );
+        if !problems.is_empty() {
+            for problem in &problems {
+                error!("Broker startup check failed: {}", problem);
+            }
+            return false;
+        }
         let mut result = self.initialize_metadata();
         if !result {
             warn!("Initialize metadata failed");
@@ -444,7 +550,11 @@ impl BrokerRuntime {
             self.inner.broker_stats = Some(BrokerStats::new(message_store.clone()));
             self.inner.message_store = Some(message_store);
         } else if self.inner.message_store_config.store_type == StoreType::RocksDB {
-            unimplemented!("Use RocksDB as message store unimplemented");
+            error!(
+                "RocksDB store is not supported, neither for messages nor for the metadata, use \
+                 LocalFile"
+            );
+            return false;
         } else {
             warn!("Unknown store type");
             return false;
@@ -469,7 +579,7 @@ impl BrokerRuntime {
         if self.inner.message_store.is_some() {
             self.register_message_store_hook();
             // load message store
-            self.inner.message_store.as_mut().unwrap().load().await;
+            result &= self.inner.message_store.as_mut().unwrap().load().await;
         }
 
         if self
@@ -485,6 +595,7 @@ impl BrokerRuntime {
         result &= self.inner.schedule_message_service.as_ref().unwrap().load();
 
         if result {
+            self.inner.readiness.mark_recovered();
             self.initialize_remoting_server();
             self.initialize_resources();
             self.initialize_scheduled_tasks().await;
@@ -500,12 +611,18 @@ impl BrokerRuntime {
         let config = self.inner.message_store_config.clone();
         let arc = self.inner.topic_config_manager().topic_config_table();
         let broker_runtime_inner = ArcMut::clone(&self.inner);
+        let put_message_hooks = std::mem::take(&mut self.put_message_hooks);
         if let Some(ref mut message_store) = self.inner.message_store {
             message_store.set_put_message_hook(Box::new(CheckBeforePutMessageHook::new(
                 message_store.clone(),
-                config,
+                config.clone(),
             )));
+            message_store.set_put_message_hook(Box::new(MessageSizeCheckHook::new(&config)));
+            message_store.set_put_message_hook(Box::new(DelayLevelCheckHook));
             message_store.set_put_message_hook(Box::new(BatchCheckBeforePutMessageHook::new(arc)));
+            for put_message_hook in put_message_hooks {
+                message_store.set_put_message_hook(put_message_hook);
+            }
             message_store
                 .set_put_message_hook(Box::new(ScheduleMessageHook::new(broker_runtime_inner)))
         }
@@ -571,6 +688,9 @@ impl BrokerRuntime {
 
         let notification_processor = NotificationProcessor::new(self.inner.clone());
         self.inner.notification_processor = Some(notification_processor.clone());
+        let message_request_mode_manager =
+            MessageRequestModeManager::new(Arc::new(self.inner.message_store_config().clone()));
+        let _ = message_request_mode_manager.load();
         BrokerRequestProcessor {
             send_message_processor: ArcMut::new(send_message_processor),
             pull_message_processor,
@@ -585,16 +705,22 @@ impl BrokerRuntime {
             polling_info_processor: Default::default(),
             reply_message_processor: ArcMut::new(reply_message_processor),
             admin_broker_processor: ArcMut::new(admin_broker_processor),
-            client_manage_processor: ArcMut::new(ClientManageProcessor::new(self.inner.clone())),
+            client_manage_processor: ArcMut::new(ClientManageProcessor::new(
+                self.inner.clone(),
+                message_request_mode_manager.clone(),
+            )),
             consumer_manage_processor: ArcMut::new(consumer_manage_processor),
             query_assignment_processor: ArcMut::new(QueryAssignmentProcessor::new(
                 self.inner.clone(),
+                message_request_mode_manager,
             )),
             query_message_processor: ArcMut::new(query_message_processor),
             end_transaction_processor: ArcMut::new(EndTransactionProcessor::new(
                 self.transactional_message_service.as_ref().unwrap().clone(),
                 self.inner.clone(),
             )),
+            shutdown: self.inner.shutdown.clone(),
+            request_auditor: self.inner.request_auditor.clone(),
         }
     }
 
@@ -602,114 +728,108 @@ impl BrokerRuntime {
         let initial_delay = compute_next_morning_time_millis() - get_current_millis();
         let period = Duration::from_days(1).as_millis() as u64;
         let broker_stats_ = self.inner.clone();
-        self.broker_runtime
-            .as_ref()
-            .unwrap()
-            .get_handle()
-            .spawn(async move {
-                info!("BrokerStats Start scheduled task");
-                tokio::time::sleep(Duration::from_millis(initial_delay)).await;
-                loop {
-                    let current_execution_time = tokio::time::Instant::now();
-                    broker_stats_.broker_stats().as_ref().unwrap().record();
-                    let next_execution_time =
-                        current_execution_time + Duration::from_millis(period);
-                    let delay =
-                        next_execution_time.saturating_duration_since(tokio::time::Instant::now());
-                    tokio::time::sleep(delay).await;
-                }
-            });
+        self.runtime_group.spawn_scheduled(async move {
+            info!("BrokerStats Start scheduled task");
+            tokio::time::sleep(Duration::from_millis(initial_delay)).await;
+            loop {
+                let current_execution_time = tokio::time::Instant::now();
+                broker_stats_.broker_stats().as_ref().unwrap().record();
+                let next_execution_time = current_execution_time + Duration::from_millis(period);
+                let delay =
+                    next_execution_time.saturating_duration_since(tokio::time::Instant::now());
+                tokio::time::sleep(delay).await;
+            }
+        });
 
         //need to optimize
         let consumer_offset_manager_inner = self.inner.clone();
         let flush_consumer_offset_interval =
             self.inner.broker_config.flush_consumer_offset_interval;
-        self.broker_runtime
-            .as_ref()
-            .unwrap()
-            .get_handle()
-            .spawn(async move {
-                info!("Consumer offset manager Start scheduled task");
-                tokio::time::sleep(Duration::from_millis(1000 * 10)).await;
-                loop {
-                    let current_execution_time = tokio::time::Instant::now();
-                    consumer_offset_manager_inner
-                        .consumer_offset_manager
-                        .persist();
-                    let next_execution_time = current_execution_time
-                        + Duration::from_millis(flush_consumer_offset_interval);
-                    let delay =
-                        next_execution_time.saturating_duration_since(tokio::time::Instant::now());
-                    tokio::time::sleep(delay).await;
-                }
-            });
+        self.runtime_group.spawn_scheduled(async move {
+            info!("Consumer offset manager Start scheduled task");
+            tokio::time::sleep(Duration::from_millis(1000 * 10)).await;
+            loop {
+                let current_execution_time = tokio::time::Instant::now();
+                consumer_offset_manager_inner
+                    .consumer_offset_manager
+                    .persist_if_dirty()
+                    .await;
+                let next_execution_time =
+                    current_execution_time + Duration::from_millis(flush_consumer_offset_interval);
+                let delay =
+                    next_execution_time.saturating_duration_since(tokio::time::Instant::now());
+                tokio::time::sleep(delay).await;
+            }
+        });
 
         //need to optimize
         let mut _inner = self.inner.clone();
         //let mut  consumer_order_info_manager = self.inner.consumer_order_info_manager.clone();
-        self.broker_runtime
-            .as_ref()
-            .unwrap()
-            .get_handle()
-            .spawn(async move {
-                info!("consumer filter manager Start scheduled task");
-                info!("consumer order info manager Start scheduled task");
-                tokio::time::sleep(Duration::from_millis(1000 * 10)).await;
+        self.runtime_group.spawn_scheduled(async move {
+            info!("consumer filter manager Start scheduled task");
+            info!("consumer order info manager Start scheduled task");
+            tokio::time::sleep(Duration::from_millis(1000 * 10)).await;
+            loop {
+                let current_execution_time = tokio::time::Instant::now();
+                _inner.consumer_filter_manager.as_mut().unwrap().persist();
+                _inner
+                    .consumer_order_info_manager
+                    .as_mut()
+                    .unwrap()
+                    .persist();
+                let next_execution_time = current_execution_time + Duration::from_millis(1000 * 10);
+                let delay =
+                    next_execution_time.saturating_duration_since(tokio::time::Instant::now());
+                tokio::time::sleep(delay).await;
+            }
+        });
+
+        if self.inner.broker_config.compress_pull_response_body {
+            let cpu_load_sampler = self.inner.cpu_load_sampler.clone();
+            self.runtime_group.spawn_scheduled(async move {
+                info!("Cpu load sampler Start scheduled task");
                 loop {
                     let current_execution_time = tokio::time::Instant::now();
-                    _inner.consumer_filter_manager.as_mut().unwrap().persist();
-                    _inner
-                        .consumer_order_info_manager
-                        .as_mut()
-                        .unwrap()
-                        .persist();
-                    let next_execution_time =
-                        current_execution_time + Duration::from_millis(1000 * 10);
+                    cpu_load_sampler.sample();
+                    let next_execution_time = current_execution_time + Duration::from_secs(5);
                     let delay =
                         next_execution_time.saturating_duration_since(tokio::time::Instant::now());
                     tokio::time::sleep(delay).await;
                 }
             });
+        }
 
         let mut runtime = self.inner.clone();
-        self.broker_runtime
-            .as_ref()
-            .unwrap()
-            .get_handle()
-            .spawn(async move {
-                info!("Protect broker Start scheduled task");
-                tokio::time::sleep(Duration::from_mins(3)).await;
-                loop {
-                    let current_execution_time = tokio::time::Instant::now();
-                    runtime.protect_broker();
-                    let next_execution_time = current_execution_time + Duration::from_mins(3);
-                    let delay =
-                        next_execution_time.saturating_duration_since(tokio::time::Instant::now());
-                    tokio::time::sleep(delay).await;
-                }
-            });
+        self.runtime_group.spawn_scheduled(async move {
+            info!("Protect broker Start scheduled task");
+            tokio::time::sleep(Duration::from_mins(3)).await;
+            loop {
+                let current_execution_time = tokio::time::Instant::now();
+                runtime.protect_broker();
+                let next_execution_time = current_execution_time + Duration::from_mins(3);
+                let delay =
+                    next_execution_time.saturating_duration_since(tokio::time::Instant::now());
+                tokio::time::sleep(delay).await;
+            }
+        });
 
         let message_store_inner = self.inner.clone();
-        self.broker_runtime
-            .as_ref()
-            .unwrap()
-            .get_handle()
-            .spawn(async move {
-                info!("Message store dispatch_behind_bytes Start scheduled task");
-                tokio::time::sleep(Duration::from_secs(10)).await;
-                loop {
-                    let current_execution_time = tokio::time::Instant::now();
-                    message_store_inner
-                        .message_store
-                        .as_ref()
-                        .unwrap()
-                        .dispatch_behind_bytes();
-                    let next_execution_time = current_execution_time + Duration::from_secs(60);
-                    let delay =
-                        next_execution_time.saturating_duration_since(tokio::time::Instant::now());
-                    tokio::time::sleep(delay).await;
-                }
-            });
+        self.runtime_group.spawn_scheduled(async move {
+            info!("Message store dispatch_behind_bytes Start scheduled task");
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            loop {
+                let current_execution_time = tokio::time::Instant::now();
+                message_store_inner
+                    .message_store
+                    .as_ref()
+                    .unwrap()
+                    .dispatch_behind_bytes();
+                let next_execution_time = current_execution_time + Duration::from_secs(60);
+                let delay =
+                    next_execution_time.saturating_duration_since(tokio::time::Instant::now());
+                tokio::time::sleep(delay).await;
+            }
+        });
 
         if self.inner.broker_config.enable_controller_mode {
             self.inner.update_master_haserver_addr_periodically = true;
@@ -722,21 +842,17 @@ impl BrokerRuntime {
                 namesrv_address
             );
             let mut broker_runtime = self.inner.clone();
-            self.broker_runtime
-                .as_ref()
-                .unwrap()
-                .get_handle()
-                .spawn(async move {
-                    tokio::time::sleep(Duration::from_secs(10)).await;
-                    loop {
-                        let current_execution_time = tokio::time::Instant::now();
-                        broker_runtime.update_namesrv_addr_inner().await;
-                        let next_execution_time = current_execution_time + Duration::from_secs(60);
-                        let delay = next_execution_time
-                            .saturating_duration_since(tokio::time::Instant::now());
-                        tokio::time::sleep(delay).await;
-                    }
-                });
+            self.runtime_group.spawn_scheduled(async move {
+                tokio::time::sleep(Duration::from_secs(10)).await;
+                loop {
+                    let current_execution_time = tokio::time::Instant::now();
+                    broker_runtime.update_namesrv_addr_inner().await;
+                    let next_execution_time = current_execution_time + Duration::from_secs(60);
+                    let delay =
+                        next_execution_time.saturating_duration_since(tokio::time::Instant::now());
+                    tokio::time::sleep(delay).await;
+                }
+            });
         }
     }
 
@@ -760,12 +876,33 @@ impl BrokerRuntime {
 
     fn initial_acl(&mut self) {}
 
-    fn initial_rpc_hooks(&mut self) {}
+    fn initial_rpc_hooks(&mut self) {
+        if let Some(rpc_hook) = inner_client_rpc_hook(&self.inner.broker_config) {
+            self.inner.broker_outer_api.register_rpc_hook(rpc_hook);
+        }
+    }
 
     fn initial_request_pipeline(&mut self) {}
 
+    fn server_shutdown_signal(&self) -> impl Future<Output = ()> + Send + 'static {
+        let mut server_shutdown_rx = self.server_shutdown_tx.subscribe();
+        async move {
+            tokio::select! {
+                _ = wait_for_signal() => {}
+                _ = server_shutdown_rx.recv() => {}
+            }
+        }
+    }
+
     fn start_basic_service(&mut self) {
+        // Store services spawn their loops while starting, entering the store runtime makes
+        // them land there.
+        let store_runtime = self.runtime_group.store_handle();
+        let _store_runtime_guard = store_runtime.as_ref().map(|runtime| runtime.enter());
         if let Some(ref mut message_store) = self.inner.message_store {
+            if let Some(flush_runtime) = self.runtime_group.flush_handle() {
+                message_store.set_flush_runtime(flush_runtime);
+            }
             message_store
                 .start()
                 .unwrap_or_else(|e| panic!("Failed to start message store: {e}"));
@@ -783,7 +920,14 @@ impl BrokerRuntime {
         let request_processor = self.init_processor();
         let fast_request_processor = request_processor.clone();
 
-        let server = RocketMQServer::new(self.inner.server_config.clone());
+        let processor_runtime = self.runtime_group.processor_handle();
+        let mut server = RocketMQServer::new(self.inner.server_config.clone());
+        if let Some(ref runtime) = processor_runtime {
+            server = server.with_connection_runtime(runtime.clone());
+        }
+        if let Some(ref authentication_provider) = self.authentication_provider {
+            server = server.with_authentication_provider(authentication_provider.clone());
+        }
         //start nomarl broker remoting_server
         let client_housekeeping_service_main = self
             .inner
@@ -791,20 +935,53 @@ impl BrokerRuntime {
             .clone()
             .map(|item| item as Arc<dyn ChannelEventListener>);
         let client_housekeeping_service_fast = client_housekeeping_service_main.clone();
-        tokio::spawn(async move {
+        let server_shutdown = self.server_shutdown_signal();
+        self.runtime_group.spawn_network(async move {
             server
-                .run(request_processor, client_housekeeping_service_main)
+                .run_until(
+                    request_processor,
+                    client_housekeeping_service_main,
+                    server_shutdown,
+                )
                 .await
         });
         //start fast broker remoting_server
         let mut fast_server_config = self.inner.server_config.as_ref().clone();
         fast_server_config.listen_port = self.inner.server_config.listen_port - 2;
-        let fast_server = RocketMQServer::new(Arc::new(fast_server_config));
-        tokio::spawn(async move {
+        let mut fast_server = RocketMQServer::new(Arc::new(fast_server_config));
+        if let Some(runtime) = processor_runtime {
+            fast_server = fast_server.with_connection_runtime(runtime);
+        }
+        if let Some(ref authentication_provider) = self.authentication_provider {
+            fast_server = fast_server.with_authentication_provider(authentication_provider.clone());
+        }
+        let fast_server_shutdown = self.server_shutdown_signal();
+        self.runtime_group.spawn_network(async move {
             fast_server
-                .run(fast_request_processor, client_housekeeping_service_fast)
+                .run_until(
+                    fast_request_processor,
+                    client_housekeeping_service_fast,
+                    fast_server_shutdown,
+                )
                 .await
         });
+        if self.inner.broker_config.enable_http_admin {
+            match http_admin_forward_address(&self.inner.server_config) {
+                Ok(broker_addr) => {
+                    let http_admin_server = HttpAdminServer::new(
+                        self.inner.broker_config.http_admin_bind_address.to_string(),
+                        self.inner.broker_config.http_admin_listen_port,
+                        broker_addr,
+                        self.inner.broker_config.forward_timeout,
+                        self.authentication_provider.is_some(),
+                    );
+                    let http_admin_shutdown = self.server_shutdown_signal();
+                    self.runtime_group
+                        .spawn_network(http_admin_server.run_until(http_admin_shutdown));
+                }
+                Err(e) => error!("Http admin server not started: {}", e),
+            }
+        }
 
         if let Some(pop_message_processor) = self.inner.pop_message_processor.as_mut() {
             pop_message_processor.start();
@@ -887,38 +1064,46 @@ impl BrokerRuntime {
             self.register_broker_all(true, false, true).await;
         }
 
+        if self.inner.message_store_config.enable_dledger_commit_log {
+            if let Some(dledger_commit_log) = self
+                .inner
+                .message_store_unchecked()
+                .dledger_commit_log()
+                .cloned()
+            {
+                let handler = DLedgerRoleChangeHandler::new(self.inner.clone(), dledger_commit_log);
+                self.runtime_group.spawn_scheduled(handler.run());
+            }
+        }
+
         //start register broker to name server scheduled task
         let broker_runtime_inner = self.inner.clone();
-        self.broker_runtime
-            .as_ref()
-            .unwrap()
-            .get_handle()
-            .spawn(async move {
-                let period = Duration::from_millis(
-                    10000.max(
-                        60000.min(
-                            broker_runtime_inner
-                                .broker_config
-                                .register_name_server_period,
-                        ),
+        self.runtime_group.spawn_scheduled(async move {
+            let period = Duration::from_millis(
+                10000.max(
+                    60000.min(
+                        broker_runtime_inner
+                            .broker_config
+                            .register_name_server_period,
                     ),
-                );
-                let initial_delay = Duration::from_secs(10);
-                tokio::time::sleep(initial_delay).await;
-                loop {
-                    let start_time = broker_runtime_inner
-                        .should_start_time
-                        .load(Ordering::Relaxed);
-                    if get_current_millis() < start_time {
-                        info!("Register to namesrv after {}", start_time);
-                        continue;
-                    }
-                    if broker_runtime_inner.is_isolated.load(Ordering::Relaxed) {
-                        info!("Skip register for broker is isolated");
-                        continue;
-                    }
-                    // record current execution time
-                    let current_execution_time = tokio::time::Instant::now();
+                ),
+            );
+            let initial_delay = Duration::from_secs(10);
+            tokio::time::sleep(initial_delay).await;
+            loop {
+                let start_time = broker_runtime_inner
+                    .should_start_time
+                    .load(Ordering::Relaxed);
+                // record current execution time
+                let current_execution_time = tokio::time::Instant::now();
+                if broker_runtime_inner.shutdown.load(Ordering::Acquire) {
+                    break;
+                }
+                if get_current_millis() < start_time {
+                    info!("Register to namesrv after {}", start_time);
+                } else if broker_runtime_inner.is_isolated.load(Ordering::Relaxed) {
+                    info!("Skip register for broker is isolated");
+                } else {
                     // execute task
                     let this = broker_runtime_inner.clone();
                     broker_runtime_inner
@@ -929,69 +1114,40 @@ impl BrokerRuntime {
                             broker_runtime_inner.broker_config.force_register,
                         )
                         .await;
-                    // Calculate the time of the next execution
-                    let next_execution_time = current_execution_time + period;
-
-                    // Wait until the next execution
-                    let delay =
-                        next_execution_time.saturating_duration_since(tokio::time::Instant::now());
-                    tokio::time::sleep(delay).await;
                 }
-            });
+                // Calculate the time of the next execution
+                let next_execution_time = current_execution_time + period;
 
-        if self.inner.broker_config.enable_slave_acting_master {
-            self.schedule_send_heartbeat();
-            let broker_runtime_inner = self.inner.clone();
-            self.broker_runtime
-                .as_ref()
-                .unwrap()
-                .get_handle()
-                .spawn(async move {
-                    let period = Duration::from_secs(1);
-                    let initial_delay = Duration::from_millis(
-                        broker_runtime_inner
-                            .broker_config
-                            .sync_broker_member_group_period,
-                    );
-                    tokio::time::sleep(initial_delay).await;
-                    loop {
-                        // record current execution time
-                        let current_execution_time = tokio::time::Instant::now();
-                        // execute task
-                        broker_runtime_inner.sync_broker_member_group();
-                        // Calculate the time of the next execution
-                        let next_execution_time = current_execution_time + period;
-
-                        // Wait until the next execution
-                        let delay = next_execution_time
-                            .saturating_duration_since(tokio::time::Instant::now());
-                        tokio::time::sleep(delay).await;
-                    }
-                });
-        }
+                // Wait until the next execution
+                let delay =
+                    next_execution_time.saturating_duration_since(tokio::time::Instant::now());
+                tokio::time::sleep(delay).await;
+            }
+        });
 
-        if self.inner.broker_config.enable_controller_mode {
+        if self.inner.broker_config.enable_slave_acting_master {
             self.schedule_send_heartbeat();
         }
 
-        if self.inner.broker_config.skip_pre_online {
-            self.start_service_without_condition().await;
-        }
-
-        let broker_out_api_inner = self.inner.clone();
-        self.broker_runtime
-            .as_ref()
-            .unwrap()
-            .get_handle()
-            .spawn(async move {
-                let period = Duration::from_secs(5);
-                let initial_delay = Duration::from_secs(10);
+        // a controller elects the masters in controller mode, syncing the members tells the
+        // producers about it
+        if self.inner.broker_config.enable_slave_acting_master
+            || self.inner.broker_config.enable_controller_mode
+        {
+            let mut broker_runtime_inner = self.inner.clone();
+            self.runtime_group.spawn_scheduled(async move {
+                let period = Duration::from_secs(1);
+                let initial_delay = Duration::from_millis(
+                    broker_runtime_inner
+                        .broker_config
+                        .sync_broker_member_group_period,
+                );
                 tokio::time::sleep(initial_delay).await;
                 loop {
                     // record current execution time
                     let current_execution_time = tokio::time::Instant::now();
                     // execute task
-                    broker_out_api_inner.broker_outer_api.refresh_metadata();
+                    broker_runtime_inner.sync_broker_member_group().await;
                     // Calculate the time of the next execution
                     let next_execution_time = current_execution_time + period;
 
@@ -1001,6 +1157,35 @@ impl BrokerRuntime {
                     tokio::time::sleep(delay).await;
                 }
             });
+        }
+
+        if self.inner.broker_config.enable_controller_mode {
+            self.schedule_send_heartbeat();
+        }
+
+        if self.inner.broker_config.skip_pre_online {
+            self.start_service_without_condition().await;
+        }
+
+        let broker_out_api_inner = self.inner.clone();
+        self.runtime_group.spawn_scheduled(async move {
+            let period = Duration::from_secs(5);
+            let initial_delay = Duration::from_secs(10);
+            tokio::time::sleep(initial_delay).await;
+            loop {
+                // record current execution time
+                let current_execution_time = tokio::time::Instant::now();
+                // execute task
+                broker_out_api_inner.broker_outer_api.refresh_metadata();
+                // Calculate the time of the next execution
+                let next_execution_time = current_execution_time + period;
+
+                // Wait until the next execution
+                let delay =
+                    next_execution_time.saturating_duration_since(tokio::time::Instant::now());
+                tokio::time::sleep(delay).await;
+            }
+        });
         info!(
             "Rocketmq Broker({} ----Rust) start success",
             self.inner.broker_config.broker_identity.broker_name
@@ -1061,9 +1246,9 @@ impl BrokerRuntime {
             .broker_cluster_name
             .clone();
         let broker_name = self.inner.broker_config.broker_identity.broker_name.clone();
-        let broker_addr = CheetahString::from_string(format!(
-            "{}:{}",
-            self.inner.broker_config.broker_ip1, self.inner.server_config.listen_port
+        let broker_addr = CheetahString::from_string(NetworkUtil::format_address(
+            self.inner.broker_config.broker_ip1.as_str(),
+            self.inner.server_config.listen_port,
         ));
         let broker_id = self.inner.broker_config.broker_identity.broker_id;
         //  let weak = Arc::downgrade(&self.inner.broker_outer_api);
@@ -1100,10 +1285,7 @@ impl<MS: MessageStore> BrokerRuntimeInner<MS> {
         }
         self.broker_outer_api
             .register_single_topic_all(
-                self.broker_config
-                    .broker_identity
-                    .broker_cluster_name
-                    .clone(),
+                self.broker_config.broker_identity.broker_name.clone(),
                 topic_config,
                 3000,
             )
@@ -1162,7 +1344,25 @@ impl<MS: MessageStore> BrokerRuntimeInner<MS> {
 
     async fn do_register_broker_all(
         this: ArcMut<BrokerRuntimeInner<MS>>,
-        _check_order_config: bool,
+        check_order_config: bool,
+        oneway: bool,
+        topic_config_wrapper: TopicConfigAndMappingSerializeWrapper,
+    ) {
+        let name_server_address_list = this.broker_outer_api.get_available_name_srv_list();
+        BrokerRuntimeInner::<MS>::do_register_broker_to(
+            this,
+            name_server_address_list,
+            check_order_config,
+            oneway,
+            topic_config_wrapper,
+        )
+        .await;
+    }
+
+    async fn do_register_broker_to(
+        this: ArcMut<BrokerRuntimeInner<MS>>,
+        name_server_address_list: Vec<CheetahString>,
+        check_order_config: bool,
         oneway: bool,
         topic_config_wrapper: TopicConfigAndMappingSerializeWrapper,
     ) {
@@ -1180,14 +1380,18 @@ impl<MS: MessageStore> BrokerRuntimeInner<MS> {
             .broker_cluster_name
             .clone();
         let broker_name = this.broker_config.broker_identity.broker_name.clone();
-        let broker_addr = CheetahString::from_string(format!(
-            "{}:{}",
-            this.broker_config.broker_ip1, this.server_config.listen_port
+        let broker_addr = CheetahString::from_string(NetworkUtil::format_address(
+            this.broker_config.broker_ip1.as_str(),
+            this.server_config.listen_port,
         ));
         let broker_id = this.broker_config.broker_identity.broker_id;
+        // there is nothing to register with when no name server is known
+        let no_name_server = name_server_address_list.is_empty();
         //let weak = Arc::downgrade(&self.broker_out_api);
-        this.broker_outer_api
-            .register_broker_all(
+        let register_broker_result_list = this
+            .broker_outer_api
+            .register_broker_to(
+                name_server_address_list,
                 cluster_name,
                 broker_addr.clone(),
                 broker_name,
@@ -1202,10 +1406,13 @@ impl<MS: MessageStore> BrokerRuntimeInner<MS> {
                 this.broker_config
                     .enable_slave_acting_master
                     .then_some(this.broker_config.broker_not_active_timeout_millis),
-                Default::default(), //optimize
                 this.clone(),
             )
             .await;
+        if no_name_server || !register_broker_result_list.is_empty() {
+            this.readiness.mark_registered();
+        }
+        this.handle_register_broker_result(register_broker_result_list, check_order_config);
     }
 }
 
@@ -1319,6 +1526,11 @@ pub(crate) struct BrokerRuntimeInner<MS> {
     pop_inflight_message_counter: PopInflightMessageCounter,
     replicas_manager: Option<ReplicasManager>,
     broker_fast_failure: BrokerFastFailure,
+    flow_controller: FlowController,
+    send_dedup_table: Arc<SendDedupTable>,
+    cpu_load_sampler: Arc<CpuLoadSampler>,
+    request_auditor: Arc<RequestAuditor>,
+    readiness: BrokerReadiness,
     cold_data_pull_request_hold_service: Option<ColdDataPullRequestHoldService>,
     cold_data_cg_ctr_service: Option<ColdDataCgCtrService>,
     is_schedule_service_start: Arc<AtomicBool>,
@@ -1703,6 +1915,11 @@ impl<MS: MessageStore> BrokerRuntimeInner<MS> {
         &self.is_isolated
     }
 
+    #[inline]
+    pub fn is_shutdown(&self) -> bool {
+        self.shutdown.load(Ordering::Acquire)
+    }
+
     #[inline]
     pub fn pull_request_hold_service(&self) -> &Option<PullRequestHoldService<MS>> {
         &self.pull_request_hold_service
@@ -1755,6 +1972,36 @@ impl<MS: MessageStore> BrokerRuntimeInner<MS> {
         &self.pop_inflight_message_counter
     }
 
+    #[inline]
+    pub fn broker_fast_failure(&self) -> &BrokerFastFailure {
+        &self.broker_fast_failure
+    }
+
+    #[inline]
+    pub fn flow_controller(&self) -> &FlowController {
+        &self.flow_controller
+    }
+
+    #[inline]
+    pub fn send_dedup_table(&self) -> &Arc<SendDedupTable> {
+        &self.send_dedup_table
+    }
+
+    #[inline]
+    pub(crate) fn cpu_load_sampler(&self) -> &Arc<CpuLoadSampler> {
+        &self.cpu_load_sampler
+    }
+
+    #[inline]
+    pub fn request_auditor(&self) -> &RequestAuditor {
+        &self.request_auditor
+    }
+
+    #[inline]
+    pub fn readiness(&self) -> &BrokerReadiness {
+        &self.readiness
+    }
+
     #[inline]
     pub fn set_store_host(&mut self, store_host: SocketAddr) {
         self.store_host = store_host;
@@ -2046,25 +2293,7 @@ impl<MS: MessageStore> BrokerRuntimeInner<MS> {
                 topic_queue_mapping_info_map,
             );
 
-        if self.broker_config.enable_split_registration
-            || force_register
-            || need_register(
-                self.broker_config
-                    .broker_identity
-                    .broker_cluster_name
-                    .clone()
-                    .as_str(),
-                self.broker_config.broker_ip1.clone().as_str(),
-                self.broker_config
-                    .broker_identity
-                    .broker_name
-                    .clone()
-                    .as_str(),
-                self.broker_config.broker_identity.broker_id,
-                self.broker_config.register_broker_timeout_mills,
-                self.broker_config.is_in_broker_container,
-            )
-        {
+        if self.broker_config.enable_split_registration || force_register {
             BrokerRuntimeInner::<MS>::do_register_broker_all(
                 this,
                 check_order_config,
@@ -2072,6 +2301,64 @@ impl<MS: MessageStore> BrokerRuntimeInner<MS> {
                 topic_config_wrapper,
             )
             .await;
+            return;
+        }
+        // Only the name servers that hold an outdated data version get the topic config again,
+        // the others already refreshed the broker while answering the data version query.
+        let name_server_address_list = self.need_register(&topic_config_wrapper).await;
+        if name_server_address_list.is_empty() {
+            self.readiness.mark_registered();
+        } else {
+            BrokerRuntimeInner::<MS>::do_register_broker_to(
+                this,
+                name_server_address_list,
+                check_order_config,
+                oneway,
+                topic_config_wrapper,
+            )
+            .await;
+        }
+    }
+
+    async fn need_register(
+        &self,
+        topic_config_wrapper: &TopicConfigAndMappingSerializeWrapper,
+    ) -> Vec<CheetahString> {
+        let broker_addr = CheetahString::from_string(NetworkUtil::format_address(
+            self.broker_config.broker_ip1.as_str(),
+            self.server_config.listen_port,
+        ));
+        self.broker_outer_api
+            .need_register(
+                &self.broker_config.broker_identity.broker_cluster_name,
+                &broker_addr,
+                &self.broker_config.broker_identity.broker_name,
+                self.broker_config.broker_identity.broker_id,
+                topic_config_wrapper,
+                self.broker_config.register_broker_timeout_mills as u64,
+            )
+            .await
+    }
+
+    fn handle_register_broker_result(
+        &self,
+        register_broker_result_list: Vec<RegisterBrokerResult>,
+        check_order_config: bool,
+    ) {
+        let Some(register_broker_result) = register_broker_result_list.into_iter().next() else {
+            return;
+        };
+        if self.update_master_haserver_addr_periodically
+            && !register_broker_result.ha_server_addr.is_empty()
+        {
+            if let Some(message_store) = self.message_store.as_ref() {
+                message_store.update_ha_master_address(&register_broker_result.ha_server_addr);
+                message_store.update_master_address(&register_broker_result.master_addr);
+            }
+        }
+        if check_order_config {
+            self.topic_config_manager()
+                .update_order_topic_config(&register_broker_result.kv_table);
         }
     }
 
@@ -2087,9 +2374,9 @@ impl<MS: MessageStore> BrokerRuntimeInner<MS> {
             .broker_cluster_name
             .clone();
         let broker_name = this.broker_config.broker_identity.broker_name.clone();
-        let broker_addr = CheetahString::from_string(format!(
-            "{}:{}",
-            this.broker_config.broker_ip1, this.server_config.listen_port
+        let broker_addr = CheetahString::from_string(NetworkUtil::format_address(
+            this.broker_config.broker_ip1.as_str(),
+            this.server_config.listen_port,
         ));
         let broker_id = this.broker_config.broker_identity.broker_id;
         //  let weak = Arc::downgrade(&self.inner.broker_outer_api);
@@ -2117,14 +2404,124 @@ impl<MS: MessageStore> BrokerRuntimeInner<MS> {
     pub fn get_broker_addr(&self) -> &CheetahString {
         &self.broker_addr
     }
-    pub fn sync_broker_member_group(&self) {
-        warn!("sync_broker_member_group not implemented");
+    /// Tells the connected producers that the min broker id of this broker group changed, so they
+    /// refresh the route of the group right away instead of on the next periodic update.
+    /// `offline_broker_addr` is the address that stopped serving under its old broker id.
+    ///
+    /// The members of the group are synced from the name server first, the local view only
+    /// knows about this broker.
+    pub(crate) async fn notify_min_broker_id_changed(
+        &mut self,
+        offline_broker_addr: Option<CheetahString>,
+    ) {
+        self.refresh_broker_member_group().await;
+        let min_broker = self.broker_member_group.minimum_broker();
+        let request_header = NotifyMinBrokerIdChangeRequestHeader::new(
+            min_broker.map(|(broker_id, _)| broker_id),
+            Some(self.broker_member_group.broker_name.clone()),
+            min_broker.map(|(_, broker_addr)| broker_addr.clone()),
+            offline_broker_addr,
+            None,
+        );
+        for mut channel in self.producer_manager.get_all_channels() {
+            if let Err(e) = Broker2Client
+                .notify_min_broker_id_changed(&mut channel, request_header.clone())
+                .await
+            {
+                warn!(
+                    "notify min broker id change to {} failed: {}",
+                    channel.remote_address(),
+                    e
+                );
+            }
+        }
+    }
+
+    /// Syncs the members of this broker group from the name server. When the min broker of the
+    /// group changed meanwhile, e.g. a controller elected another master, the producers are told.
+    pub async fn sync_broker_member_group(&mut self) {
+        let last_min_broker = self
+            .broker_member_group
+            .minimum_broker()
+            .map(|(broker_id, broker_addr)| (broker_id, broker_addr.clone()));
+        if !self.refresh_broker_member_group().await || self.is_isolated.load(Ordering::Acquire) {
+            return;
+        }
+        let min_broker = self
+            .broker_member_group
+            .minimum_broker()
+            .map(|(broker_id, broker_addr)| (broker_id, broker_addr.clone()));
+        if min_broker == last_min_broker {
+            return;
+        }
+        info!(
+            "min broker of {} changed from {:?} to {:?}",
+            self.broker_member_group.broker_name, last_min_broker, min_broker
+        );
+        let offline_broker_addr =
+            last_min_broker
+                .map(|(_, broker_addr)| broker_addr)
+                .filter(|broker_addr| {
+                    min_broker
+                        .as_ref()
+                        .map_or(true, |(_, min_broker_addr)| min_broker_addr != broker_addr)
+                });
+        self.notify_min_broker_id_changed(offline_broker_addr).await;
+    }
+
+    /// Replaces the broker member group with the one registered on the name server. Returns
+    /// false and keeps the group when the name server has none.
+    async fn refresh_broker_member_group(&mut self) -> bool {
+        let broker_member_group = self
+            .broker_outer_api
+            .sync_broker_member_group(
+                &self.broker_config.broker_identity.broker_cluster_name,
+                &self.broker_config.broker_identity.broker_name,
+                self.broker_config.compatible_with_old_name_srv,
+            )
+            .await;
+        match broker_member_group {
+            Ok(Some(broker_member_group)) if !broker_member_group.broker_addrs.is_empty() => {
+                let mut alive_broker_num = broker_member_group.broker_addrs.len();
+                if !broker_member_group
+                    .broker_addrs
+                    .contains_key(&self.broker_config.broker_identity.broker_id)
+                {
+                    alive_broker_num += 1;
+                }
+                if let Some(message_store) = &self.message_store {
+                    message_store.set_alive_replica_num_in_group(alive_broker_num as i32);
+                }
+                self.broker_member_group = broker_member_group;
+                true
+            }
+            Ok(_) => {
+                warn!(
+                    "Couldn't find any broker member from namesrv in {}/{}",
+                    self.broker_config.broker_identity.broker_cluster_name,
+                    self.broker_config.broker_identity.broker_name
+                );
+                false
+            }
+            Err(e) => {
+                error!("syncBrokerMemberGroup from namesrv failed, {}", e);
+                false
+            }
+        }
+    }
+
+    pub fn pop_message_processor(&self) -> Option<&ArcMut<PopMessageProcessor<MS>>> {
+        self.pop_message_processor.as_ref()
     }
 
     pub fn pop_message_processor_unchecked(&self) -> &ArcMut<PopMessageProcessor<MS>> {
         unsafe { self.pop_message_processor.as_ref().unwrap_unchecked() }
     }
 
+    pub fn ack_message_processor(&self) -> Option<&ArcMut<AckMessageProcessor<MS>>> {
+        self.ack_message_processor.as_ref()
+    }
+
     pub fn ack_message_processor_unchecked(&self) -> &ArcMut<AckMessageProcessor<MS>> {
         unsafe { self.ack_message_processor.as_ref().unwrap_unchecked() }
     }
@@ -2191,8 +2588,28 @@ impl<MS: MessageStore> BrokerRuntimeInner<MS> {
         }
     }
 
-    pub fn start_service(&mut self, _min_broker_id: u64, _min_broker_addr: CheetahString) {
-        unimplemented!("BrokerRuntimeInner#start_service");
+    pub(crate) async fn start_service(
+        this: ArcMut<Self>,
+        min_broker_id: u64,
+        min_broker_addr: CheetahString,
+    ) {
+        info!(
+            "{} start service, min broker id is {}, min broker addr: {}",
+            this.broker_config.broker_identity.get_canonical_name(),
+            min_broker_id,
+            min_broker_addr
+        );
+        let is_min_broker = this.broker_config.broker_identity.broker_id == min_broker_id;
+        this.mut_from_ref()
+            .change_special_service_status(is_min_broker);
+        this.register_broker_all_inner(
+            this.clone(),
+            true,
+            false,
+            this.broker_config.force_register,
+        )
+        .await;
+        this.is_isolated.store(false, Ordering::Release);
     }
 
     fn on_min_broker_change(
@@ -2206,13 +2623,67 @@ impl<MS: MessageStore> BrokerRuntimeInner<MS> {
     }
 }
 
-fn need_register(
-    _cluster_name: &str,
-    _broker_addr: &str,
-    _broker_name: &str,
-    _broker_id: u64,
-    _register_timeout_mills: i32,
-    _in_broker_container: bool,
-) -> bool {
-    unimplemented!()
+/// Returns the address the http admin server forwards its requests to: the remoting server of
+/// the broker, reached through loopback when it listens on every interface.
+fn http_admin_forward_address(server_config: &ServerConfig) -> RocketMQResult<SocketAddr> {
+    let mut addr = NetworkUtil::resolve_host_port(
+        server_config.bind_address.as_str(),
+        server_config.listen_port,
+    )?;
+    match addr.ip() {
+        IpAddr::V4(ip) if ip.is_unspecified() => addr.set_ip(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+        IpAddr::V6(ip) if ip.is_unspecified() => addr.set_ip(IpAddr::V6(Ipv6Addr::LOCALHOST)),
+        _ => {}
+    }
+    Ok(addr)
+}
+
+/// Returns the hook adding the inner credentials of the broker to the requests it sends, `None`
+/// when no credentials are configured.
+fn inner_client_rpc_hook(broker_config: &BrokerConfig) -> Option<Arc<Box<dyn RPCHook>>> {
+    let credentials = match (
+        &broker_config.inner_auth_token,
+        &broker_config.inner_access_key,
+        &broker_config.inner_secret_key,
+    ) {
+        (Some(auth_token), _, _) => SessionCredentials::bearer_token(auth_token.clone()),
+        (None, Some(access_key), Some(secret_key)) => {
+            SessionCredentials::access_key(access_key.clone(), secret_key.clone())
+        }
+        _ => return None,
+    };
+    Some(Arc::new(Box::new(AclClientRpcHook::new(credentials))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server_config(bind_address: &str) -> ServerConfig {
+        ServerConfig {
+            bind_address: bind_address.to_string(),
+            listen_port: 10911,
+            ..ServerConfig::default()
+        }
+    }
+
+    #[test]
+    fn http_admin_forwards_to_the_bind_address() {
+        assert_eq!(
+            http_admin_forward_address(&server_config("192.168.0.7")).unwrap(),
+            "192.168.0.7:10911".parse::<SocketAddr>().unwrap()
+        );
+        assert_eq!(
+            http_admin_forward_address(&server_config("fd00::7")).unwrap(),
+            "[fd00::7]:10911".parse::<SocketAddr>().unwrap()
+        );
+        assert_eq!(
+            http_admin_forward_address(&server_config("0.0.0.0")).unwrap(),
+            "127.0.0.1:10911".parse::<SocketAddr>().unwrap()
+        );
+        assert_eq!(
+            http_admin_forward_address(&server_config("::")).unwrap(),
+            "[::1]:10911".parse::<SocketAddr>().unwrap()
+        );
+    }
 }
@@ -0,0 +1,211 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::future::Future;
+
+use parking_lot::Mutex;
+use rocketmq_common::common::broker::broker_config::BrokerConfig;
+use rocketmq_runtime::RocketMQRuntime;
+use tokio::runtime::Handle;
+use tokio::task::AbortHandle;
+use tokio::task::JoinHandle;
+
+/// Runtimes the broker's work is split across, so fsync-heavy store services cannot add latency
+/// to request handling. Work without a dedicated runtime stays on the runtime that started the
+/// broker, scheduled tasks always run on their own runtime.
+///
+/// The brokers of a container run on the runtimes of the container, see [`Self::share`].
+pub(crate) struct BrokerRuntimeGroup {
+    scheduled: Handle,
+    network: Option<Handle>,
+    processor: Option<Handle>,
+    store: Option<Handle>,
+    flush: Option<Handle>,
+    // runtimes created by this group and shut down with it, a shared group owns none
+    owned: Vec<RocketMQRuntime>,
+    // scheduled tasks spawned through this group, aborted on shutdown as a shared runtime
+    // outlives the broker
+    scheduled_tasks: Mutex<Vec<AbortHandle>>,
+}
+
+impl BrokerRuntimeGroup {
+    pub(crate) fn new(broker_config: &BrokerConfig) -> Self {
+        Self::with_threads(
+            broker_config.network_runtime_threads,
+            broker_config.processor_runtime_threads,
+            broker_config.store_runtime_threads,
+            broker_config.flush_on_dedicated_thread,
+        )
+    }
+
+    /// Creates the runtimes, a thread count of 0 keeps that work on the runtime that started
+    /// the broker.
+    pub(crate) fn with_threads(
+        network_threads: usize,
+        processor_threads: usize,
+        store_threads: usize,
+        flush_on_dedicated_thread: bool,
+    ) -> Self {
+        let scheduled_runtime = RocketMQRuntime::new_multi(10, "broker-thread");
+        let scheduled = scheduled_runtime.get_handle().clone();
+        let mut owned = vec![scheduled_runtime];
+        let mut dedicated = |threads: usize, name: &str| {
+            (threads > 0).then(|| {
+                let runtime = RocketMQRuntime::new_multi(threads, name);
+                let handle = runtime.get_handle().clone();
+                owned.push(runtime);
+                handle
+            })
+        };
+        let network = dedicated(network_threads, "broker-network");
+        let processor = dedicated(processor_threads, "broker-processor");
+        let store = dedicated(store_threads, "broker-store");
+        // A single worker is a dedicated OS thread nothing else gets scheduled on.
+        let flush = dedicated(usize::from(flush_on_dedicated_thread), "broker-flush");
+        BrokerRuntimeGroup {
+            scheduled,
+            network,
+            processor,
+            store,
+            flush,
+            owned,
+            scheduled_tasks: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns a group running on the same runtimes, which stay alive as long as this group.
+    pub(crate) fn share(&self) -> Self {
+        BrokerRuntimeGroup {
+            scheduled: self.scheduled.clone(),
+            network: self.network.clone(),
+            processor: self.processor.clone(),
+            store: self.store.clone(),
+            flush: self.flush.clone(),
+            owned: Vec::new(),
+            scheduled_tasks: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Spawns a periodic task of the broker, it is aborted when the group shuts down.
+    pub(crate) fn spawn_scheduled<F>(&self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let handle = self.scheduled.spawn(future);
+        self.scheduled_tasks.lock().push(handle.abort_handle());
+        handle
+    }
+
+    /// Spawns an acceptor loop on the network runtime.
+    pub(crate) fn spawn_network<F>(&self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        match self.network {
+            Some(ref runtime) => runtime.spawn(future),
+            None => tokio::spawn(future),
+        }
+    }
+
+    pub(crate) fn processor_handle(&self) -> Option<Handle> {
+        self.processor.clone()
+    }
+
+    pub(crate) fn store_handle(&self) -> Option<Handle> {
+        self.store.clone()
+    }
+
+    pub(crate) fn flush_handle(&self) -> Option<Handle> {
+        self.flush.clone()
+    }
+
+    pub(crate) fn shutdown(&mut self) {
+        for task in self.scheduled_tasks.lock().drain(..) {
+            task.abort();
+        }
+        // Background shutdown, a runtime must not be dropped from within an async context.
+        for runtime in self.owned.drain(..) {
+            runtime.shutdown();
+        }
+    }
+}
+
+impl Drop for BrokerRuntimeGroup {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn runs_work_on_dedicated_runtimes_when_configured() {
+        let broker_config = BrokerConfig {
+            network_runtime_threads: 1,
+            flush_on_dedicated_thread: true,
+            ..Default::default()
+        };
+        let mut runtimes = BrokerRuntimeGroup::new(&broker_config);
+        assert!(runtimes.processor_handle().is_none());
+        assert!(runtimes.store_handle().is_none());
+
+        let thread_name = runtimes
+            .spawn_network(async { std::thread::current().name().map(str::to_string) })
+            .await
+            .unwrap();
+        assert_eq!(thread_name.as_deref(), Some("broker-network"));
+
+        let flush_thread = runtimes
+            .flush_handle()
+            .unwrap()
+            .spawn(async { std::thread::current().name().map(str::to_string) })
+            .await
+            .unwrap();
+        assert_eq!(flush_thread.as_deref(), Some("broker-flush"));
+        runtimes.shutdown();
+    }
+
+    #[test]
+    fn creates_only_the_scheduled_runtime_by_default() {
+        let runtimes = BrokerRuntimeGroup::new(&BrokerConfig::default());
+        assert_eq!(runtimes.owned.len(), 1);
+        assert!(runtimes.network.is_none());
+        assert!(runtimes.processor_handle().is_none());
+        assert!(runtimes.store_handle().is_none());
+        assert!(runtimes.flush_handle().is_none());
+    }
+
+    #[tokio::test]
+    async fn shared_group_aborts_its_scheduled_tasks_but_keeps_the_runtimes() {
+        let mut runtimes = BrokerRuntimeGroup::with_threads(1, 0, 0, false);
+        let mut shared = runtimes.share();
+        let task = shared.spawn_scheduled(std::future::pending::<()>());
+        shared.shutdown();
+        assert!(task.await.unwrap_err().is_cancelled());
+
+        let thread_name = runtimes
+            .spawn_network(async { std::thread::current().name().map(str::to_string) })
+            .await
+            .unwrap();
+        assert_eq!(thread_name.as_deref(), Some("broker-network"));
+        runtimes.shutdown();
+    }
+}
@@ -0,0 +1,168 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Rates at which single clients produce or consume, so that the misbehaving instance of a
+//! group can be told apart from the others.
+
+use cheetah_string::CheetahString;
+use dashmap::DashMap;
+use rocketmq_common::TimeUtils::get_current_millis;
+
+const RATE_WINDOW_MILLIS: u64 = 60_000;
+
+/// What a client produced or consumed lately.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ClientRate {
+    /// Messages in the last complete window of a minute.
+    pub msgs_per_minute: u64,
+    /// Messages since the client was first seen.
+    pub total_msgs: u64,
+    /// Time of the last message, in milliseconds.
+    pub last_update_timestamp: u64,
+}
+
+struct RateCounter {
+    rate: ClientRate,
+    window_start: u64,
+    window_msgs: u64,
+}
+
+impl RateCounter {
+    fn roll(&mut self, now: u64) {
+        let elapsed = now.saturating_sub(self.window_start);
+        if elapsed < RATE_WINDOW_MILLIS {
+            return;
+        }
+        // idle windows after the last message count as windows without messages
+        self.rate.msgs_per_minute = self.window_msgs * RATE_WINDOW_MILLIS / elapsed;
+        self.window_start = now;
+        self.window_msgs = 0;
+    }
+}
+
+/// Message rates keyed by group and client id, a client of several groups has a rate for each.
+/// Recording only locks the shard of the client, so clients do not wait for each other.
+#[derive(Default)]
+pub struct ClientRateTable {
+    counters: DashMap<(CheetahString, CheetahString), RateCounter>,
+}
+
+impl ClientRateTable {
+    /// Counts `msgs` messages produced or consumed by `client_id` of `group` now.
+    pub fn record(&self, group: &CheetahString, client_id: &CheetahString, msgs: u64) {
+        self.record_at(group, client_id, msgs, get_current_millis());
+    }
+
+    fn record_at(&self, group: &CheetahString, client_id: &CheetahString, msgs: u64, now: u64) {
+        let mut counter = self
+            .counters
+            .entry((group.clone(), client_id.clone()))
+            .or_insert_with(|| RateCounter {
+                rate: ClientRate::default(),
+                window_start: now,
+                window_msgs: 0,
+            });
+        counter.roll(now);
+        counter.window_msgs += msgs;
+        counter.rate.total_msgs += msgs;
+        counter.rate.last_update_timestamp = now;
+    }
+
+    /// Returns the rate of `client_id` of `group`, `None` when it neither produced nor consumed
+    /// lately.
+    pub fn get(&self, group: &str, client_id: &str) -> Option<ClientRate> {
+        self.get_at(group, client_id, get_current_millis())
+    }
+
+    fn get_at(&self, group: &str, client_id: &str, now: u64) -> Option<ClientRate> {
+        let key = (
+            CheetahString::from_slice(group),
+            CheetahString::from_slice(client_id),
+        );
+        let mut counter = self.counters.get_mut(&key)?;
+        counter.roll(now);
+        Some(counter.rate)
+    }
+
+    /// Forgets the clients without messages for `idle_timeout` milliseconds.
+    pub fn remove_idle(&self, idle_timeout: u64) {
+        let now = get_current_millis();
+        self.counters.retain(|_, counter| {
+            now.saturating_sub(counter.rate.last_update_timestamp) <= idle_timeout
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_covers_the_last_complete_minute() {
+        let table = ClientRateTable::default();
+        let group = CheetahString::from_static_str("group");
+        let client_id = CheetahString::from_static_str("client-a");
+        table.record_at(&group, &client_id, 30, 1_000);
+        table.record_at(&group, &client_id, 30, 50_000);
+        assert_eq!(
+            table
+                .get_at(&group, &client_id, 55_000)
+                .unwrap()
+                .msgs_per_minute,
+            0
+        );
+
+        let rate = table.get_at(&group, &client_id, 61_000).unwrap();
+        assert_eq!(rate.msgs_per_minute, 60);
+        assert_eq!(rate.total_msgs, 60);
+        assert_eq!(rate.last_update_timestamp, 50_000);
+
+        // a minute of traffic followed by a minute of silence averages out
+        table.record_at(&group, &client_id, 60, 62_000);
+        assert_eq!(
+            table
+                .get_at(&group, &client_id, 181_000)
+                .unwrap()
+                .msgs_per_minute,
+            30
+        );
+        assert!(table.get_at(&group, "client-b", 181_000).is_none());
+    }
+
+    #[test]
+    fn a_client_has_a_rate_per_group() {
+        let table = ClientRateTable::default();
+        let client_id = CheetahString::from_static_str("client-a");
+        table.record_at(&"group-a".into(), &client_id, 10, 1_000);
+        table.record_at(&"group-b".into(), &client_id, 20, 1_000);
+        assert_eq!(
+            table
+                .get_at("group-a", &client_id, 2_000)
+                .unwrap()
+                .total_msgs,
+            10
+        );
+        assert_eq!(
+            table
+                .get_at("group-b", &client_id, 2_000)
+                .unwrap()
+                .total_msgs,
+            20
+        );
+        assert!(table.get_at("group-c", &client_id, 2_000).is_none());
+    }
+}
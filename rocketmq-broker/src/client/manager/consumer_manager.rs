@@ -35,6 +35,8 @@ use tracing::info;
 use tracing::warn;
 
 use crate::client::client_channel_info::ClientChannelInfo;
+use crate::client::client_rate_table::ClientRate;
+use crate::client::client_rate_table::ClientRateTable;
 use crate::client::consumer_group_event::ConsumerGroupEvent;
 use crate::client::consumer_group_info::ConsumerGroupInfo;
 use crate::client::consumer_ids_change_listener::ConsumerIdsChangeListener;
@@ -47,6 +49,7 @@ pub struct ConsumerManager {
     broker_stats_manager: Option<Weak<BrokerStatsManager>>,
     channel_expired_timeout: u64,
     subscription_expired_timeout: u64,
+    consume_rates: ClientRateTable,
 }
 
 impl ConsumerManager {
@@ -64,6 +67,7 @@ impl ConsumerManager {
             broker_stats_manager: None,
             channel_expired_timeout: expired_timeout,
             subscription_expired_timeout: expired_timeout,
+            consume_rates: ClientRateTable::default(),
         }
     }
 
@@ -81,6 +85,7 @@ impl ConsumerManager {
             broker_stats_manager: None,
             channel_expired_timeout: broker_config.channel_expired_timeout,
             subscription_expired_timeout: broker_config.subscription_expired_timeout,
+            consume_rates: ClientRateTable::default(),
         }
     }
 }
@@ -114,6 +119,20 @@ impl ConsumerManager {
         None
     }
 
+    /// Counts `msgs` messages pulled or popped over `channel` by a consumer of `group`.
+    /// Messages of channels not registered by a heartbeat are not counted.
+    pub fn record_consumed(&self, group: &CheetahString, channel: &Channel, msgs: u64) {
+        if let Some(client_channel_info) = self.find_channel_by_channel(group, channel) {
+            self.consume_rates
+                .record(group, client_channel_info.client_id(), msgs);
+        }
+    }
+
+    /// Returns the rate `client_id` consumed the messages of `group` at lately.
+    pub fn consume_rate(&self, group: &str, client_id: &str) -> Option<ClientRate> {
+        self.consume_rates.get(group, client_id)
+    }
+
     pub fn find_subscription_data(
         &self,
         group: &CheetahString,
@@ -492,6 +511,7 @@ impl ConsumerManager {
         }
 
         self.remove_expire_consumer_group_info();
+        self.consume_rates.remove_idle(self.channel_expired_timeout);
     }
 
     pub fn do_channel_close_event(&self, _remote_addr: &str, channel: &Channel) -> bool {
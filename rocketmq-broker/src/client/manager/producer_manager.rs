@@ -30,6 +30,8 @@ use tracing::info;
 use tracing::warn;
 
 use crate::client::client_channel_info::ClientChannelInfo;
+use crate::client::client_rate_table::ClientRate;
+use crate::client::client_rate_table::ClientRateTable;
 use crate::client::producer_change_listener::ArcProducerChangeListener;
 use crate::client::producer_group_event::ProducerGroupEvent;
 
@@ -44,6 +46,7 @@ pub struct ProducerManager {
     positive_atomic_counter: Arc<AtomicI32>,
     producer_change_listener_vec: Vec<ArcProducerChangeListener>,
     broker_stats_manager: Option<Arc<BrokerStatsManager>>,
+    produce_rates: ClientRateTable,
 }
 
 impl ProducerManager {
@@ -54,6 +57,7 @@ impl ProducerManager {
             positive_atomic_counter: Arc::new(Default::default()),
             producer_change_listener_vec: vec![],
             broker_stats_manager: None,
+            produce_rates: ClientRateTable::default(),
         }
     }
 
@@ -186,10 +190,43 @@ impl ProducerManager {
         );
     }
 
+    /// Returns the clients registered in `group`.
+    pub fn get_group_channels(&self, group: &str) -> Vec<ClientChannelInfo> {
+        self.group_channel_table
+            .lock()
+            .get(group)
+            .map(|channel_table| channel_table.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Counts `msgs` messages sent over `channel` by a producer of `group`. Messages of
+    /// channels not registered by a heartbeat are not counted.
+    pub fn record_produced(&self, group: &CheetahString, channel: &Channel, msgs: u64) {
+        let client_id = self
+            .group_channel_table
+            .lock()
+            .get(group)
+            .and_then(|channel_table| channel_table.get(channel))
+            .map(|client_channel_info| client_channel_info.client_id().clone());
+        if let Some(client_id) = client_id {
+            self.produce_rates.record(group, &client_id, msgs);
+        }
+    }
+
+    /// Returns the rate `client_id` produced the messages of `group` at lately.
+    pub fn produce_rate(&self, group: &str, client_id: &str) -> Option<ClientRate> {
+        self.produce_rates.get(group, client_id)
+    }
+
     pub fn find_channel(&self, client_id: &str) -> Option<Channel> {
         self.client_channel_table.lock().get(client_id).cloned()
     }
 
+    /// Returns the channels of all registered producer clients.
+    pub fn get_all_channels(&self) -> Vec<Channel> {
+        self.client_channel_table.lock().values().cloned().collect()
+    }
+
     pub fn get_available_channel(&self, group: Option<&CheetahString>) -> Option<Channel> {
         let group = group?;
         let group_channel_table = self.group_channel_table.lock();
@@ -271,6 +308,7 @@ impl ProducerManager {
             );
             self.call_producer_change_listener(ProducerGroupEvent::GroupUnregister, &group, None);
         }
+        self.produce_rates.remove_idle(CHANNEL_EXPIRED_TIMEOUT);
     }
 
     pub fn do_channel_close_event(&self, remote_addr: &str, channel: &Channel) -> bool {
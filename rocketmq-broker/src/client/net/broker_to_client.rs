@@ -20,6 +20,7 @@ use rocketmq_common::MessageDecoder;
 use rocketmq_remoting::code::request_code::RequestCode;
 use rocketmq_remoting::net::channel::Channel;
 use rocketmq_remoting::protocol::header::check_transaction_state_request_header::CheckTransactionStateRequestHeader;
+use rocketmq_remoting::protocol::header::namesrv::brokerid_change_request_header::NotifyMinBrokerIdChangeRequestHeader;
 use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
 
 #[derive(Default, Clone)]
@@ -72,4 +73,22 @@ impl Broker2Client {
             },
         }
     }
+
+    /// Tells the client of `channel` that the min broker id of a broker group changed.
+    pub async fn notify_min_broker_id_changed(
+        &self,
+        channel: &mut Channel,
+        request_header: NotifyMinBrokerIdChangeRequestHeader,
+    ) -> rocketmq_error::RocketMQResult<()> {
+        let request = RemotingCommand::create_request_command(
+            RequestCode::NotifyMinBrokerIdChange,
+            request_header,
+        );
+        match channel.upgrade() {
+            None => Err(rocketmq_error::RocketmqError::ChannelError(
+                "Channel is closed".to_string(),
+            )),
+            Some(channel) => channel.send_one_way(request, 100).await.map(|_| ()),
+        }
+    }
 }
@@ -0,0 +1,292 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use cheetah_string::CheetahString;
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use rocketmq_common::common::broker::broker_config::BrokerConfig;
+
+/// Where a message was stored, answered again to the retried sends of the message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct StoredMessage {
+    pub msg_id: CheetahString,
+    pub queue_offset: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum DedupEntry {
+    /// The first send of the message is storing it.
+    Storing,
+    Stored(StoredMessage),
+}
+
+#[derive(Default)]
+struct RecentKeys {
+    entries: HashMap<CheetahString, DedupEntry>,
+    // unique keys in the order they were reserved, the oldest is evicted first
+    order: VecDeque<CheetahString>,
+}
+
+/// What a send finds when it reserves the unique key of its message.
+pub(crate) enum Reservation {
+    /// The key is new, the send stores the message and completes the reservation.
+    Reserved(DedupReservation),
+    /// Another send of the message is storing it right now.
+    Storing,
+    /// The message was stored before.
+    Stored(StoredMessage),
+}
+
+/// Unique keys of the latest messages stored to every queue of the topics with the
+/// `dedup.enable` attribute.
+///
+/// A producer resends a message when the response of a send times out, even though the broker
+/// may have stored it. Such a retry carries the unique key of the first send and is answered with
+/// the stored message instead of being stored twice, as long as the key is still remembered.
+pub(crate) struct SendDedupTable {
+    keys_per_queue: usize,
+    queues: DashMap<(CheetahString, i32), Mutex<RecentKeys>>,
+}
+
+impl SendDedupTable {
+    pub fn new(broker_config: &BrokerConfig) -> Self {
+        Self {
+            keys_per_queue: broker_config.send_dedup_keys_per_queue,
+            queues: DashMap::new(),
+        }
+    }
+
+    /// Reserves `uniq_key` for a send to the queue unless another send already stored the
+    /// message or is storing it. Checking and reserving happen under one lock, so of concurrent
+    /// sends of a message only one stores it.
+    pub fn reserve(
+        self: &Arc<Self>,
+        topic: &CheetahString,
+        queue_id: i32,
+        uniq_key: CheetahString,
+    ) -> Reservation {
+        if self.keys_per_queue == 0 {
+            return Reservation::Reserved(DedupReservation {
+                table: self.clone(),
+                topic: topic.clone(),
+                queue_id,
+                uniq_key: None,
+            });
+        }
+        let queue = self.queues.entry((topic.clone(), queue_id)).or_default();
+        let mut recent_keys = queue.lock();
+        match recent_keys.entries.get(&uniq_key) {
+            Some(DedupEntry::Storing) => return Reservation::Storing,
+            Some(DedupEntry::Stored(stored_message)) => {
+                return Reservation::Stored(stored_message.clone())
+            }
+            None => {}
+        }
+        recent_keys
+            .entries
+            .insert(uniq_key.clone(), DedupEntry::Storing);
+        recent_keys.order.push_back(uniq_key.clone());
+        while recent_keys.order.len() > self.keys_per_queue {
+            if let Some(oldest) = recent_keys.order.pop_front() {
+                recent_keys.entries.remove(&oldest);
+            }
+        }
+        Reservation::Reserved(DedupReservation {
+            table: self.clone(),
+            topic: topic.clone(),
+            queue_id,
+            uniq_key: Some(uniq_key),
+        })
+    }
+
+    /// Remembers where the reserved message was stored, unless its key was evicted meanwhile.
+    fn record(
+        &self,
+        topic: &CheetahString,
+        queue_id: i32,
+        uniq_key: &CheetahString,
+        stored_message: StoredMessage,
+    ) {
+        if let Some(queue) = self.queues.get(&(topic.clone(), queue_id)) {
+            if let Some(entry) = queue.lock().entries.get_mut(uniq_key) {
+                *entry = DedupEntry::Stored(stored_message);
+            }
+        }
+    }
+
+    /// Forgets a key whose message was not stored, so a retried send stores it.
+    fn release(&self, topic: &CheetahString, queue_id: i32, uniq_key: &CheetahString) {
+        if let Some(queue) = self.queues.get(&(topic.clone(), queue_id)) {
+            let mut recent_keys = queue.lock();
+            if recent_keys.entries.get(uniq_key) == Some(&DedupEntry::Storing) {
+                recent_keys.entries.remove(uniq_key);
+                recent_keys.order.retain(|key| key != uniq_key);
+            }
+        }
+    }
+
+    pub fn remove_topic(&self, topic: &CheetahString) {
+        self.queues
+            .retain(|(queue_topic, _), _| queue_topic != topic);
+    }
+}
+
+/// The unique key a send reserved while it stores its message. Dropping the reservation without
+/// completing it releases the key.
+pub(crate) struct DedupReservation {
+    table: Arc<SendDedupTable>,
+    topic: CheetahString,
+    queue_id: i32,
+    uniq_key: Option<CheetahString>,
+}
+
+impl DedupReservation {
+    /// Completes the reservation with where the message was stored, `None` if storing it failed.
+    pub fn complete(mut self, stored_message: Option<StoredMessage>) {
+        let Some(uniq_key) = self.uniq_key.take() else {
+            return;
+        };
+        match stored_message {
+            Some(stored_message) => {
+                self.table
+                    .record(&self.topic, self.queue_id, &uniq_key, stored_message)
+            }
+            None => self.table.release(&self.topic, self.queue_id, &uniq_key),
+        }
+    }
+}
+
+impl Drop for DedupReservation {
+    fn drop(&mut self) {
+        if let Some(uniq_key) = self.uniq_key.take() {
+            self.table.release(&self.topic, self.queue_id, &uniq_key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stored_message(queue_offset: i64) -> StoredMessage {
+        StoredMessage {
+            msg_id: CheetahString::from_string(format!("msg-{queue_offset}")),
+            queue_offset,
+        }
+    }
+
+    fn dedup_table(keys_per_queue: usize) -> Arc<SendDedupTable> {
+        Arc::new(SendDedupTable::new(&BrokerConfig {
+            send_dedup_keys_per_queue: keys_per_queue,
+            ..Default::default()
+        }))
+    }
+
+    fn store(
+        table: &Arc<SendDedupTable>,
+        topic: &CheetahString,
+        queue_id: i32,
+        key: &str,
+        offset: i64,
+    ) {
+        match table.reserve(topic, queue_id, CheetahString::from_string(key.to_string())) {
+            Reservation::Reserved(reservation) => {
+                reservation.complete(Some(stored_message(offset)))
+            }
+            _ => panic!("{key} was reserved before"),
+        }
+    }
+
+    fn find(
+        table: &Arc<SendDedupTable>,
+        topic: &CheetahString,
+        queue_id: i32,
+        key: &str,
+    ) -> Option<StoredMessage> {
+        match table.reserve(topic, queue_id, CheetahString::from_string(key.to_string())) {
+            Reservation::Stored(stored_message) => Some(stored_message),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn stored_message_is_found_by_queue_and_key() {
+        let table = dedup_table(16);
+        let topic = CheetahString::from_static_str("topic");
+        store(&table, &topic, 0, "key", 7);
+
+        assert_eq!(find(&table, &topic, 0, "key"), Some(stored_message(7)));
+        assert_eq!(find(&table, &topic, 1, "key"), None);
+        table.remove_topic(&topic);
+        assert_eq!(find(&table, &topic, 0, "key"), None);
+    }
+
+    #[test]
+    fn oldest_key_is_evicted_when_the_queue_is_full() {
+        let table = dedup_table(2);
+        let topic = CheetahString::from_static_str("topic");
+        for offset in 0..3 {
+            store(&table, &topic, 0, &format!("key-{offset}"), offset);
+        }
+
+        assert_eq!(find(&table, &topic, 0, "key-0"), None);
+        assert_eq!(find(&table, &topic, 0, "key-2"), Some(stored_message(2)));
+    }
+
+    #[test]
+    fn concurrent_send_of_a_message_being_stored_is_not_reserved() {
+        let table = dedup_table(16);
+        let topic = CheetahString::from_static_str("topic");
+        let key = CheetahString::from_static_str("key");
+        let Reservation::Reserved(reservation) = table.reserve(&topic, 0, key.clone()) else {
+            panic!("a new key is reserved");
+        };
+        assert!(matches!(
+            table.reserve(&topic, 0, key.clone()),
+            Reservation::Storing
+        ));
+
+        reservation.complete(Some(stored_message(3)));
+        assert!(matches!(
+            table.reserve(&topic, 0, key),
+            Reservation::Stored(stored) if stored == stored_message(3)
+        ));
+    }
+
+    #[test]
+    fn key_of_a_failed_or_abandoned_send_is_released() {
+        let table = dedup_table(16);
+        let topic = CheetahString::from_static_str("topic");
+        let key = CheetahString::from_static_str("key");
+        let Reservation::Reserved(reservation) = table.reserve(&topic, 0, key.clone()) else {
+            panic!("a new key is reserved");
+        };
+        reservation.complete(None);
+
+        let Reservation::Reserved(reservation) = table.reserve(&topic, 0, key.clone()) else {
+            panic!("the key of a failed send is released");
+        };
+        drop(reservation);
+        assert!(matches!(
+            table.reserve(&topic, 0, key),
+            Reservation::Reserved(_)
+        ));
+    }
+}
@@ -0,0 +1,221 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::sync::Arc;
+use std::time::Duration;
+
+use cheetah_string::CheetahString;
+use rocketmq_common::common::broker::broker_role::BrokerRole;
+use rocketmq_common::common::mix_all;
+use rocketmq_remoting::protocol::body::broker_body::broker_member_group::BrokerMemberGroup;
+use rocketmq_rust::ArcMut;
+use rocketmq_store::base::message_store::MessageStore;
+use rocketmq_store::dledger::dledger_commit_log::DLedgerCommitLog;
+use rocketmq_store::dledger::member_state::MemberRole;
+use rocketmq_store::dledger::member_state::RoleState;
+use rocketmq_store::message_store::local_file_message_store::LocalFileMessageStore;
+use tracing::info;
+use tracing::warn;
+
+use crate::broker_runtime::BrokerRuntimeInner;
+
+const CATCH_UP_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Follows the role of this broker in its DLedger group: the leader serves as master with broker
+/// id 0, the other members serve as slaves. Every switch is registered with the name servers, so
+/// clients move over to a new leader without a controller.
+pub(crate) struct DLedgerRoleChangeHandler {
+    broker_runtime_inner: ArcMut<BrokerRuntimeInner<LocalFileMessageStore>>,
+    dledger_commit_log: Arc<DLedgerCommitLog>,
+}
+
+impl DLedgerRoleChangeHandler {
+    pub fn new(
+        broker_runtime_inner: ArcMut<BrokerRuntimeInner<LocalFileMessageStore>>,
+        dledger_commit_log: Arc<DLedgerCommitLog>,
+    ) -> Self {
+        DLedgerRoleChangeHandler {
+            broker_runtime_inner,
+            dledger_commit_log,
+        }
+    }
+
+    /// Handles the current role and every later change until the broker shuts down.
+    pub async fn run(mut self) {
+        let mut role_rx = self.dledger_commit_log.subscribe_role();
+        loop {
+            let role_state = role_rx.borrow_and_update().clone();
+            self.handle(role_state).await;
+            if role_rx.changed().await.is_err() {
+                return;
+            }
+        }
+    }
+
+    async fn handle(&mut self, role_state: RoleState) {
+        info!(
+            "dledger role of broker {} changed to {} in term {}",
+            self.broker_runtime_inner
+                .broker_config()
+                .broker_identity
+                .broker_name,
+            role_state.role,
+            role_state.term
+        );
+        match role_state.role {
+            MemberRole::Leader => {
+                if self.catch_up(role_state.term).await {
+                    self.change_to_master().await;
+                }
+            }
+            MemberRole::Follower => self.change_to_slave().await,
+            MemberRole::Candidate => {
+                if self.broker_runtime_inner.message_store_config().broker_role != BrokerRole::Slave
+                {
+                    self.change_to_slave().await;
+                }
+            }
+        }
+    }
+
+    /// Waits until everything stored before this term is committed and dispatched, so the queue
+    /// offsets the new master hands out continue the consume queues. Gives up once leadership
+    /// of `term` is lost.
+    async fn catch_up(&self, term: i64) -> bool {
+        let server = self.dledger_commit_log.server();
+        let ledger_end_index = server.ledger_end_index();
+        loop {
+            let role_state = server.role_state();
+            if role_state.role != MemberRole::Leader || role_state.term != term {
+                warn!("dledger leadership of term {} lost while catching up", term);
+                return false;
+            }
+            let dispatch_behind_bytes = self
+                .broker_runtime_inner
+                .message_store_unchecked()
+                .dispatch_behind_bytes();
+            if server.committed_index() >= ledger_end_index && dispatch_behind_bytes <= 0 {
+                return true;
+            }
+            tokio::time::sleep(CATCH_UP_CHECK_INTERVAL).await;
+        }
+    }
+
+    async fn change_to_master(&mut self) {
+        self.broker_runtime_inner
+            .message_store_unchecked_mut()
+            .recover_topic_queue_table();
+        let last_broker_id = self.switch_role(BrokerRole::SyncMaster, mix_all::MASTER_ID);
+        self.broker_runtime_inner
+            .change_special_service_status(true);
+        self.register().await;
+        if last_broker_id != mix_all::MASTER_ID {
+            self.broker_runtime_inner
+                .notify_min_broker_id_changed(None)
+                .await;
+        }
+        info!("broker switched to master by dledger");
+    }
+
+    async fn change_to_slave(&mut self) {
+        let broker_id = self
+            .dledger_commit_log
+            .server()
+            .config()
+            .follower_broker_id();
+        let last_broker_id = self.switch_role(BrokerRole::Slave, broker_id);
+        self.broker_runtime_inner
+            .change_special_service_status(false);
+        self.register().await;
+        if last_broker_id == mix_all::MASTER_ID {
+            // producers must not write to the demoted master any more
+            let broker_addr = self.broker_runtime_inner.get_broker_addr().clone();
+            self.broker_runtime_inner
+                .notify_min_broker_id_changed(Some(broker_addr))
+                .await;
+        }
+        info!("broker switched to slave {} by dledger", broker_id);
+    }
+
+    /// Switches to `broker_role` under `broker_id` and returns the broker id served before.
+    fn switch_role(&mut self, broker_role: BrokerRole, broker_id: u64) -> u64 {
+        let broker_addr = self.broker_runtime_inner.get_broker_addr().clone();
+        update_member_group(
+            self.broker_runtime_inner.broker_member_group_mut(),
+            &broker_addr,
+            broker_id,
+        );
+        let mut broker_config = self.broker_runtime_inner.broker_config().clone();
+        let last_broker_id = broker_config.broker_identity.broker_id;
+        broker_config.broker_role = broker_role;
+        broker_config.broker_identity.broker_id = broker_id;
+        let mut message_store_config = self.broker_runtime_inner.message_store_config().clone();
+        message_store_config.broker_role = broker_role;
+        self.broker_runtime_inner.set_broker_config(broker_config);
+        self.broker_runtime_inner
+            .set_message_store_config(message_store_config);
+        last_broker_id
+    }
+
+    async fn register(&self) {
+        let this = self.broker_runtime_inner.clone();
+        self.broker_runtime_inner
+            .register_broker_all_inner(this, true, false, true)
+            .await;
+    }
+}
+
+/// Files `broker_addr` under `broker_id` in `broker_member_group`, dropping it from the broker id
+/// it served under before.
+fn update_member_group(
+    broker_member_group: &mut BrokerMemberGroup,
+    broker_addr: &CheetahString,
+    broker_id: u64,
+) {
+    broker_member_group
+        .broker_addrs
+        .retain(|_, addr| addr != broker_addr);
+    broker_member_group
+        .broker_addrs
+        .insert(broker_id, broker_addr.clone());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[test]
+    fn member_group_follows_the_broker_id() {
+        let mut broker_member_group = BrokerMemberGroup::new(
+            CheetahString::from_static_str("cluster"),
+            CheetahString::from_static_str("broker-a"),
+        );
+        let master_addr = CheetahString::from_static_str("10.0.0.1:10911");
+        let slave_addr = CheetahString::from_static_str("10.0.0.2:10911");
+        update_member_group(&mut broker_member_group, &master_addr, mix_all::MASTER_ID);
+        update_member_group(&mut broker_member_group, &slave_addr, 1);
+
+        update_member_group(&mut broker_member_group, &master_addr, 2);
+        update_member_group(&mut broker_member_group, &slave_addr, mix_all::MASTER_ID);
+
+        assert_eq!(
+            broker_member_group.broker_addrs,
+            HashMap::from([(mix_all::MASTER_ID, slave_addr), (2, master_addr)])
+        );
+    }
+}
@@ -21,8 +21,10 @@ use cheetah_string::CheetahString;
 use futures::future::BoxFuture;
 use futures::FutureExt;
 use rocketmq_client_rust::consumer::pull_status::PullStatus;
+use rocketmq_client_rust::producer::producer_impl::topic_publish_info::TopicPublishInfo;
 use rocketmq_client_rust::producer::send_result::SendResult;
 use rocketmq_client_rust::producer::send_status::SendStatus;
+use rocketmq_common::common::constant::PermName;
 use rocketmq_common::common::hasher::string_hasher::JavaStringHasher;
 use rocketmq_common::common::message::message_decoder;
 use rocketmq_common::common::message::message_ext::MessageExt;
@@ -141,32 +143,32 @@ impl<MS> EscapeBridge<MS>
 where
     MS: MessageStore,
 {
+    fn is_remote_escape_enabled(&self) -> bool {
+        let broker_config = self.broker_runtime_inner.broker_config();
+        broker_config.enable_slave_acting_master && broker_config.enable_remote_escape
+    }
+
+    /// A master keeps messages in its own store; when it has lost write permission the
+    /// messages escape to another broker as long as remote escape is enabled.
+    fn can_put_to_local_store(&self) -> bool {
+        let broker_config = self.broker_runtime_inner.broker_config();
+        broker_config.broker_identity.broker_id == mix_all::MASTER_ID
+            && (PermName::is_writeable(broker_config.broker_permission)
+                || !self.is_remote_escape_enabled())
+    }
+
     pub async fn put_message(
         &mut self,
         mut message_ext: MessageExtBrokerInner,
     ) -> PutMessageResult {
-        if self
-            .broker_runtime_inner
-            .broker_config()
-            .broker_identity
-            .broker_id
-            == mix_all::MASTER_ID
-        {
+        if self.can_put_to_local_store() {
             self.broker_runtime_inner
                 .message_store_mut()
                 .as_mut()
                 .unwrap()
                 .put_message(message_ext)
                 .await
-        } else if self
-            .broker_runtime_inner
-            .broker_config()
-            .enable_slave_acting_master
-            && self
-                .broker_runtime_inner
-                .broker_config()
-                .enable_remote_escape
-        {
+        } else if self.is_remote_escape_enabled() {
             message_ext.set_wait_store_msg_ok(false);
             match self.put_message_to_remote_broker(message_ext, None).await {
                 Ok(send_result) => transform_send_result2put_result(send_result),
@@ -237,28 +239,24 @@ where
             .as_ref()
             .is_some_and(|value| !value.is_empty())
         {
-            let mq = topic_publish_info
-                .select_one_message_queue_by_broker(broker_name_to_send.as_ref())
-                .unwrap();
-            message_to_put.message_ext_inner.queue_id = mq.get_queue_id();
-            broker_name_to_send = Some(mq.get_broker_name().clone());
-            if self
-                .broker_runtime_inner
-                .broker_config()
-                .broker_identity
-                .broker_name
-                .as_str()
-                == mq.get_broker_name().as_str()
-            {
+            let Some(mq) = select_remote_queue(
+                &topic_publish_info,
+                &self
+                    .broker_runtime_inner
+                    .broker_config()
+                    .broker_identity
+                    .broker_name,
+            ) else {
                 warn!(
                     "putMessageToRemoteBroker failed, remote broker not found. Topic: {}, MsgId: \
-                     {}, Broker: {}",
+                     {}",
                     message_to_put.get_topic(),
-                    message_to_put.message_ext_inner.msg_id,
-                    mq.get_broker_name()
+                    message_to_put.message_ext_inner.msg_id
                 );
                 return Ok(None);
-            }
+            };
+            message_to_put.message_ext_inner.queue_id = mq.get_queue_id();
+            broker_name_to_send = Some(mq.get_broker_name().clone());
             mq
         } else {
             MessageQueue::from_parts(
@@ -313,28 +311,14 @@ where
         &mut self,
         mut message_ext: MessageExtBrokerInner,
     ) -> PutMessageResult {
-        if self
-            .broker_runtime_inner
-            .broker_config()
-            .broker_identity
-            .broker_id
-            == mix_all::MASTER_ID
-        {
+        if self.can_put_to_local_store() {
             self.broker_runtime_inner
                 .message_store_mut()
                 .as_mut()
                 .unwrap()
                 .put_message(message_ext)
                 .await
-        } else if self
-            .broker_runtime_inner
-            .broker_config()
-            .enable_slave_acting_master
-            && self
-                .broker_runtime_inner
-                .broker_config()
-                .enable_remote_escape
-        {
+        } else if self.is_remote_escape_enabled() {
             message_ext.set_wait_store_msg_ok(false);
             let topic_publish_info = self
                 .broker_runtime_inner
@@ -345,23 +329,39 @@ where
                 return PutMessageResult::new_default(PutMessageStatus::ServiceNotAvailable);
             }
             let topic_publish_info = topic_publish_info.unwrap();
-            let mq_selected = topic_publish_info.select_one_message_queue();
+            let mq_selected = select_remote_queue(
+                &topic_publish_info,
+                &self
+                    .broker_runtime_inner
+                    .broker_config()
+                    .broker_identity
+                    .broker_name,
+            );
             if mq_selected.is_none() {
                 return PutMessageResult::new_default(PutMessageStatus::ServiceNotAvailable);
             }
             let message_queue = mq_selected.unwrap();
             message_ext.message_ext_inner.queue_id = message_queue.get_queue_id();
             let broker_name_to_send = message_queue.get_broker_name();
-            let broker_addr_to_send = self
+            let Some(broker_addr_to_send) = self
                 .broker_runtime_inner
                 .topic_route_info_manager()
-                .find_broker_address_in_publish(Some(broker_name_to_send));
+                .find_broker_address_in_publish(Some(broker_name_to_send))
+            else {
+                warn!(
+                    "asyncPutMessage failed, remote broker address not found. Topic: {}, Broker: \
+                     {}",
+                    message_ext.get_topic(),
+                    broker_name_to_send
+                );
+                return PutMessageResult::new(PutMessageStatus::PutToRemoteBrokerFail, None, true);
+            };
             let producer_group = self.get_producer_group(&message_ext);
             let result = self
                 .broker_runtime_inner
                 .broker_outer_api()
                 .send_message_to_specific_broker(
-                    broker_addr_to_send.as_ref().unwrap(),
+                    &broker_addr_to_send,
                     broker_name_to_send,
                     message_ext.message_ext_inner,
                     producer_group,
@@ -378,28 +378,14 @@ where
         &mut self,
         mut message_ext: MessageExtBrokerInner,
     ) -> PutMessageResult {
-        if self
-            .broker_runtime_inner
-            .broker_config()
-            .broker_identity
-            .broker_id
-            == mix_all::MASTER_ID
-        {
+        if self.can_put_to_local_store() {
             self.broker_runtime_inner
                 .message_store_mut()
                 .as_mut()
                 .unwrap()
                 .put_message(message_ext)
                 .await
-        } else if self
-            .broker_runtime_inner
-            .broker_config()
-            .enable_slave_acting_master
-            && self
-                .broker_runtime_inner
-                .broker_config()
-                .enable_remote_escape
-        {
+        } else if self.is_remote_escape_enabled() {
             message_ext.set_wait_store_msg_ok(false);
             let topic_publish_info = self
                 .broker_runtime_inner
@@ -410,31 +396,43 @@ where
                 return PutMessageResult::new(PutMessageStatus::PutToRemoteBrokerFail, None, true);
             }
             let topic_publish_info = topic_publish_info.unwrap();
-
-            if topic_publish_info.message_queue_list.is_empty() {
-                return PutMessageResult::new(PutMessageStatus::PutToRemoteBrokerFail, None, true);
-            }
-            //let message_queue = mq_selected.unwrap();
             let id = format!(
                 "{}{}",
                 message_ext.get_topic(),
                 message_ext.message_ext_inner.store_host
             );
-            let code = JavaStringHasher::hash_str(id.as_str());
-            let index = code as usize % topic_publish_info.message_queue_list.len();
-            let message_queue = topic_publish_info.message_queue_list[index].clone();
+            let Some(message_queue) = select_remote_queue_by_key(
+                &topic_publish_info,
+                &self
+                    .broker_runtime_inner
+                    .broker_config()
+                    .broker_identity
+                    .broker_name,
+                id.as_str(),
+            ) else {
+                return PutMessageResult::new(PutMessageStatus::PutToRemoteBrokerFail, None, true);
+            };
             message_ext.message_ext_inner.queue_id = message_queue.get_queue_id();
             let broker_name_to_send = message_queue.get_broker_name();
-            let broker_addr_to_send = self
+            let Some(broker_addr_to_send) = self
                 .broker_runtime_inner
                 .topic_route_info_manager()
-                .find_broker_address_in_publish(Some(broker_name_to_send));
+                .find_broker_address_in_publish(Some(broker_name_to_send))
+            else {
+                warn!(
+                    "putMessageToSpecificQueue failed, remote broker address not found. Topic: \
+                     {}, Broker: {}",
+                    message_ext.get_topic(),
+                    broker_name_to_send
+                );
+                return PutMessageResult::new(PutMessageStatus::PutToRemoteBrokerFail, None, true);
+            };
             let producer_group = self.get_producer_group(&message_ext);
             match self
                 .broker_runtime_inner
                 .broker_outer_api()
                 .send_message_to_specific_broker(
-                    broker_addr_to_send.as_ref().unwrap(),
+                    &broker_addr_to_send,
                     broker_name_to_send,
                     message_ext.message_ext_inner,
                     producer_group,
@@ -599,7 +597,13 @@ where
                         }
                     }
                 }
-                Err(_e) => {}
+                Err(e) => {
+                    warn!(
+                        "Get message from remote failed, topic {}, offset {}, queueId {}, broker \
+                         {}, error {}",
+                        topic, offset, queue_id, broker_name, e
+                    );
+                }
             }
 
             (None, "Get message from remote failed".to_string(), true)
@@ -614,8 +618,7 @@ fn decode_msg_list(
 ) -> Vec<MessageExt> {
     let mut found_list = Vec::new();
     for bb in get_message_result.message_mapped_list() {
-        let data = &bb.mapped_file.as_ref().unwrap().get_mapped_file()
-            [bb.start_offset as usize..(bb.start_offset + bb.size as u64) as usize];
+        let data = bb.get_buffer();
         let mut bytes = Bytes::copy_from_slice(data);
         let msg_ext =
             message_decoder::decode(&mut bytes, true, de_compress_body, false, false, false);
@@ -626,6 +629,36 @@ fn decode_msg_list(
     found_list
 }
 
+/// Picks a queue of the route hosted by another broker than `local_broker_name`. Messages escape
+/// because their own broker cannot store them, they must never be sent back to it.
+fn select_remote_queue(
+    topic_publish_info: &TopicPublishInfo,
+    local_broker_name: &CheetahString,
+) -> Option<MessageQueue> {
+    let local_broker_name = local_broker_name.clone();
+    let remote = move |mq: &MessageQueue| mq.get_broker_name() != &local_broker_name;
+    topic_publish_info.select_one_message_queue_filters(&[&remote])
+}
+
+/// Picks the queue hosted by another broker than `local_broker_name` that messages with `key`
+/// always escape to.
+fn select_remote_queue_by_key(
+    topic_publish_info: &TopicPublishInfo,
+    local_broker_name: &CheetahString,
+    key: &str,
+) -> Option<MessageQueue> {
+    let remote_queues = topic_publish_info
+        .message_queue_list
+        .iter()
+        .filter(|mq| mq.get_broker_name() != local_broker_name)
+        .collect::<Vec<_>>();
+    if remote_queues.is_empty() {
+        return None;
+    }
+    let index = JavaStringHasher::hash_str(key) as usize % remote_queues.len();
+    Some(remote_queues[index].clone())
+}
+
 #[inline]
 fn transform_send_result2put_result(send_result: Option<SendResult>) -> PutMessageResult {
     match send_result {
@@ -709,4 +742,46 @@ mod tests {
             PutMessageStatus::SlaveNotAvailable
         );
     }
+
+    fn publish_info(brokers: &[&str]) -> TopicPublishInfo {
+        TopicPublishInfo {
+            message_queue_list: brokers
+                .iter()
+                .flat_map(|broker| {
+                    (0..4).map(move |queue_id| {
+                        MessageQueue::from_parts("TopicTest", *broker, queue_id)
+                    })
+                })
+                .collect(),
+            ..TopicPublishInfo::new()
+        }
+    }
+
+    #[test]
+    fn escaped_messages_are_never_sent_to_the_local_broker() {
+        let local = CheetahString::from_static_str("broker-a");
+        let topic_publish_info = publish_info(&["broker-a", "broker-b"]);
+        for i in 0..16 {
+            let mq = select_remote_queue(&topic_publish_info, &local).unwrap();
+            assert_eq!(mq.get_broker_name(), "broker-b");
+            let mq = select_remote_queue_by_key(&topic_publish_info, &local, &format!("key{i}"))
+                .unwrap();
+            assert_eq!(mq.get_broker_name(), "broker-b");
+        }
+
+        let topic_publish_info = publish_info(&["broker-a"]);
+        assert!(select_remote_queue(&topic_publish_info, &local).is_none());
+        assert!(select_remote_queue_by_key(&topic_publish_info, &local, "key").is_none());
+    }
+
+    #[test]
+    fn messages_with_the_same_key_escape_to_the_same_queue() {
+        let local = CheetahString::from_static_str("broker-a");
+        let topic_publish_info = publish_info(&["broker-a", "broker-b", "broker-c"]);
+        let mq = select_remote_queue_by_key(&topic_publish_info, &local, "key").unwrap();
+        assert_eq!(
+            select_remote_queue_by_key(&topic_publish_info, &local, "key"),
+            Some(mq)
+        );
+    }
 }
@@ -108,6 +108,19 @@ impl ConsumerFilterData {
     pub fn compiled_expression(&self) -> &Option<Arc<Box<dyn Expression + Send + Sync + 'static>>> {
         &self.compiled_expression
     }
+
+    pub fn set_compiled_expression(
+        &mut self,
+        compiled_expression: Option<Arc<Box<dyn Expression + Send + Sync + 'static>>>,
+    ) {
+        self.compiled_expression = compiled_expression;
+    }
+
+    /// Whether a message stored at `msg_store_time` was stored after the consumer subscribed,
+    /// only those messages carry a filter bit for it.
+    pub fn is_msg_in_live(&self, msg_store_time: i64) -> bool {
+        msg_store_time > self.born_time as i64
+    }
 }
 
 impl Debug for ConsumerFilterData {
@@ -15,21 +15,98 @@
  * limitations under the License.
  */
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use cheetah_string::CheetahString;
+use dashmap::DashMap;
+use rocketmq_common::common::filter::expression_type::ExpressionType;
+use rocketmq_common::common::message::MessageConst;
+use rocketmq_common::common::mix_all::RETRY_GROUP_TOPIC_PREFIX;
+use rocketmq_remoting::protocol::heartbeat::subscription_data::SubscriptionData;
 use rocketmq_store::consume_queue::consume_queue_ext::CqExtUnit;
 use rocketmq_store::filter::MessageFilter;
 
-pub struct ExpressionForRetryMessageFilter;
+use crate::filter::consumer_filter_data::ConsumerFilterData;
+use crate::filter::expression_message_filter::decode_properties;
+use crate::filter::expression_message_filter::evaluate_filter_data;
+use crate::filter::expression_message_filter::is_tag_matched;
+use crate::filter::expression_message_filter::ExpressionMessageFilter;
+use crate::filter::manager::consumer_filter_manager::ConsumerFilterManager;
+
+/// Message filter which also applies the original subscription of a consumer group to the
+/// messages of its retry topic.
+///
+/// Retry messages keep the real topic in the `RETRY_TOPIC` property, so they are matched
+/// against the subscription the group registered for that topic instead of the `*`
+/// subscription of the retry topic itself.
+pub struct ExpressionForRetryMessageFilter {
+    inner: ExpressionMessageFilter,
+    subscription_data: Option<SubscriptionData>,
+    consumer_filter_manager: Arc<ConsumerFilterManager>,
+    group_subscriptions: Option<Arc<DashMap<CheetahString, SubscriptionData>>>,
+}
+
+impl ExpressionForRetryMessageFilter {
+    pub fn new(
+        subscription_data: Option<SubscriptionData>,
+        consumer_filter_data: Option<ConsumerFilterData>,
+        consumer_filter_manager: Arc<ConsumerFilterManager>,
+        group_subscriptions: Option<Arc<DashMap<CheetahString, SubscriptionData>>>,
+    ) -> Self {
+        Self {
+            inner: ExpressionMessageFilter::new(
+                subscription_data.clone(),
+                consumer_filter_data,
+                consumer_filter_manager.clone(),
+            ),
+            subscription_data,
+            consumer_filter_manager,
+            group_subscriptions,
+        }
+    }
+
+    fn is_retry_matched(
+        &self,
+        group: &str,
+        properties: &HashMap<CheetahString, CheetahString>,
+    ) -> bool {
+        let Some(real_topic) = properties.get(MessageConst::PROPERTY_RETRY_TOPIC) else {
+            return true;
+        };
+        let real_subscription = self
+            .group_subscriptions
+            .as_ref()
+            .and_then(|subscriptions| subscriptions.get(real_topic).map(|sub| sub.clone()));
+        let Some(real_subscription) = real_subscription else {
+            return true;
+        };
+        if real_subscription.class_filter_mode {
+            return true;
+        }
+        if ExpressionType::is_tag_type(Some(real_subscription.expression_type.as_str())) {
+            return real_subscription.tags_set.is_empty()
+                || is_tag_matched(&real_subscription, properties);
+        }
+        match self
+            .consumer_filter_manager
+            .get_consumer_filter_data(real_topic, &CheetahString::from_slice(group))
+        {
+            None => true,
+            Some(real_filter_data) => {
+                evaluate_filter_data(&real_filter_data, None, Some(properties))
+            }
+        }
+    }
+}
 
-#[allow(unused_variables)]
 impl MessageFilter for ExpressionForRetryMessageFilter {
     fn is_matched_by_consume_queue(
         &self,
         tags_code: Option<i64>,
         cq_ext_unit: Option<&CqExtUnit>,
     ) -> bool {
-        true
+        self.inner
+            .is_matched_by_consume_queue(tags_code, cq_ext_unit)
     }
 
     fn is_matched_by_commit_log(
@@ -37,6 +114,101 @@ impl MessageFilter for ExpressionForRetryMessageFilter {
         msg_buffer: Option<&[u8]>,
         properties: Option<&HashMap<CheetahString, CheetahString>>,
     ) -> bool {
-        true
+        let Some(subscription_data) = self.subscription_data.as_ref() else {
+            return true;
+        };
+        if subscription_data.class_filter_mode {
+            return true;
+        }
+        let Some(group) = subscription_data
+            .topic
+            .as_str()
+            .strip_prefix(RETRY_GROUP_TOPIC_PREFIX)
+        else {
+            return self.inner.is_matched_by_commit_log(msg_buffer, properties);
+        };
+        match properties {
+            Some(properties) => self.is_retry_matched(group, properties),
+            None => match decode_properties(msg_buffer) {
+                Some(properties) => self.is_retry_matched(group, &properties),
+                None => true,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rocketmq_common::common::broker::broker_config::BrokerConfig;
+    use rocketmq_remoting::protocol::filter::filter_api::FilterAPI;
+
+    use super::*;
+
+    fn subscription(topic: &str, sub_string: &str) -> SubscriptionData {
+        FilterAPI::build(
+            &CheetahString::from_slice(topic),
+            &CheetahString::from_slice(sub_string),
+            None,
+        )
+        .unwrap()
+    }
+
+    fn retry_filter(group_subscriptions: Vec<SubscriptionData>) -> ExpressionForRetryMessageFilter {
+        let table = DashMap::new();
+        for subscription_data in group_subscriptions {
+            table.insert(subscription_data.topic.clone(), subscription_data);
+        }
+        ExpressionForRetryMessageFilter::new(
+            Some(subscription("%RETRY%group", "*")),
+            None,
+            Arc::new(ConsumerFilterManager::new(
+                Arc::new(BrokerConfig::default()),
+            )),
+            Some(Arc::new(table)),
+        )
+    }
+
+    fn retry_properties(real_topic: &str, tags: &str) -> HashMap<CheetahString, CheetahString> {
+        let mut properties = HashMap::new();
+        properties.insert(
+            CheetahString::from_static_str(MessageConst::PROPERTY_RETRY_TOPIC),
+            CheetahString::from_slice(real_topic),
+        );
+        properties.insert(
+            CheetahString::from_static_str(MessageConst::PROPERTY_TAGS),
+            CheetahString::from_slice(tags),
+        );
+        properties
+    }
+
+    #[test]
+    fn retry_message_uses_real_topic_subscription() {
+        let filter = retry_filter(vec![subscription("topic", "TagA")]);
+
+        assert!(filter.is_matched_by_commit_log(None, Some(&retry_properties("topic", "TagA"))));
+        assert!(!filter.is_matched_by_commit_log(None, Some(&retry_properties("topic", "TagB"))));
+    }
+
+    #[test]
+    fn retry_message_without_registered_subscription_matches() {
+        let filter = retry_filter(vec![subscription("topic", "TagA")]);
+
+        assert!(filter.is_matched_by_commit_log(None, Some(&retry_properties("other", "TagB"))));
+        assert!(filter.is_matched_by_commit_log(None, Some(&HashMap::new())));
+    }
+
+    #[test]
+    fn normal_topic_delegates_to_tag_filter() {
+        let filter = ExpressionForRetryMessageFilter::new(
+            Some(subscription("topic", "TagA")),
+            None,
+            Arc::new(ConsumerFilterManager::new(
+                Arc::new(BrokerConfig::default()),
+            )),
+            None,
+        );
+
+        assert!(filter.is_matched_by_commit_log(None, Some(&retry_properties("topic", "TagA"))));
+        assert!(!filter.is_matched_by_commit_log(None, Some(&retry_properties("topic", "TagB"))));
     }
 }
@@ -17,10 +17,11 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use bytes::Bytes;
 use cheetah_string::CheetahString;
 use rocketmq_common::common::filter::expression_type::ExpressionType;
 use rocketmq_common::common::message::message_decoder;
+use rocketmq_common::common::message::MessageConst;
+use rocketmq_filter::utils::bits_array::BitsArray;
 use rocketmq_remoting::protocol::heartbeat::subscription_data::SubscriptionData;
 use rocketmq_store::consume_queue::consume_queue_ext::CqExtUnit;
 use rocketmq_store::filter::MessageFilter;
@@ -84,7 +85,39 @@ impl MessageFilter for ExpressionMessageFilter {
                 .code_set
                 .contains(&(tags_code.unwrap() as i32))
         } else {
-            unimplemented!("SQL92 expression type is not supported yet.")
+            // entries without a usable bit map fall through to the expression evaluation
+            // against the commit log
+            let Some(filter_data) = self.consumer_filter_data.as_ref() else {
+                return true;
+            };
+            let Some(bloom_filter_data) = filter_data.bloom_filter_data() else {
+                return true;
+            };
+            if filter_data.expression().is_none() || filter_data.compiled_expression().is_none() {
+                return true;
+            }
+            // messages stored before the consumer subscribed have no bit for it
+            let Some(cq_ext_unit) =
+                cq_ext_unit.filter(|unit| filter_data.is_msg_in_live(unit.msg_store_time()))
+            else {
+                return true;
+            };
+            let Some(filter_bit_map) = cq_ext_unit.filter_bit_map() else {
+                return true;
+            };
+            let Some(bloom_filter) = self.consumer_filter_manager.bloom_filter() else {
+                return true;
+            };
+            if !self.bloom_data_valid
+                || filter_bit_map.is_empty()
+                || filter_bit_map.len() * 8 != bloom_filter_data.bit_num() as usize
+            {
+                return true;
+            }
+            bloom_filter.is_hit(
+                bloom_filter_data,
+                &BitsArray::from_bytes(filter_bit_map, None),
+            )
         }
     }
 
@@ -101,34 +134,193 @@ impl MessageFilter for ExpressionMessageFilter {
             return true;
         }
         if ExpressionType::is_tag_type(Some(subscription_data.expression_type.as_str())) {
-            return true;
-        }
-        if self.consumer_filter_data.is_none() {
-            return true;
+            // the consume queue only compares tag hash codes, re-check the real tag so that
+            // hash collisions are not delivered to the consumer
+            if subscription_data.sub_string.as_str() == SubscriptionData::SUB_ALL
+                || subscription_data.tags_set.is_empty()
+            {
+                return true;
+            }
+            return match properties {
+                Some(properties) => is_tag_matched(subscription_data, properties),
+                None => match msg_buffer.and_then(message_decoder::decode_view) {
+                    Some(view) => view
+                        .property(MessageConst::PROPERTY_TAGS)
+                        .is_some_and(|tags| subscription_data.tags_set.contains(tags)),
+                    None => true,
+                },
+            };
         }
-        let real_filter_data = self.consumer_filter_data.as_ref().unwrap();
-        if real_filter_data.expression().is_none()
-            || real_filter_data.expression_type().is_none()
-            || real_filter_data.compiled_expression().is_none()
-        {
-            return true;
+        match self.consumer_filter_data.as_ref() {
+            None => true,
+            Some(real_filter_data) => {
+                evaluate_filter_data(real_filter_data, msg_buffer, properties)
+            }
         }
+    }
+}
 
-        let temp_properties = if properties.is_none() && msg_buffer.is_some() {
-            let bytes = msg_buffer.unwrap();
-            let mut bytes_ = Bytes::copy_from_slice(bytes);
-            message_decoder::decode_properties(&mut bytes_)
-        } else {
-            None
-        };
-        let context = MessageEvaluationContext::new(&temp_properties);
-        if let Some(filter) = real_filter_data.compiled_expression() {
-            match filter.evaluate(&context) {
-                Ok(value) => *value.downcast_ref::<bool>().unwrap_or(&false),
-                Err(_) => false,
-            }
-        } else {
-            true
+/// Decodes the message properties from a serialized commit log entry.
+pub(crate) fn decode_properties(
+    msg_buffer: Option<&[u8]>,
+) -> Option<HashMap<CheetahString, CheetahString>> {
+    let view = message_decoder::decode_view(msg_buffer?)?;
+    if view.properties.is_empty() {
+        return None;
+    }
+    Some(view.properties_map())
+}
+
+/// Checks the message tag against the tags of a tag-type subscription.
+pub(crate) fn is_tag_matched(
+    subscription_data: &SubscriptionData,
+    properties: &HashMap<CheetahString, CheetahString>,
+) -> bool {
+    if subscription_data.sub_string.as_str() == SubscriptionData::SUB_ALL {
+        return true;
+    }
+    properties
+        .get(MessageConst::PROPERTY_TAGS)
+        .is_some_and(|tags| subscription_data.tags_set.contains(tags))
+}
+
+/// Evaluates the compiled expression of `filter_data` against the message properties.
+pub(crate) fn evaluate_filter_data(
+    filter_data: &ConsumerFilterData,
+    msg_buffer: Option<&[u8]>,
+    properties: Option<&HashMap<CheetahString, CheetahString>>,
+) -> bool {
+    if filter_data.expression().is_none() || filter_data.expression_type().is_none() {
+        return true;
+    }
+    let Some(filter) = filter_data.compiled_expression() else {
+        return true;
+    };
+    let temp_properties = match properties {
+        Some(properties) => Some(properties.clone()),
+        None => decode_properties(msg_buffer),
+    };
+    let context = MessageEvaluationContext::new(&temp_properties);
+    match filter.evaluate(&context) {
+        Ok(value) => *value.downcast_ref::<bool>().unwrap_or(&false),
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rocketmq_common::common::broker::broker_config::BrokerConfig;
+    use rocketmq_common::common::hasher::string_hasher::JavaStringHasher;
+    use rocketmq_filter::expression::evaluation_context::EvaluationContext;
+    use rocketmq_filter::expression::Expression;
+    use rocketmq_filter::utils::bloom_filter_data::BloomFilterData;
+    use rocketmq_remoting::protocol::filter::filter_api::FilterAPI;
+
+    use super::*;
+
+    fn tag_filter(sub_string: &str) -> ExpressionMessageFilter {
+        let subscription_data = FilterAPI::build(
+            &CheetahString::from_static_str("topic"),
+            &CheetahString::from_slice(sub_string),
+            None,
+        )
+        .unwrap();
+        ExpressionMessageFilter::new(
+            Some(subscription_data),
+            None,
+            Arc::new(ConsumerFilterManager::new(
+                Arc::new(BrokerConfig::default()),
+            )),
+        )
+    }
+
+    fn tags_properties(tags: &str) -> HashMap<CheetahString, CheetahString> {
+        let mut properties = HashMap::new();
+        properties.insert(
+            CheetahString::from_static_str(MessageConst::PROPERTY_TAGS),
+            CheetahString::from_slice(tags),
+        );
+        properties
+    }
+
+    #[test]
+    fn consume_queue_matches_subscribed_tag_codes() {
+        let filter = tag_filter("TagA || TagB");
+        let tag_a = JavaStringHasher::hash_str("TagA") as i64;
+        let tag_c = JavaStringHasher::hash_str("TagC") as i64;
+
+        assert!(filter.is_matched_by_consume_queue(Some(tag_a), None));
+        assert!(!filter.is_matched_by_consume_queue(Some(tag_c), None));
+        assert!(filter.is_matched_by_consume_queue(None, None));
+    }
+
+    #[test]
+    fn sub_all_matches_every_message() {
+        let filter = tag_filter("*");
+        let tag_c = JavaStringHasher::hash_str("TagC") as i64;
+
+        assert!(filter.is_matched_by_consume_queue(Some(tag_c), None));
+        assert!(filter.is_matched_by_commit_log(None, Some(&tags_properties("TagC"))));
+    }
+
+    struct AlwaysTrue;
+
+    impl Expression for AlwaysTrue {
+        fn evaluate(
+            &self,
+            _context: &dyn EvaluationContext,
+        ) -> Result<
+            Box<dyn std::any::Any + Send + Sync + 'static>,
+            Box<dyn std::error::Error + Send + Sync + 'static>,
+        > {
+            Ok(Box::new(true))
         }
     }
+
+    #[test]
+    fn consume_queue_skips_sql_entries_missing_the_bit_of_the_consumer() {
+        let consumer_filter_manager = Arc::new(ConsumerFilterManager::new(Arc::new(
+            BrokerConfig::default(),
+        )));
+        let bloom_filter = *consumer_filter_manager.bloom_filter().unwrap();
+        let bloom_filter_data =
+            BloomFilterData::new((0..bloom_filter.k()).collect(), bloom_filter.m() as u32);
+        let mut consumer_filter_data = ConsumerFilterData::default();
+        consumer_filter_data.set_expression(Some(CheetahString::from_static_str("a > 1")));
+        consumer_filter_data.set_compiled_expression(Some(Arc::new(Box::new(AlwaysTrue))));
+        consumer_filter_data.set_born_time(100);
+        consumer_filter_data.set_bloom_filter_data(Some(bloom_filter_data.clone()));
+        let subscription_data = FilterAPI::build(
+            &CheetahString::from_static_str("topic"),
+            &CheetahString::from_static_str("a > 1"),
+            Some(CheetahString::from_static_str(ExpressionType::SQL92)),
+        )
+        .unwrap();
+        let filter = ExpressionMessageFilter::new(
+            Some(subscription_data),
+            Some(consumer_filter_data),
+            consumer_filter_manager,
+        );
+
+        let mut bits = BitsArray::create(bloom_filter.m() as usize);
+        let missed = CqExtUnit::new(0, 200, Some(bits.bytes().to_vec()));
+        bloom_filter.hash_to(&bloom_filter_data, &mut bits);
+        let hit = CqExtUnit::new(0, 200, Some(bits.bytes().to_vec()));
+        let before_subscribing = CqExtUnit::new(0, 50, missed.filter_bit_map().clone());
+
+        assert!(filter.is_matched_by_consume_queue(None, Some(&hit)));
+        assert!(!filter.is_matched_by_consume_queue(None, Some(&missed)));
+        assert!(filter.is_matched_by_consume_queue(None, Some(&before_subscribing)));
+        assert!(filter.is_matched_by_consume_queue(None, None));
+    }
+
+    #[test]
+    fn commit_log_rechecks_exact_tag() {
+        let filter = tag_filter("TagA");
+
+        assert!(filter.is_matched_by_commit_log(None, Some(&tags_properties("TagA"))));
+        assert!(!filter.is_matched_by_commit_log(None, Some(&tags_properties("TagB"))));
+        assert!(!filter.is_matched_by_commit_log(None, Some(&HashMap::new())));
+        assert!(filter.is_matched_by_commit_log(None, None));
+    }
 }
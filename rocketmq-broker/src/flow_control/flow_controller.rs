@@ -0,0 +1,381 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+use cheetah_string::CheetahString;
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use rocketmq_common::common::attribute::long_range_attribute::LongRangeAttribute;
+use rocketmq_common::common::attribute::subscription_group_attributes::SubscriptionGroupAttributes;
+use rocketmq_common::common::attribute::topic_attributes::TopicAttributes;
+use rocketmq_common::common::attribute::Attribute;
+use rocketmq_common::common::broker::broker_config::BrokerConfig;
+use rocketmq_common::TimeUtils::get_current_millis;
+
+const WINDOW_MILLIS: u64 = 1000;
+
+/// Rate limit of a single topic or consumer group, a value of 0 means unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct FlowLimit {
+    pub msgs_per_second: u64,
+    pub bytes_per_second: u64,
+}
+
+impl FlowLimit {
+    pub fn new(msgs_per_second: u64, bytes_per_second: u64) -> Self {
+        Self {
+            msgs_per_second,
+            bytes_per_second,
+        }
+    }
+
+    #[inline]
+    pub fn is_unlimited(&self) -> bool {
+        self.msgs_per_second == 0 && self.bytes_per_second == 0
+    }
+}
+
+#[derive(Default)]
+struct FlowWindow {
+    window_start: u64,
+    msgs: u64,
+    bytes: u64,
+}
+
+#[derive(Default)]
+struct FlowCounter {
+    window: Mutex<FlowWindow>,
+    throttled_times: AtomicU64,
+}
+
+impl FlowCounter {
+    /// Accounts `msgs`/`bytes` into the current one-second window. When `check` is set the
+    /// acquisition is refused if it would exceed `limit`, except for the first request of a
+    /// window so that a single oversized request can never be starved forever.
+    fn acquire(&self, limit: FlowLimit, msgs: u64, bytes: u64, now: u64, check: bool) -> bool {
+        let mut window = self.window.lock();
+        if now.saturating_sub(window.window_start) >= WINDOW_MILLIS {
+            window.window_start = now;
+            window.msgs = 0;
+            window.bytes = 0;
+        }
+        if check && (window.msgs > 0 || window.bytes > 0) {
+            let msgs_exceeded =
+                limit.msgs_per_second > 0 && window.msgs + msgs > limit.msgs_per_second;
+            let bytes_exceeded =
+                limit.bytes_per_second > 0 && window.bytes + bytes > limit.bytes_per_second;
+            if msgs_exceeded || bytes_exceeded {
+                drop(window);
+                self.throttled_times.fetch_add(1, Ordering::Relaxed);
+                return false;
+            }
+        }
+        window.msgs += msgs;
+        window.bytes += bytes;
+        true
+    }
+
+    fn is_exhausted(&self, limit: FlowLimit, now: u64) -> bool {
+        let window = self.window.lock();
+        if now.saturating_sub(window.window_start) >= WINDOW_MILLIS {
+            return false;
+        }
+        (limit.msgs_per_second > 0 && window.msgs >= limit.msgs_per_second)
+            || (limit.bytes_per_second > 0 && window.bytes >= limit.bytes_per_second)
+    }
+}
+
+/// Topic level produce and group level consume flow control.
+///
+/// Every topic/group owns a fixed one-second window. The default limits come from `BrokerConfig`,
+/// a topic overrides them with its `flow.put.*` attributes and a consumer group with its
+/// `flow.get.*` attributes.
+pub(crate) struct FlowController {
+    enable: bool,
+    topic_default_limit: FlowLimit,
+    group_default_limit: FlowLimit,
+    topic_counters: DashMap<CheetahString, FlowCounter>,
+    group_counters: DashMap<CheetahString, FlowCounter>,
+}
+
+impl FlowController {
+    pub fn new(broker_config: &BrokerConfig) -> Self {
+        Self {
+            enable: broker_config.enable_flow_control,
+            topic_default_limit: FlowLimit::new(
+                broker_config.topic_put_msgs_per_second,
+                broker_config.topic_put_bytes_per_second,
+            ),
+            group_default_limit: FlowLimit::new(
+                broker_config.group_get_msgs_per_second,
+                broker_config.group_get_bytes_per_second,
+            ),
+            topic_counters: DashMap::new(),
+            group_counters: DashMap::new(),
+        }
+    }
+
+    #[inline]
+    pub fn is_enable(&self) -> bool {
+        self.enable
+    }
+
+    /// Returns the put limit of a topic with the `attributes`.
+    pub fn topic_limit(&self, attributes: &HashMap<CheetahString, CheetahString>) -> FlowLimit {
+        FlowLimit::new(
+            attribute_override(
+                attributes,
+                TopicAttributes::topic_put_msgs_per_second_attribute(),
+            )
+            .unwrap_or(self.topic_default_limit.msgs_per_second),
+            attribute_override(
+                attributes,
+                TopicAttributes::topic_put_bytes_per_second_attribute(),
+            )
+            .unwrap_or(self.topic_default_limit.bytes_per_second),
+        )
+    }
+
+    /// Returns the get limit of a consumer group with the `attributes`.
+    pub fn group_limit(&self, attributes: &HashMap<CheetahString, CheetahString>) -> FlowLimit {
+        FlowLimit::new(
+            attribute_override(
+                attributes,
+                SubscriptionGroupAttributes::group_get_msgs_per_second_attribute(),
+            )
+            .unwrap_or(self.group_default_limit.msgs_per_second),
+            attribute_override(
+                attributes,
+                SubscriptionGroupAttributes::group_get_bytes_per_second_attribute(),
+            )
+            .unwrap_or(self.group_default_limit.bytes_per_second),
+        )
+    }
+
+    /// Returns `false` if putting `msgs`/`bytes` to `topic` exceeds `limit`.
+    pub fn try_acquire_topic_put(
+        &self,
+        topic: &CheetahString,
+        limit: FlowLimit,
+        msgs: u64,
+        bytes: u64,
+    ) -> bool {
+        self.try_acquire_topic_put_at(topic, limit, msgs, bytes, get_current_millis())
+    }
+
+    fn try_acquire_topic_put_at(
+        &self,
+        topic: &CheetahString,
+        limit: FlowLimit,
+        msgs: u64,
+        bytes: u64,
+        now: u64,
+    ) -> bool {
+        if !self.enable || limit.is_unlimited() {
+            return true;
+        }
+        self.topic_counters
+            .entry(topic.clone())
+            .or_default()
+            .acquire(limit, msgs, bytes, now, true)
+    }
+
+    /// Returns `true` if `group` has already used up `limit` in the current window.
+    pub fn is_group_get_throttled(&self, group: &CheetahString, limit: FlowLimit) -> bool {
+        self.is_group_get_throttled_at(group, limit, get_current_millis())
+    }
+
+    fn is_group_get_throttled_at(&self, group: &CheetahString, limit: FlowLimit, now: u64) -> bool {
+        if !self.enable || limit.is_unlimited() {
+            return false;
+        }
+        match self.group_counters.get(group) {
+            Some(counter) => {
+                let exhausted = counter.is_exhausted(limit, now);
+                if exhausted {
+                    counter.throttled_times.fetch_add(1, Ordering::Relaxed);
+                }
+                exhausted
+            }
+            None => false,
+        }
+    }
+
+    /// Accounts messages already read by `group`, the amount is only known after the read.
+    pub fn record_group_get(&self, group: &CheetahString, limit: FlowLimit, msgs: u64, bytes: u64) {
+        self.record_group_get_at(group, limit, msgs, bytes, get_current_millis());
+    }
+
+    fn record_group_get_at(
+        &self,
+        group: &CheetahString,
+        limit: FlowLimit,
+        msgs: u64,
+        bytes: u64,
+        now: u64,
+    ) {
+        if !self.enable || limit.is_unlimited() || msgs == 0 {
+            return;
+        }
+        self.group_counters
+            .entry(group.clone())
+            .or_default()
+            .acquire(limit, msgs, bytes, now, false);
+    }
+
+    /// Returns how many puts were refused by topic limits, over all topics.
+    pub fn topic_throttled_times(&self) -> u64 {
+        self.topic_counters
+            .iter()
+            .map(|counter| counter.throttled_times.load(Ordering::Relaxed))
+            .sum()
+    }
+
+    /// Returns how many pulls and pops were refused by group limits, over all groups.
+    pub fn group_throttled_times(&self) -> u64 {
+        self.group_counters
+            .iter()
+            .map(|counter| counter.throttled_times.load(Ordering::Relaxed))
+            .sum()
+    }
+
+    pub fn remove_topic(&self, topic: &CheetahString) {
+        self.topic_counters.remove(topic);
+    }
+
+    pub fn remove_group(&self, group: &CheetahString) {
+        self.group_counters.remove(group);
+    }
+}
+
+/// Reads a limit from `attributes`, negative values leave the broker default in place.
+fn attribute_override(
+    attributes: &HashMap<CheetahString, CheetahString>,
+    attribute: &LongRangeAttribute,
+) -> Option<u64> {
+    attributes
+        .get(attribute.name())
+        .and_then(|value| value.parse::<i64>().ok())
+        .and_then(|value| u64::try_from(value).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flow_controller(topic_msgs: u64, topic_bytes: u64, group_msgs: u64) -> FlowController {
+        let broker_config = BrokerConfig {
+            enable_flow_control: true,
+            topic_put_msgs_per_second: topic_msgs,
+            topic_put_bytes_per_second: topic_bytes,
+            group_get_msgs_per_second: group_msgs,
+            ..Default::default()
+        };
+        FlowController::new(&broker_config)
+    }
+
+    #[test]
+    fn disabled_flow_control_never_throttles() {
+        let controller = FlowController::new(&BrokerConfig::default());
+        let topic = CheetahString::from_static_str("topic");
+        let limit = FlowLimit::new(1, 1);
+        for _ in 0..100 {
+            assert!(controller.try_acquire_topic_put_at(&topic, limit, 1, 1024, 0));
+        }
+        assert!(!controller.is_group_get_throttled_at(&topic, limit, 0));
+    }
+
+    #[test]
+    fn topic_put_is_throttled_by_msgs() {
+        let controller = flow_controller(2, 0, 0);
+        let topic = CheetahString::from_static_str("topic");
+        let limit = controller.topic_limit(&HashMap::new());
+        assert!(controller.try_acquire_topic_put_at(&topic, limit, 1, 10, 1000));
+        assert!(controller.try_acquire_topic_put_at(&topic, limit, 1, 10, 1100));
+        assert!(!controller.try_acquire_topic_put_at(&topic, limit, 1, 10, 1200));
+        assert_eq!(controller.topic_throttled_times(), 1);
+        // next window
+        assert!(controller.try_acquire_topic_put_at(&topic, limit, 1, 10, 2000));
+    }
+
+    #[test]
+    fn topic_put_is_throttled_by_bytes() {
+        let controller = flow_controller(0, 100, 0);
+        let topic = CheetahString::from_static_str("topic");
+        let limit = controller.topic_limit(&HashMap::new());
+        assert!(controller.try_acquire_topic_put_at(&topic, limit, 1, 60, 1000));
+        assert!(!controller.try_acquire_topic_put_at(&topic, limit, 1, 60, 1001));
+        assert!(controller.try_acquire_topic_put_at(&topic, limit, 1, 40, 1002));
+    }
+
+    #[test]
+    fn first_request_in_window_is_always_accepted() {
+        let controller = flow_controller(0, 100, 0);
+        let topic = CheetahString::from_static_str("topic");
+        let limit = controller.topic_limit(&HashMap::new());
+        assert!(controller.try_acquire_topic_put_at(&topic, limit, 1, 1000, 1000));
+        assert!(!controller.try_acquire_topic_put_at(&topic, limit, 1, 1, 1001));
+    }
+
+    #[test]
+    fn attributes_override_the_broker_limits() {
+        let controller = flow_controller(1, 100, 10);
+        let topic_attributes = HashMap::from([
+            (
+                CheetahString::from_static_str("flow.put.msgs.per.second"),
+                CheetahString::from_static_str("0"),
+            ),
+            (
+                CheetahString::from_static_str("flow.put.bytes.per.second"),
+                CheetahString::from_static_str("-1"),
+            ),
+        ]);
+        assert_eq!(
+            controller.topic_limit(&topic_attributes),
+            FlowLimit::new(0, 100)
+        );
+        let group_attributes = HashMap::from([(
+            CheetahString::from_static_str("flow.get.msgs.per.second"),
+            CheetahString::from_static_str("3"),
+        )]);
+        assert_eq!(
+            controller.group_limit(&group_attributes),
+            FlowLimit::new(3, 0)
+        );
+        assert_eq!(
+            controller.group_limit(&HashMap::new()),
+            FlowLimit::new(10, 0)
+        );
+    }
+
+    #[test]
+    fn group_get_is_throttled_after_quota_used() {
+        let controller = flow_controller(0, 0, 10);
+        let group = CheetahString::from_static_str("group");
+        let limit = controller.group_limit(&HashMap::new());
+        assert!(!controller.is_group_get_throttled_at(&group, limit, 1000));
+        controller.record_group_get_at(&group, limit, 10, 100, 1000);
+        assert!(controller.is_group_get_throttled_at(&group, limit, 1500));
+        assert_eq!(controller.group_throttled_times(), 1);
+        assert!(!controller.is_group_get_throttled_at(&group, limit, 2000));
+
+        controller.remove_group(&group);
+        assert_eq!(controller.group_throttled_times(), 0);
+    }
+}
@@ -0,0 +1,554 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use cheetah_string::CheetahString;
+use rocketmq_remoting::auth::authentication_provider::AUTHORIZATION;
+use rocketmq_remoting::clients::Client;
+use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::code::response_code::ResponseCode;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_remoting::request_processor::default_request_processor::DefaultRemotingRequestProcessor;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+use tracing::error;
+use tracing::info;
+use tracing::warn;
+
+const MAX_REQUEST_HEAD_SIZE: usize = 8 * 1024;
+const MAX_HEADERS: usize = 32;
+/// Time a client has to send its request head, so idle connections do not pile up.
+const REQUEST_HEAD_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Paths served by the HTTP admin endpoint and the admin requests they are forwarded as. Only
+/// read-only requests are listed, so the endpoint cannot change the broker.
+const ROUTES: &[(&str, RequestCode)] = &[
+    ("/topics", RequestCode::GetAllTopicConfig),
+    ("/consume-stats", RequestCode::GetConsumeStats),
+    ("/runtime-info", RequestCode::GetBrokerRuntimeInfo),
+    ("/pop-stats", RequestCode::GetPopStats),
+    ("/ready", RequestCode::GetBrokerReadiness),
+];
+
+/// HTTP/JSON facade over the read-only admin requests of a broker, for dashboards that cannot
+/// speak the remoting protocol.
+///
+/// A `GET` of one of the [`ROUTES`] is sent to the remoting port of the broker as the matching
+/// admin request, with the query parameters as its header fields, e.g.
+/// `GET /consume-stats?consumerGroup=group&topic=topic`. The JSON body of a successful response
+/// is answered as is, a failed one as `{"code":..,"remark":..}`.
+///
+/// The `Authorization` header of a request is forwarded as the `Authorization` field of the admin
+/// request, so the broker authenticates the HTTP caller like any remoting client.
+pub(crate) struct HttpAdminServer {
+    bind_address: String,
+    listen_port: u32,
+    broker_addr: SocketAddr,
+    timeout_millis: u64,
+    require_authorization: bool,
+    request_head_timeout: Duration,
+}
+
+impl HttpAdminServer {
+    /// Creates the endpoint listening on `bind_address` and forwarding to `broker_addr`. With
+    /// `require_authorization` requests without an `Authorization` header are rejected.
+    pub fn new(
+        bind_address: String,
+        listen_port: u32,
+        broker_addr: SocketAddr,
+        timeout_millis: u64,
+        require_authorization: bool,
+    ) -> Self {
+        Self {
+            bind_address,
+            listen_port,
+            broker_addr,
+            timeout_millis,
+            require_authorization,
+            request_head_timeout: REQUEST_HEAD_TIMEOUT,
+        }
+    }
+
+    pub async fn run_until(self, shutdown: impl Future) {
+        let listener =
+            match TcpListener::bind((self.bind_address.as_str(), self.listen_port as u16)).await {
+                Ok(listener) => listener,
+                Err(e) => {
+                    error!(
+                        "bind http admin to {}:{} failed: {}",
+                        self.bind_address, self.listen_port, e
+                    );
+                    return;
+                }
+            };
+        info!(
+            "http admin listening on {}:{}",
+            self.bind_address, self.listen_port
+        );
+        self.serve(listener, shutdown).await;
+    }
+
+    async fn serve(self, listener: TcpListener, shutdown: impl Future) {
+        tokio::pin!(shutdown);
+        loop {
+            let stream = tokio::select! {
+                _ = &mut shutdown => break,
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        warn!("accept http admin connection failed: {}", e);
+                        continue;
+                    }
+                },
+            };
+            let broker_addr = self.broker_addr;
+            let timeout_millis = self.timeout_millis;
+            let require_authorization = self.require_authorization;
+            let request_head_timeout = self.request_head_timeout;
+            tokio::spawn(async move {
+                if let Err(e) = handle_connection(
+                    stream,
+                    broker_addr,
+                    timeout_millis,
+                    require_authorization,
+                    request_head_timeout,
+                )
+                .await
+                {
+                    warn!("serve http admin request failed: {}", e);
+                }
+            });
+        }
+        info!("http admin on port {} stopped", self.listen_port);
+    }
+}
+
+/// Serves the single request of a connection, the connection is closed afterwards.
+async fn handle_connection(
+    mut stream: TcpStream,
+    broker_addr: SocketAddr,
+    timeout_millis: u64,
+    require_authorization: bool,
+    request_head_timeout: Duration,
+) -> std::io::Result<()> {
+    let head =
+        match tokio::time::timeout(request_head_timeout, read_request_head(&mut stream)).await {
+            Ok(head) => head?,
+            Err(_) => Err(error_response(
+                "408 Request Timeout",
+                "request head not received in time",
+            )),
+        };
+    let (status, body) = match head {
+        Ok(RequestHead {
+            authorization: None,
+            ..
+        }) if require_authorization => {
+            error_response("401 Unauthorized", "missing Authorization header")
+        }
+        Ok(head) => match route(&head.target) {
+            Ok(mut request) => {
+                if let Some(authorization) = head.authorization {
+                    request.add_ext_field(AUTHORIZATION, authorization);
+                }
+                forward(broker_addr, request, timeout_millis).await
+            }
+            Err(rejection) => rejection,
+        },
+        Err(rejection) => rejection,
+    };
+    let head = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: \
+         close\r\n\r\n",
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(&body).await?;
+    stream.shutdown().await
+}
+
+type Rejection = (&'static str, Vec<u8>);
+
+/// The parts of a `GET` request head an admin request is built from.
+struct RequestHead {
+    target: String,
+    authorization: Option<String>,
+}
+
+/// Reads the request head of a `GET` request.
+async fn read_request_head(
+    stream: &mut TcpStream,
+) -> std::io::Result<Result<RequestHead, Rejection>> {
+    let mut buf = Vec::with_capacity(1024);
+    loop {
+        let mut headers = [httparse::EMPTY_HEADER; MAX_HEADERS];
+        let mut request = httparse::Request::new(&mut headers);
+        match request.parse(&buf) {
+            Ok(httparse::Status::Complete(_)) => {
+                if request.method != Some("GET") {
+                    return Ok(Err(error_response(
+                        "405 Method Not Allowed",
+                        "only GET is supported",
+                    )));
+                }
+                let authorization = match request
+                    .headers
+                    .iter()
+                    .find(|header| header.name.eq_ignore_ascii_case(AUTHORIZATION))
+                    .map(|header| std::str::from_utf8(header.value))
+                {
+                    Some(Ok(authorization)) => Some(authorization.to_string()),
+                    Some(Err(_)) => {
+                        return Ok(Err(error_response(
+                            "400 Bad Request",
+                            "malformed Authorization header",
+                        )));
+                    }
+                    None => None,
+                };
+                return Ok(Ok(RequestHead {
+                    target: request.path.unwrap_or("/").to_string(),
+                    authorization,
+                }));
+            }
+            Ok(httparse::Status::Partial) if buf.len() < MAX_REQUEST_HEAD_SIZE => {}
+            Ok(httparse::Status::Partial) => {
+                return Ok(Err(error_response(
+                    "431 Request Header Fields Too Large",
+                    "request head too large",
+                )));
+            }
+            Err(e) => return Ok(Err(error_response("400 Bad Request", &e.to_string()))),
+        }
+        if stream.read_buf(&mut buf).await? == 0 {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+    }
+}
+
+/// Builds the admin request a request target is forwarded as.
+fn route(target: &str) -> Result<RemotingCommand, Rejection> {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let Some((_, request_code)) = ROUTES.iter().find(|(route, _)| *route == path) else {
+        return Err(error_response("404 Not Found", &format!("no route {path}")));
+    };
+    let ext_fields = parse_query(query)
+        .ok_or_else(|| error_response("400 Bad Request", "malformed query string"))?;
+    Ok(RemotingCommand::create_remoting_command(*request_code).set_ext_fields(ext_fields))
+}
+
+async fn forward(
+    broker_addr: SocketAddr,
+    request: RemotingCommand,
+    timeout_millis: u64,
+) -> (&'static str, Vec<u8>) {
+    let response = match Client::connect(broker_addr, DefaultRemotingRequestProcessor, None).await {
+        Ok(mut client) => client.send_read(request, timeout_millis).await,
+        Err(e) => Err(e),
+    };
+    match response {
+        Ok(response) if response.code() == ResponseCode::Success as i32 => {
+            let body = response
+                .body()
+                .as_ref()
+                .map_or_else(|| b"{}".to_vec(), |body| body.to_vec());
+            ("200 OK", body)
+        }
+        // a broker that is not ready yet, probes take it as such
+        Ok(response) if response.code() == ResponseCode::ServiceNotAvailable as i32 => (
+            "503 Service Unavailable",
+            error_body(
+                response.code(),
+                response.remark().map_or("", |remark| remark.as_str()),
+            ),
+        ),
+        Ok(response) => (
+            "500 Internal Server Error",
+            error_body(
+                response.code(),
+                response.remark().map_or("", |remark| remark.as_str()),
+            ),
+        ),
+        Err(e) => error_response("502 Bad Gateway", &e.to_string()),
+    }
+}
+
+fn error_response(status: &'static str, remark: &str) -> Rejection {
+    (status, error_body(-1, remark))
+}
+
+fn error_body(code: i32, remark: &str) -> Vec<u8> {
+    serde_json::json!({ "code": code, "remark": remark })
+        .to_string()
+        .into_bytes()
+}
+
+/// Splits `a=1&b=2` into its percent decoded name/value pairs.
+fn parse_query(query: &str) -> Option<HashMap<CheetahString, CheetahString>> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            Some((
+                CheetahString::from_string(percent_decode(name)?),
+                CheetahString::from_string(percent_decode(value)?),
+            ))
+        })
+        .collect()
+}
+
+fn percent_decode(value: &str) -> Option<String> {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+                decoded.push(u8::from_str_radix(hex, 16).ok()?);
+                i += 3;
+            }
+            b'+' => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            byte => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use rocketmq_remoting::auth::authentication_provider::authentication_failed;
+    use rocketmq_remoting::auth::authentication_provider::AuthenticationProvider;
+    use rocketmq_remoting::auth::authentication_provider::Credentials;
+    use rocketmq_remoting::auth::authentication_provider::Principal;
+    use rocketmq_remoting::auth::authentication_provider::RequestContext;
+    use rocketmq_remoting::auth::authentication_rpc_hook::AuthenticationRpcHook;
+    use rocketmq_remoting::request_processor::request_processor_table::RequestProcessorTable;
+    use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
+    use rocketmq_remoting::runtime::processor::RequestProcessor;
+    use rocketmq_remoting::runtime::RPCHook;
+
+    use super::*;
+
+    #[test]
+    fn query_is_percent_decoded() {
+        let query = parse_query("consumerGroup=%25RETRY%25group&topic=a+b&flag").unwrap();
+        assert_eq!(query["consumerGroup"], "%RETRY%group");
+        assert_eq!(query["topic"], "a b");
+        assert_eq!(query["flag"], "");
+        assert!(parse_query("topic=%zz").is_none());
+    }
+
+    #[test]
+    fn only_read_only_routes_are_served() {
+        let request = route("/consume-stats?consumerGroup=group").unwrap();
+        assert_eq!(request.code(), RequestCode::GetConsumeStats as i32);
+        assert_eq!(
+            request.ext_fields().unwrap()["consumerGroup"].as_str(),
+            "group"
+        );
+        assert!(matches!(
+            route("/delete-topic?topic=topic"),
+            Err(("404 Not Found", _))
+        ));
+    }
+
+    #[derive(Clone)]
+    struct TopicEchoProcessor;
+
+    impl RequestProcessor for TopicEchoProcessor {
+        async fn process_request(
+            &mut self,
+            _channel: rocketmq_remoting::net::channel::Channel,
+            _ctx: ConnectionHandlerContext,
+            request: RemotingCommand,
+        ) -> rocketmq_error::RocketMQResult<Option<RemotingCommand>> {
+            let topic = request.ext_fields().unwrap()["topic"].clone();
+            Ok(Some(
+                RemotingCommand::create_response_command()
+                    .set_body(format!("{{\"topic\":\"{topic}\"}}")),
+            ))
+        }
+    }
+
+    #[derive(Clone)]
+    struct NotReadyProcessor;
+
+    impl RequestProcessor for NotReadyProcessor {
+        async fn process_request(
+            &mut self,
+            _channel: rocketmq_remoting::net::channel::Channel,
+            _ctx: ConnectionHandlerContext,
+            _request: RemotingCommand,
+        ) -> rocketmq_error::RocketMQResult<Option<RemotingCommand>> {
+            Ok(Some(
+                RemotingCommand::create_response_command_with_code_remark(
+                    ResponseCode::ServiceNotAvailable,
+                    "broker is not ready",
+                ),
+            ))
+        }
+    }
+
+    async fn http_get(http_addr: SocketAddr, target: &str) -> String {
+        http_get_with_headers(http_addr, target, "").await
+    }
+
+    async fn http_get_with_headers(http_addr: SocketAddr, target: &str, headers: &str) -> String {
+        let mut stream = TcpStream::connect(http_addr).await.unwrap();
+        stream
+            .write_all(format!("GET {target} HTTP/1.1\r\nHost: broker\r\n{headers}\r\n").as_bytes())
+            .await
+            .unwrap();
+        let mut response = String::new();
+        tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            stream.read_to_string(&mut response),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn get_is_forwarded_to_the_broker() {
+        let broker_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let broker_addr = broker_listener.local_addr().unwrap();
+        let mut table = RequestProcessorTable::new();
+        table.register_processor(RequestCode::GetAllTopicConfig, TopicEchoProcessor);
+        table.register_processor(RequestCode::GetBrokerReadiness, NotReadyProcessor);
+        tokio::spawn(rocketmq_remoting::remoting_server::server::run(
+            broker_listener,
+            std::future::pending::<()>(),
+            table,
+            None,
+            vec![],
+            None,
+        ));
+
+        let http_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let http_addr = http_listener.local_addr().unwrap();
+        let server = HttpAdminServer::new("127.0.0.1".to_string(), 0, broker_addr, 3000, false);
+        tokio::spawn(server.serve(http_listener, std::future::pending::<()>()));
+
+        let response = http_get(http_addr, "/topics?topic=TopicTest").await;
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with("{\"topic\":\"TopicTest\"}"));
+
+        let response = http_get(http_addr, "/ready").await;
+        assert!(response.starts_with("HTTP/1.1 503 Service Unavailable\r\n"));
+    }
+
+    struct TokenProvider;
+
+    impl AuthenticationProvider for TokenProvider {
+        fn validate(
+            &self,
+            credentials: &Credentials,
+            _request_context: &RequestContext<'_>,
+        ) -> rocketmq_error::RocketMQResult<Principal> {
+            match credentials {
+                Credentials::BearerToken(token) if token == "admin-token" => {
+                    Ok(Principal::new("admin"))
+                }
+                _ => Err(authentication_failed("invalid token")),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn forwarded_requests_carry_the_credentials_of_the_caller() {
+        let broker_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let broker_addr = broker_listener.local_addr().unwrap();
+        let mut table = RequestProcessorTable::new();
+        table.register_processor(RequestCode::GetAllTopicConfig, TopicEchoProcessor);
+        let authentication_hook: Box<dyn RPCHook> =
+            Box::new(AuthenticationRpcHook::new(Arc::new(TokenProvider)));
+        tokio::spawn(rocketmq_remoting::remoting_server::server::run(
+            broker_listener,
+            std::future::pending::<()>(),
+            table,
+            None,
+            vec![authentication_hook],
+            None,
+        ));
+
+        let http_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let http_addr = http_listener.local_addr().unwrap();
+        let server = HttpAdminServer::new("127.0.0.1".to_string(), 0, broker_addr, 3000, true);
+        tokio::spawn(server.serve(http_listener, std::future::pending::<()>()));
+
+        for (headers, status) in [
+            ("Authorization: Bearer admin-token\r\n", "200 OK"),
+            ("authorization: Bearer admin-token\r\n", "200 OK"),
+            (
+                "Authorization: Bearer other-token\r\n",
+                "500 Internal Server Error",
+            ),
+            ("", "401 Unauthorized"),
+        ] {
+            let response =
+                http_get_with_headers(http_addr, "/topics?topic=TopicTest", headers).await;
+            assert!(
+                response.starts_with(&format!("HTTP/1.1 {status}\r\n")),
+                "{response}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn connections_not_sending_their_request_head_are_closed() {
+        let http_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let http_addr = http_listener.local_addr().unwrap();
+        let mut server = HttpAdminServer::new(
+            "127.0.0.1".to_string(),
+            0,
+            "127.0.0.1:1".parse().unwrap(),
+            3000,
+            false,
+        );
+        server.request_head_timeout = std::time::Duration::from_millis(100);
+        tokio::spawn(server.serve(http_listener, std::future::pending::<()>()));
+
+        let mut stream = TcpStream::connect(http_addr).await.unwrap();
+        stream.write_all(b"GET /topics HTTP/1.1\r\n").await.unwrap();
+        let mut response = String::new();
+        tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            stream.read_to_string(&mut response),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert!(response.starts_with("HTTP/1.1 408 Request Timeout\r\n"));
+    }
+}
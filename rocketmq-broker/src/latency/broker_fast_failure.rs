@@ -14,16 +14,40 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
-use tracing::warn;
+use rocketmq_store::base::message_store::MessageStore;
+use tracing::info;
 
+/// Fails send requests fast while the store cannot take writes in time, instead of letting them
+/// pile up behind a stalled disk until the clients time out.
 pub struct BrokerFastFailure;
 
 impl BrokerFastFailure {
     pub fn start(&mut self) {
-        warn!("BrokerFastFailure started not implemented");
+        info!("BrokerFastFailure started");
     }
 
     pub fn shutdown(&mut self) {
-        warn!("BrokerFastFailure shutdown not implemented");
+        info!("BrokerFastFailure shutdown");
+    }
+
+    /// Returns the remark of the SYSTEM_BUSY response when a send request has to be rejected:
+    /// the commit log append lock has been held longer than `osPageCacheBusyTimeOutMills`, or
+    /// the transient store pool has no buffer left.
+    pub fn reject_send_request<MS: MessageStore>(&self, message_store: &MS) -> Option<String> {
+        if message_store.is_os_page_cache_busy() {
+            return Some(format!(
+                "[PCBUSY_CLEAN_QUEUE]broker busy, start flow control for a while, period in lock: \
+                 {}ms",
+                message_store.lock_time_millis()
+            ));
+        }
+        if message_store.is_transient_store_pool_deficient() {
+            return Some(
+                "[TRANSIENT_STORE_POOL_DEFICIENT]broker busy, no buffer left in transient store \
+                 pool, start flow control for a while"
+                    .to_string(),
+            );
+        }
+        None
     }
 }
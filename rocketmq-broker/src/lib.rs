@@ -22,19 +22,28 @@
 
 pub use broker_bootstrap::BrokerBootstrap;
 pub use broker_bootstrap::Builder;
+pub use broker_container::broker_container_config::BrokerContainerConfig;
+pub use broker_container::BrokerContainer;
 
 pub mod command;
 
+pub(crate) mod audit;
 pub(crate) mod broker;
 pub(crate) mod broker_bootstrap;
+pub(crate) mod broker_container;
 pub(crate) mod broker_path_config_helper;
 pub(crate) mod broker_runtime;
+pub(crate) mod broker_runtime_group;
 pub(crate) mod client;
 pub(crate) mod coldctr;
 pub(crate) mod controller;
+pub(crate) mod dedup;
+pub(crate) mod dledger;
 pub(crate) mod failover;
 pub(crate) mod filter;
+pub(crate) mod flow_control;
 pub(crate) mod hook;
+pub(crate) mod http_admin;
 pub(crate) mod latency;
 pub(crate) mod load_balance;
 pub(crate) mod long_polling;
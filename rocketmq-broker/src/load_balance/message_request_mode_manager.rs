@@ -19,6 +19,8 @@ use std::sync::Arc;
 
 use cheetah_string::CheetahString;
 use rocketmq_common::common::config_manager::ConfigManager;
+use rocketmq_common::common::message::message_enum::MessageRequestMode;
+use rocketmq_common::common::mix_all::RETRY_GROUP_TOPIC_PREFIX;
 use rocketmq_common::utils::serde_json_utils::SerdeJsonUtils;
 use rocketmq_remoting::protocol::body::set_message_request_mode_request_body::SetMessageRequestModeRequestBody;
 use rocketmq_store::config::message_store_config::MessageStoreConfig;
@@ -26,6 +28,7 @@ use tracing::info;
 
 use crate::broker_path_config_helper;
 
+#[derive(Clone)]
 pub(crate) struct MessageRequestModeManager {
     message_store_config: Arc<MessageStoreConfig>,
     message_request_mode_map: Arc<
@@ -72,6 +75,35 @@ impl MessageRequestModeManager {
         }
         None
     }
+
+    /// Returns the request mode `consumer_group` should use for `topic`: the mode set through
+    /// `SET_MESSAGE_REQUEST_MODE` if there is one, otherwise the broker defaults. Retry topics
+    /// are always consumed in pull mode.
+    pub fn resolve_message_request_mode(
+        &self,
+        topic: &CheetahString,
+        consumer_group: &CheetahString,
+        default_mode: MessageRequestMode,
+        default_pop_share_queue_num: i32,
+    ) -> SetMessageRequestModeRequestBody {
+        if let Some(body) = self.get_message_request_mode(topic, consumer_group) {
+            return body;
+        }
+        let mut body = SetMessageRequestModeRequestBody {
+            topic: topic.clone(),
+            consumer_group: consumer_group.clone(),
+            ..Default::default()
+        };
+        body.mode = if topic.starts_with(RETRY_GROUP_TOPIC_PREFIX) {
+            MessageRequestMode::Pull
+        } else {
+            default_mode
+        };
+        if body.mode == MessageRequestMode::Pop {
+            body.pop_share_queue_num = default_pop_share_queue_num;
+        }
+        body
+    }
 }
 
 impl ConfigManager for MessageRequestModeManager {
@@ -112,7 +144,6 @@ mod tests {
     use std::sync::Arc;
 
     use cheetah_string::CheetahString;
-    use rocketmq_common::common::message::message_enum::MessageRequestMode;
     use rocketmq_remoting::protocol::body::set_message_request_mode_request_body::SetMessageRequestModeRequestBody;
     use rocketmq_store::config::message_store_config::MessageStoreConfig;
 
@@ -191,4 +222,49 @@ mod tests {
         assert!(result.is_some());
         assert_eq!(result.unwrap().mode, MessageRequestMode::Pull);
     }
+
+    #[test]
+    fn resolve_message_request_mode_falls_back_to_defaults() {
+        let manager = MessageRequestModeManager::new(Arc::new(MessageStoreConfig::default()));
+        let group = CheetahString::from("test_group");
+
+        let body = manager.resolve_message_request_mode(
+            &CheetahString::from("test_topic"),
+            &group,
+            MessageRequestMode::Pop,
+            3,
+        );
+        assert_eq!(body.mode, MessageRequestMode::Pop);
+        assert_eq!(body.pop_share_queue_num, 3);
+
+        let body = manager.resolve_message_request_mode(
+            &CheetahString::from("%RETRY%test_group"),
+            &group,
+            MessageRequestMode::Pop,
+            3,
+        );
+        assert_eq!(body.mode, MessageRequestMode::Pull);
+        assert_eq!(body.pop_share_queue_num, 0);
+    }
+
+    #[test]
+    fn resolve_message_request_mode_prefers_configured_mode() {
+        let manager = MessageRequestModeManager::new(Arc::new(MessageStoreConfig::default()));
+        let topic = CheetahString::from("test_topic");
+        let group = CheetahString::from("test_group");
+        manager.set_message_request_mode(
+            topic.clone(),
+            group.clone(),
+            SetMessageRequestModeRequestBody {
+                topic: topic.clone(),
+                consumer_group: group.clone(),
+                mode: MessageRequestMode::Pop,
+                pop_share_queue_num: 1,
+            },
+        );
+        let body =
+            manager.resolve_message_request_mode(&topic, &group, MessageRequestMode::Pull, 0);
+        assert_eq!(body.mode, MessageRequestMode::Pop);
+        assert_eq!(body.pop_share_queue_num, 1);
+    }
 }
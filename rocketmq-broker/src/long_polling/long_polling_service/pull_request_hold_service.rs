@@ -106,6 +106,8 @@ where
 
     pub fn shutdown(&mut self) {
         self.shutdown.notify_waiters();
+        // answer the held requests now instead of leaving the consumers to time out
+        self.wakeup_all_pull_requests();
     }
     pub fn suspend_pull_request(&self, topic: &str, queue_id: i32, mut pull_request: PullRequest) {
         let key = build_key(topic, queue_id);
@@ -230,14 +232,15 @@ where
     }
 
     pub async fn notify_master_online(&self) {
+        info!("notify master online, wakeup all held pull requests");
+        self.wakeup_all_pull_requests();
+    }
+
+    /// Processes every held pull request again, whether or not new messages arrived.
+    pub fn wakeup_all_pull_requests(&self) {
         for (_, mpr) in self.pull_request_table.read().iter() {
             if let Some(request_list) = mpr.clone_list_and_clear() {
                 for request in request_list {
-                    info!(
-                        "notify master online, wakeup {}",
-                        //  request.client_channel(),
-                        request.request_command()
-                    );
                     let pull_message_this = self.pull_message_processor.clone();
                     self.pull_message_processor.execute_request_when_wakeup(
                         pull_message_this,
@@ -20,6 +20,7 @@ use std::collections::HashSet;
 use std::fmt;
 use std::net::SocketAddr;
 use std::sync::atomic::AtomicI64;
+use std::sync::atomic::AtomicU64;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
@@ -51,6 +52,9 @@ pub(crate) struct ConsumerOffsetManager {
     pub(crate) broker_config: Arc<BrokerConfig>,
     consumer_offset_wrapper: ConsumerOffsetWrapper,
     message_store: Option<ArcMut<LocalFileMessageStore>>,
+    // bumped on every change of the offsets, persisted_version trails it until they are written
+    change_version: Arc<AtomicU64>,
+    persisted_version: Arc<AtomicU64>,
 }
 
 impl ConsumerOffsetManager {
@@ -68,6 +72,8 @@ impl ConsumerOffsetManager {
                 version_change_counter: Arc::new(AtomicI64::new(0)),
             },
             message_store,
+            change_version: Arc::new(AtomicU64::new(0)),
+            persisted_version: Arc::new(AtomicU64::new(0)),
         }
     }
     pub fn set_message_store(&mut self, message_store: Option<ArcMut<LocalFileMessageStore>>) {
@@ -88,11 +94,57 @@ impl ConsumerOffsetManager {
                 }
             }
         }
+        if !keys_to_remove.is_empty() {
+            self.mark_dirty();
+        }
         for key in keys_to_remove {
             offset_table.remove(&key);
         }
     }
 
+    pub fn remove_offset(&self, group: &CheetahString) {
+        let mut offset_table = self.consumer_offset_wrapper.offset_table.write();
+        let mut removed = false;
+        offset_table.retain(|topic_at_group, offsets| {
+            let matched = topic_at_group
+                .split_once(TOPIC_GROUP_SEPARATOR)
+                .is_some_and(|(_, key_group)| key_group == group.as_str());
+            if matched {
+                warn!("Clean group's offset, {}, {:?}", topic_at_group, offsets);
+                removed = true;
+            }
+            !matched
+        });
+        if removed {
+            self.mark_dirty();
+        }
+    }
+
+    /// Removes the offsets of the topic@group entries `retain` rejects, `retain` is called with
+    /// the topic, the group and the offsets of each entry. Returns the removed keys.
+    pub fn prune_offsets(
+        &self,
+        retain: impl Fn(&CheetahString, &CheetahString, &HashMap<i32, i64>) -> bool,
+    ) -> Vec<CheetahString> {
+        let mut offset_table = self.consumer_offset_wrapper.offset_table.write();
+        let mut pruned = Vec::new();
+        offset_table.retain(|topic_at_group, offsets| {
+            let Some((topic, group)) = topic_at_group.split_once(TOPIC_GROUP_SEPARATOR) else {
+                return true;
+            };
+            if retain(&topic.into(), &group.into(), offsets) {
+                return true;
+            }
+            warn!("remove topic offset, {}", topic_at_group);
+            pruned.push(topic_at_group.clone());
+            false
+        });
+        if !pruned.is_empty() {
+            self.mark_dirty();
+        }
+        pruned
+    }
+
     pub fn which_group_by_topic(&self, topic: &str) -> HashSet<CheetahString> {
         let read_guard = self.consumer_offset_wrapper.offset_table.read();
         let mut groups = HashSet::new();
@@ -119,6 +171,8 @@ impl ConsumerOffsetManager {
         let mut write_guard = self.consumer_offset_wrapper.offset_table.write();
         let map = write_guard.entry(key.clone()).or_default();
         let store_offset = map.insert(queue_id, offset);
+        drop(write_guard);
+        self.mark_dirty();
         if let Some(store_offset) = store_offset {
             if offset < store_offset {
                 warn!(
@@ -236,6 +290,77 @@ impl ConfigManager for ConsumerOffsetManager {
 
 #[allow(unused_variables)]
 impl ConsumerOffsetManager {
+    pub fn get_data_version(&self) -> DataVersion {
+        self.consumer_offset_wrapper.data_version.as_ref().clone()
+    }
+
+    /// Applies the consumer offsets of another broker in the same group, as long as their data
+    /// version is not older than the local one. Returns whether the offsets were applied.
+    pub fn sync_consumer_offset(&self, json_string: &str) -> bool {
+        let wrapper = match SerdeJsonUtils::from_json_str::<ConsumerOffsetWrapper>(json_string) {
+            Ok(wrapper) => wrapper,
+            Err(e) => {
+                warn!("decode synced consumer offset failed: {}", e);
+                return false;
+            }
+        };
+        if self
+            .get_data_version()
+            .compare(wrapper.data_version.as_ref())
+            .is_gt()
+        {
+            return false;
+        }
+        self.consumer_offset_wrapper
+            .offset_table
+            .write()
+            .extend(wrapper.offset_table.read().clone());
+        self.consumer_offset_wrapper
+            .data_version
+            .mut_from_ref()
+            .assign_new_one(wrapper.data_version.as_ref());
+        self.persist_changes();
+        true
+    }
+
+    fn mark_dirty(&self) {
+        self.change_version.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Returns whether offsets changed since they were last persisted.
+    pub fn is_dirty(&self) -> bool {
+        self.change_version.load(Ordering::Acquire)
+            != self.persisted_version.load(Ordering::Acquire)
+    }
+
+    /// Persists the offsets and returns whether they were written. Changes made while writing
+    /// keep the manager dirty, a failed write leaves every change to the next call.
+    fn persist_changes(&self) -> bool {
+        let version = self.change_version.load(Ordering::Acquire);
+        if !self.persist() {
+            return false;
+        }
+        self.persisted_version.fetch_max(version, Ordering::AcqRel);
+        true
+    }
+
+    /// Persists the offsets on a blocking thread when they changed since the last call, so
+    /// brokers with large but idle offset tables skip rewriting them. Returns whether the
+    /// offsets were written.
+    pub async fn persist_if_dirty(&self) -> bool {
+        if !self.is_dirty() {
+            return false;
+        }
+        let consumer_offset_manager = self.clone();
+        match tokio::task::spawn_blocking(move || consumer_offset_manager.persist_changes()).await {
+            Ok(persisted) => persisted,
+            Err(e) => {
+                warn!("persist changed consumer offsets failed: {}", e);
+                false
+            }
+        }
+    }
+
     pub fn commit_pull_offset(
         &self,
         _client_host: SocketAddr,
@@ -251,6 +376,7 @@ impl ConsumerOffsetManager {
             .entry(key)
             .or_default()
             .insert(queue_id, offset);
+        self.mark_dirty();
     }
 
     pub fn query_then_erase_reset_offset(
@@ -261,11 +387,12 @@ impl ConsumerOffsetManager {
     ) -> Option<i64> {
         let key = format!("{topic}{TOPIC_GROUP_SEPARATOR}{group}");
         let mut write_guard = self.consumer_offset_wrapper.reset_offset_table.write();
-        let offset_table = write_guard.get_mut(key.as_str());
-        match offset_table {
-            None => None,
-            Some(value) => value.remove(&queue_id),
+        let offset = write_guard.get_mut(key.as_str())?.remove(&queue_id);
+        drop(write_guard);
+        if offset.is_some() {
+            self.mark_dirty();
         }
+        offset
     }
 }
 
@@ -442,3 +569,114 @@ impl<'de> Deserialize<'de> for ConsumerOffsetWrapper {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(manager: &ConsumerOffsetManager, group: &str, topic: &str, offset: i64) {
+        manager.commit_offset(
+            CheetahString::from_static_str("127.0.0.1"),
+            &CheetahString::from(group),
+            &CheetahString::from(topic),
+            0,
+            offset,
+        );
+    }
+
+    fn mark_persisted(manager: &ConsumerOffsetManager) {
+        manager.persisted_version.store(
+            manager.change_version.load(Ordering::Acquire),
+            Ordering::Release,
+        );
+    }
+
+    #[test]
+    fn changed_offsets_mark_the_manager_dirty() {
+        let manager = ConsumerOffsetManager::new(Arc::new(BrokerConfig::default()), None);
+        assert!(!manager.is_dirty());
+
+        commit(&manager, "group_a", "topic_a", 10);
+        assert!(manager.is_dirty());
+
+        mark_persisted(&manager);
+        manager.remove_offset(&CheetahString::from_static_str("group_b"));
+        assert!(!manager.is_dirty());
+        manager.remove_offset(&CheetahString::from_static_str("group_a"));
+        assert!(manager.is_dirty());
+
+        mark_persisted(&manager);
+        manager.commit_pull_offset(
+            "127.0.0.1:10911".parse().unwrap(),
+            &CheetahString::from_static_str("group_a"),
+            &CheetahString::from_static_str("topic_a"),
+            0,
+            5,
+        );
+        assert!(manager.is_dirty());
+    }
+
+    #[tokio::test]
+    async fn failed_persist_keeps_the_changes_dirty() {
+        let store_dir = tempfile::tempdir().unwrap();
+        // a file where the config directory should be makes every write fail
+        let blocked_root = store_dir.path().join("blocked");
+        std::fs::write(&blocked_root, b"").unwrap();
+        let mut manager = ConsumerOffsetManager::new(
+            Arc::new(BrokerConfig {
+                store_path_root_dir: blocked_root.to_string_lossy().into_owned().into(),
+                ..BrokerConfig::default()
+            }),
+            None,
+        );
+        commit(&manager, "group_a", "topic_a", 10);
+        assert!(!manager.persist_if_dirty().await);
+        assert!(manager.is_dirty());
+
+        manager.broker_config = Arc::new(BrokerConfig {
+            store_path_root_dir: store_dir
+                .path()
+                .join("store")
+                .to_string_lossy()
+                .into_owned()
+                .into(),
+            ..BrokerConfig::default()
+        });
+        assert!(manager.persist_if_dirty().await);
+        assert!(!manager.is_dirty());
+        assert!(!manager.persist_if_dirty().await);
+    }
+
+    #[test]
+    fn prune_offsets_removes_rejected_entries() {
+        let manager = ConsumerOffsetManager::new(Arc::new(BrokerConfig::default()), None);
+        commit(&manager, "group_a", "topic_a", 10);
+        commit(&manager, "group_b", "topic_a", 20);
+        commit(&manager, "group_a", "topic_b", 30);
+        mark_persisted(&manager);
+
+        let pruned =
+            manager.prune_offsets(|topic, group, _| topic != "topic_a" || group != "group_b");
+        assert_eq!(
+            pruned,
+            vec![CheetahString::from_static_str("topic_a@group_b")]
+        );
+        assert!(manager.is_dirty());
+        assert_eq!(
+            manager.query_offset(
+                &CheetahString::from_static_str("group_b"),
+                &CheetahString::from_static_str("topic_a"),
+                0
+            ),
+            -1
+        );
+        assert_eq!(
+            manager.query_offset(
+                &CheetahString::from_static_str("group_a"),
+                &CheetahString::from_static_str("topic_a"),
+                0
+            ),
+            10
+        );
+    }
+}
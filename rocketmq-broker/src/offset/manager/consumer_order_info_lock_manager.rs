@@ -14,11 +14,137 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
 
+use cheetah_string::CheetahString;
+use rocketmq_common::TimeUtils::get_current_millis;
+use rocketmq_rust::ArcMut;
+use rocketmq_store::base::message_store::MessageStore;
+use tokio::task::AbortHandle;
+use tracing::error;
+
+use crate::broker_runtime::BrokerRuntimeInner;
 use crate::offset::manager::consumer_order_info_manager::ConsumerOrderInfoWrapper;
+use crate::offset::manager::consumer_order_info_manager::OrderInfo;
+use crate::offset::manager::consumer_order_info_manager::TOPIC_GROUP_SEPARATOR;
+
+type LockKey = (
+    CheetahString, /* topic */
+    CheetahString, /* group */
+    i32,
+);
+
+/// Wakes up the pop long polling requests of an orderly consumer group once the order lock of a
+/// queue is released, instead of letting them wait for the polling timeout.
+pub(crate) struct ConsumerOrderInfoLockManager<MS> {
+    broker_runtime_inner: ArcMut<BrokerRuntimeInner<MS>>,
+    timeout_map: Arc<parking_lot::Mutex<HashMap<LockKey, AbortHandle>>>,
+}
+
+impl<MS: MessageStore> ConsumerOrderInfoLockManager<MS> {
+    pub fn new(broker_runtime_inner: ArcMut<BrokerRuntimeInner<MS>>) -> Self {
+        Self {
+            broker_runtime_inner,
+            timeout_map: Arc::new(parking_lot::Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn recover(&self, consumer_order_info_wrapper: &ConsumerOrderInfoWrapper) {
+        if !self.notify_enabled() {
+            return;
+        }
+        for (topic_at_group, qs) in consumer_order_info_wrapper.table() {
+            let Some((topic, group)) = topic_at_group.split_once(TOPIC_GROUP_SEPARATOR) else {
+                continue;
+            };
+            let topic = CheetahString::from_slice(topic);
+            let group = CheetahString::from_slice(group);
+            for (queue_id, order_info) in qs {
+                self.update_lock_free_timestamp(&topic, &group, *queue_id, order_info);
+            }
+        }
+    }
+
+    pub fn update_lock_free_timestamp(
+        &self,
+        topic: &CheetahString,
+        group: &CheetahString,
+        queue_id: i32,
+        order_info: &OrderInfo,
+    ) {
+        self.update_lock_free_timestamp_with(
+            topic,
+            group,
+            queue_id,
+            order_info.get_lock_free_timestamp(),
+        );
+    }
+
+    pub fn update_lock_free_timestamp_with(
+        &self,
+        topic: &CheetahString,
+        group: &CheetahString,
+        queue_id: i32,
+        lock_free_timestamp: Option<u64>,
+    ) {
+        if !self.notify_enabled() {
+            return;
+        }
+        let Some(lock_free_timestamp) = lock_free_timestamp else {
+            return;
+        };
+        let Ok(handle) = tokio::runtime::Handle::try_current() else {
+            error!(
+                "no runtime to schedule order lock release notification, topic: {}, group: {}, \
+                 queueId: {}",
+                topic, group, queue_id
+            );
+            return;
+        };
+        let key = (topic.clone(), group.clone(), queue_id);
+        let delay = lock_free_timestamp.saturating_sub(get_current_millis());
+        let broker_runtime_inner = self.broker_runtime_inner.clone();
+        let timeout_map = self.timeout_map.clone();
+        let task_key = key.clone();
+
+        let mut timeout_map_guard = self.timeout_map.lock();
+        let task = handle.spawn(async move {
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+            timeout_map.lock().remove(&task_key);
+            let (topic, group, queue_id) = task_key;
+            if let Some(pop_message_processor) = broker_runtime_inner.pop_message_processor() {
+                pop_message_processor
+                    .notify_long_polling_request_if_need(&topic, &group, queue_id)
+                    .await;
+            }
+        });
+        if let Some(old_task) = timeout_map_guard.insert(key, task.abort_handle()) {
+            old_task.abort();
+        }
+    }
+
+    pub fn clear_by_topic(&self, topic: &CheetahString) {
+        self.timeout_map.lock().retain(|(lock_topic, _, _), task| {
+            if lock_topic == topic {
+                task.abort();
+                return false;
+            }
+            true
+        });
+    }
 
-pub struct ConsumerOrderInfoLockManager {}
+    pub fn shutdown(&self) {
+        for (_, task) in self.timeout_map.lock().drain() {
+            task.abort();
+        }
+    }
 
-impl ConsumerOrderInfoLockManager {
-    pub fn recover(&self, _consumer_order_info_wrapper: &ConsumerOrderInfoWrapper) {}
+    #[inline]
+    fn notify_enabled(&self) -> bool {
+        self.broker_runtime_inner
+            .broker_config()
+            .enable_notify_after_pop_order_lock_release
+    }
 }
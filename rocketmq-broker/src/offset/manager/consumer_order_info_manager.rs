@@ -14,8 +14,6 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
-#![allow(unused_variables)]
-
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fmt::Display;
@@ -25,6 +23,7 @@ use cheetah_string::CheetahString;
 use rocketmq_common::common::config_manager::ConfigManager;
 use rocketmq_common::utils::serde_json_utils::SerdeJsonUtils;
 use rocketmq_common::TimeUtils::get_current_millis;
+use rocketmq_remoting::protocol::header::extra_info_util::ExtraInfoUtil;
 use rocketmq_rust::ArcMut;
 use rocketmq_store::base::message_store::MessageStore;
 use serde::Deserialize;
@@ -36,16 +35,16 @@ use crate::broker_path_config_helper::get_consumer_order_info_path;
 use crate::broker_runtime::BrokerRuntimeInner;
 use crate::offset::manager::consumer_order_info_lock_manager::ConsumerOrderInfoLockManager;
 
-const TOPIC_GROUP_SEPARATOR: &str = "@";
+pub(crate) const TOPIC_GROUP_SEPARATOR: &str = "@";
 const CLEAN_SPAN_FROM_LAST: u64 = 24 * 3600 * 1000;
 
 pub(crate) struct ConsumerOrderInfoManager<MS> {
     pub(crate) consumer_order_info_wrapper: parking_lot::Mutex<ConsumerOrderInfoWrapper>,
-    pub(crate) consumer_order_info_lock_manager: Option<ConsumerOrderInfoLockManager>,
+    pub(crate) consumer_order_info_lock_manager: Option<ConsumerOrderInfoLockManager<MS>>,
     broker_runtime_inner: ArcMut<BrokerRuntimeInner<MS>>,
 }
 
-impl<MS> ConsumerOrderInfoManager<MS> {
+impl<MS: MessageStore> ConsumerOrderInfoManager<MS> {
     pub fn new(
         broker_runtime_inner: ArcMut<BrokerRuntimeInner<MS>>,
     ) -> ConsumerOrderInfoManager<MS> {
@@ -53,14 +52,14 @@ impl<MS> ConsumerOrderInfoManager<MS> {
             consumer_order_info_wrapper: parking_lot::Mutex::new(
                 ConsumerOrderInfoWrapper::default(),
             ),
-            consumer_order_info_lock_manager: None,
+            consumer_order_info_lock_manager: Some(ConsumerOrderInfoLockManager::new(
+                broker_runtime_inner.clone(),
+            )),
             broker_runtime_inner,
         }
     }
 }
 
-//Fully implemented will be removed
-#[allow(unused_variables)]
 impl<MS: MessageStore> ConfigManager for ConsumerOrderInfoManager<MS> {
     fn config_file_path(&self) -> String {
         get_consumer_order_info_path(
@@ -75,9 +74,9 @@ impl<MS: MessageStore> ConfigManager for ConsumerOrderInfoManager<MS> {
         self.auto_clean();
         let wrapper = self.consumer_order_info_wrapper.lock();
         match pretty_format {
-            true => SerdeJsonUtils::to_json_pretty(&wrapper.table)
+            true => SerdeJsonUtils::to_json_pretty(wrapper.deref())
                 .expect("Failed to serialize consumer order info wrapper"),
-            false => serde_json::to_string(&wrapper.table)
+            false => serde_json::to_string(wrapper.deref())
                 .expect("Failed to serialize consumer order info wrapper"),
         }
     }
@@ -105,7 +104,34 @@ impl<MS: MessageStore> ConfigManager for ConsumerOrderInfoManager<MS> {
 
 impl<MS: MessageStore> ConsumerOrderInfoManager<MS> {
     pub fn clear_block(&self, topic: &CheetahString, group: &CheetahString, queue_id: i32) {
-        unimplemented!()
+        let key = CheetahString::from_string(build_key(topic, group));
+        if let Some(qs) = self.consumer_order_info_wrapper.lock().table.get_mut(&key) {
+            qs.remove(&queue_id);
+        }
+    }
+
+    /// Drops the order info of every group consuming `topic`, returning whether anything was
+    /// removed.
+    pub fn clear_by_topic(&self, topic: &CheetahString) -> bool {
+        let prefix = format!("{topic}{TOPIC_GROUP_SEPARATOR}");
+        let removed = {
+            let mut wrapper = self.consumer_order_info_wrapper.lock();
+            let before = wrapper.table.len();
+            wrapper
+                .table
+                .retain(|topic_at_group, _| !topic_at_group.starts_with(prefix.as_str()));
+            before != wrapper.table.len()
+        };
+        if let Some(lock_manager) = self.consumer_order_info_lock_manager.as_ref() {
+            lock_manager.clear_by_topic(topic);
+        }
+        removed
+    }
+
+    pub fn shutdown(&self) {
+        if let Some(lock_manager) = self.consumer_order_info_lock_manager.as_ref() {
+            lock_manager.shutdown();
+        }
     }
 
     pub fn auto_clean(&self) {
@@ -164,7 +190,7 @@ impl<MS: MessageStore> ConsumerOrderInfoManager<MS> {
             // Clean individual queues in the current topic@group
             let mut queues_to_remove = Vec::new();
             for (queue_id, order_info) in qs.iter_mut() {
-                if *queue_id == topic_config.read_queue_nums as i32 {
+                if *queue_id >= topic_config.read_queue_nums as i32 {
                     queues_to_remove.push(*queue_id);
                     info!(
                         "Queue not exist, Clean order info, {}:{}, {}",
@@ -239,14 +265,19 @@ impl<MS: MessageStore> ConsumerOrderInfoManager<MS> {
 
     fn update_lock_free_timestamp(
         &self,
-        _topic: &CheetahString,
-        _group: &CheetahString,
-        _queue_id: i32,
-        _order_info: &OrderInfo,
+        topic: &CheetahString,
+        group: &CheetahString,
+        queue_id: i32,
+        order_info: &OrderInfo,
     ) {
-        unimplemented!("")
+        if let Some(lock_manager) = self.consumer_order_info_lock_manager.as_ref() {
+            lock_manager.update_lock_free_timestamp(topic, group, queue_id, order_info);
+        }
     }
 
+    /// Marks `queue_offset` of the order info as acked and returns the offset the group can
+    /// commit, `-1` if the offset is not part of the current pop and `-2` if `pop_time` does
+    /// not match the last pop.
     pub fn commit_and_next(
         &self,
         topic: &CheetahString,
@@ -255,9 +286,49 @@ impl<MS: MessageStore> ConsumerOrderInfoManager<MS> {
         queue_offset: u64,
         pop_time: u64,
     ) -> i64 {
-        unimplemented!()
+        let key = CheetahString::from_string(build_key(topic, group));
+        let mut wrapper = self.consumer_order_info_wrapper.lock();
+        let Some(qs) = wrapper.table.get_mut(&key) else {
+            return queue_offset as i64 + 1;
+        };
+        let Some(order_info) = qs.get_mut(&queue_id) else {
+            warn!("OrderInfo is null, {}, {}, {}", key, queue_offset, queue_id);
+            return queue_offset as i64 + 1;
+        };
+        if order_info.offset_list.is_empty() {
+            warn!(
+                "OrderInfo is empty, {}, {}, {}",
+                key, queue_offset, order_info
+            );
+            return -1;
+        }
+        if pop_time != order_info.pop_time {
+            warn!(
+                "popTime is not equal to orderInfo saved. key: {}, offset: {}, orderInfo: {}, \
+                 popTime: {}",
+                key, queue_offset, order_info, pop_time
+            );
+            return -2;
+        }
+        let Some(index) = (0..order_info.offset_list.len())
+            .find(|index| order_info.get_queue_offset(*index) == queue_offset)
+        else {
+            warn!(
+                "OrderInfo not found commit offset, {}, {}, {}",
+                key, queue_offset, order_info
+            );
+            return -1;
+        };
+        if index < 64 {
+            order_info.commit_offset_bit |= 1 << index;
+        }
+        let next_offset = order_info.get_next_offset();
+        self.update_lock_free_timestamp(topic, group, queue_id, order_info);
+        next_offset
     }
 
+    /// Returns whether messages popped by another attempt are still invisible, in which case
+    /// the queue stays blocked for orderly consumption.
     pub fn check_block(
         &self,
         attempt_id: &CheetahString,
@@ -266,22 +337,79 @@ impl<MS: MessageStore> ConsumerOrderInfoManager<MS> {
         queue_id: i32,
         invisible_time: u64,
     ) -> bool {
-        unimplemented!()
+        let key = CheetahString::from_string(build_key(topic, group));
+        let mut wrapper = self.consumer_order_info_wrapper.lock();
+        match wrapper
+            .table
+            .get_mut(&key)
+            .and_then(|qs| qs.get_mut(&queue_id))
+        {
+            None => false,
+            Some(order_info) => order_info.need_block(attempt_id.as_str(), invisible_time),
+        }
     }
 
+    /// Records the messages handed out by an orderly pop and appends their consumed times to
+    /// `order_info_builder`.
     pub fn update(
         &self,
         attempt_id: CheetahString,
-        is_retry: bool,
+        _is_retry: bool,
         topic: &CheetahString,
         group: &CheetahString,
         queue_id: i32,
         pop_time: u64,
         invisible_time: u64,
         msg_queue_offset_list: Vec<u64>,
-        order_info_builder: &str,
-    ) -> bool {
-        unimplemented!()
+        order_info_builder: &mut String,
+    ) {
+        if msg_queue_offset_list.is_empty() {
+            return;
+        }
+        let key = CheetahString::from_string(build_key(topic, group));
+        let mut wrapper = self.consumer_order_info_wrapper.lock();
+        let qs = wrapper.table.entry(key).or_default();
+        let mut order_info = OrderInfo::new(
+            attempt_id.to_string(),
+            pop_time,
+            invisible_time,
+            msg_queue_offset_list,
+            get_current_millis(),
+            0,
+        );
+        if let Some(pre_order_info) = qs.remove(&queue_id) {
+            order_info.merge_offset_consumed_count(
+                &pre_order_info.attempt_id,
+                pre_order_info.offset_list,
+                pre_order_info.offset_consumed_count,
+            );
+        }
+
+        let mut min_consumed_times = i32::MAX;
+        for (offset, consumed_times) in &order_info.offset_consumed_count {
+            ExtraInfoUtil::build_queue_offset_order_count_info(
+                order_info_builder,
+                topic,
+                queue_id as i64,
+                *offset as i64,
+                *consumed_times,
+            );
+            min_consumed_times = min_consumed_times.min(*consumed_times);
+        }
+        // only messages consumed before are counted, so a size mismatch means there are
+        // messages popped for the first time
+        if order_info.offset_consumed_count.len() != order_info.offset_list.len() {
+            min_consumed_times = 0;
+        }
+        // the old pop sdk reads the consumed times by queue id
+        ExtraInfoUtil::build_queue_id_order_count_info(
+            order_info_builder,
+            topic,
+            queue_id,
+            min_consumed_times,
+        );
+        self.update_lock_free_timestamp(topic, group, queue_id, &order_info);
+        qs.insert(queue_id, order_info);
     }
 }
 
@@ -293,16 +421,25 @@ fn build_key(topic: &CheetahString, group: &CheetahString) -> String {
 
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
 pub(crate) struct ConsumerOrderInfoWrapper {
+    #[serde(default)]
     table: HashMap<CheetahString /* topic@group */, HashMap<i32, OrderInfo>>,
 }
 
+impl ConsumerOrderInfoWrapper {
+    #[inline]
+    pub(crate) fn table(&self) -> &HashMap<CheetahString, HashMap<i32, OrderInfo>> {
+        &self.table
+    }
+}
+
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[serde(default)]
 pub(crate) struct OrderInfo {
-    #[serde(rename = "popTime")]
+    #[serde(rename = "p")]
     pop_time: u64,
     #[serde(rename = "i")]
     invisible_time: Option<u64>,
-    #[serde(rename = "0")]
+    #[serde(rename = "o")]
     offset_list: Vec<u64>,
     #[serde(rename = "ot")]
     offset_next_visible_time: HashMap<u64, u64>,
@@ -336,6 +473,26 @@ impl Display for OrderInfo {
 }
 
 impl OrderInfo {
+    pub fn new(
+        attempt_id: String,
+        pop_time: u64,
+        invisible_time: u64,
+        queue_offset_list: Vec<u64>,
+        last_consume_timestamp: u64,
+        commit_offset_bit: u64,
+    ) -> Self {
+        Self {
+            pop_time,
+            invisible_time: Some(invisible_time),
+            offset_list: Self::build_offset_list(queue_offset_list),
+            offset_next_visible_time: HashMap::new(),
+            offset_consumed_count: HashMap::new(),
+            last_consume_timestamp,
+            commit_offset_bit,
+            attempt_id,
+        }
+    }
+
     /// Builds a list of offsets from a given list of queue offsets.
     /// If the list contains only one element, it returns the same list.
     /// Otherwise, it returns a list where each element is the difference
@@ -350,7 +507,7 @@ impl OrderInfo {
     /// A vector of offsets.
     pub fn build_offset_list(queue_offset_list: Vec<u64>) -> Vec<u64> {
         let mut simple = Vec::new();
-        if queue_offset_list.len() == 1 {
+        if queue_offset_list.len() <= 1 {
             simple.extend(queue_offset_list);
             return simple;
         }
@@ -622,4 +779,55 @@ mod tests {
         assert_eq!(order_info.offset_consumed_count.get(&1), Some(&1));
         assert_eq!(order_info.offset_consumed_count.get(&2), Some(&1));
     }
+
+    #[test]
+    fn new_order_info_stores_offsets_relative_to_first() {
+        let order_info = OrderInfo::new("attempt".to_string(), 1000, 3000, vec![5, 6, 8], 0, 0);
+        assert_eq!(order_info.offset_list, vec![5, 1, 3]);
+        assert_eq!(order_info.get_queue_offset(2), 8);
+        assert_eq!(order_info.get_next_offset(), 5);
+    }
+
+    #[test]
+    fn get_next_offset_skips_acked_prefix() {
+        let mut order_info = OrderInfo::new("attempt".to_string(), 1000, 3000, vec![5, 6, 8], 0, 0);
+        order_info.commit_offset_bit |= 1;
+        assert_eq!(order_info.get_next_offset(), 6);
+        order_info.commit_offset_bit |= 1 << 2;
+        assert_eq!(order_info.get_next_offset(), 6);
+        order_info.commit_offset_bit |= 1 << 1;
+        assert_eq!(order_info.get_next_offset(), 9);
+    }
+
+    #[test]
+    fn need_block_until_not_acked_message_is_visible() {
+        let now = get_current_millis();
+        let mut order_info = OrderInfo::new("attempt".to_string(), now, 60_000, vec![5], now, 0);
+        assert!(order_info.need_block("other", 60_000));
+        assert!(!order_info.need_block("attempt", 60_000));
+
+        order_info.update_offset_next_visible_time(5, now - 1);
+        assert!(!order_info.need_block("other", 60_000));
+    }
+
+    #[test]
+    fn wrapper_serializes_with_java_field_names() {
+        let mut wrapper = ConsumerOrderInfoWrapper::default();
+        wrapper.table.insert(
+            CheetahString::from_static_str("topic@group"),
+            HashMap::from([(
+                0,
+                OrderInfo::new("attempt".to_string(), 1000, 3000, vec![5, 6], 1000, 0),
+            )]),
+        );
+        let json = serde_json::to_string(&wrapper).unwrap();
+        assert!(json.contains("\"table\""));
+        assert!(json.contains("\"p\":1000"));
+        assert!(json.contains("\"o\":[5,1]"));
+
+        let decoded: ConsumerOrderInfoWrapper = serde_json::from_str(&json).unwrap();
+        let order_info = &decoded.table()["topic@group"][&0];
+        assert_eq!(order_info.pop_time, 1000);
+        assert_eq!(order_info.attempt_id, "attempt");
+    }
 }
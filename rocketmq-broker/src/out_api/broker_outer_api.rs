@@ -45,6 +45,8 @@ use rocketmq_remoting::clients::rocketmq_default_impl::RocketmqDefaultClient;
 use rocketmq_remoting::clients::RemotingClient;
 use rocketmq_remoting::code::request_code::RequestCode;
 use rocketmq_remoting::code::response_code::ResponseCode;
+use rocketmq_remoting::protocol::body::broker_body::broker_member_group::BrokerMemberGroup;
+use rocketmq_remoting::protocol::body::broker_body::broker_member_group::GetBrokerMemberGroupResponseBody;
 use rocketmq_remoting::protocol::body::broker_body::register_broker_body::RegisterBrokerBody;
 use rocketmq_remoting::protocol::body::kv_table::KVTable;
 use rocketmq_remoting::protocol::body::response::lock_batch_response_body::LockBatchResponseBody;
@@ -54,7 +56,10 @@ use rocketmq_remoting::protocol::header::lock_batch_mq_request_header::LockBatch
 use rocketmq_remoting::protocol::header::message_operation_header::send_message_request_header::SendMessageRequestHeader;
 use rocketmq_remoting::protocol::header::message_operation_header::send_message_request_header_v2::SendMessageRequestHeaderV2;
 use rocketmq_remoting::protocol::header::message_operation_header::send_message_response_header::SendMessageResponseHeader;
+use rocketmq_remoting::protocol::header::namesrv::broker_request::GetBrokerMemberGroupRequestHeader;
 use rocketmq_remoting::protocol::header::namesrv::broker_request::UnRegisterBrokerRequestHeader;
+use rocketmq_remoting::protocol::header::namesrv::query_data_version_header::QueryDataVersionRequestHeader;
+use rocketmq_remoting::protocol::header::namesrv::query_data_version_header::QueryDataVersionResponseHeader;
 use rocketmq_remoting::protocol::header::namesrv::register_broker_header::RegisterBrokerRequestHeader;
 use rocketmq_remoting::protocol::header::namesrv::register_broker_header::RegisterBrokerResponseHeader;
 use rocketmq_remoting::protocol::header::namesrv::topic_operation_header::RegisterTopicRequestHeader;
@@ -67,6 +72,7 @@ use rocketmq_remoting::protocol::namesrv::RegisterBrokerResult;
 use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
 use rocketmq_remoting::protocol::route::route_data_view::QueueData;
 use rocketmq_remoting::protocol::route::topic_route_data::TopicRouteData;
+use rocketmq_remoting::protocol::DataVersion;
 use rocketmq_remoting::protocol::RemotingDeserializable;
 use rocketmq_remoting::protocol::RemotingSerializable;
 use rocketmq_remoting::remoting::RemotingService;
@@ -127,6 +133,11 @@ impl BrokerOuterAPI {
         }
     }
 
+    /// Runs `rpc_hook` on every request sent to the name servers and the other brokers.
+    pub fn register_rpc_hook(&mut self, rpc_hook: Arc<Box<dyn RPCHook>>) {
+        self.remoting_client.register_rpc_hook(rpc_hook);
+    }
+
     fn create_request(broker_name: CheetahString, topic_config: TopicConfig) -> RemotingCommand {
         let request_header =
             RegisterTopicRequestHeader::new(topic_config.topic_name.as_ref().cloned().unwrap());
@@ -166,6 +177,10 @@ impl BrokerOuterAPI {
             .await
     }
 
+    pub fn get_available_name_srv_list(&self) -> Vec<CheetahString> {
+        self.remoting_client.get_available_name_srv_list()
+    }
+
     pub async fn update_name_server_address_list_by_dns_lookup(&self, domain: CheetahString) {
         let address_list = dns_lookup_address_by_domain(domain.as_str());
         self.remoting_client
@@ -191,6 +206,44 @@ impl BrokerOuterAPI {
         broker_runtime_inner: ArcMut<BrokerRuntimeInner<MS>>,
     ) -> Vec<RegisterBrokerResult> {
         let name_server_address_list = self.remoting_client.get_available_name_srv_list();
+        self.register_broker_to(
+            name_server_address_list,
+            cluster_name,
+            broker_addr,
+            broker_name,
+            broker_id,
+            ha_server_addr,
+            topic_config_wrapper,
+            filter_server_list,
+            oneway,
+            timeout_mills,
+            enable_acting_master,
+            compressed,
+            heartbeat_timeout_millis,
+            broker_runtime_inner,
+        )
+        .await
+    }
+
+    /// Registers the broker with the given name servers only, e.g. with the ones that reported a
+    /// changed data version from [`need_register`](Self::need_register).
+    pub async fn register_broker_to<MS: MessageStore>(
+        &self,
+        name_server_address_list: Vec<CheetahString>,
+        cluster_name: CheetahString,
+        broker_addr: CheetahString,
+        broker_name: CheetahString,
+        broker_id: u64,
+        ha_server_addr: CheetahString,
+        topic_config_wrapper: TopicConfigAndMappingSerializeWrapper,
+        filter_server_list: Vec<CheetahString>,
+        oneway: bool,
+        timeout_mills: u64,
+        enable_acting_master: bool,
+        compressed: bool,
+        heartbeat_timeout_millis: Option<i64>,
+        broker_runtime_inner: ArcMut<BrokerRuntimeInner<MS>>,
+    ) -> Vec<RegisterBrokerResult> {
         let mut register_broker_result_list = Vec::new();
         if !name_server_address_list.is_empty() {
             let mut request_header = RegisterBrokerRequestHeader {
@@ -200,7 +253,7 @@ impl BrokerOuterAPI {
                 cluster_name,
                 ha_server_addr,
                 enable_acting_master: Some(enable_acting_master),
-                compressed: false,
+                compressed,
                 heartbeat_timeout_millis,
                 body_crc32: 0,
             };
@@ -250,6 +303,86 @@ impl BrokerOuterAPI {
         register_broker_result_list
     }
 
+    /// Ask every name server whether the topic config data version of this broker changed.
+    ///
+    /// Returns the name servers the broker has to register with again; a name server that cannot
+    /// be reached or answers with an error counts as changed.
+    pub async fn need_register(
+        &self,
+        cluster_name: &CheetahString,
+        broker_addr: &CheetahString,
+        broker_name: &CheetahString,
+        broker_id: u64,
+        topic_config_wrapper: &TopicConfigAndMappingSerializeWrapper,
+        timeout_mills: u64,
+    ) -> Vec<CheetahString> {
+        let data_version = &topic_config_wrapper
+            .topic_config_serialize_wrapper
+            .data_version;
+        let body = match data_version.encode() {
+            Ok(body) => body,
+            Err(e) => {
+                error!("encode data version failed, {}", e);
+                return self.remoting_client.get_name_server_address_list().to_vec();
+            }
+        };
+        let name_server_address_list = self.remoting_client.get_name_server_address_list();
+        let mut changed_list = Vec::with_capacity(name_server_address_list.len());
+        for namesrv_addr in name_server_address_list.iter() {
+            let request_header = QueryDataVersionRequestHeader::new(
+                broker_name.clone(),
+                broker_addr.clone(),
+                cluster_name.clone(),
+                broker_id,
+            );
+            let request = RemotingCommand::create_request_command(
+                RequestCode::QueryDataVersion,
+                request_header,
+            )
+            .set_body(body.clone());
+            match self
+                .remoting_client
+                .invoke_async(Some(namesrv_addr), request, timeout_mills)
+                .await
+            {
+                Ok(response) => {
+                    if ResponseCode::from(response.code()) != ResponseCode::Success {
+                        changed_list.push(namesrv_addr.clone());
+                        continue;
+                    }
+                    let mut changed = response
+                        .decode_command_custom_header::<QueryDataVersionResponseHeader>()
+                        .map_or(true, |header| header.changed());
+                    if let Some(body) = response.body() {
+                        match DataVersion::decode(body.as_ref()) {
+                            Ok(name_server_data_version) => {
+                                if *data_version != name_server_data_version {
+                                    changed = true;
+                                }
+                            }
+                            Err(_) => changed = true,
+                        }
+                    }
+                    debug!(
+                        "Query data version done, changed {}, namesrv_addr={}",
+                        changed, namesrv_addr
+                    );
+                    if changed {
+                        changed_list.push(namesrv_addr.clone());
+                    }
+                }
+                Err(e) => {
+                    error!(
+                        "Query data version from name server error, namesrv_addr={}, error={}",
+                        namesrv_addr, e
+                    );
+                    changed_list.push(namesrv_addr.clone());
+                }
+            }
+        }
+        changed_list
+    }
+
     async fn register_broker(
         &self,
         namesrv_addr: &CheetahString,
@@ -550,6 +683,120 @@ impl BrokerOuterAPI {
         }
     }
 
+    pub async fn sync_broker_member_group(
+        &self,
+        cluster_name: &CheetahString,
+        broker_name: &CheetahString,
+        is_compatible_with_old_name_srv: bool,
+    ) -> rocketmq_error::RocketMQResult<Option<BrokerMemberGroup>> {
+        if is_compatible_with_old_name_srv {
+            self.get_broker_member_group_compatible(cluster_name, broker_name)
+                .await
+        } else {
+            self.get_broker_member_group(cluster_name, broker_name)
+                .await
+        }
+    }
+
+    async fn get_broker_member_group(
+        &self,
+        cluster_name: &CheetahString,
+        broker_name: &CheetahString,
+    ) -> rocketmq_error::RocketMQResult<Option<BrokerMemberGroup>> {
+        let request_header =
+            GetBrokerMemberGroupRequestHeader::new(cluster_name.clone(), broker_name.clone());
+        let request = RemotingCommand::create_request_command(
+            RequestCode::GetBrokerMemberGroup,
+            request_header,
+        );
+        let response = self
+            .remoting_client
+            .invoke_async(None, request, 3000)
+            .await?;
+        if ResponseCode::from(response.code()) != ResponseCode::Success {
+            return Err(RocketmqError::MQBrokerError(
+                response.code(),
+                response.remark().map_or("".to_string(), |s| s.to_string()),
+                "".to_string(),
+            ));
+        }
+        match response.body() {
+            Some(body) => {
+                Ok(GetBrokerMemberGroupResponseBody::decode(body.as_ref())?.broker_member_group)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Name servers that do not support `GET_BROKER_MEMBER_GROUP` still route the per broker
+    /// sync topic, so the member group is rebuilt from that route.
+    async fn get_broker_member_group_compatible(
+        &self,
+        cluster_name: &CheetahString,
+        broker_name: &CheetahString,
+    ) -> rocketmq_error::RocketMQResult<Option<BrokerMemberGroup>> {
+        let mut broker_member_group =
+            BrokerMemberGroup::new(cluster_name.clone(), broker_name.clone());
+        let topic = CheetahString::from_string(format!(
+            "{}{}",
+            TopicValidator::SYNC_BROKER_MEMBER_GROUP_PREFIX,
+            broker_name
+        ));
+        let topic_route_data = self
+            .get_topic_route_info_from_name_server(&topic, 3000, false)
+            .await?;
+        for broker_data in topic_route_data.broker_datas.iter() {
+            if broker_data.broker_name() == broker_name
+                && broker_data.cluster() == cluster_name.as_str()
+            {
+                broker_member_group
+                    .broker_addrs
+                    .extend(broker_data.broker_addrs().clone());
+            }
+        }
+        Ok(Some(broker_member_group))
+    }
+
+    pub async fn get_all_consumer_offset(
+        &self,
+        broker_addr: &CheetahString,
+    ) -> rocketmq_error::RocketMQResult<String> {
+        self.get_all_config_content(broker_addr, RequestCode::GetAllConsumerOffset)
+            .await
+    }
+
+    pub async fn get_all_delay_offset(
+        &self,
+        broker_addr: &CheetahString,
+    ) -> rocketmq_error::RocketMQResult<String> {
+        self.get_all_config_content(broker_addr, RequestCode::GetAllDelayOffset)
+            .await
+    }
+
+    async fn get_all_config_content(
+        &self,
+        broker_addr: &CheetahString,
+        request_code: RequestCode,
+    ) -> rocketmq_error::RocketMQResult<String> {
+        let request = RemotingCommand::create_remoting_command(request_code);
+        let response = self
+            .remoting_client
+            .invoke_async(Some(broker_addr), request, 3000)
+            .await?;
+        if ResponseCode::from(response.code()) != ResponseCode::Success {
+            return Err(RocketmqError::MQBrokerError(
+                response.code(),
+                response.remark().map_or("".to_string(), |s| s.to_string()),
+                broker_addr.to_string(),
+            ));
+        }
+        Ok(response
+            .body()
+            .as_ref()
+            .map(|body| String::from_utf8_lossy(body.as_ref()).into_owned())
+            .unwrap_or_default())
+    }
+
     pub async fn unregister_broker_all(
         &self,
         cluster_name: &CheetahString,
@@ -14,7 +14,12 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
 use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::code::response_code::ResponseCode;
 use rocketmq_remoting::net::channel::Channel;
 use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
 use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
@@ -24,6 +29,7 @@ use rocketmq_store::base::message_store::MessageStore;
 use tracing::info;
 
 use self::client_manage_processor::ClientManageProcessor;
+use crate::audit::request_auditor::RequestAuditor;
 use crate::processor::ack_message_processor::AckMessageProcessor;
 use crate::processor::admin_broker_processor::AdminBrokerProcessor;
 use crate::processor::change_invisible_time_processor::ChangeInvisibleTimeProcessor;
@@ -76,6 +82,9 @@ pub struct BrokerRequestProcessor<MS, TS> {
     pub(crate) query_assignment_processor: ArcMut<QueryAssignmentProcessor<MS>>,
     pub(crate) end_transaction_processor: ArcMut<EndTransactionProcessor<TS, MS>>,
     pub(crate) admin_broker_processor: ArcMut<AdminBrokerProcessor<MS>>,
+    /// Set once the broker starts shutting down, requests are turned away from then on.
+    pub(crate) shutdown: Arc<AtomicBool>,
+    pub(crate) request_auditor: Arc<RequestAuditor>,
 }
 impl<MS, TS> Clone for BrokerRequestProcessor<MS, TS> {
     fn clone(&self) -> Self {
@@ -95,6 +104,8 @@ impl<MS, TS> Clone for BrokerRequestProcessor<MS, TS> {
             query_assignment_processor: self.query_assignment_processor.clone(),
             query_message_processor: self.query_message_processor.clone(),
             end_transaction_processor: self.end_transaction_processor.clone(),
+            shutdown: self.shutdown.clone(),
+            request_auditor: self.request_auditor.clone(),
         }
     }
 }
@@ -109,9 +120,41 @@ where
         channel: Channel,
         ctx: ConnectionHandlerContext,
         request: RemotingCommand,
+    ) -> rocketmq_error::RocketMQResult<Option<RemotingCommand>> {
+        let Some(audit_record) = self
+            .request_auditor
+            .sample(&request, channel.remote_address())
+        else {
+            return self.dispatch(channel, ctx, request).await;
+        };
+        let result = self.dispatch(channel, ctx, request).await;
+        self.request_auditor.finish(audit_record, &result);
+        result
+    }
+}
+
+impl<MS, TS> BrokerRequestProcessor<MS, TS>
+where
+    MS: MessageStore + Send + Sync + 'static,
+    TS: TransactionalMessageService,
+{
+    async fn dispatch(
+        &mut self,
+        channel: Channel,
+        ctx: ConnectionHandlerContext,
+        request: RemotingCommand,
     ) -> rocketmq_error::RocketMQResult<Option<RemotingCommand>> {
         let request_code = RequestCode::from(request.code());
         info!("process_request: {:?}", request_code);
+        if self.shutdown.load(Ordering::Acquire) {
+            // clients take SYSTEM_BUSY as a hint to send to another broker
+            return Ok(Some(
+                RemotingCommand::create_response_command_with_code_remark(
+                    ResponseCode::SystemBusy,
+                    "the broker is shutting down",
+                ),
+            ));
+        }
         let result = match request_code {
             RequestCode::SendMessage
             | RequestCode::SendMessageV2
@@ -195,9 +238,10 @@ where
                     .await;
             }
             _ => {
-                self.admin_broker_processor
+                return self
+                    .admin_broker_processor
                     .process_request(channel, ctx, request_code, request)
-                    .await
+                    .await;
             }
         };
         Ok(result)
@@ -52,6 +52,7 @@ use tracing::warn;
 use crate::broker_runtime::BrokerRuntimeInner;
 use crate::processor::pop_message_processor::PopMessageProcessor;
 use crate::processor::processor_service::pop_revive_service::PopReviveService;
+use crate::processor::processor_service::revive_queues;
 
 pub struct AckMessageProcessor<MS> {
     pop_message_processor: ArcMut<PopMessageProcessor<MS>>,
@@ -66,7 +67,7 @@ where
 {
     pub fn new(
         broker_runtime_inner: ArcMut<BrokerRuntimeInner<MS>>,
-        pop_message_processor: ArcMut<PopMessageProcessor<MS>>,
+        mut pop_message_processor: ArcMut<PopMessageProcessor<MS>>,
     ) -> AckMessageProcessor<MS> {
         let revive_topic = CheetahString::from_string(PopAckConstants::build_cluster_revive_topic(
             broker_runtime_inner
@@ -82,9 +83,11 @@ where
             .broker_id
             == MASTER_ID;
 
+        let revive_queue_num = grow_revive_topic(&broker_runtime_inner, &revive_topic);
+        pop_message_processor.set_revived_queue_num(revive_queue_num);
+
         // each PopReviveService handles one revive topic's revive queue
-        for i in 0..broker_runtime_inner.broker_config().revive_queue_num {
-            let revive_queue_id = POP_ORDER_REVIVE_QUEUE;
+        for i in 0..revive_queue_num {
             let mut pop_revive_service =
                 PopReviveService::new(revive_topic.clone(), i as i32, broker_runtime_inner.clone());
             pop_revive_service.set_should_run_pop_revive(is_run_pop_revive);
@@ -134,6 +137,18 @@ where
         }
     }
 
+    /// Messages in the revive topic not yet processed and the delay of the slowest revive queue.
+    pub fn get_revive_behind(&self) -> (i64, i64) {
+        self.pop_revive_services
+            .iter()
+            .fold((0, 0), |(messages, millis), pop_revive_service| {
+                (
+                    messages + pop_revive_service.get_revive_behind_messages(),
+                    millis.max(pop_revive_service.get_revive_behind_millis()),
+                )
+            })
+    }
+
     pub fn set_pop_revive_service_status(&mut self, status: bool) {
         for pop_revive_service in self.pop_revive_services.iter_mut() {
             pop_revive_service.set_should_run_pop_revive(status);
@@ -518,8 +533,8 @@ where
             .broker_runtime_inner
             .consumer_order_info_manager()
             .commit_and_next(
-                &consume_group,
                 &topic,
+                &consume_group,
                 q_id,
                 ack_offset as u64,
                 pop_time as u64,
@@ -565,8 +580,8 @@ where
                     .consumer_order_info_manager()
                     .check_block(
                         &CheetahString::empty(),
-                        &consume_group,
                         &topic,
+                        &consume_group,
                         q_id,
                         invisible_time as u64,
                     )
@@ -594,3 +609,40 @@ where
         }
     }
 }
+
+/// Grows the revive topic to the configured number of revive queues and returns the number of
+/// queues it has, see [`revive_queues`].
+fn grow_revive_topic<MS: MessageStore>(
+    broker_runtime_inner: &ArcMut<BrokerRuntimeInner<MS>>,
+    revive_topic: &CheetahString,
+) -> u32 {
+    let topic_config = broker_runtime_inner
+        .topic_config_manager()
+        .select_topic_config(revive_topic);
+    let revive_queue_num = revive_queues::revive_queue_count(
+        broker_runtime_inner.broker_config().revive_queue_num,
+        topic_config
+            .as_ref()
+            .map(|topic_config| topic_config.read_queue_nums),
+    );
+    if let Some(mut topic_config) = topic_config {
+        if topic_config.read_queue_nums < revive_queue_num
+            || topic_config.write_queue_nums < revive_queue_num
+        {
+            warn!(
+                "grow revive topic {} from {} to {} queues",
+                revive_topic, topic_config.read_queue_nums, revive_queue_num
+            );
+            topic_config.read_queue_nums = revive_queue_num;
+            topic_config.write_queue_nums = revive_queue_num;
+            if let Err(e) = broker_runtime_inner
+                .mut_from_ref()
+                .topic_config_manager_mut()
+                .update_topic_config(&mut topic_config)
+            {
+                error!("grow revive topic {} failed: {:?}", revive_topic, e);
+            }
+        }
+    }
+    revive_queue_num
+}
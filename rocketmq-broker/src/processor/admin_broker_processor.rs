@@ -15,6 +15,7 @@
  * limitations under the License.
  */
 
+use rocketmq_error::RocketMQResult;
 use rocketmq_remoting::code::request_code::RequestCode;
 use rocketmq_remoting::code::response_code::ResponseCode;
 use rocketmq_remoting::net::channel::Channel;
@@ -29,12 +30,16 @@ use crate::processor::admin_broker_processor::batch_mq_handler::BatchMqHandler;
 use crate::processor::admin_broker_processor::broker_config_request_handler::BrokerConfigRequestHandler;
 use crate::processor::admin_broker_processor::consumer_request_handler::ConsumerRequestHandler;
 use crate::processor::admin_broker_processor::offset_request_handler::OffsetRequestHandler;
+use crate::processor::admin_broker_processor::producer_request_handler::ProducerRequestHandler;
+use crate::processor::admin_broker_processor::subscription_group_request_handler::SubscriptionGroupRequestHandler;
 use crate::processor::admin_broker_processor::topic_request_handler::TopicRequestHandler;
 
 mod batch_mq_handler;
 mod broker_config_request_handler;
 mod consumer_request_handler;
 mod offset_request_handler;
+mod producer_request_handler;
+mod subscription_group_request_handler;
 mod topic_request_handler;
 
 pub struct AdminBrokerProcessor<MS> {
@@ -42,6 +47,8 @@ pub struct AdminBrokerProcessor<MS> {
     broker_config_request_handler: BrokerConfigRequestHandler<MS>,
     consumer_request_handler: ConsumerRequestHandler<MS>,
     offset_request_handler: OffsetRequestHandler<MS>,
+    producer_request_handler: ProducerRequestHandler<MS>,
+    subscription_group_request_handler: SubscriptionGroupRequestHandler<MS>,
     batch_mq_handler: BatchMqHandler<MS>,
     broker_runtime_inner: ArcMut<BrokerRuntimeInner<MS>>,
 }
@@ -53,12 +60,17 @@ impl<MS: MessageStore> AdminBrokerProcessor<MS> {
             BrokerConfigRequestHandler::new(broker_runtime_inner.clone());
         let consumer_request_handler = ConsumerRequestHandler::new(broker_runtime_inner.clone());
         let offset_request_handler = OffsetRequestHandler::new(broker_runtime_inner.clone());
+        let producer_request_handler = ProducerRequestHandler::new(broker_runtime_inner.clone());
+        let subscription_group_request_handler =
+            SubscriptionGroupRequestHandler::new(broker_runtime_inner.clone());
         let batch_mq_handler = BatchMqHandler::new(broker_runtime_inner.clone());
         AdminBrokerProcessor {
             topic_request_handler,
             broker_config_request_handler,
             consumer_request_handler,
             offset_request_handler,
+            producer_request_handler,
+            subscription_group_request_handler,
             batch_mq_handler,
             broker_runtime_inner,
         }
@@ -72,8 +84,8 @@ impl<MS: MessageStore> AdminBrokerProcessor<MS> {
         ctx: ConnectionHandlerContext,
         request_code: RequestCode,
         request: RemotingCommand,
-    ) -> Option<RemotingCommand> {
-        match request_code {
+    ) -> RocketMQResult<Option<RemotingCommand>> {
+        let response = match request_code {
             RequestCode::UpdateAndCreateTopic => {
                 self.topic_request_handler
                     .update_and_create_topic(channel, ctx, request_code, request)
@@ -114,21 +126,66 @@ impl<MS: MessageStore> AdminBrokerProcessor<MS> {
                     .get_topic_stats_info(channel, ctx, request_code, request)
                     .await
             }
+            RequestCode::GetTopicDiskUsage => {
+                self.topic_request_handler
+                    .get_topic_disk_usage(channel, ctx, request_code, request)
+                    .await?
+            }
             RequestCode::GetConsumerConnectionList => {
                 self.consumer_request_handler
                     .get_consumer_connection_list(channel, ctx, request_code, request)
                     .await
             }
+            RequestCode::GetConsumerRunningInfo => {
+                self.consumer_request_handler
+                    .get_consumer_running_info(channel, ctx, request_code, request)
+                    .await
+            }
+            RequestCode::GetProducerConnectionList => {
+                self.producer_request_handler
+                    .get_producer_connection_list(channel, ctx, request_code, request)
+                    .await
+            }
             RequestCode::GetConsumeStats => {
                 self.consumer_request_handler
                     .get_consume_stats(channel, ctx, request_code, request)
                     .await
             }
+            RequestCode::GetPopStats => {
+                self.consumer_request_handler
+                    .get_pop_stats(channel, ctx, request_code, request)
+                    .await
+            }
             RequestCode::GetAllConsumerOffset => {
                 self.consumer_request_handler
                     .get_all_consumer_offset(channel, ctx, request_code, request)
                     .await
             }
+            RequestCode::GetAllDelayOffset => {
+                self.consumer_request_handler
+                    .get_all_delay_offset(channel, ctx, request_code, request)
+                    .await
+            }
+            RequestCode::UpdateAndCreateSubscriptionGroup => {
+                self.subscription_group_request_handler
+                    .update_and_create_subscription_group(channel, ctx, request_code, request)
+                    .await?
+            }
+            RequestCode::GetAllSubscriptionGroupConfig => {
+                self.subscription_group_request_handler
+                    .get_all_subscription_group_config(channel, ctx, request_code, request)
+                    .await?
+            }
+            RequestCode::DeleteSubscriptionGroup => {
+                self.subscription_group_request_handler
+                    .delete_subscription_group(channel, ctx, request_code, request)
+                    .await?
+            }
+            RequestCode::CleanUnusedTopic => {
+                self.topic_request_handler
+                    .clean_unused_topic(channel, ctx, request_code, request)
+                    .await
+            }
             RequestCode::GetTopicConfig => {
                 self.topic_request_handler
                     .get_topic_config(channel, ctx, request_code, request)
@@ -139,6 +196,11 @@ impl<MS: MessageStore> AdminBrokerProcessor<MS> {
                     .get_broker_runtime_info(channel, ctx, request_code, request)
                     .await
             }
+            RequestCode::GetBrokerReadiness => {
+                self.broker_config_request_handler
+                    .get_broker_readiness(channel, ctx, request_code, request)
+                    .await
+            }
             RequestCode::QueryTopicConsumeByWho => {
                 self.topic_request_handler
                     .query_topic_consume_by_who(channel, ctx, request_code, request)
@@ -152,12 +214,12 @@ impl<MS: MessageStore> AdminBrokerProcessor<MS> {
             RequestCode::GetMaxOffset => {
                 self.offset_request_handler
                     .get_max_offset(channel, ctx, request_code, request)
-                    .await
+                    .await?
             }
             RequestCode::GetMinOffset => {
                 self.offset_request_handler
                     .get_min_offset(channel, ctx, request_code, request)
-                    .await
+                    .await?
             }
 
             RequestCode::LockBatchMq => {
@@ -172,7 +234,8 @@ impl<MS: MessageStore> AdminBrokerProcessor<MS> {
                     .await
             }
             _ => Some(get_unknown_cmd_response(request_code)),
-        }
+        };
+        Ok(response)
     }
 }
 
@@ -16,11 +16,13 @@
  */
 
 use std::collections::HashMap;
+use std::sync::atomic::Ordering;
 
 use cheetah_string::CheetahString;
 use rocketmq_common::common::mix_all;
 use rocketmq_common::common::mq_version::RocketMqVersion;
 use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::code::response_code::ResponseCode;
 use rocketmq_remoting::net::channel::Channel;
 use rocketmq_remoting::protocol::body::kv_table::KVTable;
 use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
@@ -28,6 +30,7 @@ use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerCon
 use rocketmq_rust::ArcMut;
 use rocketmq_store::base::message_store::MessageStore;
 use sysinfo::Disks;
+use tracing::info;
 
 use crate::broker_runtime::BrokerRuntimeInner;
 
@@ -44,14 +47,75 @@ impl<MS> BrokerConfigRequestHandler<MS> {
     }
 }
 impl<MS: MessageStore> BrokerConfigRequestHandler<MS> {
+    /// Applies the properties in the request body. Only the request audit settings can change
+    /// while the broker is running, a request with any other property is refused as a whole.
     pub async fn update_broker_config(
         &mut self,
-        _channel: Channel,
+        channel: Channel,
         _ctx: ConnectionHandlerContext,
         _request_code: RequestCode,
-        _request: RemotingCommand,
+        request: RemotingCommand,
     ) -> Option<RemotingCommand> {
-        todo!()
+        let Some(properties) = request
+            .body()
+            .as_ref()
+            .and_then(|body| std::str::from_utf8(body).ok())
+            .and_then(mix_all::string_to_properties)
+        else {
+            return Some(RemotingCommand::create_response_command_with_code_remark(
+                ResponseCode::SystemError,
+                "string2Properties error",
+            ));
+        };
+        let mut enable_request_audit = None;
+        let mut request_audit_sample_rates = None;
+        for (key, value) in &properties {
+            match key.as_str() {
+                "enableRequestAudit" => match value.parse::<bool>() {
+                    Ok(enable) => enable_request_audit = Some(enable),
+                    Err(_) => {
+                        return Some(RemotingCommand::create_response_command_with_code_remark(
+                            ResponseCode::SystemError,
+                            format!("enableRequestAudit `{value}` is not a boolean"),
+                        ));
+                    }
+                },
+                "requestAuditSampleRates" => request_audit_sample_rates = Some(value),
+                _ => {
+                    return Some(RemotingCommand::create_response_command_with_code_remark(
+                        ResponseCode::NoPermission,
+                        format!("{key} can not be updated while the broker is running"),
+                    ));
+                }
+            }
+        }
+        let request_auditor = self.broker_runtime_inner.request_auditor();
+        if let Some(rates) = request_audit_sample_rates {
+            if let Err(e) = request_auditor.update_sample_rates(rates) {
+                return Some(RemotingCommand::create_response_command_with_code_remark(
+                    ResponseCode::SystemError,
+                    e,
+                ));
+            }
+        }
+        if let Some(enable) = enable_request_audit {
+            request_auditor.set_enable(enable);
+        }
+        // keep the config getBrokerConfig reports in line with the auditor
+        let mut broker_config = self.broker_runtime_inner.broker_config().clone();
+        if let Some(enable) = enable_request_audit {
+            broker_config.enable_request_audit = enable;
+        }
+        if let Some(rates) = request_audit_sample_rates {
+            broker_config.request_audit_sample_rates = rates.clone();
+        }
+        self.broker_runtime_inner.set_broker_config(broker_config);
+        info!(
+            "update broker config by {}: {:?}",
+            channel.remote_address(),
+            properties
+        );
+        Some(RemotingCommand::create_response_command())
     }
 
     pub async fn get_broker_config(
@@ -88,6 +152,42 @@ impl<MS: MessageStore> BrokerConfigRequestHandler<MS> {
         Some(response)
     }
 
+    /// Answers whether the broker finished its startup, with the startup steps still pending.
+    /// A broker that is not ready answers SERVICE_NOT_AVAILABLE.
+    pub async fn get_broker_readiness(
+        &mut self,
+        _channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        _request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let is_isolated = self
+            .broker_runtime_inner
+            .is_isolated()
+            .load(Ordering::Acquire);
+        let pending_steps = self
+            .broker_runtime_inner
+            .readiness()
+            .pending_steps(is_isolated);
+        let body = serde_json::json!({
+            "ready": pending_steps.is_empty(),
+            "pendingSteps": pending_steps,
+        })
+        .to_string();
+        let response = if pending_steps.is_empty() {
+            RemotingCommand::create_response_command()
+        } else {
+            RemotingCommand::create_response_command_with_code_remark(
+                ResponseCode::ServiceNotAvailable,
+                format!(
+                    "broker is not ready, waiting for {}",
+                    pending_steps.join(", ")
+                ),
+            )
+        };
+        Some(response.set_body(body))
+    }
+
     pub async fn get_broker_runtime_info(
         &mut self,
         _channel: Channel,
@@ -114,6 +214,28 @@ impl<MS: MessageStore> BrokerConfigRequestHandler<MS> {
         self.broker_runtime_inner
             .schedule_message_service()
             .build_running_stats(&mut runtime_info);
+        let broker_stats_manager = self.broker_runtime_inner.broker_stats_manager();
+        runtime_info.insert(
+            "scheduleDeliverFailedNums".to_string(),
+            broker_stats_manager
+                .get_schedule_deliver_failed_nums()
+                .to_string(),
+        );
+        runtime_info.insert(
+            "popReviveFailedNums".to_string(),
+            broker_stats_manager
+                .get_pop_revive_failed_nums()
+                .to_string(),
+        );
+        let flow_controller = self.broker_runtime_inner.flow_controller();
+        runtime_info.insert(
+            "topicFlowControlThrottledTimes".to_string(),
+            flow_controller.topic_throttled_times().to_string(),
+        );
+        runtime_info.insert(
+            "groupFlowControlThrottledTimes".to_string(),
+            flow_controller.group_throttled_times().to_string(),
+        );
         runtime_info.insert(
             "brokerActive".to_string(),
             self.is_special_service_running().to_string(),
@@ -210,61 +332,34 @@ impl<MS: MessageStore> BrokerConfigRequestHandler<MS> {
             .broker_runtime_inner
             .message_store_config()
             .is_timer_wheel_enable();
-        if is_timer_wheel_enable {
+        // stores without a timer store report the timer as idle
+        let timer_message_store = self
+            .broker_runtime_inner
+            .message_store_unchecked()
+            .get_timer_message_store()
+            .filter(|_| is_timer_wheel_enable);
+        if let Some(timer_message_store) = timer_message_store {
             runtime_info.insert(
                 "timerReadBehind".to_string(),
-                self.broker_runtime_inner
-                    .message_store()
-                    .as_ref()
-                    .unwrap()
-                    .get_timer_message_store()
-                    .unwrap()
-                    .get_dequeue_behind()
-                    .to_string(),
+                timer_message_store.get_dequeue_behind().to_string(),
             );
             runtime_info.insert(
                 "timerOffsetBehind".to_string(),
-                self.broker_runtime_inner
-                    .message_store()
-                    .as_ref()
-                    .unwrap()
-                    .get_timer_message_store()
-                    .unwrap()
+                timer_message_store
                     .get_enqueue_behind_messages()
                     .to_string(),
             );
             runtime_info.insert(
                 "timerCongestNum".to_string(),
-                self.broker_runtime_inner
-                    .message_store()
-                    .as_ref()
-                    .unwrap()
-                    .get_timer_message_store()
-                    .unwrap()
-                    .get_all_congest_num()
-                    .to_string(),
+                timer_message_store.get_all_congest_num().to_string(),
             );
             runtime_info.insert(
                 "timerEnqueueTps".to_string(),
-                self.broker_runtime_inner
-                    .message_store()
-                    .as_ref()
-                    .unwrap()
-                    .get_timer_message_store()
-                    .unwrap()
-                    .get_enqueue_tps()
-                    .to_string(),
+                timer_message_store.get_enqueue_tps().to_string(),
             );
             runtime_info.insert(
                 "timerDequeueTps".to_string(),
-                self.broker_runtime_inner
-                    .message_store()
-                    .as_ref()
-                    .unwrap()
-                    .get_timer_message_store()
-                    .unwrap()
-                    .get_dequeue_tps()
-                    .to_string(),
+                timer_message_store.get_dequeue_tps().to_string(),
             );
         } else {
             runtime_info.insert("timerReadBehind".to_string(), "0".to_string());
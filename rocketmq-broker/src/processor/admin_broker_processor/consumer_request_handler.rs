@@ -17,17 +17,23 @@
 
 use std::collections::HashSet;
 
+use cheetah_string::CheetahString;
 use rocketmq_common::common::config_manager::ConfigManager;
 use rocketmq_common::common::message::message_queue::MessageQueue;
+use rocketmq_common::common::mq_version::RocketMqVersion;
 use rocketmq_remoting::code::request_code::RequestCode;
 use rocketmq_remoting::code::response_code::ResponseCode;
 use rocketmq_remoting::net::channel::Channel;
 use rocketmq_remoting::protocol::admin::consume_stats::ConsumeStats;
 use rocketmq_remoting::protocol::admin::offset_wrapper::OffsetWrapper;
+use rocketmq_remoting::protocol::admin::pop_stats::PopQueueStats;
+use rocketmq_remoting::protocol::admin::pop_stats::PopStats;
 use rocketmq_remoting::protocol::body::connection::Connection;
 use rocketmq_remoting::protocol::body::consumer_connection::ConsumerConnection;
 use rocketmq_remoting::protocol::header::get_consume_stats_request_header::GetConsumeStatsRequestHeader;
 use rocketmq_remoting::protocol::header::get_consumer_connection_list_request_header::GetConsumerConnectionListRequestHeader;
+use rocketmq_remoting::protocol::header::get_consumer_running_info_request_header::GetConsumerRunningInfoRequestHeader;
+use rocketmq_remoting::protocol::heartbeat::consume_type::ConsumeType;
 use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
 use rocketmq_remoting::protocol::RemotingSerializable;
 use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
@@ -36,6 +42,9 @@ use rocketmq_store::base::message_store::MessageStore;
 use tracing::warn;
 
 use crate::broker_runtime::BrokerRuntimeInner;
+use crate::client::net::broker_to_client::Broker2Client;
+
+const CALL_CONSUMER_TIMEOUT_MILLIS: u64 = 10_000;
 
 #[derive(Clone)]
 pub(super) struct ConsumerRequestHandler<MS> {
@@ -85,6 +94,14 @@ impl<MS: MessageStore> ConsumerRequestHandler<MS> {
                     connection.set_version(channel_info.version());
                     connection
                         .set_client_addr(channel_info.key().remote_address().to_string().into());
+                    connection.set_last_update_timestamp(channel_info.last_update_timestamp());
+                    if let Some(rate) = self.broker_runtime_inner.consumer_manager().consume_rate(
+                        request_header.get_consumer_group(),
+                        channel_info.client_id(),
+                    ) {
+                        connection.set_msgs_per_minute(rate.msgs_per_minute);
+                        connection.set_last_msg_timestamp(rate.last_update_timestamp);
+                    }
                     body_data.get_connection_set().insert(connection);
                 }
                 let body = body_data
@@ -104,6 +121,85 @@ impl<MS: MessageStore> ConsumerRequestHandler<MS> {
         }
     }
 
+    /// Forwards the running info request to the consumer, the client has to be recent enough to
+    /// answer it.
+    pub async fn get_consumer_running_info(
+        &mut self,
+        _channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let request_header = request
+            .decode_command_custom_header::<GetConsumerRunningInfoRequestHeader>()
+            .unwrap();
+        self.call_consumer(
+            RequestCode::GetConsumerRunningInfo,
+            request,
+            &request_header.consumer_group,
+            &request_header.client_id,
+        )
+        .await
+    }
+
+    async fn call_consumer(
+        &mut self,
+        request_code: RequestCode,
+        request: RemotingCommand,
+        consumer_group: &CheetahString,
+        client_id: &CheetahString,
+    ) -> Option<RemotingCommand> {
+        let response = RemotingCommand::create_response_command();
+        let client_channel_info = self
+            .broker_runtime_inner
+            .consumer_manager()
+            .find_channel_by_client_id(consumer_group, client_id);
+        let Some(client_channel_info) = client_channel_info else {
+            return Some(
+                response
+                    .set_code(ResponseCode::SystemError)
+                    .set_remark(format!(
+                        "The Consumer <{}> <{}> not online",
+                        consumer_group, client_id
+                    )),
+            );
+        };
+        if client_channel_info.version() < i32::from(RocketMqVersion::V318Snapshot) {
+            return Some(
+                response
+                    .set_code(ResponseCode::SystemError)
+                    .set_remark(format!(
+                        "The Consumer <{}> Version <{}> too low to finish, please upgrade it to {}",
+                        client_id,
+                        RocketMqVersion::from_version(client_channel_info.version()),
+                        RocketMqVersion::V318Snapshot
+                    )),
+            );
+        }
+        let mut new_request = RemotingCommand::create_remoting_command(request_code);
+        if let Some(ext_fields) = request.ext_fields() {
+            new_request = new_request.set_ext_fields(ext_fields.clone());
+        }
+        if let Some(body) = request.get_body() {
+            new_request = new_request.set_body(body.clone());
+        }
+        let mut channel = client_channel_info.channel().clone();
+        match Broker2Client
+            .call_client(&mut channel, new_request, CALL_CONSUMER_TIMEOUT_MILLIS)
+            .await
+        {
+            Ok(consumer_response) => Some(consumer_response),
+            Err(e) => Some(
+                response
+                    .set_code(ResponseCode::SystemError)
+                    .set_remark(format!(
+                        "invoke consumer <{}> <{}> Exception: {}",
+                        consumer_group, client_id, e
+                    )),
+            ),
+        }
+    }
+
     pub async fn get_consume_stats(
         &mut self,
         _channel: Channel,
@@ -116,15 +212,7 @@ impl<MS: MessageStore> ConsumerRequestHandler<MS> {
             .decode_command_custom_header::<GetConsumeStatsRequestHeader>()
             .unwrap();
         let mut consume_stats = ConsumeStats::new();
-        let mut topics = HashSet::new();
-        if request_header.get_topic().is_empty() {
-            topics = self
-                .broker_runtime_inner
-                .consumer_offset_manager()
-                .which_topic_by_consumer(request_header.get_consumer_group());
-        } else {
-            topics.insert(request_header.get_topic().clone());
-        }
+        let topics = self.consumed_topics(&request_header);
         for topic in topics.iter() {
             let topic_config = self
                 .broker_runtime_inner
@@ -218,7 +306,7 @@ impl<MS: MessageStore> ConsumerRequestHandler<MS> {
                     }
                 }
 
-                consume_stats.get_offset_table().insert(mq, offset_wrapper);
+                consume_stats.offset_table.insert(mq, offset_wrapper);
             }
 
             let consume_tps = self
@@ -229,11 +317,114 @@ impl<MS: MessageStore> ConsumerRequestHandler<MS> {
             let new_consume_tps = consume_stats.get_consume_tps() + consume_tps;
             consume_stats.set_consume_tps(new_consume_tps);
         }
+        let pop_stats = self.build_pop_stats(request_header.get_consumer_group(), &topics);
+        if self.is_pop_consumer(request_header.get_consumer_group())
+            || pop_stats
+                .offset_table
+                .values()
+                .any(|stats| stats.inflight_message_num > 0 || stats.buffered_message_num > 0)
+        {
+            consume_stats.set_pop_stats(Some(pop_stats));
+        }
         let body = consume_stats.encode().expect("consume stats encode failed");
         response.set_body_mut_ref(body);
         Some(response)
     }
 
+    pub async fn get_pop_stats(
+        &mut self,
+        _channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let mut response = RemotingCommand::create_response_command();
+        let request_header = request
+            .decode_command_custom_header::<GetConsumeStatsRequestHeader>()
+            .unwrap();
+        let topics = self.consumed_topics(&request_header);
+        let pop_stats = self.build_pop_stats(request_header.get_consumer_group(), &topics);
+        let body = pop_stats.encode().expect("pop stats encode failed");
+        response.set_body_mut_ref(body);
+        Some(response)
+    }
+
+    /// Topics of the request, or every topic the group has committed offsets for if none is given.
+    fn consumed_topics(
+        &self,
+        request_header: &GetConsumeStatsRequestHeader,
+    ) -> HashSet<CheetahString> {
+        if request_header.get_topic().is_empty() {
+            self.broker_runtime_inner
+                .consumer_offset_manager()
+                .which_topic_by_consumer(request_header.get_consumer_group())
+        } else {
+            HashSet::from([request_header.get_topic().clone()])
+        }
+    }
+
+    fn is_pop_consumer(&self, group: &CheetahString) -> bool {
+        self.broker_runtime_inner
+            .consumer_manager()
+            .get_consumer_group_info(group)
+            .is_some_and(|group_info| group_info.get_consume_type() == ConsumeType::ConsumePop)
+    }
+
+    /// Collects the in flight messages of `group` per queue and the revive backlog of this broker.
+    fn build_pop_stats(&self, group: &CheetahString, topics: &HashSet<CheetahString>) -> PopStats {
+        let mut pop_stats = PopStats::default();
+        let broker_name = &self.broker_runtime_inner.broker_config().broker_name;
+        for topic in topics.iter() {
+            let Some(topic_config) = self
+                .broker_runtime_inner
+                .topic_config_manager()
+                .select_topic_config(topic)
+            else {
+                continue;
+            };
+            for queue_id in 0..topic_config.get_read_queue_nums() as i32 {
+                let broker_offset = self
+                    .broker_runtime_inner
+                    .message_store_unchecked()
+                    .get_max_offset_in_queue(topic, queue_id)
+                    .max(0);
+                let consumer_offset = self
+                    .broker_runtime_inner
+                    .consumer_offset_manager()
+                    .query_offset(group, topic, queue_id)
+                    .max(0);
+                let inflight_message_num = self
+                    .broker_runtime_inner
+                    .pop_inflight_message_counter()
+                    .get_group_pop_in_flight_message_num(topic, group, queue_id);
+                let buffered_message_num = self
+                    .broker_runtime_inner
+                    .pop_message_processor()
+                    .map_or(0, |pop_message_processor| {
+                        pop_message_processor
+                            .pop_buffer_merge_service()
+                            .get_buffered_message_num(topic, group, queue_id)
+                    });
+                pop_stats.offset_table.insert(
+                    MessageQueue::from_parts(topic.clone(), broker_name.clone(), queue_id),
+                    PopQueueStats {
+                        broker_offset,
+                        consumer_offset,
+                        inflight_message_num,
+                        buffered_message_num,
+                    },
+                );
+            }
+        }
+        if let Some(ack_message_processor) = self.broker_runtime_inner.ack_message_processor() {
+            let (revive_behind_messages, revive_behind_millis) =
+                ack_message_processor.get_revive_behind();
+            pop_stats.revive_behind_messages = revive_behind_messages;
+            pop_stats.revive_behind_millis = revive_behind_millis;
+        }
+        pop_stats
+    }
+
     pub async fn get_all_consumer_offset(
         &mut self,
         _channel: Channel,
@@ -257,4 +448,28 @@ impl<MS: MessageStore> ConsumerRequestHandler<MS> {
             )
         }
     }
+
+    pub async fn get_all_delay_offset(
+        &mut self,
+        _channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        _request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let mut response = RemotingCommand::create_response_command();
+        let content = self
+            .broker_runtime_inner
+            .schedule_message_service()
+            .encode_pretty(false);
+        if !content.is_empty() {
+            response.set_body_mut_ref(content);
+            Some(response)
+        } else {
+            Some(
+                response
+                    .set_code(ResponseCode::SystemError)
+                    .set_remark("No delay offset in this broker"),
+            )
+        }
+    }
 }
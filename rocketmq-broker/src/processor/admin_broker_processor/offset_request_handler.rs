@@ -14,6 +14,7 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use rocketmq_error::RocketMQResult;
 use rocketmq_remoting::code::request_code::RequestCode;
 use rocketmq_remoting::code::response_code::ResponseCode;
 use rocketmq_remoting::net::channel::Channel;
@@ -53,10 +54,8 @@ impl<MS: MessageStore> OffsetRequestHandler<MS> {
         _ctx: ConnectionHandlerContext,
         _request_code: RequestCode,
         request: RemotingCommand,
-    ) -> Option<RemotingCommand> {
-        let request_header = request
-            .decode_command_custom_header::<GetMaxOffsetRequestHeader>()
-            .unwrap(); //need to optimize
+    ) -> RocketMQResult<Option<RemotingCommand>> {
+        let request_header = request.decode_command_custom_header::<GetMaxOffsetRequestHeader>()?;
         let mapping_context = self
             .broker_runtime_inner
             .topic_queue_mapping_manager()
@@ -67,7 +66,7 @@ impl<MS: MessageStore> OffsetRequestHandler<MS> {
             .rewrite_request_for_static_topic(request_header, mapping_context)
             .await;
         if rewrite_result.is_some() {
-            return rewrite_result;
+            return Ok(rewrite_result);
         }
 
         let offset = self
@@ -77,9 +76,9 @@ impl<MS: MessageStore> OffsetRequestHandler<MS> {
             .unwrap()
             .get_max_offset_in_queue(topic.as_ref(), queue_id);
         let response_header = GetMaxOffsetResponseHeader { offset };
-        Some(RemotingCommand::create_response_command_with_header(
+        Ok(Some(RemotingCommand::create_response_command_with_header(
             response_header,
-        ))
+        )))
     }
 
     pub async fn get_min_offset(
@@ -88,10 +87,8 @@ impl<MS: MessageStore> OffsetRequestHandler<MS> {
         _ctx: ConnectionHandlerContext,
         _request_code: RequestCode,
         request: RemotingCommand,
-    ) -> Option<RemotingCommand> {
-        let request_header = request
-            .decode_command_custom_header::<GetMinOffsetRequestHeader>()
-            .unwrap(); //need to optimize
+    ) -> RocketMQResult<Option<RemotingCommand>> {
+        let request_header = request.decode_command_custom_header::<GetMinOffsetRequestHeader>()?;
 
         let mapping_context = self
             .broker_runtime_inner
@@ -103,7 +100,7 @@ impl<MS: MessageStore> OffsetRequestHandler<MS> {
             .handle_get_min_offset_for_static_topic(request_header, mapping_context)
             .await;
         if rewrite_result.is_some() {
-            return rewrite_result;
+            return Ok(rewrite_result);
         }
 
         let offset = self
@@ -113,9 +110,9 @@ impl<MS: MessageStore> OffsetRequestHandler<MS> {
             .unwrap()
             .get_min_offset_in_queue(topic.as_ref(), queue_id);
         let response_header = GetMinOffsetResponseHeader { offset };
-        Some(RemotingCommand::create_response_command_with_header(
+        Ok(Some(RemotingCommand::create_response_command_with_header(
             response_header,
-        ))
+        )))
     }
     /*
     async fn handle_get_min_offset(
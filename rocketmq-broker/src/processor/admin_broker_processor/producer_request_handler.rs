@@ -0,0 +1,92 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::code::response_code::ResponseCode;
+use rocketmq_remoting::net::channel::Channel;
+use rocketmq_remoting::protocol::body::connection::Connection;
+use rocketmq_remoting::protocol::body::producer_connection::ProducerConnection;
+use rocketmq_remoting::protocol::header::get_producer_connection_list_request_header::GetProducerConnectionListRequestHeader;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_remoting::protocol::RemotingSerializable;
+use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
+use rocketmq_rust::ArcMut;
+use rocketmq_store::base::message_store::MessageStore;
+
+use crate::broker_runtime::BrokerRuntimeInner;
+
+#[derive(Clone)]
+pub(super) struct ProducerRequestHandler<MS> {
+    broker_runtime_inner: ArcMut<BrokerRuntimeInner<MS>>,
+}
+
+impl<MS> ProducerRequestHandler<MS> {
+    pub fn new(broker_runtime_inner: ArcMut<BrokerRuntimeInner<MS>>) -> Self {
+        Self {
+            broker_runtime_inner,
+        }
+    }
+}
+
+impl<MS: MessageStore> ProducerRequestHandler<MS> {
+    pub async fn get_producer_connection_list(
+        &mut self,
+        _channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        let mut response = RemotingCommand::create_response_command();
+        let request_header = request
+            .decode_command_custom_header::<GetProducerConnectionListRequestHeader>()
+            .unwrap();
+        let producer_manager = self.broker_runtime_inner.producer_manager();
+        let channels = producer_manager.get_group_channels(request_header.get_producer_group());
+        if channels.is_empty() {
+            return Some(
+                response
+                    .set_code(ResponseCode::SystemError)
+                    .set_remark(format!(
+                        "the producer group[{}] not exist",
+                        request_header.get_producer_group()
+                    )),
+            );
+        }
+        let mut body_data = ProducerConnection::default();
+        for channel_info in channels {
+            let mut connection = Connection::new();
+            connection.set_client_id(channel_info.client_id().clone());
+            connection.set_language(channel_info.language());
+            connection.set_version(channel_info.version());
+            connection.set_client_addr(channel_info.channel().remote_address().to_string().into());
+            connection.set_last_update_timestamp(channel_info.last_update_timestamp());
+            if let Some(rate) = producer_manager.produce_rate(
+                request_header.get_producer_group(),
+                channel_info.client_id(),
+            ) {
+                connection.set_msgs_per_minute(rate.msgs_per_minute);
+                connection.set_last_msg_timestamp(rate.last_update_timestamp);
+            }
+            body_data.connection_set.insert(connection);
+        }
+        let body = body_data
+            .encode()
+            .expect("producer connection list encode failed");
+        response.set_body_mut_ref(body);
+        Some(response)
+    }
+}
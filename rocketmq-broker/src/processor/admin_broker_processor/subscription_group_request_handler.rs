@@ -0,0 +1,143 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use rocketmq_common::common::config_manager::ConfigManager;
+use rocketmq_error::response_err;
+use rocketmq_error::ResponseErr;
+use rocketmq_error::RocketMQResult;
+use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::code::response_code::ResponseCode;
+use rocketmq_remoting::net::channel::Channel;
+use rocketmq_remoting::protocol::header::delete_subscription_group_request_header::DeleteSubscriptionGroupRequestHeader;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_remoting::protocol::subscription::subscription_group_config::SubscriptionGroupConfig;
+use rocketmq_remoting::protocol::RemotingDeserializable;
+use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
+use rocketmq_rust::ArcMut;
+use rocketmq_store::base::message_store::MessageStore;
+use tracing::info;
+use tracing::warn;
+
+use crate::broker_runtime::BrokerRuntimeInner;
+
+#[derive(Clone)]
+pub(super) struct SubscriptionGroupRequestHandler<MS> {
+    broker_runtime_inner: ArcMut<BrokerRuntimeInner<MS>>,
+}
+
+impl<MS> SubscriptionGroupRequestHandler<MS> {
+    pub fn new(broker_runtime_inner: ArcMut<BrokerRuntimeInner<MS>>) -> Self {
+        Self {
+            broker_runtime_inner,
+        }
+    }
+}
+
+impl<MS: MessageStore> SubscriptionGroupRequestHandler<MS> {
+    pub async fn update_and_create_subscription_group(
+        &mut self,
+        channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        request: RemotingCommand,
+    ) -> RocketMQResult<Option<RemotingCommand>> {
+        info!(
+            "AdminBrokerProcessor#updateAndCreateSubscriptionGroup called by {}",
+            channel.remote_address()
+        );
+        let Some(body) = request.body() else {
+            return response_err!(
+                ResponseCode::SystemError,
+                "subscription group config is empty"
+            );
+        };
+        let mut config = SubscriptionGroupConfig::decode(body.as_ref()).map_err(|e| {
+            ResponseErr::new(
+                ResponseCode::SystemError,
+                format!("decode subscription group config failed: {e}"),
+            )
+        })?;
+        self.broker_runtime_inner
+            .subscription_group_manager()
+            .update_subscription_group_config(&mut config)
+            .map_err(|e| ResponseErr::new(ResponseCode::SystemError, e.to_string()))?;
+        Ok(Some(RemotingCommand::create_response_command()))
+    }
+
+    pub async fn get_all_subscription_group_config(
+        &mut self,
+        _channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        _request: RemotingCommand,
+    ) -> RocketMQResult<Option<RemotingCommand>> {
+        let content = self
+            .broker_runtime_inner
+            .subscription_group_manager()
+            .encode_pretty(false);
+        if content.is_empty() {
+            warn!("No subscription group in this broker");
+            return response_err!(
+                ResponseCode::SystemError,
+                "No subscription group in this broker"
+            );
+        }
+        Ok(Some(
+            RemotingCommand::create_response_command().set_body(content),
+        ))
+    }
+
+    pub async fn delete_subscription_group(
+        &mut self,
+        channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        request: RemotingCommand,
+    ) -> RocketMQResult<Option<RemotingCommand>> {
+        let request_header =
+            request.decode_command_custom_header::<DeleteSubscriptionGroupRequestHeader>()?;
+        info!(
+            "AdminBrokerProcessor#deleteSubscriptionGroup, caller={}",
+            channel.remote_address()
+        );
+        let group_name = &request_header.group_name;
+        self.broker_runtime_inner
+            .subscription_group_manager()
+            .delete_subscription_group_config(group_name);
+        self.broker_runtime_inner
+            .flow_controller()
+            .remove_group(group_name);
+        if request_header.clean_offset {
+            self.broker_runtime_inner
+                .consumer_offset_manager()
+                .remove_offset(group_name);
+            self.broker_runtime_inner
+                .pop_inflight_message_counter()
+                .clear_in_flight_message_num_by_group_name(group_name);
+        }
+        if self
+            .broker_runtime_inner
+            .broker_config()
+            .auto_delete_unused_stats
+        {
+            self.broker_runtime_inner
+                .broker_stats_manager()
+                .on_group_deleted(group_name);
+        }
+        Ok(Some(RemotingCommand::create_response_command()))
+    }
+}
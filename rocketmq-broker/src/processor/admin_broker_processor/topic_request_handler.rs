@@ -16,18 +16,24 @@
  */
 
 use std::collections::HashMap;
+use std::collections::HashSet;
 
 use cheetah_string::CheetahString;
 use rocketmq_common::common::attribute::attribute_parser::AttributeParser;
 use rocketmq_common::common::attribute::topic_message_type::TopicMessageType;
 use rocketmq_common::common::config::TopicConfig;
+use rocketmq_common::common::config_manager::ConfigManager;
+use rocketmq_common::common::constant::PermName;
 use rocketmq_common::common::key_builder::KeyBuilder;
 use rocketmq_common::common::message::message_queue::MessageQueue;
 use rocketmq_common::common::topic::TopicValidator;
 use rocketmq_common::common::TopicFilterType;
+use rocketmq_common::utils::network_util::NetworkUtil;
+use rocketmq_error::RocketMQResult;
 use rocketmq_remoting::code::request_code::RequestCode;
 use rocketmq_remoting::code::response_code::ResponseCode;
 use rocketmq_remoting::net::channel::Channel;
+use rocketmq_remoting::protocol::admin::topic_disk_usage::TopicDiskUsage;
 use rocketmq_remoting::protocol::admin::topic_offset::TopicOffset;
 use rocketmq_remoting::protocol::admin::topic_stats_table::TopicStatsTable;
 use rocketmq_remoting::protocol::body::create_topic_list_request_body::CreateTopicListRequestBody;
@@ -38,6 +44,7 @@ use rocketmq_remoting::protocol::body::topic_info_wrapper::topic_config_wrapper:
 use rocketmq_remoting::protocol::header::create_topic_request_header::CreateTopicRequestHeader;
 use rocketmq_remoting::protocol::header::delete_topic_request_header::DeleteTopicRequestHeader;
 use rocketmq_remoting::protocol::header::get_topic_config_request_header::GetTopicConfigRequestHeader;
+use rocketmq_remoting::protocol::header::get_topic_disk_usage_request_header::GetTopicDiskUsageRequestHeader;
 use rocketmq_remoting::protocol::header::get_topic_stats_request_header::GetTopicStatsRequestHeader;
 use rocketmq_remoting::protocol::header::query_topic_consume_by_who_request_header::QueryTopicConsumeByWhoRequestHeader;
 use rocketmq_remoting::protocol::header::query_topics_by_consumer_request_header::QueryTopicsByConsumerRequestHeader;
@@ -49,6 +56,7 @@ use rocketmq_remoting::protocol::RemotingSerializable;
 use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
 use rocketmq_rust::ArcMut;
 use rocketmq_store::base::message_store::MessageStore;
+use tracing::error;
 use tracing::info;
 
 use crate::broker_runtime::BrokerRuntimeInner;
@@ -107,6 +115,16 @@ impl<MS: MessageStore> TopicRequestHandler<MS> {
                     )),
             );
         }
+        if request_header.perm < 0 || !PermName::is_valid(request_header.perm as u32) {
+            return Some(
+                response
+                    .set_code(ResponseCode::SystemError)
+                    .set_remark(format!(
+                        "topicPermission value: {} is invalid.",
+                        request_header.perm
+                    )),
+            );
+        }
 
         let attributes = match AttributeParser::parse_to_map(
             request_header
@@ -167,9 +185,18 @@ impl<MS: MessageStore> TopicRequestHandler<MS> {
             );
             return Some(response.set_code(ResponseCode::Success));
         }
-        self.broker_runtime_inner
+        if let Err(e) = self
+            .broker_runtime_inner
             .topic_config_manager_mut()
-            .update_topic_config(&mut topic_config);
+            .update_topic_config(&mut topic_config)
+        {
+            error!("update topic config failed, topic: {}, {}", topic, e);
+            return Some(
+                response
+                    .set_code(ResponseCode::SystemError)
+                    .set_remark(e.to_string()),
+            );
+        }
 
         if self
             .broker_runtime_inner
@@ -273,9 +300,21 @@ impl<MS: MessageStore> TopicRequestHandler<MS> {
             }
         }
 
-        self.broker_runtime_inner
+        if let Err(e) = self
+            .broker_runtime_inner
             .topic_config_manager_mut()
-            .update_topic_config_list(request_body.topic_config_list.as_mut_slice());
+            .update_topic_config_list(request_body.topic_config_list.as_mut_slice())
+        {
+            error!(
+                "update topic config list failed, topics: {}, {}",
+                topic_names, e
+            );
+            return Some(
+                response
+                    .set_code(ResponseCode::SystemError)
+                    .set_remark(e.to_string()),
+            );
+        }
         if self
             .broker_runtime_inner
             .broker_config()
@@ -325,7 +364,7 @@ impl<MS: MessageStore> TopicRequestHandler<MS> {
             return Some(
                 response
                     .set_code(ResponseCode::SystemError)
-                    .set_remark("he specified topic is blank."),
+                    .set_remark("The specified topic is blank."),
             );
         }
         if self
@@ -374,6 +413,62 @@ impl<MS: MessageStore> TopicRequestHandler<MS> {
         Some(response.set_code(ResponseCode::Success))
     }
 
+    /// Drops the consume queues of topics no longer configured and prunes the consumer offsets
+    /// of topics that were deleted or that a group stopped subscribing and has nothing left to
+    /// consume from.
+    pub async fn clean_unused_topic(
+        &mut self,
+        channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        _request: RemotingCommand,
+    ) -> Option<RemotingCommand> {
+        info!(
+            "AdminBrokerProcessor#cleanUnusedTopic: caller={}",
+            channel.remote_address()
+        );
+        let retain_topics: HashSet<String> = self
+            .broker_runtime_inner
+            .topic_config_manager()
+            .topic_config_table()
+            .lock()
+            .keys()
+            .map(|topic| topic.to_string())
+            .collect();
+        let message_store = self.broker_runtime_inner.message_store().clone();
+        if let Some(message_store) = message_store.as_ref() {
+            message_store.clean_unused_topic(&retain_topics);
+        }
+        let consumer_manager = self.broker_runtime_inner.consumer_manager();
+        let pruned = self
+            .broker_runtime_inner
+            .consumer_offset_manager()
+            .prune_offsets(|topic, group, offsets| {
+                if !retain_topics.contains(topic.as_str()) {
+                    return false;
+                }
+                if consumer_manager
+                    .find_subscription_data(group, topic)
+                    .is_some()
+                {
+                    return true;
+                }
+                // nobody subscribes any more, keep the offsets while messages are left behind them
+                let Some(message_store) = message_store.as_ref() else {
+                    return true;
+                };
+                offsets.is_empty()
+                    || offsets.iter().any(|(queue_id, offset)| {
+                        *offset > message_store.get_min_offset_in_queue(topic, *queue_id)
+                    })
+            });
+        info!(
+            "cleanUnusedTopic pruned the consumer offsets of {} topic@group entries",
+            pruned.len()
+        );
+        Some(RemotingCommand::create_response_command())
+    }
+
     pub async fn get_all_topic_config(
         &mut self,
         _channel: Channel,
@@ -516,6 +611,70 @@ impl<MS: MessageStore> TopicRequestHandler<MS> {
         Some(response)
     }
 
+    pub async fn get_topic_disk_usage(
+        &mut self,
+        _channel: Channel,
+        _ctx: ConnectionHandlerContext,
+        _request_code: RequestCode,
+        request: RemotingCommand,
+    ) -> RocketMQResult<Option<RemotingCommand>> {
+        let request_header =
+            request.decode_command_custom_header::<GetTopicDiskUsageRequestHeader>()?;
+        let topics = match request_header.topic {
+            Some(topic) if !topic.is_empty() => {
+                if self
+                    .broker_runtime_inner
+                    .topic_config_manager()
+                    .select_topic_config(&topic)
+                    .is_none()
+                {
+                    return Ok(Some(
+                        RemotingCommand::create_response_command_with_code_remark(
+                            ResponseCode::TopicNotExist,
+                            format!("The topic[{topic}] not exist."),
+                        ),
+                    ));
+                }
+                vec![topic]
+            }
+            _ => self
+                .broker_runtime_inner
+                .topic_config_manager()
+                .topic_config_table()
+                .lock()
+                .keys()
+                .cloned()
+                .collect(),
+        };
+        let message_store = self.broker_runtime_inner.message_store().clone().unwrap();
+        // the first count of a queue reads all of its entries, keep it off the async workers
+        let disk_usage_table = match tokio::task::spawn_blocking(move || {
+            topics
+                .into_iter()
+                .map(|topic| {
+                    let disk_usage = message_store.get_topic_disk_usage(&topic);
+                    (topic, disk_usage)
+                })
+                .collect()
+        })
+        .await
+        {
+            Ok(disk_usage_table) => disk_usage_table,
+            Err(e) => {
+                return Ok(Some(
+                    RemotingCommand::create_response_command_with_code_remark(
+                        ResponseCode::SystemError,
+                        format!("count the topic disk usage failed: {e}"),
+                    ),
+                ));
+            }
+        };
+        let topic_disk_usage = TopicDiskUsage { disk_usage_table };
+        Ok(Some(
+            RemotingCommand::create_response_command().set_body(topic_disk_usage.encode()?),
+        ))
+    }
+
     pub async fn get_topic_config(
         &mut self,
         _channel: Channel,
@@ -604,10 +763,12 @@ impl<MS: MessageStore> TopicRequestHandler<MS> {
             .broker_runtime_inner
             .consumer_offset_manager()
             .which_topic_by_consumer(request_header.get_group());
-        let broker_addr = format!(
-            "{}:{}",
-            self.broker_runtime_inner.broker_config().broker_ip1,
-            self.broker_runtime_inner.server_config().listen_port
+        let broker_addr = NetworkUtil::format_address(
+            self.broker_runtime_inner
+                .broker_config()
+                .broker_ip1
+                .as_str(),
+            self.broker_runtime_inner.server_config().listen_port,
         );
         let topic_list = TopicList {
             topic_list: topics.into_iter().collect(),
@@ -630,6 +791,21 @@ impl<MS: MessageStore> TopicRequestHandler<MS> {
         self.broker_runtime_inner
             .pop_inflight_message_counter()
             .clear_in_flight_message_num_by_topic_name(topic);
+        self.broker_runtime_inner
+            .send_dedup_table()
+            .remove_topic(topic);
+        self.broker_runtime_inner
+            .flow_controller()
+            .remove_topic(topic);
+        if self
+            .broker_runtime_inner
+            .consumer_order_info_manager()
+            .clear_by_topic(topic)
+        {
+            self.broker_runtime_inner
+                .consumer_order_info_manager()
+                .persist();
+        }
         self.broker_runtime_inner
             .message_store_mut()
             .as_mut()
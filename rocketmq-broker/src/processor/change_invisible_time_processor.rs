@@ -44,6 +44,7 @@ use tracing::info;
 
 use crate::broker_runtime::BrokerRuntimeInner;
 use crate::processor::pop_message_processor::PopMessageProcessor;
+use crate::processor::processor_service::revive_queues;
 
 pub struct ChangeInvisibleTimeProcessor<MS> {
     // broker_config: Arc<BrokerConfig>,
@@ -79,13 +80,6 @@ impl<MS: MessageStore> ChangeInvisibleTimeProcessor<MS> {
                 .broker_cluster_name
                 .as_str(),
         );
-        /*        let store_host = format!(
-            "{}:{}",
-            broker_runtime_inner.broker_config().broker_ip1,
-            broker_runtime_inner.broker_config().listen_port
-        )
-        .parse::<SocketAddr>()
-        .unwrap();*/
         ChangeInvisibleTimeProcessor {
             /* broker_config,
             topic_config_manager,
@@ -95,7 +89,6 @@ impl<MS: MessageStore> ChangeInvisibleTimeProcessor<MS> {
             broker_stats_manager,
             escape_bridge,*/
             revive_topic: CheetahString::from_string(revive_topic),
-            // store_host,
             pop_message_processor,
             broker_runtime_inner,
         }
@@ -208,7 +201,10 @@ where
         }
         // add new ck
         let now = get_current_millis();
-        let revive_qid = ExtraInfoUtil::get_revive_qid(extra_info.as_slice())?;
+        let revive_qid = revive_queues::reassign_revive_queue(
+            ExtraInfoUtil::get_revive_qid(extra_info.as_slice())?,
+            self.pop_message_processor.active_revive_queue_num(),
+        );
         let ck_result = self
             .append_check_point(
                 &request_header,
@@ -20,24 +20,36 @@ use std::sync::Arc;
 
 use cheetah_string::CheetahString;
 use rocketmq_common::common::constant::PermName;
+use rocketmq_common::common::filter::expression_type::ExpressionType;
 use rocketmq_common::common::mix_all;
 use rocketmq_common::common::mix_all::IS_SUB_CHANGE;
 use rocketmq_common::common::mix_all::IS_SUPPORT_HEART_BEAT_V2;
 use rocketmq_common::common::sys_flag::topic_sys_flag;
 use rocketmq_common::utils::serde_json_utils::SerdeJsonUtils;
 use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::code::response_code::RemotingSysResponseCode;
+use rocketmq_remoting::code::response_code::ResponseCode;
 use rocketmq_remoting::net::channel::Channel;
+use rocketmq_remoting::protocol::body::check_client_request_body::CheckClientRequestBody;
+use rocketmq_remoting::protocol::body::heartbeat_response_body::HeartbeatResponseBody;
+use rocketmq_remoting::protocol::body::set_message_request_mode_request_body::SetMessageRequestModeRequestBody;
 use rocketmq_remoting::protocol::header::unregister_client_request_header::UnregisterClientRequestHeader;
 use rocketmq_remoting::protocol::heartbeat::consume_type::ConsumeType;
 use rocketmq_remoting::protocol::heartbeat::heartbeat_data::HeartbeatData;
 use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_remoting::protocol::subscription::group_retry_policy::GroupRetryPolicy;
+use rocketmq_remoting::protocol::subscription::subscription_group_config::SubscriptionGroupConfig;
+use rocketmq_remoting::protocol::RemotingDeserializable;
+use rocketmq_remoting::protocol::RemotingSerializable;
 use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
 use rocketmq_rust::ArcMut;
 use rocketmq_store::base::message_store::MessageStore;
 use tracing::info;
+use tracing::warn;
 
 use crate::broker_runtime::BrokerRuntimeInner;
 use crate::client::client_channel_info::ClientChannelInfo;
+use crate::load_balance::message_request_mode_manager::MessageRequestModeManager;
 
 pub struct ClientManageProcessor<MS> {
     consumer_group_heartbeat_table: Arc<
@@ -45,6 +57,7 @@ pub struct ClientManageProcessor<MS> {
             HashMap<CheetahString /* ConsumerGroup */, i32 /* HeartbeatFingerprint */>,
         >,
     >,
+    message_request_mode_manager: MessageRequestModeManager,
     broker_runtime_inner: ArcMut<BrokerRuntimeInner<MS>>,
 }
 
@@ -52,9 +65,13 @@ impl<MS> ClientManageProcessor<MS>
 where
     MS: MessageStore,
 {
-    pub fn new(broker_runtime_inner: ArcMut<BrokerRuntimeInner<MS>>) -> Self {
+    pub fn new(
+        broker_runtime_inner: ArcMut<BrokerRuntimeInner<MS>>,
+        message_request_mode_manager: MessageRequestModeManager,
+    ) -> Self {
         Self {
             consumer_group_heartbeat_table: Arc::new(parking_lot::RwLock::new(HashMap::new())),
+            message_request_mode_manager,
             broker_runtime_inner,
         }
     }
@@ -74,15 +91,62 @@ where
         match request_code {
             RequestCode::HeartBeat => self.heart_beat(channel, ctx, request),
             RequestCode::UnregisterClient => self.unregister_client(channel, ctx, request),
-            RequestCode::CheckClientConfig => {
-                unimplemented!("CheckClientConfig")
-            }
+            RequestCode::CheckClientConfig => self.check_client_config(request),
             _ => {
                 unimplemented!("CheckClientConfig")
             }
         }
     }
 
+    /// Checks that the broker can serve the subscription of a client, so a consumer filtering by
+    /// SQL92 fails on start instead of never receiving messages.
+    fn check_client_config(
+        &self,
+        request: RemotingCommand,
+    ) -> rocketmq_error::RocketMQResult<Option<RemotingCommand>> {
+        let response = RemotingCommand::create_response_command();
+        let Some(body) = request.get_body() else {
+            return Ok(Some(response));
+        };
+        let request_body = CheckClientRequestBody::decode(body)?;
+        let subscription_data = &request_body.subscription_data;
+        if ExpressionType::is_tag_type(Some(subscription_data.expression_type.as_str())) {
+            return Ok(Some(response));
+        }
+        if !self
+            .broker_runtime_inner
+            .broker_config()
+            .enable_property_filter
+        {
+            return Ok(Some(
+                response
+                    .set_code(RemotingSysResponseCode::SystemError)
+                    .set_remark(format!(
+                        "The broker does not support consumer to filter message by {}",
+                        subscription_data.expression_type
+                    )),
+            ));
+        }
+        // there is no expression compiler yet, so only the expression type and presence are checked
+        if subscription_data.expression_type != ExpressionType::SQL92
+            || subscription_data.sub_string.trim().is_empty()
+        {
+            warn!(
+                "Client {}@{} filter message, but failed to compile expression! sub={}",
+                request_body.client_id, request_body.group, subscription_data.sub_string
+            );
+            return Ok(Some(
+                response
+                    .set_code(ResponseCode::SubscriptionParseFailed)
+                    .set_remark(format!(
+                        "unsupported expression type {} or empty expression",
+                        subscription_data.expression_type
+                    )),
+            ));
+        }
+        Ok(Some(response))
+    }
+
     fn unregister_client(
         &self,
         channel: Channel,
@@ -181,6 +245,12 @@ where
                 continue;
             }
             let subscription_group_config = subscription_group_config.unwrap();
+            if let Some(ref group_retry_policy) = consumer_data.group_retry_policy {
+                self.update_group_retry_policy(
+                    subscription_group_config.clone(),
+                    group_retry_policy,
+                );
+            }
             let is_notify_consumer_ids_changed_enable =
                 subscription_group_config.notify_consumer_ids_changed_enable();
             let topic_sys_flag = if consumer_data.unit_mode {
@@ -230,9 +300,73 @@ where
         let mut response_command = RemotingCommand::create_response_command();
         response_command.add_ext_field(IS_SUPPORT_HEART_BEAT_V2.to_string(), true.to_string());
         response_command.add_ext_field(IS_SUB_CHANGE.to_string(), true.to_string());
+        let message_request_modes = self.message_request_modes_of(&heartbeat_data);
+        if !message_request_modes.is_empty() {
+            let body = HeartbeatResponseBody {
+                message_request_modes,
+            };
+            response_command.set_body_mut_ref(body.encode()?);
+        }
         Ok(Some(response_command))
     }
 
+    /// Stores the retry policy a consumer reports for its group, the broker applies it when
+    /// reviving pop messages of the group.
+    fn update_group_retry_policy(
+        &self,
+        mut subscription_group_config: SubscriptionGroupConfig,
+        group_retry_policy: &GroupRetryPolicy,
+    ) {
+        if subscription_group_config.group_retry_policy() == group_retry_policy {
+            return;
+        }
+        subscription_group_config.set_group_retry_policy(group_retry_policy.clone());
+        if let Err(e) = self
+            .broker_runtime_inner
+            .subscription_group_manager()
+            .update_subscription_group_config(&mut subscription_group_config)
+        {
+            warn!(
+                "update retry policy of group {} failed: {:?}",
+                subscription_group_config.group_name(),
+                e
+            );
+        }
+    }
+
+    /// Resolves the request mode of every non-retry topic the push consumers in `heartbeat_data`
+    /// subscribe to, so clients can switch between POP and PULL without a restart.
+    fn message_request_modes_of(
+        &self,
+        heartbeat_data: &HeartbeatData,
+    ) -> Vec<SetMessageRequestModeRequestBody> {
+        let broker_config = self.broker_runtime_inner.broker_config();
+        let mut modes = Vec::new();
+        for consumer_data in heartbeat_data.consumer_data_set.iter() {
+            if consumer_data.consume_type != ConsumeType::ConsumePassively {
+                continue;
+            }
+            for subscription_data in consumer_data.subscription_data_set.iter() {
+                if subscription_data
+                    .topic
+                    .starts_with(mix_all::RETRY_GROUP_TOPIC_PREFIX)
+                {
+                    continue;
+                }
+                modes.push(
+                    self.message_request_mode_manager
+                        .resolve_message_request_mode(
+                            &subscription_data.topic,
+                            &consumer_data.group_name,
+                            broker_config.default_message_request_mode,
+                            broker_config.default_pop_share_queue_num,
+                        ),
+                );
+            }
+        }
+        modes
+    }
+
     fn heart_beat_v2(
         &self,
         _channel: &Channel,
@@ -15,7 +15,6 @@
  * limitations under the License.
  */
 use std::any::Any;
-use std::net::SocketAddr;
 use std::sync::Arc;
 
 use bytes::Bytes;
@@ -45,7 +44,6 @@ use rocketmq_store::base::get_message_result::GetMessageResult;
 use rocketmq_store::base::message_status_enum::GetMessageStatus;
 use rocketmq_store::base::message_store::MessageStore;
 use rocketmq_store::filter::MessageFilter;
-use rocketmq_store::message_store::local_file_message_store::LocalFileMessageStore;
 use rocketmq_store::stats::broker_stats_manager::BrokerStatsManager;
 use rocketmq_store::stats::stats_type::StatsType;
 use tracing::debug;
@@ -56,6 +54,7 @@ use crate::broker_runtime::BrokerRuntimeInner;
 use crate::long_polling::pull_request::PullRequest;
 use crate::mqtrace::consume_message_context::ConsumeMessageContext;
 use crate::mqtrace::consume_message_hook::ConsumeMessageHook;
+use crate::processor::processor_service::response_body_compression;
 use crate::processor::pull_message_processor::is_broadcast;
 use crate::processor::pull_message_processor::rewrite_response_for_static_topic;
 use crate::processor::pull_message_result_handler::PullMessageResultHandler;
@@ -79,7 +78,7 @@ impl<MS> DefaultPullMessageResultHandler<MS> {
     pub fn set_pull_request_hold_service(
         &mut self,
         //pull_request_hold_service: Option<ArcMut<PullRequestHoldService<DefaultMessageStore>>>,
-        _inner: ArcMut<BrokerRuntimeInner<LocalFileMessageStore>>,
+        _inner: ArcMut<BrokerRuntimeInner<MS>>,
     ) {
         //self.pull_request_hold_service = pull_request_hold_service;
     }
@@ -115,6 +114,15 @@ impl<MS: MessageStore> PullMessageResultHandler for DefaultPullMessageResultHand
             &mut response,
             client_address.as_str(),
         );
+        // The consumer has not taken the earlier responses yet, writing the messages now would only
+        // pile them up in memory. Ask it to pull the same offset again instead.
+        if ResponseCode::from(response.code()) == ResponseCode::Success && !channel.is_writable() {
+            response.set_code_ref(ResponseCode::PullRetryImmediately);
+            response
+                .read_custom_header_mut::<PullMessageResponseHeader>()
+                .unwrap()
+                .next_begin_offset = request_header.queue_offset;
+        }
         let code = From::from(response.code());
         self.execute_consume_message_hook_before(
             &request,
@@ -150,7 +158,7 @@ impl<MS: MessageStore> PullMessageResultHandler for DefaultPullMessageResultHand
             broker_allow_suspend,
             &request_header,
             get_message_result.next_begin_offset(),
-            channel.remote_address(),
+            &channel,
         );
 
         match code {
@@ -175,12 +183,23 @@ impl<MS: MessageStore> PullMessageResultHandler for DefaultPullMessageResultHand
                         request_header.topic.as_str(),
                         get_message_result.message_count(),
                     );
+                self.broker_runtime_inner
+                    .consumer_manager()
+                    .record_consumed(
+                        &request_header.consumer_group,
+                        &channel,
+                        get_message_result.message_count() as u64,
+                    );
 
-                if self
-                    .broker_runtime_inner
-                    .broker_config()
-                    .transfer_msg_by_heap
-                {
+                let broker_config = self.broker_runtime_inner.broker_config();
+                // a body to compress has to be read to the heap
+                let compress_body = response_body_compression::should_compress_body(
+                    broker_config,
+                    self.broker_runtime_inner.cpu_load_sampler(),
+                    request.accepts_compressed_body(),
+                    get_message_result.buffer_total_size().max(0) as usize,
+                );
+                if broker_config.transfer_msg_by_heap || compress_body {
                     let body = self.read_get_message_result(
                         &get_message_result,
                         request_header.consumer_group.as_str(),
@@ -190,6 +209,10 @@ impl<MS: MessageStore> PullMessageResultHandler for DefaultPullMessageResultHand
                     if let Some(body) = body {
                         response.set_body_mut_ref(body);
                     }
+                    if compress_body {
+                        response_body_compression::compress_body(broker_config, &mut response)
+                            .await;
+                    }
                     Some(response)
                 } else {
                     //zero copy transfer
@@ -328,8 +351,7 @@ impl<MS: MessageStore> DefaultPullMessageResultHandler<MS> {
         let mut bytes_mut =
             BytesMut::with_capacity(get_message_result.buffer_total_size() as usize);
         for msg in get_message_result.message_mapped_list() {
-            let data = &msg.mapped_file.as_ref().unwrap().get_mapped_file()
-                [msg.start_offset as usize..(msg.start_offset + msg.size as u64) as usize];
+            let data = msg.get_buffer();
             bytes_mut.extend_from_slice(data);
         }
         Some(bytes_mut.freeze())
@@ -539,8 +561,9 @@ impl<MS: MessageStore> DefaultPullMessageResultHandler<MS> {
         broker_allow_suspend: bool,
         request_header: &PullMessageRequestHeader,
         next_offset: i64,
-        client_address: SocketAddr,
+        channel: &Channel,
     ) {
+        let client_address = channel.remote_address();
         self.broker_runtime_inner
             .consumer_offset_manager()
             .commit_pull_offset(
@@ -555,6 +578,9 @@ impl<MS: MessageStore> DefaultPullMessageResultHandler<MS> {
         let has_commit_offset_flag =
             PullSysFlag::has_commit_offset_flag(request_header.sys_flag as u32);
         store_offset_enable = store_offset_enable && has_commit_offset_flag;
+        // The consumer only advances its local offset once the pull response arrives, so an
+        // offset piggybacked by a consumer that has already gone away is not persisted.
+        store_offset_enable = store_offset_enable && channel.is_writable();
         if store_offset_enable {
             self.broker_runtime_inner
                 .consumer_offset_manager()
@@ -630,3 +656,62 @@ impl<MS: MessageStore> DefaultPullMessageResultHandler<MS> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rocketmq_store::message_store::local_file_message_store::LocalFileMessageStore;
+
+    use super::*;
+
+    fn suggested_broker_id(broker_config: BrokerConfig, suggest_pulling_from_slave: bool) -> u64 {
+        let mut get_message_result = GetMessageResult::new();
+        get_message_result.set_status(Some(GetMessageStatus::Found));
+        get_message_result.set_suggest_pulling_from_slave(suggest_pulling_from_slave);
+        let mut subscription_group_config = SubscriptionGroupConfig::default();
+        subscription_group_config.set_which_broker_when_consume_slowly(1);
+        let mut response = RemotingCommand::create_response_command();
+        DefaultPullMessageResultHandler::<LocalFileMessageStore>::compose_response_header(
+            &Arc::new(broker_config),
+            &PullMessageRequestHeader::default(),
+            &get_message_result,
+            0,
+            &subscription_group_config,
+            &mut response,
+            "127.0.0.1:10911",
+        );
+        response
+            .read_custom_header_ref::<PullMessageResponseHeader>()
+            .unwrap()
+            .suggest_which_broker_id
+    }
+
+    #[test]
+    fn master_redirects_lagging_consumer_to_slave() {
+        let broker_config = BrokerConfig {
+            slave_read_enable: true,
+            ..BrokerConfig::default()
+        };
+        assert_eq!(suggested_broker_id(broker_config.clone(), true), 1);
+        assert_eq!(suggested_broker_id(broker_config, false), MASTER_ID);
+    }
+
+    #[test]
+    fn slave_read_disabled_keeps_consumer_on_master() {
+        let broker_config = BrokerConfig {
+            slave_read_enable: false,
+            ..BrokerConfig::default()
+        };
+        assert_eq!(suggested_broker_id(broker_config, true), MASTER_ID);
+    }
+
+    #[test]
+    fn slave_sends_caught_up_consumer_back_to_master() {
+        let mut broker_config = BrokerConfig {
+            slave_read_enable: true,
+            ..BrokerConfig::default()
+        };
+        broker_config.broker_identity.broker_id = 1;
+        assert_eq!(suggested_broker_id(broker_config.clone(), true), 1);
+        assert_eq!(suggested_broker_id(broker_config, false), MASTER_ID);
+    }
+}
@@ -78,6 +78,8 @@ use crate::long_polling::long_polling_service::pop_long_polling_service::PopLong
 use crate::long_polling::polling_header::PollingHeader;
 use crate::long_polling::polling_result::PollingResult;
 use crate::processor::processor_service::pop_buffer_merge_service::PopBufferMergeService;
+use crate::processor::processor_service::response_body_compression;
+use crate::processor::processor_service::revive_queues;
 
 const BORN_TIME: &str = "bornTime";
 
@@ -87,6 +89,8 @@ pub struct PopMessageProcessor<MS> {
     pop_buffer_merge_service: ArcMut<PopBufferMergeService<MS>>,
     queue_lock_manager: QueueLockManager,
     revive_topic: CheetahString,
+    // revive queues with a revive service, new check points go to the active ones among them
+    revived_queue_num: u32,
     broker_runtime_inner: ArcMut<BrokerRuntimeInner<MS>>,
 }
 
@@ -114,6 +118,7 @@ impl<MS: MessageStore> PopMessageProcessor<MS> {
 
             queue_lock_manager,
             revive_topic,
+            revived_queue_num: broker_runtime_inner.broker_config().revive_queue_num,
 
             broker_runtime_inner,
         }
@@ -142,6 +147,7 @@ impl<MS: MessageStore> PopMessageProcessor<MS> {
 
             queue_lock_manager,
             revive_topic,
+            revived_queue_num: broker_runtime_inner.broker_config().revive_queue_num,
 
             broker_runtime_inner,
         };
@@ -158,6 +164,18 @@ impl<MS: MessageStore> PopMessageProcessor<MS> {
         PopBufferMergeService::start(self.pop_buffer_merge_service.clone());
         self.queue_lock_manager.start();
     }
+
+    pub(crate) fn set_revived_queue_num(&mut self, revived_queue_num: u32) {
+        self.revived_queue_num = revived_queue_num;
+    }
+
+    /// Number of revive queues new check points are written to.
+    pub(crate) fn active_revive_queue_num(&self) -> u32 {
+        revive_queues::active_revive_queue_count(
+            self.broker_runtime_inner.broker_config().revive_queue_num,
+            self.revived_queue_num,
+        )
+    }
 }
 
 impl<MS> RequestProcessor for PopMessageProcessor<MS>
@@ -337,6 +355,27 @@ where
             ));
         }
 
+        let group_flow_limit = self
+            .broker_runtime_inner
+            .flow_controller()
+            .group_limit(subscription_group_config.attributes());
+        if self
+            .broker_runtime_inner
+            .flow_controller()
+            .is_group_get_throttled(&request_header.consumer_group, group_flow_limit)
+        {
+            return Ok(Some(
+                RemotingCommand::create_response_command_with_code_remark(
+                    ResponseCode::FlowControl,
+                    format!(
+                        "[GROUP_FLOW_CONTROL]the consumer group[{}] pop rate exceeds the limit, \
+                         start flow control for a while",
+                        request_header.consumer_group
+                    ),
+                ),
+            ));
+        }
+
         let exp = request_header.exp.as_ref();
 
         let (subscription_data, message_filter) = if exp.is_some() && !exp.unwrap().is_empty() {
@@ -490,9 +529,10 @@ where
         let revive_qid = if request_header.order.unwrap_or(false) {
             POP_ORDER_REVIVE_QUEUE
         } else {
-            (self.ck_message_number.fetch_add(1, Ordering::AcqRel)
-                % self.broker_runtime_inner.broker_config().revive_queue_num as i64)
-                .abs() as i32
+            revive_queues::select_revive_queue(
+                self.ck_message_number.fetch_add(1, Ordering::AcqRel),
+                self.active_revive_queue_num(),
+            )
         };
         let mut get_message_result = ArcMut::new(GetMessageResult::new_result_size(
             request_header.max_msg_nums as usize,
@@ -675,6 +715,15 @@ where
                 .await
             };
         }
+        self.broker_runtime_inner
+            .flow_controller()
+            .record_group_get(
+                &request_header.consumer_group,
+                group_flow_limit,
+                get_message_result.message_mapped_list().len() as u64,
+                get_message_result.buffer_total_size().max(0) as u64,
+            );
+        let accepts_compressed_body = request.accepts_compressed_body();
         let mut final_response = RemotingCommand::create_response_command();
         final_response.set_opaque_mut(opaque);
         if !get_message_result.message_mapped_list().is_empty() {
@@ -740,11 +789,22 @@ where
 
         match ResponseCode::from(final_response.code()) {
             ResponseCode::Success => {
-                if self
-                    .broker_runtime_inner
-                    .broker_config()
-                    .transfer_msg_by_heap
-                {
+                self.broker_runtime_inner
+                    .consumer_manager()
+                    .record_consumed(
+                        &request_header.consumer_group,
+                        &channel,
+                        get_message_result.message_count() as u64,
+                    );
+                let broker_config = self.broker_runtime_inner.broker_config();
+                // a body to compress has to be read to the heap
+                let compress_body = response_body_compression::should_compress_body(
+                    broker_config,
+                    self.broker_runtime_inner.cpu_load_sampler(),
+                    accepts_compressed_body,
+                    get_message_result.buffer_total_size().max(0) as usize,
+                );
+                if broker_config.transfer_msg_by_heap || compress_body {
                     if let Some(bytes) = self.read_get_message_result(
                         &get_message_result,
                         &request_header.consumer_group,
@@ -753,6 +813,13 @@ where
                     ) {
                         final_response.set_body_mut_ref(bytes);
                     }
+                    if compress_body {
+                        response_body_compression::compress_body(
+                            broker_config,
+                            &mut final_response,
+                        )
+                        .await;
+                    }
                     final_response.set_command_custom_header_ref(response_header);
                     Ok(Some(final_response))
                 } else {
@@ -788,7 +855,7 @@ where
         message_filter: Option<Arc<Box<dyn MessageFilter>>>,
         start_offset_info: &mut String,
         msg_offset_info: &mut String,
-        order_count_info: &mut str,
+        order_count_info: &mut String,
         random_q: i32,
         mut rest_num: i64,
     ) -> i64 {
@@ -797,7 +864,7 @@ where
             rest_num = self
                 .pop_msg_from_queue(
                     &topic_config.topic_name.clone().unwrap_or_default(),
-                    &request_header.consumer_group,
+                    &request_header.attempt_id.clone().unwrap_or_default(),
                     is_retry,
                     get_message_result.clone(),
                     request_header,
@@ -828,7 +895,7 @@ where
         message_filter: Option<Arc<Box<dyn MessageFilter>>>,
         start_offset_info: &mut String,
         msg_offset_info: &mut String,
-        order_count_info: &mut str,
+        order_count_info: &mut String,
         random_q: i32,
         rest_num: i64,
     ) -> i64 {
@@ -872,7 +939,7 @@ where
         message_filter: Option<Arc<Box<dyn MessageFilter>>>,
         start_offset_info: &mut String,
         msg_offset_info: &mut String,
-        order_count_info: &mut str,
+        order_count_info: &mut String,
     ) -> i64 {
         let lock_key = CheetahString::from_string(format!(
             "{}{}{}{}{}",
@@ -1432,6 +1499,37 @@ where
             .notify_message_arriving(topic, queue_id, cid, None, 0, None, None);
     }
 
+    /// Wakes up the pop requests held for `group` when the queue has messages the group has not
+    /// consumed yet.
+    pub async fn notify_long_polling_request_if_need(
+        &self,
+        topic: &CheetahString,
+        group: &CheetahString,
+        queue_id: i32,
+    ) {
+        let pop_buffer_offset = self
+            .pop_buffer_merge_service
+            .get_latest_offset_full(topic, group, queue_id)
+            .await;
+        let consumer_offset = self
+            .broker_runtime_inner
+            .consumer_offset_manager()
+            .query_offset(group, topic, queue_id);
+        let max_offset = self
+            .broker_runtime_inner
+            .message_store_unchecked()
+            .get_max_offset_in_queue(topic, queue_id);
+        if max_offset > pop_buffer_offset.max(consumer_offset) {
+            let notify_success = self
+                .pop_long_polling_service
+                .notify_message_arriving(topic, -1, group, None, 0, None, None);
+            if !notify_success {
+                self.pop_long_polling_service
+                    .notify_message_arriving(topic, queue_id, group, None, 0, None, None);
+            }
+        }
+    }
+
     pub fn notify_message_arriving_full(
         &self,
         topic: CheetahString,
@@ -1462,8 +1560,7 @@ where
         let mut bytes_mut =
             BytesMut::with_capacity(get_message_result.buffer_total_size() as usize);
         for msg in get_message_result.message_mapped_list() {
-            let data = &msg.mapped_file.as_ref().unwrap().get_mapped_file()
-                [msg.start_offset as usize..(msg.start_offset + msg.size as u64) as usize];
+            let data = msg.get_buffer();
             bytes_mut.extend_from_slice(data);
         }
         Some(bytes_mut.freeze())
@@ -15,4 +15,6 @@
  * limitations under the License.
  */
 pub(crate) mod pop_buffer_merge_service;
-pub(super) mod pop_revive_service;
+pub(super) mod pop_revive_service;
+pub(crate) mod response_body_compression;
+pub(crate) mod revive_queues;
@@ -590,6 +590,33 @@ impl<MS: MessageStore> PopBufferMergeService<MS> {
         count
     }
 
+    /// Counts the messages of `topic`, `group` and `queue_id` held in buffered check points which
+    /// are not acked yet.
+    pub fn get_buffered_message_num(
+        &self,
+        topic: &CheetahString,
+        group: &CheetahString,
+        queue_id: i32,
+    ) -> i64 {
+        self.buffer
+            .iter()
+            .filter(|entry| {
+                let ck = entry.value().get_ck();
+                !entry.value().is_just_offset()
+                    && ck.queue_id == queue_id
+                    && &ck.topic == topic
+                    && &ck.cid == group
+            })
+            .map(|entry| {
+                let point_wrapper = entry.value();
+                let bits = point_wrapper.get_bits().load(Ordering::Acquire);
+                (0..point_wrapper.ck.num)
+                    .filter(|i| !DataConverter::get_bit(bits, *i as usize))
+                    .count() as i64
+            })
+            .sum()
+    }
+
     pub fn start(this: ArcMut<Self>) {
         tokio::spawn(async move {
             let interval = this.interval * 200 * 5;
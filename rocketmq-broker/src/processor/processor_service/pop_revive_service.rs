@@ -144,6 +144,16 @@ impl<MS: MessageStore> PopReviveService<MS> {
                 CheetahString::from(pop_check_point.pop_time.to_string()),
             );
         }
+        if self
+            .broker_runtime_inner
+            .broker_config()
+            .enable_pop_retry_policy
+        {
+            let delay_level = self.retry_delay_level(&pop_check_point.cid, message_ext);
+            if delay_level > 0 {
+                msg_inner.set_delay_time_level(delay_level);
+            }
+        }
         msg_inner.properties_string =
             message_decoder::message_properties_to_string(msg_inner.get_properties());
         self.add_retry_topic_if_not_exist(msg_inner.get_topic(), &pop_check_point.cid);
@@ -156,11 +166,33 @@ impl<MS: MessageStore> PopReviveService<MS> {
             || put_message_result.append_message_result().unwrap().status
                 != AppendMessageStatus::PutOk
         {
+            self.broker_runtime_inner
+                .broker_stats_manager()
+                .inc_pop_revive_failed_nums();
             return false;
         }
         true
     }
 
+    /// Delay level that holds a revived message back for the next delay of the group's retry
+    /// policy, chosen by how often the message has been consumed already.
+    fn retry_delay_level(&self, consumer_group: &CheetahString, message_ext: &MessageExt) -> i32 {
+        let Some(subscription_group_config) = self
+            .broker_runtime_inner
+            .subscription_group_manager()
+            .find_subscription_group_config(consumer_group)
+        else {
+            return 0;
+        };
+        let delay_millis = subscription_group_config
+            .group_retry_policy()
+            .get_retry_policy()
+            .next_delay_duration(message_ext.reconsume_times());
+        self.broker_runtime_inner
+            .schedule_message_service()
+            .delay_level_of(delay_millis)
+    }
+
     pub fn add_retry_topic_if_not_exist(
         &mut self,
         topic: &CheetahString,
@@ -179,9 +211,14 @@ impl<MS: MessageStore> PopReviveService<MS> {
         topic_config.topic_filter_type = TopicFilterType::SingleTag;
         topic_config.perm = 6;
         topic_config.topic_sys_flag = 0;
-        self.broker_runtime_inner
+        if let Err(e) = self
+            .broker_runtime_inner
             .topic_config_manager_mut()
-            .update_topic_config(&mut topic_config);
+            .update_topic_config(&mut topic_config)
+        {
+            error!("create pop retry topic {} failed: {}", topic, e);
+            return;
+        }
         self.init_pop_retry_offset(topic, consumer_group);
     }
 
@@ -358,6 +395,9 @@ impl<MS: MessageStore> PopReviveService<MS> {
                 if let Err(e) = Self::merge_and_revive(this.clone(), &mut consume_revive_obj).await
                 {
                     error!("reviveQueueId={}, revive error:{}", this.queue_id, e);
+                    this.broker_runtime_inner
+                        .broker_stats_manager()
+                        .inc_pop_revive_failed_nums();
                     continue;
                 }
                 let mut delay = 0;
@@ -438,22 +478,14 @@ impl<MS: MessageStore> PopReviveService<MS> {
             let message_exts = self.get_revive_message(offset, self.queue_id).await;
             if message_exts.is_none() || message_exts.as_ref().unwrap().is_empty() {
                 let old = end_time;
-                let timer_delay = self
+                // without a timer store nothing is waiting to be enqueued or dequeued
+                let timer_message_store = self
                     .broker_runtime_inner
-                    .message_store()
-                    .as_ref()
-                    .unwrap()
-                    .get_timer_message_store()
-                    .unwrap() // may be need to unwrap
-                    .get_dequeue_behind();
-                let commit_log_delay = self
-                    .broker_runtime_inner
-                    .message_store()
-                    .as_ref()
-                    .unwrap()
-                    .get_timer_message_store()
-                    .unwrap() // may be need to unwrap
-                    .get_enqueue_behind();
+                    .message_store_unchecked()
+                    .get_timer_message_store();
+                let timer_delay = timer_message_store.map_or(0, |store| store.get_dequeue_behind());
+                let commit_log_delay =
+                    timer_message_store.map_or(0, |store| store.get_enqueue_behind());
                 if end_time != 0
                     && get_current_millis() - end_time > (3 * PopAckConstants::SECOND) as u64
                     && timer_delay <= 0
@@ -1005,8 +1037,7 @@ fn decode_msg_list(
 ) -> Vec<ArcMut<MessageExt>> {
     let mut found_list = Vec::new();
     for bb in get_message_result.message_mapped_list() {
-        let data = &bb.mapped_file.as_ref().unwrap().get_mapped_file()
-            [bb.start_offset as usize..(bb.start_offset + bb.size as u64) as usize];
+        let data = bb.get_buffer();
         let mut bytes = Bytes::copy_from_slice(data);
         let msg_ext =
             message_decoder::decode(&mut bytes, true, de_compress_body, false, false, false);
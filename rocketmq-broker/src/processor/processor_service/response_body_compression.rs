@@ -0,0 +1,140 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Compression of the message bodies of pull and pop responses.
+//!
+//! A consumer flags its requests when it inflates zstd compressed bodies, the broker compresses
+//! the body of the response when it is enabled, the body is large enough to be worth it and the
+//! CPU is not busy. The codec of the consumer inflates the body before the response is handled.
+
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+use rocketmq_common::common::broker::broker_config::BrokerConfig;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use sysinfo::System;
+use tracing::warn;
+
+/// The load average of the last minute per CPU. Reading it costs a file read, so it is sampled
+/// on a timer instead of for every response.
+#[derive(Default)]
+pub(crate) struct CpuLoadSampler {
+    load_bits: AtomicU64,
+}
+
+impl CpuLoadSampler {
+    pub fn sample(&self) {
+        let load = System::load_average().one / num_cpus::get().max(1) as f64;
+        self.load_bits.store(load.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Returns the load of the last sample, 0 before the first one.
+    pub fn cpu_load(&self) -> f64 {
+        f64::from_bits(self.load_bits.load(Ordering::Relaxed))
+    }
+}
+
+/// Returns whether a response body of `body_size` bytes is to be compressed, for a request
+/// whose sender does or does not accept compressed bodies.
+pub(crate) fn should_compress_body(
+    broker_config: &BrokerConfig,
+    cpu_load_sampler: &CpuLoadSampler,
+    accepts_compressed_body: bool,
+    body_size: usize,
+) -> bool {
+    if !broker_config.compress_pull_response_body || !accepts_compressed_body {
+        return false;
+    }
+    within_thresholds(broker_config, body_size, cpu_load_sampler.cpu_load())
+}
+
+/// Compresses the body of `response` on a blocking thread, large bodies take long enough to
+/// stall the other requests of an async worker. Returns whether it was compressed.
+pub(crate) async fn compress_body(
+    broker_config: &BrokerConfig,
+    response: &mut RemotingCommand,
+) -> bool {
+    let Some(body) = response.body().clone() else {
+        return false;
+    };
+    let level = broker_config.compress_pull_response_level;
+    match tokio::task::spawn_blocking(move || RemotingCommand::zstd_compress_body(&body, level))
+        .await
+    {
+        Ok(Some(compressed)) => {
+            response.set_zstd_compressed_body(compressed);
+            true
+        }
+        Ok(None) => false,
+        Err(e) => {
+            warn!("compress response body failed: {}", e);
+            false
+        }
+    }
+}
+
+fn within_thresholds(broker_config: &BrokerConfig, body_size: usize, cpu_load: f64) -> bool {
+    body_size >= broker_config.compress_pull_response_threshold
+        && cpu_load <= broker_config.compress_pull_response_max_cpu_load
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_large_bodies_are_compressed_while_the_cpu_is_not_busy() {
+        let broker_config = BrokerConfig {
+            compress_pull_response_body: true,
+            compress_pull_response_threshold: 1024,
+            compress_pull_response_max_cpu_load: 0.8,
+            ..BrokerConfig::default()
+        };
+        assert!(within_thresholds(&broker_config, 4096, 0.5));
+        assert!(!within_thresholds(&broker_config, 512, 0.5));
+        assert!(!within_thresholds(&broker_config, 4096, 0.9));
+
+        let cpu_load_sampler = CpuLoadSampler::default();
+        assert!(should_compress_body(
+            &broker_config,
+            &cpu_load_sampler,
+            true,
+            4096
+        ));
+        assert!(!should_compress_body(
+            &broker_config,
+            &cpu_load_sampler,
+            false,
+            4096
+        ));
+        cpu_load_sampler.sample();
+        assert!(cpu_load_sampler.cpu_load() >= 0.0);
+    }
+
+    #[tokio::test]
+    async fn compressed_body_is_flagged() {
+        let broker_config = BrokerConfig::default();
+        let mut response =
+            RemotingCommand::create_response_command().set_body(vec![b'a'; 64 * 1024]);
+        assert!(compress_body(&broker_config, &mut response).await);
+        assert!(response.is_body_zstd_compressed());
+        assert!(response.body().as_ref().unwrap().len() < 64 * 1024);
+
+        let mut response = RemotingCommand::create_response_command();
+        assert!(!compress_body(&broker_config, &mut response).await);
+    }
+}
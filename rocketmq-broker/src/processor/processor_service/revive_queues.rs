@@ -0,0 +1,88 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Layout of the queues of the cluster revive topic.
+//!
+//! The check point of every pop is written to one revive queue of the broker, and its id is
+//! recorded as `rq_id` in the extra info of the popped messages. Acks and invisible time changes
+//! of those messages are written to the same queue, where the revive service of the queue
+//! matches them against the check point. Revive queues are local to a broker: each master
+//! revives the check points of its own pops, nothing is handed over between brokers.
+//!
+//! `reviveQueueNum` may be changed between restarts. The revive topic never shrinks, so check
+//! points written to queues beyond a lowered number are still revived; only new check points
+//! go to the first `reviveQueueNum` queues. A raised number grows the revive topic on start.
+
+use rocketmq_common::common::key_builder::POP_ORDER_REVIVE_QUEUE;
+
+/// Number of revive queues to revive: `configured`, or more when the revive topic kept the
+/// queues of an earlier, larger configuration.
+pub(crate) fn revive_queue_count(configured: u32, revive_topic_queues: Option<u32>) -> u32 {
+    configured.max(revive_topic_queues.unwrap_or(0)).max(1)
+}
+
+/// Number of revive queues new check points are written to: `configured` capped at the
+/// `revived` queues, a number raised at runtime only takes effect on restart.
+pub(crate) fn active_revive_queue_count(configured: u32, revived: u32) -> u32 {
+    configured.min(revived).max(1)
+}
+
+/// Picks the revive queue of the `ck_number`-th check point, round robin over the `active`
+/// queues so that revive load is spread evenly.
+pub(crate) fn select_revive_queue(ck_number: i64, active: u32) -> i32 {
+    ck_number.rem_euclid(active.max(1) as i64) as i32
+}
+
+/// Picks the revive queue of a check point replacing one in `revive_qid`. It stays in the same
+/// queue unless that queue is no longer active, then it is hashed onto an active one so that
+/// inactive queues drain.
+pub(crate) fn reassign_revive_queue(revive_qid: i32, active: u32) -> i32 {
+    if revive_qid == POP_ORDER_REVIVE_QUEUE || (0..active as i32).contains(&revive_qid) {
+        return revive_qid;
+    }
+    select_revive_queue(revive_qid as i64, active)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn revive_topic_never_shrinks() {
+        assert_eq!(revive_queue_count(8, None), 8);
+        assert_eq!(revive_queue_count(4, Some(8)), 8);
+        assert_eq!(revive_queue_count(16, Some(8)), 16);
+        assert_eq!(active_revive_queue_count(4, 8), 4);
+        assert_eq!(active_revive_queue_count(16, 8), 8);
+    }
+
+    #[test]
+    fn check_points_only_go_to_active_queues() {
+        let selected = (0..8)
+            .map(|n| select_revive_queue(n, 4))
+            .collect::<Vec<_>>();
+        assert_eq!(selected, vec![0, 1, 2, 3, 0, 1, 2, 3]);
+        assert_eq!(select_revive_queue(-1, 4), 3);
+
+        assert_eq!(reassign_revive_queue(2, 4), 2);
+        assert_eq!(reassign_revive_queue(6, 4), 2);
+        assert_eq!(
+            reassign_revive_queue(POP_ORDER_REVIVE_QUEUE, 4),
+            POP_ORDER_REVIVE_QUEUE
+        );
+    }
+}
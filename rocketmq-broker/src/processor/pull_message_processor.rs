@@ -608,7 +608,7 @@ where
                 );
                 return Some(
                     response
-                        .set_code(ResponseCode::SubscriptionNotExist)
+                        .set_code(ResponseCode::SubscriptionNotLatest)
                         .set_remark("the consumer's subscription not latest"),
                 );
             }
@@ -674,13 +674,43 @@ where
             );
         }
 
+        let group_flow_limit = self
+            .broker_runtime_inner
+            .flow_controller()
+            .group_limit(subscription_group_config.as_ref().unwrap().attributes());
+        if self
+            .broker_runtime_inner
+            .flow_controller()
+            .is_group_get_throttled(&request_header.consumer_group, group_flow_limit)
+        {
+            return Some(
+                response
+                    .set_code(ResponseCode::FlowControl)
+                    .set_remark(format!(
+                        "[GROUP_FLOW_CONTROL]the consumer group[{}] pull rate exceeds the limit, \
+                         start flow control for a while",
+                        request_header.consumer_group
+                    )),
+            );
+        }
+
         //need optimize
         let message_filter: Arc<Box<dyn MessageFilter>> = if self
             .broker_runtime_inner
             .broker_config()
             .filter_support_retry
         {
-            Arc::new(Box::new(ExpressionForRetryMessageFilter))
+            let group_subscriptions = self
+                .broker_runtime_inner
+                .consumer_manager()
+                .get_consumer_group_info(request_header.consumer_group.as_ref())
+                .map(|consumer_group_info| consumer_group_info.get_subscription_table());
+            Arc::new(Box::new(ExpressionForRetryMessageFilter::new(
+                Some(subscription_data.clone()),
+                consumer_filter_data,
+                Arc::new(self.broker_runtime_inner.consumer_filter_manager().clone()),
+                group_subscriptions,
+            )))
         } else {
             Arc::new(Box::new(ExpressionMessageFilter::new(
                 Some(subscription_data.clone()),
@@ -744,21 +774,34 @@ where
                 get_message_result.set_next_begin_offset(broadcast_init_offset);
                 Some(get_message_result)
             } else {
-                let result = self
-                    .broker_runtime_inner
-                    .message_store()
-                    .as_ref()
-                    .unwrap()
-                    .get_message(
-                        group,
-                        topic,
-                        queue_id,
-                        request_header.queue_offset,
-                        request_header.max_msg_nums,
-                        //   MAX_PULL_MSG_SIZE,
-                        Some(message_filter.clone()),
-                    )
-                    .await;
+                let message_store = self.broker_runtime_inner.message_store().as_ref().unwrap();
+                let result = match request_header.max_msg_bytes.filter(|bytes| *bytes > 0) {
+                    Some(max_msg_bytes) => {
+                        message_store
+                            .get_message_with_size_limit(
+                                group,
+                                topic,
+                                queue_id,
+                                request_header.queue_offset,
+                                request_header.max_msg_nums,
+                                max_msg_bytes,
+                                Some(message_filter.clone()),
+                            )
+                            .await
+                    }
+                    None => {
+                        message_store
+                            .get_message(
+                                group,
+                                topic,
+                                queue_id,
+                                request_header.queue_offset,
+                                request_header.max_msg_nums,
+                                Some(message_filter.clone()),
+                            )
+                            .await
+                    }
+                };
                 if result.is_none() {
                     return Some(
                         response
@@ -770,6 +813,14 @@ where
             }
         };
         if let Some(get_message_result) = get_message_result {
+            self.broker_runtime_inner
+                .flow_controller()
+                .record_group_get(
+                    &request_header.consumer_group,
+                    group_flow_limit,
+                    get_message_result.message_count().max(0) as u64,
+                    get_message_result.buffer_total_size().max(0) as u64,
+                );
             return self
                 .pull_message_result_handler
                 .handle(
@@ -68,7 +68,10 @@ pub struct QueryAssignmentProcessor<MS> {
 }
 
 impl<MS: MessageStore> QueryAssignmentProcessor<MS> {
-    pub fn new(broker_runtime_inner: ArcMut<BrokerRuntimeInner<MS>>) -> Self {
+    pub fn new(
+        broker_runtime_inner: ArcMut<BrokerRuntimeInner<MS>>,
+        message_request_mode_manager: MessageRequestModeManager,
+    ) -> Self {
         let allocate_message_queue_averagely: Arc<dyn AllocateMessageQueueStrategy> =
             Arc::new(AllocateMessageQueueAveragely);
         let allocate_message_queue_averagely_by_circle: Arc<dyn AllocateMessageQueueStrategy> =
@@ -82,12 +85,8 @@ impl<MS: MessageStore> QueryAssignmentProcessor<MS> {
             CheetahString::from_static_str(allocate_message_queue_averagely_by_circle.get_name()),
             allocate_message_queue_averagely_by_circle,
         );
-        let manager = MessageRequestModeManager::new(Arc::new(
-            broker_runtime_inner.message_store_config().clone(),
-        ));
-        let _ = manager.load();
         Self {
-            message_request_mode_manager: manager,
+            message_request_mode_manager,
             load_strategy,
             broker_runtime_inner,
         }
@@ -129,37 +128,27 @@ impl<MS: MessageStore> QueryAssignmentProcessor<MS> {
 
         let set_message_request_mode_request_body = self
             .message_request_mode_manager
-            .get_message_request_mode(&request_body.topic, &request_body.consumer_group);
-
-        let set_message_request_mode_request_body =
-            if let Some(set_message_request_mode_request_body) =
-                set_message_request_mode_request_body
-            {
-                set_message_request_mode_request_body
-            } else {
-                let mut body = SetMessageRequestModeRequestBody {
-                    topic: request_body.topic.clone(),
-                    consumer_group: request_body.consumer_group.clone(),
-                    ..Default::default()
-                };
-                if request_body.topic.starts_with(RETRY_GROUP_TOPIC_PREFIX) {
-                    // retry topic must be pull mode
-                    body.mode = MessageRequestMode::Pull;
-                } else {
-                    body.mode = self
-                        .broker_runtime_inner
-                        .broker_config()
-                        .default_message_request_mode;
-                }
-                if body.mode == MessageRequestMode::Pop {
-                    body.pop_share_queue_num = self
-                        .broker_runtime_inner
-                        .broker_config()
-                        .default_pop_share_queue_num;
-                }
-                body
-            };
+            .resolve_message_request_mode(
+                &request_body.topic,
+                &request_body.consumer_group,
+                self.broker_runtime_inner
+                    .broker_config()
+                    .default_message_request_mode,
+                self.broker_runtime_inner
+                    .broker_config()
+                    .default_pop_share_queue_num,
+            );
         let mode = set_message_request_mode_request_body.mode;
+        let attachments = (mode == MessageRequestMode::Pop).then(|| {
+            HashMap::from([(
+                CheetahString::from_static_str(MessageQueueAssignment::POP_SHARE_QUEUE_NUM),
+                CheetahString::from_string(
+                    set_message_request_mode_request_body
+                        .pop_share_queue_num
+                        .to_string(),
+                ),
+            )])
+        });
 
         //do load balance, get message queues
         let message_queues = self
@@ -179,7 +168,7 @@ impl<MS: MessageStore> QueryAssignmentProcessor<MS> {
                 .map(|mq| MessageQueueAssignment {
                     message_queue: Some(mq),
                     mode,
-                    attachments: None,
+                    attachments: attachments.clone(),
                 })
                 .collect()
         } else {
@@ -22,6 +22,9 @@ use std::time::Instant;
 use cheetah_string::CheetahString;
 use rand::Rng;
 use rocketmq_common::common::attribute::cleanup_policy::CleanupPolicy;
+use rocketmq_common::common::attribute::topic_message_type::TopicMessageType;
+use rocketmq_common::common::broker::broker_config::BrokerConfig;
+use rocketmq_common::common::config::TopicConfig;
 use rocketmq_common::common::constant::PermName;
 use rocketmq_common::common::key_builder::KeyBuilder;
 use rocketmq_common::common::message::message_batch::MessageExtBatch;
@@ -36,6 +39,7 @@ use rocketmq_common::common::mix_all::RETRY_GROUP_TOPIC_PREFIX;
 use rocketmq_common::common::mq_version::RocketMqVersion;
 use rocketmq_common::common::sys_flag::message_sys_flag::MessageSysFlag;
 use rocketmq_common::common::topic::TopicValidator;
+use rocketmq_common::common::topic::TOPIC_MAX_LENGTH;
 use rocketmq_common::common::FAQUrl;
 use rocketmq_common::common::TopicFilterType;
 use rocketmq_common::common::TopicSysFlag;
@@ -76,6 +80,9 @@ use tracing::warn;
 
 use crate::broker_runtime::BrokerRuntimeInner;
 use crate::client::net::broker_to_client::Broker2Client;
+use crate::dedup::send_dedup_table::DedupReservation;
+use crate::dedup::send_dedup_table::Reservation;
+use crate::dedup::send_dedup_table::StoredMessage;
 use crate::mqtrace::consume_message_context::ConsumeMessageContext;
 use crate::mqtrace::consume_message_hook::ConsumeMessageHook;
 use crate::mqtrace::send_message_context::SendMessageContext;
@@ -252,6 +259,32 @@ where
                     )),
             ));
         }
+        let mut response = response;
+        if !self.inner.message_type_check(
+            &topic_config,
+            &string_to_message_properties(request_header.properties.as_ref()),
+            &mut response,
+        ) {
+            return Ok(Some(response));
+        }
+        // the type of a batch is decided by the messages it wraps, not by the batch header
+        let type_check_enabled = self
+            .inner
+            .broker_runtime_inner
+            .broker_config()
+            .enable_topic_message_type_check;
+        if let Some(mut body) = request.body().clone().filter(|_| type_check_enabled) {
+            for message in MessageDecoder::decode_messages(&mut body) {
+                if !self.inner.message_type_check(
+                    &topic_config,
+                    message.get_properties(),
+                    &mut response,
+                ) {
+                    return Ok(Some(response));
+                }
+            }
+        }
+        let dedup_enable = topic_config.is_dedup_enable();
         let mut message_ext = MessageExtBrokerInner::default();
         message_ext.message_ext_inner.message.topic = request_header.topic().clone();
         message_ext.message_ext_inner.queue_id = queue_id;
@@ -351,6 +384,21 @@ where
             .message_ext_inner
             .topic()
             .clone();
+        // a retried batch carries the unique key of the batch sent first
+        let dedup_key = transaction_id.clone().filter(|_| dedup_enable);
+        let dedup_reservation = match self.reserve_dedup_key(&topic, queue_id, dedup_key) {
+            Ok(dedup_reservation) => dedup_reservation,
+            Err(reservation) => {
+                return Ok(answer_duplicate_send(
+                    reservation,
+                    response,
+                    &topic,
+                    queue_id,
+                    transaction_id,
+                    &mapping_context,
+                ))
+            }
+        };
         if self
             .inner
             .broker_runtime_inner
@@ -377,6 +425,7 @@ where
             })
             .await
             .map_err(|e| TokioHandlerError(e.to_string()))?;
+            complete_dedup_reservation(dedup_reservation, &put_message_result);
             Ok(self
                 .handle_put_message_result(
                     put_message_result,
@@ -412,6 +461,7 @@ where
                     .put_messages(batch_message)
                     .await
             };
+            complete_dedup_reservation(dedup_reservation, &put_message_result);
             Ok(self
                 .handle_put_message_result(
                     put_message_result,
@@ -469,6 +519,13 @@ where
         message_ext.message_ext_inner.queue_id = queue_id;
         let mut ori_props =
             MessageDecoder::string_to_message_properties(request_header.properties.as_ref());
+        if !request_header.topic.starts_with(RETRY_GROUP_TOPIC_PREFIX)
+            && !self
+                .inner
+                .message_type_check(&topic_config, &ori_props, &mut response)
+        {
+            return Ok(Some(response));
+        }
         if !self.handle_retry_and_dlq(
             &request_header,
             &mut response,
@@ -487,7 +544,7 @@ where
         message_ext.message_ext_inner.message.flag = request_header.flag;
 
         let uniq_key = ori_props.get(MessageConst::PROPERTY_UNIQ_CLIENT_MESSAGE_ID_KEYIDX);
-        if !uniq_key.is_some_and(|uniq_key_inner| uniq_key_inner.is_empty()) {
+        if !uniq_key.is_some_and(|uniq_key_inner| !uniq_key_inner.is_empty()) {
             ori_props.insert(
                 CheetahString::from_static_str(
                     MessageConst::PROPERTY_UNIQ_CLIENT_MESSAGE_ID_KEYIDX,
@@ -572,6 +629,24 @@ where
         let topic = message_ext.topic().clone();
         let transaction_id =
             MessageClientIDSetter::get_uniq_id(&message_ext.message_ext_inner.message);
+        let dedup_key = if topic_config.is_dedup_enable() && !send_transaction_prepare_message {
+            transaction_id.clone()
+        } else {
+            None
+        };
+        let dedup_reservation = match self.reserve_dedup_key(&topic, queue_id, dedup_key) {
+            Ok(dedup_reservation) => dedup_reservation,
+            Err(reservation) => {
+                return Ok(answer_duplicate_send(
+                    reservation,
+                    response,
+                    &topic,
+                    queue_id,
+                    transaction_id,
+                    &mapping_context,
+                ))
+            }
+        };
         if self
             .inner
             .broker_runtime_inner
@@ -600,6 +675,7 @@ where
             let put_message_result = put_message_handle
                 .await
                 .map_err(|e| TokioHandlerError(e.to_string()))?;
+            complete_dedup_reservation(dedup_reservation, &put_message_result);
             Ok(self
                 .handle_put_message_result(
                     put_message_result,
@@ -632,6 +708,7 @@ where
                     .put_message(message_ext)
                     .await
             };
+            complete_dedup_reservation(dedup_reservation, &put_message_result);
 
             Ok(self
                 .handle_put_message_result(
@@ -653,6 +730,28 @@ where
         }
     }
 
+    /// Reserves the unique key of a message sent to a topic with dedup enabled, before the
+    /// message is stored. Fails with what a retried send of a stored or storing message finds.
+    fn reserve_dedup_key(
+        &self,
+        topic: &CheetahString,
+        queue_id: i32,
+        dedup_key: Option<CheetahString>,
+    ) -> Result<Option<DedupReservation>, Reservation> {
+        let Some(uniq_key) = dedup_key else {
+            return Ok(None);
+        };
+        match self
+            .inner
+            .broker_runtime_inner
+            .send_dedup_table()
+            .reserve(topic, queue_id, uniq_key)
+        {
+            Reservation::Reserved(dedup_reservation) => Ok(Some(dedup_reservation)),
+            reservation => Err(reservation),
+        }
+    }
+
     async fn handle_put_message_result(
         &self,
         put_message_result: PutMessageResult,
@@ -694,7 +793,7 @@ where
                         },
                         rocketmq_store::base::message_status_enum::PutMessageStatus::MessageIllegal |
                         rocketmq_store::base::message_status_enum::PutMessageStatus::PropertiesSizeExceeded => {
-                           response.set_code_mut(ResponseCode::MessageIllegal).set_remark_mut("the message is illegal, maybe msg body or properties length not matched. msg body length limit B, msg properties length limit 32KB.");
+                           response.set_code_mut(ResponseCode::MessageIllegal).set_remark_mut(format!("the message is illegal, maybe msg body or properties length not matched. msg body length limit {}B, msg properties length limit 32KB.", self.inner.broker_runtime_inner.message_store_config().max_message_size));
                         },
                         rocketmq_store::base::message_status_enum::PutMessageStatus::OsPageCacheBusy =>{
                             response.set_code_mut(RemotingSysResponseCode::SystemError).set_remark_mut("[PC_SYNCHRONIZED]broker busy, start flow control for a while");
@@ -792,6 +891,14 @@ where
                     topic,
                     put_message_result.append_message_result().unwrap().msg_num,
                 );
+            self.inner
+                .broker_runtime_inner
+                .producer_manager()
+                .record_produced(
+                    &send_message_context.producer_group,
+                    ctx.channel(),
+                    put_message_result.append_message_result().unwrap().msg_num as u64,
+                );
             self.inner
                 .broker_runtime_inner
                 .broker_stats_manager()
@@ -932,9 +1039,29 @@ where
                 ));
             return response;
         }
+        if self
+            .inner
+            .broker_runtime_inner
+            .broker_config()
+            .broker_fast_failure_enable
+        {
+            let broker_runtime_inner = &self.inner.broker_runtime_inner;
+            if let Some(remark) = broker_runtime_inner
+                .broker_fast_failure()
+                .reject_send_request(broker_runtime_inner.message_store_unchecked().as_ref())
+            {
+                return response
+                    .set_code(ResponseCode::SystemBusy)
+                    .set_remark(remark);
+            }
+        }
         response = response.set_code(-1);
         self.inner
             .msg_check(channel, ctx, request, request_header, &mut response);
+        if response.code() == -1 {
+            self.inner
+                .flow_control_check(request, request_header, &mut response);
+        }
         response
     }
 
@@ -969,7 +1096,7 @@ where
             let subscription_group_config = subscription_group_config.unwrap();
 
             let mut max_reconsume_times = subscription_group_config.retry_max_times();
-            if request.version() >= From::from(RocketMqVersion::V349)
+            if request.is_version_at_least(RocketMqVersion::V349)
                 && request_header.max_reconsume_times.is_some()
             {
                 max_reconsume_times = request_header.max_reconsume_times.unwrap();
@@ -1226,7 +1353,7 @@ where
         msg_ext.set_wait_store_msg_ok(false);
         let mut delay_level = request_header.delay_level;
         let mut max_reconsume_times = subscription_group_config.retry_max_times();
-        if request.version() >= RocketMqVersion::V349 as i32 {
+        if request.is_version_at_least(RocketMqVersion::V349) {
             if let Some(num) = request_header.max_reconsume_times {
                 max_reconsume_times = num;
             }
@@ -1452,7 +1579,7 @@ where
         &mut self,
         channel: &Channel,
         _ctx: &ConnectionHandlerContext,
-        _request: &RemotingCommand,
+        request: &RemotingCommand,
         request_header: &SendMessageRequestHeader,
         response: &mut RemotingCommand,
     ) {
@@ -1474,6 +1601,20 @@ where
             return;
         }
 
+        //check message size, properties and topic length
+        if let Some(remark) = check_message_content(
+            request_header.topic.as_str(),
+            request_header.properties.as_ref().map(|p| p.as_str()),
+            request.body().as_ref().map_or(0, |body| body.len()),
+            self.broker_runtime_inner
+                .message_store_config()
+                .max_message_size as usize,
+        ) {
+            response.with_code(ResponseCode::MessageIllegal);
+            response.with_remark(remark);
+            return;
+        }
+
         //check Topic
         let result = TopicValidator::validate_topic(request_header.topic.as_str());
         if !result.valid() {
@@ -1490,6 +1631,18 @@ where
             ));
             return;
         }
+
+        if is_trace_topic_forbidden(
+            self.broker_runtime_inner.broker_config(),
+            request_header.topic.as_str(),
+        ) {
+            response.with_code(ResponseCode::NoPermission);
+            response.with_remark(format!(
+                "Sending message to trace topic[{}] is forbidden, traceTopicEnable is false.",
+                request_header.topic.as_str()
+            ));
+            return;
+        }
         let mut topic_config = self
             .broker_runtime_inner
             .topic_config_manager()
@@ -1543,8 +1696,17 @@ where
             }
         }
 
-        let queue_id_int = request_header.queue_id;
         let topic_config_inner = topic_config.as_ref().unwrap();
+        if !PermName::is_writeable(topic_config_inner.perm) {
+            response.with_code(ResponseCode::NoPermission);
+            response.with_remark(format!(
+                "the topic[{}] sending message is forbidden",
+                request_header.topic.as_str()
+            ));
+            return;
+        }
+
+        let queue_id_int = request_header.queue_id;
         let id_valid = topic_config_inner
             .write_queue_nums
             .max(topic_config_inner.read_queue_nums);
@@ -1559,11 +1721,211 @@ where
         }
     }
 
+    pub(crate) fn flow_control_check(
+        &self,
+        request: &RemotingCommand,
+        request_header: &SendMessageRequestHeader,
+        response: &mut RemotingCommand,
+    ) {
+        let flow_controller = self.broker_runtime_inner.flow_controller();
+        if !flow_controller.is_enable() {
+            return;
+        }
+        let body = request.body();
+        let msgs = if request_header.batch.unwrap_or(false) {
+            MessageDecoder::count_inner_msg_num(body.clone()).max(1) as u64
+        } else {
+            1
+        };
+        let bytes = body.as_ref().map_or(0, |body| body.len() as u64);
+        let limit = self
+            .broker_runtime_inner
+            .topic_config_manager()
+            .select_topic_config(&request_header.topic)
+            .map_or_else(
+                || flow_controller.topic_limit(&HashMap::new()),
+                |topic_config| flow_controller.topic_limit(&topic_config.attributes),
+            );
+        if !flow_controller.try_acquire_topic_put(&request_header.topic, limit, msgs, bytes) {
+            response.with_code(ResponseCode::SystemBusy);
+            response.with_remark(format!(
+                "[TOPIC_FLOW_CONTROL]topic[{}] put rate exceeds {} msgs/s or {} bytes/s, start \
+                 flow control for a while",
+                request_header.topic, limit.msgs_per_second, limit.bytes_per_second
+            ));
+        }
+    }
+
+    /// Returns `false` and fills `response` when the message type carried by `properties`
+    /// does not match the `message.type` attribute of `topic_config`.
+    pub(crate) fn message_type_check(
+        &self,
+        topic_config: &TopicConfig,
+        properties: &HashMap<CheetahString, CheetahString>,
+        response: &mut RemotingCommand,
+    ) -> bool {
+        if !self
+            .broker_runtime_inner
+            .broker_config()
+            .enable_topic_message_type_check
+        {
+            return true;
+        }
+        let topic_message_type = topic_config.get_topic_message_type();
+        let message_type = TopicMessageType::parse_from_message_property(properties);
+        if topic_message_type.is_compatible_with(&message_type) {
+            return true;
+        }
+        response.with_code(ResponseCode::MessageIllegal);
+        response.with_remark(format!(
+            "the message type[{}] is not matched with topic[{}] message type[{}]",
+            message_type,
+            topic_config
+                .topic_name
+                .as_ref()
+                .map_or("", |topic| topic.as_str()),
+            topic_message_type
+        ));
+        false
+    }
+
     pub(crate) fn random_queue_id(&self, write_queue_nums: u32) -> u32 {
         rand::rng().random_range(0..=99999999) % write_queue_nums
     }
 }
 
+/// Properties the broker assigns itself, a client setting them would corrupt batch, light
+/// message queue or pop checkpoint bookkeeping.
+const BROKER_INTERNAL_PROPERTIES: [&str; 4] = [
+    MessageConst::PROPERTY_INNER_BASE,
+    MessageConst::PROPERTY_INNER_MULTI_QUEUE_OFFSET,
+    MessageConst::PROPERTY_POP_CK,
+    MessageConst::PROPERTY_POP_CK_OFFSET,
+];
+
+/// Validates the parts of a send request that the store relies on, returning the remark of the
+/// `MESSAGE_ILLEGAL` response when the message must be rejected.
+fn check_message_content(
+    topic: &str,
+    properties: Option<&str>,
+    body_len: usize,
+    max_message_size: usize,
+) -> Option<String> {
+    if topic.len() > TOPIC_MAX_LENGTH {
+        return Some(format!(
+            "message topic length too long {}, max length {}",
+            topic.len(),
+            TOPIC_MAX_LENGTH
+        ));
+    }
+    if body_len > max_message_size {
+        return Some(format!(
+            "message body size exceeded, msg body size: {body_len}, maxMessageSize: \
+             {max_message_size}"
+        ));
+    }
+    let properties = properties?;
+    if properties.len() > i16::MAX as usize {
+        return Some(format!(
+            "message properties length too long, msg properties length: {}, max length: {}",
+            properties.len(),
+            i16::MAX
+        ));
+    }
+    // broker-to-broker traffic on system topics legitimately carries internal properties
+    if TopicValidator::is_system_topic(topic) {
+        return None;
+    }
+    let properties = string_to_message_properties(Some(&CheetahString::from_slice(properties)));
+    BROKER_INTERNAL_PROPERTIES
+        .iter()
+        .find(|name| properties.contains_key(**name))
+        .map(|name| format!("message property {name} is reserved by the broker"))
+}
+
+/// Answers a send whose message is stored or being stored by an earlier send of it.
+fn answer_duplicate_send(
+    reservation: Reservation,
+    response: RemotingCommand,
+    topic: &CheetahString,
+    queue_id: i32,
+    transaction_id: Option<CheetahString>,
+    mapping_context: &TopicQueueMappingContext,
+) -> Option<RemotingCommand> {
+    match reservation {
+        Reservation::Stored(stored_message) => {
+            info!(
+                "drop the duplicate send of message {} to {}-{}, it was stored at offset {}",
+                transaction_id.as_deref().unwrap_or_default(),
+                topic,
+                queue_id,
+                stored_message.queue_offset
+            );
+            duplicate_send_response(
+                response,
+                stored_message,
+                queue_id,
+                transaction_id,
+                mapping_context,
+            )
+        }
+        // the producer retries, by then the first send stored the message or gave up
+        _ => Some(
+            response
+                .set_code(ResponseCode::SystemBusy)
+                .set_remark(format!(
+                    "message {} is being stored by an earlier send, try again later",
+                    transaction_id.unwrap_or_default()
+                )),
+        ),
+    }
+}
+
+/// Remembers where the message of a reserved dedup key was stored, or releases the key when
+/// storing it failed.
+fn complete_dedup_reservation(
+    dedup_reservation: Option<DedupReservation>,
+    put_message_result: &PutMessageResult,
+) {
+    let Some(dedup_reservation) = dedup_reservation else {
+        return;
+    };
+    let stored_message = put_message_result
+        .append_message_result()
+        .filter(|_| put_message_result.is_ok())
+        .and_then(|append_message_result| {
+            Some(StoredMessage {
+                msg_id: CheetahString::from_string(append_message_result.get_message_id()?),
+                queue_offset: append_message_result.logics_offset,
+            })
+        });
+    dedup_reservation.complete(stored_message);
+}
+
+/// Answers a retried send with the message stored by the first send.
+fn duplicate_send_response(
+    mut response: RemotingCommand,
+    stored_message: StoredMessage,
+    queue_id: i32,
+    transaction_id: Option<CheetahString>,
+    mapping_context: &TopicQueueMappingContext,
+) -> Option<RemotingCommand> {
+    response.set_code_ref(RemotingSysResponseCode::Success);
+    let response_header = response
+        .read_custom_header_mut::<SendMessageResponseHeader>()
+        .unwrap();
+    response_header.set_msg_id(stored_message.msg_id);
+    response_header.set_queue_id(queue_id);
+    response_header.set_queue_offset(stored_message.queue_offset);
+    response_header.set_transaction_id(transaction_id);
+    if let Some(rewrite_result) =
+        rewrite_response_for_static_topic(response_header, mapping_context)
+    {
+        return Some(rewrite_result);
+    }
+    Some(response)
+}
+
 fn rewrite_response_for_static_topic(
     response_header: &mut SendMessageResponseHeader,
     mapping_context: &TopicQueueMappingContext,
@@ -1591,3 +1953,135 @@ fn rewrite_response_for_static_topic(
     response_header.set_queue_offset(static_logic_offset);
     None
 }
+
+/// Returns true if `topic` is the message trace topic and the broker does not accept traces.
+fn is_trace_topic_forbidden(broker_config: &BrokerConfig, topic: &str) -> bool {
+    !broker_config.trace_topic_enable
+        && (topic == TopicValidator::RMQ_SYS_TRACE_TOPIC
+            || topic == broker_config.msg_trace_topic_name.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX_MESSAGE_SIZE: usize = 1024;
+
+    #[test]
+    fn duplicate_send_is_answered_with_the_stored_message() {
+        let response = RemotingCommand::create_response_command_with_header(
+            SendMessageResponseHeader::default(),
+        )
+        .set_code(-1);
+        let stored_message = StoredMessage {
+            msg_id: CheetahString::from_static_str("stored-msg-id"),
+            queue_offset: 42,
+        };
+        let mut response = duplicate_send_response(
+            response,
+            stored_message,
+            3,
+            Some(CheetahString::from_static_str("uniq-key")),
+            &TopicQueueMappingContext::default(),
+        )
+        .unwrap();
+
+        assert_eq!(response.code(), RemotingSysResponseCode::Success as i32);
+        let response_header = response
+            .read_custom_header_mut::<SendMessageResponseHeader>()
+            .unwrap();
+        assert_eq!(response_header.msg_id().as_str(), "stored-msg-id");
+        assert_eq!(response_header.queue_id(), 3);
+        assert_eq!(response_header.queue_offset(), 42);
+        assert_eq!(response_header.transaction_id(), Some("uniq-key"));
+    }
+
+    #[test]
+    fn send_of_a_message_being_stored_is_asked_to_retry() {
+        let response = RemotingCommand::create_response_command_with_header(
+            SendMessageResponseHeader::default(),
+        )
+        .set_code(-1);
+        let response = answer_duplicate_send(
+            Reservation::Storing,
+            response,
+            &CheetahString::from_static_str("topic"),
+            3,
+            Some(CheetahString::from_static_str("uniq-key")),
+            &TopicQueueMappingContext::default(),
+        )
+        .unwrap();
+
+        assert_eq!(response.code(), ResponseCode::SystemBusy as i32);
+    }
+
+    #[test]
+    fn accepts_regular_message() {
+        let properties = message_properties_to_string(&HashMap::from([(
+            CheetahString::from_static_str(MessageConst::PROPERTY_TAGS),
+            CheetahString::from_static_str("TagA"),
+        )]));
+        assert!(check_message_content(
+            "topic",
+            Some(properties.as_str()),
+            MAX_MESSAGE_SIZE,
+            MAX_MESSAGE_SIZE
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn rejects_oversized_body_and_topic() {
+        let remark = check_message_content("topic", None, MAX_MESSAGE_SIZE + 1, MAX_MESSAGE_SIZE);
+        assert!(remark.unwrap().contains("message body size exceeded"));
+
+        let topic = "t".repeat(TOPIC_MAX_LENGTH + 1);
+        let remark = check_message_content(&topic, None, 0, MAX_MESSAGE_SIZE);
+        assert!(remark.unwrap().contains("message topic length too long"));
+    }
+
+    #[test]
+    fn rejects_too_long_properties() {
+        let properties = "a".repeat(i16::MAX as usize + 1);
+        let remark = check_message_content("topic", Some(&properties), 0, MAX_MESSAGE_SIZE);
+        assert!(remark
+            .unwrap()
+            .contains("message properties length too long"));
+    }
+
+    #[test]
+    fn rejects_broker_internal_properties_on_user_topics() {
+        let properties = message_properties_to_string(&HashMap::from([(
+            CheetahString::from_static_str(MessageConst::PROPERTY_INNER_MULTI_QUEUE_OFFSET),
+            CheetahString::from_static_str("1"),
+        )]));
+        let remark = check_message_content("topic", Some(&properties), 0, MAX_MESSAGE_SIZE);
+        assert_eq!(
+            remark.unwrap(),
+            "message property INNER_MULTI_QUEUE_OFFSET is reserved by the broker"
+        );
+        assert!(check_message_content(
+            TopicValidator::RMQ_SYS_TRACE_TOPIC,
+            Some(&properties),
+            0,
+            MAX_MESSAGE_SIZE
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn trace_topic_requires_trace_topic_enable() {
+        let mut broker_config = BrokerConfig::default();
+        assert!(is_trace_topic_forbidden(
+            &broker_config,
+            TopicValidator::RMQ_SYS_TRACE_TOPIC
+        ));
+        assert!(!is_trace_topic_forbidden(&broker_config, "TopicA"));
+
+        broker_config.trace_topic_enable = true;
+        assert!(!is_trace_topic_forbidden(
+            &broker_config,
+            TopicValidator::RMQ_SYS_TRACE_TOPIC
+        ));
+    }
+}
@@ -49,8 +49,6 @@ use rocketmq_rust::ArcMut;
 use rocketmq_store::base::message_result::PutMessageResult;
 use rocketmq_store::base::message_status_enum::PutMessageStatus;
 use rocketmq_store::base::message_store::MessageStore;
-use rocketmq_store::queue::consume_queue_store::ConsumeQueueStoreTrait;
-use rocketmq_store::queue::local_file_consume_queue_store::ConsumeQueueStore;
 use rocketmq_store::store_path_config_helper::get_delay_offset_store_path;
 use tokio::sync::Mutex;
 use tracing::error;
@@ -114,6 +112,8 @@ pub type DeliverPendingTable<MS> = Arc<DashMap<i32, Arc<Mutex<VecDeque<PutResult
 pub struct ScheduleMessageService<MS> {
     delay_level_table: ArcMut<BTreeMap<i32 /* level */, i64 /* delay timeMillis */>>,
     offset_table: ArcMut<DashMap<i32, i64>>,
+    // how far behind the due time of its messages each delay level delivers, in milliseconds
+    dequeue_lag_table: DashMap<i32, i64>,
     started: AtomicBool,
     max_delay_level: AtomicI32,
     data_version: ArcMut<DataVersion>,
@@ -132,6 +132,7 @@ impl<MS: MessageStore> ScheduleMessageService<MS> {
         Self {
             delay_level_table: ArcMut::new(BTreeMap::new()),
             offset_table: ArcMut::new(DashMap::new()),
+            dequeue_lag_table: DashMap::new(),
             started: AtomicBool::new(false),
             max_delay_level: AtomicI32::new(0),
             data_version: ArcMut::new(DataVersion::new()),
@@ -165,9 +166,31 @@ impl<MS: MessageStore> ScheduleMessageService<MS> {
                 delay_level
             );
             stats.insert(key, value);
+
+            let key = format!(
+                "{}_{}",
+                RunningStats::ScheduleMessagePending.as_str(),
+                delay_level
+            );
+            stats.insert(key, (max_offset - *delay_offset).max(0).to_string());
+
+            let dequeue_lag = self
+                .dequeue_lag_table
+                .get(delay_level)
+                .map_or(0, |lag| *lag);
+            let key = format!(
+                "{}_{}",
+                RunningStats::ScheduleMessageDequeueLag.as_str(),
+                delay_level
+            );
+            stats.insert(key, dequeue_lag.to_string());
         }
     }
 
+    fn update_dequeue_lag(&self, delay_level: i32, lag_millis: i64) {
+        self.dequeue_lag_table.insert(delay_level, lag_millis);
+    }
+
     fn update_offset(&self, delay_level: i32, offset: i64) {
         self.offset_table.insert(delay_level, offset);
 
@@ -197,6 +220,19 @@ impl<MS: MessageStore> ScheduleMessageService<MS> {
         }
     }
 
+    /// Returns the smallest delay level that waits at least `delay_millis`, or the max level when
+    /// no level is long enough. Returns 0 when there is nothing to wait for.
+    pub fn delay_level_of(&self, delay_millis: i64) -> i32 {
+        if delay_millis <= 0 {
+            return 0;
+        }
+        self.delay_level_table
+            .iter()
+            .find(|(_, time)| **time >= delay_millis)
+            .map(|(level, _)| *level)
+            .unwrap_or_else(|| self.get_max_delay_level())
+    }
+
     pub fn start(this: ArcMut<Self>) -> Result<(), Box<dyn std::error::Error>> {
         if this
             .started
@@ -287,6 +323,29 @@ impl<MS: MessageStore> ScheduleMessageService<MS> {
         *current = data_version;
     }
 
+    /// Applies the delay offsets of another broker in the same group, as long as their data
+    /// version is not older than the local one. Returns whether the offsets were applied.
+    pub fn sync_delay_offset(&self, json_string: &str) -> bool {
+        let wrapper =
+            match SerdeJsonUtils::from_json_str::<DelayOffsetSerializeWrapper>(json_string) {
+                Ok(wrapper) => wrapper,
+                Err(e) => {
+                    warn!("decode synced delay offset failed: {}", e);
+                    return false;
+                }
+            };
+        match wrapper.data_version() {
+            Some(data_version) if !self.data_version.compare(data_version).is_gt() => {}
+            _ => return false,
+        }
+        self.decode(json_string);
+        if let Err(e) = self.load_when_sync_delay_offset() {
+            warn!("load synced delay offset failed: {}", e);
+        }
+        self.persist();
+        true
+    }
+
     fn load_super(&self) -> Result<bool, Box<dyn std::error::Error>> {
         // Mock implementation for the parent class load method
         Ok(true)
@@ -308,30 +367,21 @@ impl<MS: MessageStore> ScheduleMessageService<MS> {
     /// `true` if corrections were successful, `false` otherwise
     pub fn correct_delay_offset(&self) -> bool {
         let topic = CheetahString::from_static_str(TopicValidator::RMQ_SYS_SCHEDULE_TOPIC);
+        let message_store = self.broker_controller.message_store_unchecked();
         for delay_level in self.delay_level_table.keys() {
             let queue_id = delay_level_to_queue_id(*delay_level);
-            let cq = self
-                .broker_controller
-                .message_store_unchecked()
-                .get_queue_store()
-                .downcast_ref::<ConsumeQueueStore>()
-                .expect("Failed to downcast to ConsumeQueueStore")
-                .find_or_create_consume_queue(&topic, queue_id);
 
             if let Some(current_delay_offset) = self.offset_table.get(delay_level) {
                 let mut correct_delay_offset = *current_delay_offset;
-                let cq_min_offset = cq.get_min_offset_in_queue();
-                let cq_max_offset = cq.get_max_offset_in_queue();
+                let cq_min_offset = message_store.get_min_offset_in_queue(&topic, queue_id);
+                let cq_max_offset = message_store.get_max_offset_in_queue(&topic, queue_id);
 
                 if *current_delay_offset < cq_min_offset {
                     correct_delay_offset = cq_min_offset;
                     error!(
                         "schedule CQ offset invalid. offset={}, cqMinOffset={}, cqMaxOffset={}, \
                          queueId={}",
-                        *current_delay_offset,
-                        cq_min_offset,
-                        cq_max_offset,
-                        cq.get_queue_id()
+                        *current_delay_offset, cq_min_offset, cq_max_offset, queue_id
                     );
                 }
 
@@ -340,10 +390,7 @@ impl<MS: MessageStore> ScheduleMessageService<MS> {
                     error!(
                         "schedule CQ offset invalid. offset={}, cqMinOffset={}, cqMaxOffset={}, \
                          queueId={}",
-                        *current_delay_offset,
-                        cq_min_offset,
-                        cq_max_offset,
-                        cq.get_queue_id()
+                        *current_delay_offset, cq_min_offset, cq_max_offset, queue_id
                     );
                 }
 
@@ -585,6 +632,10 @@ impl<MS: MessageStore> DeliverDelayedMessageTimerTask<MS> {
             Ok(_) => {}
             Err(e) => {
                 error!("ScheduleMessageService, executeOnTimeUp exception: {}", e);
+                self.schedule_service
+                    .broker_controller
+                    .broker_stats_manager()
+                    .inc_schedule_deliver_failed_nums();
                 self.schedule_next_timer_task(self.offset, DELAY_FOR_A_PERIOD);
             }
         }
@@ -632,7 +683,7 @@ impl<MS: MessageStore> DeliverDelayedMessageTimerTask<MS> {
                         "schedule CQ offset invalid. offset={}, cqMinOffset={}, queueId={}",
                         self.offset,
                         cq.get_min_offset_in_queue(),
-                        cq.get_queue_id()
+                        queue_id
                     );
                     cq.get_min_offset_in_queue()
                 } else if cq.get_max_offset_in_queue() < self.offset {
@@ -640,7 +691,7 @@ impl<MS: MessageStore> DeliverDelayedMessageTimerTask<MS> {
                         "schedule CQ offset invalid. offset={}, cqMaxOffset={}, queueId={}",
                         self.offset,
                         cq.get_max_offset_in_queue(),
-                        cq.get_queue_id()
+                        queue_id
                     );
                     cq.get_max_offset_in_queue()
                 } else {
@@ -677,8 +728,8 @@ impl<MS: MessageStore> DeliverDelayedMessageTimerTask<MS> {
                     .schedule_service
                     .broker_controller
                     .message_store_unchecked()
-                    .get_commit_log()
-                    .pickup_store_timestamp(offset_py, size_py);
+                    .look_message_by_offset_with_size(offset_py, size_py)
+                    .map_or(-1, |msg| msg.store_timestamp);
 
                 tags_code = self
                     .schedule_service
@@ -695,11 +746,15 @@ impl<MS: MessageStore> DeliverDelayedMessageTimerTask<MS> {
 
             let countdown = deliver_timestamp - now;
             if countdown > 0 {
+                self.schedule_service
+                    .update_dequeue_lag(self.delay_level, 0);
                 self.schedule_next_timer_task(curr_offset, DELAY_FOR_A_WHILE);
                 self.schedule_service
                     .update_offset(self.delay_level, curr_offset);
                 return Ok(());
             }
+            self.schedule_service
+                .update_dequeue_lag(self.delay_level, -countdown);
 
             // Look up the actual message
             let msg_ext = match self
@@ -742,6 +797,8 @@ impl<MS: MessageStore> DeliverDelayedMessageTimerTask<MS> {
 
         // Release the iterator
         buffer_cq.release();
+        self.schedule_service
+            .update_dequeue_lag(self.delay_level, 0);
 
         // Schedule the next task
         self.schedule_next_timer_task(next_offset, DELAY_FOR_A_WHILE);
@@ -1157,6 +1214,9 @@ impl<MS: MessageStore> PutResultProcess<MS> {
     /// Handle an exception during processing
     pub fn on_exception(&self) {
         warn!("ScheduleMessageService onException, info: {}", self);
+        self.broker_controller
+            .broker_stats_manager()
+            .inc_schedule_deliver_failed_nums();
 
         let status_guard = self.status.mut_from_ref();
         *status_guard = if self.auto_resend {
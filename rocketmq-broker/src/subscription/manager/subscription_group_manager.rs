@@ -19,6 +19,9 @@ use std::collections::HashMap;
 use std::sync::Arc;
 
 use cheetah_string::CheetahString;
+use rocketmq_common::common::attribute::attribute_util::AttributeError;
+use rocketmq_common::common::attribute::attribute_util::AttributeUtil;
+use rocketmq_common::common::attribute::subscription_group_attributes::SubscriptionGroupAttributes;
 use rocketmq_common::common::config_manager::ConfigManager;
 use rocketmq_common::common::mix_all::is_sys_consumer_group;
 use rocketmq_common::common::topic::TopicValidator;
@@ -30,6 +33,7 @@ use rocketmq_store::base::message_store::MessageStore;
 use serde::Deserialize;
 use serde::Serialize;
 use tracing::info;
+use tracing::warn;
 
 use crate::broker_path_config_helper::get_subscription_group_path;
 use crate::broker_runtime::BrokerRuntimeInner;
@@ -171,6 +175,87 @@ where
         subscription_group_config
     }
 
+    pub fn update_subscription_group_config(
+        &self,
+        config: &mut SubscriptionGroupConfig,
+    ) -> Result<(), AttributeError> {
+        self.update_subscription_group_config_without_persist(config)?;
+        self.persist();
+        Ok(())
+    }
+
+    fn update_subscription_group_config_without_persist(
+        &self,
+        config: &mut SubscriptionGroupConfig,
+    ) -> Result<(), AttributeError> {
+        let group_name = CheetahString::from(config.group_name());
+        let state_machine_version =
+            if let Some(ref store) = self.broker_runtime_inner.message_store() {
+                store.get_state_machine_version()
+            } else {
+                0
+            };
+        // one guard, so a concurrent update cannot slip in between reading the attributes and
+        // writing the config back
+        let old = {
+            let mut wrapper = self.subscription_group_wrapper.lock();
+            let current_attributes = wrapper
+                .subscription_group_table
+                .get(&group_name)
+                .map(|old| old.attributes());
+            let final_attributes = AttributeUtil::alter_current_attributes(
+                current_attributes.is_none(),
+                SubscriptionGroupAttributes::all(),
+                current_attributes.unwrap_or(&HashMap::new()),
+                config.attributes(),
+            )?;
+            config.set_attributes(final_attributes);
+            let old = wrapper
+                .subscription_group_table
+                .insert(group_name, config.clone());
+            wrapper
+                .data_version
+                .next_version_with(state_machine_version);
+            old
+        };
+        match old {
+            Some(old) => info!(
+                "update subscription group config, old: {:?} new: {:?}",
+                old, config
+            ),
+            None => info!("create new subscription group, {:?}", config),
+        }
+        Ok(())
+    }
+
+    pub fn delete_subscription_group_config(&self, group_name: &CheetahString) {
+        let old = {
+            let mut wrapper = self.subscription_group_wrapper.lock();
+            wrapper.forbidden_table.remove(group_name);
+            wrapper.subscription_group_table.remove(group_name)
+        };
+        match old {
+            Some(old) => {
+                info!("delete subscription group OK, subscription group:{:?}", old);
+                let state_machine_version =
+                    if let Some(ref store) = self.broker_runtime_inner.message_store() {
+                        store.get_state_machine_version()
+                    } else {
+                        0
+                    };
+                self.subscription_group_wrapper
+                    .lock()
+                    .data_version
+                    .next_version_with(state_machine_version);
+                self.persist();
+            }
+            None => warn!(
+                "delete subscription group failed, subscription groupName: {} not exist",
+                group_name
+            ),
+        }
+    }
+
     fn find_subscription_group_config_inner(
         &self,
         group: &CheetahString,
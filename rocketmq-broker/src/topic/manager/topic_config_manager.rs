@@ -21,6 +21,7 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use cheetah_string::CheetahString;
+use rocketmq_common::common::attribute::attribute_util::AttributeError;
 use rocketmq_common::common::attribute::attribute_util::AttributeUtil;
 use rocketmq_common::common::config::TopicConfig;
 use rocketmq_common::common::config_manager::ConfigManager;
@@ -30,6 +31,7 @@ use rocketmq_common::common::pop_ack_constants::PopAckConstants;
 use rocketmq_common::common::topic::TopicValidator;
 use rocketmq_common::utils::serde_json_utils::SerdeJsonUtils;
 use rocketmq_common::TopicAttributes::TopicAttributes;
+use rocketmq_remoting::protocol::body::kv_table::KVTable;
 use rocketmq_remoting::protocol::body::topic_info_wrapper::topic_config_wrapper::TopicConfigAndMappingSerializeWrapper;
 use rocketmq_remoting::protocol::body::topic_info_wrapper::TopicConfigSerializeWrapper;
 use rocketmq_remoting::protocol::static_topic::topic_queue_info::TopicQueueMappingInfo;
@@ -363,6 +365,11 @@ impl<MS: MessageStore> TopicConfigManager<MS> {
                     (None, false)
                 }
             } else {
+                warn!(
+                    "Create new topic failed, because the default topic[{}] not exist. \
+                     producer:[{}]",
+                    default_topic, remote_address
+                );
                 (None, false)
             }
         } else {
@@ -386,7 +393,11 @@ impl<MS: MessageStore> TopicConfigManager<MS> {
         if let Some(ref mut config) = self.get_topic_config(topic) {
             if is_order != config.order {
                 config.order = is_order;
-                self.update_topic_config(config);
+                // attributes of a stored config are already applied, keep them untouched
+                config.attributes.clear();
+                if let Err(e) = self.update_topic_config(config) {
+                    error!("update order of topic {} failed: {}", topic, e);
+                }
             }
             return Some(config.clone());
         }
@@ -430,21 +441,60 @@ impl<MS: MessageStore> TopicConfigManager<MS> {
         let broker_config = self.broker_runtime_inner.broker_config().clone();
         let broker_runtime_inner = self.broker_runtime_inner.clone();
         let topic_config_clone = topic_config.clone();
+        let data_version = self.data_version.as_ref().clone();
         tokio::spawn(async move {
             if broker_config.enable_single_topic_register {
                 broker_runtime_inner
                     .register_single_topic_all(topic_config_clone)
                     .await;
             } else {
-                unimplemented!()
+                BrokerRuntimeInner::register_increment_broker_data(
+                    broker_runtime_inner,
+                    vec![topic_config_clone],
+                    data_version,
+                )
+                .await;
             }
         });
     }
 
-    pub fn update_topic_config_list(&mut self, topic_config_list: &mut [TopicConfig]) {
+    /// Mark the topics carried in the order config table from the name server as order topics.
+    pub fn update_order_topic_config(&self, order_kv_table_from_ns: &KVTable) {
+        let mut is_change = false;
+        {
+            let mut topic_config_table = self.topic_config_table.lock();
+            for topic in order_kv_table_from_ns.table.keys() {
+                if let Some(topic_config) = topic_config_table.get_mut(topic) {
+                    if !topic_config.order {
+                        topic_config.order = true;
+                        is_change = true;
+                        info!("update order topic config, topic={}, order={}", topic, true);
+                    }
+                }
+            }
+        }
+        if is_change {
+            let state_machine_version =
+                if let Some(message_store) = self.broker_runtime_inner.message_store().as_ref() {
+                    message_store.get_state_machine_version()
+                } else {
+                    0
+                };
+            self.data_version
+                .mut_from_ref()
+                .next_version_with(state_machine_version);
+            self.persist();
+        }
+    }
+
+    pub fn update_topic_config_list(
+        &mut self,
+        topic_config_list: &mut [TopicConfig],
+    ) -> Result<(), AttributeError> {
         for topic_config in topic_config_list {
-            self.update_topic_config(topic_config);
+            self.update_topic_config(topic_config)?;
         }
+        Ok(())
     }
 
     #[inline]
@@ -471,7 +521,10 @@ impl<MS: MessageStore> TopicConfigManager<MS> {
         }
     }
 
-    pub fn update_topic_config(&mut self, topic_config: &mut TopicConfig) {
+    pub fn update_topic_config(
+        &mut self,
+        topic_config: &mut TopicConfig,
+    ) -> Result<(), AttributeError> {
         let new_attributes = Self::request(topic_config);
         let current_attributes = self.current(topic_config.topic_name.as_ref().unwrap().as_str());
         let create = self
@@ -480,22 +533,12 @@ impl<MS: MessageStore> TopicConfigManager<MS> {
             .get(topic_config.topic_name.as_ref().unwrap().as_str())
             .is_none();
 
-        let final_attributes_result = AttributeUtil::alter_current_attributes(
+        topic_config.attributes = AttributeUtil::alter_current_attributes(
             create,
             TopicAttributes::all(),
-            &new_attributes,
             &current_attributes,
-        );
-        match final_attributes_result {
-            Ok(final_attributes) => {
-                topic_config.attributes = final_attributes;
-            }
-            Err(e) => {
-                error!("Failed to alter current attributes: {:?}", e);
-                // Decide on an appropriate fallback action, e.g., using default attributes
-                topic_config.attributes = Default::default();
-            }
-        }
+            &new_attributes,
+        )?;
         match self.put_topic_config(topic_config.clone()) {
             None => {
                 info!("create new topic [{:?}]", topic_config)
@@ -519,6 +562,7 @@ impl<MS: MessageStore> TopicConfigManager<MS> {
             topic_config.topic_name.as_ref().unwrap().as_str(),
             Box::new(topic_config.clone()),
         );
+        Ok(())
     }
 
     fn request(topic_config: &TopicConfig) -> HashMap<CheetahString, CheetahString> {
@@ -0,0 +1,212 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::fs::File;
+use std::io;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Write;
+use std::path::PathBuf;
+
+use clap::Parser;
+use clap::Subcommand;
+use rocketmq_cli::store_tools::commit_log_reader::CommitLogReader;
+use rocketmq_cli::store_tools::exported_message::read_messages;
+use rocketmq_cli::store_tools::exported_message::write_messages;
+use rocketmq_cli::store_tools::exported_message::ExportFormat;
+use rocketmq_cli::store_tools::exported_message::ExportedMessage;
+use rocketmq_cli::store_tools::message_filter::MessageFilter;
+use rocketmq_client_rust::producer::default_mq_producer::DefaultMQProducer;
+use rocketmq_client_rust::producer::mq_producer::MQProducer;
+use rocketmq_client_rust::producer::send_status::SendStatus;
+use rocketmq_error::RocketMQResult;
+use rocketmq_rust::rocketmq;
+
+#[derive(Parser, Debug)]
+#[command(
+    author = "mxsm",
+    version = "0.2.0",
+    about = "Export messages from commit log files and import them into a broker"
+)]
+struct StoreToolsCli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Export the messages of a commit log, the broker owning it has to be stopped
+    Export {
+        /// Commit log directory, e.g. store/commitlog, or a single commit log file
+        #[arg(short, long, value_name = "PATH")]
+        path: PathBuf,
+
+        /// Only export messages stored under this topic
+        #[arg(short, long)]
+        topic: Option<String>,
+
+        /// Only export messages stored at or after this time, in milliseconds
+        #[arg(long, value_name = "MILLIS")]
+        begin: Option<i64>,
+
+        /// Only export messages stored before this time, in milliseconds
+        #[arg(long, value_name = "MILLIS")]
+        end: Option<i64>,
+
+        /// Only export messages with this key or unique key
+        #[arg(short, long)]
+        key: Option<String>,
+
+        /// Max message size of the broker that wrote the commit log, the largest body it stores.
+        /// Larger entries than a message with such a body are corrupted
+        #[arg(long, value_name = "BYTES", default_value_t = 4 * 1024 * 1024)]
+        max_message_size: usize,
+
+        /// Format of the export
+        #[arg(short, long, value_enum, default_value_t = ExportFormat::Ndjson)]
+        format: ExportFormat,
+
+        /// File to write to instead of the standard output
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Send exported messages to the brokers of a cluster
+    Import {
+        /// Export file, in either format
+        #[arg(short, long, value_name = "FILE")]
+        input: PathBuf,
+
+        /// Name server address, e.g. 127.0.0.1:9876
+        #[arg(short, long, default_value = "127.0.0.1:9876")]
+        namesrv_addr: String,
+
+        /// Send every message to this topic instead of its own
+        #[arg(short, long)]
+        topic: Option<String>,
+
+        /// Producer group
+        #[arg(short, long, default_value = "store_tools_import")]
+        group: String,
+
+        /// Send timeout in milliseconds
+        #[arg(long, default_value_t = 3000)]
+        send_timeout: u32,
+    },
+}
+
+#[rocketmq::main]
+async fn main() -> RocketMQResult<()> {
+    rocketmq_common::log::init_logger_with_level(rocketmq_common::log::Level::WARN);
+    match StoreToolsCli::parse().command {
+        Command::Export {
+            path,
+            topic,
+            begin,
+            end,
+            key,
+            max_message_size,
+            format,
+            output,
+        } => {
+            let filter = MessageFilter {
+                topic,
+                begin_timestamp: begin,
+                end_timestamp: end,
+                key,
+            };
+            export(path, filter, max_message_size, format, output)?;
+        }
+        Command::Import {
+            input,
+            namesrv_addr,
+            topic,
+            group,
+            send_timeout,
+        } => {
+            let mut producer = DefaultMQProducer::builder()
+                .producer_group(group)
+                .name_server_addr(namesrv_addr)
+                .send_msg_timeout(send_timeout)
+                .build();
+            producer.start().await?;
+            let result = import(&mut producer, input, topic.as_deref()).await;
+            producer.shutdown().await;
+            result?;
+        }
+    }
+    Ok(())
+}
+
+fn export(
+    path: PathBuf,
+    filter: MessageFilter,
+    max_message_size: usize,
+    format: ExportFormat,
+    output: Option<PathBuf>,
+) -> io::Result<()> {
+    let mut reader = CommitLogReader::open(path)?.with_max_message_size(max_message_size);
+    let writer: Box<dyn Write> = match output {
+        Some(output) => Box::new(BufWriter::new(File::create(output)?)),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+    let messages = reader
+        .by_ref()
+        .filter_map(|message| match message {
+            Ok(message) => Some(message),
+            Err(e) => {
+                eprintln!("skip the rest of a commit log file: {e}");
+                None
+            }
+        })
+        .filter(|message| filter.matches(message))
+        .map(|message| ExportedMessage::from(&message));
+    let count = write_messages(writer, format, messages)?;
+    eprintln!(
+        "exported {count} messages, skipped {} corrupted messages",
+        reader.skipped()
+    );
+    Ok(())
+}
+
+async fn import(
+    producer: &mut DefaultMQProducer,
+    input: PathBuf,
+    topic: Option<&str>,
+) -> RocketMQResult<()> {
+    let (mut sent, mut failed, mut skipped) = (0usize, 0usize, 0usize);
+    for exported in read_messages(BufReader::new(File::open(input)?))? {
+        let exported = exported?;
+        let Some(message) = exported.to_message(topic)? else {
+            skipped += 1;
+            continue;
+        };
+        let result = producer.send(message).await;
+        match result {
+            Ok(result) if result.send_status == SendStatus::SendOk => sent += 1,
+            Ok(result) => {
+                failed += 1;
+                eprintln!("send {} returns {:?}", exported.msg_id, result.send_status);
+            }
+            Err(e) => {
+                failed += 1;
+                eprintln!("send {} failed: {e}", exported.msg_id);
+            }
+        }
+    }
+    eprintln!("imported {sent} messages, {failed} failed, skipped {skipped} system messages");
+    Ok(())
+}
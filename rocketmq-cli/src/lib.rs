@@ -17,3 +17,4 @@
 
 pub mod command_line;
 pub mod content_show;
+pub mod store_tools;
@@ -0,0 +1,264 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::VecDeque;
+use std::fs;
+use std::fs::File;
+use std::io;
+use std::io::BufReader;
+use std::io::ErrorKind;
+use std::io::Read;
+use std::path::Path;
+use std::path::PathBuf;
+
+use bytes::Bytes;
+use bytes::BytesMut;
+use rocketmq_common::common::message::message_decoder;
+use rocketmq_common::common::message::message_decoder::MESSAGE_MAGIC_CODE;
+use rocketmq_common::common::message::message_decoder::MESSAGE_MAGIC_CODE_V2;
+use rocketmq_common::common::message::message_ext::MessageExt;
+use rocketmq_store::config::message_store_config::MessageStoreConfig;
+
+// TOTALSIZE and MAGICCODE
+const MESSAGE_HEADER_SIZE: usize = 4 + 4;
+// Room the broker leaves for the fields around a body of the max message size, see
+// `MessageExtEncoder`.
+const MESSAGE_FIELDS_HEADROOM: usize = 64 * 1024;
+
+/// Reads the messages of commit log files in offset order.
+///
+/// A file ends at its first blank or unwritten entry, the rest of it is padding. Messages whose
+/// body does not match its CRC are skipped and counted. An entry larger than a message whose body
+/// has the max message size is corrupted, the rest of its file is given up with an error.
+pub struct CommitLogReader {
+    files: VecDeque<PathBuf>,
+    current: Option<BufReader<File>>,
+    max_message_size: usize,
+    skipped: usize,
+}
+
+impl CommitLogReader {
+    /// Opens `path`, either a single commit log file or a commit log directory.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+        let files = if path.is_dir() {
+            let mut files = fs::read_dir(path)?
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|file| file.is_file())
+                .collect::<Vec<_>>();
+            // the files are named after the zero padded offset of their first byte
+            files.sort();
+            files
+        } else {
+            vec![path.to_path_buf()]
+        };
+        Ok(CommitLogReader {
+            files: files.into(),
+            current: None,
+            max_message_size: MessageStoreConfig::default().max_message_size as usize,
+            skipped: 0,
+        })
+    }
+
+    /// Sets the max message size of the broker that wrote the commit log, the largest body it
+    /// stores. Larger entries than a message with such a body are corrupted.
+    pub fn with_max_message_size(mut self, max_message_size: usize) -> Self {
+        self.max_message_size = max_message_size;
+        self
+    }
+
+    /// Returns the number of corrupted messages skipped so far.
+    pub fn skipped(&self) -> usize {
+        self.skipped
+    }
+}
+
+impl Iterator for CommitLogReader {
+    type Item = io::Result<MessageExt>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Some(reader) = self.current.as_mut() else {
+                let file = self.files.pop_front()?;
+                match File::open(&file) {
+                    Ok(file) => self.current = Some(BufReader::new(file)),
+                    Err(e) => return Some(Err(e)),
+                }
+                continue;
+            };
+            match read_entry(reader, self.max_message_size) {
+                Ok(Some(mut entry)) => {
+                    match message_decoder::decode(&mut entry, true, true, false, false, true) {
+                        Some(message) => return Some(Ok(message)),
+                        None => self.skipped += 1,
+                    }
+                }
+                Ok(None) => self.current = None,
+                Err(e) => {
+                    self.current = None;
+                    return Some(Err(e));
+                }
+            }
+        }
+    }
+}
+
+/// Reads the next message entry of a commit log file, `None` at the end of its messages.
+fn read_entry(reader: &mut impl Read, max_message_size: usize) -> io::Result<Option<Bytes>> {
+    let max_entry_size = max_message_size.saturating_add(MESSAGE_FIELDS_HEADROOM);
+    let mut header = [0u8; MESSAGE_HEADER_SIZE];
+    if !read_full(reader, &mut header)? {
+        return Ok(None);
+    }
+    let total_size = i32::from_be_bytes([header[0], header[1], header[2], header[3]]);
+    let magic_code = i32::from_be_bytes([header[4], header[5], header[6], header[7]]);
+    if magic_code != MESSAGE_MAGIC_CODE && magic_code != MESSAGE_MAGIC_CODE_V2 {
+        return Ok(None);
+    }
+    if total_size <= MESSAGE_HEADER_SIZE as i32 {
+        return Ok(None);
+    }
+    if total_size as usize > max_entry_size {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "message of {total_size} bytes exceeds the max entry size {max_entry_size} of the \
+                 max message size {max_message_size}"
+            ),
+        ));
+    }
+    let mut entry = BytesMut::zeroed(total_size as usize);
+    entry[..MESSAGE_HEADER_SIZE].copy_from_slice(&header);
+    // a message cut short by a crash is dropped like the broker does on recovery
+    if !read_full(reader, &mut entry[MESSAGE_HEADER_SIZE..])? {
+        return Ok(None);
+    }
+    Ok(Some(entry.freeze()))
+}
+
+/// Fills `buf`, returns `false` when the reader ends before.
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<bool> {
+    match reader.read_exact(buf) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use rocketmq_common::common::message::message_decoder::BLANK_MAGIC_CODE;
+    use rocketmq_common::common::message::message_single::Message;
+    use rocketmq_common::CRC32Utils::crc32;
+
+    use super::*;
+
+    fn encoded(topic: &str, body: &[u8], commit_log_offset: i64) -> Bytes {
+        let message_ext = MessageExt {
+            message: Message::new(topic, body),
+            commit_log_offset,
+            body_crc: crc32(body),
+            ..MessageExt::default()
+        };
+        message_decoder::encode(&message_ext, false).unwrap()
+    }
+
+    #[test]
+    fn messages_are_read_across_files_until_the_blank_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = encoded("TopicA", b"a", 0);
+        let mut corrupted = encoded("TopicA", b"b", first.len() as i64).to_vec();
+        // flip the byte of the body, which follows the 84 bytes of fixed fields and its length
+        corrupted[84 + 4] ^= 0xff;
+
+        let mut file = File::create(dir.path().join("00000000000000000000")).unwrap();
+        file.write_all(&first).unwrap();
+        file.write_all(&corrupted).unwrap();
+        file.write_all(&64i32.to_be_bytes()).unwrap();
+        file.write_all(&BLANK_MAGIC_CODE.to_be_bytes()).unwrap();
+        file.write_all(&[0u8; 56]).unwrap();
+
+        let mut file = File::create(dir.path().join("00000000000000001024")).unwrap();
+        file.write_all(&encoded("TopicB", b"c", 1024)).unwrap();
+        // unwritten tail of the last file
+        file.write_all(&[0u8; 128]).unwrap();
+
+        let mut reader = CommitLogReader::open(dir.path()).unwrap();
+        let messages = reader
+            .by_ref()
+            .map(|message| message.unwrap())
+            .map(|message| (message.message.topic.to_string(), message.commit_log_offset))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![("TopicA".to_string(), 0), ("TopicB".to_string(), 1024)]
+        );
+        assert_eq!(reader.skipped(), 1);
+    }
+
+    #[test]
+    fn a_message_cut_short_ends_the_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("00000000000000000000");
+        let message = encoded("TopicA", b"body", 0);
+        let mut file = File::create(&path).unwrap();
+        file.write_all(&message).unwrap();
+        file.write_all(&message[..message.len() / 2]).unwrap();
+
+        let messages = CommitLogReader::open(&path)
+            .unwrap()
+            .collect::<io::Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].message.body.as_deref(), Some(&b"body"[..]));
+    }
+
+    #[test]
+    fn an_entry_larger_than_the_max_message_size_is_corrupted() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("00000000000000000000");
+        let mut file = File::create(&path).unwrap();
+        file.write_all(&i32::MAX.to_be_bytes()).unwrap();
+        file.write_all(&MESSAGE_MAGIC_CODE.to_be_bytes()).unwrap();
+        file.write_all(&[0u8; 64]).unwrap();
+
+        let mut reader = CommitLogReader::open(&path).unwrap();
+        let error = reader.next().unwrap().unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert!(reader.next().is_none());
+
+        // the max message size limits the body, the other fields come on top of it
+        let body = vec![b'x'; 1024];
+        let message = encoded("TopicA", &body, 0);
+        File::create(&path).unwrap().write_all(&message).unwrap();
+        let mut reader = CommitLogReader::open(&path)
+            .unwrap()
+            .with_max_message_size(body.len());
+        assert!(reader.next().unwrap().is_ok());
+
+        let mut file = File::create(&path).unwrap();
+        file.write_all(&((1024 + MESSAGE_FIELDS_HEADROOM + 1) as i32).to_be_bytes())
+            .unwrap();
+        file.write_all(&MESSAGE_MAGIC_CODE.to_be_bytes()).unwrap();
+        let mut reader = CommitLogReader::open(&path)
+            .unwrap()
+            .with_max_message_size(1024);
+        assert!(reader.next().unwrap().is_err());
+    }
+}
@@ -0,0 +1,301 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::BTreeMap;
+use std::io;
+use std::io::BufRead;
+use std::io::Write;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use bytes::Bytes;
+use cheetah_string::CheetahString;
+use rocketmq_common::common::message::message_ext::MessageExt;
+use rocketmq_common::common::message::message_single::Message;
+use rocketmq_common::common::message::MessageConst;
+use rocketmq_common::common::topic::TopicValidator;
+use serde::Deserialize;
+use serde::Serialize;
+
+// properties the broker fills in when it stores a message again
+const BROKER_PROPERTIES: [&str; 3] = [
+    MessageConst::PROPERTY_MIN_OFFSET,
+    MessageConst::PROPERTY_MAX_OFFSET,
+    MessageConst::PROPERTY_CLUSTER,
+];
+
+// properties of a delayed message held under the schedule topic
+const DELAYED_PROPERTIES: [&str; 3] = [
+    MessageConst::PROPERTY_REAL_TOPIC,
+    MessageConst::PROPERTY_REAL_QUEUE_ID,
+    MessageConst::PROPERTY_DELAY_TIME_LEVEL,
+];
+
+/// File format of exported messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// One JSON array of all messages.
+    Json,
+    /// One JSON object per line.
+    Ndjson,
+}
+
+/// A stored message as written to an export file, the body is base64 encoded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedMessage {
+    pub topic: String,
+    pub msg_id: String,
+    pub queue_id: i32,
+    pub queue_offset: i64,
+    pub commit_log_offset: i64,
+    pub born_timestamp: i64,
+    pub store_timestamp: i64,
+    pub reconsume_times: i32,
+    pub flag: i32,
+    #[serde(default)]
+    pub properties: BTreeMap<String, String>,
+    #[serde(default)]
+    pub body: String,
+}
+
+impl From<&MessageExt> for ExportedMessage {
+    fn from(message: &MessageExt) -> Self {
+        ExportedMessage {
+            topic: message.message.topic.to_string(),
+            msg_id: message.msg_id.to_string(),
+            queue_id: message.queue_id,
+            queue_offset: message.queue_offset,
+            commit_log_offset: message.commit_log_offset,
+            born_timestamp: message.born_timestamp,
+            store_timestamp: message.store_timestamp,
+            reconsume_times: message.reconsume_times,
+            flag: message.message.flag,
+            properties: message
+                .message
+                .properties
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            body: message
+                .message
+                .body
+                .as_ref()
+                .map(|body| STANDARD.encode(body))
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl ExportedMessage {
+    /// Builds the message to send again, to `topic` if given instead of its own topic. The unique
+    /// key is kept, so the message keeps its message id.
+    ///
+    /// Delayed messages are stored under the schedule topic, they are sent to their real topic
+    /// right away. Messages of the other system topics, like transaction halves and revive
+    /// checkpoints, only mean something to the broker that stored them and are `None`.
+    pub fn to_message(&self, topic: Option<&str>) -> io::Result<Option<Message>> {
+        let (own_topic, dropped_properties): (&str, &[&str]) =
+            if self.topic == TopicValidator::RMQ_SYS_SCHEDULE_TOPIC {
+                match self.properties.get(MessageConst::PROPERTY_REAL_TOPIC) {
+                    Some(real_topic) => (real_topic, &DELAYED_PROPERTIES),
+                    None => return Ok(None),
+                }
+            } else if TopicValidator::is_system_topic(&self.topic) {
+                return Ok(None);
+            } else {
+                (&self.topic, &[])
+            };
+        let body = STANDARD
+            .decode(&self.body)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let mut message = Message::new_body(topic.unwrap_or(own_topic), Some(Bytes::from(body)));
+        message.flag = self.flag;
+        message.properties = self
+            .properties
+            .iter()
+            .filter(|(key, _)| {
+                !BROKER_PROPERTIES.contains(&key.as_str())
+                    && !dropped_properties.contains(&key.as_str())
+            })
+            .map(|(key, value)| {
+                (
+                    CheetahString::from(key.as_str()),
+                    CheetahString::from(value.as_str()),
+                )
+            })
+            .collect();
+        Ok(Some(message))
+    }
+}
+
+/// Writes `messages` to `writer` in `format`, returns the number of messages written.
+pub fn write_messages<W: Write>(
+    mut writer: W,
+    format: ExportFormat,
+    messages: impl Iterator<Item = ExportedMessage>,
+) -> io::Result<usize> {
+    let mut count = 0;
+    if format == ExportFormat::Json {
+        writer.write_all(b"[")?;
+    }
+    for message in messages {
+        if format == ExportFormat::Json && count > 0 {
+            writer.write_all(b",")?;
+        }
+        serde_json::to_writer(&mut writer, &message)?;
+        if format == ExportFormat::Ndjson {
+            writer.write_all(b"\n")?;
+        }
+        count += 1;
+    }
+    if format == ExportFormat::Json {
+        writer.write_all(b"]\n")?;
+    }
+    writer.flush()?;
+    Ok(count)
+}
+
+/// Reads messages written by [`write_messages`] in either format.
+pub fn read_messages<'a, R: BufRead + 'a>(
+    mut reader: R,
+) -> io::Result<Box<dyn Iterator<Item = io::Result<ExportedMessage>> + 'a>> {
+    if first_non_whitespace(&mut reader)? == Some(b'[') {
+        let messages: Vec<ExportedMessage> = serde_json::from_reader(reader)?;
+        return Ok(Box::new(messages.into_iter().map(Ok)));
+    }
+    let messages = serde_json::Deserializer::from_reader(reader)
+        .into_iter::<ExportedMessage>()
+        .map(|message| message.map_err(io::Error::from));
+    Ok(Box::new(messages))
+}
+
+fn first_non_whitespace(reader: &mut impl BufRead) -> io::Result<Option<u8>> {
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            return Ok(None);
+        }
+        match buf.iter().position(|b| !b.is_ascii_whitespace()) {
+            Some(pos) => {
+                let first = buf[pos];
+                reader.consume(pos);
+                return Ok(Some(first));
+            }
+            None => {
+                let len = buf.len();
+                reader.consume(len);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stored_message(offset: i64) -> MessageExt {
+        let mut message = MessageExt {
+            message: Message::with_keys(
+                "TopicA",
+                "TagA",
+                "order-1",
+                format!("body-{offset}").as_bytes(),
+            ),
+            queue_offset: offset,
+            commit_log_offset: offset * 100,
+            store_timestamp: 1_700_000_000_000,
+            ..MessageExt::default()
+        };
+        message.message.properties.insert(
+            CheetahString::from_static_str(MessageConst::PROPERTY_MAX_OFFSET),
+            CheetahString::from_static_str("2"),
+        );
+        message
+    }
+
+    #[test]
+    fn both_formats_read_back_what_was_written() {
+        let messages = (0..2)
+            .map(|offset| ExportedMessage::from(&stored_message(offset)))
+            .collect::<Vec<_>>();
+        for format in [ExportFormat::Json, ExportFormat::Ndjson] {
+            let mut out = Vec::new();
+            let count = write_messages(&mut out, format, messages.clone().into_iter()).unwrap();
+            assert_eq!(count, 2);
+            let read = read_messages(out.as_slice())
+                .unwrap()
+                .collect::<io::Result<Vec<_>>>()
+                .unwrap();
+            assert_eq!(read, messages);
+        }
+        assert_eq!(
+            read_messages(&b" \n"[..]).unwrap().count(),
+            0,
+            "an empty export has no messages"
+        );
+    }
+
+    #[test]
+    fn resent_message_drops_broker_properties() {
+        let exported = ExportedMessage::from(&stored_message(1));
+        assert_eq!(exported.body, "Ym9keS0x");
+
+        let message = exported.to_message(Some("TopicB")).unwrap().unwrap();
+        assert_eq!(message.topic.as_str(), "TopicB");
+        assert_eq!(message.body.as_deref(), Some(&b"body-1"[..]));
+        assert_eq!(
+            message
+                .properties
+                .get(MessageConst::PROPERTY_KEYS)
+                .map(|keys| keys.as_str()),
+            Some("order-1")
+        );
+        assert!(!message
+            .properties
+            .contains_key(MessageConst::PROPERTY_MAX_OFFSET));
+    }
+
+    #[test]
+    fn delayed_message_is_resent_to_its_real_topic_and_system_messages_are_not() {
+        let mut exported = ExportedMessage::from(&stored_message(1));
+        exported.topic = TopicValidator::RMQ_SYS_SCHEDULE_TOPIC.to_string();
+        for (key, value) in [
+            (MessageConst::PROPERTY_REAL_TOPIC, "TopicA"),
+            (MessageConst::PROPERTY_REAL_QUEUE_ID, "3"),
+            (MessageConst::PROPERTY_DELAY_TIME_LEVEL, "2"),
+        ] {
+            exported
+                .properties
+                .insert(key.to_string(), value.to_string());
+        }
+        let message = exported.to_message(None).unwrap().unwrap();
+        assert_eq!(message.topic.as_str(), "TopicA");
+        for key in DELAYED_PROPERTIES {
+            assert!(!message.properties.contains_key(key));
+        }
+
+        for topic in [
+            TopicValidator::RMQ_SYS_TRANS_HALF_TOPIC,
+            TopicValidator::RMQ_SYS_TRANS_OP_HALF_TOPIC,
+            "rmq_sys_REVIVE_LOG_DefaultCluster",
+        ] {
+            exported.topic = topic.to_string();
+            assert!(exported.to_message(Some("TopicB")).unwrap().is_none());
+        }
+    }
+}
@@ -0,0 +1,128 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use rocketmq_common::common::message::message_ext::MessageExt;
+use rocketmq_common::common::message::MessageConst;
+
+/// Selects the messages to export, every set condition has to match.
+#[derive(Debug, Clone, Default)]
+pub struct MessageFilter {
+    /// Topic the message is stored under.
+    pub topic: Option<String>,
+    /// Earliest store timestamp in milliseconds, inclusive.
+    pub begin_timestamp: Option<i64>,
+    /// Latest store timestamp in milliseconds, exclusive.
+    pub end_timestamp: Option<i64>,
+    /// One of the keys of the message, or its unique key.
+    pub key: Option<String>,
+}
+
+impl MessageFilter {
+    pub fn matches(&self, message: &MessageExt) -> bool {
+        if let Some(topic) = self.topic.as_deref() {
+            if message.message.topic.as_str() != topic {
+                return false;
+            }
+        }
+        if let Some(begin_timestamp) = self.begin_timestamp {
+            if message.store_timestamp < begin_timestamp {
+                return false;
+            }
+        }
+        if let Some(end_timestamp) = self.end_timestamp {
+            if message.store_timestamp >= end_timestamp {
+                return false;
+            }
+        }
+        match self.key.as_deref() {
+            Some(key) => has_key(message, key),
+            None => true,
+        }
+    }
+}
+
+fn has_key(message: &MessageExt, key: &str) -> bool {
+    let properties = &message.message.properties;
+    let in_keys = properties
+        .get(MessageConst::PROPERTY_KEYS)
+        .is_some_and(|keys| {
+            keys.split(MessageConst::KEY_SEPARATOR)
+                .any(|one| one == key)
+        });
+    in_keys
+        || properties
+            .get(MessageConst::PROPERTY_UNIQ_CLIENT_MESSAGE_ID_KEYIDX)
+            .is_some_and(|uniq_key| uniq_key.as_str() == key)
+}
+
+#[cfg(test)]
+mod tests {
+    use cheetah_string::CheetahString;
+
+    use super::*;
+
+    fn message(topic: &str, store_timestamp: i64, keys: &str) -> MessageExt {
+        let mut message = MessageExt {
+            store_timestamp,
+            ..MessageExt::default()
+        };
+        message.message.topic = CheetahString::from(topic);
+        message.message.properties.insert(
+            CheetahString::from_static_str(MessageConst::PROPERTY_KEYS),
+            CheetahString::from(keys),
+        );
+        message.message.properties.insert(
+            CheetahString::from_static_str(MessageConst::PROPERTY_UNIQ_CLIENT_MESSAGE_ID_KEYIDX),
+            CheetahString::from_static_str("7F00000100002A9F0000000000000000"),
+        );
+        message
+    }
+
+    #[test]
+    fn every_set_condition_has_to_match() {
+        let message = message("TopicA", 1_000, "order-1 user-7");
+        assert!(MessageFilter::default().matches(&message));
+
+        let filter = MessageFilter {
+            topic: Some("TopicA".to_string()),
+            begin_timestamp: Some(1_000),
+            end_timestamp: Some(2_000),
+            key: Some("user-7".to_string()),
+        };
+        assert!(filter.matches(&message));
+        assert!(!MessageFilter {
+            topic: Some("TopicB".to_string()),
+            ..filter.clone()
+        }
+        .matches(&message));
+        assert!(!MessageFilter {
+            end_timestamp: Some(1_000),
+            ..filter.clone()
+        }
+        .matches(&message));
+        assert!(!MessageFilter {
+            key: Some("user".to_string()),
+            ..filter.clone()
+        }
+        .matches(&message));
+        assert!(MessageFilter {
+            key: Some("7F00000100002A9F0000000000000000".to_string()),
+            ..filter
+        }
+        .matches(&message));
+    }
+}
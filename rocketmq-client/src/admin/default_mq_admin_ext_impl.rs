@@ -27,12 +27,17 @@ use lazy_static::lazy_static;
 use rocketmq_common::common::base::plain_access_config::PlainAccessConfig;
 use rocketmq_common::common::base::service_state::ServiceState;
 use rocketmq_common::common::config::TopicConfig;
+use rocketmq_common::common::key_builder::KeyBuilder;
 use rocketmq_common::common::message::message_enum::MessageRequestMode;
 use rocketmq_common::common::message::message_queue::MessageQueue;
 use rocketmq_common::common::mix_all;
 use rocketmq_common::common::FAQUrl;
+use rocketmq_error::mq_client_err;
 use rocketmq_error::ClientErr;
+use rocketmq_remoting::code::response_code::ResponseCode;
 use rocketmq_remoting::protocol::admin::consume_stats::ConsumeStats;
+use rocketmq_remoting::protocol::admin::pop_stats::PopStats;
+use rocketmq_remoting::protocol::admin::topic_disk_usage::TopicDiskUsage;
 use rocketmq_remoting::protocol::admin::topic_stats_table::TopicStatsTable;
 use rocketmq_remoting::protocol::body::broker_body::cluster_info::ClusterInfo;
 use rocketmq_remoting::protocol::body::consume_message_directly_result::ConsumeMessageDirectlyResult;
@@ -56,6 +61,7 @@ use crate::admin::mq_admin_ext_async_inner::MQAdminExtInnerImpl;
 use crate::base::client_config::ClientConfig;
 use crate::common::admin_tool_result::AdminToolResult;
 use crate::factory::mq_client_instance::MQClientInstance;
+use crate::implementation::mq_client_api_impl::MQClientAPIImpl;
 use crate::implementation::mq_client_manager::MQClientManager;
 
 lazy_static! {
@@ -116,6 +122,102 @@ impl DefaultMQAdminExtImpl {
     pub fn set_inner(&mut self, inner: ArcMut<DefaultMQAdminExtImpl>) {
         self.inner = Some(inner);
     }
+
+    fn mq_client_api_impl(&self) -> &ArcMut<MQClientAPIImpl> {
+        self.client_instance
+            .as_ref()
+            .expect("DefaultMQAdminExtImpl is not started")
+            .mq_client_api_impl
+            .as_ref()
+            .expect("MQClientAPIImpl is not initialized")
+    }
+
+    fn timeout_millis(&self) -> u64 {
+        self.timeout_millis.as_millis() as u64
+    }
+
+    /// Addresses of the brokers serving `consumer_group`: the masters of `cluster_name` if given,
+    /// otherwise the brokers routing its retry topic, `topic` or the pop retry topic of `topic`.
+    async fn consumer_broker_addrs(
+        &self,
+        consumer_group: &CheetahString,
+        topic: Option<&CheetahString>,
+        cluster_name: Option<&CheetahString>,
+    ) -> rocketmq_error::RocketMQResult<Vec<CheetahString>> {
+        if let Some(cluster_name) = cluster_name {
+            let cluster_info = self.examine_broker_cluster_info().await?;
+            return Ok(master_addrs_of_cluster(&cluster_info, cluster_name));
+        }
+        let mut route_topics = vec![CheetahString::from_string(mix_all::get_retry_topic(
+            consumer_group,
+        ))];
+        if let Some(topic) = topic {
+            route_topics.push(topic.clone());
+            route_topics.push(CheetahString::from_string(
+                KeyBuilder::build_pop_retry_topic_default(topic, consumer_group),
+            ));
+        }
+        let mut last_error = None;
+        for route_topic in route_topics {
+            match self.examine_topic_route_info(route_topic).await {
+                Ok(Some(topic_route_data)) => {
+                    return Ok(topic_route_data
+                        .broker_datas
+                        .iter()
+                        .filter_map(|broker_data| broker_data.select_broker_addr())
+                        .collect());
+                }
+                Ok(None) => {}
+                Err(e) => last_error = Some(e),
+            }
+        }
+        match last_error {
+            Some(e) => Err(e),
+            None => mq_client_err!(
+                ResponseCode::TopicNotExist,
+                format!("no route found for consumer group {consumer_group}")
+            ),
+        }
+    }
+}
+
+/// Collects the master addresses of every broker in `cluster_name`.
+fn master_addrs_of_cluster(
+    cluster_info: &ClusterInfo,
+    cluster_name: &CheetahString,
+) -> Vec<CheetahString> {
+    let (Some(cluster_addr_table), Some(broker_addr_table)) = (
+        cluster_info.cluster_addr_table.as_ref(),
+        cluster_info.broker_addr_table.as_ref(),
+    ) else {
+        return vec![];
+    };
+    cluster_addr_table
+        .get(cluster_name)
+        .into_iter()
+        .flatten()
+        .filter_map(|broker_name| broker_addr_table.get(broker_name))
+        .filter_map(|broker_data| broker_data.broker_addrs().get(&mix_all::MASTER_ID).cloned())
+        .collect()
+}
+
+/// Collects the master and slave addresses of every broker in `cluster_name`.
+fn master_and_slave_addrs_of_cluster(
+    cluster_info: &ClusterInfo,
+    cluster_name: &CheetahString,
+) -> Option<HashSet<CheetahString>> {
+    let broker_names = cluster_info
+        .cluster_addr_table
+        .as_ref()?
+        .get(cluster_name)?;
+    let broker_addr_table = cluster_info.broker_addr_table.as_ref()?;
+    Some(
+        broker_names
+            .iter()
+            .filter_map(|broker_name| broker_addr_table.get(broker_name))
+            .flat_map(|broker_data| broker_data.broker_addrs().values().cloned())
+            .collect(),
+    )
 }
 
 #[allow(unused_variables)]
@@ -216,7 +318,9 @@ impl MQAdminExt for DefaultMQAdminExtImpl {
         broker_addr: CheetahString,
         properties: HashMap<CheetahString, CheetahString>,
     ) -> rocketmq_error::RocketMQResult<()> {
-        todo!()
+        self.mq_client_api_impl()
+            .update_broker_config(&broker_addr, &properties, self.timeout_millis())
+            .await
     }
 
     async fn get_broker_config(
@@ -314,14 +418,18 @@ impl MQAdminExt for DefaultMQAdminExtImpl {
     }
 
     async fn fetch_all_topic_list(&self) -> rocketmq_error::RocketMQResult<TopicList> {
-        todo!()
+        self.mq_client_api_impl()
+            .get_topic_list_from_name_server(self.timeout_millis())
+            .await
     }
 
     async fn fetch_topics_by_cluster(
         &self,
         cluster_name: CheetahString,
     ) -> rocketmq_error::RocketMQResult<TopicList> {
-        todo!()
+        self.mq_client_api_impl()
+            .get_topics_by_cluster(&cluster_name, self.timeout_millis())
+            .await
     }
 
     async fn fetch_broker_runtime_stats(
@@ -339,24 +447,91 @@ impl MQAdminExt for DefaultMQAdminExtImpl {
         broker_addr: Option<CheetahString>,
         timeout_millis: Option<u64>,
     ) -> rocketmq_error::RocketMQResult<ConsumeStats> {
-        todo!()
+        let timeout_millis = timeout_millis.unwrap_or(self.timeout_millis() * 3);
+        let broker_addrs = match broker_addr {
+            Some(broker_addr) => vec![broker_addr],
+            None => {
+                self.consumer_broker_addrs(&consumer_group, topic.as_ref(), cluster_name.as_ref())
+                    .await?
+            }
+        };
+        let mut result = ConsumeStats::new();
+        for addr in broker_addrs.iter() {
+            let consume_stats = self
+                .mq_client_api_impl()
+                .get_consume_stats(addr, &consumer_group, topic.as_deref(), timeout_millis)
+                .await?;
+            result.offset_table.extend(consume_stats.offset_table);
+            result.consume_tps += consume_stats.consume_tps;
+            if let Some(pop_stats) = consume_stats.pop_stats {
+                result
+                    .pop_stats
+                    .get_or_insert_with(PopStats::default)
+                    .merge(pop_stats);
+            }
+        }
+        if result.offset_table.is_empty() {
+            return mq_client_err!(
+                ResponseCode::ConsumerNotOnline,
+                "Not found the consumer group consume stats, because return offset table is \
+                 empty, maybe the consumer not consume any message"
+                    .to_string()
+            );
+        }
+        Ok(result)
+    }
+
+    async fn examine_pop_stats(
+        &self,
+        consumer_group: CheetahString,
+        topic: Option<CheetahString>,
+        broker_addr: Option<CheetahString>,
+    ) -> rocketmq_error::RocketMQResult<PopStats> {
+        let broker_addrs = match broker_addr {
+            Some(broker_addr) => vec![broker_addr],
+            None => {
+                self.consumer_broker_addrs(&consumer_group, topic.as_ref(), None)
+                    .await?
+            }
+        };
+        let mut result = PopStats::default();
+        for addr in broker_addrs.iter() {
+            let pop_stats = self
+                .mq_client_api_impl()
+                .get_pop_stats(
+                    addr,
+                    &consumer_group,
+                    topic.as_deref(),
+                    self.timeout_millis(),
+                )
+                .await?;
+            result.merge(pop_stats);
+        }
+        Ok(result)
+    }
+
+    async fn examine_topic_disk_usage(
+        &self,
+        broker_addr: CheetahString,
+        topic: Option<CheetahString>,
+    ) -> rocketmq_error::RocketMQResult<TopicDiskUsage> {
+        self.mq_client_api_impl()
+            .get_topic_disk_usage(&broker_addr, topic.as_deref(), self.timeout_millis())
+            .await
     }
 
     async fn examine_broker_cluster_info(&self) -> rocketmq_error::RocketMQResult<ClusterInfo> {
-        todo!()
+        self.mq_client_api_impl()
+            .get_broker_cluster_info(self.timeout_millis())
+            .await
     }
 
     async fn examine_topic_route_info(
         &self,
         topic: CheetahString,
     ) -> rocketmq_error::RocketMQResult<Option<TopicRouteData>> {
-        self.client_instance
-            .as_ref()
-            .unwrap()
-            .mq_client_api_impl
-            .as_ref()
-            .unwrap()
-            .get_topic_route_info_from_name_server(&topic, self.timeout_millis.as_millis() as u64)
+        self.mq_client_api_impl()
+            .get_topic_route_info_from_name_server(&topic, self.timeout_millis())
             .await
     }
 
@@ -377,7 +552,9 @@ impl MQAdminExt for DefaultMQAdminExtImpl {
     }
 
     async fn get_name_server_address_list(&self) -> Vec<CheetahString> {
-        todo!()
+        self.mq_client_api_impl()
+            .get_name_server_address_list()
+            .to_vec()
     }
 
     async fn wipe_write_perm_of_broker(
@@ -425,7 +602,22 @@ impl MQAdminExt for DefaultMQAdminExtImpl {
         topic_name: CheetahString,
         cluster_name: CheetahString,
     ) -> rocketmq_error::RocketMQResult<()> {
-        todo!()
+        let cluster_info = self.examine_broker_cluster_info().await?;
+        let Some(broker_addrs) = master_and_slave_addrs_of_cluster(&cluster_info, &cluster_name)
+        else {
+            return mq_client_err!(format!(
+                "cluster {cluster_name} does not exist on the connected name servers"
+            ));
+        };
+        self.delete_topic_in_broker(broker_addrs, topic_name.clone())
+            .await?;
+        let name_servers = self
+            .get_name_server_address_list()
+            .await
+            .into_iter()
+            .collect();
+        self.delete_topic_in_name_server(name_servers, Some(cluster_name), topic_name)
+            .await
     }
 
     async fn delete_topic_in_broker(
@@ -433,7 +625,12 @@ impl MQAdminExt for DefaultMQAdminExtImpl {
         addrs: HashSet<CheetahString>,
         topic: CheetahString,
     ) -> rocketmq_error::RocketMQResult<()> {
-        todo!()
+        for addr in addrs.iter() {
+            self.mq_client_api_impl()
+                .delete_topic_in_broker(addr, &topic, self.timeout_millis())
+                .await?;
+        }
+        Ok(())
     }
 
     async fn delete_topic_in_name_server(
@@ -442,7 +639,22 @@ impl MQAdminExt for DefaultMQAdminExtImpl {
         cluster_name: Option<CheetahString>,
         topic: CheetahString,
     ) -> rocketmq_error::RocketMQResult<()> {
-        todo!()
+        let addrs = if addrs.is_empty() {
+            self.get_name_server_address_list().await
+        } else {
+            addrs.into_iter().collect()
+        };
+        for addr in addrs.iter() {
+            self.mq_client_api_impl()
+                .delete_topic_in_name_server(
+                    addr,
+                    cluster_name.as_deref(),
+                    &topic,
+                    self.timeout_millis(),
+                )
+                .await?;
+        }
+        Ok(())
     }
 
     async fn delete_subscription_group(
@@ -23,6 +23,8 @@ use rocketmq_common::common::config::TopicConfig;
 use rocketmq_common::common::message::message_enum::MessageRequestMode;
 use rocketmq_common::common::message::message_queue::MessageQueue;
 use rocketmq_remoting::protocol::admin::consume_stats::ConsumeStats;
+use rocketmq_remoting::protocol::admin::pop_stats::PopStats;
+use rocketmq_remoting::protocol::admin::topic_disk_usage::TopicDiskUsage;
 use rocketmq_remoting::protocol::admin::topic_stats_table::TopicStatsTable;
 use rocketmq_remoting::protocol::body::broker_body::cluster_info::ClusterInfo;
 use rocketmq_remoting::protocol::body::consume_message_directly_result::ConsumeMessageDirectlyResult;
@@ -156,6 +158,23 @@ pub trait MQAdminExtLocal: Sync {
         timeout_millis: Option<u64>,
     ) -> rocketmq_error::RocketMQResult<ConsumeStats>;
 
+    /// Queries the pop consumption progress of `consumer_group`: messages popped but not acked
+    /// per queue and the revive backlog of the brokers.
+    async fn examine_pop_stats(
+        &self,
+        consumer_group: CheetahString,
+        topic: Option<CheetahString>,
+        broker_addr: Option<CheetahString>,
+    ) -> rocketmq_error::RocketMQResult<PopStats>;
+
+    /// Queries the bytes the messages of `topic` take on the broker at `broker_addr`, of all the
+    /// topics of the broker when `topic` is `None`.
+    async fn examine_topic_disk_usage(
+        &self,
+        broker_addr: CheetahString,
+        topic: Option<CheetahString>,
+    ) -> rocketmq_error::RocketMQResult<TopicDiskUsage>;
+
     /*async fn check_rocksdb_cq_write_progress(
         &self,
         broker_addr: CheetahString,
@@ -27,6 +27,7 @@ use rocketmq_common::utils::name_server_address_utils::NAMESRV_ENDPOINT_PATTERN;
 use rocketmq_common::utils::network_util::NetworkUtil;
 use rocketmq_common::utils::string_utils::StringUtils;
 use rocketmq_common::TimeUtils::get_current_nano;
+use rocketmq_remoting::auth::acl_client_rpc_hook::SessionCredentials;
 use rocketmq_remoting::protocol::namespace_util::NamespaceUtil;
 use rocketmq_remoting::protocol::request_type::RequestType;
 use rocketmq_remoting::protocol::LanguageCode;
@@ -65,6 +66,11 @@ pub struct ClientConfig {
     pub enable_heartbeat_channel_event_listener: bool,
     pub enable_trace: bool,
     pub trace_topic: Option<CheetahString>,
+    // Ask brokers to compress the message bodies of pull and pop responses, they are inflated
+    // while decoding. Brokers only do it when compression is enabled on their side.
+    pub accept_compressed_pull_body: bool,
+    // Added to every request sent, for brokers and name servers authenticating their requests.
+    pub session_credentials: Option<SessionCredentials>,
 }
 
 impl Default for ClientConfig {
@@ -136,6 +142,8 @@ impl ClientConfig {
             enable_heartbeat_channel_event_listener: true,
             enable_trace: false,
             trace_topic: None,
+            accept_compressed_pull_body: false,
+            session_credentials: None,
         }
     }
 }
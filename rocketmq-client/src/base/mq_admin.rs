@@ -18,10 +18,14 @@ use std::collections::HashMap;
 
 use rocketmq_common::common::message::message_ext::MessageExt;
 use rocketmq_common::common::message::message_queue::MessageQueue;
+use rocketmq_error::mq_client_err;
 
 use crate::base::query_result::QueryResult;
 
 /// Trait defining administrative operations for a Message Queue (MQ).
+///
+/// Every operation defaults to a "not supported" error, so types that only need to satisfy
+/// the bound (for example consumer mocks in unit tests) can implement it with an empty block.
 #[allow(dead_code)]
 pub trait MQAdmin {
     /// Creates a new topic.
@@ -40,7 +44,9 @@ pub trait MQAdmin {
         new_topic: &str,
         queue_num: i32,
         attributes: HashMap<String, String>,
-    ) -> rocketmq_error::RocketMQResult<()>;
+    ) -> rocketmq_error::RocketMQResult<()> {
+        mq_client_err!("create_topic is not supported")
+    }
 
     /// Creates a new topic with a system flag.
     ///
@@ -60,7 +66,9 @@ pub trait MQAdmin {
         queue_num: i32,
         topic_sys_flag: i32,
         attributes: HashMap<String, String>,
-    ) -> rocketmq_error::RocketMQResult<()>;
+    ) -> rocketmq_error::RocketMQResult<()> {
+        mq_client_err!("create_topic_with_flag is not supported")
+    }
 
     /// Searches for the offset of a message in a queue at a given timestamp.
     ///
@@ -74,7 +82,9 @@ pub trait MQAdmin {
         &self,
         mq: &MessageQueue,
         timestamp: u64,
-    ) -> rocketmq_error::RocketMQResult<i64>;
+    ) -> rocketmq_error::RocketMQResult<i64> {
+        mq_client_err!("search_offset is not supported")
+    }
 
     /// Retrieves the maximum offset of a message in a queue.
     ///
@@ -83,7 +93,9 @@ pub trait MQAdmin {
     ///
     /// # Returns
     /// A `Result` containing the maximum offset, or an error.
-    fn max_offset(&self, mq: &MessageQueue) -> rocketmq_error::RocketMQResult<i64>;
+    fn max_offset(&self, mq: &MessageQueue) -> rocketmq_error::RocketMQResult<i64> {
+        mq_client_err!("max_offset is not supported")
+    }
 
     /// Retrieves the minimum offset of a message in a queue.
     ///
@@ -92,7 +104,9 @@ pub trait MQAdmin {
     ///
     /// # Returns
     /// A `Result` containing the minimum offset, or an error.
-    fn min_offset(&self, mq: &MessageQueue) -> rocketmq_error::RocketMQResult<i64>;
+    fn min_offset(&self, mq: &MessageQueue) -> rocketmq_error::RocketMQResult<i64> {
+        mq_client_err!("min_offset is not supported")
+    }
 
     /// Retrieves the earliest message store time in a queue.
     ///
@@ -101,7 +115,9 @@ pub trait MQAdmin {
     ///
     /// # Returns
     /// A `Result` containing the earliest message store time, or an error.
-    fn earliest_msg_store_time(&self, mq: &MessageQueue) -> rocketmq_error::RocketMQResult<u64>;
+    fn earliest_msg_store_time(&self, mq: &MessageQueue) -> rocketmq_error::RocketMQResult<u64> {
+        mq_client_err!("earliest_msg_store_time is not supported")
+    }
 
     /// Queries messages in a topic by key within a time range.
     ///
@@ -121,7 +137,9 @@ pub trait MQAdmin {
         max_num: i32,
         begin: u64,
         end: u64,
-    ) -> rocketmq_error::RocketMQResult<QueryResult>;
+    ) -> rocketmq_error::RocketMQResult<QueryResult> {
+        mq_client_err!("query_message is not supported")
+    }
 
     /// Views a message by its ID in a topic.
     ///
@@ -131,6 +149,11 @@ pub trait MQAdmin {
     ///
     /// # Returns
     /// A `Result` containing the `MessageExt` if found, or an error.
-    fn view_message(&self, topic: &str, msg_id: &str)
-        -> rocketmq_error::RocketMQResult<MessageExt>;
+    fn view_message(
+        &self,
+        topic: &str,
+        msg_id: &str,
+    ) -> rocketmq_error::RocketMQResult<MessageExt> {
+        mq_client_err!("view_message is not supported")
+    }
 }
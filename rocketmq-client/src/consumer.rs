@@ -15,10 +15,12 @@
  * limitations under the License.
  */
 pub(crate) mod ack_callback;
-pub(crate) mod ack_result;
-pub(crate) mod ack_status;
+pub mod ack_result;
+pub mod ack_status;
 pub mod allocate_message_queue_strategy;
 pub(crate) mod consumer_impl;
+pub mod default_mq_pull_consumer;
+pub mod default_mq_pull_consumer_builder;
 pub mod default_mq_push_consumer;
 pub mod default_mq_push_consumer_builder;
 pub mod listener;
@@ -36,5 +38,6 @@ pub(crate) mod pull_callback;
 pub mod pull_result;
 pub mod pull_status;
 pub mod rebalance_strategy;
+pub mod receipt_handle;
 pub(crate) mod store;
 pub mod topic_message_queue_change_listener;
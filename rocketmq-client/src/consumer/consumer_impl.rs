@@ -21,6 +21,7 @@ pub(crate) mod consume_message_orderly_service;
 pub(crate) mod consume_message_pop_concurrently_service;
 pub(crate) mod consume_message_pop_orderly_service;
 pub(crate) mod consume_message_service;
+pub(crate) mod default_mq_pull_consumer_impl;
 pub(crate) mod default_mq_push_consumer_impl;
 pub(crate) mod message_request;
 pub(crate) mod pop_process_queue;
@@ -31,6 +32,7 @@ pub(crate) mod pull_message_service;
 pub(crate) mod pull_request;
 pub(crate) mod pull_request_ext;
 pub(crate) mod re_balance;
+pub(crate) mod receipt_handle_renewal_service;
 
 pub(crate) static PULL_MAX_IDLE_TIME: Lazy<u64> = Lazy::new(|| {
     std::env::var("rocketmq.client.pull.pullMaxIdleTime")
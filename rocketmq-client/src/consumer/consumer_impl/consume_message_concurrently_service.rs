@@ -400,6 +400,7 @@ impl ConsumeRequest {
                         .get_namespace()
                         .unwrap_or_default(),
                     access_channel: Default::default(),
+                    consume_rt: 0,
                 });
                 default_mqpush_consumer_impl.execute_hook_before(&mut consume_message_context);
             }
@@ -451,6 +452,7 @@ impl ConsumeRequest {
             cmc.status = status.unwrap().to_string().into();
             cmc.success = status.unwrap() == ConsumeConcurrentlyStatus::ConsumeSuccess;
             cmc.access_channel = Some(default_mqpush_consumer_impl.client_config.access_channel);
+            cmc.consume_rt = consume_rt;
             default_mqpush_consumer_impl.execute_hook_after(&mut consume_message_context);
         }
 
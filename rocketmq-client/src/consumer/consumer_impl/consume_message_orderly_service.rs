@@ -593,6 +593,7 @@ impl ConsumeRequest {
                             .get_namespace()
                             .unwrap_or_default(),
                         access_channel: Default::default(),
+                        consume_rt: 0,
                     });
                     default_mqpush_consumer_impl.execute_hook_before(&mut consume_message_context);
                 }
@@ -665,10 +666,11 @@ impl ConsumeRequest {
                 }
                 if default_mqpush_consumer_impl.has_hook() {
                     let status = *status.as_ref().unwrap();
-                    consume_message_context.as_mut().unwrap().success = status
-                        == ConsumeOrderlyStatus::Success
+                    let cmc = consume_message_context.as_mut().unwrap();
+                    cmc.success = status == ConsumeOrderlyStatus::Success
                         || status == ConsumeOrderlyStatus::Commit;
-                    consume_message_context.as_mut().unwrap().status = status.to_string().into();
+                    cmc.status = status.to_string().into();
+                    cmc.consume_rt = consume_rt;
                     default_mqpush_consumer_impl.execute_hook_after(&mut consume_message_context);
                 }
                 let continue_consume = consume_message_orderly_service_inner
@@ -47,6 +47,7 @@ use crate::consumer::listener::consume_concurrently_context::ConsumeConcurrently
 use crate::consumer::listener::consume_concurrently_status::ConsumeConcurrentlyStatus;
 use crate::consumer::listener::consume_return_type::ConsumeReturnType;
 use crate::consumer::listener::message_listener_concurrently::ArcBoxMessageListenerConcurrently;
+use crate::consumer::receipt_handle::ReceiptHandle;
 use crate::hook::consume_message_context::ConsumeMessageContext;
 
 pub struct ConsumeMessagePopConcurrentlyService {
@@ -408,6 +409,17 @@ impl ConsumeRequest {
             self.default_mqpush_consumer_impl.as_ref().unwrap().clone();
         default_mqpush_consumer_impl
             .reset_retry_and_namespace(&mut self.msgs, self.consumer_group.as_str());
+        let renewal_service = default_mqpush_consumer_impl
+            .receipt_handle_renewal_service
+            .clone();
+        let receipt_handles = self
+            .msgs
+            .iter()
+            .map(|msg| ReceiptHandle::from_message(msg.as_ref()).ok())
+            .collect::<Vec<Option<ReceiptHandle>>>();
+        for receipt_handle in receipt_handles.iter().flatten() {
+            renewal_service.add(receipt_handle.clone());
+        }
         let mut consume_message_context = None;
 
         let begin_timestamp = Instant::now();
@@ -439,6 +451,7 @@ impl ConsumeRequest {
                     .get_namespace()
                     .unwrap_or_default(),
                 access_channel: Default::default(),
+                consume_rt: 0,
             });
             default_mqpush_consumer_impl.execute_hook_before(&mut consume_message_context);
         }
@@ -455,6 +468,16 @@ impl ConsumeRequest {
                 has_exception = true;
             }
         }
+        // ack and change invisible time with the latest handle, renewals replace the popped one
+        for (msg, receipt_handle) in self.msgs.iter().zip(receipt_handles) {
+            if let Some(latest) = receipt_handle.and_then(|handle| renewal_service.remove(&handle))
+            {
+                msg.mut_from_ref().put_property(
+                    CheetahString::from_static_str(MessageConst::PROPERTY_POP_CK),
+                    latest.encode(),
+                );
+            }
+        }
         let consume_rt = begin_timestamp.elapsed().as_millis() as u64;
         if status.is_none() {
             if has_exception {
@@ -488,6 +511,7 @@ impl ConsumeRequest {
             cmc.status = status.unwrap().to_string().into();
             cmc.success = status.unwrap() == ConsumeConcurrentlyStatus::ConsumeSuccess;
             cmc.access_channel = Some(default_mqpush_consumer_impl.client_config.access_channel);
+            cmc.consume_rt = consume_rt;
             default_mqpush_consumer_impl.execute_hook_after(&mut consume_message_context);
         }
 
@@ -14,10 +14,8 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
-use std::collections::HashSet;
-use std::hash::Hash;
-use std::hash::Hasher;
 use std::sync::Arc;
+use std::time::Duration;
 use std::time::Instant;
 
 use cheetah_string::CheetahString;
@@ -27,6 +25,7 @@ use rocketmq_remoting::protocol::body::cm_result::CMResult;
 use rocketmq_remoting::protocol::body::consume_message_directly_result::ConsumeMessageDirectlyResult;
 use rocketmq_runtime::RocketMQRuntime;
 use rocketmq_rust::ArcMut;
+use tracing::error;
 use tracing::info;
 use tracing::warn;
 
@@ -40,6 +39,7 @@ use crate::consumer::listener::consume_orderly_context::ConsumeOrderlyContext;
 use crate::consumer::listener::consume_orderly_status::ConsumeOrderlyStatus;
 use crate::consumer::listener::message_listener_orderly::ArcBoxMessageListenerOrderly;
 use crate::consumer::message_queue_lock::MessageQueueLock;
+use crate::consumer::receipt_handle::ReceiptHandle;
 
 pub struct ConsumeMessagePopOrderlyService {
     pub(crate) default_mqpush_consumer_impl: Option<ArcMut<DefaultMQPushConsumerImpl>>,
@@ -48,9 +48,7 @@ pub struct ConsumeMessagePopOrderlyService {
     pub(crate) consumer_group: CheetahString,
     pub(crate) message_listener: ArcBoxMessageListenerOrderly,
     pub(crate) consume_runtime: RocketMQRuntime,
-    pub(self) consume_request_set: HashSet<ConsumeRequest>,
     pub(crate) message_queue_lock: MessageQueueLock,
-    pub(crate) consume_request_lock: MessageQueueLock,
 }
 
 impl ConsumeMessagePopOrderlyService {
@@ -73,32 +71,7 @@ impl ConsumeMessagePopOrderlyService {
                 consume_thread as usize,
                 consumer_group_tag.as_str(),
             ),
-            consume_request_set: Default::default(),
             message_queue_lock: Default::default(),
-            consume_request_lock: Default::default(),
-        }
-    }
-
-    fn remove_consume_request(&mut self, request: &ConsumeRequest) {
-        self.consume_request_set.remove(request);
-    }
-
-    async fn submit_consume_request(
-        &mut self,
-        this: ArcMut<Self>,
-        mut request: ConsumeRequest,
-        force: bool,
-    ) {
-        let lock = self
-            .consume_request_lock
-            .fetch_lock_object_with_sharding_key(&request.message_queue, request.sharding_key_index)
-            .await;
-        let _lock = lock.lock().await;
-        let is_new_req = self.consume_request_set.insert(request.clone());
-        if is_new_req || force {
-            self.consume_runtime.get_handle().spawn(async move {
-                request.run(this).await;
-            });
         }
     }
 }
@@ -185,59 +158,151 @@ impl ConsumeMessageServiceTrait for ConsumeMessagePopOrderlyService {
         process_queue: &PopProcessQueue,
         message_queue: &MessageQueue,
     ) {
-        ConsumeRequest::new(Arc::new(process_queue.clone()), message_queue.clone());
+        let mut request = ConsumeRequest::new(
+            msgs.into_iter().map(ArcMut::new).collect(),
+            process_queue.clone(),
+            message_queue.clone(),
+        );
+        self.consume_runtime.get_handle().spawn(async move {
+            request.run(this).await;
+        });
     }
 }
 
-#[derive(Clone)]
+/// The messages of one pop of an orderly queue. They are consumed in order, and the queue is not
+/// popped again before they are acked.
 struct ConsumeRequest {
-    process_queue: Arc<PopProcessQueue>,
+    msgs: Vec<ArcMut<MessageExt>>,
+    process_queue: PopProcessQueue,
     message_queue: MessageQueue,
-    sharding_key_index: i32,
 }
 
 impl ConsumeRequest {
-    pub fn new(process_queue: Arc<PopProcessQueue>, message_queue: MessageQueue) -> Self {
+    pub fn new(
+        msgs: Vec<ArcMut<MessageExt>>,
+        process_queue: PopProcessQueue,
+        message_queue: MessageQueue,
+    ) -> Self {
         Self {
+            msgs,
             process_queue,
             message_queue,
-            sharding_key_index: 0,
         }
     }
 
-    pub async fn run(
-        &mut self,
-        mut consume_message_pop_orderly_service: ArcMut<ConsumeMessagePopOrderlyService>,
-    ) {
+    #[allow(deprecated)]
+    pub async fn run(&mut self, service: ArcMut<ConsumeMessagePopOrderlyService>) {
         if self.process_queue.is_dropped() {
             warn!(
                 "run, message queue not be able to consume, because it's dropped. {}",
                 self.message_queue
             );
-            consume_message_pop_orderly_service.remove_consume_request(self);
+            self.process_queue.dec_found_msg(self.msgs.len());
             return;
         }
-        let _ = consume_message_pop_orderly_service
+        let lock = service
             .message_queue_lock
-            .fetch_lock_object_with_sharding_key(&self.message_queue, self.sharding_key_index)
+            .fetch_lock_object(&self.message_queue)
             .await;
-    }
-}
-
-impl PartialEq for ConsumeRequest {
-    fn eq(&self, other: &Self) -> bool {
-        self.sharding_key_index == other.sharding_key_index
-            && Arc::ptr_eq(&self.process_queue, &other.process_queue)
-            || (self.message_queue.eq(&other.message_queue))
-    }
-}
+        let _lock = lock.lock().await;
 
-impl Eq for ConsumeRequest {}
+        let mut default_mqpush_consumer_impl = service
+            .default_mqpush_consumer_impl
+            .as_ref()
+            .unwrap()
+            .clone();
+        default_mqpush_consumer_impl
+            .reset_retry_and_namespace(&mut self.msgs, service.consumer_group.as_str());
+        let renewal_service = default_mqpush_consumer_impl
+            .receipt_handle_renewal_service
+            .clone();
+        let mut receipt_handles = Vec::with_capacity(self.msgs.len());
+        let process_queue = &self.process_queue;
+        self.msgs
+            .retain(|msg| match ReceiptHandle::from_message(msg.as_ref()) {
+                Ok(receipt_handle) => {
+                    renewal_service.add(receipt_handle.clone());
+                    receipt_handles.push(receipt_handle);
+                    true
+                }
+                Err(e) => {
+                    warn!(
+                        "consume orderly, skip message without receipt handle: {}",
+                        e
+                    );
+                    process_queue.ack();
+                    false
+                }
+            });
 
-impl Hash for ConsumeRequest {
-    fn hash<H: Hasher>(&self, state: &mut H) {
-        self.sharding_key_index.hash(state);
-        self.process_queue.as_ref().hash(state);
-        self.message_queue.hash(state);
+        let batch_size = service
+            .consumer_config
+            .consume_message_batch_max_size
+            .max(1) as usize;
+        let mut all_acked = true;
+        let mut consumed = 0;
+        while consumed < self.msgs.len() {
+            let end = (consumed + batch_size).min(self.msgs.len());
+            let mut context = ConsumeOrderlyContext::new(self.message_queue.clone());
+            let msgs = self.msgs[consumed..end]
+                .iter()
+                .map(|msg| msg.as_ref())
+                .collect::<Vec<&MessageExt>>();
+            let status = service
+                .message_listener
+                .consume_message(&msgs, &mut context);
+            let suspend_millis = match status {
+                Ok(ConsumeOrderlyStatus::Success) | Ok(ConsumeOrderlyStatus::Commit) => None,
+                Ok(_) => Some(context.get_suspend_current_queue_time_millis()),
+                Err(e) => {
+                    warn!(
+                        "consumeMessage exception: {}, Group: {} Msgs: {} MQ: {}",
+                        e,
+                        service.consumer_group,
+                        msgs.len(),
+                        self.message_queue
+                    );
+                    Some(-1)
+                }
+            };
+            if let Some(suspend_millis) = suspend_millis {
+                let suspend = if suspend_millis > 0 {
+                    suspend_millis as u64
+                } else {
+                    service.consumer_config.suspend_current_queue_time_millis
+                };
+                // the failed batch and the ones after it are popped again by the same attempt
+                // once they are visible again
+                for receipt_handle in &receipt_handles[consumed..] {
+                    let latest = renewal_service
+                        .remove(receipt_handle)
+                        .unwrap_or_else(|| receipt_handle.clone());
+                    if let Err(e) = default_mqpush_consumer_impl
+                        .change_invisible_duration(&latest, Duration::from_millis(suspend))
+                        .await
+                    {
+                        error!("change invisible time of {} failed: {}", latest, e);
+                    }
+                }
+                self.process_queue.dec_found_msg(self.msgs.len() - consumed);
+                self.process_queue.finish_attempt();
+                return;
+            }
+            for receipt_handle in &receipt_handles[consumed..end] {
+                let latest = renewal_service
+                    .remove(receipt_handle)
+                    .unwrap_or_else(|| receipt_handle.clone());
+                if let Err(e) = default_mqpush_consumer_impl.ack_message(&latest).await {
+                    error!("ack message {} failed: {}", latest, e);
+                    all_acked = false;
+                }
+                self.process_queue.ack();
+            }
+            consumed = end;
+        }
+        // messages whose ack failed are popped again, they have to be popped by the same attempt
+        if all_acked {
+            self.process_queue.finish_attempt();
+        }
     }
 }
@@ -0,0 +1,378 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use cheetah_string::CheetahString;
+use rocketmq_common::common::base::service_state::ServiceState;
+use rocketmq_common::common::consumer::consume_from_where::ConsumeFromWhere;
+use rocketmq_common::common::message::message_queue::MessageQueue;
+use rocketmq_common::common::sys_flag::pull_sys_flag::PullSysFlag;
+use rocketmq_common::common::FAQUrl;
+use rocketmq_error::mq_client_err;
+use rocketmq_remoting::protocol::body::consumer_running_info::ConsumerRunningInfo;
+use rocketmq_remoting::protocol::filter::filter_api::FilterAPI;
+use rocketmq_remoting::protocol::heartbeat::consume_type::ConsumeType;
+use rocketmq_remoting::protocol::heartbeat::message_model::MessageModel;
+use rocketmq_remoting::protocol::heartbeat::subscription_data::SubscriptionData;
+use rocketmq_remoting::protocol::subscription::group_retry_policy::GroupRetryPolicy;
+use rocketmq_remoting::runtime::RPCHook;
+use rocketmq_rust::ArcMut;
+use tokio::sync::Mutex;
+use tracing::info;
+use tracing::warn;
+
+use crate::base::client_config::ClientConfig;
+use crate::consumer::consumer_impl::pull_api_wrapper::PullAPIWrapper;
+use crate::consumer::consumer_impl::pull_request_ext::PullResultExt;
+use crate::consumer::default_mq_pull_consumer::PullConsumerConfig;
+use crate::consumer::mq_consumer_inner::MQConsumerInner;
+use crate::consumer::mq_consumer_inner::MQConsumerInnerImpl;
+use crate::consumer::pull_callback::PullCallback;
+use crate::consumer::pull_result::PullResult;
+use crate::consumer::store::local_file_offset_store::LocalFileOffsetStore;
+use crate::consumer::store::offset_store::OffsetStore;
+use crate::consumer::store::read_offset_type::ReadOffsetType;
+use crate::consumer::store::remote_broker_offset_store::RemoteBrokerOffsetStore;
+use crate::factory::mq_client_instance::MQClientInstance;
+use crate::implementation::communication_mode::CommunicationMode;
+use crate::implementation::mq_client_manager::MQClientManager;
+
+/// Pull requests of the pull consumer are always synchronous, the callback is never invoked.
+struct SyncPullCallback;
+
+impl PullCallback for SyncPullCallback {
+    async fn on_success(&mut self, _pull_result: PullResultExt) {}
+
+    fn on_exception(&mut self, _e: Box<dyn std::error::Error + Send>) {}
+}
+
+pub(crate) struct DefaultMQPullConsumerImpl {
+    client_config: ClientConfig,
+    consumer_config: ArcMut<PullConsumerConfig>,
+    rpc_hook: Option<Arc<Box<dyn RPCHook>>>,
+    service_state: ArcMut<ServiceState>,
+    client_instance: Option<ArcMut<MQClientInstance>>,
+    pull_api_wrapper: Option<ArcMut<PullAPIWrapper>>,
+    offset_store: Option<ArcMut<OffsetStore>>,
+    // queues whose consume offset was updated, persisted by the client instance and on shutdown
+    offset_queues: Arc<Mutex<HashSet<MessageQueue>>>,
+}
+
+impl DefaultMQPullConsumerImpl {
+    pub fn new(
+        client_config: ClientConfig,
+        consumer_config: ArcMut<PullConsumerConfig>,
+        rpc_hook: Option<Arc<Box<dyn RPCHook>>>,
+    ) -> Self {
+        DefaultMQPullConsumerImpl {
+            client_config,
+            consumer_config,
+            rpc_hook,
+            service_state: ArcMut::new(ServiceState::CreateJust),
+            client_instance: None,
+            pull_api_wrapper: None,
+            offset_store: None,
+            offset_queues: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+
+    /// Starts the consumer, `this` is the consumer itself registered to the client instance so
+    /// its group is part of the heartbeats and its offsets are persisted periodically.
+    pub async fn start(&mut self, this: ArcMut<Self>) -> rocketmq_error::RocketMQResult<()> {
+        match *self.service_state {
+            ServiceState::CreateJust => {
+                *self.service_state = ServiceState::StartFailed;
+                if self.consumer_config.consumer_group.is_empty() {
+                    return mq_client_err!("consumerGroup is empty");
+                }
+                if self.consumer_config.message_model == MessageModel::Clustering {
+                    self.client_config.change_instance_name_to_pid();
+                }
+                let mut client_instance = MQClientManager::get_instance()
+                    .get_or_create_mq_client_instance(
+                        self.client_config.clone(),
+                        self.rpc_hook.clone(),
+                    );
+                self.pull_api_wrapper = Some(ArcMut::new(PullAPIWrapper::new(
+                    client_instance.clone(),
+                    self.consumer_config.consumer_group.clone(),
+                    self.consumer_config.unit_mode,
+                )));
+                let offset_store = match self.consumer_config.message_model {
+                    MessageModel::Broadcasting => {
+                        OffsetStore::new_with_local(LocalFileOffsetStore::new(
+                            client_instance.clone(),
+                            self.consumer_config.consumer_group.clone(),
+                        ))
+                    }
+                    MessageModel::Clustering => {
+                        OffsetStore::new_with_remote(RemoteBrokerOffsetStore::new(
+                            client_instance.clone(),
+                            self.consumer_config.consumer_group.clone(),
+                        ))
+                    }
+                };
+                offset_store.load().await?;
+                self.offset_store = Some(ArcMut::new(offset_store));
+
+                let register_ok = client_instance
+                    .register_consumer(
+                        &self.consumer_config.consumer_group,
+                        MQConsumerInnerImpl {
+                            default_mqpush_consumer_impl: None,
+                            default_mqpull_consumer_impl: Some(this),
+                        },
+                    )
+                    .await;
+                if !register_ok {
+                    *self.service_state = ServiceState::CreateJust;
+                    return mq_client_err!(format!(
+                        "The consumer group[{}] has been created before, specify another name \
+                         please. {}",
+                        self.consumer_config.consumer_group,
+                        FAQUrl::suggest_todo(FAQUrl::GROUP_NAME_DUPLICATE_URL)
+                    ));
+                }
+                let cloned = client_instance.clone();
+                client_instance.start(cloned).await?;
+                self.client_instance = Some(client_instance);
+                info!(
+                    "the pull consumer [{}] start OK, message_model={}",
+                    self.consumer_config.consumer_group, self.consumer_config.message_model
+                );
+                *self.service_state = ServiceState::Running;
+                Ok(())
+            }
+            ServiceState::Running => mq_client_err!("The PullConsumer service state is Running"),
+            ServiceState::ShutdownAlready => {
+                mq_client_err!("The PullConsumer service state is ShutdownAlready")
+            }
+            ServiceState::StartFailed => mq_client_err!(format!(
+                "The PullConsumer service state not OK, maybe started once,{:?},{}",
+                *self.service_state,
+                FAQUrl::suggest_todo(FAQUrl::CLIENT_SERVICE_NOT_OK)
+            )),
+        }
+    }
+
+    pub async fn shutdown(&mut self) {
+        match *self.service_state {
+            ServiceState::Running => {
+                MQConsumerInner::persist_consumer_offset(self).await;
+                if let Some(client_instance) = self.client_instance.as_mut() {
+                    client_instance
+                        .unregister_consumer(self.consumer_config.consumer_group.clone())
+                        .await;
+                    client_instance.shutdown().await;
+                }
+                *self.service_state = ServiceState::ShutdownAlready;
+                info!(
+                    "the pull consumer [{}] shutdown OK",
+                    self.consumer_config.consumer_group
+                );
+            }
+            _ => {
+                warn!(
+                    "the pull consumer [{}] is not running, do nothing",
+                    self.consumer_config.consumer_group
+                );
+            }
+        }
+    }
+
+    fn make_sure_state_ok(&self) -> rocketmq_error::RocketMQResult<()> {
+        if *self.service_state != ServiceState::Running {
+            return mq_client_err!(format!(
+                "The consumer service state not OK, {:?} {}",
+                *self.service_state,
+                FAQUrl::suggest_todo(FAQUrl::CLIENT_SERVICE_NOT_OK)
+            ));
+        }
+        Ok(())
+    }
+
+    pub async fn fetch_subscribe_message_queues(
+        &mut self,
+        topic: &CheetahString,
+    ) -> rocketmq_error::RocketMQResult<Vec<MessageQueue>> {
+        self.make_sure_state_ok()?;
+        self.client_instance
+            .as_mut()
+            .unwrap()
+            .mq_admin_impl
+            .fetch_subscribe_message_queues(topic)
+            .await
+    }
+
+    pub async fn pull(
+        &mut self,
+        mq: &MessageQueue,
+        sub_expression: &CheetahString,
+        offset: i64,
+        max_nums: i32,
+        block: bool,
+        timeout_millis: u64,
+    ) -> rocketmq_error::RocketMQResult<PullResult> {
+        self.make_sure_state_ok()?;
+        if offset < 0 {
+            return mq_client_err!("offset < 0");
+        }
+        if max_nums <= 0 {
+            return mq_client_err!("maxNums <= 0");
+        }
+        let subscription_data =
+            match FilterAPI::build_subscription_data(mq.get_topic_cs(), sub_expression) {
+                Ok(subscription_data) => subscription_data,
+                Err(e) => return mq_client_err!(format!("parse subscription error: {}", e)),
+            };
+        let timeout_millis = if block {
+            self.consumer_config.consumer_timeout_millis_when_suspend
+        } else {
+            timeout_millis
+        };
+        let sys_flag = PullSysFlag::build_sys_flag(false, block, true, false);
+        let pull_api_wrapper = self.pull_api_wrapper.as_mut().unwrap();
+        let result = pull_api_wrapper
+            .pull_kernel_impl(
+                mq,
+                subscription_data.sub_string.clone(),
+                subscription_data.expression_type.clone(),
+                0,
+                offset,
+                max_nums,
+                i32::MAX,
+                sys_flag as i32,
+                0,
+                self.consumer_config.broker_suspend_max_time_millis,
+                timeout_millis,
+                CommunicationMode::Sync,
+                SyncPullCallback,
+            )
+            .await;
+        match result {
+            Ok(Some(mut pull_result_ext)) => {
+                pull_api_wrapper.process_pull_result(mq, &mut pull_result_ext, &subscription_data);
+                Ok(pull_result_ext.pull_result)
+            }
+            Ok(None) => mq_client_err!(format!("pull message from {} returns nothing", mq)),
+            Err(e) => {
+                // the broker of the queue may be gone, pick up the current route for the next pull
+                self.client_instance
+                    .as_mut()
+                    .unwrap()
+                    .update_topic_route_info_from_name_server_topic(mq.get_topic_cs())
+                    .await;
+                Err(e)
+            }
+        }
+    }
+
+    pub async fn update_consume_offset(
+        &mut self,
+        mq: &MessageQueue,
+        offset: i64,
+    ) -> rocketmq_error::RocketMQResult<()> {
+        self.make_sure_state_ok()?;
+        self.offset_store
+            .as_ref()
+            .unwrap()
+            .update_offset(mq, offset, false)
+            .await;
+        self.offset_queues.lock().await.insert(mq.clone());
+        Ok(())
+    }
+
+    pub async fn fetch_consume_offset(
+        &mut self,
+        mq: &MessageQueue,
+        from_store: bool,
+    ) -> rocketmq_error::RocketMQResult<i64> {
+        self.make_sure_state_ok()?;
+        let read_offset_type = if from_store {
+            ReadOffsetType::ReadFromStore
+        } else {
+            ReadOffsetType::MemoryFirstThenStore
+        };
+        Ok(self
+            .offset_store
+            .as_ref()
+            .unwrap()
+            .read_offset(mq, read_offset_type)
+            .await)
+    }
+}
+
+impl MQConsumerInner for DefaultMQPullConsumerImpl {
+    fn group_name(&self) -> CheetahString {
+        self.consumer_config.consumer_group.clone()
+    }
+
+    fn message_model(&self) -> MessageModel {
+        self.consumer_config.message_model
+    }
+
+    fn consume_type(&self) -> ConsumeType {
+        ConsumeType::ConsumeActively
+    }
+
+    fn consume_from_where(&self) -> ConsumeFromWhere {
+        ConsumeFromWhere::ConsumeFromLastOffset
+    }
+
+    /// The queues to pull are chosen by the application, no topic is subscribed.
+    fn subscriptions(&self) -> HashSet<SubscriptionData> {
+        HashSet::new()
+    }
+
+    fn do_rebalance(&self) {}
+
+    async fn try_rebalance(&self) -> rocketmq_error::RocketMQResult<bool> {
+        Ok(true)
+    }
+
+    async fn persist_consumer_offset(&self) {
+        if let Some(offset_store) = self.offset_store.as_ref() {
+            let mqs = self.offset_queues.lock().await.clone();
+            offset_store.mut_from_ref().persist_all(&mqs).await;
+        }
+    }
+
+    async fn update_topic_subscribe_info(
+        &self,
+        _topic: CheetahString,
+        _info: &HashSet<MessageQueue>,
+    ) -> bool {
+        false
+    }
+
+    async fn is_subscribe_topic_need_update(&self, _topic: &str) -> bool {
+        false
+    }
+
+    fn is_unit_mode(&self) -> bool {
+        self.consumer_config.unit_mode
+    }
+
+    fn group_retry_policy(&self) -> Option<GroupRetryPolicy> {
+        None
+    }
+
+    fn consumer_running_info(&self) -> ConsumerRunningInfo {
+        ConsumerRunningInfo {}
+    }
+}
@@ -21,11 +21,13 @@ use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
 use std::thread;
+use std::time::Duration;
 use std::time::Instant;
 
 use cheetah_string::CheetahString;
 use rocketmq_common::common::base::service_state::ServiceState;
 use rocketmq_common::common::consumer::consume_from_where::ConsumeFromWhere;
+use rocketmq_common::common::filter::expression_type::ExpressionType;
 use rocketmq_common::common::key_builder::KeyBuilder;
 use rocketmq_common::common::message::message_ext::MessageExt;
 use rocketmq_common::common::message::message_queue::MessageQueue;
@@ -50,6 +52,7 @@ use rocketmq_remoting::protocol::heartbeat::consume_type::ConsumeType;
 use rocketmq_remoting::protocol::heartbeat::message_model::MessageModel;
 use rocketmq_remoting::protocol::heartbeat::subscription_data::SubscriptionData;
 use rocketmq_remoting::protocol::namespace_util::NamespaceUtil;
+use rocketmq_remoting::protocol::subscription::group_retry_policy::GroupRetryPolicy;
 use rocketmq_remoting::rpc::rpc_request_header::RpcRequestHeader;
 use rocketmq_remoting::rpc::topic_request_header::TopicRequestHeader;
 use rocketmq_remoting::runtime::RPCHook;
@@ -64,6 +67,7 @@ use crate::base::client_config::ClientConfig;
 use crate::base::validators::Validators;
 use crate::consumer::ack_callback::AckCallback;
 use crate::consumer::ack_result::AckResult;
+use crate::consumer::ack_status::AckStatus;
 use crate::consumer::consumer_impl::consume_message_concurrently_service::ConsumeMessageConcurrentlyService;
 use crate::consumer::consumer_impl::consume_message_orderly_service::ConsumeMessageOrderlyService;
 use crate::consumer::consumer_impl::consume_message_pop_concurrently_service::ConsumeMessagePopConcurrentlyService;
@@ -75,14 +79,17 @@ use crate::consumer::consumer_impl::pull_api_wrapper::PullAPIWrapper;
 use crate::consumer::consumer_impl::pull_request::PullRequest;
 use crate::consumer::consumer_impl::re_balance::rebalance_push_impl::RebalancePushImpl;
 use crate::consumer::consumer_impl::re_balance::Rebalance;
+use crate::consumer::consumer_impl::receipt_handle_renewal_service::ReceiptHandleRenewalService;
 use crate::consumer::default_mq_push_consumer::ConsumerConfig;
 use crate::consumer::listener::message_listener::MessageListener;
+use crate::consumer::message_selector::MessageSelector;
 use crate::consumer::mq_consumer_inner::MQConsumerInner;
 use crate::consumer::mq_consumer_inner::MQConsumerInnerImpl;
 use crate::consumer::pop_callback::DefaultPopCallback;
 use crate::consumer::pop_result::PopResult;
 use crate::consumer::pop_status::PopStatus;
 use crate::consumer::pull_callback::DefaultPullCallback;
+use crate::consumer::receipt_handle::ReceiptHandle;
 use crate::consumer::store::local_file_offset_store::LocalFileOffsetStore;
 use crate::consumer::store::offset_store::OffsetStore;
 use crate::consumer::store::read_offset_type::ReadOffsetType;
@@ -139,6 +146,7 @@ pub struct DefaultMQPushConsumerImpl {
             >,
         >,
     >,
+    pub(crate) receipt_handle_renewal_service: ReceiptHandleRenewalService,
     queue_flow_control_times: u64,
     queue_max_span_flow_control_times: u64,
     pub(crate) pop_delay_level: Arc<[i32; 16]>,
@@ -169,6 +177,7 @@ impl DefaultMQPushConsumerImpl {
             offset_store: None,
             consume_message_service: None,
             consume_message_pop_service: None,
+            receipt_handle_renewal_service: ReceiptHandleRenewalService::default(),
             queue_flow_control_times: 0,
             queue_max_span_flow_control_times: 0,
             pop_delay_level: Arc::new([
@@ -349,16 +358,24 @@ impl DefaultMQPushConsumerImpl {
                 {
                     consume_message_orderly_service.start();
                 }
+                if let Some(default_mqpush_consumer_impl) =
+                    self.default_mqpush_consumer_impl.clone()
+                {
+                    self.receipt_handle_renewal_service
+                        .start(default_mqpush_consumer_impl);
+                }
                 self.client_instance
                     .as_mut()
                     .unwrap()
                     .register_consumer(
                         self.consumer_config.consumer_group.as_ref(),
                         MQConsumerInnerImpl {
-                            default_mqpush_consumer_impl: self
-                                .default_mqpush_consumer_impl
-                                .clone()
-                                .expect("default_mqpush_consumer_impl is None"),
+                            default_mqpush_consumer_impl: Some(
+                                self.default_mqpush_consumer_impl
+                                    .clone()
+                                    .expect("default_mqpush_consumer_impl is None"),
+                            ),
+                            default_mqpull_consumer_impl: None,
                         },
                     )
                     .await;
@@ -416,6 +433,7 @@ impl DefaultMQPushConsumerImpl {
                         .shutdown(await_terminate_millis)
                         .await;
                 }
+                self.receipt_handle_renewal_service.shutdown();
                 self.persist_consumer_offset().await;
                 let client = self.client_instance.as_mut().unwrap();
                 client
@@ -671,8 +689,13 @@ impl DefaultMQPushConsumerImpl {
         Ok(())
     }
 
-    pub fn register_consume_message_hook(&mut self, hook: impl ConsumeMessageHook) {
-        unimplemented!("registerConsumeMessageHook");
+    pub fn register_consume_message_hook(
+        &mut self,
+        hook: impl ConsumeMessageHook + Send + Sync + 'static,
+    ) {
+        info!("register consumeMessageHook Hook, {}", hook.hook_name());
+        self.consume_message_hook_list
+            .push(Arc::new(Box::new(hook)));
     }
 
     pub fn register_message_listener(&mut self, message_listener: Option<ArcMut<MessageListener>>) {
@@ -700,6 +723,45 @@ impl DefaultMQPushConsumerImpl {
         Ok(())
     }
 
+    /// Subscribes to `topic` with the messages chosen by `selector`, all messages when it is
+    /// `None`. A running consumer asks the brokers whether they support an SQL92 selector.
+    pub async fn subscribe_with_selector(
+        &mut self,
+        topic: CheetahString,
+        selector: Option<MessageSelector>,
+    ) -> rocketmq_error::RocketMQResult<()> {
+        let Some(selector) = selector else {
+            return self
+                .subscribe(
+                    topic,
+                    CheetahString::from_static_str(SubscriptionData::SUB_ALL),
+                )
+                .await;
+        };
+        let subscription_data = match FilterAPI::build(
+            &topic,
+            &CheetahString::from(selector.get_expression()),
+            Some(CheetahString::from(selector.get_expression_type())),
+        ) {
+            Ok(subscription_data) => subscription_data,
+            Err(e) => return mq_client_err!(format!("buildSubscriptionData exception, {}", e)),
+        };
+        let tag_type =
+            ExpressionType::is_tag_type(Some(subscription_data.expression_type.as_str()));
+        self.rebalance_impl
+            .put_subscription_data(topic, subscription_data)
+            .await;
+        if let Some(ref mut client_instance) = self.client_instance {
+            if !tag_type && *self.service_state == ServiceState::Running {
+                client_instance.check_client_in_broker().await?;
+            }
+            client_instance
+                .send_heartbeat_to_all_broker_with_lock()
+                .await;
+        }
+        Ok(())
+    }
+
     pub async fn execute_pull_request_immediately(&mut self, pull_request: PullRequest) {
         self.client_instance
             .as_mut()
@@ -788,6 +850,16 @@ impl DefaultMQPushConsumerImpl {
             return;
         }
 
+        // the messages of an orderly queue popped before have to be acked first, the broker
+        // blocks the queue for a new attempt until then
+        if self.consume_orderly && process_queue.get_wai_ack_msg_count() > 0 {
+            self.execute_pop_request_later(
+                pop_request,
+                PULL_TIME_DELAY_MILLS_WHEN_CACHE_FLOW_CONTROL,
+            );
+            return;
+        }
+
         let subscription_data = self
             .rebalance_impl
             .get_subscription_inner()
@@ -810,6 +882,9 @@ impl DefaultMQPushConsumerImpl {
         let mq = pop_request.get_message_queue().clone();
         let consumer_group = pop_request.get_consumer_group().clone();
         let init_mode = pop_request.get_init_mode();
+        let attempt_id = self
+            .consume_orderly
+            .then(|| pop_request.get_pop_process_queue().attempt_id());
         let this = self.default_mqpush_consumer_impl.clone().unwrap();
 
         match self
@@ -830,7 +905,8 @@ impl DefaultMQPushConsumerImpl {
                 },
                 true,
                 init_mode,
-                false,
+                self.consume_orderly,
+                attempt_id,
                 expression_type,
                 sub_string,
             )
@@ -1102,6 +1178,11 @@ impl DefaultMQPushConsumerImpl {
                 begin_timestamp.elapsed().as_millis(),
                 e
             );
+            if let Some(client_instance) = self.client_instance.as_mut() {
+                client_instance
+                    .update_topic_route_info_from_name_server_topic(message_queue.get_topic_cs())
+                    .await;
+            }
             self.execute_pull_request_later(
                 pull_request,
                 self.pull_time_delay_mills_when_exception,
@@ -1480,6 +1561,133 @@ impl DefaultMQPushConsumerImpl {
         let broker_name =
             CheetahString::from_string(ExtraInfoUtil::get_broker_name(extra_info_strs.as_slice())?);
         let queue_id = ExtraInfoUtil::get_queue_id(extra_info_strs.as_slice())?;
+        let broker_addr = self
+            .find_pop_broker_addr(topic, &broker_name, queue_id)
+            .await?;
+        let request_header = ChangeInvisibleTimeRequestHeader {
+            consumer_group: consumer_group.clone(),
+            topic: CheetahString::from_string(ExtraInfoUtil::get_real_topic(
+                extra_info_strs.as_slice(),
+                topic,
+                consumer_group,
+            )?),
+            queue_id,
+            extra_info: extra_info.clone(),
+            offset: ExtraInfoUtil::get_queue_offset(extra_info_strs.as_slice())?,
+            invisible_time: invisible_time as i64,
+            topic_request_header: Some(TopicRequestHeader {
+                rpc_request_header: Some(RpcRequestHeader {
+                    broker_name: Some(broker_name.clone()),
+                    ..Default::default()
+                }),
+                lo: None,
+            }),
+        };
+        self.client_instance
+            .as_mut()
+            .unwrap()
+            .get_mq_client_api_impl()
+            .change_invisible_time_async(
+                &broker_name,
+                &broker_addr,
+                request_header,
+                ASYNC_TIMEOUT,
+                callback,
+            )
+            .await
+    }
+
+    /// Acks the popped message identified by `receipt_handle`.
+    pub(crate) async fn ack_message(
+        &mut self,
+        receipt_handle: &ReceiptHandle,
+    ) -> rocketmq_error::RocketMQResult<AckResult> {
+        let consumer_group = self.consumer_config.consumer_group().clone();
+        let broker_name = receipt_handle.broker_name().clone();
+        let broker_addr = self
+            .find_pop_broker_addr(
+                receipt_handle.topic(),
+                &broker_name,
+                receipt_handle.queue_id(),
+            )
+            .await?;
+        let request_header = AckMessageRequestHeader {
+            topic: receipt_handle.real_topic(&consumer_group)?,
+            consumer_group,
+            queue_id: receipt_handle.queue_id(),
+            extra_info: receipt_handle.encode(),
+            offset: receipt_handle.queue_offset(),
+            topic_request_header: Some(TopicRequestHeader {
+                rpc_request_header: Some(RpcRequestHeader {
+                    broker_name: Some(broker_name),
+                    ..Default::default()
+                }),
+                lo: None,
+            }),
+        };
+        self.client_instance
+            .as_mut()
+            .unwrap()
+            .get_mq_client_api_impl()
+            .ack_message(&broker_addr, request_header, ASYNC_TIMEOUT)
+            .await
+    }
+
+    /// Keeps the popped message identified by `receipt_handle` invisible for `duration` from
+    /// now on and returns the handle that replaces it.
+    pub(crate) async fn change_invisible_duration(
+        &mut self,
+        receipt_handle: &ReceiptHandle,
+        duration: Duration,
+    ) -> rocketmq_error::RocketMQResult<ReceiptHandle> {
+        let consumer_group = self.consumer_config.consumer_group().clone();
+        let broker_name = receipt_handle.broker_name().clone();
+        let broker_addr = self
+            .find_pop_broker_addr(
+                receipt_handle.topic(),
+                &broker_name,
+                receipt_handle.queue_id(),
+            )
+            .await?;
+        let request_header = ChangeInvisibleTimeRequestHeader {
+            topic: receipt_handle.real_topic(&consumer_group)?,
+            consumer_group,
+            queue_id: receipt_handle.queue_id(),
+            extra_info: receipt_handle.encode(),
+            offset: receipt_handle.queue_offset(),
+            invisible_time: duration.as_millis() as i64,
+            topic_request_header: Some(TopicRequestHeader {
+                rpc_request_header: Some(RpcRequestHeader {
+                    broker_name: Some(broker_name.clone()),
+                    ..Default::default()
+                }),
+                lo: None,
+            }),
+        };
+        let ack_result = self
+            .client_instance
+            .as_mut()
+            .unwrap()
+            .get_mq_client_api_impl()
+            .change_invisible_time(&broker_name, &broker_addr, request_header, ASYNC_TIMEOUT)
+            .await?;
+        if ack_result.status() != AckStatus::Ok {
+            return mq_client_err!(format!(
+                "change invisible time failed, receipt handle {} does not exist",
+                receipt_handle
+            ));
+        }
+        ReceiptHandle::decode(receipt_handle.topic().clone(), ack_result.extra_info())
+    }
+
+    /// Resolves the master address of the broker that popped a message, looking the route up
+    /// again when it is unknown.
+    async fn find_pop_broker_addr(
+        &mut self,
+        topic: &CheetahString,
+        broker_name: &CheetahString,
+        queue_id: i32,
+    ) -> rocketmq_error::RocketMQResult<CheetahString> {
         let des_broker_name = if !broker_name.is_empty()
             && broker_name.starts_with(mix_all::LOGICAL_QUEUE_MOCK_BROKER_PREFIX)
         {
@@ -1510,43 +1718,13 @@ impl DefaultMQPushConsumerImpl {
                 .find_broker_address_in_subscribe(&des_broker_name, mix_all::MASTER_ID, true)
                 .await;
         }
-
-        if find_broker_result.is_none() {
-            return mq_client_err!(format!(
+        match find_broker_result {
+            Some(find_broker_result) => Ok(find_broker_result.broker_addr),
+            None => mq_client_err!(format!(
                 "The broker[{}] not exist",
                 des_broker_name.as_str()
-            ));
+            )),
         }
-        let request_header = ChangeInvisibleTimeRequestHeader {
-            consumer_group: consumer_group.clone(),
-            topic: CheetahString::from_string(ExtraInfoUtil::get_real_topic(
-                extra_info_strs.as_slice(),
-                topic,
-                consumer_group,
-            )?),
-            queue_id,
-            extra_info: extra_info.clone(),
-            offset: ExtraInfoUtil::get_queue_offset(extra_info_strs.as_slice())?,
-            invisible_time: invisible_time as i64,
-            topic_request_header: Some(TopicRequestHeader {
-                rpc_request_header: Some(RpcRequestHeader {
-                    broker_name: Some(broker_name.clone()),
-                    ..Default::default()
-                }),
-                lo: None,
-            }),
-        };
-        let find_broker_result = find_broker_result.unwrap();
-        client_instance
-            .get_mq_client_api_impl()
-            .change_invisible_time_async(
-                &broker_name,
-                &find_broker_result.broker_addr,
-                request_header,
-                ASYNC_TIMEOUT,
-                callback,
-            )
-            .await
     }
 }
 
@@ -1627,18 +1805,21 @@ impl MQConsumerInner for DefaultMQPushConsumerImpl {
         &self,
         topic: CheetahString,
         info: &HashSet<MessageQueue>,
-    ) {
+    ) -> bool {
         let sub_table = self.rebalance_impl.get_subscription_inner();
         let sub_table_inner = sub_table.read().await;
-        if sub_table_inner.contains_key(&topic) {
-            let mut guard = self
-                .rebalance_impl
-                .rebalance_impl_inner
-                .topic_subscribe_info_table
-                .write()
-                .await;
-            guard.insert(topic, info.clone());
+        if !sub_table_inner.contains_key(&topic) {
+            return false;
         }
+        let mut guard = self
+            .rebalance_impl
+            .rebalance_impl_inner
+            .topic_subscribe_info_table
+            .write()
+            .await;
+        guard
+            .insert(topic, info.clone())
+            .map_or(true, |old| old != *info)
     }
 
     async fn is_subscribe_topic_need_update(&self, topic: &str) -> bool {
@@ -1661,6 +1842,10 @@ impl MQConsumerInner for DefaultMQPushConsumerImpl {
         self.consumer_config.unit_mode
     }
 
+    fn group_retry_policy(&self) -> Option<GroupRetryPolicy> {
+        self.consumer_config.group_retry_policy.clone()
+    }
+
     fn consumer_running_info(&self) -> ConsumerRunningInfo {
         todo!()
     }
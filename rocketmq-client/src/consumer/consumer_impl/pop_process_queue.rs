@@ -24,6 +24,9 @@ use std::sync::atomic::Ordering::Acquire;
 use std::sync::atomic::Ordering::Relaxed;
 use std::sync::Arc;
 
+use cheetah_string::CheetahString;
+use parking_lot::Mutex;
+use rocketmq_common::common::message::message_client_id_setter::MessageClientIDSetter;
 use rocketmq_common::TimeUtils::get_current_millis;
 use rocketmq_remoting::protocol::body::pop_process_queue_info::PopProcessQueueInfo;
 
@@ -34,6 +37,9 @@ pub(crate) struct PopProcessQueue {
     last_pop_timestamp: Arc<AtomicU64>,
     wait_ack_counter: Arc<AtomicUsize>,
     dropped: Arc<AtomicBool>,
+    // attempt id of the orderly pop in progress, the broker hands the messages of an unacked
+    // attempt to the same attempt id only
+    attempt_id: Arc<Mutex<Option<CheetahString>>>,
 }
 
 impl Hash for PopProcessQueue {
@@ -51,6 +57,7 @@ impl Default for PopProcessQueue {
             last_pop_timestamp: Arc::new(AtomicU64::new(get_current_millis())),
             wait_ack_counter: Arc::new(AtomicUsize::new(0)),
             dropped: Arc::new(AtomicBool::new(false)),
+            attempt_id: Arc::new(Mutex::new(None)),
         }
     }
 }
@@ -102,6 +109,22 @@ impl PopProcessQueue {
         self.dropped.store(dropped, Ordering::Release);
     }
 
+    /// Returns the attempt id of the orderly pop in progress, starting a new attempt when there is
+    /// none. Pops retried before the attempt is finished reuse its id.
+    pub(crate) fn attempt_id(&self) -> CheetahString {
+        self.attempt_id
+            .lock()
+            .get_or_insert_with(|| {
+                CheetahString::from_string(MessageClientIDSetter::create_uniq_id())
+            })
+            .clone()
+    }
+
+    /// Finishes the orderly pop in progress once its messages are acked or made invisible again.
+    pub(crate) fn finish_attempt(&self) {
+        self.attempt_id.lock().take();
+    }
+
     #[inline]
     pub(crate) fn fill_pop_process_queue_info(&self, info: &mut PopProcessQueueInfo) {
         info.set_wait_ack_count(self.get_wai_ack_msg_count() as i32);
@@ -179,6 +202,17 @@ mod tests {
         assert!(queue.is_dropped());
     }
 
+    #[test]
+    fn attempt_id_is_kept_until_the_attempt_is_finished() {
+        let queue = PopProcessQueue::new();
+        let attempt_id = queue.attempt_id();
+        assert!(!attempt_id.is_empty());
+        assert_eq!(queue.clone().attempt_id(), attempt_id);
+
+        queue.finish_attempt();
+        assert_ne!(queue.attempt_id(), attempt_id);
+    }
+
     #[test]
     fn pop_process_queue_detects_pull_expired() {
         let queue = PopProcessQueue::new();
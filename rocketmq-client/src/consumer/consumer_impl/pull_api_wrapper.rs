@@ -389,6 +389,7 @@ impl PullAPIWrapper {
         poll: bool,
         init_mode: i32,
         order: bool,
+        attempt_id: Option<CheetahString>,
         expression_type: CheetahString,
         expression: CheetahString,
     ) -> rocketmq_error::RocketMQResult<()>
@@ -419,6 +420,7 @@ impl PullAPIWrapper {
                 exp_type: Some(expression_type),
                 exp: Some(expression),
                 order: Some(order),
+                attempt_id,
                 topic_request_header: Some(TopicRequestHeader {
                     lo: None,
                     rpc: Some(RpcRequestHeader {
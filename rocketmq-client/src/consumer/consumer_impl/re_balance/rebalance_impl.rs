@@ -64,6 +64,9 @@ pub(crate) struct RebalanceImpl<R> {
     pub(crate) sub_rebalance_impl: Option<WeakArcMut<R>>,
     pub(crate) topic_broker_rebalance: Arc<RwLock<HashMap<CheetahString, CheetahString>>>,
     pub(crate) topic_client_rebalance: Arc<RwLock<HashMap<CheetahString, CheetahString>>>,
+    /// Request mode the broker suggested per topic through heartbeat responses.
+    pub(crate) topic_request_mode_suggestion:
+        Arc<parking_lot::RwLock<HashMap<CheetahString, MessageRequestMode>>>,
 }
 
 impl<R> RebalanceImpl<R>
@@ -88,9 +91,66 @@ where
             sub_rebalance_impl: None,
             topic_broker_rebalance: Arc::new(RwLock::new(HashMap::with_capacity(64))),
             topic_client_rebalance: Arc::new(RwLock::new(HashMap::with_capacity(64))),
+            topic_request_mode_suggestion: Arc::new(parking_lot::RwLock::new(
+                HashMap::with_capacity(64),
+            )),
         }
     }
 
+    /// Returns the request mode the broker last suggested for `topic`, if any.
+    #[inline]
+    pub fn suggested_request_mode(&self, topic: &str) -> Option<MessageRequestMode> {
+        self.topic_request_mode_suggestion
+            .read()
+            .get(topic)
+            .copied()
+    }
+
+    /// Records the request mode suggested by the broker for `topic`.
+    ///
+    /// When the suggestion differs from the previous one, the cached rebalance decision for the
+    /// topic is discarded so the next rebalance picks the new strategy. Switching to `Pull` also
+    /// drops the pop process queues of the topic, since client-side rebalance never revisits them.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the suggestion changed and a rebalance should be triggered.
+    pub async fn apply_message_request_mode(
+        &self,
+        topic: &CheetahString,
+        mode: MessageRequestMode,
+    ) -> bool {
+        let previous = self
+            .topic_request_mode_suggestion
+            .write()
+            .insert(topic.clone(), mode);
+        if previous == Some(mode) {
+            return false;
+        }
+        match mode {
+            MessageRequestMode::Pop => {
+                self.topic_client_rebalance.write().await.remove(topic);
+            }
+            MessageRequestMode::Pull => {
+                self.topic_broker_rebalance.write().await.remove(topic);
+                let mut pop_process_queue_table = self.pop_process_queue_table.write().await;
+                pop_process_queue_table.retain(|mq, pq| {
+                    if mq.get_topic() == topic.as_str() {
+                        pq.set_dropped(true);
+                        false
+                    } else {
+                        true
+                    }
+                });
+            }
+        }
+        info!(
+            "{:?}, broker suggests {:?} request mode for topic {}",
+            self.consumer_group, mode, topic
+        );
+        true
+    }
+
     #[inline]
     pub async fn put_subscription_data(
         &self,
@@ -262,6 +322,9 @@ where
         topic_client_rebalance.retain(|topic, _| sub_table.contains_key(topic));
         let mut topic_broker_rebalance = self.topic_broker_rebalance.write().await;
         topic_broker_rebalance.retain(|topic, _| sub_table.contains_key(topic));
+        self.topic_request_mode_suggestion
+            .write()
+            .retain(|topic, _| sub_table.contains_key(topic));
     }
 
     /// Retrieves the rebalance result from the broker for a given topic.
@@ -1149,3 +1212,71 @@ where
         result
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::consumer::consumer_impl::re_balance::rebalance_push_impl::RebalancePushImpl;
+
+    #[tokio::test]
+    async fn apply_message_request_mode_switches_cached_strategy() {
+        let rebalance_impl: RebalanceImpl<RebalancePushImpl> =
+            RebalanceImpl::new(None, None, None, None);
+        let topic = CheetahString::from_static_str("TopicTest");
+        rebalance_impl
+            .topic_client_rebalance
+            .write()
+            .await
+            .insert(topic.clone(), topic.clone());
+
+        assert!(
+            rebalance_impl
+                .apply_message_request_mode(&topic, MessageRequestMode::Pop)
+                .await
+        );
+        assert!(!rebalance_impl
+            .topic_client_rebalance
+            .read()
+            .await
+            .contains_key(&topic));
+        assert_eq!(
+            rebalance_impl.suggested_request_mode(&topic),
+            Some(MessageRequestMode::Pop)
+        );
+        assert!(
+            !rebalance_impl
+                .apply_message_request_mode(&topic, MessageRequestMode::Pop)
+                .await
+        );
+
+        let mq = MessageQueue::from_parts(topic.clone(), "broker-a", 0);
+        let pq = Arc::new(PopProcessQueue::default());
+        rebalance_impl
+            .pop_process_queue_table
+            .write()
+            .await
+            .insert(mq, pq.clone());
+        rebalance_impl
+            .topic_broker_rebalance
+            .write()
+            .await
+            .insert(topic.clone(), topic.clone());
+
+        assert!(
+            rebalance_impl
+                .apply_message_request_mode(&topic, MessageRequestMode::Pull)
+                .await
+        );
+        assert!(rebalance_impl
+            .pop_process_queue_table
+            .read()
+            .await
+            .is_empty());
+        assert!(rebalance_impl
+            .topic_broker_rebalance
+            .read()
+            .await
+            .is_empty());
+        assert!(pq.is_dropped());
+    }
+}
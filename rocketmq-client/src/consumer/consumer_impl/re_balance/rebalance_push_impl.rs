@@ -23,6 +23,7 @@ use cheetah_string::CheetahString;
 use once_cell::sync::Lazy;
 use rocketmq_common::common::constant::consume_init_mode::ConsumeInitMode;
 use rocketmq_common::common::consumer::consume_from_where::ConsumeFromWhere;
+use rocketmq_common::common::message::message_enum::MessageRequestMode;
 use rocketmq_common::common::message::message_queue::MessageQueue;
 use rocketmq_common::common::mix_all;
 use rocketmq_common::utils::util_all;
@@ -496,6 +497,9 @@ impl Rebalance for RebalancePushImpl {
     /// 2. If the message model is set to `Broadcasting`.
     /// 3. If the consumer is configured to consume messages in order.
     ///
+    /// Outside of the first two modes, a request mode suggested by the broker takes precedence
+    /// over `client_rebalance`.
+    ///
     /// # Arguments
     ///
     /// * `topic` - A string slice that holds the name of the topic.
@@ -507,13 +511,20 @@ impl Rebalance for RebalancePushImpl {
     fn client_rebalance(&mut self, topic: &str) -> bool {
         //Pop message mode, order message consumer not implement, it's use
         // ConsumeMessageOrderlyService to consume
-        self.consumer_config.client_rebalance
-            || self.rebalance_impl_inner.message_model.unwrap() == MessageModel::Broadcasting
+        if self.rebalance_impl_inner.message_model.unwrap() == MessageModel::Broadcasting
             || self
                 .default_mqpush_consumer_impl
                 .as_ref()
                 .unwrap()
                 .is_consume_orderly()
+        {
+            return true;
+        }
+        match self.rebalance_impl_inner.suggested_request_mode(topic) {
+            Some(MessageRequestMode::Pop) => false,
+            Some(MessageRequestMode::Pull) => true,
+            None => self.consumer_config.client_rebalance,
+        }
     }
 
     fn destroy(&mut self) {
@@ -0,0 +1,222 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use cheetah_string::CheetahString;
+use parking_lot::Mutex;
+use rocketmq_common::TimeUtils::get_current_millis;
+use rocketmq_rust::ArcMut;
+use rocketmq_rust::Shutdown;
+use tracing::info;
+use tracing::warn;
+
+use crate::consumer::consumer_impl::default_mq_push_consumer_impl::DefaultMQPushConsumerImpl;
+use crate::consumer::receipt_handle::ReceiptHandle;
+
+const RENEW_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Identifies a popped message across renewals, the receipt handle itself changes every time.
+type RenewalKey = (CheetahString, CheetahString, CheetahString, i32, i64);
+
+struct RenewalEntry {
+    receipt_handle: ReceiptHandle,
+    track_timestamp: u64,
+}
+
+/// Keeps popped messages invisible while they are still being consumed.
+///
+/// Every tracked receipt handle is renewed with CHANGE_INVISIBLE_TIME shortly before the message
+/// would become visible again, so a slow consumer does not get the message delivered twice.
+/// Renewing stops once a message has been tracked for `pop_max_renew_millis`.
+#[derive(Clone, Default)]
+pub(crate) struct ReceiptHandleRenewalService {
+    handles: Arc<Mutex<HashMap<RenewalKey, RenewalEntry>>>,
+    tx_shutdown: Option<tokio::sync::broadcast::Sender<()>>,
+}
+
+impl ReceiptHandleRenewalService {
+    pub fn start(&mut self, default_mqpush_consumer_impl: ArcMut<DefaultMQPushConsumerImpl>) {
+        let (mut shutdown, tx_shutdown) = Shutdown::new(1);
+        self.tx_shutdown = Some(tx_shutdown);
+        let this = self.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(RENEW_CHECK_INTERVAL);
+            loop {
+                tokio::select! {
+                    _ = shutdown.recv() => {
+                        info!("ReceiptHandleRenewalService shutdown");
+                        return;
+                    }
+                    _ = interval.tick() => {
+                        this.renew(default_mqpush_consumer_impl.clone()).await;
+                    }
+                }
+            }
+        });
+    }
+
+    pub fn shutdown(&mut self) {
+        if let Some(tx_shutdown) = self.tx_shutdown.take() {
+            let _ = tx_shutdown.send(());
+        }
+        self.handles.lock().clear();
+    }
+
+    /// Starts renewing `receipt_handle` until it is removed again.
+    pub fn add(&self, receipt_handle: ReceiptHandle) {
+        self.handles.lock().insert(
+            renewal_key(&receipt_handle),
+            RenewalEntry {
+                receipt_handle,
+                track_timestamp: get_current_millis(),
+            },
+        );
+    }
+
+    /// Stops renewing and returns the latest handle of the message, the one to ack it with.
+    pub fn remove(&self, receipt_handle: &ReceiptHandle) -> Option<ReceiptHandle> {
+        self.handles
+            .lock()
+            .remove(&renewal_key(receipt_handle))
+            .map(|entry| entry.receipt_handle)
+    }
+
+    async fn renew(&self, mut default_mqpush_consumer_impl: ArcMut<DefaultMQPushConsumerImpl>) {
+        let consumer_config = default_mqpush_consumer_impl.consumer_config.clone();
+        let due = self.take_due(
+            get_current_millis(),
+            consumer_config.pop_renew_ahead_millis,
+            consumer_config.pop_max_renew_millis,
+        );
+        let invisible_duration = Duration::from_millis(consumer_config.pop_invisible_time);
+        for receipt_handle in due {
+            match default_mqpush_consumer_impl
+                .change_invisible_duration(&receipt_handle, invisible_duration)
+                .await
+            {
+                Ok(renewed) => self.replace(&receipt_handle, renewed),
+                Err(e) => {
+                    warn!("renew receipt handle {} failed: {}", receipt_handle, e);
+                    if receipt_handle.is_expired() {
+                        self.remove(&receipt_handle);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Collects the handles that expire within `renew_ahead_millis` and drops the ones tracked
+    /// longer than `max_renew_millis`, their messages will be delivered again.
+    fn take_due(
+        &self,
+        now: u64,
+        renew_ahead_millis: u64,
+        max_renew_millis: u64,
+    ) -> Vec<ReceiptHandle> {
+        let mut due = Vec::new();
+        self.handles.lock().retain(|_, entry| {
+            if now.saturating_sub(entry.track_timestamp) >= max_renew_millis {
+                warn!(
+                    "stop renewing receipt handle {}, tracked for more than {}ms",
+                    entry.receipt_handle, max_renew_millis
+                );
+                return false;
+            }
+            if entry.receipt_handle.next_visible_time() - (now as i64) <= renew_ahead_millis as i64
+            {
+                due.push(entry.receipt_handle.clone());
+            }
+            true
+        });
+        due
+    }
+
+    /// Swaps in the renewed handle unless the message was removed while renewing.
+    fn replace(&self, receipt_handle: &ReceiptHandle, renewed: ReceiptHandle) {
+        if let Some(entry) = self.handles.lock().get_mut(&renewal_key(receipt_handle)) {
+            entry.receipt_handle = renewed;
+        }
+    }
+}
+
+fn renewal_key(receipt_handle: &ReceiptHandle) -> RenewalKey {
+    (
+        receipt_handle.topic().clone(),
+        receipt_handle.retry().clone(),
+        receipt_handle.broker_name().clone(),
+        receipt_handle.queue_id(),
+        receipt_handle.queue_offset(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn receipt_handle(pop_time: u64, invisible_time: u64, queue_offset: i64) -> ReceiptHandle {
+        ReceiptHandle::decode(
+            "TopicTest",
+            &format!(
+                "0 {} {} 0 0 broker-a 1 {}",
+                pop_time, invisible_time, queue_offset
+            ),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn remove_returns_latest_handle() {
+        let service = ReceiptHandleRenewalService::default();
+        let handle = receipt_handle(1_000, 60_000, 7);
+        service.add(handle.clone());
+        let renewed = receipt_handle(50_000, 60_000, 7);
+        service.replace(&handle, renewed.clone());
+        assert_eq!(service.remove(&handle), Some(renewed));
+        assert_eq!(service.remove(&handle), None);
+    }
+
+    #[test]
+    fn replace_ignores_removed_handles() {
+        let service = ReceiptHandleRenewalService::default();
+        let handle = receipt_handle(1_000, 60_000, 7);
+        service.replace(&handle, receipt_handle(50_000, 60_000, 7));
+        assert!(service.handles.lock().is_empty());
+    }
+
+    #[test]
+    fn take_due_selects_handles_close_to_expiry() {
+        let service = ReceiptHandleRenewalService::default();
+        let now = get_current_millis();
+        service.add(receipt_handle(now - 55_000, 60_000, 1));
+        service.add(receipt_handle(now, 60_000, 2));
+        let due = service.take_due(now, 10_000, u64::MAX);
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].queue_offset(), 1);
+        assert_eq!(service.handles.lock().len(), 2);
+    }
+
+    #[test]
+    fn take_due_stops_renewing_after_max_renew_time() {
+        let service = ReceiptHandleRenewalService::default();
+        let now = get_current_millis();
+        service.add(receipt_handle(now - 55_000, 60_000, 1));
+        assert!(service.take_due(now + 1_000, 10_000, 1_000).is_empty());
+        assert!(service.handles.lock().is_empty());
+    }
+}
@@ -0,0 +1,254 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::sync::Arc;
+
+use cheetah_string::CheetahString;
+use rocketmq_common::common::message::message_queue::MessageQueue;
+use rocketmq_remoting::protocol::heartbeat::message_model::MessageModel;
+use rocketmq_remoting::protocol::namespace_util::NamespaceUtil;
+use rocketmq_remoting::runtime::RPCHook;
+use rocketmq_rust::ArcMut;
+
+use crate::base::client_config::ClientConfig;
+use crate::consumer::consumer_impl::default_mq_pull_consumer_impl::DefaultMQPullConsumerImpl;
+use crate::consumer::default_mq_pull_consumer_builder::DefaultMQPullConsumerBuilder;
+use crate::consumer::pull_result::PullResult;
+
+#[derive(Clone)]
+pub struct PullConsumerConfig {
+    pub(crate) consumer_group: CheetahString,
+    pub(crate) message_model: MessageModel,
+    pub(crate) broker_suspend_max_time_millis: u64,
+    pub(crate) consumer_timeout_millis_when_suspend: u64,
+    pub(crate) consumer_pull_timeout_millis: u64,
+    pub(crate) unit_mode: bool,
+    pub(crate) rpc_hook: Option<Arc<Box<dyn RPCHook>>>,
+}
+
+impl PullConsumerConfig {
+    pub fn consumer_group(&self) -> &CheetahString {
+        &self.consumer_group
+    }
+
+    pub fn message_model(&self) -> MessageModel {
+        self.message_model
+    }
+
+    pub fn broker_suspend_max_time_millis(&self) -> u64 {
+        self.broker_suspend_max_time_millis
+    }
+
+    pub fn consumer_timeout_millis_when_suspend(&self) -> u64 {
+        self.consumer_timeout_millis_when_suspend
+    }
+
+    pub fn consumer_pull_timeout_millis(&self) -> u64 {
+        self.consumer_pull_timeout_millis
+    }
+
+    pub fn unit_mode(&self) -> bool {
+        self.unit_mode
+    }
+}
+
+impl Default for PullConsumerConfig {
+    fn default() -> Self {
+        PullConsumerConfig {
+            consumer_group: CheetahString::new(),
+            message_model: MessageModel::Clustering,
+            broker_suspend_max_time_millis: 1000 * 20,
+            consumer_timeout_millis_when_suspend: 1000 * 30,
+            consumer_pull_timeout_millis: 1000 * 10,
+            unit_mode: false,
+            rpc_hook: None,
+        }
+    }
+}
+
+/// A consumer that pulls messages from queues and offsets chosen by the caller.
+///
+/// The pull consumer does not rebalance queues, the caller picks them from
+/// [`fetch_subscribe_message_queues`](Self::fetch_subscribe_message_queues) and records its
+/// progress with [`update_consume_offset`](Self::update_consume_offset). Recorded offsets are
+/// persisted periodically and on shutdown.
+pub struct DefaultMQPullConsumer {
+    client_config: ClientConfig,
+    consumer_config: ArcMut<PullConsumerConfig>,
+    default_mq_pull_consumer_impl: ArcMut<DefaultMQPullConsumerImpl>,
+}
+
+impl DefaultMQPullConsumer {
+    pub fn builder() -> DefaultMQPullConsumerBuilder {
+        DefaultMQPullConsumerBuilder::default()
+    }
+
+    pub fn new(
+        client_config: ClientConfig,
+        consumer_config: PullConsumerConfig,
+    ) -> DefaultMQPullConsumer {
+        let consumer_config = ArcMut::new(consumer_config);
+        let default_mq_pull_consumer_impl = ArcMut::new(DefaultMQPullConsumerImpl::new(
+            client_config.clone(),
+            consumer_config.clone(),
+            consumer_config.rpc_hook.clone(),
+        ));
+        DefaultMQPullConsumer {
+            client_config,
+            consumer_config,
+            default_mq_pull_consumer_impl,
+        }
+    }
+
+    pub fn consumer_config(&self) -> &PullConsumerConfig {
+        &self.consumer_config
+    }
+
+    pub async fn start(&mut self) -> rocketmq_error::RocketMQResult<()> {
+        self.consumer_config.consumer_group =
+            CheetahString::from_string(NamespaceUtil::wrap_namespace(
+                self.client_config
+                    .get_namespace()
+                    .unwrap_or_default()
+                    .as_str(),
+                self.consumer_config.consumer_group.as_str(),
+            ));
+        let this = self.default_mq_pull_consumer_impl.clone();
+        self.default_mq_pull_consumer_impl.start(this).await
+    }
+
+    pub async fn shutdown(&mut self) {
+        self.default_mq_pull_consumer_impl.shutdown().await
+    }
+
+    /// Fetches the queues of `topic` that can be pulled from.
+    pub async fn fetch_subscribe_message_queues(
+        &mut self,
+        topic: impl Into<CheetahString>,
+    ) -> rocketmq_error::RocketMQResult<Vec<MessageQueue>> {
+        self.default_mq_pull_consumer_impl
+            .fetch_subscribe_message_queues(&topic.into())
+            .await
+    }
+
+    /// Pulls at most `max_nums` messages matching `sub_expression` from `mq`, starting at
+    /// `offset`. Returns at once when no message is found.
+    pub async fn pull(
+        &mut self,
+        mq: &MessageQueue,
+        sub_expression: impl Into<CheetahString>,
+        offset: i64,
+        max_nums: i32,
+    ) -> rocketmq_error::RocketMQResult<PullResult> {
+        let timeout_millis = self.consumer_config.consumer_pull_timeout_millis;
+        self.pull_with_timeout(mq, sub_expression, offset, max_nums, timeout_millis)
+            .await
+    }
+
+    pub async fn pull_with_timeout(
+        &mut self,
+        mq: &MessageQueue,
+        sub_expression: impl Into<CheetahString>,
+        offset: i64,
+        max_nums: i32,
+        timeout_millis: u64,
+    ) -> rocketmq_error::RocketMQResult<PullResult> {
+        self.default_mq_pull_consumer_impl
+            .pull(
+                mq,
+                &sub_expression.into(),
+                offset,
+                max_nums,
+                false,
+                timeout_millis,
+            )
+            .await
+    }
+
+    /// Like [`pull`](Self::pull), but the broker holds the request for up to
+    /// `broker_suspend_max_time_millis` until a message arrives.
+    pub async fn pull_block_if_not_found(
+        &mut self,
+        mq: &MessageQueue,
+        sub_expression: impl Into<CheetahString>,
+        offset: i64,
+        max_nums: i32,
+    ) -> rocketmq_error::RocketMQResult<PullResult> {
+        let timeout_millis = self.consumer_config.consumer_pull_timeout_millis;
+        self.default_mq_pull_consumer_impl
+            .pull(
+                mq,
+                &sub_expression.into(),
+                offset,
+                max_nums,
+                true,
+                timeout_millis,
+            )
+            .await
+    }
+
+    /// Pulls in the background and hands the result to `pull_callback`.
+    pub fn pull_with_callback<F>(
+        &self,
+        mq: MessageQueue,
+        sub_expression: impl Into<CheetahString>,
+        offset: i64,
+        max_nums: i32,
+        pull_callback: F,
+    ) where
+        F: FnOnce(rocketmq_error::RocketMQResult<PullResult>) + Send + 'static,
+    {
+        let mut default_mq_pull_consumer_impl = self.default_mq_pull_consumer_impl.clone();
+        let sub_expression = sub_expression.into();
+        let timeout_millis = self.consumer_config.consumer_pull_timeout_millis;
+        tokio::spawn(async move {
+            let result = default_mq_pull_consumer_impl
+                .pull(
+                    &mq,
+                    &sub_expression,
+                    offset,
+                    max_nums,
+                    false,
+                    timeout_millis,
+                )
+                .await;
+            pull_callback(result);
+        });
+    }
+
+    /// Records `offset` as the consume offset of `mq`.
+    pub async fn update_consume_offset(
+        &mut self,
+        mq: &MessageQueue,
+        offset: i64,
+    ) -> rocketmq_error::RocketMQResult<()> {
+        self.default_mq_pull_consumer_impl
+            .update_consume_offset(mq, offset)
+            .await
+    }
+
+    /// Returns the consume offset of `mq`, read from the offset store instead of the local cache
+    /// when `from_store` is set.
+    pub async fn fetch_consume_offset(
+        &mut self,
+        mq: &MessageQueue,
+        from_store: bool,
+    ) -> rocketmq_error::RocketMQResult<i64> {
+        self.default_mq_pull_consumer_impl
+            .fetch_consume_offset(mq, from_store)
+            .await
+    }
+}
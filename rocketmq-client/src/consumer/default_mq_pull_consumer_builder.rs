@@ -0,0 +1,153 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::sync::Arc;
+
+use cheetah_string::CheetahString;
+use rocketmq_remoting::protocol::heartbeat::message_model::MessageModel;
+use rocketmq_remoting::runtime::RPCHook;
+
+use crate::base::client_config::ClientConfig;
+use crate::consumer::default_mq_pull_consumer::DefaultMQPullConsumer;
+use crate::consumer::default_mq_pull_consumer::PullConsumerConfig;
+
+pub struct DefaultMQPullConsumerBuilder {
+    client_config: Option<ClientConfig>,
+    consumer_group: Option<CheetahString>,
+    message_model: Option<MessageModel>,
+    broker_suspend_max_time_millis: Option<u64>,
+    consumer_timeout_millis_when_suspend: Option<u64>,
+    consumer_pull_timeout_millis: Option<u64>,
+    unit_mode: Option<bool>,
+    rpc_hook: Option<Arc<Box<dyn RPCHook>>>,
+}
+
+impl Default for DefaultMQPullConsumerBuilder {
+    fn default() -> Self {
+        Self {
+            client_config: Some(Default::default()),
+            consumer_group: None,
+            message_model: None,
+            broker_suspend_max_time_millis: None,
+            consumer_timeout_millis_when_suspend: None,
+            consumer_pull_timeout_millis: None,
+            unit_mode: None,
+            rpc_hook: None,
+        }
+    }
+}
+
+impl DefaultMQPullConsumerBuilder {
+    pub fn name_server_addr(mut self, name_server_addr: impl Into<CheetahString>) -> Self {
+        if let Some(client_config) = self.client_config.as_mut() {
+            client_config.namesrv_addr = Some(name_server_addr.into());
+            client_config
+                .namespace_initialized
+                .store(false, std::sync::atomic::Ordering::Release);
+        }
+        self
+    }
+
+    pub fn client_config(mut self, client_config: ClientConfig) -> Self {
+        self.client_config = Some(client_config);
+        self
+    }
+
+    pub fn consumer_group(mut self, consumer_group: impl Into<CheetahString>) -> Self {
+        self.consumer_group = Some(consumer_group.into());
+        self
+    }
+
+    pub fn message_model(mut self, message_model: MessageModel) -> Self {
+        self.message_model = Some(message_model);
+        self
+    }
+
+    pub fn broker_suspend_max_time_millis(mut self, broker_suspend_max_time_millis: u64) -> Self {
+        self.broker_suspend_max_time_millis = Some(broker_suspend_max_time_millis);
+        self
+    }
+
+    pub fn consumer_timeout_millis_when_suspend(
+        mut self,
+        consumer_timeout_millis_when_suspend: u64,
+    ) -> Self {
+        self.consumer_timeout_millis_when_suspend = Some(consumer_timeout_millis_when_suspend);
+        self
+    }
+
+    pub fn consumer_pull_timeout_millis(mut self, consumer_pull_timeout_millis: u64) -> Self {
+        self.consumer_pull_timeout_millis = Some(consumer_pull_timeout_millis);
+        self
+    }
+
+    pub fn unit_mode(mut self, unit_mode: bool) -> Self {
+        self.unit_mode = Some(unit_mode);
+        self
+    }
+
+    pub fn rpc_hook(mut self, rpc_hook: Arc<Box<dyn RPCHook>>) -> Self {
+        self.rpc_hook = Some(rpc_hook);
+        self
+    }
+
+    pub fn build(self) -> DefaultMQPullConsumer {
+        let mut consumer_config = PullConsumerConfig::default();
+        if let Some(consumer_group) = self.consumer_group {
+            consumer_config.consumer_group = consumer_group;
+        }
+        if let Some(message_model) = self.message_model {
+            consumer_config.message_model = message_model;
+        }
+        if let Some(broker_suspend_max_time_millis) = self.broker_suspend_max_time_millis {
+            consumer_config.broker_suspend_max_time_millis = broker_suspend_max_time_millis;
+        }
+        if let Some(consumer_timeout_millis_when_suspend) =
+            self.consumer_timeout_millis_when_suspend
+        {
+            consumer_config.consumer_timeout_millis_when_suspend =
+                consumer_timeout_millis_when_suspend;
+        }
+        if let Some(consumer_pull_timeout_millis) = self.consumer_pull_timeout_millis {
+            consumer_config.consumer_pull_timeout_millis = consumer_pull_timeout_millis;
+        }
+        if let Some(unit_mode) = self.unit_mode {
+            consumer_config.unit_mode = unit_mode;
+        }
+        consumer_config.rpc_hook = self.rpc_hook;
+        DefaultMQPullConsumer::new(self.client_config.unwrap_or_default(), consumer_config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_applies_settings_over_defaults() {
+        let consumer = DefaultMQPullConsumerBuilder::default()
+            .consumer_group("pull_group")
+            .message_model(MessageModel::Broadcasting)
+            .consumer_pull_timeout_millis(3000)
+            .build();
+        let config = consumer.consumer_config();
+        assert_eq!(config.consumer_group().as_str(), "pull_group");
+        assert_eq!(config.message_model(), MessageModel::Broadcasting);
+        assert_eq!(config.consumer_pull_timeout_millis(), 3000);
+        assert_eq!(config.broker_suspend_max_time_millis(), 20_000);
+        assert_eq!(config.consumer_timeout_millis_when_suspend(), 30_000);
+    }
+}
@@ -18,6 +18,7 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::thread;
+use std::time::Duration;
 
 use cheetah_string::CheetahString;
 use rocketmq_common::common::consumer::consume_from_where::ConsumeFromWhere;
@@ -27,13 +28,14 @@ use rocketmq_common::utils::util_all;
 use rocketmq_common::TimeUtils::get_current_millis;
 use rocketmq_remoting::protocol::heartbeat::message_model::MessageModel;
 use rocketmq_remoting::protocol::namespace_util::NamespaceUtil;
+use rocketmq_remoting::protocol::subscription::group_retry_policy::GroupRetryPolicy;
 use rocketmq_remoting::runtime::RPCHook;
 use rocketmq_rust::ArcMut;
 use tokio::runtime::Handle;
 
 use crate::base::client_config::ClientConfig;
 use crate::base::mq_admin::MQAdmin;
-use crate::base::query_result::QueryResult;
+use crate::consumer::ack_result::AckResult;
 use crate::consumer::allocate_message_queue_strategy::AllocateMessageQueueStrategy;
 use crate::consumer::consumer_impl::default_mq_push_consumer_impl::DefaultMQPushConsumerImpl;
 use crate::consumer::default_mq_push_consumer_builder::DefaultMQPushConsumerBuilder;
@@ -49,6 +51,8 @@ use crate::consumer::message_selector::MessageSelector;
 use crate::consumer::mq_consumer::MQConsumer;
 use crate::consumer::mq_push_consumer::MQPushConsumer;
 use crate::consumer::rebalance_strategy::allocate_message_queue_averagely::AllocateMessageQueueAveragely;
+use crate::consumer::receipt_handle::ReceiptHandle;
+use crate::hook::consume_message_hook::ConsumeMessageHook;
 use crate::trace::async_trace_dispatcher::AsyncTraceDispatcher;
 use crate::trace::hook::consume_message_trace_hook_impl::ConsumeMessageTraceHookImpl;
 use crate::trace::trace_dispatcher::TraceDispatcher;
@@ -87,6 +91,9 @@ pub struct ConsumerConfig {
     pub(crate) consume_timeout: u64,
     pub(crate) pop_invisible_time: u64,
     pub(crate) pop_batch_nums: u32,
+    pub(crate) pop_renew_ahead_millis: u64,
+    pub(crate) pop_max_renew_millis: u64,
+    pub(crate) group_retry_policy: Option<GroupRetryPolicy>,
     pub(crate) await_termination_millis_when_shutdown: u64,
     pub(crate) trace_dispatcher: Option<Arc<Box<dyn TraceDispatcher + Send + Sync>>>,
     pub(crate) client_rebalance: bool,
@@ -206,6 +213,18 @@ impl ConsumerConfig {
         self.pop_batch_nums
     }
 
+    pub fn pop_renew_ahead_millis(&self) -> u64 {
+        self.pop_renew_ahead_millis
+    }
+
+    pub fn pop_max_renew_millis(&self) -> u64 {
+        self.pop_max_renew_millis
+    }
+
+    pub fn group_retry_policy(&self) -> Option<&GroupRetryPolicy> {
+        self.group_retry_policy.as_ref()
+    }
+
     pub fn await_termination_millis_when_shutdown(&self) -> u64 {
         self.await_termination_millis_when_shutdown
     }
@@ -356,6 +375,19 @@ impl ConsumerConfig {
         self.pop_batch_nums = pop_batch_nums;
     }
 
+    pub fn set_pop_renew_ahead_millis(&mut self, pop_renew_ahead_millis: u64) {
+        self.pop_renew_ahead_millis = pop_renew_ahead_millis;
+    }
+
+    pub fn set_pop_max_renew_millis(&mut self, pop_max_renew_millis: u64) {
+        self.pop_max_renew_millis = pop_max_renew_millis;
+    }
+
+    /// Retry policy reported in heartbeats, the broker applies it to the whole group.
+    pub fn set_group_retry_policy(&mut self, group_retry_policy: Option<GroupRetryPolicy>) {
+        self.group_retry_policy = group_retry_policy;
+    }
+
     pub fn set_await_termination_millis_when_shutdown(
         &mut self,
         await_termination_millis_when_shutdown: u64,
@@ -417,6 +449,9 @@ impl Default for ConsumerConfig {
             consume_timeout: 15,
             pop_invisible_time: 60000,
             pop_batch_nums: 32,
+            pop_renew_ahead_millis: 10_000,
+            pop_max_renew_millis: 3 * 60 * 60 * 1000,
+            group_retry_policy: None,
             await_termination_millis_when_shutdown: 0,
             trace_dispatcher: None,
             client_rebalance: true,
@@ -449,67 +484,7 @@ impl MQConsumer for DefaultMQPushConsumer {
     }
 }
 
-impl MQAdmin for DefaultMQPushConsumer {
-    fn create_topic(
-        &self,
-        key: &str,
-        new_topic: &str,
-        queue_num: i32,
-        attributes: HashMap<String, String>,
-    ) -> rocketmq_error::RocketMQResult<()> {
-        panic!("This method is not implemented for DefaultMQPushConsumer");
-    }
-
-    fn create_topic_with_flag(
-        &self,
-        key: &str,
-        new_topic: &str,
-        queue_num: i32,
-        topic_sys_flag: i32,
-        attributes: HashMap<String, String>,
-    ) -> rocketmq_error::RocketMQResult<()> {
-        panic!("This method is not implemented for DefaultMQPushConsumer");
-    }
-
-    fn search_offset(
-        &self,
-        mq: &MessageQueue,
-        timestamp: u64,
-    ) -> rocketmq_error::RocketMQResult<i64> {
-        panic!("This method is not implemented for DefaultMQPushConsumer");
-    }
-
-    fn max_offset(&self, mq: &MessageQueue) -> rocketmq_error::RocketMQResult<i64> {
-        panic!("This method is not implemented for DefaultMQPushConsumer");
-    }
-
-    fn min_offset(&self, mq: &MessageQueue) -> rocketmq_error::RocketMQResult<i64> {
-        panic!("This method is not implemented for DefaultMQPushConsumer");
-    }
-
-    fn earliest_msg_store_time(&self, mq: &MessageQueue) -> rocketmq_error::RocketMQResult<u64> {
-        panic!("This method is not implemented for DefaultMQPushConsumer");
-    }
-
-    fn query_message(
-        &self,
-        topic: &str,
-        key: &str,
-        max_num: i32,
-        begin: u64,
-        end: u64,
-    ) -> rocketmq_error::RocketMQResult<QueryResult> {
-        panic!("This method is not implemented for DefaultMQPushConsumer");
-    }
-
-    fn view_message(
-        &self,
-        topic: &str,
-        msg_id: &str,
-    ) -> rocketmq_error::RocketMQResult<MessageExt> {
-        panic!("This method is not implemented for DefaultMQPushConsumer");
-    }
-}
+impl MQAdmin for DefaultMQPushConsumer {}
 
 impl MQPushConsumer for DefaultMQPushConsumer {
     async fn start(&mut self) -> rocketmq_error::RocketMQResult<()> {
@@ -643,7 +618,11 @@ impl MQPushConsumer for DefaultMQPushConsumer {
         topic: &str,
         selector: Option<MessageSelector>,
     ) -> rocketmq_error::RocketMQResult<()> {
-        todo!()
+        self.default_mqpush_consumer_impl
+            .as_mut()
+            .unwrap()
+            .subscribe_with_selector(topic.into(), selector)
+            .await
     }
 
     async fn unsubscribe(&mut self, topic: &str) {
@@ -698,4 +677,42 @@ impl DefaultMQPushConsumer {
     pub fn set_consume_from_where(&mut self, consume_from_where: ConsumeFromWhere) {
         self.consumer_config.consume_from_where = consume_from_where;
     }
+
+    /// Runs `hook` before and after every call of the message listener. Hooks run in the order
+    /// they were registered.
+    pub fn register_consume_message_hook(
+        &mut self,
+        hook: impl ConsumeMessageHook + Send + Sync + 'static,
+    ) {
+        self.default_mqpush_consumer_impl
+            .as_mut()
+            .unwrap()
+            .register_consume_message_hook(hook);
+    }
+
+    /// Acks a popped message, see [`ReceiptHandle::from_message`].
+    pub async fn ack_message(
+        &mut self,
+        receipt_handle: &ReceiptHandle,
+    ) -> rocketmq_error::RocketMQResult<AckResult> {
+        self.default_mqpush_consumer_impl
+            .as_mut()
+            .unwrap()
+            .ack_message(receipt_handle)
+            .await
+    }
+
+    /// Keeps a popped message invisible for `duration` from now on. Later acks and changes must
+    /// use the returned handle, the old one becomes invalid.
+    pub async fn change_invisible_duration(
+        &mut self,
+        receipt_handle: &ReceiptHandle,
+        duration: Duration,
+    ) -> rocketmq_error::RocketMQResult<ReceiptHandle> {
+        self.default_mqpush_consumer_impl
+            .as_mut()
+            .unwrap()
+            .change_invisible_duration(receipt_handle, duration)
+            .await
+    }
 }
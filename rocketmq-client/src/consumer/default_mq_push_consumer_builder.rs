@@ -20,6 +20,7 @@ use std::sync::Arc;
 use cheetah_string::CheetahString;
 use rocketmq_common::common::consumer::consume_from_where::ConsumeFromWhere;
 use rocketmq_remoting::protocol::heartbeat::message_model::MessageModel;
+use rocketmq_remoting::protocol::subscription::group_retry_policy::GroupRetryPolicy;
 use rocketmq_remoting::runtime::RPCHook;
 use rocketmq_rust::ArcMut;
 
@@ -63,6 +64,9 @@ pub struct DefaultMQPushConsumerBuilder {
     consume_timeout: Option<u64>,
     pop_invisible_time: Option<u64>,
     pop_batch_nums: Option<u32>,
+    pop_renew_ahead_millis: Option<u64>,
+    pop_max_renew_millis: Option<u64>,
+    group_retry_policy: Option<GroupRetryPolicy>,
     await_termination_millis_when_shutdown: Option<u64>,
     trace_dispatcher: Option<Arc<Box<dyn TraceDispatcher + Send + Sync>>>,
     client_rebalance: Option<bool>,
@@ -101,6 +105,9 @@ impl Default for DefaultMQPushConsumerBuilder {
             consume_timeout: None,
             pop_invisible_time: None,
             pop_batch_nums: None,
+            pop_renew_ahead_millis: None,
+            pop_max_renew_millis: None,
+            group_retry_policy: None,
             await_termination_millis_when_shutdown: None,
             trace_dispatcher: None,
             client_rebalance: None,
@@ -286,6 +293,21 @@ impl DefaultMQPushConsumerBuilder {
         self
     }
 
+    pub fn pop_renew_ahead_millis(mut self, pop_renew_ahead_millis: u64) -> Self {
+        self.pop_renew_ahead_millis = Some(pop_renew_ahead_millis);
+        self
+    }
+
+    pub fn pop_max_renew_millis(mut self, pop_max_renew_millis: u64) -> Self {
+        self.pop_max_renew_millis = Some(pop_max_renew_millis);
+        self
+    }
+
+    pub fn group_retry_policy(mut self, group_retry_policy: GroupRetryPolicy) -> Self {
+        self.group_retry_policy = Some(group_retry_policy);
+        self
+    }
+
     pub fn await_termination_millis_when_shutdown(
         mut self,
         await_termination_millis_when_shutdown: u64,
@@ -399,6 +421,15 @@ impl DefaultMQPushConsumerBuilder {
         if let Some(pop_batch_nums) = self.pop_batch_nums {
             consumer_config.pop_batch_nums = pop_batch_nums;
         }
+        if let Some(pop_renew_ahead_millis) = self.pop_renew_ahead_millis {
+            consumer_config.pop_renew_ahead_millis = pop_renew_ahead_millis;
+        }
+        if let Some(pop_max_renew_millis) = self.pop_max_renew_millis {
+            consumer_config.pop_max_renew_millis = pop_max_renew_millis;
+        }
+        if let Some(group_retry_policy) = self.group_retry_policy {
+            consumer_config.group_retry_policy = Some(group_retry_policy);
+        }
         if let Some(await_termination_millis_when_shutdown) =
             self.await_termination_millis_when_shutdown
         {
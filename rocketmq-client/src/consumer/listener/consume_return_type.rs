@@ -66,3 +66,18 @@ impl std::fmt::Display for ConsumeReturnType {
         }
     }
 }
+
+impl std::str::FromStr for ConsumeReturnType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "SUCCESS" => Ok(ConsumeReturnType::Success),
+            "TIME_OUT" => Ok(ConsumeReturnType::TimeOut),
+            "EXCEPTION" => Ok(ConsumeReturnType::Exception),
+            "RETURN_NULL" => Ok(ConsumeReturnType::ReturnNull),
+            "FAILED" => Ok(ConsumeReturnType::Failed),
+            _ => Err(format!("Unknown ConsumeReturnType value: {s}")),
+        }
+    }
+}
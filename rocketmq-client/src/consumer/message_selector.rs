@@ -14,7 +14,21 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use rocketmq_common::common::filter::expression_type::ExpressionType;
 
+/// Selects the messages a consumer receives from a subscribed topic, either by tags or by an
+/// SQL92 expression over the message properties. SQL92 filtering needs `enablePropertyFilter`
+/// on the broker.
+///
+/// ```
+/// use rocketmq_client_rust::consumer::message_selector::MessageSelector;
+///
+/// let by_tag = MessageSelector::by_tag("TagA || TagB");
+/// let by_sql = MessageSelector::by_sql("a > 5 AND region = 'EU'");
+/// assert_eq!(by_tag.get_expression_type(), "TAG");
+/// assert_eq!(by_sql.get_expression_type(), "SQL92");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MessageSelector {
     type_: String,
     expression: String,
@@ -28,12 +42,14 @@ impl MessageSelector {
         }
     }
 
+    /// Selects messages whose properties match the SQL92 expression `sql`.
     pub fn by_sql(sql: &str) -> Self {
-        Self::new("SQL92", sql)
+        Self::new(ExpressionType::SQL92, sql)
     }
 
+    /// Selects messages by tag, `tag` is `*` for all messages or tags joined by `||`.
     pub fn by_tag(tag: &str) -> Self {
-        Self::new("TAG", tag)
+        Self::new(ExpressionType::TAG, tag)
     }
 
     pub fn get_expression_type(&self) -> &str {
@@ -44,3 +60,40 @@ impl MessageSelector {
         &self.expression
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use cheetah_string::CheetahString;
+    use rocketmq_remoting::protocol::filter::filter_api::FilterAPI;
+
+    use super::*;
+
+    #[test]
+    fn selectors_build_subscription_data_of_their_type() {
+        let topic = CheetahString::from_static_str("topic");
+
+        let selector = MessageSelector::by_tag("A || B");
+        let subscription_data = FilterAPI::build(
+            &topic,
+            &selector.get_expression().into(),
+            Some(selector.get_expression_type().into()),
+        )
+        .unwrap();
+        assert_eq!(subscription_data.expression_type, ExpressionType::TAG);
+        assert_eq!(subscription_data.tags_set.len(), 2);
+
+        let selector = MessageSelector::by_sql("a > 5 AND region = 'EU'");
+        let subscription_data = FilterAPI::build(
+            &topic,
+            &selector.get_expression().into(),
+            Some(selector.get_expression_type().into()),
+        )
+        .unwrap();
+        assert_eq!(subscription_data.expression_type, ExpressionType::SQL92);
+        assert_eq!(
+            subscription_data.sub_string.as_str(),
+            "a > 5 AND region = 'EU'"
+        );
+        assert!(subscription_data.tags_set.is_empty());
+    }
+}
@@ -19,6 +19,7 @@ use std::collections::HashSet;
 
 use cheetah_string::CheetahString;
 use rocketmq_common::common::consumer::consume_from_where::ConsumeFromWhere;
+use rocketmq_common::common::message::message_enum::MessageRequestMode;
 use rocketmq_common::common::message::message_ext::MessageExt;
 use rocketmq_common::common::message::message_queue::MessageQueue;
 use rocketmq_remoting::protocol::body::consume_message_directly_result::ConsumeMessageDirectlyResult;
@@ -26,8 +27,10 @@ use rocketmq_remoting::protocol::body::consumer_running_info::ConsumerRunningInf
 use rocketmq_remoting::protocol::heartbeat::consume_type::ConsumeType;
 use rocketmq_remoting::protocol::heartbeat::message_model::MessageModel;
 use rocketmq_remoting::protocol::heartbeat::subscription_data::SubscriptionData;
+use rocketmq_remoting::protocol::subscription::group_retry_policy::GroupRetryPolicy;
 use rocketmq_rust::ArcMut;
 
+use crate::consumer::consumer_impl::default_mq_pull_consumer_impl::DefaultMQPullConsumerImpl;
 use crate::consumer::consumer_impl::default_mq_push_consumer_impl::DefaultMQPushConsumerImpl;
 use crate::consumer::consumer_impl::pop_request::PopRequest;
 use crate::consumer::consumer_impl::pull_request::PullRequest;
@@ -69,7 +72,13 @@ pub trait MQConsumerInnerLocal: MQConsumerInnerAny + Sync + 'static {
     ///
     /// * `topic` - A string slice that holds the name of the topic.
     /// * `info` - A reference to a `HashSet` containing `MessageQueue` information.
-    async fn update_topic_subscribe_info(&self, topic: CheetahString, info: &HashSet<MessageQueue>);
+    ///
+    /// Returns `true` if the topic is subscribed and its queue set changed.
+    async fn update_topic_subscribe_info(
+        &self,
+        topic: CheetahString,
+        info: &HashSet<MessageQueue>,
+    ) -> bool;
 
     /// Checks if the subscription information for a given topic needs to be updated asynchronously.
     ///
@@ -85,6 +94,9 @@ pub trait MQConsumerInnerLocal: MQConsumerInnerAny + Sync + 'static {
     /// Returns whether the consumer is in unit mode.
     fn is_unit_mode(&self) -> bool;
 
+    /// Returns the retry policy the consumer wants for its group, if any.
+    fn group_retry_policy(&self) -> Option<GroupRetryPolicy>;
+
     /// Returns the running information of the consumer.
     fn consumer_running_info(&self) -> ConsumerRunningInfo;
 }
@@ -105,22 +117,26 @@ impl<T: MQConsumerInner> MQConsumerInnerAny for T {
     }
 }
 
+/// A consumer registered to the client instance, either a push or a pull consumer.
 #[derive(Clone)]
 pub struct MQConsumerInnerImpl {
-    pub(crate) default_mqpush_consumer_impl: ArcMut<DefaultMQPushConsumerImpl>,
+    pub(crate) default_mqpush_consumer_impl: Option<ArcMut<DefaultMQPushConsumerImpl>>,
+    pub(crate) default_mqpull_consumer_impl: Option<ArcMut<DefaultMQPullConsumerImpl>>,
 }
 
 impl MQConsumerInnerImpl {
     pub(crate) async fn pop_message(&mut self, pop_request: PopRequest) {
-        self.default_mqpush_consumer_impl
-            .pop_message(pop_request)
-            .await;
+        if let Some(default_mqpush_consumer_impl) = self.default_mqpush_consumer_impl.as_mut() {
+            default_mqpush_consumer_impl.pop_message(pop_request).await;
+        }
     }
 
     pub(crate) async fn pull_message(&mut self, pull_request: PullRequest) {
-        self.default_mqpush_consumer_impl
-            .pull_message(pull_request)
-            .await;
+        if let Some(default_mqpush_consumer_impl) = self.default_mqpush_consumer_impl.as_mut() {
+            default_mqpush_consumer_impl
+                .pull_message(pull_request)
+                .await;
+        }
     }
 
     pub(crate) async fn consume_message_directly(
@@ -128,51 +144,122 @@ impl MQConsumerInnerImpl {
         msg: MessageExt,
         broker_name: Option<CheetahString>,
     ) -> Option<ConsumeMessageDirectlyResult> {
-        self.default_mqpush_consumer_impl
-            .consume_message_directly(msg, broker_name)
-            .await
+        match self.default_mqpush_consumer_impl.as_ref() {
+            Some(default_mqpush_consumer_impl) => {
+                default_mqpush_consumer_impl
+                    .consume_message_directly(msg, broker_name)
+                    .await
+            }
+            None => None,
+        }
+    }
+
+    /// Applies a request mode the broker suggested for `topic`, returning whether it changed.
+    pub(crate) async fn apply_message_request_mode(
+        &self,
+        topic: &CheetahString,
+        mode: MessageRequestMode,
+    ) -> bool {
+        match self.default_mqpush_consumer_impl.as_ref() {
+            Some(default_mqpush_consumer_impl) => {
+                default_mqpush_consumer_impl
+                    .rebalance_impl
+                    .rebalance_impl_inner
+                    .apply_message_request_mode(topic, mode)
+                    .await
+            }
+            None => false,
+        }
+    }
+
+    #[inline]
+    fn pull_consumer(&self) -> &DefaultMQPullConsumerImpl {
+        self.default_mqpull_consumer_impl
+            .as_ref()
+            .expect("neither a push nor a pull consumer")
     }
 }
 
 impl MQConsumerInner for MQConsumerInnerImpl {
     #[inline]
     fn group_name(&self) -> CheetahString {
-        MQConsumerInner::group_name(self.default_mqpush_consumer_impl.as_ref())
+        match self.default_mqpush_consumer_impl.as_ref() {
+            Some(default_mqpush_consumer_impl) => {
+                MQConsumerInner::group_name(default_mqpush_consumer_impl.as_ref())
+            }
+            None => MQConsumerInner::group_name(self.pull_consumer()),
+        }
     }
 
     #[inline]
     fn message_model(&self) -> MessageModel {
-        MQConsumerInner::message_model(self.default_mqpush_consumer_impl.as_ref())
+        match self.default_mqpush_consumer_impl.as_ref() {
+            Some(default_mqpush_consumer_impl) => {
+                MQConsumerInner::message_model(default_mqpush_consumer_impl.as_ref())
+            }
+            None => MQConsumerInner::message_model(self.pull_consumer()),
+        }
     }
 
     #[inline]
     fn consume_type(&self) -> ConsumeType {
-        MQConsumerInner::consume_type(self.default_mqpush_consumer_impl.as_ref())
+        match self.default_mqpush_consumer_impl.as_ref() {
+            Some(default_mqpush_consumer_impl) => {
+                MQConsumerInner::consume_type(default_mqpush_consumer_impl.as_ref())
+            }
+            None => MQConsumerInner::consume_type(self.pull_consumer()),
+        }
     }
 
     #[inline]
     fn consume_from_where(&self) -> ConsumeFromWhere {
-        MQConsumerInner::consume_from_where(self.default_mqpush_consumer_impl.as_ref())
+        match self.default_mqpush_consumer_impl.as_ref() {
+            Some(default_mqpush_consumer_impl) => {
+                MQConsumerInner::consume_from_where(default_mqpush_consumer_impl.as_ref())
+            }
+            None => MQConsumerInner::consume_from_where(self.pull_consumer()),
+        }
     }
 
     #[inline]
     fn subscriptions(&self) -> HashSet<SubscriptionData> {
-        MQConsumerInner::subscriptions(self.default_mqpush_consumer_impl.as_ref())
+        match self.default_mqpush_consumer_impl.as_ref() {
+            Some(default_mqpush_consumer_impl) => {
+                MQConsumerInner::subscriptions(default_mqpush_consumer_impl.as_ref())
+            }
+            None => MQConsumerInner::subscriptions(self.pull_consumer()),
+        }
     }
 
     #[inline]
     fn do_rebalance(&self) {
-        MQConsumerInner::do_rebalance(self.default_mqpush_consumer_impl.as_ref())
+        match self.default_mqpush_consumer_impl.as_ref() {
+            Some(default_mqpush_consumer_impl) => {
+                MQConsumerInner::do_rebalance(default_mqpush_consumer_impl.as_ref())
+            }
+            None => MQConsumerInner::do_rebalance(self.pull_consumer()),
+        }
     }
 
     #[inline]
     async fn try_rebalance(&self) -> rocketmq_error::RocketMQResult<bool> {
-        MQConsumerInner::try_rebalance(self.default_mqpush_consumer_impl.as_ref()).await
+        match self.default_mqpush_consumer_impl.as_ref() {
+            Some(default_mqpush_consumer_impl) => {
+                MQConsumerInner::try_rebalance(default_mqpush_consumer_impl.as_ref()).await
+            }
+            None => MQConsumerInner::try_rebalance(self.pull_consumer()).await,
+        }
     }
 
     #[inline]
     async fn persist_consumer_offset(&self) {
-        MQConsumerInner::persist_consumer_offset(self.default_mqpush_consumer_impl.as_ref()).await
+        match self.default_mqpush_consumer_impl.as_ref() {
+            Some(default_mqpush_consumer_impl) => {
+                MQConsumerInner::persist_consumer_offset(default_mqpush_consumer_impl.as_ref())
+                    .await
+            }
+            None => MQConsumerInner::persist_consumer_offset(self.pull_consumer()).await,
+        }
     }
 
     #[inline]
@@ -180,31 +267,66 @@ impl MQConsumerInner for MQConsumerInnerImpl {
         &self,
         topic: CheetahString,
         info: &HashSet<MessageQueue>,
-    ) {
-        MQConsumerInner::update_topic_subscribe_info(
-            self.default_mqpush_consumer_impl.mut_from_ref(),
-            topic,
-            info,
-        )
-        .await
+    ) -> bool {
+        match self.default_mqpush_consumer_impl.as_ref() {
+            Some(default_mqpush_consumer_impl) => {
+                MQConsumerInner::update_topic_subscribe_info(
+                    default_mqpush_consumer_impl.mut_from_ref(),
+                    topic,
+                    info,
+                )
+                .await
+            }
+            None => {
+                MQConsumerInner::update_topic_subscribe_info(self.pull_consumer(), topic, info)
+                    .await
+            }
+        }
     }
 
     #[inline]
     async fn is_subscribe_topic_need_update(&self, topic: &str) -> bool {
-        MQConsumerInner::is_subscribe_topic_need_update(
-            self.default_mqpush_consumer_impl.as_ref(),
-            topic,
-        )
-        .await
+        match self.default_mqpush_consumer_impl.as_ref() {
+            Some(default_mqpush_consumer_impl) => {
+                MQConsumerInner::is_subscribe_topic_need_update(
+                    default_mqpush_consumer_impl.as_ref(),
+                    topic,
+                )
+                .await
+            }
+            None => {
+                MQConsumerInner::is_subscribe_topic_need_update(self.pull_consumer(), topic).await
+            }
+        }
     }
 
     #[inline]
     fn is_unit_mode(&self) -> bool {
-        MQConsumerInner::is_unit_mode(self.default_mqpush_consumer_impl.as_ref())
+        match self.default_mqpush_consumer_impl.as_ref() {
+            Some(default_mqpush_consumer_impl) => {
+                MQConsumerInner::is_unit_mode(default_mqpush_consumer_impl.as_ref())
+            }
+            None => MQConsumerInner::is_unit_mode(self.pull_consumer()),
+        }
+    }
+
+    #[inline]
+    fn group_retry_policy(&self) -> Option<GroupRetryPolicy> {
+        match self.default_mqpush_consumer_impl.as_ref() {
+            Some(default_mqpush_consumer_impl) => {
+                MQConsumerInner::group_retry_policy(default_mqpush_consumer_impl.as_ref())
+            }
+            None => MQConsumerInner::group_retry_policy(self.pull_consumer()),
+        }
     }
 
     #[inline]
     fn consumer_running_info(&self) -> ConsumerRunningInfo {
-        MQConsumerInner::consumer_running_info(self.default_mqpush_consumer_impl.as_ref())
+        match self.default_mqpush_consumer_impl.as_ref() {
+            Some(default_mqpush_consumer_impl) => {
+                MQConsumerInner::consumer_running_info(default_mqpush_consumer_impl.as_ref())
+            }
+            None => MQConsumerInner::consumer_running_info(self.pull_consumer()),
+        }
     }
 }
@@ -129,3 +129,139 @@ pub trait MQPushConsumerLocal: MQConsumer {
     /// Resumes the push consumer.
     async fn resume(&mut self);
 }
+
+#[cfg(test)]
+mod tests {
+    use rocketmq_common::common::message::message_queue::MessageQueue;
+
+    use super::*;
+    use crate::base::mq_admin::MQAdmin;
+
+    #[derive(Default)]
+    struct MockPushConsumer {
+        started: bool,
+        subscriptions: Vec<(String, String)>,
+        has_listener: bool,
+    }
+
+    impl MQAdmin for MockPushConsumer {}
+
+    impl MQConsumer for MockPushConsumer {
+        async fn send_message_back(
+            &mut self,
+            _msg: MessageExt,
+            _delay_level: i32,
+            _broker_name: &str,
+        ) -> rocketmq_error::RocketMQResult<()> {
+            Ok(())
+        }
+
+        async fn fetch_subscribe_message_queues(
+            &mut self,
+            _topic: &str,
+        ) -> rocketmq_error::RocketMQResult<Vec<MessageQueue>> {
+            Ok(vec![])
+        }
+    }
+
+    impl MQPushConsumer for MockPushConsumer {
+        async fn start(&mut self) -> rocketmq_error::RocketMQResult<()> {
+            self.started = true;
+            Ok(())
+        }
+
+        async fn shutdown(&mut self) {
+            self.started = false;
+        }
+
+        fn register_message_listener_concurrently_fn<MLCFN>(&mut self, _message_listener: MLCFN)
+        where
+            MLCFN: Fn(
+                    Vec<MessageExt>,
+                    ConsumeConcurrentlyContext,
+                ) -> rocketmq_error::RocketMQResult<ConsumeConcurrentlyStatus>
+                + Send
+                + Sync,
+        {
+            self.has_listener = true;
+        }
+
+        fn register_message_listener_concurrently<ML>(&mut self, _message_listener: ML)
+        where
+            ML: MessageListenerConcurrently + Send + Sync + 'static,
+        {
+            self.has_listener = true;
+        }
+
+        async fn register_message_listener_orderly_fn<MLOFN>(&mut self, _message_listener: MLOFN)
+        where
+            MLOFN: Fn(
+                    Vec<MessageExt>,
+                    ConsumeOrderlyContext,
+                ) -> rocketmq_error::RocketMQResult<ConsumeOrderlyStatus>
+                + Send
+                + Sync,
+        {
+            self.has_listener = true;
+        }
+
+        fn register_message_listener_orderly<ML>(&mut self, _message_listener: ML)
+        where
+            ML: MessageListenerOrderly + Send + Sync + 'static,
+        {
+            self.has_listener = true;
+        }
+
+        fn subscribe(
+            &mut self,
+            topic: &str,
+            sub_expression: &str,
+        ) -> rocketmq_error::RocketMQResult<()> {
+            self.subscriptions
+                .push((topic.to_string(), sub_expression.to_string()));
+            Ok(())
+        }
+
+        async fn subscribe_with_selector(
+            &mut self,
+            topic: &str,
+            _selector: Option<MessageSelector>,
+        ) -> rocketmq_error::RocketMQResult<()> {
+            self.subscriptions
+                .push((topic.to_string(), "*".to_string()));
+            Ok(())
+        }
+
+        async fn unsubscribe(&mut self, topic: &str) {
+            self.subscriptions.retain(|(t, _)| t != topic);
+        }
+
+        async fn suspend(&mut self) {}
+
+        async fn resume(&mut self) {}
+    }
+
+    async fn bootstrap<C: MQPushConsumer>(consumer: &mut C) -> rocketmq_error::RocketMQResult<()> {
+        consumer.subscribe("TopicTest", "TagA || TagB")?;
+        consumer.register_message_listener_concurrently_fn(|_msgs, _context| {
+            Ok(ConsumeConcurrentlyStatus::ConsumeSuccess)
+        });
+        consumer.start().await
+    }
+
+    #[tokio::test]
+    async fn application_code_runs_against_mock_consumer() {
+        let mut consumer = MockPushConsumer::default();
+        bootstrap(&mut consumer).await.unwrap();
+
+        assert!(consumer.started);
+        assert!(consumer.has_listener);
+        assert_eq!(
+            consumer.subscriptions,
+            vec![("TopicTest".to_string(), "TagA || TagB".to_string())]
+        );
+        assert!(consumer
+            .max_offset(&MessageQueue::from_parts("TopicTest", "broker-a", 0))
+            .is_err());
+    }
+}
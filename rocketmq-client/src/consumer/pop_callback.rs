@@ -117,12 +117,16 @@ impl PopCallback for DefaultPopCallback {
                         .execute_pop_request_immediately(pop_request)
                         .await;
                 } else {
+                    let msgs = pop_result.msg_found_list.unwrap_or_default();
+                    pop_request
+                        .get_pop_process_queue()
+                        .inc_found_msg(msgs.len());
                     push_consumer_impl
                         .consume_message_pop_service
                         .as_mut()
                         .unwrap()
                         .submit_pop_consume_request(
-                            pop_result.msg_found_list.unwrap_or_default(),
+                            msgs,
                             pop_request.get_pop_process_queue(),
                             pop_request.get_message_queue(),
                         )
@@ -0,0 +1,194 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::fmt::Display;
+
+use cheetah_string::CheetahString;
+use rocketmq_common::common::message::message_ext::MessageExt;
+use rocketmq_common::common::message::MessageConst;
+use rocketmq_common::common::message::MessageTrait;
+use rocketmq_common::TimeUtils::get_current_millis;
+use rocketmq_error::mq_client_err;
+use rocketmq_error::RocketMQResult;
+use rocketmq_remoting::protocol::header::extra_info_util::ExtraInfoUtil;
+
+/// Handle of a popped message, needed to ack it or to change its invisible time.
+///
+/// The broker attaches the handle to every popped message as the extra info property
+/// `POP_CK`, a separated list of `ckQueueOffset popTime invisibleTime reviveQid retry brokerName
+/// queueId queueOffset`. [`ReceiptHandle::from_message`] parses it and
+/// [`ReceiptHandle::encode`] builds it back, so callers never touch the raw string.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReceiptHandle {
+    topic: CheetahString,
+    ck_queue_offset: i64,
+    pop_time: i64,
+    invisible_time: i64,
+    revive_queue_id: i32,
+    retry: CheetahString,
+    broker_name: CheetahString,
+    queue_id: i32,
+    queue_offset: i64,
+}
+
+impl ReceiptHandle {
+    /// Parses the extra info of a message popped from `topic`.
+    pub fn decode(topic: impl Into<CheetahString>, extra_info: &str) -> RocketMQResult<Self> {
+        let extra_info_strs = ExtraInfoUtil::split(extra_info);
+        Ok(ReceiptHandle {
+            topic: topic.into(),
+            ck_queue_offset: ExtraInfoUtil::get_ck_queue_offset(&extra_info_strs)?,
+            pop_time: ExtraInfoUtil::get_pop_time(&extra_info_strs)?,
+            invisible_time: ExtraInfoUtil::get_invisible_time(&extra_info_strs)?,
+            revive_queue_id: ExtraInfoUtil::get_revive_qid(&extra_info_strs)?,
+            retry: ExtraInfoUtil::get_retry(&extra_info_strs)?.into(),
+            broker_name: ExtraInfoUtil::get_broker_name(&extra_info_strs)?.into(),
+            queue_id: ExtraInfoUtil::get_queue_id(&extra_info_strs)?,
+            queue_offset: ExtraInfoUtil::get_queue_offset(&extra_info_strs)?,
+        })
+    }
+
+    /// Reads the handle of a message returned by pop.
+    pub fn from_message(message: &MessageExt) -> RocketMQResult<Self> {
+        let Some(extra_info) = message.get_property(&CheetahString::from_static_str(
+            MessageConst::PROPERTY_POP_CK,
+        )) else {
+            return mq_client_err!(format!(
+                "message {} of topic {} was not popped, it has no receipt handle",
+                message.msg_id(),
+                message.get_topic()
+            ));
+        };
+        Self::decode(message.get_topic().clone(), &extra_info)
+    }
+
+    /// Builds the extra info string the broker expects.
+    pub fn encode(&self) -> CheetahString {
+        CheetahString::from_string(format!(
+            "{}{sep}{}{sep}{}{sep}{}{sep}{}{sep}{}{sep}{}{sep}{}",
+            self.ck_queue_offset,
+            self.pop_time,
+            self.invisible_time,
+            self.revive_queue_id,
+            self.retry,
+            self.broker_name,
+            self.queue_id,
+            self.queue_offset,
+            sep = MessageConst::KEY_SEPARATOR
+        ))
+    }
+
+    /// Topic the message is stored in: `topic` itself or the pop retry topic of
+    /// `consumer_group`.
+    pub fn real_topic(&self, consumer_group: &str) -> RocketMQResult<CheetahString> {
+        ExtraInfoUtil::get_real_topic_with_retry(&self.topic, consumer_group, &self.retry)
+            .map(CheetahString::from_string)
+    }
+
+    /// Time in milliseconds at which the message becomes visible to consumers again.
+    pub fn next_visible_time(&self) -> i64 {
+        self.pop_time + self.invisible_time
+    }
+
+    /// Whether the message is visible again, acks and changes after that fail.
+    pub fn is_expired(&self) -> bool {
+        self.next_visible_time() <= get_current_millis() as i64
+    }
+
+    pub fn topic(&self) -> &CheetahString {
+        &self.topic
+    }
+
+    pub fn pop_time(&self) -> i64 {
+        self.pop_time
+    }
+
+    pub fn invisible_time(&self) -> i64 {
+        self.invisible_time
+    }
+
+    pub fn retry(&self) -> &CheetahString {
+        &self.retry
+    }
+
+    pub fn broker_name(&self) -> &CheetahString {
+        &self.broker_name
+    }
+
+    pub fn queue_id(&self) -> i32 {
+        self.queue_id
+    }
+
+    pub fn queue_offset(&self) -> i64 {
+        self.queue_offset
+    }
+}
+
+impl Display for ReceiptHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.encode())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXTRA_INFO: &str = "12 1700000000000 60000 3 0 broker-a 2 42";
+
+    #[test]
+    fn decode_and_encode_round_trip() {
+        let receipt_handle = ReceiptHandle::decode("TopicTest", EXTRA_INFO).unwrap();
+        assert_eq!(receipt_handle.topic(), "TopicTest");
+        assert_eq!(receipt_handle.pop_time(), 1_700_000_000_000);
+        assert_eq!(receipt_handle.invisible_time(), 60_000);
+        assert_eq!(receipt_handle.broker_name(), "broker-a");
+        assert_eq!(receipt_handle.queue_id(), 2);
+        assert_eq!(receipt_handle.queue_offset(), 42);
+        assert_eq!(receipt_handle.next_visible_time(), 1_700_000_060_000);
+        assert!(receipt_handle.is_expired());
+        assert_eq!(receipt_handle.encode(), EXTRA_INFO);
+    }
+
+    #[test]
+    fn decode_rejects_truncated_extra_info() {
+        assert!(ReceiptHandle::decode("TopicTest", "12 1700000000000 60000").is_err());
+    }
+
+    #[test]
+    fn real_topic_follows_retry_flag() {
+        let receipt_handle = ReceiptHandle::decode("TopicTest", EXTRA_INFO).unwrap();
+        assert_eq!(receipt_handle.real_topic("GroupTest").unwrap(), "TopicTest");
+        let retry_handle =
+            ReceiptHandle::decode("TopicTest", "12 1700000000000 60000 3 1 broker-a 2 42").unwrap();
+        assert_ne!(retry_handle.real_topic("GroupTest").unwrap(), "TopicTest");
+    }
+
+    #[test]
+    fn from_message_requires_pop_ck() {
+        let mut message = MessageExt::default();
+        message.set_topic(CheetahString::from_static_str("TopicTest"));
+        assert!(ReceiptHandle::from_message(&message).is_err());
+        message.put_property(
+            CheetahString::from_static_str(MessageConst::PROPERTY_POP_CK),
+            CheetahString::from_static_str(EXTRA_INFO),
+        );
+        assert_eq!(
+            ReceiptHandle::from_message(&message).unwrap().encode(),
+            EXTRA_INFO
+        );
+    }
+}
@@ -35,6 +35,7 @@ use rocketmq_common::TimeUtils::get_current_millis;
 use rocketmq_error::mq_client_err;
 use rocketmq_remoting::base::connection_net_event::ConnectionNetEvent;
 use rocketmq_remoting::protocol::body::consume_message_directly_result::ConsumeMessageDirectlyResult;
+use rocketmq_remoting::protocol::body::heartbeat_response_body::HeartbeatResponseBody;
 use rocketmq_remoting::protocol::heartbeat::consumer_data::ConsumerData;
 use rocketmq_remoting::protocol::heartbeat::heartbeat_data::HeartbeatData;
 use rocketmq_remoting::protocol::heartbeat::message_model::MessageModel;
@@ -297,7 +298,14 @@ impl MQClientInstance {
             ServiceState::CreateJust => {
                 self.service_state = ServiceState::StartFailed;
                 // If not specified,looking address from name remoting_server
-                if self.client_config.namesrv_addr.is_none() {
+                if let Some(namesrv_addr) = self.client_config.namesrv_addr.as_deref() {
+                    // the update spawned on construction may not have run yet
+                    self.mq_client_api_impl
+                        .as_ref()
+                        .expect("mq_client_api_impl is None")
+                        .update_name_server_address_list(namesrv_addr)
+                        .await;
+                } else {
                     self.mq_client_api_impl
                         .as_mut()
                         .expect("mq_client_api_impl is None")
@@ -466,6 +474,8 @@ impl MQClientInstance {
             self.update_topic_route_info_from_name_server_topic(topic)
                 .await;
         }
+        // brokers that left every route must not get heartbeats or requests anymore
+        self.clean_offline_broker().await;
     }
 
     #[inline]
@@ -615,17 +625,26 @@ impl MQClientInstance {
                     }
                 }
 
-                // Update sub info
+                // Update sub info, consumers rebalance at once when the queue set of a
+                // subscribed topic changed instead of waiting for the next rebalance period
                 {
                     let consumer_table = self.consumer_table.read().await;
                     if !consumer_table.is_empty() {
                         let subscribe_info =
                             topic_route_data2topic_subscribe_info(topic, &topic_route_data);
-                        for (_, value) in consumer_table.iter() {
-                            value
+                        let mut subscribe_info_changed = false;
+                        for value in consumer_table.values() {
+                            subscribe_info_changed |= value
                                 .update_topic_subscribe_info(topic.clone(), &subscribe_info)
                                 .await;
                         }
+                        if subscribe_info_changed {
+                            info!(
+                                "the topic[{}] subscribe info changed, rebalance immediately",
+                                topic
+                            );
+                            self.re_balance_immediately();
+                        }
                     }
                 }
                 let clone_topic_route_data = TopicRouteData::from_existing(&topic_route_data);
@@ -645,26 +664,22 @@ impl MQClientInstance {
     }
 
     async fn is_need_update_topic_route_info(&self, topic: &CheetahString) -> bool {
-        let mut result = false;
         let producer_table = self.producer_table.read().await;
-        for (key, value) in producer_table.iter() {
-            if !result {
-                result = value.is_publish_topic_need_update(topic);
-                break;
-            }
-        }
-        if result {
+        if producer_table
+            .values()
+            .any(|value| value.is_publish_topic_need_update(topic))
+        {
             return true;
         }
+        drop(producer_table);
 
         let consumer_table = self.consumer_table.read().await;
-        for (key, value) in consumer_table.iter() {
-            if !result {
-                result = value.is_subscribe_topic_need_update(topic).await;
-                break;
+        for value in consumer_table.values() {
+            if value.is_subscribe_topic_need_update(topic).await {
+                return true;
             }
         }
-        result
+        false
     }
 
     pub async fn persist_all_consumer_offset(&mut self) {
@@ -674,38 +689,30 @@ impl MQClientInstance {
         }
     }
 
+    /// Handles a broker of `broker_name` announcing a new min broker id of its group.
+    /// `offline_broker_addr` stops serving under its old broker id at once, so producers do not
+    /// write to a demoted master while the routes are refreshed from the name server.
+    pub async fn on_min_broker_id_changed(
+        &mut self,
+        broker_name: &CheetahString,
+        offline_broker_addr: Option<&CheetahString>,
+    ) {
+        if let Some(offline_broker_addr) = offline_broker_addr {
+            let mut broker_addr_table = self.broker_addr_table.write().await;
+            remove_broker_addr(&mut broker_addr_table, broker_name, offline_broker_addr);
+        }
+        self.update_topic_route_info_from_name_server().await;
+    }
+
     pub async fn clean_offline_broker(&mut self) {
         let lock = self
             .lock_namesrv
             .try_lock_timeout(Duration::from_millis(LOCK_TIMEOUT_MILLIS))
             .await;
         if let Some(lock) = lock {
+            let route_broker_addrs = self.topic_route_broker_addrs().await;
             let mut broker_addr_table = self.broker_addr_table.write().await;
-            let mut updated_table = HashMap::with_capacity(broker_addr_table.len());
-            let mut broker_name_set = HashSet::new();
-            for (broker_name, one_table) in broker_addr_table.iter() {
-                let mut clone_addr_table = one_table.clone();
-                let mut remove_id_set = HashSet::new();
-                for (id, addr) in one_table.iter() {
-                    if !self.is_broker_addr_exist_in_topic_route_table(addr).await {
-                        remove_id_set.insert(*id);
-                    }
-                }
-                clone_addr_table.retain(|k, _| !remove_id_set.contains(k));
-                if clone_addr_table.is_empty() {
-                    info!(
-                        "the broker[{}] name's host is offline, remove it",
-                        broker_name
-                    );
-                    broker_name_set.insert(broker_name.clone());
-                } else {
-                    updated_table.insert(broker_name.clone(), clone_addr_table);
-                }
-            }
-            broker_addr_table.retain(|k, _| !broker_name_set.contains(k));
-            if !updated_table.is_empty() {
-                broker_addr_table.extend(updated_table);
-            }
+            remove_offline_brokers(&mut broker_addr_table, &route_broker_addrs);
         }
     }
     pub async fn send_heartbeat_to_all_broker_with_lock(&mut self) -> bool {
@@ -844,7 +851,7 @@ impl MQClientInstance {
         addr: &CheetahString,
         heartbeat_data: &HeartbeatData,
     ) -> bool {
-        if let Ok(version) = self
+        if let Ok((version, response_body)) = self
             .mq_client_api_impl
             .as_ref()
             .unwrap()
@@ -865,6 +872,10 @@ impl MQClientInstance {
                 map.insert(addr.clone(), version);
                 broker_version_table.insert(broker_name.clone(), map);
             }
+            drop(broker_version_table);
+            if let Some(response_body) = response_body {
+                self.apply_message_request_modes(&response_body).await;
+            }
 
             let times = self
                 .send_heartbeat_times_total
@@ -892,6 +903,25 @@ impl MQClientInstance {
         false
     }
 
+    /// Lets the consumers switch between POP and PULL as suggested by the broker, waking up the
+    /// rebalance service when any of them changed.
+    async fn apply_message_request_modes(&self, response_body: &HeartbeatResponseBody) {
+        let mut changed = false;
+        {
+            let consumer_table = self.consumer_table.read().await;
+            for mode in response_body.message_request_modes.iter() {
+                if let Some(consumer) = consumer_table.get(&mode.consumer_group) {
+                    changed |= consumer
+                        .apply_message_request_mode(&mode.topic, mode.mode)
+                        .await;
+                }
+            }
+        }
+        if changed {
+            self.rebalance_service.wakeup();
+        }
+    }
+
     async fn is_broker_in_name_server(&self, broker_name: &str) -> bool {
         let broker_addr_table = self.topic_route_table.read().await;
         for (_, value) in broker_addr_table.iter() {
@@ -921,6 +951,7 @@ impl MQClientInstance {
                 consume_from_where: value.consume_from_where(),
                 subscription_data_set: value.subscriptions(),
                 unit_mode: value.is_unit_mode(),
+                group_retry_policy: value.group_retry_policy(),
             };
             if !is_without_sub {
                 value.subscriptions().iter().for_each(|sub| {
@@ -961,7 +992,7 @@ impl MQClientInstance {
         for (key, value) in consumer_table.iter() {
             let subscription_inner = value.subscriptions();
             if subscription_inner.is_empty() {
-                return Ok(());
+                continue;
             }
             for subscription_data in subscription_inner.iter() {
                 if ExpressionType::is_tag_type(Some(subscription_data.expression_type.as_str())) {
@@ -1068,8 +1099,10 @@ impl MQClientInstance {
                 found = broker_addr.is_some();
             }
             if !found && !only_this_broker {
+                // The suggested broker may be gone, e.g. a master pointing a slow consumer at a
+                // slave that is offline, so fall back to any broker of the group.
                 if let Some((key, value)) = map.iter().next() {
-                    //broker_addr = Some(value.clone());
+                    broker_addr = Some(value);
                     slave = *key != mix_all::MASTER_ID;
                     found = !value.is_empty();
                 }
@@ -1110,7 +1143,9 @@ impl MQClientInstance {
     }
 
     pub async fn unregister_consumer(&mut self, group: impl Into<CheetahString>) {
-        self.unregister_client(None, Some(group.into())).await;
+        let group = group.into();
+        self.consumer_table.write().await.remove(&group);
+        self.unregister_client(None, Some(group)).await;
     }
     pub async fn unregister_producer(&mut self, group: impl Into<CheetahString>) {
         self.unregister_client(Some(group.into()), None).await;
@@ -1152,18 +1187,13 @@ impl MQClientInstance {
         }
     }
 
-    async fn is_broker_addr_exist_in_topic_route_table(&self, addr: &str) -> bool {
+    async fn topic_route_broker_addrs(&self) -> HashSet<CheetahString> {
         let topic_route_table = self.topic_route_table.read().await;
-        for (_, value) in topic_route_table.iter() {
-            for bd in value.broker_datas.iter() {
-                for (_, value) in bd.broker_addrs().iter() {
-                    if value.as_str() == addr {
-                        return true;
-                    }
-                }
-            }
-        }
-        false
+        topic_route_table
+            .values()
+            .flat_map(|route| route.broker_datas.iter())
+            .flat_map(|bd| bd.broker_addrs().values().cloned())
+            .collect()
     }
 
     /// Queries the assignment for a given topic.
@@ -1351,3 +1381,148 @@ pub fn topic_route_data2topic_subscribe_info(
     }
     mq_list
 }
+
+/// Removes the addresses of `broker_addr_table` that are in no topic route any more, and the
+/// brokers left without an address.
+fn remove_offline_brokers(
+    broker_addr_table: &mut HashMap<CheetahString, HashMap<u64, CheetahString>>,
+    route_broker_addrs: &HashSet<CheetahString>,
+) {
+    broker_addr_table.retain(|broker_name, broker_addrs| {
+        broker_addrs.retain(|_, addr| {
+            let online = route_broker_addrs.contains(addr);
+            if !online {
+                info!(
+                    "the broker addr[{} {}] is offline, remove it",
+                    broker_name, addr
+                );
+            }
+            online
+        });
+        if broker_addrs.is_empty() {
+            info!(
+                "the broker[{}] name's host is offline, remove it",
+                broker_name
+            );
+        }
+        !broker_addrs.is_empty()
+    });
+}
+
+/// Removes `broker_addr` from the addresses of `broker_name`, and the broker when it is left
+/// without an address.
+fn remove_broker_addr(
+    broker_addr_table: &mut HashMap<CheetahString, HashMap<u64, CheetahString>>,
+    broker_name: &CheetahString,
+    broker_addr: &CheetahString,
+) {
+    if let Some(broker_addrs) = broker_addr_table.get_mut(broker_name) {
+        broker_addrs.retain(|_, addr| addr != broker_addr);
+        if broker_addrs.is_empty() {
+            broker_addr_table.remove(broker_name);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rocketmq_remoting::protocol::heartbeat::consume_type::ConsumeType;
+
+    use super::*;
+    use crate::consumer::consumer_impl::default_mq_pull_consumer_impl::DefaultMQPullConsumerImpl;
+    use crate::consumer::default_mq_pull_consumer::PullConsumerConfig;
+
+    #[test]
+    fn registered_pull_consumer_is_part_of_the_heartbeat() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let _guard = runtime.enter();
+        let mut instance =
+            MQClientInstance::new_arc(ClientConfig::default(), 0, "127.0.0.1@test", None);
+        let group = CheetahString::from_static_str("pull_group");
+        let consumer_config = PullConsumerConfig {
+            consumer_group: group.clone(),
+            ..PullConsumerConfig::default()
+        };
+        let pull_consumer = ArcMut::new(DefaultMQPullConsumerImpl::new(
+            ClientConfig::default(),
+            ArcMut::new(consumer_config),
+            None,
+        ));
+        runtime.block_on(async {
+            assert!(
+                instance
+                    .register_consumer(
+                        &group,
+                        MQConsumerInnerImpl {
+                            default_mqpush_consumer_impl: None,
+                            default_mqpull_consumer_impl: Some(pull_consumer),
+                        },
+                    )
+                    .await
+            );
+            let heartbeat_data = instance.prepare_heartbeat_data(false).await;
+            let consumer_data = heartbeat_data.consumer_data_set.iter().next().unwrap();
+            assert_eq!(consumer_data.group_name, group);
+            assert_eq!(consumer_data.consume_type, ConsumeType::ConsumeActively);
+
+            instance.unregister_consumer(group.clone()).await;
+            let heartbeat_data = instance.prepare_heartbeat_data(false).await;
+            assert!(heartbeat_data.consumer_data_set.is_empty());
+        });
+    }
+
+    #[test]
+    fn offline_brokers_are_removed_from_broker_addr_table() {
+        let mut broker_addr_table = HashMap::from([
+            (
+                CheetahString::from_static_str("broker-a"),
+                HashMap::from([
+                    (0, CheetahString::from_static_str("10.0.0.1:10911")),
+                    (1, CheetahString::from_static_str("10.0.0.2:10911")),
+                ]),
+            ),
+            (
+                CheetahString::from_static_str("broker-b"),
+                HashMap::from([(0, CheetahString::from_static_str("10.0.0.3:10911"))]),
+            ),
+        ]);
+        let route_broker_addrs = HashSet::from([CheetahString::from_static_str("10.0.0.1:10911")]);
+
+        remove_offline_brokers(&mut broker_addr_table, &route_broker_addrs);
+
+        assert_eq!(broker_addr_table.len(), 1);
+        assert_eq!(
+            broker_addr_table["broker-a"],
+            HashMap::from([(0, CheetahString::from_static_str("10.0.0.1:10911"))])
+        );
+    }
+
+    #[test]
+    fn broker_addr_is_removed_from_its_broker() {
+        let mut broker_addr_table = HashMap::from([(
+            CheetahString::from_static_str("broker-a"),
+            HashMap::from([
+                (0, CheetahString::from_static_str("10.0.0.1:10911")),
+                (1, CheetahString::from_static_str("10.0.0.2:10911")),
+            ]),
+        )]);
+        let broker_name = CheetahString::from_static_str("broker-a");
+
+        remove_broker_addr(
+            &mut broker_addr_table,
+            &broker_name,
+            &CheetahString::from_static_str("10.0.0.1:10911"),
+        );
+        assert_eq!(
+            broker_addr_table["broker-a"],
+            HashMap::from([(1, CheetahString::from_static_str("10.0.0.2:10911"))])
+        );
+
+        remove_broker_addr(
+            &mut broker_addr_table,
+            &broker_name,
+            &CheetahString::from_static_str("10.0.0.2:10911"),
+        );
+        assert!(broker_addr_table.is_empty());
+    }
+}
@@ -16,8 +16,8 @@
  */
 pub(crate) mod check_forbidden_context;
 pub(crate) mod check_forbidden_hook;
-pub(crate) mod consume_message_context;
-pub(crate) mod consume_message_hook;
+pub mod consume_message_context;
+pub mod consume_message_hook;
 pub(crate) mod end_transaction_context;
 pub(crate) mod end_transaction_hook;
 pub(crate) mod filter_message_context;
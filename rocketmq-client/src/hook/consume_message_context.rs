@@ -24,6 +24,9 @@ use rocketmq_rust::ArcMut;
 
 use crate::base::access_channel::AccessChannel;
 
+/// What a [`ConsumeMessageHook`](crate::hook::consume_message_hook::ConsumeMessageHook) sees of
+/// one call of the message listener. `success`, `status`, `consume_rt` and the
+/// `CONSUME_CONTEXT_TYPE` prop are filled in before the hook runs after consumption.
 #[derive(Default)]
 pub struct ConsumeMessageContext<'a> {
     pub consumer_group: CheetahString,
@@ -35,4 +38,6 @@ pub struct ConsumeMessageContext<'a> {
     pub props: HashMap<CheetahString, CheetahString>,
     pub namespace: CheetahString,
     pub access_channel: Option<AccessChannel>,
+    /// Milliseconds the message listener took to consume `msg_list`.
+    pub consume_rt: u64,
 }
@@ -16,10 +16,14 @@
  */
 use crate::hook::consume_message_context::ConsumeMessageContext;
 
+/// Intercepts message consumption of a push consumer, e.g. for tracing, metrics or context
+/// propagation. Register it with `DefaultMQPushConsumer::register_consume_message_hook`.
 pub trait ConsumeMessageHook {
     fn hook_name(&self) -> &str;
 
+    /// Called before the message listener consumes the messages of `context`.
     fn consume_message_before(&self, context: Option<&mut ConsumeMessageContext>);
 
+    /// Called after the message listener returned, with the consume status and RT filled in.
     fn consume_message_after(&self, context: Option<&mut ConsumeMessageContext>);
 }
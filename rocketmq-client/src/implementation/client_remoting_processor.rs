@@ -31,6 +31,7 @@ use rocketmq_remoting::code::response_code::ResponseCode;
 use rocketmq_remoting::net::channel::Channel;
 use rocketmq_remoting::protocol::header::check_transaction_state_request_header::CheckTransactionStateRequestHeader;
 use rocketmq_remoting::protocol::header::consume_message_directly_result_request_header::ConsumeMessageDirectlyResultRequestHeader;
+use rocketmq_remoting::protocol::header::namesrv::brokerid_change_request_header::NotifyMinBrokerIdChangeRequestHeader;
 use rocketmq_remoting::protocol::header::notify_consumer_ids_changed_request_header::NotifyConsumerIdsChangedRequestHeader;
 use rocketmq_remoting::protocol::header::reply_message_request_header::ReplyMessageRequestHeader;
 use rocketmq_remoting::protocol::namespace_util::NamespaceUtil;
@@ -87,6 +88,9 @@ impl RequestProcessor for ClientRemotingProcessor {
             RequestCode::NotifyConsumerIdsChanged => {
                 self.notify_consumer_ids_changed(channel, ctx, request)
             }
+            RequestCode::NotifyMinBrokerIdChange => {
+                self.notify_min_broker_id_changed(channel, request)
+            }
 
             _ => {
                 info!("Unknown request code: {:?}", request_code);
@@ -215,6 +219,39 @@ impl ClientRemotingProcessor {
         Ok(None)
     }
 
+    fn notify_min_broker_id_changed(
+        &mut self,
+        channel: Channel,
+        request: RemotingCommand,
+    ) -> rocketmq_error::RocketMQResult<Option<RemotingCommand>> {
+        let request_header =
+            request.decode_command_custom_header::<NotifyMinBrokerIdChangeRequestHeader>()?;
+        let Some(broker_name) = request_header.broker_name else {
+            warn!(
+                "receive broker's notification[{}] without broker name, ignore it",
+                channel.remote_address()
+            );
+            return Ok(None);
+        };
+        info!(
+            "receive broker's notification[{}], the min broker id of {} changed to {:?}({:?}), \
+             offline broker: {:?}, update topic route immediately",
+            channel.remote_address(),
+            broker_name,
+            request_header.min_broker_id,
+            request_header.min_broker_addr,
+            request_header.offline_broker_addr
+        );
+
+        let mut client_instance = self.client_instance.clone();
+        tokio::spawn(async move {
+            client_instance
+                .on_min_broker_id_changed(&broker_name, request_header.offline_broker_addr.as_ref())
+                .await;
+        });
+        Ok(None)
+    }
+
     async fn check_transaction_state(
         &mut self,
         channel: Channel,
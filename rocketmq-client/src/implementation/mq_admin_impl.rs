@@ -14,6 +14,7 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use cheetah_string::CheetahString;
 use rocketmq_common::common::message::message_queue::MessageQueue;
 use rocketmq_error::mq_client_err;
 use rocketmq_remoting::protocol::namespace_util::NamespaceUtil;
@@ -92,6 +93,29 @@ impl MQAdminImpl {
         ))
     }
 
+    /// Fetches the queues of `topic` that can be consumed from the name server.
+    pub async fn fetch_subscribe_message_queues(
+        &mut self,
+        topic: &CheetahString,
+    ) -> rocketmq_error::RocketMQResult<Vec<MessageQueue>> {
+        let client = self.client.as_mut().expect("client is None");
+        let topic_route_data = client
+            .get_mq_client_api_impl()
+            .get_topic_route_info_from_name_server(topic, self.timeout_millis)
+            .await?;
+        if let Some(topic_route_data) = topic_route_data {
+            let message_queues =
+                mq_client_instance::topic_route_data2topic_subscribe_info(topic, &topic_route_data);
+            if !message_queues.is_empty() {
+                return Ok(message_queues.into_iter().collect());
+            }
+        }
+        mq_client_err!(format!(
+            "Can not find Message Queue for this topic, {} Namesrv return empty",
+            topic
+        ))
+    }
+
     pub async fn max_offset(&mut self, mq: &MessageQueue) -> rocketmq_error::RocketMQResult<i64> {
         let client = self.client.as_mut().expect("client is None");
         let broker_name = client.get_broker_name_from_message_queue(mq).await;
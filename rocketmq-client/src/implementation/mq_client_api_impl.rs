@@ -42,35 +42,48 @@ use rocketmq_error::mq_client_err;
 use rocketmq_error::MQBrokerErr;
 use rocketmq_error::RocketMQResult;
 use rocketmq_error::RocketmqError::MQClientBrokerError;
+use rocketmq_remoting::auth::acl_client_rpc_hook::AclClientRpcHook;
 use rocketmq_remoting::base::connection_net_event::ConnectionNetEvent;
 use rocketmq_remoting::clients::rocketmq_default_impl::RocketmqDefaultClient;
 use rocketmq_remoting::clients::RemotingClient;
 use rocketmq_remoting::code::request_code::RequestCode;
 use rocketmq_remoting::code::response_code::ResponseCode;
+use rocketmq_remoting::protocol::admin::consume_stats::ConsumeStats;
+use rocketmq_remoting::protocol::admin::pop_stats::PopStats;
+use rocketmq_remoting::protocol::admin::topic_disk_usage::TopicDiskUsage;
 use rocketmq_remoting::protocol::body::batch_ack_message_request_body::BatchAckMessageRequestBody;
+use rocketmq_remoting::protocol::body::broker_body::cluster_info::ClusterInfo;
 use rocketmq_remoting::protocol::body::check_client_request_body::CheckClientRequestBody;
 use rocketmq_remoting::protocol::body::get_consumer_listby_group_response_body::GetConsumerListByGroupResponseBody;
+use rocketmq_remoting::protocol::body::heartbeat_response_body::HeartbeatResponseBody;
 use rocketmq_remoting::protocol::body::query_assignment_request_body::QueryAssignmentRequestBody;
 use rocketmq_remoting::protocol::body::query_assignment_response_body::QueryAssignmentResponseBody;
 use rocketmq_remoting::protocol::body::request::lock_batch_request_body::LockBatchRequestBody;
 use rocketmq_remoting::protocol::body::response::lock_batch_response_body::LockBatchResponseBody;
 use rocketmq_remoting::protocol::body::set_message_request_mode_request_body::SetMessageRequestModeRequestBody;
+use rocketmq_remoting::protocol::body::topic::topic_list::TopicList;
 use rocketmq_remoting::protocol::body::unlock_batch_request_body::UnlockBatchRequestBody;
+use rocketmq_remoting::protocol::command_custom_header::CommandCustomHeader;
 use rocketmq_remoting::protocol::header::ack_message_request_header::AckMessageRequestHeader;
 use rocketmq_remoting::protocol::header::change_invisible_time_request_header::ChangeInvisibleTimeRequestHeader;
 use rocketmq_remoting::protocol::header::change_invisible_time_response_header::ChangeInvisibleTimeResponseHeader;
 use rocketmq_remoting::protocol::header::client_request_header::GetRouteInfoRequestHeader;
 use rocketmq_remoting::protocol::header::consumer_send_msg_back_request_header::ConsumerSendMsgBackRequestHeader;
+use rocketmq_remoting::protocol::header::delete_topic_request_header::DeleteTopicRequestHeader;
 use rocketmq_remoting::protocol::header::end_transaction_request_header::EndTransactionRequestHeader;
 use rocketmq_remoting::protocol::header::extra_info_util::ExtraInfoUtil;
+use rocketmq_remoting::protocol::header::get_consume_stats_request_header::GetConsumeStatsRequestHeader;
 use rocketmq_remoting::protocol::header::get_consumer_listby_group_request_header::GetConsumerListByGroupRequestHeader;
 use rocketmq_remoting::protocol::header::get_max_offset_request_header::GetMaxOffsetRequestHeader;
 use rocketmq_remoting::protocol::header::get_max_offset_response_header::GetMaxOffsetResponseHeader;
+use rocketmq_remoting::protocol::header::get_topic_disk_usage_request_header::GetTopicDiskUsageRequestHeader;
 use rocketmq_remoting::protocol::header::heartbeat_request_header::HeartbeatRequestHeader;
 use rocketmq_remoting::protocol::header::lock_batch_mq_request_header::LockBatchMqRequestHeader;
 use rocketmq_remoting::protocol::header::message_operation_header::send_message_request_header::SendMessageRequestHeader;
 use rocketmq_remoting::protocol::header::message_operation_header::send_message_request_header_v2::SendMessageRequestHeaderV2;
 use rocketmq_remoting::protocol::header::message_operation_header::send_message_response_header::SendMessageResponseHeader;
+use rocketmq_remoting::protocol::header::namesrv::topic_operation_header::DeleteTopicFromNamesrvRequestHeader;
+use rocketmq_remoting::protocol::header::namesrv::topic_operation_header::GetTopicsByClusterRequestHeader;
 use rocketmq_remoting::protocol::header::pop_message_request_header::PopMessageRequestHeader;
 use rocketmq_remoting::protocol::header::pop_message_response_header::PopMessageResponseHeader;
 use rocketmq_remoting::protocol::header::pull_message_request_header::PullMessageRequestHeader;
@@ -152,6 +165,10 @@ impl MQClientAPIImpl {
         if let Some(hook) = rpc_hook {
             default_client.register_rpc_hook(hook);
         }
+        if let Some(credentials) = client_config.session_credentials.clone() {
+            default_client
+                .register_rpc_hook(Arc::new(Box::new(AclClientRpcHook::new(credentials))));
+        }
 
         MQClientAPIImpl {
             remoting_client: ArcMut::new(default_client),
@@ -283,6 +300,253 @@ impl MQClientAPIImpl {
         }
     }
 
+    pub async fn get_broker_cluster_info(
+        &self,
+        timeout_millis: u64,
+    ) -> rocketmq_error::RocketMQResult<ClusterInfo> {
+        let request = RemotingCommand::create_remoting_command(RequestCode::GetBrokerClusterInfo);
+        self.invoke_name_server_for_body(request, timeout_millis)
+            .await
+    }
+
+    pub async fn get_topic_list_from_name_server(
+        &self,
+        timeout_millis: u64,
+    ) -> rocketmq_error::RocketMQResult<TopicList> {
+        let request =
+            RemotingCommand::create_remoting_command(RequestCode::GetAllTopicListFromNameserver);
+        self.invoke_name_server_for_body(request, timeout_millis)
+            .await
+    }
+
+    pub async fn get_topics_by_cluster(
+        &self,
+        cluster: &str,
+        timeout_millis: u64,
+    ) -> rocketmq_error::RocketMQResult<TopicList> {
+        let request = RemotingCommand::create_request_command(
+            RequestCode::GetTopicsByCluster,
+            GetTopicsByClusterRequestHeader::new(cluster),
+        );
+        self.invoke_name_server_for_body(request, timeout_millis)
+            .await
+    }
+
+    pub async fn get_system_topic_list(
+        &self,
+        timeout_millis: u64,
+    ) -> rocketmq_error::RocketMQResult<TopicList> {
+        let request =
+            RemotingCommand::create_remoting_command(RequestCode::GetSystemTopicListFromNs);
+        self.invoke_name_server_for_body(request, timeout_millis)
+            .await
+    }
+
+    pub async fn delete_topic_in_broker(
+        &self,
+        addr: &str,
+        topic: &str,
+        timeout_millis: u64,
+    ) -> rocketmq_error::RocketMQResult<()> {
+        let request_header = DeleteTopicRequestHeader {
+            topic: CheetahString::from_slice(topic),
+            topic_request_header: None,
+        };
+        let request = RemotingCommand::create_request_command(
+            RequestCode::DeleteTopicInBroker,
+            request_header,
+        );
+        let response = self
+            .remoting_client
+            .invoke_async(
+                Some(
+                    mix_all::broker_vip_channel(self.client_config.vip_channel_enabled, addr)
+                        .as_ref(),
+                ),
+                request,
+                timeout_millis,
+            )
+            .await?;
+        if ResponseCode::from(response.code()) == ResponseCode::Success {
+            return Ok(());
+        }
+        client_broker_err!(
+            response.code(),
+            response.remark().map_or("".to_string(), |s| s.to_string()),
+            addr.to_string()
+        )
+    }
+
+    pub async fn get_consume_stats(
+        &self,
+        addr: &str,
+        consumer_group: &str,
+        topic: Option<&str>,
+        timeout_millis: u64,
+    ) -> rocketmq_error::RocketMQResult<ConsumeStats> {
+        self.invoke_broker_for_body(
+            addr,
+            RequestCode::GetConsumeStats,
+            consume_stats_request_header(consumer_group, topic),
+            timeout_millis,
+        )
+        .await
+    }
+
+    /// Queries the pop consumption progress of `consumer_group` on the broker at `addr`.
+    pub async fn get_pop_stats(
+        &self,
+        addr: &str,
+        consumer_group: &str,
+        topic: Option<&str>,
+        timeout_millis: u64,
+    ) -> rocketmq_error::RocketMQResult<PopStats> {
+        self.invoke_broker_for_body(
+            addr,
+            RequestCode::GetPopStats,
+            consume_stats_request_header(consumer_group, topic),
+            timeout_millis,
+        )
+        .await
+    }
+
+    /// Updates the config of the broker at `addr` with `properties`.
+    pub async fn update_broker_config(
+        &self,
+        addr: &str,
+        properties: &HashMap<CheetahString, CheetahString>,
+        timeout_millis: u64,
+    ) -> rocketmq_error::RocketMQResult<()> {
+        let request = RemotingCommand::create_remoting_command(RequestCode::UpdateBrokerConfig)
+            .set_body(mix_all::properties_to_string(properties));
+        let response = self
+            .remoting_client
+            .invoke_async(
+                Some(
+                    mix_all::broker_vip_channel(self.client_config.vip_channel_enabled, addr)
+                        .as_ref(),
+                ),
+                request,
+                timeout_millis,
+            )
+            .await?;
+        if ResponseCode::from(response.code()) == ResponseCode::Success {
+            return Ok(());
+        }
+        client_broker_err!(
+            response.code(),
+            response.remark().map_or("".to_string(), |s| s.to_string()),
+            addr.to_string()
+        )
+    }
+
+    /// Queries the disk usage of `topic` on the broker at `addr`, of all its topics when `topic`
+    /// is `None`.
+    pub async fn get_topic_disk_usage(
+        &self,
+        addr: &str,
+        topic: Option<&str>,
+        timeout_millis: u64,
+    ) -> rocketmq_error::RocketMQResult<TopicDiskUsage> {
+        self.invoke_broker_for_body(
+            addr,
+            RequestCode::GetTopicDiskUsage,
+            GetTopicDiskUsageRequestHeader {
+                topic: topic.map(CheetahString::from),
+            },
+            timeout_millis,
+        )
+        .await
+    }
+
+    /// Sends a request to the broker at `addr` and decodes the body of a successful response.
+    async fn invoke_broker_for_body<H, T>(
+        &self,
+        addr: &str,
+        request_code: RequestCode,
+        request_header: H,
+        timeout_millis: u64,
+    ) -> rocketmq_error::RocketMQResult<T>
+    where
+        H: CommandCustomHeader + Sync + Send + 'static,
+        T: RemotingDeserializable<Output = T>,
+    {
+        let request = RemotingCommand::create_request_command(request_code, request_header);
+        let response = self
+            .remoting_client
+            .invoke_async(
+                Some(
+                    mix_all::broker_vip_channel(self.client_config.vip_channel_enabled, addr)
+                        .as_ref(),
+                ),
+                request,
+                timeout_millis,
+            )
+            .await?;
+        if ResponseCode::from(response.code()) == ResponseCode::Success {
+            if let Some(body) = response.body() {
+                return T::decode(body.as_ref());
+            }
+        }
+        client_broker_err!(
+            response.code(),
+            response.remark().map_or("".to_string(), |s| s.to_string()),
+            addr.to_string()
+        )
+    }
+
+    pub async fn delete_topic_in_name_server(
+        &self,
+        addr: &str,
+        cluster_name: Option<&str>,
+        topic: &str,
+        timeout_millis: u64,
+    ) -> rocketmq_error::RocketMQResult<()> {
+        let request = RemotingCommand::create_request_command(
+            RequestCode::DeleteTopicInNamesrv,
+            DeleteTopicFromNamesrvRequestHeader::new(topic, cluster_name),
+        );
+        let response = self
+            .remoting_client
+            .invoke_async(
+                Some(&CheetahString::from_slice(addr)),
+                request,
+                timeout_millis,
+            )
+            .await?;
+        if ResponseCode::from(response.code()) == ResponseCode::Success {
+            return Ok(());
+        }
+        mq_client_err!(
+            response.code(),
+            response.remark().cloned().unwrap_or_default().to_string()
+        )
+    }
+
+    /// Sends `request` to a name server and decodes the body of a successful response.
+    async fn invoke_name_server_for_body<T>(
+        &self,
+        request: RemotingCommand,
+        timeout_millis: u64,
+    ) -> rocketmq_error::RocketMQResult<T>
+    where
+        T: RemotingDeserializable<Output = T>,
+    {
+        let response = self
+            .remoting_client
+            .invoke_async(None, request, timeout_millis)
+            .await?;
+        if ResponseCode::from(response.code()) == ResponseCode::Success {
+            if let Some(body) = response.body() {
+                return T::decode(body.as_ref());
+            }
+        }
+        mq_client_err!(
+            response.code(),
+            response.remark().cloned().unwrap_or_default().to_string()
+        )
+    }
+
     pub fn get_name_server_address_list(&self) -> &[CheetahString] {
         self.remoting_client.get_name_server_address_list()
     }
@@ -551,6 +815,15 @@ impl MQClientAPIImpl {
             ResponseCode::FlushSlaveTimeout => SendStatus::FlushSlaveTimeout,
             ResponseCode::SlaveNotAvailable => SendStatus::SlaveNotAvailable,
             ResponseCode::Success => SendStatus::SendOk,
+            ResponseCode::SystemBusy | ResponseCode::FlowControl => {
+                return Err(rocketmq_error::RocketmqError::BrokerFlowControlError(
+                    rocketmq_error::MQBrokerErr::new_with_broker(
+                        response.code(),
+                        response.remark().map_or("".to_string(), |s| s.to_string()),
+                        addr.to_string(),
+                    ),
+                ))
+            }
             _ => {
                 return Err(rocketmq_error::RocketmqError::MQClientBrokerError(
                     rocketmq_error::MQBrokerErr::new_with_broker(
@@ -684,7 +957,7 @@ impl MQClientAPIImpl {
         addr: &CheetahString,
         heartbeat_data: &HeartbeatData,
         timeout_millis: u64,
-    ) -> rocketmq_error::RocketMQResult<i32> {
+    ) -> rocketmq_error::RocketMQResult<(i32, Option<HeartbeatResponseBody>)> {
         let request = RemotingCommand::create_request_command(
             RequestCode::HeartBeat,
             HeartbeatRequestHeader::default(),
@@ -700,7 +973,11 @@ impl MQClientAPIImpl {
             .invoke_async(Some(addr), request, timeout_millis)
             .await?;
         if ResponseCode::from(response.code()) == ResponseCode::Success {
-            return Ok(response.version());
+            let body = match response.body() {
+                Some(body) if !body.is_empty() => Some(HeartbeatResponseBody::decode(body)?),
+                _ => None,
+            };
+            return Ok((response.version(), body));
         }
         client_broker_err!(
             response.code(),
@@ -903,11 +1180,14 @@ impl MQClientAPIImpl {
     where
         PCB: PullCallback + 'static,
     {
-        let request = if PullSysFlag::has_lite_pull_flag(request_header.sys_flag as u32) {
+        let mut request = if PullSysFlag::has_lite_pull_flag(request_header.sys_flag as u32) {
             RemotingCommand::create_request_command(RequestCode::LitePullMessage, request_header)
         } else {
             RemotingCommand::create_request_command(RequestCode::PullMessage, request_header)
         };
+        if this.client_config.accept_compressed_pull_body {
+            request.mark_accept_compressed_body_ref();
+        }
         match communication_mode {
             CommunicationMode::Sync => {
                 let result_ext = this
@@ -1353,6 +1633,25 @@ impl MQClientAPIImpl {
         timeout_millis: u64,
         ack_callback: impl AckCallback,
     ) -> rocketmq_error::RocketMQResult<()> {
+        match self
+            .change_invisible_time(broker_name, addr, request_header, timeout_millis)
+            .await
+        {
+            Ok(ack_result) => ack_callback.on_success(ack_result),
+            Err(e) => ack_callback.on_exception(Box::new(e)),
+        }
+        Ok(())
+    }
+
+    /// Changes the invisible time of a popped message, the extra info of the returned result is
+    /// the new receipt handle of the message.
+    pub async fn change_invisible_time(
+        &self,
+        broker_name: &CheetahString,
+        addr: &CheetahString,
+        request_header: ChangeInvisibleTimeRequestHeader,
+        timeout_millis: u64,
+    ) -> rocketmq_error::RocketMQResult<AckResult> {
         let offset = request_header.offset;
         let topic = request_header.topic.clone();
         let queue_id = request_header.queue_id;
@@ -1360,46 +1659,38 @@ impl MQClientAPIImpl {
             RequestCode::ChangeMessageInvisibleTime,
             request_header,
         );
-        match self
+        let response = self
             .remoting_client
             .invoke_async(Some(addr), request, timeout_millis)
-            .await
-        {
-            Ok(response) => {
-                let response_header =
-                    response.decode_command_custom_header::<ChangeInvisibleTimeResponseHeader>()?;
-                let ack_result = if ResponseCode::from(response.code()) == ResponseCode::Success {
-                    AckResult {
-                        status: AckStatus::Ok,
-                        pop_time: response_header.pop_time as i64,
-                        extra_info: CheetahString::from_string(format!(
-                            "{}{}{}",
-                            ExtraInfoUtil::build_extra_info(
-                                offset,
-                                response_header.pop_time as i64,
-                                response_header.invisible_time,
-                                response_header.revive_qid,
-                                &topic,
-                                broker_name,
-                                queue_id,
-                            ),
-                            MessageConst::KEY_SEPARATOR,
-                            offset
-                        )),
-                    }
-                } else {
-                    AckResult {
-                        status: AckStatus::NotExist,
-                        ..Default::default()
-                    }
-                };
-                ack_callback.on_success(ack_result);
+            .await?;
+        let response_header =
+            response.decode_command_custom_header::<ChangeInvisibleTimeResponseHeader>()?;
+        let ack_result = if ResponseCode::from(response.code()) == ResponseCode::Success {
+            AckResult {
+                status: AckStatus::Ok,
+                pop_time: response_header.pop_time as i64,
+                extra_info: CheetahString::from_string(format!(
+                    "{}{}{}",
+                    ExtraInfoUtil::build_extra_info(
+                        offset,
+                        response_header.pop_time as i64,
+                        response_header.invisible_time,
+                        response_header.revive_qid,
+                        &topic,
+                        broker_name,
+                        queue_id,
+                    ),
+                    MessageConst::KEY_SEPARATOR,
+                    offset
+                )),
             }
-            Err(e) => {
-                ack_callback.on_exception(Box::new(e));
+        } else {
+            AckResult {
+                status: AckStatus::NotExist,
+                ..Default::default()
             }
         };
-        Ok(())
+        Ok(ack_result)
     }
 
     pub async fn pop_message_async<PC>(
@@ -1415,8 +1706,11 @@ impl MQClientAPIImpl {
     {
         let topic = request_header.topic.clone();
         let order = request_header.order.unwrap_or_default();
-        let request =
+        let mut request =
             RemotingCommand::create_request_command(RequestCode::PopMessage, request_header);
+        if self.client_config.accept_compressed_pull_body {
+            request.mark_accept_compressed_body_ref();
+        }
         match self
             .remoting_client
             .invoke_async(Some(addr), request, timeout_millis)
@@ -1701,6 +1995,18 @@ impl MQClientAPIImpl {
         .await
     }
 
+    /// Acks a popped message and waits for the broker's answer.
+    pub async fn ack_message(
+        &self,
+        addr: &CheetahString,
+        request_header: AckMessageRequestHeader,
+        timeout_millis: u64,
+    ) -> rocketmq_error::RocketMQResult<AckResult> {
+        let request =
+            RemotingCommand::create_request_command(RequestCode::AckMessage, request_header);
+        self.invoke_ack(addr, request, timeout_millis).await
+    }
+
     pub(self) async fn ack_message_async_inner(
         &self,
         addr: &CheetahString,
@@ -1715,32 +2021,33 @@ impl MQClientAPIImpl {
             let body = request_body.unwrap();
             RemotingCommand::new_request(RequestCode::BatchAckMessage, body.encode()?)
         };
-        match self
-            .remoting_client
-            .invoke_async(Some(addr), request, timeout_millis)
-            .await
-        {
-            Ok(response) => {
-                let response_code = ResponseCode::from(response.code());
-                let ack_result = if response_code == ResponseCode::Success {
-                    AckResult {
-                        status: AckStatus::Ok,
-                        ..Default::default()
-                    }
-                } else {
-                    AckResult {
-                        status: AckStatus::NotExist,
-                        ..Default::default()
-                    }
-                };
-                ack_callback.on_success(ack_result);
-            }
-            Err(e) => {
-                ack_callback.on_exception(Box::new(e));
-            }
+        match self.invoke_ack(addr, request, timeout_millis).await {
+            Ok(ack_result) => ack_callback.on_success(ack_result),
+            Err(e) => ack_callback.on_exception(Box::new(e)),
         }
         Ok(())
     }
+
+    async fn invoke_ack(
+        &self,
+        addr: &CheetahString,
+        request: RemotingCommand,
+        timeout_millis: u64,
+    ) -> rocketmq_error::RocketMQResult<AckResult> {
+        let response = self
+            .remoting_client
+            .invoke_async(Some(addr), request, timeout_millis)
+            .await?;
+        let status = if ResponseCode::from(response.code()) == ResponseCode::Success {
+            AckStatus::Ok
+        } else {
+            AckStatus::NotExist
+        };
+        Ok(AckResult {
+            status,
+            ..Default::default()
+        })
+    }
 }
 
 fn build_queue_offset_sorted_map(
@@ -1802,3 +2109,14 @@ fn build_queue_offset_sorted_map(
     }
     Ok(sort_map)
 }
+
+fn consume_stats_request_header(
+    consumer_group: &str,
+    topic: Option<&str>,
+) -> GetConsumeStatsRequestHeader {
+    GetConsumeStatsRequestHeader {
+        consumer_group: CheetahString::from_slice(consumer_group),
+        topic: topic.map(CheetahString::from_slice).unwrap_or_default(),
+        topic_request_header: None,
+    }
+}
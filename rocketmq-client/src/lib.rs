@@ -27,7 +27,7 @@ pub mod client_error;
 pub mod common;
 pub mod consumer;
 pub mod factory;
-mod hook;
+pub mod hook;
 pub mod implementation;
 mod latency;
 pub mod producer;
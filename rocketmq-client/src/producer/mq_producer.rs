@@ -64,7 +64,9 @@ pub trait MQProducerLocal {
     /// # Returns
     ///
     /// * `rocketmq_error::RocketMQResult<SendResult>` - A result containing the send result or an
-    ///   error.
+    ///   error. When the brokers stay busy through every retry the error is a
+    ///   `RocketmqError::BrokerFlowControlError`, which callers can check with
+    ///   `RocketmqError::is_flow_control` to back off and send again.
     async fn send<M>(&mut self, msg: M) -> rocketmq_error::RocketMQResult<SendResult>
     where
         M: MessageTrait + Clone + Send + Sync;
@@ -235,10 +235,19 @@ impl DefaultMQProducerImpl {
     {
         self.make_sure_state_ok()?;
         Validators::check_message(Some(&msg), self.producer_config.as_ref())?;
-        self.send_default_impl(
+        if msg.get_topic() != mq.get_topic() {
+            return mq_client_err!(format!(
+                "message topic [{}] is not equal with message queue topic [{}]",
+                msg.get_topic(),
+                mq.get_topic()
+            ));
+        }
+        self.send_kernel_impl(
             &mut msg,
+            &mq,
             CommunicationMode::Oneway,
             None,
+            None,
             self.producer_config.send_msg_timeout() as u64,
         )
         .await?;
@@ -791,7 +800,8 @@ impl DefaultMQProducerImpl {
                                     exception = Some(err);
                                     continue;
                                 }
-                                rocketmq_error::RocketmqError::MQClientBrokerError(ref er) => {
+                                rocketmq_error::RocketmqError::MQClientBrokerError(ref er)
+                                | rocketmq_error::RocketmqError::BrokerFlowControlError(ref er) => {
                                     end_timestamp = Instant::now();
                                     let elapsed =
                                         (end_timestamp - begin_timestamp_prev).as_millis() as u64;
@@ -860,6 +870,17 @@ impl DefaultMQProducerImpl {
                     ));
                 }
 
+                // Every attempt failed, the route may point at brokers that are gone, so the
+                // next send should not wait for the periodic route refresh
+                if exception.is_some() {
+                    self.client_instance
+                        .as_ref()
+                        .unwrap()
+                        .mut_from_ref()
+                        .update_topic_route_info_from_name_server_topic(&topic)
+                        .await;
+                }
+
                 let info = format!(
                     "Send [{}] times, still failed, cost [{}]ms, Topic:{}, BrokersSent: {} {}",
                     times_total,
@@ -880,6 +901,8 @@ impl DefaultMQProducerImpl {
                         rocketmq_error::RocketmqError::MQClientBrokerError(_) => {
                             mq_client_err!(ClientErrorCode::BROKER_NOT_EXIST_EXCEPTION, info)
                         }
+                        // keep the flow control type so callers can back off and send again
+                        rocketmq_error::RocketmqError::BrokerFlowControlError(_) => Err(err),
                         rocketmq_error::RocketmqError::RequestTimeoutError(_) => {
                             mq_client_err!(ClientErrorCode::BROKER_NOT_EXIST_EXCEPTION, info)
                         }
@@ -1934,9 +1957,9 @@ impl DefaultMQProducerImpl {
         local_transaction_state: LocalTransactionState,
     ) -> rocketmq_error::RocketMQResult<()> {
         let id = if let Some(ref offset_msg_id) = send_result.offset_msg_id {
-            MessageDecoder::decode_message_id(offset_msg_id)
+            MessageDecoder::decode_message_id(offset_msg_id)?
         } else {
-            MessageDecoder::decode_message_id(send_result.msg_id.as_ref().unwrap())
+            MessageDecoder::decode_message_id(send_result.msg_id.as_ref().unwrap())?
         };
         let transaction_id = send_result.transaction_id.clone();
         let queue = self
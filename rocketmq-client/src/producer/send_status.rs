@@ -21,12 +21,20 @@ use serde::Deserializer;
 use serde::Serialize;
 use serde::Serializer;
 
+/// How durably the broker stored a sent message.
+///
+/// Every status but `SendOk` means the message is on the master, but not as durable as the
+/// message asked for with `wait_store_msg_ok`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum SendStatus {
+    /// The message is stored, flushed and replicated as required.
     #[default]
     SendOk,
+    /// The master did not flush the message to disk in time on a `SYNC_FLUSH` broker.
     FlushDiskTimeout,
+    /// The slaves did not acknowledge the message in time on a `SYNC_MASTER` broker.
     FlushSlaveTimeout,
+    /// No slave could replicate the message on a `SYNC_MASTER` broker.
     SlaveNotAvailable,
 }
 
@@ -39,7 +39,7 @@ pub struct TransactionProducerConfig {
     pub check_runtime: Option<Arc<RocketMQRuntime>>,
 }
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct TransactionMQProducer {
     default_producer: DefaultMQProducer,
     transaction_producer_config: TransactionProducerConfig,
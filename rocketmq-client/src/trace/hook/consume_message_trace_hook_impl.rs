@@ -16,9 +16,20 @@
  */
 use std::sync::Arc;
 
+use cheetah_string::CheetahString;
+use rocketmq_common::common::message::MessageConst;
+use rocketmq_common::common::message::MessageTrait;
+use rocketmq_common::common::mix_all;
+use rocketmq_common::TimeUtils::get_current_millis;
+use rocketmq_remoting::protocol::namespace_util::NamespaceUtil;
+
+use crate::consumer::listener::consume_return_type::ConsumeReturnType;
 use crate::hook::consume_message_context::ConsumeMessageContext;
 use crate::hook::consume_message_hook::ConsumeMessageHook;
+use crate::trace::trace_bean::TraceBean;
+use crate::trace::trace_context::TraceContext;
 use crate::trace::trace_dispatcher::TraceDispatcher;
+use crate::trace::trace_type::TraceType;
 
 pub struct ConsumeMessageTraceHookImpl {
     trace_dispatcher: Arc<Box<dyn TraceDispatcher + Send + Sync>>,
@@ -32,14 +43,188 @@ impl ConsumeMessageTraceHookImpl {
 
 impl ConsumeMessageHook for ConsumeMessageTraceHookImpl {
     fn hook_name(&self) -> &str {
-        todo!()
+        "ConsumeMessageTraceHook"
     }
 
     fn consume_message_before(&self, context: Option<&mut ConsumeMessageContext>) {
-        todo!()
+        let Some(context) = context else {
+            return;
+        };
+        if context.msg_list.is_empty() {
+            return;
+        }
+        let mut trace_context = TraceContext::new();
+        trace_context.trace_type = Some(TraceType::SubBefore);
+        trace_context.group_name =
+            NamespaceUtil::without_namespace(context.consumer_group.as_str()).into();
+        let mut beans = Vec::with_capacity(context.msg_list.len());
+        for msg in context.msg_list {
+            let trace_on = msg.get_property(&CheetahString::from_static_str(
+                MessageConst::PROPERTY_TRACE_SWITCH,
+            ));
+            if trace_on.is_some_and(|trace_on| trace_on == "false") {
+                continue;
+            }
+            if let Some(region_id) = msg.get_property(&CheetahString::from_static_str(
+                MessageConst::PROPERTY_MSG_REGION,
+            )) {
+                trace_context.region_id = region_id;
+            }
+            beans.push(TraceBean {
+                topic: NamespaceUtil::without_namespace(msg.get_topic().as_str()).into(),
+                msg_id: msg.msg_id().clone(),
+                tags: msg.get_tags().unwrap_or_default(),
+                keys: msg.get_keys().unwrap_or_default(),
+                store_time: msg.store_timestamp(),
+                body_length: msg.store_size(),
+                retry_times: msg.reconsume_times(),
+                ..Default::default()
+            });
+        }
+        if beans.is_empty() {
+            return;
+        }
+        trace_context.trace_beans = Some(beans);
+        trace_context.time_stamp = get_current_millis();
+        self.trace_dispatcher.append(&trace_context);
+        context.mq_trace_context = Some(Arc::new(Box::new(trace_context)));
     }
 
     fn consume_message_after(&self, context: Option<&mut ConsumeMessageContext>) {
-        todo!()
+        let Some(context) = context else {
+            return;
+        };
+        if context.msg_list.is_empty() {
+            return;
+        }
+        let Some(sub_before_context) = context
+            .mq_trace_context
+            .as_ref()
+            .and_then(|trace_context| trace_context.downcast_ref::<TraceContext>())
+        else {
+            return;
+        };
+        let Some(trace_beans) = sub_before_context.trace_beans.as_ref() else {
+            return;
+        };
+        let cost_time = get_current_millis().saturating_sub(sub_before_context.time_stamp)
+            / context.msg_list.len() as u64;
+        let context_code = context
+            .props
+            .get(mix_all::CONSUME_CONTEXT_TYPE)
+            .and_then(|context_type| context_type.parse::<ConsumeReturnType>().ok())
+            .map_or(0, i32::from);
+        let sub_after_context = TraceContext {
+            trace_type: Some(TraceType::SubAfter),
+            region_id: sub_before_context.region_id.clone(),
+            group_name: sub_before_context.group_name.clone(),
+            request_id: sub_before_context.request_id.clone(),
+            access_channel: context.access_channel,
+            is_success: context.success,
+            cost_time: cost_time as i32,
+            context_code,
+            trace_beans: Some(trace_beans.clone()),
+            ..TraceContext::new()
+        };
+        self.trace_dispatcher.append(&sub_after_context);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::Any;
+    use std::collections::HashMap;
+
+    use parking_lot::Mutex;
+    use rocketmq_common::common::message::message_ext::MessageExt;
+    use rocketmq_rust::ArcMut;
+
+    use super::*;
+    use crate::base::access_channel::AccessChannel;
+
+    // trace type, success, context code and bean count of every appended trace context
+    type Appended = Vec<(Option<TraceType>, bool, i32, usize)>;
+
+    #[derive(Default)]
+    struct RecordingDispatcher {
+        appended: Arc<Mutex<Appended>>,
+    }
+
+    impl TraceDispatcher for RecordingDispatcher {
+        fn start(
+            &self,
+            _name_srv_addr: &str,
+            _access_channel: AccessChannel,
+        ) -> rocketmq_error::RocketMQResult<()> {
+            Ok(())
+        }
+
+        fn append(&self, ctx: &dyn Any) -> bool {
+            let ctx = ctx.downcast_ref::<TraceContext>().unwrap();
+            self.appended.lock().push((
+                ctx.trace_type,
+                ctx.is_success,
+                ctx.context_code,
+                ctx.trace_beans.as_ref().map_or(0, Vec::len),
+            ));
+            true
+        }
+
+        fn flush(&self) -> rocketmq_error::RocketMQResult<()> {
+            Ok(())
+        }
+
+        fn shutdown(&self) {}
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_mut_any(&mut self) -> &mut dyn Any {
+            self
+        }
+    }
+
+    #[test]
+    fn traces_before_and_after_consumption() {
+        let dispatcher = RecordingDispatcher::default();
+        let appended = dispatcher.appended.clone();
+        let hook = ConsumeMessageTraceHookImpl::new(Arc::new(Box::new(dispatcher)));
+
+        let mut traced = MessageExt::default();
+        traced.set_topic(CheetahString::from_static_str("topic"));
+        let mut untraced = MessageExt::default();
+        untraced.put_property(
+            CheetahString::from_static_str(MessageConst::PROPERTY_TRACE_SWITCH),
+            CheetahString::from_static_str("false"),
+        );
+        let msgs = vec![ArcMut::new(traced), ArcMut::new(untraced)];
+        let mut context = ConsumeMessageContext {
+            consumer_group: CheetahString::from_static_str("group"),
+            msg_list: &msgs,
+            props: HashMap::new(),
+            ..Default::default()
+        };
+
+        hook.consume_message_before(Some(&mut context));
+        context.success = false;
+        context.props.insert(
+            CheetahString::from_static_str(mix_all::CONSUME_CONTEXT_TYPE),
+            ConsumeReturnType::Failed.to_string().into(),
+        );
+        hook.consume_message_after(Some(&mut context));
+
+        assert_eq!(
+            *appended.lock(),
+            vec![
+                (Some(TraceType::SubBefore), true, 0, 1),
+                (
+                    Some(TraceType::SubAfter),
+                    false,
+                    i32::from(ConsumeReturnType::Failed),
+                    1
+                ),
+            ]
+        );
     }
 }
@@ -208,9 +208,8 @@ impl AttributeUtil {
 
 #[cfg(test)]
 mod tests {
-    use std::sync::Arc;
-
     use super::*;
+    use crate::common::attribute::topic_attributes::TopicAttributes;
 
     #[test]
     fn alter_current_attributes_create_only_supports_add() {
@@ -268,4 +267,64 @@ mod tests {
         assert!(result.is_err());
         assert_eq!(result.unwrap_err().to_string(), "Wrong format key: key1");
     }
+
+    #[test]
+    fn alter_current_attributes_unchangeable_attribute() {
+        let mut current_attributes = HashMap::new();
+        current_attributes.insert("queue.type".into(), "SimpleCQ".into());
+        let mut new_attributes = HashMap::new();
+        new_attributes.insert("+queue.type".into(), "BatchCQ".into());
+
+        let result = AttributeUtil::alter_current_attributes(
+            false,
+            TopicAttributes::all(),
+            &current_attributes,
+            &new_attributes,
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Attempt to update an unchangeable attribute. Key: queue.type"
+        );
+    }
+
+    #[test]
+    fn alter_current_attributes_verification_failed() {
+        let current_attributes = HashMap::new();
+        let mut new_attributes = HashMap::new();
+        new_attributes.insert("+message.type".into(), "UNKNOWN".into());
+
+        let result = AttributeUtil::alter_current_attributes(
+            true,
+            TopicAttributes::all(),
+            &current_attributes,
+            &new_attributes,
+        );
+        assert!(matches!(
+            result,
+            Err(AttributeError::AttributeVerificationFailed(_))
+        ));
+    }
+
+    #[test]
+    fn alter_current_attributes_add_update_delete() {
+        let mut current_attributes = HashMap::new();
+        current_attributes.insert("message.type".into(), "NORMAL".into());
+        current_attributes.insert("reserve.time".into(), "72".into());
+        let mut new_attributes = HashMap::new();
+        new_attributes.insert("+message.type".into(), "FIFO".into());
+        new_attributes.insert("-reserve.time".into(), "".into());
+
+        let result = AttributeUtil::alter_current_attributes(
+            false,
+            TopicAttributes::all(),
+            &current_attributes,
+            &new_attributes,
+        )
+        .unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(
+            result.get("message.type"),
+            Some(&CheetahString::from("FIFO"))
+        );
+    }
 }
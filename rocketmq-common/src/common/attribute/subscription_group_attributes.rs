@@ -0,0 +1,111 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::OnceLock;
+
+use cheetah_string::CheetahString;
+
+use crate::common::attribute::long_range_attribute::LongRangeAttribute;
+use crate::common::attribute::Attribute;
+
+/// Defines the attributes supported by subscription groups.
+///
+/// Keys that are not registered here are rejected when a subscription group is created or
+/// updated with `+key=value` / `-key` attribute modifications.
+pub struct SubscriptionGroupAttributes;
+
+impl SubscriptionGroupAttributes {
+    /// Messages per second the group may consume, overriding `groupGetMsgsPerSecond` of the
+    /// broker when not negative
+    pub fn group_get_msgs_per_second_attribute() -> &'static LongRangeAttribute {
+        static INSTANCE: OnceLock<LongRangeAttribute> = OnceLock::new();
+        INSTANCE.get_or_init(|| {
+            LongRangeAttribute::new("flow.get.msgs.per.second".into(), true, -1, i64::MAX, -1)
+        })
+    }
+
+    /// Bytes per second the group may consume, overriding `groupGetBytesPerSecond` of the
+    /// broker when not negative
+    pub fn group_get_bytes_per_second_attribute() -> &'static LongRangeAttribute {
+        static INSTANCE: OnceLock<LongRangeAttribute> = OnceLock::new();
+        INSTANCE.get_or_init(|| {
+            LongRangeAttribute::new("flow.get.bytes.per.second".into(), true, -1, i64::MAX, -1)
+        })
+    }
+
+    /// Returns all defined attributes in a HashMap
+    pub fn all() -> &'static HashMap<CheetahString, Arc<dyn Attribute>> {
+        static ALL: OnceLock<HashMap<CheetahString, Arc<dyn Attribute>>> = OnceLock::new();
+        ALL.get_or_init(|| {
+            let mut map = HashMap::new();
+
+            let get_msgs_per_second = Self::group_get_msgs_per_second_attribute();
+            let get_bytes_per_second = Self::group_get_bytes_per_second_attribute();
+
+            map.insert(
+                get_msgs_per_second.name().clone(),
+                Arc::new(get_msgs_per_second.clone()) as Arc<dyn Attribute>,
+            );
+            map.insert(
+                get_bytes_per_second.name().clone(),
+                Arc::new(get_bytes_per_second.clone()) as Arc<dyn Attribute>,
+            );
+
+            map
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::attribute::attribute_util::AttributeUtil;
+
+    #[test]
+    fn unregistered_attribute_is_rejected() {
+        let current_attributes = HashMap::new();
+        let mut new_attributes = HashMap::new();
+        new_attributes.insert("+unknown.key".into(), "value".into());
+
+        let result = AttributeUtil::alter_current_attributes(
+            true,
+            SubscriptionGroupAttributes::all(),
+            &current_attributes,
+            &new_attributes,
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Unsupported key: unknown.key"
+        );
+    }
+
+    #[test]
+    fn flow_limits_can_be_set_per_group() {
+        let mut new_attributes = HashMap::new();
+        new_attributes.insert("+flow.get.msgs.per.second".into(), "100".into());
+
+        let attributes = AttributeUtil::alter_current_attributes(
+            true,
+            SubscriptionGroupAttributes::all(),
+            &HashMap::new(),
+            &new_attributes,
+        )
+        .unwrap();
+        assert_eq!(attributes["flow.get.msgs.per.second"], "100");
+    }
+}
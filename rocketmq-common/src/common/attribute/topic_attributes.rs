@@ -5,6 +5,7 @@ use std::sync::OnceLock;
 
 use cheetah_string::CheetahString;
 
+use crate::common::attribute::bool_attribute::BooleanAttribute;
 use crate::common::attribute::enum_attribute::EnumAttribute;
 use crate::common::attribute::long_range_attribute::LongRangeAttribute;
 use crate::common::attribute::topic_message_type::TopicMessageType;
@@ -60,13 +61,43 @@ impl TopicAttributes {
         })
     }
 
-    /// Reserve time attribute defining how long messages are kept
+    /// Reserve time attribute defining how many hours messages are kept
     pub fn topic_reserve_time_attribute() -> &'static LongRangeAttribute {
         static INSTANCE: OnceLock<LongRangeAttribute> = OnceLock::new();
         INSTANCE
             .get_or_init(|| LongRangeAttribute::new("reserve.time".into(), true, -1, i64::MAX, -1))
     }
 
+    /// Max size attribute capping the bytes of messages a topic keeps on a broker
+    pub fn topic_max_size_attribute() -> &'static LongRangeAttribute {
+        static INSTANCE: OnceLock<LongRangeAttribute> = OnceLock::new();
+        INSTANCE.get_or_init(|| LongRangeAttribute::new("max.size".into(), true, -1, i64::MAX, -1))
+    }
+
+    /// Dedup attribute making the broker drop retried sends of messages it already stored
+    pub fn topic_dedup_enable_attribute() -> &'static BooleanAttribute {
+        static INSTANCE: OnceLock<BooleanAttribute> = OnceLock::new();
+        INSTANCE.get_or_init(|| BooleanAttribute::new("dedup.enable".into(), true, false))
+    }
+
+    /// Messages per second producers may put to the topic, overriding
+    /// `topicPutMsgsPerSecond` of the broker when not negative
+    pub fn topic_put_msgs_per_second_attribute() -> &'static LongRangeAttribute {
+        static INSTANCE: OnceLock<LongRangeAttribute> = OnceLock::new();
+        INSTANCE.get_or_init(|| {
+            LongRangeAttribute::new("flow.put.msgs.per.second".into(), true, -1, i64::MAX, -1)
+        })
+    }
+
+    /// Bytes per second producers may put to the topic, overriding
+    /// `topicPutBytesPerSecond` of the broker when not negative
+    pub fn topic_put_bytes_per_second_attribute() -> &'static LongRangeAttribute {
+        static INSTANCE: OnceLock<LongRangeAttribute> = OnceLock::new();
+        INSTANCE.get_or_init(|| {
+            LongRangeAttribute::new("flow.put.bytes.per.second".into(), true, -1, i64::MAX, -1)
+        })
+    }
+
     /// Returns all defined attributes in a HashMap
     pub fn all() -> &'static HashMap<CheetahString, Arc<dyn Attribute>> {
         static ALL: OnceLock<HashMap<CheetahString, Arc<dyn Attribute>>> = OnceLock::new();
@@ -77,6 +108,10 @@ impl TopicAttributes {
             let cleanup_policy = Self::cleanup_policy_attribute();
             let message_type = Self::topic_message_type_attribute();
             let reserve_time = Self::topic_reserve_time_attribute();
+            let max_size = Self::topic_max_size_attribute();
+            let dedup_enable = Self::topic_dedup_enable_attribute();
+            let put_msgs_per_second = Self::topic_put_msgs_per_second_attribute();
+            let put_bytes_per_second = Self::topic_put_bytes_per_second_attribute();
 
             map.insert(
                 queue_type.name().clone(),
@@ -94,6 +129,22 @@ impl TopicAttributes {
                 reserve_time.name().clone(),
                 Arc::new(reserve_time.clone()) as Arc<dyn Attribute>,
             );
+            map.insert(
+                max_size.name().clone(),
+                Arc::new(max_size.clone()) as Arc<dyn Attribute>,
+            );
+            map.insert(
+                dedup_enable.name().clone(),
+                Arc::new(dedup_enable.clone()) as Arc<dyn Attribute>,
+            );
+            map.insert(
+                put_msgs_per_second.name().clone(),
+                Arc::new(put_msgs_per_second.clone()) as Arc<dyn Attribute>,
+            );
+            map.insert(
+                put_bytes_per_second.name().clone(),
+                Arc::new(put_bytes_per_second.clone()) as Arc<dyn Attribute>,
+            );
 
             map
         })
@@ -169,6 +220,13 @@ mod tests {
         assert_eq!(attribute.max(), i64::MAX);
     }
 
+    #[test]
+    fn topic_max_size_attribute_default_value() {
+        let attribute = TopicAttributes::topic_max_size_attribute();
+        assert_eq!(attribute.default_value(), -1);
+        assert_eq!(attribute.min(), -1);
+    }
+
     #[test]
     fn all_attributes_contains_all_defined_attributes() {
         let all_attributes = TopicAttributes::all();
@@ -176,5 +234,9 @@ mod tests {
         assert!(all_attributes.contains_key("cleanup.policy"));
         assert!(all_attributes.contains_key("message.type"));
         assert!(all_attributes.contains_key("reserve.time"));
+        assert!(all_attributes.contains_key("max.size"));
+        assert!(all_attributes.contains_key("dedup.enable"));
+        assert!(all_attributes.contains_key("flow.put.msgs.per.second"));
+        assert!(all_attributes.contains_key("flow.put.bytes.per.second"));
     }
 }
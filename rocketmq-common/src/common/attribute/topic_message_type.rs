@@ -14,9 +14,11 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use std::borrow::Borrow;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fmt::Display;
+use std::hash::Hash;
 use std::str::FromStr;
 
 use crate::common::message::MessageConst;
@@ -59,10 +61,15 @@ impl TopicMessageType {
         .collect()
     }
 
-    pub fn parse_from_message_property(message_property: &HashMap<String, String>) -> Self {
-        let is_trans = message_property.get(MessageConst::PROPERTY_TRANSACTION_PREPARED);
-        let is_trans_value = "true";
-        if Some(&is_trans_value.to_string()) == is_trans {
+    pub fn parse_from_message_property<K, V>(message_property: &HashMap<K, V>) -> Self
+    where
+        K: Borrow<str> + Hash + Eq,
+        V: AsRef<str>,
+    {
+        let is_trans = message_property
+            .get(MessageConst::PROPERTY_TRANSACTION_PREPARED)
+            .is_some_and(|value| value.as_ref().parse().unwrap_or(false));
+        if is_trans {
             return Self::Transaction;
         } else if message_property.contains_key(MessageConst::PROPERTY_DELAY_TIME_LEVEL)
             || message_property.contains_key(MessageConst::PROPERTY_TIMER_DELIVER_MS)
@@ -75,6 +82,13 @@ impl TopicMessageType {
         Self::Normal
     }
 
+    /// Whether a message of `message_type` may be stored in a topic of this type.
+    ///
+    /// `MIXED` topics accept any message type, and `UNSPECIFIED` topics are not checked.
+    pub fn is_compatible_with(&self, message_type: &TopicMessageType) -> bool {
+        matches!(self, Self::Mixed | Self::Unspecified) || self == message_type
+    }
+
     pub fn get_metrics_value(&self) -> String {
         self.to_string().to_lowercase()
     }
@@ -98,6 +112,8 @@ impl Display for TopicMessageType {
 mod tests {
     use std::collections::HashMap;
 
+    use cheetah_string::CheetahString;
+
     use super::*;
 
     #[test]
@@ -156,13 +172,35 @@ mod tests {
 
     #[test]
     fn test_parse_from_message_property_normal() {
-        let message_property = HashMap::new();
+        let message_property: HashMap<String, String> = HashMap::new();
+        assert_eq!(
+            TopicMessageType::parse_from_message_property(&message_property),
+            TopicMessageType::Normal
+        );
+    }
+
+    #[test]
+    fn test_parse_from_message_property_transaction_false() {
+        let mut message_property = HashMap::new();
+        message_property.insert(
+            CheetahString::from_static_str(MessageConst::PROPERTY_TRANSACTION_PREPARED),
+            CheetahString::from_static_str("false"),
+        );
         assert_eq!(
             TopicMessageType::parse_from_message_property(&message_property),
             TopicMessageType::Normal
         );
     }
 
+    #[test]
+    fn test_is_compatible_with() {
+        assert!(TopicMessageType::Normal.is_compatible_with(&TopicMessageType::Normal));
+        assert!(!TopicMessageType::Normal.is_compatible_with(&TopicMessageType::Delay));
+        assert!(!TopicMessageType::Fifo.is_compatible_with(&TopicMessageType::Transaction));
+        assert!(TopicMessageType::Mixed.is_compatible_with(&TopicMessageType::Fifo));
+        assert!(TopicMessageType::Unspecified.is_compatible_with(&TopicMessageType::Delay));
+    }
+
     #[test]
     fn test_get_metrics_value() {
         assert_eq!(TopicMessageType::Normal.get_metrics_value(), "normal");
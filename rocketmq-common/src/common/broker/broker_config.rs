@@ -17,6 +17,7 @@
 
 use std::any::Any;
 use std::collections::HashMap;
+use std::fmt;
 use std::time::Duration;
 
 use cheetah_string::CheetahString;
@@ -31,6 +32,7 @@ use crate::common::mix_all;
 use crate::common::mix_all::NAMESRV_ADDR_PROPERTY;
 use crate::common::server::config::ServerConfig;
 use crate::common::topic::TopicValidator;
+use crate::utils::network_util::NetworkUtil;
 
 const DEFAULT_CLUSTER_NAME: &str = "DefaultCluster";
 
@@ -117,7 +119,7 @@ impl BrokerIdentity {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct BrokerConfig {
     pub broker_identity: BrokerIdentity,
@@ -154,6 +156,7 @@ pub struct BrokerConfig {
     pub cluster_topic_enable: bool,
     pub revive_queue_num: u32,
     pub enable_slave_acting_master: bool,
+    pub compatible_with_old_name_srv: bool,
     pub reject_transaction_message: bool,
     pub enable_detail_stat: bool,
     pub flush_consumer_offset_interval: u64,
@@ -218,9 +221,14 @@ pub struct BrokerConfig {
     pub max_pop_polling_size: u64,
     pub pop_polling_size: usize,
     pub enable_pop_message_threshold: bool,
+    pub enable_notify_after_pop_order_lock_release: bool,
     pub pop_inflight_message_threshold: i64,
     pub pop_ck_max_buffer_size: i64,
     pub pop_ck_offset_max_queue_size: u64,
+    // Delay revived pop messages by the retry policy of their consumer group.
+    pub enable_pop_retry_policy: bool,
+    // Reject send requests with SYSTEM_BUSY while the store is stalled.
+    pub broker_fast_failure_enable: bool,
     pub delay_offset_update_version_step: u64,
     pub revive_ack_wait_ms: u64,
 
@@ -229,6 +237,347 @@ pub struct BrokerConfig {
     // 1. Calculate filter bit map when construct queue.
     // 2. Filter bit map will be saved to consume queue extend file if allowed.
     pub enable_calc_filter_bit_map: bool,
+
+    // Switch of topic/group level flow control, a limit of 0 means unlimited. The limits below
+    // are the defaults, topics override them with their `flow.put.*` attributes and consumer
+    // groups with their `flow.get.*` attributes.
+    pub enable_flow_control: bool,
+    pub topic_put_msgs_per_second: u64,
+    pub topic_put_bytes_per_second: u64,
+    pub group_get_msgs_per_second: u64,
+    pub group_get_bytes_per_second: u64,
+
+    // Reject messages whose type (normal/fifo/delay/transaction) does not match the
+    // `message.type` attribute of the target topic.
+    pub enable_topic_message_type_check: bool,
+
+    // Unique keys of the latest messages remembered per queue to drop retried sends of a message
+    // already stored, for topics with the `dedup.enable` attribute.
+    pub send_dedup_keys_per_queue: usize,
+
+    // Serve read-only admin queries as HTTP/JSON for tools that do not speak the remoting
+    // protocol. It only listens on loopback unless `http_admin_bind_address` says otherwise. When
+    // the broker authenticates its requests, callers must send an `Authorization` header, which is
+    // checked like the credentials of a remoting client.
+    pub enable_http_admin: bool,
+    pub http_admin_bind_address: CheetahString,
+    pub http_admin_listen_port: u32,
+
+    // Log the metadata of sampled requests under the `rocketmq_broker::audit` target. The sample
+    // rates are `code:rate` pairs separated by `,` with rates between 0 and 1, `*` sets the rate
+    // of the request codes not listed. Both can be changed at runtime with UPDATE_BROKER_CONFIG.
+    pub enable_request_audit: bool,
+    pub request_audit_sample_rates: CheetahString,
+
+    // Worker threads of the runtimes the broker is split into, 0 keeps the work on the runtime
+    // that started the broker. The network runtime runs the acceptors, the processor runtime the
+    // connections and their request processors, the store runtime the reput/dispatch and
+    // housekeeping services of the message store.
+    pub network_runtime_threads: usize,
+    pub processor_runtime_threads: usize,
+    pub store_runtime_threads: usize,
+    // Run commit log flush and commit on a dedicated OS thread so fsync never stalls a worker
+    // that serves requests.
+    pub flush_on_dedicated_thread: bool,
+
+    // Compress the message bodies of pull and pop responses with zstd for the consumers that
+    // advertise they inflate them. Only bodies of at least `compress_pull_response_threshold`
+    // bytes are compressed, and none while the load average per core is above
+    // `compress_pull_response_max_cpu_load`.
+    pub compress_pull_response_body: bool,
+    pub compress_pull_response_threshold: usize,
+    pub compress_pull_response_level: i32,
+    pub compress_pull_response_max_cpu_load: f64,
+
+    // Credentials the requests the broker sends itself are authenticated with, for name servers,
+    // masters and its own remoting port authenticating their requests. The bearer token is used
+    // when set, the access key and secret key otherwise. The secrets are never serialized, so
+    // they stay out of the reported properties, and are masked in the debug output.
+    pub inner_access_key: Option<CheetahString>,
+    #[serde(skip_serializing)]
+    pub inner_secret_key: Option<CheetahString>,
+    #[serde(skip_serializing)]
+    pub inner_auth_token: Option<CheetahString>,
+}
+
+impl fmt::Debug for BrokerConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let masked = |secret: &Option<CheetahString>| secret.as_ref().map(|_| "****");
+        f.debug_struct("BrokerConfig")
+            .field("broker_identity", &self.broker_identity)
+            .field("topic_queue_config", &self.topic_queue_config)
+            .field("timer_wheel_config", &self.timer_wheel_config)
+            .field("broker_server_config", &self.broker_server_config)
+            .field("broker_ip1", &self.broker_ip1)
+            .field("broker_ip2", &self.broker_ip2)
+            .field("listen_port", &self.listen_port)
+            .field("trace_topic_enable", &self.trace_topic_enable)
+            .field("msg_trace_topic_name", &self.msg_trace_topic_name)
+            .field("enable_controller_mode", &self.enable_controller_mode)
+            .field("broker_name", &self.broker_name)
+            .field("region_id", &self.region_id)
+            .field("trace_on", &self.trace_on)
+            .field("broker_permission", &self.broker_permission)
+            .field("async_send_enable", &self.async_send_enable)
+            .field("store_path_root_dir", &self.store_path_root_dir)
+            .field("enable_split_registration", &self.enable_split_registration)
+            .field("split_registration_size", &self.split_registration_size)
+            .field(
+                "register_broker_timeout_mills",
+                &self.register_broker_timeout_mills,
+            )
+            .field("is_in_broker_container", &self.is_in_broker_container)
+            .field("commercial_size_per_msg", &self.commercial_size_per_msg)
+            .field("recover_concurrently", &self.recover_concurrently)
+            .field("duplication_enable", &self.duplication_enable)
+            .field(
+                "start_accept_send_request_time_stamp",
+                &self.start_accept_send_request_time_stamp,
+            )
+            .field("auto_create_topic_enable", &self.auto_create_topic_enable)
+            .field(
+                "enable_single_topic_register",
+                &self.enable_single_topic_register,
+            )
+            .field("broker_topic_enable", &self.broker_topic_enable)
+            .field("cluster_topic_enable", &self.cluster_topic_enable)
+            .field("revive_queue_num", &self.revive_queue_num)
+            .field(
+                "enable_slave_acting_master",
+                &self.enable_slave_acting_master,
+            )
+            .field(
+                "compatible_with_old_name_srv",
+                &self.compatible_with_old_name_srv,
+            )
+            .field(
+                "reject_transaction_message",
+                &self.reject_transaction_message,
+            )
+            .field("enable_detail_stat", &self.enable_detail_stat)
+            .field(
+                "flush_consumer_offset_interval",
+                &self.flush_consumer_offset_interval,
+            )
+            .field("force_register", &self.force_register)
+            .field(
+                "register_name_server_period",
+                &self.register_name_server_period,
+            )
+            .field("skip_pre_online", &self.skip_pre_online)
+            .field("namesrv_addr", &self.namesrv_addr)
+            .field(
+                "fetch_name_srv_addr_by_dns_lookup",
+                &self.fetch_name_srv_addr_by_dns_lookup,
+            )
+            .field("lite_pull_message_enable", &self.lite_pull_message_enable)
+            .field(
+                "auto_create_subscription_group",
+                &self.auto_create_subscription_group,
+            )
+            .field("channel_expired_timeout", &self.channel_expired_timeout)
+            .field(
+                "subscription_expired_timeout",
+                &self.subscription_expired_timeout,
+            )
+            .field("enable_property_filter", &self.enable_property_filter)
+            .field("filter_support_retry", &self.filter_support_retry)
+            .field(
+                "use_server_side_reset_offset",
+                &self.use_server_side_reset_offset,
+            )
+            .field("slave_read_enable", &self.slave_read_enable)
+            .field("commercial_base_count", &self.commercial_base_count)
+            .field(
+                "reject_pull_consumer_enable",
+                &self.reject_pull_consumer_enable,
+            )
+            .field(
+                "consumer_offset_update_version_step",
+                &self.consumer_offset_update_version_step,
+            )
+            .field(
+                "enable_broadcast_offset_store",
+                &self.enable_broadcast_offset_store,
+            )
+            .field("transfer_msg_by_heap", &self.transfer_msg_by_heap)
+            .field("short_polling_time_mills", &self.short_polling_time_mills)
+            .field("long_polling_enable", &self.long_polling_enable)
+            .field(
+                "max_error_rate_of_bloom_filter",
+                &self.max_error_rate_of_bloom_filter,
+            )
+            .field(
+                "expect_consumer_num_use_filter",
+                &self.expect_consumer_num_use_filter,
+            )
+            .field(
+                "bit_map_length_consume_queue_ext",
+                &self.bit_map_length_consume_queue_ext,
+            )
+            .field(
+                "validate_system_topic_when_update_topic",
+                &self.validate_system_topic_when_update_topic,
+            )
+            .field("enable_mixed_message_type", &self.enable_mixed_message_type)
+            .field("auto_delete_unused_stats", &self.auto_delete_unused_stats)
+            .field("forward_timeout", &self.forward_timeout)
+            .field(
+                "store_reply_message_enable",
+                &self.store_reply_message_enable,
+            )
+            .field("lock_in_strict_mode", &self.lock_in_strict_mode)
+            .field("transaction_timeout", &self.transaction_timeout)
+            .field(
+                "transaction_op_msg_max_size",
+                &self.transaction_op_msg_max_size,
+            )
+            .field(
+                "default_message_request_mode",
+                &self.default_message_request_mode,
+            )
+            .field(
+                "default_pop_share_queue_num",
+                &self.default_pop_share_queue_num,
+            )
+            .field(
+                "load_balance_poll_name_server_interval",
+                &self.load_balance_poll_name_server_interval,
+            )
+            .field(
+                "server_load_balancer_enable",
+                &self.server_load_balancer_enable,
+            )
+            .field("enable_remote_escape", &self.enable_remote_escape)
+            .field("enable_pop_log", &self.enable_pop_log)
+            .field("enable_retry_topic_v2", &self.enable_retry_topic_v2)
+            .field(
+                "retrieve_message_from_pop_retry_topic_v1",
+                &self.retrieve_message_from_pop_retry_topic_v1,
+            )
+            .field(
+                "pop_from_retry_probability",
+                &self.pop_from_retry_probability,
+            )
+            .field(
+                "pop_response_return_actual_retry_topic",
+                &self.pop_response_return_actual_retry_topic,
+            )
+            .field(
+                "init_pop_offset_by_check_msg_in_mem",
+                &self.init_pop_offset_by_check_msg_in_mem,
+            )
+            .field("enable_pop_buffer_merge", &self.enable_pop_buffer_merge)
+            .field(
+                "pop_ck_stay_buffer_time_out",
+                &self.pop_ck_stay_buffer_time_out,
+            )
+            .field("pop_ck_stay_buffer_time", &self.pop_ck_stay_buffer_time)
+            .field("broker_role", &self.broker_role)
+            .field("enable_pop_batch_ack", &self.enable_pop_batch_ack)
+            .field("revive_interval", &self.revive_interval)
+            .field("revive_max_slow", &self.revive_max_slow)
+            .field("revive_scan_time", &self.revive_scan_time)
+            .field(
+                "enable_skip_long_awaiting_ack",
+                &self.enable_skip_long_awaiting_ack,
+            )
+            .field(
+                "skip_when_ck_re_put_reach_max_times",
+                &self.skip_when_ck_re_put_reach_max_times,
+            )
+            .field("compressed_register", &self.compressed_register)
+            .field(
+                "broker_not_active_timeout_millis",
+                &self.broker_not_active_timeout_millis,
+            )
+            .field(
+                "sync_broker_member_group_period",
+                &self.sync_broker_member_group_period,
+            )
+            .field("pop_polling_map_size", &self.pop_polling_map_size)
+            .field("max_pop_polling_size", &self.max_pop_polling_size)
+            .field("pop_polling_size", &self.pop_polling_size)
+            .field(
+                "enable_pop_message_threshold",
+                &self.enable_pop_message_threshold,
+            )
+            .field(
+                "enable_notify_after_pop_order_lock_release",
+                &self.enable_notify_after_pop_order_lock_release,
+            )
+            .field(
+                "pop_inflight_message_threshold",
+                &self.pop_inflight_message_threshold,
+            )
+            .field("pop_ck_max_buffer_size", &self.pop_ck_max_buffer_size)
+            .field(
+                "pop_ck_offset_max_queue_size",
+                &self.pop_ck_offset_max_queue_size,
+            )
+            .field("enable_pop_retry_policy", &self.enable_pop_retry_policy)
+            .field(
+                "broker_fast_failure_enable",
+                &self.broker_fast_failure_enable,
+            )
+            .field(
+                "delay_offset_update_version_step",
+                &self.delay_offset_update_version_step,
+            )
+            .field("revive_ack_wait_ms", &self.revive_ack_wait_ms)
+            .field(
+                "enable_calc_filter_bit_map",
+                &self.enable_calc_filter_bit_map,
+            )
+            .field("enable_flow_control", &self.enable_flow_control)
+            .field("topic_put_msgs_per_second", &self.topic_put_msgs_per_second)
+            .field(
+                "topic_put_bytes_per_second",
+                &self.topic_put_bytes_per_second,
+            )
+            .field("group_get_msgs_per_second", &self.group_get_msgs_per_second)
+            .field(
+                "group_get_bytes_per_second",
+                &self.group_get_bytes_per_second,
+            )
+            .field(
+                "enable_topic_message_type_check",
+                &self.enable_topic_message_type_check,
+            )
+            .field("send_dedup_keys_per_queue", &self.send_dedup_keys_per_queue)
+            .field("enable_http_admin", &self.enable_http_admin)
+            .field("http_admin_bind_address", &self.http_admin_bind_address)
+            .field("http_admin_listen_port", &self.http_admin_listen_port)
+            .field("enable_request_audit", &self.enable_request_audit)
+            .field(
+                "request_audit_sample_rates",
+                &self.request_audit_sample_rates,
+            )
+            .field("network_runtime_threads", &self.network_runtime_threads)
+            .field("processor_runtime_threads", &self.processor_runtime_threads)
+            .field("store_runtime_threads", &self.store_runtime_threads)
+            .field("flush_on_dedicated_thread", &self.flush_on_dedicated_thread)
+            .field(
+                "compress_pull_response_body",
+                &self.compress_pull_response_body,
+            )
+            .field(
+                "compress_pull_response_threshold",
+                &self.compress_pull_response_threshold,
+            )
+            .field(
+                "compress_pull_response_level",
+                &self.compress_pull_response_level,
+            )
+            .field(
+                "compress_pull_response_max_cpu_load",
+                &self.compress_pull_response_max_cpu_load,
+            )
+            .field("inner_access_key", &self.inner_access_key)
+            .field("inner_secret_key", &masked(&self.inner_secret_key))
+            .field("inner_auth_token", &masked(&self.inner_auth_token))
+            .finish()
+    }
 }
 
 impl Default for BrokerConfig {
@@ -277,6 +626,7 @@ impl Default for BrokerConfig {
             cluster_topic_enable: true,
             revive_queue_num: 8,
             enable_slave_acting_master: false,
+            compatible_with_old_name_srv: true,
             reject_transaction_message: false,
             enable_detail_stat: true,
             flush_consumer_offset_interval: 1000 * 5,
@@ -339,12 +689,38 @@ impl Default for BrokerConfig {
             max_pop_polling_size: 100000,
             pop_polling_size: 1024,
             enable_pop_message_threshold: false,
+            enable_notify_after_pop_order_lock_release: true,
             pop_inflight_message_threshold: 10_000,
             pop_ck_max_buffer_size: 200_000,
             pop_ck_offset_max_queue_size: 20_000,
+            enable_pop_retry_policy: false,
+            broker_fast_failure_enable: true,
             delay_offset_update_version_step: 200,
             revive_ack_wait_ms: Duration::from_secs(3 * 60).as_millis() as u64,
             enable_calc_filter_bit_map: false,
+            enable_flow_control: false,
+            topic_put_msgs_per_second: 0,
+            topic_put_bytes_per_second: 0,
+            group_get_msgs_per_second: 0,
+            group_get_bytes_per_second: 0,
+            enable_topic_message_type_check: false,
+            send_dedup_keys_per_queue: 4096,
+            enable_http_admin: false,
+            http_admin_bind_address: CheetahString::from_static_str("127.0.0.1"),
+            http_admin_listen_port: 10919,
+            enable_request_audit: false,
+            request_audit_sample_rates: CheetahString::from_static_str("*:0.001"),
+            network_runtime_threads: 0,
+            processor_runtime_threads: 0,
+            store_runtime_threads: 0,
+            flush_on_dedicated_thread: false,
+            compress_pull_response_body: false,
+            compress_pull_response_threshold: 16 * 1024,
+            compress_pull_response_level: 3,
+            compress_pull_response_max_cpu_load: 0.8,
+            inner_access_key: None,
+            inner_secret_key: None,
+            inner_auth_token: None,
         }
     }
 }
@@ -384,7 +760,7 @@ impl BrokerConfig {
     }
 
     pub fn get_broker_addr(&self) -> String {
-        format!("{}:{}", self.broker_ip1, self.listen_port)
+        NetworkUtil::format_address(self.broker_ip1.as_str(), self.listen_port)
     }
 
     pub fn get_start_accept_send_request_time_stamp(&self) -> i64 {
@@ -517,6 +893,16 @@ impl BrokerConfig {
             "enableSlaveActingMaster".into(),
             self.enable_slave_acting_master.to_string().into(),
         );
+        properties.insert(
+            "compatibleWithOldNameSrv".into(),
+            self.compatible_with_old_name_srv.to_string().into(),
+        );
+        properties.insert(
+            "enableNotifyAfterPopOrderLockRelease".into(),
+            self.enable_notify_after_pop_order_lock_release
+                .to_string()
+                .into(),
+        );
         properties.insert(
             "rejectTransactionMessage".into(),
             self.reject_transaction_message.to_string().into(),
@@ -639,6 +1025,86 @@ impl BrokerConfig {
             "forwardTimeout".into(),
             self.forward_timeout.to_string().into(),
         );
+        properties.insert(
+            "enableFlowControl".into(),
+            self.enable_flow_control.to_string().into(),
+        );
+        properties.insert(
+            "topicPutMsgsPerSecond".into(),
+            self.topic_put_msgs_per_second.to_string().into(),
+        );
+        properties.insert(
+            "topicPutBytesPerSecond".into(),
+            self.topic_put_bytes_per_second.to_string().into(),
+        );
+        properties.insert(
+            "groupGetMsgsPerSecond".into(),
+            self.group_get_msgs_per_second.to_string().into(),
+        );
+        properties.insert(
+            "groupGetBytesPerSecond".into(),
+            self.group_get_bytes_per_second.to_string().into(),
+        );
+        properties.insert(
+            "enableTopicMessageTypeCheck".into(),
+            self.enable_topic_message_type_check.to_string().into(),
+        );
+        properties.insert(
+            "sendDedupKeysPerQueue".into(),
+            self.send_dedup_keys_per_queue.to_string().into(),
+        );
+        properties.insert(
+            "enableHttpAdmin".into(),
+            self.enable_http_admin.to_string().into(),
+        );
+        properties.insert(
+            "httpAdminBindAddress".into(),
+            self.http_admin_bind_address.clone(),
+        );
+        properties.insert(
+            "httpAdminListenPort".into(),
+            self.http_admin_listen_port.to_string().into(),
+        );
+        properties.insert(
+            "enableRequestAudit".into(),
+            self.enable_request_audit.to_string().into(),
+        );
+        properties.insert(
+            "requestAuditSampleRates".into(),
+            self.request_audit_sample_rates.clone(),
+        );
+        properties.insert(
+            "networkRuntimeThreads".into(),
+            self.network_runtime_threads.to_string().into(),
+        );
+        properties.insert(
+            "processorRuntimeThreads".into(),
+            self.processor_runtime_threads.to_string().into(),
+        );
+        properties.insert(
+            "storeRuntimeThreads".into(),
+            self.store_runtime_threads.to_string().into(),
+        );
+        properties.insert(
+            "flushOnDedicatedThread".into(),
+            self.flush_on_dedicated_thread.to_string().into(),
+        );
+        properties.insert(
+            "compressPullResponseBody".into(),
+            self.compress_pull_response_body.to_string().into(),
+        );
+        properties.insert(
+            "compressPullResponseThreshold".into(),
+            self.compress_pull_response_threshold.to_string().into(),
+        );
+        properties.insert(
+            "compressPullResponseLevel".into(),
+            self.compress_pull_response_level.to_string().into(),
+        );
+        properties.insert(
+            "compressPullResponseMaxCpuLoad".into(),
+            self.compress_pull_response_max_cpu_load.to_string().into(),
+        );
         properties
     }
 }
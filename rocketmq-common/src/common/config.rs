@@ -23,6 +23,7 @@ use serde::Deserialize;
 use serde::Serialize;
 
 use super::TopicFilterType;
+use crate::common::attribute::bool_attribute::BooleanAttribute;
 use crate::common::attribute::topic_message_type::TopicMessageType;
 use crate::common::attribute::Attribute;
 use crate::common::constant::PermName;
@@ -92,6 +93,15 @@ impl TopicConfig {
         TopicMessageType::Normal
     }
 
+    /// Whether the broker drops retried sends of messages of this topic it already stored.
+    pub fn is_dedup_enable(&self) -> bool {
+        let attribute = TopicAttributes::topic_dedup_enable_attribute();
+        self.attributes
+            .get(attribute.name())
+            .and_then(|value| BooleanAttribute::parse_bool(value).ok())
+            .unwrap_or(attribute.default_value())
+    }
+
     pub fn new(topic_name: impl Into<CheetahString>) -> Self {
         TopicConfig {
             topic_name: Some(topic_name.into()),
@@ -251,6 +261,17 @@ mod tests {
         assert_eq!(config.topic_sys_flag, 1);
     }
 
+    #[test]
+    fn topic_message_type_from_attributes() {
+        let mut config = TopicConfig::new("test_topic");
+        assert_eq!(config.get_topic_message_type(), TopicMessageType::Normal);
+        config.attributes.insert(
+            CheetahString::from("message.type"),
+            CheetahString::from("FIFO"),
+        );
+        assert_eq!(config.get_topic_message_type(), TopicMessageType::Fifo);
+    }
+
     #[test]
     fn encode_topic_config() {
         let topic_name = CheetahString::from("test_topic");
@@ -302,4 +323,15 @@ mod tests {
         );
         assert_eq!(config.get_topic_message_type(), TopicMessageType::Normal);
     }
+
+    #[test]
+    fn dedup_is_enabled_by_topic_attribute() {
+        let mut config = TopicConfig::default();
+        assert!(!config.is_dedup_enable());
+        config.attributes.insert(
+            CheetahString::from_static_str("dedup.enable"),
+            CheetahString::from_static_str("true"),
+        );
+        assert!(config.is_dedup_enable());
+    }
 }
@@ -83,7 +83,7 @@ pub trait ConfigManager {
     /// This method persists the configuration with a given topic.
     /// The actual implementation is delegated to the `persist` method.
     fn persist_with_topic(&mut self, _topic_name: &str, _t: Box<dyn Any>) {
-        self.persist()
+        self.persist();
     }
 
     /// Persists the configuration with a map.
@@ -91,7 +91,7 @@ pub trait ConfigManager {
     /// This method persists the configuration with a given map.
     /// The actual implementation is delegated to the `persist` method.
     fn persist_map(&mut self, _m: &HashMap<String, Box<dyn Any>>) {
-        self.persist()
+        self.persist();
     }
 
     /// Persists the configuration.
@@ -99,14 +99,19 @@ pub trait ConfigManager {
     /// This method persists the configuration to a file whose path is returned by
     /// `config_file_path`. If the encoded configuration is not empty, it writes the
     /// configuration to the file.
-    fn persist(&self) {
+    ///
+    /// # Returns
+    /// * `false` if writing the file failed, `true` otherwise.
+    fn persist(&self) -> bool {
         let json = self.encode_pretty(true);
         if !json.is_empty() {
             let file_name = self.config_file_path();
-            if FileUtils::string_to_file(json.as_str(), file_name.as_str()).is_err() {
-                error!("persist file {} exception", file_name);
+            if let Err(e) = FileUtils::string_to_file(json.as_str(), file_name.as_str()) {
+                error!("persist file {} exception: {}", file_name, e);
+                return false;
             }
         }
+        true
     }
 
     /// Decodes the configuration.
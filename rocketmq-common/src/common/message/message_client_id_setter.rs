@@ -14,6 +14,8 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use std::net::Ipv4Addr;
+use std::net::Ipv6Addr;
 use std::sync::atomic::AtomicI32;
 use std::sync::atomic::Ordering;
 
@@ -65,6 +67,9 @@ pub fn create_fake_ip() -> Vec<u8> {
     get_current_millis().to_be_bytes()[4..].to_vec()
 }
 
+/// Generates the client side unique message id (`UNIQ_KEY`) with the layout of the Java client:
+/// client ip (4 or 16 bytes), pid (2 bytes), class loader hash (4 bytes), milliseconds since the
+/// start of the month (4 bytes) and a counter (2 bytes), hex encoded.
 pub struct MessageClientIDSetter;
 
 impl MessageClientIDSetter {
@@ -115,6 +120,59 @@ impl MessageClientIDSetter {
         sb.into_iter().collect()
     }
 
+    /// Returns the ip of the client that created the unique id `msg_id`.
+    pub fn get_ip_from_id(msg_id: &str) -> Option<Vec<u8>> {
+        let bytes = util_all::string_to_bytes(msg_id)?;
+        let ip_len = Self::ip_len(bytes.len())?;
+        Some(bytes[..ip_len].to_vec())
+    }
+
+    pub fn get_ip_str_from_id(msg_id: &str) -> Option<String> {
+        let ip = Self::get_ip_from_id(msg_id)?;
+        if let Ok(ip) = <[u8; 16]>::try_from(ip.as_slice()) {
+            Some(Ipv6Addr::from(ip).to_string())
+        } else {
+            <[u8; 4]>::try_from(ip.as_slice())
+                .ok()
+                .map(|ip| Ipv4Addr::from(ip).to_string())
+        }
+    }
+
+    /// Returns the pid of the client that created the unique id `msg_id`.
+    pub fn get_pid_from_id(msg_id: &str) -> Option<i32> {
+        let bytes = util_all::string_to_bytes(msg_id)?;
+        let ip_len = Self::ip_len(bytes.len())?;
+        let pid = u16::from_be_bytes([bytes[ip_len], bytes[ip_len + 1]]);
+        Some(pid as i32)
+    }
+
+    /// Returns the time in milliseconds the unique id `msg_id` was created at. The id only holds
+    /// the time since the start of its month, so ids older than a month resolve to a later time.
+    pub fn get_nearly_time_from_id(msg_id: &str) -> Option<i64> {
+        let bytes = util_all::string_to_bytes(msg_id)?;
+        let ip_len = Self::ip_len(bytes.len())?;
+        let span_pos = ip_len + 2 + 4;
+        let span_millis = u32::from_be_bytes(bytes[span_pos..span_pos + 4].try_into().ok()?) as i64;
+
+        let now = Utc::now();
+        let mut month_start = Utc
+            .with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+            .single()?;
+        if month_start.timestamp_millis() + span_millis > now.timestamp_millis() {
+            month_start = month_start.checked_sub_months(Months::new(1))?;
+        }
+        Some(month_start.timestamp_millis() + span_millis)
+    }
+
+    // ip length of a unique id of `id_len` bytes
+    fn ip_len(id_len: usize) -> Option<usize> {
+        match id_len {
+            16 => Some(4),
+            28 => Some(16),
+            _ => None,
+        }
+    }
+
     pub fn set_uniq_id<T>(message: &mut T)
     where
         T: MessageTrait,
@@ -161,6 +219,25 @@ mod tests {
         assert_ne!(first_counter, second_counter);
     }
 
+    #[test]
+    fn unique_id_is_decoded_into_its_parts() {
+        let before = get_current_millis() as i64;
+        let unique_id = MessageClientIDSetter::create_uniq_id();
+        assert_eq!(unique_id.len(), *LEN * 2);
+
+        let ip = util_all::get_ip().unwrap_or_else(|_| create_fake_ip());
+        assert_eq!(MessageClientIDSetter::get_ip_from_id(&unique_id), Some(ip));
+        assert!(MessageClientIDSetter::get_ip_str_from_id(&unique_id).is_some());
+        assert_eq!(
+            MessageClientIDSetter::get_pid_from_id(&unique_id),
+            Some(std::process::id() as u16 as i32)
+        );
+        let created = MessageClientIDSetter::get_nearly_time_from_id(&unique_id).unwrap();
+        assert!(created >= before - 1000 && created <= get_current_millis() as i64);
+
+        assert_eq!(MessageClientIDSetter::get_pid_from_id("ABCD"), None);
+    }
+
     #[test]
     fn get_uniq_id_returns_none_when_not_set() {
         let message = Message::default();
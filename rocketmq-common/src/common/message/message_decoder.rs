@@ -66,29 +66,7 @@ pub const BORN_TIMESTAMP_POSITION: usize = 4 + 4 + 4 + 4 + 4 + 8 + 8 + 4 + 8;
 pub fn string_to_message_properties(
     properties: Option<&CheetahString>,
 ) -> HashMap<CheetahString, CheetahString> {
-    let mut map = HashMap::new();
-    if let Some(properties) = properties {
-        let mut index = 0;
-        let len = properties.len();
-        while index < len {
-            let new_index = properties[index..]
-                .find(PROPERTY_SEPARATOR)
-                .map_or(len, |i| index + i);
-            if new_index - index >= 3 {
-                if let Some(kv_sep_index) = properties[index..new_index].find(NAME_VALUE_SEPARATOR)
-                {
-                    let kv_sep_index = index + kv_sep_index;
-                    if kv_sep_index > index && kv_sep_index < new_index - 1 {
-                        let k = &properties[index..kv_sep_index];
-                        let v = &properties[kv_sep_index + 1..new_index];
-                        map.insert(CheetahString::from_slice(k), CheetahString::from_slice(v));
-                    }
-                }
-            }
-            index = new_index + 1;
-        }
-    }
-    map
+    str_to_message_properties(properties.map(|properties| properties.as_str()))
 }
 
 pub fn str_to_message_properties(
@@ -96,27 +74,59 @@ pub fn str_to_message_properties(
 ) -> HashMap<CheetahString, CheetahString> {
     let mut map = HashMap::new();
     if let Some(properties) = properties {
-        let mut index = 0;
+        for (k, v) in properties_iter(properties) {
+            map.insert(CheetahString::from_slice(k), CheetahString::from_slice(v));
+        }
+    }
+    map
+}
+
+/// Iterates over the name/value pairs of a serialized property string without building a map.
+pub fn properties_iter(properties: &str) -> PropertiesIter<'_> {
+    PropertiesIter {
+        properties,
+        index: 0,
+    }
+}
+
+/// Looks up a single property in a serialized property string, stopping at the first match.
+pub fn get_property<'a>(properties: &'a str, name: &str) -> Option<&'a str> {
+    properties_iter(properties).find_map(|(k, v)| (k == name).then_some(v))
+}
+
+/// Iterator returned by [`properties_iter`].
+pub struct PropertiesIter<'a> {
+    properties: &'a str,
+    index: usize,
+}
+
+impl<'a> Iterator for PropertiesIter<'a> {
+    type Item = (&'a str, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let properties = self.properties;
         let len = properties.len();
-        while index < len {
+        while self.index < len {
+            let index = self.index;
             let new_index = properties[index..]
                 .find(PROPERTY_SEPARATOR)
                 .map_or(len, |i| index + i);
-            if new_index - index >= 3 {
-                if let Some(kv_sep_index) = properties[index..new_index].find(NAME_VALUE_SEPARATOR)
-                {
-                    let kv_sep_index = index + kv_sep_index;
-                    if kv_sep_index > index && kv_sep_index < new_index - 1 {
-                        let k = &properties[index..kv_sep_index];
-                        let v = &properties[kv_sep_index + 1..new_index];
-                        map.insert(CheetahString::from_slice(k), CheetahString::from_slice(v));
-                    }
+            self.index = new_index + 1;
+            if new_index - index < 3 {
+                continue;
+            }
+            if let Some(kv_sep_index) = properties[index..new_index].find(NAME_VALUE_SEPARATOR) {
+                let kv_sep_index = index + kv_sep_index;
+                if kv_sep_index > index && kv_sep_index < new_index - 1 {
+                    return Some((
+                        &properties[index..kv_sep_index],
+                        &properties[kv_sep_index + 1..new_index],
+                    ));
                 }
             }
-            index = new_index + 1;
         }
+        None
     }
-    map
 }
 
 pub fn message_properties_to_string(
@@ -314,18 +324,7 @@ pub fn decode(
             }
             msg_ext.message.body = Some(body_bytes);
         } else {
-            let _ = byte_buffer.split_to(
-                BORN_TIMESTAMP_POSITION
-                    + born_host_ip_length
-                    + 4
-                    + 8
-                    + store_host_ip_length
-                    + 4
-                    + 4
-                    + 8
-                    + 4
-                    + body_len as usize,
-            );
+            byte_buffer.advance(body_len as usize);
         }
     }
 
@@ -473,36 +472,48 @@ pub fn decodes_batch_client(
     messages
 }
 
-pub fn decode_messages_from(mut message_ext: MessageExt, vec_: &mut Vec<MessageExt>) {
-    let messages = decode_messages(message_ext.message.body.as_mut().unwrap());
-    for message in messages {
-        let mut message_ext_inner = MessageExt {
-            message,
-            ..MessageExt::default()
-        };
-        message_ext_inner.set_topic(message_ext.get_topic().to_owned());
-        message_ext_inner.queue_offset = message_ext.queue_offset;
-        message_ext_inner.queue_id = message_ext.queue_id;
-        message_ext_inner.set_flag(message_ext.get_flag());
-        //MessageAccessor::set_properties(&mut
-        // message_client_ext,message.get_properties().clone()); messageClientExt.
-        // setBody(message.getBody())
-        message_ext_inner.store_host = message_ext.store_host;
-        message_ext_inner.born_host = message_ext.born_host;
-        message_ext_inner.store_timestamp = message_ext.store_timestamp;
-        message_ext_inner.born_timestamp = message_ext.born_timestamp;
-        message_ext_inner.sys_flag = message_ext.sys_flag;
-        message_ext_inner.commit_log_offset = message_ext.commit_log_offset;
-        message_ext_inner.set_wait_store_msg_ok(message_ext.is_wait_store_msg_ok());
-        vec_.push(message_ext_inner);
-    }
+pub fn decode_messages_from(message_ext: MessageExt, vec_: &mut Vec<MessageExt>) {
+    vec_.extend(decode_messages_batch(&message_ext));
+}
+
+/// Splits the body of a batch message, as written by [`encode_messages`], into the messages it
+/// wraps. Every inner message inherits the topic, queue and store information of the batch.
+pub fn decode_messages_batch(batch: &MessageExt) -> Vec<MessageExt> {
+    let Some(mut body) = batch.message.body.clone() else {
+        return Vec::new();
+    };
+    decode_messages(&mut body)
+        .into_iter()
+        .map(|message| {
+            let mut message_ext = MessageExt {
+                message,
+                ..MessageExt::default()
+            };
+            message_ext.set_topic(batch.get_topic().to_owned());
+            message_ext.queue_offset = batch.queue_offset;
+            message_ext.queue_id = batch.queue_id;
+            message_ext.set_flag(batch.get_flag());
+            message_ext.store_host = batch.store_host;
+            message_ext.born_host = batch.born_host;
+            message_ext.store_timestamp = batch.store_timestamp;
+            message_ext.born_timestamp = batch.born_timestamp;
+            message_ext.sys_flag = batch.sys_flag;
+            message_ext.commit_log_offset = batch.commit_log_offset;
+            message_ext.set_wait_store_msg_ok(batch.is_wait_store_msg_ok());
+            message_ext
+        })
+        .collect()
 }
 
 pub fn decode_messages(buffer: &mut Bytes) -> Vec<Message> {
     let mut messages = Vec::new();
-    while buffer.has_remaining() {
-        let message = decode_message(buffer);
-        messages.push(message);
+    while buffer.remaining() >= 4 {
+        let total_size = BigEndian::read_i32(&buffer[..4]);
+        if total_size <= 0 || total_size as usize > buffer.remaining() {
+            break;
+        }
+        let mut message_buffer = buffer.split_to(total_size as usize);
+        messages.push(decode_message(&mut message_buffer));
     }
     messages
 }
@@ -539,10 +550,17 @@ pub fn decode_message(buffer: &mut Bytes) -> Message {
     }
 }
 
-pub fn decode_message_id(msg_id: &str) -> MessageId {
-    let bytes = util_all::string_to_bytes(msg_id).unwrap();
+/// Decodes a message id assigned by the broker (the offset message id) into the address of the
+/// broker that stored the message and the commit log offset of the message.
+pub fn decode_message_id(msg_id: &str) -> rocketmq_error::RocketMQResult<MessageId> {
+    if !matches!(msg_id.len(), 32 | 56) || !msg_id.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(rocketmq_error::RocketmqError::IllegalArgument(format!(
+            "invalid message id: {msg_id}"
+        )));
+    }
+    let bytes = util_all::string_to_bytes(msg_id).unwrap_or_default();
     let mut buffer = Bytes::from(bytes);
-    let len = if msg_id.len() == 32 {
+    let address = if msg_id.len() == 32 {
         let mut ip = [0u8; 4];
         buffer.copy_to_slice(&mut ip);
         let port = buffer.get_i32();
@@ -553,10 +571,10 @@ pub fn decode_message_id(msg_id: &str) -> MessageId {
         let port = buffer.get_i32();
         SocketAddr::new(IpAddr::V6(Ipv6Addr::from(ip)), port as u16)
     };
-    MessageId {
-        address: len,
+    Ok(MessageId {
+        address,
         offset: buffer.get_i64(),
-    }
+    })
 }
 
 pub fn encode(
@@ -591,11 +609,10 @@ pub fn encode(
         None
     };
     let body_len = new_body.as_ref().map_or(body.len(), |b| b.len());
-    let store_size = message_ext.store_size;
-    let mut byte_buffer = if store_size > 0 {
-        BytesMut::with_capacity(store_size as usize)
+    let store_size = if message_ext.store_size > 0 {
+        message_ext.store_size
     } else {
-        let store_size = 4 // 1 TOTALSIZE
+        (4 // 1 TOTALSIZE
              + 4 // 2 MAGICCODE
              + 4 // 3 BODYCRC
              + 4 // 4 QUEUEID
@@ -611,9 +628,9 @@ pub fn encode(
              + 8 // 14 Prepared Transaction Offset
              + 4 + body_len // 14 BODY
              + 1 + topic_len // 15 TOPIC
-             + 2 + properties_length; // 16 propertiesLength
-        BytesMut::with_capacity(store_size)
+             + 2 + properties_length) as i32 // 16 propertiesLength
     };
+    let mut byte_buffer = BytesMut::with_capacity(store_size as usize);
 
     // 1 TOTALSIZE
     byte_buffer.put_i32(store_size);
@@ -679,8 +696,8 @@ pub fn encode(
         byte_buffer.put_slice(body);
     }
 
-    // 16 TOPIC
-    byte_buffer.put_i16(topic_len as i16);
+    // 16 TOPIC, MESSAGE_MAGIC_CODE is the V1 layout whose topic length takes a single byte
+    byte_buffer.put_u8(topic_len as u8);
     byte_buffer.put_slice(topics);
 
     // 17 properties
@@ -789,8 +806,8 @@ pub fn encode_uniquely(
         byte_buffer.put_slice(body);
     }
 
-    // 14 TOPIC
-    byte_buffer.put_i16(topic_len as i16);
+    // 14 TOPIC, MESSAGE_MAGIC_CODE is the V1 layout whose topic length takes a single byte
+    byte_buffer.put_u8(topic_len as u8);
     byte_buffer.put_slice(topics);
 
     // 15 properties
@@ -816,97 +833,166 @@ pub fn create_crc32(mut input: &mut [u8], crc32: u32) {
 }
 
 pub fn decode_properties(bytes: &mut Bytes) -> Option<HashMap<CheetahString, CheetahString>> {
-    // Ensure we have enough bytes to read SYSFLAG and MAGICCODE.
-    if bytes.len() < SYSFLAG_POSITION + 4 {
+    let view = decode_view(bytes)?;
+    if view.properties.is_empty() {
         return None;
     }
+    Some(str_to_message_properties(Some(view.properties)))
+}
 
-    // Read sysFlag and magicCode using fixed positions.
-    let sys_flag = BigEndian::read_i32(&bytes[SYSFLAG_POSITION..SYSFLAG_POSITION + 4]);
-    let magic_code =
-        BigEndian::read_i32(&bytes[MESSAGE_MAGIC_CODE_POSITION..MESSAGE_MAGIC_CODE_POSITION + 4]);
-    let version = match MessageVersion::value_of_magic_code(magic_code) {
-        Ok(value) => value,
-        Err(_) => return None,
-    };
+/// A message decoded in place from its commit log encoding.
+///
+/// Unlike [`decode`], nothing is copied: the body, topic and properties borrow from the
+/// underlying buffer and properties are only parsed when asked for.
+#[derive(Debug, Clone, Copy)]
+pub struct MessageView<'a> {
+    pub store_size: i32,
+    pub body_crc: u32,
+    pub queue_id: i32,
+    pub flag: i32,
+    pub queue_offset: i64,
+    pub commit_log_offset: i64,
+    pub sys_flag: i32,
+    pub born_timestamp: i64,
+    pub born_host: SocketAddr,
+    pub store_timestamp: i64,
+    pub store_host: SocketAddr,
+    pub reconsume_times: i32,
+    pub prepared_transaction_offset: i64,
+    pub body: &'a [u8],
+    pub topic: &'a str,
+    pub properties: &'a str,
+}
 
-    // Determine address lengths.
-    let bornhost_length = if (sys_flag & MessageSysFlag::BORNHOST_V6_FLAG) == 0 {
-        8
-    } else {
-        20
-    };
-    let storehost_address_length = if (sys_flag & MessageSysFlag::STOREHOSTADDRESS_V6_FLAG) == 0 {
-        8
-    } else {
-        20
-    };
+impl<'a> MessageView<'a> {
+    pub fn property(&self, name: &str) -> Option<&'a str> {
+        get_property(self.properties, name)
+    }
 
-    // Calculate the bodySizePosition as in Java.
-    let body_size_position = 4   // TOTALSIZE
-        + 4   // MAGICCODE
-        + 4   // BODYCRC
-        + 4   // QUEUEID
-        + 4   // FLAG
-        + 8   // QUEUEOFFSET
-        + 8   // PHYSICALOFFSET
-        + 4   // SYSFLAG
-        + 8   // BORNTIMESTAMP
-        + bornhost_length // BORNHOST
-        + 8   // STORETIMESTAMP
-        + storehost_address_length // STOREHOSTADDRESS
-        + 4   // RECONSUMETIMES
-        + 8; // Prepared Transaction Offset
-
-    if bytes.len() < body_size_position + 4 {
-        return None;
+    pub fn properties_map(&self) -> HashMap<CheetahString, CheetahString> {
+        str_to_message_properties(Some(self.properties))
     }
 
-    // Read the body size stored as an int.
-    let body_size =
-        BigEndian::read_i32(&bytes[body_size_position..body_size_position + 4]) as usize;
+    pub fn msg_id(&self) -> String {
+        build_message_id(self.store_host, self.commit_log_offset)
+    }
+}
 
-    // Compute the topic length position.
-    let topic_length_position = body_size_position + 4 + body_size;
-    if bytes.len() < topic_length_position {
-        return None;
+struct SliceReader<'a> {
+    buffer: &'a [u8],
+    position: usize,
+}
+
+impl<'a> SliceReader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let end = self.position.checked_add(len)?;
+        let slice = self.buffer.get(self.position..end)?;
+        self.position = end;
+        Some(slice)
     }
 
-    // Create a Cursor over the slice starting at the topic length position.
-    let slice = &bytes[topic_length_position..];
-    let cursor = Cursor::new(slice);
-    let topic_length_size = version.get_topic_length_size();
-    bytes.advance(topic_length_position);
-    let topic_length = version.get_topic_length(bytes);
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|b| b[0])
+    }
 
-    // Calculate the properties position.
-    let properties_position = topic_length_position + topic_length_size + topic_length;
-    if bytes.len() < properties_position + 2 {
-        return None;
+    fn i16(&mut self) -> Option<i16> {
+        self.take(2).map(BigEndian::read_i16)
     }
 
-    // Read a short (2 bytes) as propertiesLength.
-    let properties_length =
-        BigEndian::read_i16(&bytes[properties_position..properties_position + 2]);
+    fn i32(&mut self) -> Option<i32> {
+        self.take(4).map(BigEndian::read_i32)
+    }
 
-    // Advance past the short value.
-    let properties_start = properties_position + 2;
-    if properties_length > 0 {
-        let end = properties_start + (properties_length as usize);
-        if bytes.len() < end {
-            return None;
-        }
-        let properties_bytes = &bytes[properties_start..end];
-        if let Ok(properties_string) = String::from_utf8(properties_bytes.to_vec()) {
-            Some(string_to_message_properties(Some(
-                &CheetahString::from_string(properties_string),
-            )))
+    fn u32(&mut self) -> Option<u32> {
+        self.take(4).map(BigEndian::read_u32)
+    }
+
+    fn i64(&mut self) -> Option<i64> {
+        self.take(8).map(BigEndian::read_i64)
+    }
+
+    fn socket_addr(&mut self, v6: bool) -> Option<SocketAddr> {
+        let address = if v6 {
+            let mut ip = [0u8; 16];
+            ip.copy_from_slice(self.take(16)?);
+            IpAddr::V6(Ipv6Addr::from(ip))
         } else {
-            None
+            let mut ip = [0u8; 4];
+            ip.copy_from_slice(self.take(4)?);
+            IpAddr::V4(Ipv4Addr::from(ip))
+        };
+        let port = self.i32()?;
+        Some(SocketAddr::new(address, port as u16))
+    }
+}
+
+/// Decodes the message at the start of `buffer` without copying it, returning `None` if the
+/// buffer is truncated or does not hold a valid message.
+pub fn decode_view(buffer: &[u8]) -> Option<MessageView<'_>> {
+    let mut reader = SliceReader {
+        buffer,
+        position: 0,
+    };
+    let store_size = reader.i32()?;
+    let version = MessageVersion::value_of_magic_code(reader.i32()?).ok()?;
+    let body_crc = reader.u32()?;
+    let queue_id = reader.i32()?;
+    let flag = reader.i32()?;
+    let queue_offset = reader.i64()?;
+    let commit_log_offset = reader.i64()?;
+    let sys_flag = reader.i32()?;
+    let born_timestamp = reader.i64()?;
+    let born_host = reader.socket_addr(sys_flag & MessageSysFlag::BORNHOST_V6_FLAG != 0)?;
+    let store_timestamp = reader.i64()?;
+    let store_host =
+        reader.socket_addr(sys_flag & MessageSysFlag::STOREHOSTADDRESS_V6_FLAG != 0)?;
+    let reconsume_times = reader.i32()?;
+    let prepared_transaction_offset = reader.i64()?;
+    let body_len = reader.i32()?;
+    let body = reader.take(body_len.max(0) as usize)?;
+    let topic_len = match version {
+        MessageVersion::V1 => reader.u8()? as usize,
+        MessageVersion::V2 => reader.i16()? as usize,
+    };
+    let topic = str::from_utf8(reader.take(topic_len)?).ok()?;
+    let properties_len = reader.i16()?;
+    let properties = str::from_utf8(reader.take(properties_len.max(0) as usize)?).ok()?;
+    Some(MessageView {
+        store_size,
+        body_crc,
+        queue_id,
+        flag,
+        queue_offset,
+        commit_log_offset,
+        sys_flag,
+        born_timestamp,
+        born_host,
+        store_timestamp,
+        store_host,
+        reconsume_times,
+        prepared_transaction_offset,
+        body,
+        topic,
+        properties,
+    })
+}
+
+/// Decodes every message in `buffer` as a [`MessageView`], stopping at the first entry that
+/// cannot be decoded.
+pub fn decode_views(buffer: &[u8]) -> Vec<MessageView<'_>> {
+    let mut views = Vec::new();
+    let mut position = 0;
+    while position < buffer.len() {
+        let Some(view) = decode_view(&buffer[position..]) else {
+            break;
+        };
+        if view.store_size <= 0 {
+            break;
         }
-    } else {
-        None
+        position += view.store_size as usize;
+        views.push(view);
     }
+    views
 }
 
 #[cfg(test)]
@@ -950,11 +1036,29 @@ mod tests {
     #[test]
     fn decode_message_id_ipv4() {
         let msg_id = "7F0000010007D8260BF075769D36C348";
-        let message_id = decode_message_id(msg_id);
+        let message_id = decode_message_id(msg_id).unwrap();
         assert_eq!(message_id.address, "127.0.0.1:55334".parse().unwrap());
         assert_eq!(message_id.offset, 860316681131967304);
     }
 
+    #[test]
+    fn decode_message_id_round_trips_built_ids() {
+        for address in ["10.1.2.3:10911", "[fe80::1]:10911"] {
+            let address: SocketAddr = address.parse().unwrap();
+            let msg_id = crate::utils::message_utils::build_message_id(address, 123_456);
+            let message_id = decode_message_id(&msg_id).unwrap();
+            assert_eq!(message_id.address, address);
+            assert_eq!(message_id.offset, 123_456);
+        }
+    }
+
+    #[test]
+    fn decode_message_id_rejects_malformed_ids() {
+        assert!(decode_message_id("").is_err());
+        assert!(decode_message_id("7F0000010007D826").is_err());
+        assert!(decode_message_id("7F0000010007D8260BF075769D36C3ZZ").is_err());
+    }
+
     #[test]
     fn encode_with_compression() {
         let mut message_ext = MessageExt::default();
@@ -996,6 +1100,19 @@ mod tests {
         assert!(!bytes.is_empty());
     }
 
+    #[test]
+    fn encode_writes_total_size_and_decode_can_skip_body() {
+        let mut message_ext = MessageExt::default();
+        message_ext.set_topic(CheetahString::from_static_str("TopicTest"));
+        message_ext.set_body(Bytes::from("Hello, World!"));
+        let mut bytes = encode(&message_ext, false).unwrap();
+        assert_eq!(BigEndian::read_i32(&bytes[..4]) as usize, bytes.len());
+
+        let decoded = decode(&mut bytes, false, false, false, false, false).unwrap();
+        assert_eq!(decoded.get_topic().as_str(), "TopicTest");
+        assert!(decoded.get_body().is_none());
+    }
+
     #[test]
     fn encode_uniquely_with_compression() {
         let mut message_ext = MessageExt::default();
@@ -1129,4 +1246,113 @@ mod tests {
         );
         assert!(decode_properties(&mut bytes.freeze()).is_none());
     }
+
+    fn encoded_store_message(topic: &str, tags: &str, body: &'static str) -> Bytes {
+        let mut message_ext = MessageExt::default();
+        message_ext.set_topic(CheetahString::from_slice(topic));
+        message_ext.set_body(Bytes::from_static(body.as_bytes()));
+        message_ext.put_property(
+            CheetahString::from_static_str(MessageConst::PROPERTY_TAGS),
+            CheetahString::from_slice(tags),
+        );
+        message_ext.set_queue_id(3);
+        message_ext.set_queue_offset(42);
+        let mut bytes = BytesMut::from(encode(&message_ext, false).unwrap().as_ref());
+        let len = bytes.len() as i32;
+        bytes[..4].copy_from_slice(&len.to_be_bytes());
+        bytes.freeze()
+    }
+
+    #[test]
+    fn properties_iter_skips_malformed_pairs() {
+        let properties = "a\u{1}1\u{2}\u{1}x\u{2}bb\u{2}TAGS\u{1}TagA\u{2}";
+        let pairs: Vec<_> = properties_iter(properties).collect();
+        assert_eq!(pairs, vec![("a", "1"), ("TAGS", "TagA")]);
+        assert_eq!(get_property(properties, "TAGS"), Some("TagA"));
+        assert_eq!(get_property(properties, "KEYS"), None);
+    }
+
+    #[test]
+    fn decode_view_borrows_the_encoded_message() {
+        let bytes = encoded_store_message("TopicA", "TagA", "hello");
+        let view = decode_view(&bytes).unwrap();
+        assert_eq!(view.store_size as usize, bytes.len());
+        assert_eq!(view.queue_id, 3);
+        assert_eq!(view.queue_offset, 42);
+        assert_eq!(view.topic, "TopicA");
+        assert_eq!(view.body, b"hello");
+        assert_eq!(view.property(MessageConst::PROPERTY_TAGS), Some("TagA"));
+
+        let decoded = decode(&mut bytes.clone(), true, false, false, false, false).unwrap();
+        assert_eq!(decoded.get_topic().as_str(), view.topic);
+        assert_eq!(decoded.get_properties(), &view.properties_map());
+        assert_eq!(decoded.msg_id.as_str(), view.msg_id());
+    }
+
+    #[test]
+    fn decode_view_rejects_truncated_buffer() {
+        let bytes = encoded_store_message("TopicA", "TagA", "hello");
+        assert!(decode_view(&bytes[..bytes.len() - 1]).is_none());
+        assert!(decode_view(&[]).is_none());
+    }
+
+    #[test]
+    fn decode_views_walks_consecutive_messages() {
+        let mut buffer = BytesMut::new();
+        buffer.put_slice(&encoded_store_message("TopicA", "TagA", "one"));
+        buffer.put_slice(&encoded_store_message("TopicB", "TagB", "two"));
+        let views = decode_views(&buffer);
+        assert_eq!(views.len(), 2);
+        assert_eq!(views[1].topic, "TopicB");
+        assert_eq!(views[1].body, b"two");
+
+        let mut buffer = buffer.freeze();
+        let properties = decode_properties(&mut buffer).unwrap();
+        assert_eq!(
+            properties
+                .get(MessageConst::PROPERTY_TAGS)
+                .map(|t| t.as_str()),
+            Some("TagA")
+        );
+    }
+
+    #[test]
+    fn decode_messages_batch_splits_batch_body() {
+        let mut first = Message::default();
+        first.set_body(Bytes::from_static(b"first"));
+        first.put_property(
+            CheetahString::from_static_str(MessageConst::PROPERTY_KEYS),
+            CheetahString::from_static_str("k1"),
+        );
+        let mut second = Message::default();
+        second.set_body(Bytes::from_static(b"second"));
+
+        let mut batch = MessageExt::default();
+        batch.set_topic(CheetahString::from_static_str("BatchTopic"));
+        batch.set_queue_id(1);
+        batch.set_queue_offset(7);
+        batch.set_body(encode_messages(&[first, second]));
+
+        let messages = decode_messages_batch(&batch);
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].get_topic().as_str(), "BatchTopic");
+        assert_eq!(messages[0].queue_offset, 7);
+        assert_eq!(messages[0].get_body().unwrap().as_ref(), b"first");
+        assert_eq!(
+            messages[0]
+                .get_property(&CheetahString::from_static_str(MessageConst::PROPERTY_KEYS))
+                .map(|k| k.to_string()),
+            Some("k1".to_string())
+        );
+        assert_eq!(messages[1].get_body().unwrap().as_ref(), b"second");
+    }
+
+    #[test]
+    fn decode_messages_stops_at_truncated_entry() {
+        let mut message = Message::default();
+        message.set_body(Bytes::from_static(b"body"));
+        let encoded = encode_messages(&[message.clone(), message]);
+        let mut truncated = encoded.slice(..encoded.len() - 3);
+        assert_eq!(decode_messages(&mut truncated).len(), 1);
+    }
 }
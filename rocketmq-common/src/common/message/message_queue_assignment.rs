@@ -33,6 +33,12 @@ pub struct MessageQueueAssignment {
     pub attachments: Option<HashMap<CheetahString, CheetahString>>,
 }
 
+impl MessageQueueAssignment {
+    /// Attachment key under which the broker reports the pop share queue number of a POP
+    /// assignment.
+    pub const POP_SHARE_QUEUE_NUM: &'static str = "popShareQueueNum";
+}
+
 impl Hash for MessageQueueAssignment {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.message_queue.hash(state);
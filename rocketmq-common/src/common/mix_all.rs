@@ -180,6 +180,18 @@ pub fn string_to_properties(input: &str) -> Option<HashMap<CheetahString, Cheeta
     Some(properties)
 }
 
+/// Writes `properties` as `key=value` lines, the format read by [`string_to_properties`].
+pub fn properties_to_string(properties: &HashMap<CheetahString, CheetahString>) -> String {
+    let mut output = String::new();
+    for (key, value) in properties {
+        output.push_str(key.as_str());
+        output.push('=');
+        output.push_str(value.as_str());
+        output.push('\n');
+    }
+    output
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -291,4 +303,18 @@ mod tests {
         let result = string_to_properties(input);
         assert!(result.is_none(), "Parsing should fail for invalid input");
     }
+
+    #[test]
+    fn test_properties_to_string_round_trip() {
+        let mut properties = HashMap::new();
+        properties.insert(CheetahString::from("key1"), CheetahString::from("value1"));
+        properties.insert(
+            CheetahString::from("key2"),
+            CheetahString::from("310:1,*:0"),
+        );
+
+        let output = properties_to_string(&properties);
+        assert_eq!(output.lines().count(), 2);
+        assert_eq!(string_to_properties(&output), Some(properties));
+    }
 }
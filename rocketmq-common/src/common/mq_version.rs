@@ -1261,6 +1261,17 @@ impl From<RocketMqVersion> for i32 {
 }
 impl RocketMqVersion {
     pub const CURRENT_VERSION: RocketMqVersion = RocketMqVersion::HigherVerSion;
+
+    /// Reads the version carried by a remoting command. Versions newer than the ones known to
+    /// this build are taken as [`HigherVerSion`](Self::HigherVerSion) instead of failing, so
+    /// that newer peers can still talk to it.
+    pub fn from_version(version: i32) -> RocketMqVersion {
+        RocketMqVersion::try_from(version).unwrap_or(if version < 0 {
+            RocketMqVersion::V300Snapshot
+        } else {
+            RocketMqVersion::HigherVerSion
+        })
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -1888,6 +1899,21 @@ impl fmt::Display for RocketMqVersion {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+
     #[test]
     fn test_version_from_str() {}
+
+    #[test]
+    fn unknown_versions_are_read_leniently() {
+        assert_eq!(RocketMqVersion::from_version(37), RocketMqVersion::V3011);
+        assert_eq!(
+            RocketMqVersion::from_version(10_000),
+            RocketMqVersion::HigherVerSion
+        );
+        assert_eq!(
+            RocketMqVersion::from_version(-1),
+            RocketMqVersion::V300Snapshot
+        );
+    }
 }
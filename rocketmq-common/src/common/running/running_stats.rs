@@ -32,6 +32,12 @@ pub enum RunningStats {
 
     /// Offset for scheduled messages
     ScheduleMessageOffset,
+
+    /// Scheduled messages of a delay level that are not delivered yet
+    ScheduleMessagePending,
+
+    /// Milliseconds the delivery of a delay level runs behind the due time of its messages
+    ScheduleMessageDequeueLag,
 }
 
 impl RunningStats {
@@ -43,6 +49,8 @@ impl RunningStats {
             RunningStats::CommitLogDiskRatio => "commitLogDiskRatio",
             RunningStats::ConsumeQueueDiskRatio => "consumeQueueDiskRatio",
             RunningStats::ScheduleMessageOffset => "scheduleMessageOffset",
+            RunningStats::ScheduleMessagePending => "scheduleMessagePending",
+            RunningStats::ScheduleMessageDequeueLag => "scheduleMessageDequeueLag",
         }
     }
 }
@@ -63,6 +71,8 @@ impl std::str::FromStr for RunningStats {
             "commitLogDiskRatio" => Ok(RunningStats::CommitLogDiskRatio),
             "consumeQueueDiskRatio" => Ok(RunningStats::ConsumeQueueDiskRatio),
             "scheduleMessageOffset" => Ok(RunningStats::ScheduleMessageOffset),
+            "scheduleMessagePending" => Ok(RunningStats::ScheduleMessagePending),
+            "scheduleMessageDequeueLag" => Ok(RunningStats::ScheduleMessageDequeueLag),
             _ => Err(format!("Unknown RunningStats value: {s}")),
         }
     }
@@ -18,10 +18,21 @@ use serde::Deserialize;
 use serde::Serialize;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub struct ServerConfig {
     pub listen_port: u32,
     pub bind_address: String,
+    /// Largest request frame accepted, connections sending a larger one are closed.
+    pub max_frame_length: usize,
+    /// Largest request header accepted, connections sending a larger one are closed.
+    pub max_header_length: usize,
+    /// Inflates request bodies flagged as compressed before they reach the processors, which
+    /// otherwise reject them.
+    pub decompress_request_body: bool,
+    /// Pending outbound bytes above which a connection stops being writable.
+    pub write_buffer_high_water_mark: usize,
+    /// Pending outbound bytes below which an unwritable connection becomes writable again.
+    pub write_buffer_low_water_mark: usize,
 }
 
 impl Default for ServerConfig {
@@ -29,6 +40,11 @@ impl Default for ServerConfig {
         ServerConfig {
             listen_port: 10911,
             bind_address: "0.0.0.0".to_string(),
+            max_frame_length: 16 * 1024 * 1024,
+            max_header_length: 1024 * 1024,
+            decompress_request_body: true,
+            write_buffer_high_water_mark: 32 * 1024 * 1024,
+            write_buffer_low_water_mark: 16 * 1024 * 1024,
         }
     }
 }
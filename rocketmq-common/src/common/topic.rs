@@ -22,6 +22,8 @@ use std::sync::Mutex;
 use cheetah_string::CheetahString;
 use lazy_static::lazy_static;
 
+use crate::common::pop_ack_constants::PopAckConstants;
+
 pub const TOPIC_MAX_LENGTH: usize = 127;
 lazy_static! {
     static ref VALID_CHAR_BIT_MAP: [bool; 128] = {
@@ -135,6 +137,7 @@ impl TopicValidator {
 
     pub fn is_not_allowed_send_topic(topic: &str) -> bool {
         NOT_ALLOWED_SEND_TOPIC_SET.contains(topic)
+            || PopAckConstants::is_start_with_revive_prefix(topic)
     }
 
     pub fn add_system_topic(system_topic: impl Into<String>) {
@@ -265,4 +268,11 @@ mod tests {
         let not_allowed_topics = TopicValidator::get_not_allowed_send_topic_set();
         assert!(not_allowed_topics.contains(TopicValidator::RMQ_SYS_SCHEDULE_TOPIC));
     }
+
+    #[test]
+    fn is_not_allowed_send_topic_with_revive_topic() {
+        assert!(TopicValidator::is_not_allowed_send_topic(
+            "rmq_sys_REVIVE_LOG_DefaultCluster"
+        ));
+    }
 }
@@ -168,8 +168,13 @@ pub fn build_batch_message_id(
     message_id
 }
 
-pub fn parse_message_id(_msg_id: impl Into<String>) -> (SocketAddr, i64) {
-    unimplemented!()
+/// Parses a message id built by [`build_message_id`] back into the store host and the commit log
+/// offset.
+pub fn parse_message_id(
+    msg_id: impl Into<String>,
+) -> rocketmq_error::RocketMQResult<(SocketAddr, i64)> {
+    let message_id = crate::MessageDecoder::decode_message_id(&msg_id.into())?;
+    Ok((message_id.address, message_id.offset))
 }
 
 #[cfg(test)]
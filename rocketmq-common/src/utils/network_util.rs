@@ -15,6 +15,11 @@
  * limitations under the License.
  */
 use std::net::IpAddr;
+use std::net::SocketAddr;
+use std::net::ToSocketAddrs;
+
+use rocketmq_error::RocketMQResult;
+use rocketmq_error::RocketmqError;
 
 pub struct NetworkUtil;
 
@@ -34,4 +39,109 @@ impl NetworkUtil {
             },
         }
     }
+
+    /// Joins `host` and `port` into an address string, wrapping IPv6 literals in brackets
+    /// (`[::1]:10911`). Hosts that are already bracketed are kept as they are.
+    pub fn format_address(host: &str, port: impl std::fmt::Display) -> String {
+        if host.contains(':') && !host.starts_with('[') {
+            format!("[{host}]:{port}")
+        } else {
+            format!("{host}:{port}")
+        }
+    }
+
+    /// Splits `host:port` into its parts. IPv6 hosts must use the bracket notation, the
+    /// brackets are removed from the returned host.
+    pub fn split_host_port(addr: &str) -> Option<(&str, u16)> {
+        let (host, port) = addr.rsplit_once(':')?;
+        let port = port.parse::<u16>().ok()?;
+        let host = match host.strip_prefix('[') {
+            Some(host) => host.strip_suffix(']')?,
+            None if host.contains(':') => return None,
+            None => host,
+        };
+        if host.is_empty() {
+            return None;
+        }
+        Some((host, port))
+    }
+
+    /// Resolves `host:port` to a socket address. IP literals (including bracketed IPv6) are
+    /// parsed directly, anything else is resolved as a hostname and the first address wins.
+    pub fn resolve_socket_addr(addr: &str) -> RocketMQResult<SocketAddr> {
+        if let Ok(socket_addr) = addr.parse::<SocketAddr>() {
+            return Ok(socket_addr);
+        }
+        let (host, port) = Self::split_host_port(addr).ok_or_else(|| {
+            RocketmqError::IpError(format!(
+                "invalid address {addr}, expected host:port or [ipv6]:port"
+            ))
+        })?;
+        (host, port)
+            .to_socket_addrs()
+            .map_err(|e| RocketmqError::IpError(format!("failed to resolve address {addr}: {e}")))?
+            .next()
+            .ok_or_else(|| RocketmqError::IpError(format!("address {addr} resolved to nothing")))
+    }
+
+    /// Resolves the address built from `host` and `port`, see [`Self::resolve_socket_addr`].
+    pub fn resolve_host_port(
+        host: &str,
+        port: impl std::fmt::Display,
+    ) -> RocketMQResult<SocketAddr> {
+        Self::resolve_socket_addr(&Self::format_address(host, port))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_address_brackets_ipv6() {
+        assert_eq!(
+            NetworkUtil::format_address("127.0.0.1", 10911),
+            "127.0.0.1:10911"
+        );
+        assert_eq!(NetworkUtil::format_address("::1", 10911), "[::1]:10911");
+        assert_eq!(NetworkUtil::format_address("[::1]", 10911), "[::1]:10911");
+        assert_eq!(
+            NetworkUtil::format_address("localhost", 10911),
+            "localhost:10911"
+        );
+    }
+
+    #[test]
+    fn split_host_port_handles_brackets() {
+        assert_eq!(
+            NetworkUtil::split_host_port("[fe80::1]:9876"),
+            Some(("fe80::1", 9876))
+        );
+        assert_eq!(
+            NetworkUtil::split_host_port("broker-a:10911"),
+            Some(("broker-a", 10911))
+        );
+        assert_eq!(NetworkUtil::split_host_port("fe80::1:9876"), None);
+        assert_eq!(NetworkUtil::split_host_port("broker-a"), None);
+        assert_eq!(NetworkUtil::split_host_port(":10911"), None);
+    }
+
+    #[test]
+    fn resolve_socket_addr_parses_literals_and_hostnames() {
+        let v4 = NetworkUtil::resolve_socket_addr("127.0.0.1:10911").unwrap();
+        assert_eq!(v4.port(), 10911);
+
+        let v6 = NetworkUtil::resolve_host_port("::1", 10911).unwrap();
+        assert!(v6.is_ipv6());
+        assert_eq!(v6.port(), 10911);
+
+        let host = NetworkUtil::resolve_socket_addr("localhost:10911").unwrap();
+        assert!(host.ip().is_loopback());
+    }
+
+    #[test]
+    fn resolve_socket_addr_rejects_invalid_addresses() {
+        assert!(NetworkUtil::resolve_socket_addr("127.0.0.1").is_err());
+        assert!(NetworkUtil::resolve_socket_addr("127.0.0.1:port").is_err());
+    }
 }
@@ -15,12 +15,22 @@
  * limitations under the License.
  */
 
+use std::collections::BTreeMap;
+use std::collections::HashSet;
 use std::fmt::Debug;
+use std::path::Path;
 use std::path::PathBuf;
 
 use config::Config;
 use rocketmq_error::RocketMQResult;
+use rocketmq_error::RocketmqError;
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Map;
+use serde_json::Value;
+
+use crate::common::mix_all::string_to_properties;
 
 pub fn parse_config_file<'de, C>(config_file: PathBuf) -> RocketMQResult<C>
 where
@@ -37,3 +47,351 @@ where
     };
     Ok(config_file)
 }
+
+/// A Java style `key=value` config such as `broker.conf`, applied on top of the defaults of one
+/// or more config structs.
+///
+/// Keys are the camelCase field names of the Java config classes. Fields of nested structs are
+/// addressed by their own name, as Java flattens them (`brokerClusterName` sets
+/// `brokerIdentity.brokerClusterName`); a key matching several fields sets all of them.
+pub struct PropertiesConfig {
+    properties: BTreeMap<String, String>,
+    applied: HashSet<String>,
+}
+
+impl PropertiesConfig {
+    pub fn load(path: impl AsRef<Path>) -> RocketMQResult<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            RocketmqError::ConfigError(format!("read {} failed: {e}", path.display()))
+        })?;
+        Self::parse(&content)
+    }
+
+    pub fn parse(content: &str) -> RocketMQResult<Self> {
+        let properties = string_to_properties(content).ok_or_else(|| {
+            RocketmqError::ConfigError("every line must be a `key=value` pair".to_string())
+        })?;
+        Ok(PropertiesConfig {
+            properties: properties
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            applied: HashSet::new(),
+        })
+    }
+
+    /// Builds `C` from its defaults overridden by the properties matching its fields.
+    pub fn apply<C>(&mut self) -> RocketMQResult<C>
+    where
+        C: Default + Serialize + DeserializeOwned,
+    {
+        let config_error =
+            |err: &dyn std::fmt::Display| RocketmqError::ConfigError(err.to_string());
+        let mut config = serde_json::to_value(C::default()).map_err(|e| config_error(&e))?;
+        // fields never serialized, like secrets, can still be set as unset optional fields; the
+        // field names include the aliases, which must not be added next to their field
+        if let Value::Object(ref fields) = config {
+            let mut unserialized = fields.clone();
+            for name in struct_fields::<C>() {
+                if fields.contains_key(*name) {
+                    continue;
+                }
+                let mut candidate = fields.clone();
+                candidate.insert(name.to_string(), Value::Null);
+                if serde_json::from_value::<C>(Value::Object(candidate)).is_ok() {
+                    unserialized.insert(name.to_string(), Value::Null);
+                }
+            }
+            config = Value::Object(unserialized);
+        }
+        for (key, value) in &self.properties {
+            let Value::Object(ref mut fields) = config else {
+                return Err(config_error(&"config must be a struct"));
+            };
+            let mut unset_fields = Vec::new();
+            if set_field(fields, key, value, &mut Vec::new(), &mut unset_fields)? {
+                self.applied.insert(key.clone());
+            }
+            for path in unset_fields {
+                set_unset_field::<C>(&mut config, &path, key, value)?;
+            }
+        }
+        serde_json::from_value(config).map_err(|e| config_error(&e))
+    }
+
+    /// Keys that none of the configs applied so far had a field for.
+    pub fn unknown_keys(&self) -> Vec<&str> {
+        self.properties
+            .keys()
+            .filter(|key| !self.applied.contains(*key))
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+/// Returns the names of the fields `C` deserializes, including those it does not serialize.
+fn struct_fields<C: DeserializeOwned>() -> &'static [&'static str] {
+    struct FieldNames(&'static [&'static str]);
+
+    impl<'de> serde::Deserializer<'de> for &mut FieldNames {
+        type Error = serde::de::value::Error;
+
+        fn deserialize_any<V: serde::de::Visitor<'de>>(
+            self,
+            _visitor: V,
+        ) -> Result<V::Value, Self::Error> {
+            Err(serde::de::Error::custom("not a struct"))
+        }
+
+        fn deserialize_struct<V: serde::de::Visitor<'de>>(
+            self,
+            _name: &'static str,
+            fields: &'static [&'static str],
+            _visitor: V,
+        ) -> Result<V::Value, Self::Error> {
+            self.0 = fields;
+            Err(serde::de::Error::custom("only the field names are read"))
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+            option unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier
+            ignored_any
+        }
+    }
+
+    let mut field_names = FieldNames(&[]);
+    let _ = C::deserialize(&mut field_names);
+    field_names.0
+}
+
+/// Sets `key` on the top level fields and on the fields of nested structs, parsing `value` as the
+/// type of the current value. Keys match field names ignoring case, so Java keys like `brokerIP1`
+/// set `brokerIp1`. Unset optional fields carry no type to parse by, their paths are collected in
+/// `unset_fields` instead. Returns whether any field matched.
+fn set_field(
+    fields: &mut Map<String, Value>,
+    key: &str,
+    value: &str,
+    path: &mut Vec<String>,
+    unset_fields: &mut Vec<Vec<String>>,
+) -> RocketMQResult<bool> {
+    let mut set = false;
+    for (name, field) in fields.iter_mut() {
+        if name.eq_ignore_ascii_case(key) {
+            set = true;
+            if field.is_null() {
+                let mut field_path = path.clone();
+                field_path.push(name.clone());
+                unset_fields.push(field_path);
+            } else if let Some(parsed) = parse_value(field, value) {
+                *field = parsed;
+            } else {
+                return Err(invalid_value(key, value));
+            }
+        } else if let Value::Object(nested) = field {
+            path.push(name.clone());
+            set |= set_field(nested, key, value, path, unset_fields)?;
+            path.pop();
+        }
+    }
+    Ok(set)
+}
+
+/// Sets the unset optional field at `path` to the first reading of `value` that `C` accepts, a
+/// bool or number before falling back to a string.
+fn set_unset_field<C: DeserializeOwned>(
+    config: &mut Value,
+    path: &[String],
+    key: &str,
+    value: &str,
+) -> RocketMQResult<()> {
+    let pointer: String = path.iter().map(|name| format!("/{name}")).collect();
+    let candidates = [
+        value.parse::<bool>().ok().map(Value::Bool),
+        parse_number(value),
+        Some(Value::String(value.to_string())),
+    ];
+    for candidate in candidates.into_iter().flatten() {
+        if let Some(field) = config.pointer_mut(&pointer) {
+            *field = candidate;
+        }
+        if C::deserialize(&*config).is_ok() {
+            return Ok(());
+        }
+    }
+    if let Some(field) = config.pointer_mut(&pointer) {
+        *field = Value::Null;
+    }
+    Err(invalid_value(key, value))
+}
+
+fn invalid_value(key: &str, value: &str) -> RocketmqError {
+    RocketmqError::ConfigError(format!("invalid value `{value}` for config item `{key}`"))
+}
+
+fn parse_value(current: &Value, value: &str) -> Option<Value> {
+    match current {
+        Value::Bool(_) => value.parse::<bool>().ok().map(Value::Bool),
+        Value::Number(_) => parse_number(value),
+        // Strings and enums.
+        Value::String(_) => Some(Value::String(value.to_string())),
+        Value::Null | Value::Array(_) | Value::Object(_) => None,
+    }
+}
+
+fn parse_number(value: &str) -> Option<Value> {
+    value
+        .parse::<u64>()
+        .map(Value::from)
+        .or_else(|_| value.parse::<i64>().map(Value::from))
+        .or_else(|_| value.parse::<f64>().map(Value::from))
+        .ok()
+}
+
+/// Flattens `config` into the Java property keys and values accepted by [`PropertiesConfig`],
+/// sorted by key. Unset optional values are left out.
+pub fn config_to_properties<C: Serialize>(config: &C) -> BTreeMap<String, String> {
+    fn collect(fields: &Map<String, Value>, properties: &mut BTreeMap<String, String>) {
+        for (name, field) in fields {
+            let value = match field {
+                Value::Object(nested) => {
+                    collect(nested, properties);
+                    continue;
+                }
+                Value::Null => continue,
+                Value::String(value) => value.clone(),
+                other => other.to_string(),
+            };
+            properties.entry(name.clone()).or_insert(value);
+        }
+    }
+    let mut properties = BTreeMap::new();
+    if let Ok(Value::Object(fields)) = serde_json::to_value(config) {
+        collect(&fields, &mut properties);
+    }
+    properties
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::common::server::config::ServerConfig;
+
+    #[test]
+    fn properties_config_uses_java_keys() {
+        let mut properties = PropertiesConfig::parse(
+            "# server\nlistenPort=10931\nbindAddress = 127.0.0.1\nflushDiskType=SYNC_FLUSH\n",
+        )
+        .unwrap();
+        let config = properties.apply::<ServerConfig>().unwrap();
+        assert_eq!(config.listen_port, 10931);
+        assert_eq!(config.bind_address, "127.0.0.1");
+        assert_eq!(properties.unknown_keys(), vec!["flushDiskType"]);
+    }
+
+    #[test]
+    fn properties_config_sets_nested_fields_and_rejects_bad_values() {
+        use crate::common::broker::broker_config::BrokerConfig;
+
+        let mut properties = PropertiesConfig::parse(
+            "brokerClusterName=MigratedCluster\nbrokerId=1\nslaveReadEnable=true\n",
+        )
+        .unwrap();
+        let config = properties.apply::<BrokerConfig>().unwrap();
+        assert_eq!(
+            config.broker_identity.broker_cluster_name.as_str(),
+            "MigratedCluster"
+        );
+        assert_eq!(config.broker_identity.broker_id, 1);
+        assert!(config.slave_read_enable);
+        assert!(properties.unknown_keys().is_empty());
+        assert_eq!(
+            config_to_properties(&config)
+                .get("brokerClusterName")
+                .map(String::as_str),
+            Some("MigratedCluster")
+        );
+
+        let mut properties = PropertiesConfig::parse("slaveReadEnable=yes").unwrap();
+        assert!(properties.apply::<BrokerConfig>().is_err());
+    }
+
+    #[test]
+    fn secrets_are_applied_but_never_reported() {
+        use crate::common::broker::broker_config::BrokerConfig;
+
+        let mut properties = PropertiesConfig::parse(
+            "innerAccessKey=rocketmq\ninnerSecretKey=12345678\ninnerAuthToken=token\n",
+        )
+        .unwrap();
+        let config = properties.apply::<BrokerConfig>().unwrap();
+        assert!(properties.unknown_keys().is_empty());
+        assert_eq!(config.inner_secret_key.as_deref(), Some("12345678"));
+        assert_eq!(config.inner_auth_token.as_deref(), Some("token"));
+
+        let reported = config_to_properties(&config);
+        assert_eq!(
+            reported.get("innerAccessKey").map(String::as_str),
+            Some("rocketmq")
+        );
+        assert!(!reported.contains_key("innerSecretKey"));
+        assert!(!reported.contains_key("innerAuthToken"));
+        let debug = format!("{config:?}");
+        assert!(debug.contains("inner_secret_key: Some(\"****\")"));
+        assert!(!debug.contains("12345678") && !debug.contains("\"token\""));
+    }
+
+    #[test]
+    fn properties_config_keeps_aliased_fields_once() {
+        #[derive(Default, Serialize, Deserialize)]
+        #[serde(rename_all = "camelCase", default)]
+        struct AliasedConfig {
+            #[serde(rename = "dLegerGroup", alias = "dledgerGroup")]
+            d_ledger_group: String,
+            #[serde(skip_serializing)]
+            secret: Option<String>,
+        }
+
+        let mut properties = PropertiesConfig::parse("dLegerGroup=group\nsecret=s3cr3t\n").unwrap();
+        let config = properties.apply::<AliasedConfig>().unwrap();
+        assert_eq!(config.d_ledger_group, "group");
+        assert_eq!(config.secret.as_deref(), Some("s3cr3t"));
+    }
+
+    #[test]
+    fn properties_config_matches_keys_ignoring_case() {
+        use crate::common::broker::broker_config::BrokerConfig;
+
+        let mut properties =
+            PropertiesConfig::parse("brokerIP1=10.0.0.1\nbrokerIP2=10.0.0.2\n").unwrap();
+        let config = properties.apply::<BrokerConfig>().unwrap();
+        assert_eq!(config.broker_ip1.as_str(), "10.0.0.1");
+        assert_eq!(config.broker_ip2.as_deref(), Some("10.0.0.2"));
+        assert!(properties.unknown_keys().is_empty());
+    }
+
+    #[derive(Debug, Default, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct OptionalConfig {
+        max_size: Option<u64>,
+        offset: Option<i64>,
+        enabled: Option<bool>,
+        name: Option<String>,
+    }
+
+    #[test]
+    fn properties_config_parses_unset_optional_fields_by_their_type() {
+        let mut properties =
+            PropertiesConfig::parse("maxSize=1024\noffset=-1\nenabled=true\nname=123\n").unwrap();
+        let config = properties.apply::<OptionalConfig>().unwrap();
+        assert_eq!(config.max_size, Some(1024));
+        assert_eq!(config.offset, Some(-1));
+        assert_eq!(config.enabled, Some(true));
+        assert_eq!(config.name.as_deref(), Some("123"));
+
+        let mut properties = PropertiesConfig::parse("maxSize=large").unwrap();
+        assert!(properties.apply::<OptionalConfig>().is_err());
+    }
+}
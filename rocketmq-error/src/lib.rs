@@ -103,6 +103,12 @@ pub enum RocketmqError {
     #[error("{0}")]
     MQClientBrokerError(#[from] MQBrokerErr),
 
+    /// The broker rejected the request because it is busy or flow controlled. Unlike
+    /// [`MQClientBrokerError`](Self::MQClientBrokerError) the request itself is fine and may
+    /// succeed when retried later.
+    #[error("broker flow control, {0}")]
+    BrokerFlowControlError(MQBrokerErr),
+
     #[error("{0}")]
     RequestTimeoutError(#[from] RequestTimeoutErr),
 
@@ -135,6 +141,83 @@ pub enum RocketmqError {
 
     #[error("{0}")]
     ConfigError(String),
+
+    #[error("{0}")]
+    ResponseError(#[from] ResponseErr),
+}
+
+impl RocketmqError {
+    /// Returns the response code and remark that should be sent back to the caller when this
+    /// error escapes a request processor, or `None` if the error carries no code of its own.
+    pub fn response_code_remark(&self) -> Option<(i32, String)> {
+        match self {
+            RocketmqError::ResponseError(err) => Some((err.code, err.remark.clone())),
+            RocketmqError::AbortProcessError(code, remark) => Some((*code, remark.clone())),
+            RocketmqError::MQClientBrokerError(err)
+            | RocketmqError::BrokerFlowControlError(err) => Some((
+                err.response_code,
+                err.error_message.clone().unwrap_or_default(),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if the broker turned the request away to shed load, so it can be retried
+    /// after backing off.
+    pub fn is_flow_control(&self) -> bool {
+        matches!(self, RocketmqError::BrokerFlowControlError(_))
+    }
+}
+
+/// An error raised while handling a request, carrying the response code and remark the caller
+/// should see together with the underlying cause.
+#[derive(Error, Debug)]
+#[error("CODE: {code} DESC: {remark}")]
+pub struct ResponseErr {
+    code: i32,
+    remark: String,
+    #[source]
+    source: Option<Box<dyn std::error::Error + Send + Sync>>,
+}
+
+impl ResponseErr {
+    pub fn new(code: impl Into<i32>, remark: impl Into<String>) -> Self {
+        Self {
+            code: code.into(),
+            remark: remark.into(),
+            source: None,
+        }
+    }
+
+    pub fn with_source(
+        mut self,
+        source: impl Into<Box<dyn std::error::Error + Send + Sync>>,
+    ) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    pub fn code(&self) -> i32 {
+        self.code
+    }
+
+    pub fn remark(&self) -> &str {
+        self.remark.as_str()
+    }
+}
+
+#[macro_export]
+macro_rules! response_err {
+    ($code:expr, $fmt:expr, $($arg:expr),+) => {{
+        std::result::Result::Err($crate::RocketmqError::ResponseError(
+            $crate::ResponseErr::new($code, format!($fmt, $($arg),+)),
+        ))
+    }};
+    ($code:expr, $remark:expr) => {{
+        std::result::Result::Err($crate::RocketmqError::ResponseError(
+            $crate::ResponseErr::new($code, $remark),
+        ))
+    }};
 }
 
 #[derive(Error, Debug)]
@@ -149,15 +232,12 @@ pub struct MQBrokerErr {
 impl MQBrokerErr {
     pub fn new(response_code: i32, error_message: impl Into<String>) -> Self {
         let error_message = error_message.into();
-        /*let message = FAQUrl::attach_default_url(Some(
-            format!("CODE: {}  DESC: {}", response_code, error_message,).as_str(),
-        ));*/
-        let message = "";
+        let message = format!("CODE: {response_code}  DESC: {error_message}");
         Self {
             response_code,
             error_message: Some(error_message),
             broker_addr: None,
-            message: String::from(message),
+            message,
         }
     }
 
@@ -168,19 +248,12 @@ impl MQBrokerErr {
     ) -> Self {
         let broker_addr = broker_addr.into();
         let error_message = error_message.into();
-        /*let message = FAQUrl::attach_default_url(Some(
-            format!(
-                "CODE: {}  DESC: {} BROKER: {}",
-                response_code, error_message, broker_addr
-            )
-            .as_str(),
-        ));*/
-        let message = "";
+        let message = format!("CODE: {response_code}  DESC: {error_message} BROKER: {broker_addr}");
         Self {
             response_code,
             error_message: Some(error_message),
             broker_addr: Some(broker_addr),
-            message: String::from(message),
+            message,
         }
     }
 
@@ -357,3 +430,54 @@ macro_rules! request_timeout_err {
         ))
     }};
 }
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    use super::*;
+
+    #[test]
+    fn response_error_carries_code_and_remark() {
+        let result: RocketMQResult<()> = response_err!(25, "topic {} not exist", "TopicA");
+        let err = result.unwrap_err();
+        assert_eq!(
+            err.response_code_remark(),
+            Some((25, "topic TopicA not exist".to_string()))
+        );
+        assert_eq!(err.to_string(), "CODE: 25 DESC: topic TopicA not exist");
+    }
+
+    #[test]
+    fn response_error_keeps_source() {
+        let err = ResponseErr::new(1, "read failed").with_source(io::Error::other("disk gone"));
+        assert_eq!(err.source().unwrap().to_string(), "disk gone");
+    }
+
+    #[test]
+    fn flow_control_error_is_told_apart_from_broker_error() {
+        let err = RocketmqError::BrokerFlowControlError(MQBrokerErr::new_with_broker(
+            2,
+            "too many requests",
+            "127.0.0.1:10911",
+        ));
+        assert!(err.is_flow_control());
+        assert_eq!(
+            err.response_code_remark(),
+            Some((2, "too many requests".to_string()))
+        );
+        assert_eq!(
+            err.to_string(),
+            "broker flow control, CODE: 2  DESC: too many requests BROKER: 127.0.0.1:10911"
+        );
+
+        let err = RocketmqError::MQClientBrokerError(MQBrokerErr::new(1, "system error"));
+        assert!(!err.is_flow_control());
+    }
+
+    #[test]
+    fn plain_error_has_no_response_code() {
+        let err = RocketmqError::IllegalArgument("bad".to_string());
+        assert!(err.response_code_remark().is_none());
+    }
+}
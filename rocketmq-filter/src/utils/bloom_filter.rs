@@ -39,8 +39,6 @@ impl Default for BloomFilter {
     }
 }
 
-#[allow(unused_mut)]
-#[allow(unused_variables)]
 impl BloomFilter {
     pub fn new(f: i32, n: i32) -> Result<Self, &'static str> {
         if !(1..100).contains(&f) {
@@ -92,23 +90,72 @@ impl BloomFilter {
         }
     }
 
+    /// Sets the bits of `filter_data` in `bits`.
     pub fn hash_to(&self, filter_data: &BloomFilterData, bits: &mut BitsArray) {
-        /* if !self.is_valid(filter_data) {
-            panic!(
-                "Bloom filter data may not belong to this filter! {:?}, {:?}",
-                filter_data, self
-            );
+        self.check_data(filter_data);
+        self.check(bits);
+        for &pos in filter_data.bit_pos() {
+            bits.set_bit(pos as usize, true);
         }
-        self.hash_to_positions(filter_data.bit_pos(), bits);*/
-        unimplemented!("hash_to");
     }
 
     // Helper method for setting bits at given positions
     pub fn hash_to_positions(&self, bit_positions: &[usize], bits: &mut BitsArray) {
-        /*self.check(bits);
-        for &i in bit_positions {
-            bits.set_bit(i, true);
-        }*/
-        unimplemented!("hash_to_positions");
+        self.check(bits);
+        for &pos in bit_positions {
+            bits.set_bit(pos, true);
+        }
+    }
+
+    /// Returns whether all the bits of `filter_data` are set in `bits`. A hit may be a false
+    /// positive, a miss never is.
+    pub fn is_hit(&self, filter_data: &BloomFilterData, bits: &BitsArray) -> bool {
+        self.check_data(filter_data);
+        self.check(bits);
+        filter_data
+            .bit_pos()
+            .iter()
+            .all(|&pos| bits.get_bit(pos as usize))
+    }
+
+    fn check_data(&self, filter_data: &BloomFilterData) {
+        if !self.is_valid(Some(filter_data)) {
+            panic!(
+                "Bloom filter data may not belong to this filter! {:?}, m={}, k={}",
+                filter_data, self.m, self.k
+            );
+        }
+    }
+
+    fn check(&self, bits: &BitsArray) {
+        if bits.bit_length() != self.m as usize {
+            panic!(
+                "Length({}) of bits in BitsArray is not equal to {}!",
+                bits.bit_length(),
+                self.m
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hits_only_when_every_bit_of_the_data_is_set() {
+        let bloom_filter = BloomFilter::new(20, 64).unwrap();
+        let filter_data = BloomFilterData::new(
+            (0..bloom_filter.k()).map(|i| i * 3).collect(),
+            bloom_filter.m() as u32,
+        );
+        let mut bits = BitsArray::create(bloom_filter.m() as usize);
+        assert!(!bloom_filter.is_hit(&filter_data, &bits));
+
+        bloom_filter.hash_to(&filter_data, &mut bits);
+        assert!(bloom_filter.is_hit(&filter_data, &bits));
+
+        bits.set_bit(3, false);
+        assert!(!bloom_filter.is_hit(&filter_data, &bits));
     }
 }
@@ -75,6 +75,7 @@ async fn main() -> RocketMQResult<()> {
         .set_server_config(ServerConfig {
             listen_port: args.port,
             bind_address: args.ip,
+            ..ServerConfig::default()
         })
         .build()
         .boot()
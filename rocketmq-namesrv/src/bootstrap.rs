@@ -14,6 +14,7 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use std::future::Future;
 use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
@@ -58,25 +59,25 @@ struct NameServerRuntime {
 }
 
 impl NameServerBootstrap {
-    pub async fn boot(mut self) {
-        let (shutdown_tx, shutdown_rx) = broadcast::channel(1);
-        self.name_server_runtime.shutdown_rx = Some(shutdown_rx);
-        tokio::join!(
-            self.name_server_runtime.start(),
-            wait_for_signal_inner(shutdown_tx)
-        );
+    pub async fn boot(self) {
+        self.boot_until(async {
+            wait_for_signal().await;
+            info!("Received signal, initiating shutdown...");
+        })
+        .await;
     }
-}
 
-#[inline]
-async fn wait_for_signal_inner(shutdown_tx: broadcast::Sender<()>) {
-    tokio::select! {
-        _ = wait_for_signal() => {
-            info!("Received signal, initiating shutdown...");
-        }
+    /// Runs the name server until `shutdown` completes, which lets embedders such as integration
+    /// tests stop it without sending a process signal.
+    pub async fn boot_until(mut self, shutdown: impl Future<Output = ()>) {
+        let (shutdown_tx, shutdown_rx) = broadcast::channel(1);
+        self.name_server_runtime.shutdown_rx = Some(shutdown_rx);
+        tokio::join!(self.name_server_runtime.start(), async move {
+            shutdown.await;
+            // Send shutdown signal to all tasks
+            let _ = shutdown_tx.send(());
+        });
     }
-    // Send shutdown signal to all tasks
-    let _ = shutdown_tx.send(());
 }
 
 impl NameServerRuntime {
@@ -90,13 +91,17 @@ impl NameServerRuntime {
             .broker_housekeeping_service
             .take()
             .map(|item| item as Arc<dyn ChannelEventListener>);
+        let mut server_shutdown_rx = self.shutdown_rx.as_ref().unwrap().resubscribe();
         tokio::spawn(async move {
-            server.run(request_processor, channel_event_listener).await;
+            server
+                .run_until(request_processor, channel_event_listener, async move {
+                    let _ = server_shutdown_rx.recv().await;
+                })
+                .await;
         });
-        let namesrv = CheetahString::from_string(format!(
-            "{}:{}",
-            NetworkUtil::get_local_address().unwrap(),
-            self.inner.server_config.listen_port
+        let namesrv = CheetahString::from_string(NetworkUtil::format_address(
+            &NetworkUtil::get_local_address().unwrap(),
+            self.inner.server_config.listen_port,
         ));
         let weak_arc_mut = ArcMut::downgrade(&self.inner.remoting_client);
         self.inner
@@ -198,14 +203,42 @@ impl Builder {
 
     #[inline]
     pub fn build(self) -> NameServerBootstrap {
-        let name_server_config = self.name_server_config.unwrap_or_default();
         let runtime = RocketMQRuntime::new_multi(10, "namesrv-thread");
+        let inner = NameServerRuntimeInner::new(
+            self.name_server_config.unwrap_or_default(),
+            self.server_config.unwrap_or_default(),
+        );
+
+        NameServerBootstrap {
+            name_server_runtime: NameServerRuntime {
+                name_server_runtime: Some(runtime),
+                inner,
+                shutdown_rx: None,
+            },
+        }
+    }
+}
+
+pub(crate) struct NameServerRuntimeInner {
+    name_server_config: NamesrvConfig,
+    tokio_client_config: TokioClientConfig,
+    server_config: ServerConfig,
+    route_info_manager: Option<RouteInfoManager>,
+    kvconfig_manager: Option<KVConfigManager>,
+    remoting_client: ArcMut<RocketmqDefaultClient>,
+    broker_housekeeping_service: Option<Arc<BrokerHousekeepingService>>,
+}
+
+impl NameServerRuntimeInner {
+    pub(crate) fn new(
+        name_server_config: NamesrvConfig,
+        server_config: ServerConfig,
+    ) -> ArcMut<Self> {
         let tokio_client_config = TokioClientConfig::default();
         let remoting_client = ArcMut::new(RocketmqDefaultClient::new(
             Arc::new(tokio_client_config.clone()),
             DefaultRemotingRequestProcessor,
         ));
-        let server_config = self.server_config.unwrap_or_default();
         let mut inner = ArcMut::new(NameServerRuntimeInner {
             name_server_config,
             tokio_client_config,
@@ -223,28 +256,9 @@ impl Builder {
         inner.route_info_manager = Some(route_info_manager);
         inner.broker_housekeeping_service =
             Some(Arc::new(BrokerHousekeepingService::new(inner.clone())));
-
-        NameServerBootstrap {
-            name_server_runtime: NameServerRuntime {
-                name_server_runtime: Some(runtime),
-                inner,
-                shutdown_rx: None,
-            },
-        }
+        inner
     }
-}
 
-pub(crate) struct NameServerRuntimeInner {
-    name_server_config: NamesrvConfig,
-    tokio_client_config: TokioClientConfig,
-    server_config: ServerConfig,
-    route_info_manager: Option<RouteInfoManager>,
-    kvconfig_manager: Option<KVConfigManager>,
-    remoting_client: ArcMut<RocketmqDefaultClient>,
-    broker_housekeeping_service: Option<Arc<BrokerHousekeepingService>>,
-}
-
-impl NameServerRuntimeInner {
     #[inline]
     pub fn name_server_config_mut(&mut self) -> &mut NamesrvConfig {
         &mut self.name_server_config
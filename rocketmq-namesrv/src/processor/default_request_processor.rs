@@ -182,8 +182,15 @@ impl DefaultRequestProcessor {
     ) -> rocketmq_error::RocketMQResult<RemotingCommand> {
         let request_header =
             request.decode_command_custom_header::<QueryDataVersionRequestHeader>()?;
-        let data_version = DataVersion::decode(request.get_body().expect("body is empty"))
-            .expect("decode DataVersion failed");
+        let Some(data_version) = request
+            .get_body()
+            .and_then(|body| DataVersion::decode(body).ok())
+        else {
+            return Ok(RemotingCommand::create_response_command_with_code_remark(
+                RemotingSysResponseCode::SystemError,
+                "query data version without a valid data version body",
+            ));
+        };
         let changed = self
             .name_server_runtime_inner
             .route_info_manager()
@@ -236,7 +243,7 @@ impl DefaultRequestProcessor {
         }
 
         let mut response_command = RemotingCommand::create_response_command();
-        let broker_version = RocketMqVersion::try_from(request.version()).expect("invalid version");
+        let broker_version = RocketMqVersion::from_version(request.version());
         let topic_config_wrapper;
         let mut filter_server_list = Vec::new();
         if broker_version as usize >= RocketMqVersion::V3011 as usize {
@@ -678,7 +685,7 @@ fn extract_register_broker_body_from_request(
         if body_inner.is_empty() {
             return RegisterBrokerBody::default();
         }
-        let version = RocketMqVersion::try_from(request.version()).unwrap();
+        let version = RocketMqVersion::from_version(request.version());
         return RegisterBrokerBody::decode(body_inner, request_header.compressed, version);
     }
     RegisterBrokerBody::default()
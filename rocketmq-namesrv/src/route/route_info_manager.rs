@@ -278,13 +278,21 @@ impl RouteInfoManager {
                 .topic_config_table();
             let topic_queue_mapping_info_map =
                 topic_config_serialize_wrapper.topic_queue_mapping_info_map();
+            let data_version = topic_config_serialize_wrapper
+                .topic_config_serialize_wrapper
+                .data_version();
+            // An unchanged data version means the topic config is already known, so the
+            // topic table of a broker with many topics is not walked on every registration
+            let topic_config_changed = register_first
+                || self.is_broker_topic_config_changed(&cluster_name, &broker_addr, data_version);
 
             // Delete the topics that don't exist in tcTable from the current broker
             // Static topic is not supported currently
-            if self
-                .name_server_runtime_inner
-                .name_server_config()
-                .delete_topic_with_broker_registration
+            if topic_config_changed
+                && self
+                    .name_server_runtime_inner
+                    .name_server_config()
+                    .delete_topic_with_broker_registration
                 && topic_queue_mapping_info_map.is_empty()
             {
                 let old_topic_set = self.topic_set_of_broker_name(&broker_name);
@@ -292,8 +300,8 @@ impl RouteInfoManager {
                     .keys()
                     .map(|item| item.to_string())
                     .collect::<HashSet<String>>();
-                let to_delete_topics = new_topic_set
-                    .difference(&old_topic_set)
+                let to_delete_topics = old_topic_set
+                    .difference(&new_topic_set)
                     .map(|item| item.to_string())
                     .collect::<HashSet<String>>();
                 for to_delete_topic in to_delete_topics {
@@ -317,29 +325,25 @@ impl RouteInfoManager {
                     }
                 }
             }
-            let data_version = topic_config_serialize_wrapper
-                .topic_config_serialize_wrapper
-                .data_version();
             for topic_config in tc_table.values() {
-                let mut config = topic_config.clone();
-                if (register_first
-                    || self.is_topic_config_changed(
+                if !topic_config_changed
+                    && !self.is_topic_config_changed(
                         &cluster_name,
                         &broker_addr,
                         data_version,
                         &broker_name,
                         topic_config.topic_name.as_ref().unwrap(),
-                    ))
-                    && is_prime_slave
-                    && broker_data.enable_acting_master()
+                    )
                 {
+                    continue;
+                }
+                let mut config = topic_config.clone();
+                if is_prime_slave && broker_data.enable_acting_master() {
                     config.perm &= !PermName::PERM_WRITE;
                 }
                 self.create_and_update_queue_data(&broker_name, config);
             }
-            if self.is_broker_topic_config_changed(&cluster_name, &broker_addr, data_version)
-                || register_first
-            {
+            if topic_config_changed {
                 for (topic, vtq_info) in topic_queue_mapping_info_map {
                     if !self.topic_queue_mapping_info_table.contains_key(topic) {
                         self.topic_queue_mapping_info_table
@@ -858,11 +862,13 @@ impl RouteInfoManager {
         let mut topic_list = Vec::new();
         let lock = self.lock.read();
         if let Some(broker_name_set) = self.cluster_addr_table.get(cluster) {
-            for broker_name in broker_name_set {
-                for (topic, queue_data_map) in self.topic_queue_table.iter() {
-                    if let Some(_queue_data) = queue_data_map.get(broker_name) {
-                        topic_list.push(topic.clone());
-                    }
+            // A topic spread over several brokers of the cluster is listed once.
+            for (topic, queue_data_map) in self.topic_queue_table.iter() {
+                if broker_name_set
+                    .iter()
+                    .any(|broker_name| queue_data_map.contains_key(broker_name))
+                {
+                    topic_list.push(topic.clone());
                 }
             }
         }
@@ -876,6 +882,7 @@ impl RouteInfoManager {
     pub(crate) fn get_system_topic_list(&self) -> TopicList {
         let mut topic_list = Vec::new();
         let mut broker_addr_out = None;
+        let lock = self.lock.read();
         for (cluster_name, broker_set) in self.cluster_addr_table.iter() {
             topic_list.push(cluster_name.clone());
             broker_set.iter().for_each(|broker_name| {
@@ -891,6 +898,7 @@ impl RouteInfoManager {
                 }
             }
         }
+        drop(lock);
         TopicList {
             topic_list,
             broker_addr: broker_addr_out,
@@ -1207,3 +1215,58 @@ impl RouteInfoManager {
         self.un_register_service.shutdown();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use rocketmq_common::common::namesrv::namesrv_config::NamesrvConfig;
+    use rocketmq_common::common::server::config::ServerConfig;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn topics_left_out_of_a_changed_registration_are_deleted() {
+        let name_server_config = NamesrvConfig {
+            delete_topic_with_broker_registration: true,
+            ..NamesrvConfig::default()
+        };
+        let route_info_manager = RouteInfoManager::new(NameServerRuntimeInner::new(
+            name_server_config,
+            ServerConfig::default(),
+        ));
+        // registrations arrive decoded, each with its own data version
+        let register = |topics: &[&str], data_version: DataVersion| {
+            let mut wrapper = TopicConfigAndMappingSerializeWrapper::default();
+            for topic in topics {
+                wrapper
+                    .topic_config_serialize_wrapper
+                    .topic_config_table
+                    .insert(CheetahString::from(*topic), TopicConfig::new(*topic));
+            }
+            wrapper.topic_config_serialize_wrapper.data_version = data_version;
+            route_info_manager.register_broker(
+                CheetahString::from_static_str("DefaultCluster"),
+                CheetahString::from_static_str("127.0.0.1:10911"),
+                CheetahString::from_static_str("broker-a"),
+                mix_all::MASTER_ID,
+                CheetahString::from_static_str("127.0.0.1:10912"),
+                None,
+                None,
+                None,
+                wrapper,
+                vec![],
+                "127.0.0.1:10911".parse().unwrap(),
+            )
+        };
+
+        register(&["TopicA", "TopicB"], DataVersion::default()).unwrap();
+        let mut data_version = DataVersion::default();
+        data_version.next_version();
+        register(&["TopicA", "TopicC"], data_version).unwrap();
+
+        let topics = route_info_manager.topic_set_of_broker_name("broker-a");
+        assert_eq!(
+            topics,
+            HashSet::from(["TopicA".to_string(), "TopicC".to_string()])
+        );
+    }
+}
@@ -0,0 +1,33 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Authentication of the requests a remoting server receives.
+//!
+//! An [`AuthenticationProvider`](authentication_provider::AuthenticationProvider) validates the
+//! credentials a request carries in its ext fields and returns the principal they belong to.
+//! [`AuthenticationRpcHook`](authentication_rpc_hook::AuthenticationRpcHook) runs a provider
+//! before each request of a server and answers `NO_PERMISSION` when it fails, so identity systems
+//! are integrated by implementing a provider instead of patching the processors.
+//!
+//! Clients add their credentials to the requests they send with
+//! [`AclClientRpcHook`](acl_client_rpc_hook::AclClientRpcHook).
+
+pub mod acl_client_rpc_hook;
+pub mod authentication_provider;
+pub mod authentication_rpc_hook;
+pub mod hmac_authentication_provider;
+pub mod jwt_authentication_provider;
@@ -0,0 +1,152 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+
+use cheetah_string::CheetahString;
+use rocketmq_error::RocketMQResult;
+
+use crate::auth::authentication_provider::AUTHORIZATION;
+use crate::auth::hmac_authentication_provider::sign_request;
+use crate::protocol::remoting_command::RemotingCommand;
+use crate::runtime::RPCHook;
+
+/// The credentials a client authenticates its requests with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionCredentials {
+    /// Requests are signed with the secret key, checked by the
+    /// [`HmacAuthenticationProvider`](crate::auth::hmac_authentication_provider::HmacAuthenticationProvider).
+    AccessKey {
+        access_key: CheetahString,
+        secret_key: CheetahString,
+        security_token: Option<CheetahString>,
+    },
+    /// Requests carry the token as `Authorization: Bearer <token>`, checked by the
+    /// [`JwtAuthenticationProvider`](crate::auth::jwt_authentication_provider::JwtAuthenticationProvider).
+    BearerToken(CheetahString),
+}
+
+impl SessionCredentials {
+    pub fn access_key(
+        access_key: impl Into<CheetahString>,
+        secret_key: impl Into<CheetahString>,
+    ) -> Self {
+        SessionCredentials::AccessKey {
+            access_key: access_key.into(),
+            secret_key: secret_key.into(),
+            security_token: None,
+        }
+    }
+
+    pub fn bearer_token(token: impl Into<CheetahString>) -> Self {
+        SessionCredentials::BearerToken(token.into())
+    }
+}
+
+/// Adds the credentials of a client to each request it sends, so servers authenticating their
+/// requests accept them.
+pub struct AclClientRpcHook {
+    credentials: SessionCredentials,
+}
+
+impl AclClientRpcHook {
+    pub fn new(credentials: SessionCredentials) -> Self {
+        AclClientRpcHook { credentials }
+    }
+
+    pub fn credentials(&self) -> &SessionCredentials {
+        &self.credentials
+    }
+}
+
+impl RPCHook for AclClientRpcHook {
+    fn do_before_request(
+        &self,
+        _remote_addr: SocketAddr,
+        request: &mut RemotingCommand,
+    ) -> RocketMQResult<()> {
+        match &self.credentials {
+            SessionCredentials::AccessKey {
+                access_key,
+                secret_key,
+                security_token,
+            } => sign_request(
+                request,
+                access_key,
+                secret_key,
+                security_token.as_ref().map(|token| token.as_str()),
+            ),
+            SessionCredentials::BearerToken(token) => {
+                request.make_custom_header_to_net();
+                if request.ext_fields().is_none() {
+                    *request = std::mem::take(request).set_ext_fields(HashMap::new());
+                }
+                request.add_ext_field(AUTHORIZATION, format!("Bearer {token}"));
+            }
+        }
+        Ok(())
+    }
+
+    fn do_after_response(
+        &self,
+        _remote_addr: SocketAddr,
+        _response: &mut RemotingCommand,
+    ) -> RocketMQResult<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rocketmq_common::common::base::plain_access_config::PlainAccessConfig;
+
+    use super::*;
+    use crate::auth::authentication_provider::AuthenticationProvider;
+    use crate::auth::authentication_provider::Credentials;
+    use crate::auth::authentication_provider::RequestContext;
+    use crate::auth::hmac_authentication_provider::HmacAuthenticationProvider;
+    use crate::code::request_code::RequestCode;
+
+    #[test]
+    fn requests_carry_the_credentials_of_the_hook() {
+        let remote_addr: SocketAddr = "127.0.0.1:10911".parse().unwrap();
+        let hook = AclClientRpcHook::new(SessionCredentials::access_key("rocketmq", "12345678"));
+        let mut request = RemotingCommand::create_remoting_command(RequestCode::SendMessage)
+            .set_body(&b"hello"[..]);
+        hook.do_before_request(remote_addr, &mut request).unwrap();
+        let provider = HmacAuthenticationProvider::new([PlainAccessConfig {
+            access_key: Some("rocketmq".into()),
+            secret_key: Some("12345678".into()),
+            ..PlainAccessConfig::default()
+        }]);
+        let credentials = Credentials::from_request(&request).unwrap();
+        let request_context = RequestContext {
+            remote_addr,
+            request: &request,
+        };
+        assert!(provider.validate(&credentials, &request_context).is_ok());
+
+        let hook = AclClientRpcHook::new(SessionCredentials::bearer_token("token"));
+        let mut request = RemotingCommand::create_remoting_command(RequestCode::SendMessage);
+        hook.do_before_request(remote_addr, &mut request).unwrap();
+        assert_eq!(
+            Credentials::from_request(&request),
+            Some(Credentials::BearerToken("token".into()))
+        );
+    }
+}
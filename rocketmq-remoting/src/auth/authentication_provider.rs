@@ -0,0 +1,150 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use cheetah_string::CheetahString;
+use rocketmq_error::RocketMQResult;
+use rocketmq_error::RocketmqError;
+
+use crate::code::response_code::ResponseCode;
+use crate::protocol::remoting_command::RemotingCommand;
+
+/// Ext field holding the access key of a signed request.
+pub const ACCESS_KEY: &str = "AccessKey";
+/// Ext field holding the signature of a signed request.
+pub const SIGNATURE: &str = "Signature";
+/// Ext field holding the security token of a signed request.
+pub const SECURITY_TOKEN: &str = "SecurityToken";
+/// Ext field holding the `Bearer <token>` of a request authenticated by token.
+pub const AUTHORIZATION: &str = "Authorization";
+
+const BEARER_PREFIX: &str = "Bearer ";
+
+/// The credentials carried by a request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Credentials {
+    /// An access key and the signature of the request made with its secret key.
+    Signature {
+        access_key: CheetahString,
+        signature: CheetahString,
+        security_token: Option<CheetahString>,
+    },
+    /// A token issued by an identity provider.
+    BearerToken(CheetahString),
+}
+
+impl Credentials {
+    /// Reads the credentials from the ext fields of `request`, `None` when it carries none.
+    pub fn from_request(request: &RemotingCommand) -> Option<Self> {
+        let ext_fields = request.ext_fields()?;
+        if let Some(authorization) = ext_fields.get(AUTHORIZATION) {
+            return authorization
+                .strip_prefix(BEARER_PREFIX)
+                .map(|token| Credentials::BearerToken(token.trim().into()));
+        }
+        Some(Credentials::Signature {
+            access_key: ext_fields.get(ACCESS_KEY)?.clone(),
+            signature: ext_fields.get(SIGNATURE)?.clone(),
+            security_token: ext_fields.get(SECURITY_TOKEN).cloned(),
+        })
+    }
+}
+
+/// The identity whose credentials a request was authenticated with.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Principal {
+    pub name: CheetahString,
+    /// Attributes of the identity known to the provider, the claims of a token for instance.
+    pub attributes: HashMap<CheetahString, CheetahString>,
+}
+
+impl Principal {
+    pub fn new(name: impl Into<CheetahString>) -> Self {
+        Principal {
+            name: name.into(),
+            attributes: HashMap::new(),
+        }
+    }
+}
+
+/// The request whose credentials are validated.
+pub struct RequestContext<'a> {
+    pub remote_addr: SocketAddr,
+    pub request: &'a RemotingCommand,
+}
+
+/// Validates the credentials of requests against an identity system.
+pub trait AuthenticationProvider: Send + Sync + 'static {
+    /// Returns the principal `credentials` belong to, or an error when they do not authenticate
+    /// the request of `request_context`. Errors made with [`authentication_failed`] are answered
+    /// as they are, others are answered with `NO_PERMISSION` and their message.
+    fn validate(
+        &self,
+        credentials: &Credentials,
+        request_context: &RequestContext<'_>,
+    ) -> RocketMQResult<Principal>;
+}
+
+impl<T: AuthenticationProvider + ?Sized> AuthenticationProvider for Arc<T> {
+    fn validate(
+        &self,
+        credentials: &Credentials,
+        request_context: &RequestContext<'_>,
+    ) -> RocketMQResult<Principal> {
+        (**self).validate(credentials, request_context)
+    }
+}
+
+/// Returns the error rejecting a request with `NO_PERMISSION` and `remark`.
+pub fn authentication_failed(remark: impl Into<String>) -> RocketmqError {
+    RocketmqError::AbortProcessError(ResponseCode::NoPermission.into(), remark.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code::request_code::RequestCode;
+
+    #[test]
+    fn credentials_are_read_from_ext_fields() {
+        let request = RemotingCommand::create_remoting_command(RequestCode::SendMessage);
+        assert_eq!(Credentials::from_request(&request), None);
+
+        let request = request.set_ext_fields(HashMap::from([
+            (ACCESS_KEY.into(), "ak".into()),
+            (SIGNATURE.into(), "sig".into()),
+        ]));
+        assert_eq!(
+            Credentials::from_request(&request),
+            Some(Credentials::Signature {
+                access_key: "ak".into(),
+                signature: "sig".into(),
+                security_token: None,
+            })
+        );
+
+        let request = RemotingCommand::create_remoting_command(RequestCode::SendMessage)
+            .set_ext_fields(HashMap::from([(AUTHORIZATION.into(), "Bearer abc".into())]));
+        assert_eq!(
+            Credentials::from_request(&request),
+            Some(Credentials::BearerToken("abc".into()))
+        );
+    }
+}
@@ -0,0 +1,84 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use rocketmq_error::RocketMQResult;
+use rocketmq_error::RocketmqError;
+use tracing::debug;
+
+use crate::auth::authentication_provider::authentication_failed;
+use crate::auth::authentication_provider::AuthenticationProvider;
+use crate::auth::authentication_provider::Credentials;
+use crate::auth::authentication_provider::RequestContext;
+use crate::protocol::remoting_command::RemotingCommand;
+use crate::runtime::RPCHook;
+
+/// Authenticates each request a server receives with an [`AuthenticationProvider`], requests
+/// without credentials or with credentials the provider rejects are answered with
+/// `NO_PERMISSION` and never reach the processors.
+pub struct AuthenticationRpcHook {
+    provider: Arc<dyn AuthenticationProvider>,
+}
+
+impl AuthenticationRpcHook {
+    pub fn new(provider: Arc<dyn AuthenticationProvider>) -> Self {
+        AuthenticationRpcHook { provider }
+    }
+}
+
+impl RPCHook for AuthenticationRpcHook {
+    fn do_before_request(
+        &self,
+        remote_addr: SocketAddr,
+        request: &mut RemotingCommand,
+    ) -> RocketMQResult<()> {
+        let Some(credentials) = Credentials::from_request(request) else {
+            return Err(authentication_failed(format!(
+                "request {} from {} carries no credentials",
+                request.code(),
+                remote_addr
+            )));
+        };
+        let request_context = RequestContext {
+            remote_addr,
+            request,
+        };
+        match self.provider.validate(&credentials, &request_context) {
+            Ok(principal) => {
+                debug!(
+                    "request {} from {} authenticated as {}",
+                    request.code(),
+                    remote_addr,
+                    principal.name
+                );
+                Ok(())
+            }
+            Err(e @ RocketmqError::AbortProcessError(..)) => Err(e),
+            Err(e) => Err(authentication_failed(e.to_string())),
+        }
+    }
+
+    fn do_after_response(
+        &self,
+        _remote_addr: SocketAddr,
+        _response: &mut RemotingCommand,
+    ) -> RocketMQResult<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,175 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use cheetah_string::CheetahString;
+use ring::hmac;
+use rocketmq_common::common::base::plain_access_config::PlainAccessConfig;
+use rocketmq_error::RocketMQResult;
+
+use crate::auth::authentication_provider::authentication_failed;
+use crate::auth::authentication_provider::AuthenticationProvider;
+use crate::auth::authentication_provider::Credentials;
+use crate::auth::authentication_provider::Principal;
+use crate::auth::authentication_provider::RequestContext;
+use crate::auth::authentication_provider::ACCESS_KEY;
+use crate::auth::authentication_provider::SECURITY_TOKEN;
+use crate::auth::authentication_provider::SIGNATURE;
+use crate::protocol::remoting_command::RemotingCommand;
+
+/// Authenticates requests signed the way the ACL clients of RocketMQ sign them: an HMAC-SHA1 with
+/// the secret key of the access key, over the values of the ext fields sorted by name without the
+/// signature, followed by the body.
+pub struct HmacAuthenticationProvider {
+    accounts: HashMap<CheetahString, PlainAccessConfig>,
+}
+
+impl HmacAuthenticationProvider {
+    /// Creates a provider accepting the accounts of `plain_access_configs`, configs without an
+    /// access key or a secret key are ignored.
+    pub fn new(plain_access_configs: impl IntoIterator<Item = PlainAccessConfig>) -> Self {
+        let accounts = plain_access_configs
+            .into_iter()
+            .filter(|config| config.secret_key.is_some())
+            .filter_map(|config| Some((config.access_key.clone()?, config)))
+            .collect();
+        HmacAuthenticationProvider { accounts }
+    }
+}
+
+impl AuthenticationProvider for HmacAuthenticationProvider {
+    fn validate(
+        &self,
+        credentials: &Credentials,
+        request_context: &RequestContext<'_>,
+    ) -> RocketMQResult<Principal> {
+        let Credentials::Signature {
+            access_key,
+            signature,
+            ..
+        } = credentials
+        else {
+            return Err(authentication_failed(
+                "the request is not signed with an access key",
+            ));
+        };
+        let Some(secret_key) = self
+            .accounts
+            .get(access_key)
+            .and_then(|config| config.secret_key.as_ref())
+        else {
+            return Err(authentication_failed(format!(
+                "no account for access key {access_key}"
+            )));
+        };
+        let signature = STANDARD
+            .decode(signature.as_bytes())
+            .map_err(|_| authentication_failed("the signature is not base64"))?;
+        let key = hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, secret_key.as_bytes());
+        hmac::verify(&key, &signed_content(request_context.request), &signature).map_err(|_| {
+            authentication_failed(format!(
+                "the signature of access key {access_key} does not match"
+            ))
+        })?;
+        Ok(Principal::new(access_key.clone()))
+    }
+}
+
+/// Returns the content of `request` its signature is calculated over.
+pub fn signed_content(request: &RemotingCommand) -> Vec<u8> {
+    let mut content = Vec::new();
+    if let Some(ext_fields) = request.ext_fields() {
+        let sorted: BTreeMap<_, _> = ext_fields
+            .iter()
+            .filter(|(key, _)| key.as_str() != SIGNATURE)
+            .collect();
+        for value in sorted.values() {
+            content.extend_from_slice(value.as_bytes());
+        }
+    }
+    if let Some(body) = request.get_body() {
+        content.extend_from_slice(body);
+    }
+    content
+}
+
+/// Signs `request` with `secret_key` and adds the access key, the security token when given and
+/// the signature to its ext fields.
+pub fn sign_request(
+    request: &mut RemotingCommand,
+    access_key: &str,
+    secret_key: &str,
+    security_token: Option<&str>,
+) {
+    request.make_custom_header_to_net();
+    if request.ext_fields().is_none() {
+        *request = std::mem::take(request).set_ext_fields(HashMap::new());
+    }
+    request.add_ext_field(ACCESS_KEY, access_key);
+    if let Some(security_token) = security_token {
+        request.add_ext_field(SECURITY_TOKEN, security_token);
+    }
+    let key = hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, secret_key.as_bytes());
+    let signature = STANDARD.encode(hmac::sign(&key, &signed_content(request)));
+    request.add_ext_field(SIGNATURE, signature);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::code::request_code::RequestCode;
+
+    fn validate(
+        provider: &HmacAuthenticationProvider,
+        request: &RemotingCommand,
+    ) -> RocketMQResult<Principal> {
+        let credentials = Credentials::from_request(request).unwrap();
+        let request_context = RequestContext {
+            remote_addr: "127.0.0.1:10911".parse().unwrap(),
+            request,
+        };
+        provider.validate(&credentials, &request_context)
+    }
+
+    #[test]
+    fn signed_requests_are_authenticated_by_their_secret_key() {
+        let provider = HmacAuthenticationProvider::new([PlainAccessConfig {
+            access_key: Some("rocketmq".into()),
+            secret_key: Some("12345678".into()),
+            ..PlainAccessConfig::default()
+        }]);
+        let mut request = RemotingCommand::create_remoting_command(RequestCode::SendMessage)
+            .set_ext_fields(HashMap::from([("topic".into(), "TopicTest".into())]))
+            .set_body(&b"hello"[..]);
+        sign_request(&mut request, "rocketmq", "12345678", None);
+        assert_eq!(
+            validate(&provider, &request).unwrap(),
+            Principal::new("rocketmq")
+        );
+
+        let tampered = request.clone().set_body(&b"hello!"[..]);
+        assert!(validate(&provider, &tampered).is_err());
+
+        let mut unknown = RemotingCommand::create_remoting_command(RequestCode::SendMessage);
+        sign_request(&mut unknown, "other", "12345678", None);
+        assert!(validate(&provider, &unknown).is_err());
+    }
+}
@@ -0,0 +1,366 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! A sample provider authenticating bearer tokens that are JWTs.
+//!
+//! Tokens are verified with HS256 shared secrets or with the RS256 keys an OIDC identity provider
+//! publishes in its JWKS document. The keys are configured up front, fetching and rotating the
+//! JWKS of the issuer is left to the embedder, which can rebuild the provider when they change.
+
+use std::collections::HashMap;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use cheetah_string::CheetahString;
+use ring::hmac;
+use ring::signature::RsaPublicKeyComponents;
+use ring::signature::RSA_PKCS1_2048_8192_SHA256;
+use rocketmq_common::TimeUtils::get_current_millis;
+use rocketmq_error::RocketMQResult;
+use rocketmq_error::RocketmqError;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::auth::authentication_provider::authentication_failed;
+use crate::auth::authentication_provider::AuthenticationProvider;
+use crate::auth::authentication_provider::Credentials;
+use crate::auth::authentication_provider::Principal;
+use crate::auth::authentication_provider::RequestContext;
+
+/// A key tokens are verified with.
+#[derive(Debug, Clone)]
+pub enum JwtKey {
+    /// A shared secret of HS256 tokens.
+    Hs256(Vec<u8>),
+    /// The public key of RS256 tokens, its modulus and exponent as big endian bytes.
+    Rs256 { n: Vec<u8>, e: Vec<u8> },
+}
+
+#[derive(Deserialize)]
+struct Jwks {
+    keys: Vec<Jwk>,
+}
+
+#[derive(Deserialize)]
+struct Jwk {
+    kid: Option<String>,
+    kty: String,
+    n: Option<String>,
+    e: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct JwtHeader {
+    alg: String,
+    kid: Option<String>,
+}
+
+/// Authenticates requests carrying a JWT as bearer token, the principal is read from the `sub`
+/// claim unless another claim is configured.
+pub struct JwtAuthenticationProvider {
+    // keys by key id, the key of a token without key id is stored under the empty id
+    keys: HashMap<String, JwtKey>,
+    issuer: Option<CheetahString>,
+    audience: Option<CheetahString>,
+    principal_claim: CheetahString,
+    leeway_secs: i64,
+}
+
+impl Default for JwtAuthenticationProvider {
+    fn default() -> Self {
+        JwtAuthenticationProvider {
+            keys: HashMap::new(),
+            issuer: None,
+            audience: None,
+            principal_claim: CheetahString::from_static_str("sub"),
+            leeway_secs: 60,
+        }
+    }
+}
+
+impl JwtAuthenticationProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accepts HS256 tokens signed with `secret`, `kid` is the key id their header names.
+    pub fn with_hmac_secret(mut self, kid: impl Into<String>, secret: impl Into<Vec<u8>>) -> Self {
+        self.keys.insert(kid.into(), JwtKey::Hs256(secret.into()));
+        self
+    }
+
+    /// Accepts RS256 tokens signed by the RSA keys of the JWKS document `jwks_json`, the one an
+    /// OIDC issuer publishes at its `jwks_uri`.
+    pub fn with_jwks(mut self, jwks_json: &str) -> RocketMQResult<Self> {
+        let jwks: Jwks = serde_json::from_str(jwks_json)
+            .map_err(|e| RocketmqError::JsonError(format!("invalid JWKS: {e}")))?;
+        for jwk in jwks.keys.into_iter().filter(|jwk| jwk.kty == "RSA") {
+            let (Some(n), Some(e)) = (jwk.n, jwk.e) else {
+                continue;
+            };
+            let decode = |value: &str| {
+                URL_SAFE_NO_PAD.decode(value).map_err(|e| {
+                    RocketmqError::IllegalArgument(format!("invalid JWK component: {e}"))
+                })
+            };
+            let key = JwtKey::Rs256 {
+                n: decode(&n)?,
+                e: decode(&e)?,
+            };
+            self.keys.insert(jwk.kid.unwrap_or_default(), key);
+        }
+        Ok(self)
+    }
+
+    /// Only accepts tokens whose `iss` claim is `issuer`.
+    pub fn with_issuer(mut self, issuer: impl Into<CheetahString>) -> Self {
+        self.issuer = Some(issuer.into());
+        self
+    }
+
+    /// Only accepts tokens whose `aud` claim is or contains `audience`.
+    pub fn with_audience(mut self, audience: impl Into<CheetahString>) -> Self {
+        self.audience = Some(audience.into());
+        self
+    }
+
+    /// Reads the principal from `claim` instead of `sub`.
+    pub fn with_principal_claim(mut self, claim: impl Into<CheetahString>) -> Self {
+        self.principal_claim = claim.into();
+        self
+    }
+
+    /// Tolerates `leeway_secs` of clock skew when checking `exp` and `nbf`.
+    pub fn with_leeway_secs(mut self, leeway_secs: i64) -> Self {
+        self.leeway_secs = leeway_secs;
+        self
+    }
+
+    fn verify_signature(&self, token: &str) -> RocketMQResult<serde_json::Map<String, Value>> {
+        let mut parts = token.split('.');
+        let (Some(header), Some(payload), Some(signature), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(authentication_failed("the bearer token is not a JWT"));
+        };
+        let decode = |part: &str| {
+            URL_SAFE_NO_PAD
+                .decode(part)
+                .map_err(|_| authentication_failed("the bearer token is not a JWT"))
+        };
+        let header: JwtHeader = serde_json::from_slice(&decode(header)?)
+            .map_err(|_| authentication_failed("the header of the JWT is invalid"))?;
+        let kid = header.kid.unwrap_or_default();
+        let Some(key) = self.keys.get(&kid) else {
+            return Err(authentication_failed(format!(
+                "no key to verify JWTs of key id '{kid}'"
+            )));
+        };
+        let signed = &token[..header_and_payload_len(token)];
+        let signature = decode(signature)?;
+        let verified = match (header.alg.as_str(), key) {
+            ("HS256", JwtKey::Hs256(secret)) => {
+                let key = hmac::Key::new(hmac::HMAC_SHA256, secret);
+                hmac::verify(&key, signed.as_bytes(), &signature).is_ok()
+            }
+            ("RS256", JwtKey::Rs256 { n, e }) => RsaPublicKeyComponents { n, e }
+                .verify(&RSA_PKCS1_2048_8192_SHA256, signed.as_bytes(), &signature)
+                .is_ok(),
+            // the algorithm has to be the one of the key, the header must not pick it
+            _ => false,
+        };
+        if !verified {
+            return Err(authentication_failed(
+                "the signature of the JWT does not match",
+            ));
+        }
+        match serde_json::from_slice(&decode(payload)?) {
+            Ok(Value::Object(claims)) => Ok(claims),
+            _ => Err(authentication_failed("the claims of the JWT are invalid")),
+        }
+    }
+
+    fn validate_claims(
+        &self,
+        claims: &serde_json::Map<String, Value>,
+        now_secs: i64,
+    ) -> RocketMQResult<()> {
+        let now_secs = now_secs as f64;
+        let leeway_secs = self.leeway_secs as f64;
+        if let Some(exp) = numeric_date(claims, "exp")? {
+            if now_secs > exp + leeway_secs {
+                return Err(authentication_failed("the JWT expired"));
+            }
+        }
+        if let Some(nbf) = numeric_date(claims, "nbf")? {
+            if now_secs + leeway_secs < nbf {
+                return Err(authentication_failed("the JWT is not valid yet"));
+            }
+        }
+        if let Some(issuer) = &self.issuer {
+            if claims.get("iss").and_then(Value::as_str) != Some(issuer.as_str()) {
+                return Err(authentication_failed("the JWT is issued by another issuer"));
+            }
+        }
+        if let Some(audience) = &self.audience {
+            let accepted = match claims.get("aud") {
+                Some(Value::String(aud)) => aud == audience.as_str(),
+                Some(Value::Array(auds)) => auds
+                    .iter()
+                    .any(|aud| aud.as_str() == Some(audience.as_str())),
+                _ => false,
+            };
+            if !accepted {
+                return Err(authentication_failed(
+                    "the JWT is meant for another audience",
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reads the NumericDate `claim`, seconds since the epoch that may have a fraction. A claim that is
+/// present but not a number fails the token instead of skipping the check.
+fn numeric_date(
+    claims: &serde_json::Map<String, Value>,
+    claim: &str,
+) -> RocketMQResult<Option<f64>> {
+    match claims.get(claim) {
+        None => Ok(None),
+        Some(value) => value.as_f64().map(Some).ok_or_else(|| {
+            authentication_failed(format!("the {claim} claim of the JWT is not a number"))
+        }),
+    }
+}
+
+fn header_and_payload_len(token: &str) -> usize {
+    token.rfind('.').unwrap_or(token.len())
+}
+
+impl AuthenticationProvider for JwtAuthenticationProvider {
+    fn validate(
+        &self,
+        credentials: &Credentials,
+        _request_context: &RequestContext<'_>,
+    ) -> RocketMQResult<Principal> {
+        let Credentials::BearerToken(token) = credentials else {
+            return Err(authentication_failed("the request carries no bearer token"));
+        };
+        let claims = self.verify_signature(token)?;
+        self.validate_claims(&claims, (get_current_millis() / 1000) as i64)?;
+        let Some(name) = claims
+            .get(self.principal_claim.as_str())
+            .and_then(Value::as_str)
+        else {
+            return Err(authentication_failed(format!(
+                "the JWT has no {} claim",
+                self.principal_claim
+            )));
+        };
+        let mut principal = Principal::new(name);
+        for (claim, value) in &claims {
+            if let Some(value) = value.as_str() {
+                principal
+                    .attributes
+                    .insert(claim.as_str().into(), value.into());
+            }
+        }
+        Ok(principal)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::code::request_code::RequestCode;
+    use crate::protocol::remoting_command::RemotingCommand;
+
+    fn hs256_token(secret: &[u8], claims: Value) -> String {
+        let header = URL_SAFE_NO_PAD.encode(json!({"alg": "HS256", "typ": "JWT"}).to_string());
+        let payload = URL_SAFE_NO_PAD.encode(claims.to_string());
+        let signed = format!("{header}.{payload}");
+        let key = hmac::Key::new(hmac::HMAC_SHA256, secret);
+        let signature = URL_SAFE_NO_PAD.encode(hmac::sign(&key, signed.as_bytes()));
+        format!("{signed}.{signature}")
+    }
+
+    fn validate(provider: &JwtAuthenticationProvider, token: String) -> RocketMQResult<Principal> {
+        let request = RemotingCommand::create_remoting_command(RequestCode::SendMessage);
+        let request_context = RequestContext {
+            remote_addr: "127.0.0.1:10911".parse().unwrap(),
+            request: &request,
+        };
+        provider.validate(&Credentials::BearerToken(token.into()), &request_context)
+    }
+
+    #[test]
+    fn bearer_tokens_are_verified_with_their_key_and_claims() {
+        let provider = JwtAuthenticationProvider::new()
+            .with_hmac_secret("", "secret")
+            .with_issuer("https://idp.example.com")
+            .with_audience("rocketmq");
+        let now = (get_current_millis() / 1000) as i64;
+        let claims = json!({
+            "sub": "order-service",
+            "iss": "https://idp.example.com",
+            "aud": ["rocketmq", "other"],
+            "exp": now + 300,
+        });
+
+        let principal = validate(&provider, hs256_token(b"secret", claims.clone())).unwrap();
+        assert_eq!(principal.name.as_str(), "order-service");
+
+        assert!(validate(&provider, hs256_token(b"forged", claims.clone())).is_err());
+
+        let mut expired = claims.clone();
+        expired["exp"] = json!(now - 3600);
+        assert!(validate(&provider, hs256_token(b"secret", expired)).is_err());
+
+        // NumericDates may have a fraction, other types must not skip the check
+        let mut expired = claims.clone();
+        expired["exp"] = json!((now - 3600) as f64 + 0.5);
+        assert!(validate(&provider, hs256_token(b"secret", expired)).is_err());
+        let mut fractional = claims.clone();
+        fractional["exp"] = json!((now + 300) as f64 + 0.5);
+        assert!(validate(&provider, hs256_token(b"secret", fractional)).is_ok());
+        let mut not_numeric = claims.clone();
+        not_numeric["exp"] = json!((now + 300).to_string());
+        assert!(validate(&provider, hs256_token(b"secret", not_numeric)).is_err());
+        let mut not_numeric = claims.clone();
+        not_numeric["nbf"] = json!(null);
+        assert!(validate(&provider, hs256_token(b"secret", not_numeric)).is_err());
+
+        let mut other_audience = claims;
+        other_audience["aud"] = json!("billing");
+        assert!(validate(&provider, hs256_token(b"secret", other_audience)).is_err());
+    }
+
+    #[test]
+    fn rsa_keys_are_read_from_jwks() {
+        let provider = JwtAuthenticationProvider::new()
+            .with_jwks(r#"{"keys":[{"kid":"k1","kty":"RSA","alg":"RS256","n":"AQAB","e":"AQAB"},{"kty":"EC","crv":"P-256"}]}"#)
+            .unwrap();
+        assert!(matches!(
+            provider.keys.get("k1"),
+            Some(JwtKey::Rs256 { .. })
+        ));
+        assert_eq!(provider.keys.len(), 1);
+    }
+}
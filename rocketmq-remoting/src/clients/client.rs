@@ -15,6 +15,7 @@
  * limitations under the License.
  */
 use std::collections::HashMap;
+use std::net::SocketAddr;
 
 use rocketmq_error::RocketMQResult;
 use rocketmq_error::RocketmqError::ConnectionInvalid;
@@ -33,6 +34,7 @@ use crate::net::channel::Channel;
 use crate::net::channel::ChannelInner;
 use crate::protocol::remoting_command::RemotingCommand;
 use crate::protocol::RemotingCommandType;
+use crate::remoting::InvokeCallback;
 use crate::runtime::connection_handler_context::ConnectionHandlerContext;
 use crate::runtime::connection_handler_context::ConnectionHandlerContextWrapper;
 use crate::runtime::processor::RequestProcessor;
@@ -263,19 +265,39 @@ impl Client {
         }
     }
 
-    /// Invokes a remote operation with the given `RemotingCommand` and provides a callback function
-    /// for handling the response.
+    /// Sends `request` and returns once it is queued for sending; `callback` is handed the response
+    /// or the failure from a spawned task.
     ///
     /// # Arguments
     ///
-    /// * `_request` - The `RemotingCommand` representing the request.
-    /// * `_func` - The callback function to handle the response.
-    ///
-    /// This method is a placeholder and currently does not perform any functionality.
-    pub async fn invoke_with_callback<F>(&self, _request: RemotingCommand, _func: F)
+    /// * `request` - The `RemotingCommand` representing the request.
+    /// * `timeout_millis` - How long to wait for the response.
+    /// * `callback` - Receives the response, or the error the invocation failed with.
+    pub async fn invoke_with_callback<F>(
+        &self,
+        request: RemotingCommand,
+        timeout_millis: u64,
+        callback: F,
+    ) -> RocketMQResult<()>
     where
-        F: FnMut(),
+        F: InvokeCallback + Send + 'static,
     {
+        let (tx, rx) = tokio::sync::oneshot::channel::<RocketMQResult<RemotingCommand>>();
+        if let Err(err) = self
+            .tx
+            .send((request, Some(tx), Some(timeout_millis)))
+            .await
+        {
+            return Err(RemoteError(err.to_string()));
+        }
+        tokio::spawn(async move {
+            match rx.await {
+                Ok(Ok(response)) => callback.operation_succeed(response),
+                Ok(Err(err)) => callback.operation_fail(Box::new(err)),
+                Err(err) => callback.operation_fail(Box::new(err)),
+            }
+        });
+        Ok(())
     }
 
     /// Sends a request to the remote remoting_server.
@@ -333,6 +355,10 @@ impl Client {
         self.inner.channel.0.connection_ref()
     }
 
+    pub fn remote_address(&self) -> SocketAddr {
+        self.inner.channel.1.remote_address()
+    }
+
     pub fn connection_mut(&mut self) -> &mut Connection {
         self.inner.channel.0.connection_mut()
     }
@@ -16,6 +16,7 @@
  */
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::net::SocketAddr;
 use std::sync::atomic::AtomicI32;
 use std::sync::Arc;
 use std::time::Duration;
@@ -37,6 +38,7 @@ use crate::base::connection_net_event::ConnectionNetEvent;
 use crate::clients::Client;
 use crate::clients::RemotingClient;
 use crate::protocol::remoting_command::RemotingCommand;
+use crate::remoting::InvokeCallback;
 use crate::remoting::RemotingService;
 use crate::request_processor::default_request_processor::DefaultRemotingRequestProcessor;
 use crate::runtime::config::client_config::TokioClientConfig;
@@ -58,6 +60,7 @@ pub struct RocketmqDefaultClient<PR = DefaultRemotingRequestProcessor> {
     client_runtime: Option<RocketMQRuntime>,
     processor: PR,
     tx: Option<tokio::sync::broadcast::Sender<ConnectionNetEvent>>,
+    rpc_hooks: Vec<Arc<Box<dyn RPCHook>>>,
 }
 impl<PR: RequestProcessor + Sync + Clone + 'static> RocketmqDefaultClient<PR> {
     pub fn new(tokio_client_config: Arc<TokioClientConfig>, processor: PR) -> Self {
@@ -79,6 +82,55 @@ impl<PR: RequestProcessor + Sync + Clone + 'static> RocketmqDefaultClient<PR> {
             client_runtime: Some(RocketMQRuntime::new_multi(10, "client-thread")),
             processor,
             tx,
+            rpc_hooks: Vec::new(),
+        }
+    }
+
+    fn do_before_rpc_hooks(
+        &self,
+        remote_addr: SocketAddr,
+        request: &mut RemotingCommand,
+    ) -> rocketmq_error::RocketMQResult<()> {
+        for rpc_hook in &self.rpc_hooks {
+            rpc_hook.do_before_request(remote_addr, request)?;
+        }
+        Ok(())
+    }
+
+    fn do_after_rpc_hooks(
+        &self,
+        remote_addr: SocketAddr,
+        response: &mut RemotingCommand,
+    ) -> rocketmq_error::RocketMQResult<()> {
+        for rpc_hook in &self.rpc_hooks {
+            rpc_hook.do_after_response(remote_addr, response)?;
+        }
+        Ok(())
+    }
+
+    /// Sends `request` to `addr`, or to a name server when `addr` is `None`, without waiting for
+    /// the response; `callback` is handed the response or the failure once it is known.
+    pub async fn invoke_with_callback<F>(
+        &self,
+        addr: Option<&CheetahString>,
+        request: RemotingCommand,
+        timeout_millis: u64,
+        callback: F,
+    ) -> rocketmq_error::RocketMQResult<()>
+    where
+        F: InvokeCallback + Send + 'static,
+    {
+        match self.get_and_create_client(addr).await {
+            None => Err(rocketmq_error::RocketmqError::RemoteError(
+                "get client failed".to_string(),
+            )),
+            Some(client) => {
+                let mut request = request;
+                self.do_before_rpc_hooks(client.remote_address(), &mut request)?;
+                client
+                    .invoke_with_callback(request, timeout_millis, callback)
+                    .await
+            }
         }
     }
 }
@@ -270,11 +322,11 @@ impl<PR: RequestProcessor + Sync + Clone + 'static> RemotingService for Rocketmq
     }
 
     fn register_rpc_hook(&mut self, hook: Arc<Box<dyn RPCHook>>) {
-        todo!()
+        self.rpc_hooks.push(hook);
     }
 
     fn clear_rpc_hook(&mut self) {
-        todo!()
+        self.rpc_hooks.clear();
     }
 }
 
@@ -352,7 +404,10 @@ impl<PR: RequestProcessor + Sync + Clone + 'static> RemotingClient for RocketmqD
                 "get client failed".to_string(),
             )),
             Some(mut client) => {
-                match self
+                let remote_addr = client.remote_address();
+                let mut request = request;
+                self.do_before_rpc_hooks(remote_addr, &mut request)?;
+                let response = match self
                     .client_runtime
                     .as_ref()
                     .unwrap()
@@ -377,7 +432,10 @@ impl<PR: RequestProcessor + Sync + Clone + 'static> RemotingClient for RocketmqD
                         }
                     },
                     Err(err) => Err(rocketmq_error::RocketmqError::RemoteError(err.to_string())),
-                }
+                };
+                let mut response = response?;
+                self.do_after_rpc_hooks(remote_addr, &mut response)?;
+                Ok(response)
             }
         }
     }
@@ -394,13 +452,17 @@ impl<PR: RequestProcessor + Sync + Clone + 'static> RemotingClient for RocketmqD
                 error!("get client failed");
             }
             Some(mut client) => {
+                let mut request = request;
+                if let Err(e) = self.do_before_rpc_hooks(client.remote_address(), &mut request) {
+                    error!("rpc hook rejected oneway request: {}", e);
+                    return;
+                }
                 self.client_runtime
                     .as_ref()
                     .unwrap()
                     .get_handle()
                     .spawn(async move {
                         match time::timeout(Duration::from_millis(timeout_millis), async move {
-                            let mut request = request;
                             request.mark_oneway_rpc_ref();
                             client.send(request).await
                         })
@@ -148,6 +148,12 @@ pub enum RequestCode {
     RemoveColdDataFlowCtrConfig = 2002,
     GetColdDataFlowCtrInfo = 2003,
     SetCommitlogReadMode = 2004,
+
+    // 90000-90999 are reserved for the requests only the Rust broker serves. They are not part
+    // of the Java protocol, Java brokers answer them with REQUEST_CODE_NOT_SUPPORTED.
+    GetPopStats = 90001,
+    GetTopicDiskUsage = 90002,
+    GetBrokerReadiness = 90003,
     Unknown = -9999999,
 }
 
@@ -290,6 +296,9 @@ impl From<i32> for RequestCode {
             2002 => RequestCode::RemoveColdDataFlowCtrConfig,
             2003 => RequestCode::GetColdDataFlowCtrInfo,
             2004 => RequestCode::SetCommitlogReadMode,
+            90001 => RequestCode::GetPopStats,
+            90002 => RequestCode::GetTopicDiskUsage,
+            90003 => RequestCode::GetBrokerReadiness,
             _ => RequestCode::Unknown,
         }
     }
@@ -18,6 +18,7 @@
 use bytes::BufMut;
 use bytes::Bytes;
 use bytes::BytesMut;
+use rocketmq_common::common::server::config::ServerConfig;
 use rocketmq_error::RocketmqError;
 use tokio_util::codec::BytesCodec;
 use tokio_util::codec::Decoder;
@@ -46,7 +47,7 @@ use crate::protocol::remoting_command::RemotingCommand;
 ///
 /// This function will return an error if the encoding process fails.
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
-pub struct RemotingCommandCodec(());
+pub struct RemotingCommandCodec(CodecLimits);
 
 impl Default for RemotingCommandCodec {
     fn default() -> Self {
@@ -56,7 +57,42 @@ impl Default for RemotingCommandCodec {
 
 impl RemotingCommandCodec {
     pub fn new() -> Self {
-        RemotingCommandCodec(())
+        Self::with_limits(CodecLimits::default())
+    }
+
+    pub fn with_limits(limits: CodecLimits) -> Self {
+        RemotingCommandCodec(limits)
+    }
+}
+
+/// Bounds every decoded frame must stay within, so a peer can not make the codec buffer
+/// unbounded amounts of memory.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct CodecLimits {
+    /// Largest frame accepted, also the largest size a compressed body may inflate to.
+    pub max_frame_length: usize,
+    pub max_header_length: usize,
+    /// Whether bodies flagged as compressed are inflated while decoding.
+    pub decompress_body: bool,
+}
+
+impl Default for CodecLimits {
+    fn default() -> Self {
+        Self {
+            max_frame_length: 16 * 1024 * 1024,
+            max_header_length: 1024 * 1024,
+            decompress_body: true,
+        }
+    }
+}
+
+impl From<&ServerConfig> for CodecLimits {
+    fn from(config: &ServerConfig) -> Self {
+        Self {
+            max_frame_length: config.max_frame_length,
+            max_header_length: config.max_header_length,
+            decompress_body: config.decompress_request_body,
+        }
     }
 }
 
@@ -91,7 +127,7 @@ impl Decoder for RemotingCommandCodec {
     ///
     /// This function will return an error if the decoding process fails.
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-        RemotingCommand::decode(src)
+        RemotingCommand::decode_with_limits(src, &self.0)
     }
 }
 
@@ -137,9 +173,13 @@ pub struct CompositeCodec {
 
 impl CompositeCodec {
     pub fn new() -> Self {
+        Self::with_limits(CodecLimits::default())
+    }
+
+    pub fn with_limits(limits: CodecLimits) -> Self {
         Self {
             bytes_codec: BytesCodec::new(),
-            remoting_command_codec: RemotingCommandCodec::new(),
+            remoting_command_codec: RemotingCommandCodec::with_limits(limits),
         }
     }
 }
@@ -213,4 +253,92 @@ mod tests {
             .set_remark_option(Some("remark".to_string()));
         assert!(encoder.encode(command, &mut dst).is_ok());
     }
+
+    fn encode_frame(command: RemotingCommand) -> BytesMut {
+        let mut dst = BytesMut::new();
+        RemotingCommandCodec::new()
+            .encode(command, &mut dst)
+            .unwrap();
+        dst
+    }
+
+    fn compress(data: &[u8]) -> Bytes {
+        use std::io::Write;
+
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        Bytes::from(encoder.finish().unwrap())
+    }
+
+    #[test]
+    fn decode_rejects_frames_beyond_limits() {
+        let command = RemotingCommand::create_remoting_command(1).set_body(vec![0u8; 1024]);
+        let mut decoder = RemotingCommandCodec::with_limits(CodecLimits {
+            max_frame_length: 512,
+            ..CodecLimits::default()
+        });
+        assert!(decoder.decode(&mut encode_frame(command)).is_err());
+
+        let mut decoder = RemotingCommandCodec::with_limits(CodecLimits {
+            max_header_length: 8,
+            ..CodecLimits::default()
+        });
+        let command = RemotingCommand::create_remoting_command(1).set_remark("remark");
+        assert!(decoder.decode(&mut encode_frame(command)).is_err());
+
+        let mut src = BytesMut::from(&[0xff, 0xff, 0xff, 0xff][..]);
+        assert!(RemotingCommandCodec::new().decode(&mut src).is_err());
+    }
+
+    #[test]
+    fn decode_inflates_compressed_body_within_limit() {
+        let body = vec![7u8; 4096];
+        let command = RemotingCommand::create_remoting_command(1)
+            .set_body(compress(&body))
+            .mark_body_compressed();
+        let decoded = RemotingCommandCodec::new()
+            .decode(&mut encode_frame(command.clone()))
+            .unwrap()
+            .unwrap();
+        assert!(!decoded.is_body_compressed());
+        assert_eq!(decoded.body().as_ref().unwrap().as_ref(), body.as_slice());
+
+        let mut decoder = RemotingCommandCodec::with_limits(CodecLimits {
+            max_frame_length: 1024,
+            ..CodecLimits::default()
+        });
+        let decoded = decoder
+            .decode(&mut encode_frame(command.clone()))
+            .unwrap()
+            .unwrap();
+        assert!(decoded.is_body_compressed());
+
+        let mut decoder = RemotingCommandCodec::with_limits(CodecLimits {
+            decompress_body: false,
+            ..CodecLimits::default()
+        });
+        let decoded = decoder.decode(&mut encode_frame(command)).unwrap().unwrap();
+        assert!(decoded.is_body_compressed());
+    }
+
+    #[test]
+    fn decode_inflates_zstd_compressed_response_body() {
+        let body = vec![7u8; 4096];
+        let mut command = RemotingCommand::create_response_command().set_body(body.clone());
+        assert!(command.compress_body_zstd(3));
+        assert!(command.is_body_zstd_compressed());
+        assert!(command.body().as_ref().unwrap().len() < body.len());
+
+        let decoded = RemotingCommandCodec::new()
+            .decode(&mut encode_frame(command))
+            .unwrap()
+            .unwrap();
+        assert!(!decoded.is_body_zstd_compressed());
+        assert_eq!(decoded.body().as_ref().unwrap().as_ref(), body.as_slice());
+
+        let mut incompressible = RemotingCommand::create_response_command().set_body(vec![1u8]);
+        assert!(!incompressible.compress_body_zstd(3));
+        assert!(!incompressible.is_body_zstd_compressed());
+    }
 }
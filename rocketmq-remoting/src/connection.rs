@@ -16,6 +16,10 @@
  */
 use std::hash::Hash;
 use std::hash::Hasher;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
 
 use bytes::BufMut;
 use bytes::Bytes;
@@ -24,12 +28,159 @@ use futures_util::stream::SplitSink;
 use futures_util::stream::SplitStream;
 use futures_util::SinkExt;
 use futures_util::StreamExt;
+use parking_lot::Mutex;
+use rocketmq_common::common::server::config::ServerConfig;
 use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio::sync::Notify;
 use tokio_util::codec::Framed;
+use tracing::warn;
 
+use crate::codec::remoting_command_codec::CodecLimits;
 use crate::codec::remoting_command_codec::CompositeCodec;
 use crate::protocol::remoting_command::RemotingCommand;
 
+/// Bounds the outbound bytes a connection may have waiting on a slow peer.
+///
+/// A connection stops being writable once more than `high` bytes are pending and becomes
+/// writable again when they drain below `low`. Sends on an unwritable connection wait until it
+/// is writable again.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct WriteBufferWaterMark {
+    pub low: usize,
+    pub high: usize,
+}
+
+impl Default for WriteBufferWaterMark {
+    fn default() -> Self {
+        Self {
+            low: 16 * 1024 * 1024,
+            high: 32 * 1024 * 1024,
+        }
+    }
+}
+
+impl From<&ServerConfig> for WriteBufferWaterMark {
+    fn from(config: &ServerConfig) -> Self {
+        Self {
+            low: config.write_buffer_low_water_mark,
+            high: config.write_buffer_high_water_mark,
+        }
+    }
+}
+
+/// Counts the bytes queued for the writer task but not yet written to the socket.
+struct PendingWrites {
+    bytes: AtomicUsize,
+    writable: AtomicBool,
+    low: AtomicUsize,
+    high: AtomicUsize,
+    /// Wakes the sends waiting for the connection to become writable or to fail.
+    drained: Notify,
+    /// The error the writer task stopped on.
+    error: Mutex<Option<String>>,
+}
+
+impl PendingWrites {
+    fn new(water_mark: WriteBufferWaterMark) -> Self {
+        Self {
+            bytes: AtomicUsize::new(0),
+            writable: AtomicBool::new(true),
+            low: AtomicUsize::new(water_mark.low),
+            high: AtomicUsize::new(water_mark.high),
+            drained: Notify::new(),
+            error: Mutex::new(None),
+        }
+    }
+
+    fn set_water_mark(&self, water_mark: WriteBufferWaterMark) {
+        self.low.store(water_mark.low, Ordering::Release);
+        self.high.store(water_mark.high, Ordering::Release);
+    }
+
+    fn add(&self, len: usize) {
+        let pending = self.bytes.fetch_add(len, Ordering::AcqRel) + len;
+        if pending > self.high.load(Ordering::Acquire) {
+            self.writable.store(false, Ordering::Release);
+        }
+    }
+
+    fn remove(&self, len: usize) {
+        let pending = self.bytes.fetch_sub(len, Ordering::AcqRel) - len;
+        if pending < self.low.load(Ordering::Acquire) && !self.writable.swap(true, Ordering::AcqRel)
+        {
+            self.drained.notify_waiters();
+        }
+    }
+
+    fn fail(&self, error: String) {
+        *self.error.lock() = Some(error);
+        self.drained.notify_waiters();
+    }
+
+    fn error(&self) -> Option<std::io::Error> {
+        self.error.lock().as_ref().map(|error| {
+            std::io::Error::new(
+                std::io::ErrorKind::BrokenPipe,
+                format!("write to connection failed: {error}"),
+            )
+        })
+    }
+
+    /// Waits until the connection is writable, failing once the writer task stopped on an error.
+    async fn wait_writable(&self) -> std::io::Result<()> {
+        loop {
+            // registered before the checks, so a notification in between is not missed
+            let drained = self.drained.notified();
+            if let Some(error) = self.error() {
+                return Err(error);
+            }
+            if self.writable.load(Ordering::Acquire) {
+                return Ok(());
+            }
+            drained.await;
+        }
+    }
+}
+
+/// Writes the queued frames to the socket, flushing once per batch of frames that are ready.
+///
+/// The bytes of a frame stay pending until the flush covering it finished. After a failed write
+/// the remaining frames are dropped and later sends fail with the error of the write.
+async fn run_write(
+    mut writer: SplitSink<Framed<TcpStream, CompositeCodec>, Bytes>,
+    mut rx: mpsc::UnboundedReceiver<Bytes>,
+    pending_writes: Arc<PendingWrites>,
+) {
+    while let Some(frame) = rx.recv().await {
+        let mut batch_len = frame.len();
+        let mut result = writer.feed(frame).await;
+        while result.is_ok() {
+            match rx.try_recv() {
+                Ok(frame) => {
+                    batch_len += frame.len();
+                    result = writer.feed(frame).await;
+                }
+                Err(_) => break,
+            }
+        }
+        if result.is_ok() {
+            result = writer.flush().await;
+        }
+        pending_writes.remove(batch_len);
+        if let Err(e) = result {
+            warn!("write to connection failed: {}", e);
+            pending_writes.fail(e.to_string());
+            rx.close();
+            while let Some(frame) = rx.recv().await {
+                pending_writes.remove(frame.len());
+            }
+            return;
+        }
+    }
+    let _ = writer.close().await;
+}
+
 /// Send and receive `Frame` values from a remote peer.
 ///
 /// When implementing networking protocols, a message on that protocol is
@@ -41,12 +192,13 @@ use crate::protocol::remoting_command::RemotingCommand;
 /// the `Connection` creates the frame and returns it to the caller.
 ///
 /// When sending frames, the frame is first encoded into the write buffer.
-/// The contents of the write buffer are then written to the socket.
+/// The encoded frame is then queued for a writer task, which writes it to the socket.
 pub struct Connection {
     /// The `Framed` instance used for reading from and writing to the TCP stream.
     /// It leverages the `RemotingCommandCodec` for encoding and decoding frames.
     //pub(crate) framed: Framed<TcpStream, RemotingCommandCodec>,
-    writer: SplitSink<Framed<TcpStream, CompositeCodec>, Bytes>,
+    /// Frames queued for the writer task, which owns the write half of the stream.
+    writer: mpsc::UnboundedSender<Bytes>,
     reader: SplitStream<Framed<TcpStream, CompositeCodec>>,
 
     /// A boolean flag indicating the current state of the connection.
@@ -55,6 +207,8 @@ pub struct Connection {
     pub(crate) ok: bool,
 
     buf: BytesMut,
+
+    pending_writes: Arc<PendingWrites>,
 }
 
 impl Hash for Connection {
@@ -64,8 +218,7 @@ impl Hash for Connection {
 
         // Use the addr: *const _ess of writer and reader to hash them (they serve as a unique
         // identifier for these components)
-        let writer_addr: *const SplitSink<Framed<TcpStream, CompositeCodec>, Bytes> =
-            &self.writer as *const SplitSink<Framed<TcpStream, CompositeCodec>, Bytes>;
+        let writer_addr: *const PendingWrites = Arc::as_ptr(&self.pending_writes);
         let reader_addr: *const SplitStream<Framed<TcpStream, CompositeCodec>> =
             &self.reader as *const SplitStream<Framed<TcpStream, CompositeCodec>>;
 
@@ -80,7 +233,7 @@ impl PartialEq for Connection {
         self.ok == other.ok
 
         // Compare the addr: *const _ess of writer and reader
-            && (std::ptr::eq(&self.writer, &other.writer))
+            && Arc::ptr_eq(&self.pending_writes, &other.pending_writes)
             && (std::ptr::eq(&self.reader, &other.reader))
     }
 }
@@ -98,24 +251,51 @@ impl Connection {
     ///
     /// A new `Connection` instance.
     pub fn new(tcp_stream: TcpStream) -> Connection {
-        let framed = Framed::with_capacity(tcp_stream, CompositeCodec::new(), 1024 * 4);
+        Self::with_codec_limits(tcp_stream, CodecLimits::default())
+    }
+
+    /// Creates a connection whose inbound frames must stay within `limits`.
+    ///
+    /// Spawns the task writing the outbound frames, so it must be called within a tokio runtime.
+    pub fn with_codec_limits(tcp_stream: TcpStream, limits: CodecLimits) -> Connection {
+        let framed =
+            Framed::with_capacity(tcp_stream, CompositeCodec::with_limits(limits), 1024 * 4);
         let (writer, reader) = framed.split();
+        let pending_writes = Arc::new(PendingWrites::new(WriteBufferWaterMark::default()));
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(run_write(writer, rx, pending_writes.clone()));
         Self {
-            writer,
+            writer: tx,
             reader,
             ok: true,
             buf: BytesMut::with_capacity(4096),
+            pending_writes,
         }
     }
 
+    /// Reports the connection unwritable while the bytes waiting to be written exceed
+    /// `water_mark`.
+    pub fn with_write_buffer_water_mark(self, water_mark: WriteBufferWaterMark) -> Self {
+        self.pending_writes.set_water_mark(water_mark);
+        self
+    }
+
+    /// Bytes queued on the connection that have not been written to the socket yet.
     #[inline]
-    pub fn reader(&self) -> &SplitStream<Framed<TcpStream, CompositeCodec>> {
-        &self.reader
+    pub fn pending_write_bytes(&self) -> usize {
+        self.pending_writes.bytes.load(Ordering::Acquire)
     }
 
+    /// Whether the pending outbound bytes stay within the write buffer water mark, so more can be
+    /// written without piling up behind a slow peer.
     #[inline]
-    pub fn writer(&self) -> &SplitSink<Framed<TcpStream, CompositeCodec>, Bytes> {
-        &self.writer
+    pub fn is_writable(&self) -> bool {
+        self.pending_writes.writable.load(Ordering::Acquire)
+    }
+
+    #[inline]
+    pub fn reader(&self) -> &SplitStream<Framed<TcpStream, CompositeCodec>> {
+        &self.reader
     }
 
     /// Receives a `RemotingCommand` from the connection.
@@ -142,12 +322,37 @@ impl Connection {
         &mut self,
         mut command: RemotingCommand,
     ) -> rocketmq_error::RocketMQResult<()> {
-        self.buf.clear();
         command.fast_header_encode(&mut self.buf);
         if let Some(body_inner) = command.take_body() {
             self.buf.put(body_inner);
         }
-        self.writer.send(self.buf.clone().freeze()).await?;
+        // Hands the encoded frame off without copying it; once the writer has released earlier
+        // frames the buffer reclaims their allocation instead of allocating a new one.
+        let frame = self.buf.split().freeze();
+        self.write_frame(frame).await
+    }
+
+    /// Queues `frame` for the writer task, the bytes count as pending until they are written.
+    ///
+    /// Waits while the pending bytes exceed the write buffer water mark, so a slow peer holds back
+    /// its senders instead of piling up frames in memory.
+    async fn write_frame(&mut self, frame: Bytes) -> rocketmq_error::RocketMQResult<()> {
+        self.pending_writes.wait_writable().await?;
+        let len = frame.len();
+        self.pending_writes.add(len);
+        if self.writer.send(frame).is_err() {
+            self.pending_writes.remove(len);
+            return Err(self
+                .pending_writes
+                .error()
+                .unwrap_or_else(|| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::BrokenPipe,
+                        "the connection stopped writing",
+                    )
+                })
+                .into());
+        }
         Ok(())
     }
 
@@ -163,10 +368,9 @@ impl Connection {
     ///
     /// # Errors
     ///
-    /// This function returns a `RemotingError` if the underlying writer fails to send the data.
+    /// This function returns a `RemotingError` if an earlier write to the socket failed.
     pub async fn send_bytes(&mut self, bytes: Bytes) -> rocketmq_error::RocketMQResult<()> {
-        self.writer.send(bytes).await?;
-        Ok(())
+        self.write_frame(bytes).await
     }
 
     /// Sends a static byte slice (`&'static [u8]`) over the connection.
@@ -181,7 +385,7 @@ impl Connection {
     ///
     /// # Errors
     ///
-    /// This function returns a `RemotingError` if the underlying writer fails to send the data.
+    /// This function returns a `RemotingError` if an earlier write to the socket failed.
     ///
     /// # Notes
     ///
@@ -189,8 +393,111 @@ impl Connection {
     /// of the program, making it suitable for scenarios where the data does not need to be
     /// dynamically allocated or modified.
     pub async fn send_slice(&mut self, slice: &'static [u8]) -> rocketmq_error::RocketMQResult<()> {
-        let bytes = slice.into();
-        self.writer.send(bytes).await?;
-        Ok(())
+        self.write_frame(Bytes::from_static(slice)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    #[test]
+    fn pending_writes_toggle_writable_between_water_marks() {
+        let pending_writes = PendingWrites::new(WriteBufferWaterMark { low: 10, high: 20 });
+        pending_writes.add(15);
+        assert!(pending_writes.writable.load(Ordering::Acquire));
+
+        pending_writes.add(10);
+        assert_eq!(pending_writes.bytes.load(Ordering::Acquire), 25);
+        assert!(!pending_writes.writable.load(Ordering::Acquire));
+
+        // 15 bytes left, still above the low water mark
+        pending_writes.remove(10);
+        assert!(!pending_writes.writable.load(Ordering::Acquire));
+
+        pending_writes.remove(15);
+        assert_eq!(pending_writes.bytes.load(Ordering::Acquire), 0);
+        assert!(pending_writes.writable.load(Ordering::Acquire));
+    }
+
+    #[tokio::test]
+    async fn sends_wait_for_a_slow_peer_to_read_the_pending_bytes() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (mut peer, _) = listener.accept().await.unwrap();
+        let mut connection =
+            Connection::new(client).with_write_buffer_water_mark(WriteBufferWaterMark {
+                low: 64 * 1024,
+                high: 128 * 1024,
+            });
+
+        // far more than the socket buffers take while the peer does not read
+        let frame = Bytes::from(vec![0u8; 1024 * 1024]);
+        let mut frames = 0;
+        while tokio::time::timeout(
+            Duration::from_millis(200),
+            connection.send_bytes(frame.clone()),
+        )
+        .await
+        .is_ok_and(|result| result.is_ok())
+        {
+            frames += 1;
+            assert!(frames < 32, "sends never waited for the slow peer");
+        }
+        assert!(!connection.is_writable());
+        assert!(connection.pending_write_bytes() > 128 * 1024);
+
+        let expected = (frames + 1) * frame.len();
+        let reader = tokio::spawn(async move {
+            let mut buf = vec![0u8; 64 * 1024];
+            let mut read = 0;
+            while read < expected {
+                read += peer.read(&mut buf).await.unwrap();
+            }
+        });
+        tokio::time::timeout(Duration::from_secs(5), connection.send_bytes(frame))
+            .await
+            .unwrap()
+            .unwrap();
+        reader.await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !connection.is_writable() || connection.pending_write_bytes() > 0 {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn sends_after_a_failed_write_return_its_error() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (peer, _) = listener.accept().await.unwrap();
+        peer.set_linger(Some(Duration::ZERO)).unwrap();
+        drop(peer);
+        let mut connection = Connection::new(client);
+
+        let frame = Bytes::from(vec![0u8; 64 * 1024]);
+        let mut failed = false;
+        for _ in 0..100 {
+            if connection.send_bytes(frame.clone()).await.is_err() {
+                failed = true;
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert!(failed, "writing to a reset connection never failed");
+        let error = connection.send_bytes(frame).await.unwrap_err();
+        assert!(error.to_string().contains("write to connection failed"));
     }
 }
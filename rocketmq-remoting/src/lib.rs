@@ -19,6 +19,7 @@
 #![feature(duration_constructors)]
 extern crate core;
 
+pub mod auth;
 pub mod clients;
 pub mod code;
 pub mod codec;
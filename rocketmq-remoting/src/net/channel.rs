@@ -92,6 +92,14 @@ impl Channel {
     pub fn upgrade(&self) -> Option<ArcMut<ChannelInner>> {
         self.inner.upgrade()
     }
+
+    /// Whether the peer is still connected and keeps up with what is written to it, so a response
+    /// written now would reach it without piling up in memory.
+    pub fn is_writable(&self) -> bool {
+        self.inner
+            .upgrade()
+            .is_some_and(|inner| inner.is_ok() && inner.connection_ref().is_writable())
+    }
 }
 
 impl PartialEq for Channel {
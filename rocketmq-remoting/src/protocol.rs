@@ -84,7 +84,7 @@ impl RemotingCommandType {
 }
 
 #[repr(u8)]
-#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize, Default, Hash, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Default, Hash, Copy)]
 pub enum LanguageCode {
     JAVA,
     CPP,
@@ -100,6 +100,19 @@ pub enum LanguageCode {
     OMS,
     #[default]
     RUST,
+    #[allow(non_camel_case_types)]
+    NODE_JS,
+}
+
+// languages unknown to this build, e.g. of newer clients, are read as `OTHER`
+impl<'de> Deserialize<'de> for LanguageCode {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        Ok(LanguageCode::get_code_from_name(&name).unwrap_or(LanguageCode::OTHER))
+    }
 }
 
 impl fmt::Display for LanguageCode {
@@ -118,6 +131,7 @@ impl fmt::Display for LanguageCode {
             LanguageCode::PHP => write!(f, "PHP"),
             LanguageCode::OMS => write!(f, "OMS"),
             LanguageCode::RUST => write!(f, "RUST"),
+            LanguageCode::NODE_JS => write!(f, "NODE_JS"),
         }
     }
 }
@@ -138,6 +152,7 @@ impl LanguageCode {
             10 => Some(LanguageCode::PHP),
             11 => Some(LanguageCode::OMS),
             12 => Some(LanguageCode::RUST),
+            13 => Some(LanguageCode::NODE_JS),
             _ => None,
         }
     }
@@ -157,6 +172,7 @@ impl LanguageCode {
             LanguageCode::PHP => 10,
             LanguageCode::OMS => 11,
             LanguageCode::RUST => 12,
+            LanguageCode::NODE_JS => 13,
         }
     }
 
@@ -175,6 +191,7 @@ impl LanguageCode {
             "PHP" => Some(LanguageCode::PHP),
             "OMS" => Some(LanguageCode::OMS),
             "RUST" => Some(LanguageCode::RUST),
+            "NODE_JS" => Some(LanguageCode::NODE_JS),
             _ => None,
         }
     }
@@ -340,6 +357,14 @@ impl DataVersion {
         self.state_version = state_version;
         self.counter.fetch_add(1, Ordering::SeqCst);
     }
+
+    /// Orders two versions by state version, then counter, then timestamp.
+    pub fn compare(&self, other: &DataVersion) -> std::cmp::Ordering {
+        self.state_version
+            .cmp(&other.state_version)
+            .then_with(|| self.counter().cmp(&other.counter()))
+            .then_with(|| self.timestamp.cmp(&other.timestamp))
+    }
 }
 
 impl Display for DataVersion {
@@ -537,6 +562,11 @@ mod tests {
             Some(LanguageCode::DOTNET),
             LanguageCode::get_code_from_name("DOTNET")
         );
+
+        // languages of newer peers do not fail the command
+        assert_eq!(Some(LanguageCode::NODE_JS), LanguageCode::value_of(13));
+        let language: LanguageCode = serde_json::from_str("\"KOTLIN\"").unwrap();
+        assert_eq!(language, LanguageCode::OTHER);
     }
 
     #[cfg(test)]
@@ -585,6 +615,17 @@ mod tests {
             );
         }
 
+        #[test]
+        fn data_version_compare() {
+            let mut older = DataVersion::new();
+            let newer = DataVersion::new();
+            newer.increment_counter();
+            assert_eq!(older.compare(&newer), std::cmp::Ordering::Less);
+            assert_eq!(newer.compare(&older), std::cmp::Ordering::Greater);
+            older.set_state_version(1);
+            assert_eq!(older.compare(&newer), std::cmp::Ordering::Greater);
+        }
+
         #[test]
         fn data_version_next_version_with_state() {
             let mut data_version = DataVersion::new();
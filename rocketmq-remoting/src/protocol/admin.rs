@@ -18,6 +18,8 @@
 pub mod consume_stats;
 pub mod consume_stats_list;
 pub mod offset_wrapper;
+pub mod pop_stats;
 pub mod rollback_stats;
+pub mod topic_disk_usage;
 pub mod topic_offset;
 pub mod topic_stats_table;
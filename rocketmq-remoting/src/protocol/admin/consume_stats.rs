@@ -23,12 +23,16 @@ use serde::Serialize;
 use serde_json_any_key::*;
 
 use crate::protocol::admin::offset_wrapper::OffsetWrapper;
+use crate::protocol::admin::pop_stats::PopStats;
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct ConsumeStats {
     #[serde(with = "any_key_map")]
     pub offset_table: HashMap<MessageQueue, OffsetWrapper>,
     pub consume_tps: f64,
+    /// Pop consumption progress, only present for groups consuming by pop.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pop_stats: Option<PopStats>,
 }
 
 impl ConsumeStats {
@@ -36,6 +40,7 @@ impl ConsumeStats {
         Self {
             offset_table: HashMap::new(),
             consume_tps: 0.0,
+            pop_stats: None,
         }
     }
 
@@ -68,4 +73,12 @@ impl ConsumeStats {
     pub fn set_consume_tps(&mut self, consume_tps: f64) {
         self.consume_tps = consume_tps;
     }
+
+    pub fn get_pop_stats(&self) -> Option<&PopStats> {
+        self.pop_stats.as_ref()
+    }
+
+    pub fn set_pop_stats(&mut self, pop_stats: Option<PopStats>) {
+        self.pop_stats = pop_stats;
+    }
 }
@@ -48,7 +48,8 @@ mod tests {
         let mut map = HashMap::new();
         let consume_stats_list = vec![ConsumeStats {
             offset_table: HashMap::new(),
-            consume_tps: 1.2,
+            consume_tps: 1.2,
+            pop_stats: None,
         }];
         map.insert(CheetahString::from("group1"), consume_stats_list);
         let consume_status_list = ConsumeStatsList {
@@ -0,0 +1,118 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashMap;
+
+use rocketmq_common::common::message::message_queue::MessageQueue;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json_any_key::*;
+
+/// Pop consumption progress of a consumer group on one queue.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PopQueueStats {
+    pub broker_offset: i64,
+    pub consumer_offset: i64,
+    /// Popped messages which are neither acked nor revived yet.
+    pub inflight_message_num: i64,
+    /// Popped messages whose check points are still merged in memory by the broker.
+    pub buffered_message_num: i64,
+}
+
+/// Pop consumption progress of a consumer group, offset lag alone does not show how many popped
+/// messages are still waiting for an ack.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PopStats {
+    #[serde(with = "any_key_map")]
+    pub offset_table: HashMap<MessageQueue, PopQueueStats>,
+    /// Check points and acks in the revive topic not yet processed by the revive services.
+    pub revive_behind_messages: i64,
+    pub revive_behind_millis: i64,
+}
+
+impl PopStats {
+    pub fn compute_total_inflight(&self) -> i64 {
+        self.offset_table
+            .values()
+            .map(|value| value.inflight_message_num)
+            .sum()
+    }
+
+    /// Merges the stats of another broker into these.
+    pub fn merge(&mut self, other: PopStats) {
+        self.offset_table.extend(other.offset_table);
+        self.revive_behind_messages += other.revive_behind_messages;
+        self.revive_behind_millis = self.revive_behind_millis.max(other.revive_behind_millis);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::RemotingDeserializable;
+    use crate::protocol::RemotingSerializable;
+
+    fn queue_stats(inflight_message_num: i64) -> PopQueueStats {
+        PopQueueStats {
+            broker_offset: 100,
+            consumer_offset: 80,
+            inflight_message_num,
+            buffered_message_num: 1,
+        }
+    }
+
+    #[test]
+    fn encode_and_decode() {
+        let mut pop_stats = PopStats::default();
+        pop_stats.offset_table.insert(
+            MessageQueue::from_parts("TopicTest", "broker-a", 0),
+            queue_stats(3),
+        );
+        pop_stats.revive_behind_messages = 5;
+        let decoded = PopStats::decode(&pop_stats.encode().unwrap()).unwrap();
+        assert_eq!(decoded.compute_total_inflight(), 3);
+        assert_eq!(decoded.revive_behind_messages, 5);
+    }
+
+    #[test]
+    fn merge_sums_backlog_and_keeps_max_delay() {
+        let mut pop_stats = PopStats {
+            revive_behind_messages: 2,
+            revive_behind_millis: 300,
+            ..Default::default()
+        };
+        pop_stats.offset_table.insert(
+            MessageQueue::from_parts("TopicTest", "broker-a", 0),
+            queue_stats(3),
+        );
+        let mut other = PopStats {
+            revive_behind_messages: 4,
+            revive_behind_millis: 100,
+            ..Default::default()
+        };
+        other.offset_table.insert(
+            MessageQueue::from_parts("TopicTest", "broker-b", 0),
+            queue_stats(2),
+        );
+        pop_stats.merge(other);
+        assert_eq!(pop_stats.compute_total_inflight(), 5);
+        assert_eq!(pop_stats.revive_behind_messages, 6);
+        assert_eq!(pop_stats.revive_behind_millis, 300);
+    }
+}
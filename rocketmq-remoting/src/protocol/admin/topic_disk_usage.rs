@@ -0,0 +1,60 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use std::collections::HashMap;
+
+use cheetah_string::CheetahString;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Bytes the readable messages of each topic take on the disk of a broker.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopicDiskUsage {
+    pub disk_usage_table: HashMap<CheetahString, i64>,
+}
+
+impl TopicDiskUsage {
+    /// Adds the usage reported by another broker to these.
+    pub fn merge(&mut self, other: TopicDiskUsage) {
+        for (topic, disk_usage) in other.disk_usage_table {
+            *self.disk_usage_table.entry(topic).or_default() += disk_usage;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::RemotingDeserializable;
+    use crate::protocol::RemotingSerializable;
+
+    #[test]
+    fn encode_decode_and_merge() {
+        let mut disk_usage = TopicDiskUsage::default();
+        disk_usage
+            .disk_usage_table
+            .insert(CheetahString::from_static_str("TopicTest"), 100);
+        let mut other = TopicDiskUsage::decode(&disk_usage.encode().unwrap()).unwrap();
+        other
+            .disk_usage_table
+            .insert(CheetahString::from_static_str("OtherTopic"), 30);
+
+        disk_usage.merge(other);
+        assert_eq!(disk_usage.disk_usage_table["TopicTest"], 200);
+        assert_eq!(disk_usage.disk_usage_table["OtherTopic"], 30);
+    }
+}
@@ -39,6 +39,7 @@ pub mod group_list;
 pub mod ha_client_runtime_info;
 pub mod ha_connection_runtime_info;
 pub mod ha_runtime_info;
+pub mod heartbeat_response_body;
 pub mod kv_table;
 pub mod pop_process_queue_info;
 pub mod process_queue_info;
@@ -36,6 +36,14 @@ impl BrokerMemberGroup {
             broker_addrs: HashMap::new(),
         }
     }
+
+    /// The member with the smallest broker id, the one producers write to.
+    pub fn minimum_broker(&self) -> Option<(u64, &CheetahString)> {
+        self.broker_addrs
+            .iter()
+            .min_by_key(|(broker_id, _)| **broker_id)
+            .map(|(broker_id, broker_addr)| (*broker_id, broker_addr))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -63,6 +71,19 @@ mod tests {
         assert!(group.broker_addrs.is_empty());
     }
 
+    #[test]
+    fn minimum_broker_is_the_member_with_the_smallest_id() {
+        let mut group = BrokerMemberGroup::new("test_cluster".into(), "test_broker".into());
+        assert_eq!(group.minimum_broker(), None);
+
+        group.broker_addrs.insert(2, "127.0.0.1:10921".into());
+        group.broker_addrs.insert(1, "127.0.0.1:10911".into());
+        assert_eq!(
+            group.minimum_broker(),
+            Some((1, &CheetahString::from("127.0.0.1:10911")))
+        );
+    }
+
     #[test]
     fn broker_member_group_serializes_correctly() {
         let cluster = CheetahString::from("test_cluster");
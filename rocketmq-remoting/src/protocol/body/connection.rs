@@ -27,6 +27,16 @@ pub struct Connection {
     client_addr: CheetahString,
     language: LanguageCode,
     version: i32,
+    /// Last heartbeat of the client, in milliseconds.
+    #[serde(default)]
+    last_update_timestamp: u64,
+    /// Messages the client produced, for producer connections, or consumed, for consumer
+    /// connections, in the last minute.
+    #[serde(default)]
+    msgs_per_minute: u64,
+    /// Time of the last message the client produced or consumed, in milliseconds.
+    #[serde(default)]
+    last_msg_timestamp: u64,
 }
 
 impl Connection {
@@ -36,6 +46,9 @@ impl Connection {
             client_addr: CheetahString::default(),
             language: LanguageCode::default(),
             version: 0,
+            last_update_timestamp: 0,
+            msgs_per_minute: 0,
+            last_msg_timestamp: 0,
         }
     }
 }
@@ -72,4 +85,48 @@ impl Connection {
     pub fn set_version(&mut self, version: i32) {
         self.version = version;
     }
+
+    pub fn get_last_update_timestamp(&self) -> u64 {
+        self.last_update_timestamp
+    }
+
+    pub fn set_last_update_timestamp(&mut self, last_update_timestamp: u64) {
+        self.last_update_timestamp = last_update_timestamp;
+    }
+
+    pub fn get_msgs_per_minute(&self) -> u64 {
+        self.msgs_per_minute
+    }
+
+    pub fn set_msgs_per_minute(&mut self, msgs_per_minute: u64) {
+        self.msgs_per_minute = msgs_per_minute;
+    }
+
+    pub fn get_last_msg_timestamp(&self) -> u64 {
+        self.last_msg_timestamp
+    }
+
+    pub fn set_last_msg_timestamp(&mut self, last_msg_timestamp: u64) {
+        self.last_msg_timestamp = last_msg_timestamp;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn connection_without_rate_fields_deserializes() {
+        let json = r#"{"clientId":"127.0.0.1@1","clientAddr":"127.0.0.1:5000","language":"JAVA","version":453}"#;
+        let connection: Connection = serde_json::from_str(json).unwrap();
+        assert_eq!(connection.get_client_id().as_str(), "127.0.0.1@1");
+        assert_eq!(connection.get_version(), 453);
+        assert_eq!(connection.get_msgs_per_minute(), 0);
+        assert_eq!(connection.get_last_update_timestamp(), 0);
+
+        let mut connection = connection;
+        connection.set_msgs_per_minute(120);
+        let json = serde_json::to_string(&connection).unwrap();
+        assert!(json.contains("\"msgsPerMinute\":120"));
+    }
 }
@@ -0,0 +1,64 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::protocol::body::set_message_request_mode_request_body::SetMessageRequestModeRequestBody;
+
+/// Body of a heartbeat response, carrying the message request mode the broker expects for
+/// each topic the heartbeating consumer groups subscribe to.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct HeartbeatResponseBody {
+    pub message_request_modes: Vec<SetMessageRequestModeRequestBody>,
+}
+
+#[cfg(test)]
+mod tests {
+    use cheetah_string::CheetahString;
+    use rocketmq_common::common::message::message_enum::MessageRequestMode;
+
+    use super::*;
+    use crate::protocol::RemotingDeserializable;
+    use crate::protocol::RemotingSerializable;
+
+    #[test]
+    fn heartbeat_response_body_round_trips() {
+        let body = HeartbeatResponseBody {
+            message_request_modes: vec![SetMessageRequestModeRequestBody {
+                topic: CheetahString::from_static_str("TopicA"),
+                consumer_group: CheetahString::from_static_str("GroupA"),
+                mode: MessageRequestMode::Pop,
+                pop_share_queue_num: 2,
+            }],
+        };
+        let encoded = body.encode().unwrap();
+        let decoded = HeartbeatResponseBody::decode(encoded.as_slice()).unwrap();
+        assert_eq!(decoded.message_request_modes.len(), 1);
+        assert_eq!(
+            decoded.message_request_modes[0].mode,
+            MessageRequestMode::Pop
+        );
+        assert_eq!(decoded.message_request_modes[0].pop_share_queue_num, 2);
+    }
+
+    #[test]
+    fn heartbeat_response_body_defaults_missing_fields() {
+        let decoded = HeartbeatResponseBody::decode(b"{}").unwrap();
+        assert!(decoded.message_request_modes.is_empty());
+    }
+}
@@ -50,7 +50,7 @@ impl FilterAPI {
                 subscription_data.tags_set.insert(trimmed_tag.into());
                 subscription_data
                     .code_set
-                    .insert(JavaStringHasher::hash_str(tag));
+                    .insert(JavaStringHasher::hash_str(trimmed_tag));
             }
         }
 
@@ -112,6 +112,20 @@ mod tests {
         assert!(subscription_data.tags_set.contains("tag2"));
     }
 
+    #[test]
+    fn build_subscription_data_hashes_trimmed_tags() {
+        let topic = "test_topic".into();
+        let sub_string = "tag1 || tag2".into();
+        let subscription_data = FilterAPI::build_subscription_data(&topic, &sub_string).unwrap();
+
+        assert!(subscription_data
+            .code_set
+            .contains(&JavaStringHasher::hash_str("tag2")));
+        assert!(subscription_data
+            .code_set
+            .contains(&JavaStringHasher::hash_str("tag1")));
+    }
+
     #[test]
     fn build_subscription_data_with_empty_sub_string_creates_subscription_data_with_sub_all() {
         let topic = "test_topic".into();
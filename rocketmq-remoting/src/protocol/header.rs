@@ -40,7 +40,9 @@ pub mod get_max_offset_response_header;
 pub mod get_meta_data_response_header;
 pub mod get_min_offset_request_header;
 pub mod get_min_offset_response_header;
+pub mod get_producer_connection_list_request_header;
 pub mod get_topic_config_request_header;
+pub mod get_topic_disk_usage_request_header;
 pub mod get_topic_stats_info_request_header;
 pub mod get_topic_stats_request_header;
 pub mod heartbeat_request_header;
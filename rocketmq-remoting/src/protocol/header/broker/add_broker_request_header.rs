@@ -0,0 +1,53 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use cheetah_string::CheetahString;
+use rocketmq_macros::RequestHeaderCodec;
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, RequestHeaderCodec)]
+#[serde(rename_all = "camelCase")]
+pub struct AddBrokerRequestHeader {
+    /// Config file of the broker to add; the request body is used when absent.
+    pub config_path: Option<CheetahString>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::protocol::command_custom_header::CommandCustomHeader;
+    use crate::protocol::command_custom_header::FromMap;
+
+    #[test]
+    fn add_broker_request_header_map_round_trip() {
+        let header = AddBrokerRequestHeader {
+            config_path: Some(CheetahString::from_static_str("/conf/broker-a.toml")),
+        };
+        let map = header.to_map().unwrap();
+        assert_eq!(
+            map.get(&CheetahString::from_static_str("configPath")),
+            Some(&CheetahString::from_static_str("/conf/broker-a.toml"))
+        );
+        let decoded = <AddBrokerRequestHeader as FromMap>::from(&map).unwrap();
+        assert_eq!(decoded.config_path, header.config_path);
+
+        let decoded = <AddBrokerRequestHeader as FromMap>::from(&HashMap::new()).unwrap();
+        assert!(decoded.config_path.is_none());
+    }
+}
@@ -0,0 +1,66 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+use cheetah_string::CheetahString;
+use rocketmq_macros::RequestHeaderCodec;
+use serde::Deserialize;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, RequestHeaderCodec)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoveBrokerRequestHeader {
+    #[required]
+    pub broker_name: CheetahString,
+    #[required]
+    pub broker_cluster_name: CheetahString,
+    #[required]
+    pub broker_id: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::protocol::command_custom_header::FromMap;
+
+    #[test]
+    fn remove_broker_request_header_from_map() {
+        let mut map = HashMap::new();
+        map.insert(
+            CheetahString::from_static_str("brokerName"),
+            CheetahString::from_static_str("broker-a"),
+        );
+        map.insert(
+            CheetahString::from_static_str("brokerClusterName"),
+            CheetahString::from_static_str("DefaultCluster"),
+        );
+        map.insert(
+            CheetahString::from_static_str("brokerId"),
+            CheetahString::from_static_str("1"),
+        );
+        let header = <RemoveBrokerRequestHeader as FromMap>::from(&map).unwrap();
+        assert_eq!(header.broker_name, "broker-a");
+        assert_eq!(header.broker_cluster_name, "DefaultCluster");
+        assert_eq!(header.broker_id, 1);
+    }
+
+    #[test]
+    fn remove_broker_request_header_missing_field() {
+        let map = HashMap::new();
+        assert!(<RemoveBrokerRequestHeader as FromMap>::from(&map).is_err());
+    }
+}
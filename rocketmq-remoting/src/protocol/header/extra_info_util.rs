@@ -291,7 +291,8 @@ impl ExtraInfoUtil {
         string_builder.push_str(&order_count.to_string());
     }
 
-    /// Build message offset info
+    /// Build message offset info, the offsets of one queue are joined by `,` while the queues are
+    /// separated by `;`
     pub fn build_msg_offset_info(
         string_builder: &mut String,
         topic: &str,
@@ -308,10 +309,10 @@ impl ExtraInfoUtil {
         string_builder.push_str(MessageConst::KEY_SEPARATOR);
 
         for (i, offset) in msg_offsets.iter().enumerate() {
-            string_builder.push_str(&offset.to_string());
-            if i < msg_offsets.len() - 1 {
-                string_builder.push(';');
+            if i > 0 {
+                string_builder.push(',');
             }
+            string_builder.push_str(&offset.to_string());
         }
     }
 
@@ -831,7 +832,170 @@ mod tests {
     fn build_msg_offset_info_creates_correct_string() {
         let mut string_builder = String::new();
         ExtraInfoUtil::build_msg_offset_info(&mut string_builder, "topic", 7, &vec![100, 200, 300]);
-        assert_eq!(string_builder, "0 7 100;200;300");
+        assert_eq!(string_builder, "0 7 100,200,300");
+    }
+
+    #[test]
+    fn retry_marker_follows_the_retry_topic_version() {
+        let v1 = KeyBuilder::build_pop_retry_topic_v1("TopicA", "GID");
+        let v2 = KeyBuilder::build_pop_retry_topic_v2("TopicA", "GID");
+        assert_eq!(ExtraInfoUtil::get_retry_from_topic("TopicA"), NORMAL_TOPIC);
+        assert_eq!(ExtraInfoUtil::get_retry_from_topic(&v1), RETRY_TOPIC);
+        assert_eq!(ExtraInfoUtil::get_retry_from_topic(&v2), RETRY_TOPIC_V2);
+        assert_eq!(
+            ExtraInfoUtil::get_real_topic_with_retry("TopicA", "GID", RETRY_TOPIC_V2).unwrap(),
+            v2
+        );
+    }
+
+    #[test]
+    fn batch_pop_offset_infos_match_the_java_broker() {
+        let v1 = KeyBuilder::build_pop_retry_topic_v1("TopicA", "GID");
+        let v2 = KeyBuilder::build_pop_retry_topic_v2("TopicA", "GID");
+        let mut start_offset_info = String::new();
+        let mut msg_offset_info = String::new();
+        for (topic, queue_id, offsets) in [
+            ("TopicA", 0, vec![10, 11]),
+            (v1.as_str(), 1, vec![3]),
+            (v2.as_str(), 2, vec![7, 8, 9]),
+        ] {
+            ExtraInfoUtil::build_start_offset_info(
+                &mut start_offset_info,
+                topic,
+                queue_id,
+                offsets[0] as i64,
+            );
+            ExtraInfoUtil::build_msg_offset_info(&mut msg_offset_info, topic, queue_id, &offsets);
+        }
+        // as written by the Java broker for the same pop
+        assert_eq!(start_offset_info, "0 0 10;1 1 3;2 2 7");
+        assert_eq!(msg_offset_info, "0 0 10,11;1 1 3;2 2 7,8,9");
+
+        let start_offsets = ExtraInfoUtil::parse_start_offset_info(&start_offset_info).unwrap();
+        assert_eq!(
+            start_offsets,
+            HashMap::from([
+                ("0@0".to_string(), 10),
+                ("1@1".to_string(), 3),
+                ("2@2".to_string(), 7),
+            ])
+        );
+        let msg_offsets = ExtraInfoUtil::parse_msg_offset_info(&msg_offset_info).unwrap();
+        assert_eq!(
+            msg_offsets[&ExtraInfoUtil::get_start_offset_info_map_key(&v2, 2)],
+            vec![7, 8, 9]
+        );
+        assert_eq!(
+            msg_offsets[&ExtraInfoUtil::get_start_offset_info_map_key("TopicA", 0)],
+            vec![10, 11]
+        );
+        assert_eq!(msg_offsets.len(), 3);
+    }
+
+    #[test]
+    fn order_count_info_matches_the_java_broker() {
+        let mut order_count_info = String::new();
+        ExtraInfoUtil::build_queue_id_order_count_info(&mut order_count_info, "TopicA", 0, 2);
+        ExtraInfoUtil::build_queue_offset_order_count_info(
+            &mut order_count_info,
+            "TopicA",
+            0,
+            10,
+            2,
+        );
+        ExtraInfoUtil::build_queue_offset_order_count_info(
+            &mut order_count_info,
+            "TopicA",
+            0,
+            11,
+            0,
+        );
+        assert_eq!(order_count_info, "0 0 2;0 qo0%10 2;0 qo0%11 0");
+
+        let order_counts = ExtraInfoUtil::parse_order_count_info(&order_count_info).unwrap();
+        assert_eq!(
+            order_counts[&ExtraInfoUtil::get_start_offset_info_map_key("TopicA", 0)],
+            2
+        );
+        assert_eq!(
+            order_counts[&ExtraInfoUtil::get_queue_offset_map_key("TopicA", 0, 10)],
+            2
+        );
+        assert_eq!(
+            order_counts[&ExtraInfoUtil::get_queue_offset_map_key("TopicA", 0, 11)],
+            0
+        );
+    }
+
+    #[test]
+    fn extra_info_from_the_java_broker_is_parsed() {
+        let extra_info = ExtraInfoUtil::split("100 1700000000000 60000 3 2 broker-a 1 102");
+        assert_eq!(
+            ExtraInfoUtil::get_ck_queue_offset(&extra_info).unwrap(),
+            100
+        );
+        assert_eq!(
+            ExtraInfoUtil::get_pop_time(&extra_info).unwrap(),
+            1700000000000
+        );
+        assert_eq!(
+            ExtraInfoUtil::get_invisible_time(&extra_info).unwrap(),
+            60000
+        );
+        assert_eq!(ExtraInfoUtil::get_revive_qid(&extra_info).unwrap(), 3);
+        assert_eq!(
+            ExtraInfoUtil::get_real_topic(&extra_info, "TopicA", "GID").unwrap(),
+            KeyBuilder::build_pop_retry_topic_v2("TopicA", "GID")
+        );
+        assert_eq!(
+            ExtraInfoUtil::get_broker_name(&extra_info).unwrap(),
+            "broker-a"
+        );
+        assert_eq!(ExtraInfoUtil::get_queue_id(&extra_info).unwrap(), 1);
+        assert_eq!(ExtraInfoUtil::get_queue_offset(&extra_info).unwrap(), 102);
+        assert!(!ExtraInfoUtil::is_order(&extra_info));
+
+        let built = ExtraInfoUtil::build_extra_info_with_offset(
+            100,
+            1700000000000,
+            60000,
+            3,
+            &KeyBuilder::build_pop_retry_topic_v2("TopicA", "GID"),
+            "broker-a",
+            1,
+            102,
+        );
+        assert_eq!(built, "100 1700000000000 60000 3 2 broker-a 1 102");
+        assert!(ExtraInfoUtil::is_order(&ExtraInfoUtil::split(
+            "100 1700000000000 60000 999 0 broker-a 1 102"
+        )));
+    }
+
+    #[test]
+    fn start_offset_key_takes_the_retry_marker_of_the_pop_ck() {
+        let key = ExtraInfoUtil::get_start_offset_info_map_key_with_pop_ck(
+            "TopicA",
+            Some("100 1700000000000 60000 3 1 broker-a 1"),
+            1,
+        )
+        .unwrap();
+        assert_eq!(key, "1@1");
+        assert!(ExtraInfoUtil::get_start_offset_info_map_key_with_pop_ck(
+            "TopicA",
+            Some("100 1"),
+            1
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn malformed_offset_infos_are_rejected() {
+        // the offsets of a queue must be joined by ',', ';' starts another queue
+        assert!(ExtraInfoUtil::parse_msg_offset_info("0 0 10;11").is_err());
+        assert!(ExtraInfoUtil::parse_msg_offset_info("0 0 10;0 0 11").is_err());
+        assert!(ExtraInfoUtil::parse_start_offset_info("0 0 x").is_err());
+        assert!(ExtraInfoUtil::parse_order_count_info("0 qo0%10 2;0 qo0%10 1").is_err());
+        assert!(ExtraInfoUtil::parse_msg_offset_info("").unwrap().is_empty());
     }
 
     #[test]
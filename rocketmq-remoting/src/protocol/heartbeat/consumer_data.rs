@@ -25,6 +25,7 @@ use serde::Serialize;
 use crate::protocol::heartbeat::consume_type::ConsumeType;
 use crate::protocol::heartbeat::message_model::MessageModel;
 use crate::protocol::heartbeat::subscription_data::SubscriptionData;
+use crate::protocol::subscription::group_retry_policy::GroupRetryPolicy;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Default)]
 #[serde(rename_all = "camelCase")]
@@ -35,6 +36,9 @@ pub struct ConsumerData {
     pub consume_from_where: ConsumeFromWhere,
     pub subscription_data_set: HashSet<SubscriptionData>,
     pub unit_mode: bool,
+    /// Retry policy the consumer asks the broker to apply to its group.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_retry_policy: Option<GroupRetryPolicy>,
 }
 
 impl Hash for ConsumerData {
@@ -61,6 +65,8 @@ mod tests {
     use crate::protocol::heartbeat::consume_type::ConsumeType;
     use crate::protocol::heartbeat::message_model::MessageModel;
     use crate::protocol::heartbeat::subscription_data::SubscriptionData;
+    use crate::protocol::subscription::exponential_retry_policy::ExponentialRetryPolicy;
+    use crate::protocol::subscription::group_retry_policy_type::GroupRetryPolicyType;
 
     #[test]
     fn consumer_data_default_values() {
@@ -88,6 +94,7 @@ mod tests {
             consume_from_where: ConsumeFromWhere::default(),
             subscription_data_set: subscription_data_set.clone(),
             unit_mode: false,
+            group_retry_policy: None,
         };
 
         let consumer_data2 = ConsumerData {
@@ -97,6 +104,7 @@ mod tests {
             consume_from_where: ConsumeFromWhere::default(),
             subscription_data_set,
             unit_mode: false,
+            group_retry_policy: None,
         };
 
         assert_eq!(consumer_data1, consumer_data2);
@@ -111,6 +119,7 @@ mod tests {
             consume_from_where: ConsumeFromWhere::default(),
             subscription_data_set: HashSet::new(),
             unit_mode: false,
+            group_retry_policy: None,
         };
 
         let consumer_data2 = ConsumerData {
@@ -120,6 +129,7 @@ mod tests {
             consume_from_where: ConsumeFromWhere::default(),
             subscription_data_set: HashSet::new(),
             unit_mode: false,
+            group_retry_policy: None,
         };
 
         assert_ne!(consumer_data1, consumer_data2);
@@ -138,6 +148,7 @@ mod tests {
             consume_from_where: ConsumeFromWhere::default(),
             subscription_data_set: HashSet::new(),
             unit_mode: false,
+            group_retry_policy: None,
         };
 
         let mut hasher = DefaultHasher::new();
@@ -150,4 +161,34 @@ mod tests {
 
         assert_eq!(hash1, hash2);
     }
+
+    #[test]
+    fn consumer_data_carries_group_retry_policy() {
+        let json = r#"{"groupName":"group1","consumeType":"CONSUME_PASSIVELY","messageModel":"CLUSTERING","consumeFromWhere":"CONSUME_FROM_LAST_OFFSET","subscriptionDataSet":[],"unitMode":false}"#;
+        let consumer_data: ConsumerData = serde_json::from_str(json).unwrap();
+        assert!(consumer_data.group_retry_policy.is_none());
+        assert!(!serde_json::to_string(&consumer_data)
+            .unwrap()
+            .contains("groupRetryPolicy"));
+
+        let mut group_retry_policy = GroupRetryPolicy::default();
+        group_retry_policy.set_type_(GroupRetryPolicyType::Exponential);
+        group_retry_policy
+            .set_exponential_retry_policy(Some(ExponentialRetryPolicy::new(1000, 60_000, 3)));
+        let consumer_data = ConsumerData {
+            group_retry_policy: Some(group_retry_policy),
+            ..consumer_data
+        };
+        let decoded: ConsumerData =
+            serde_json::from_str(&serde_json::to_string(&consumer_data).unwrap()).unwrap();
+        assert_eq!(decoded, consumer_data);
+        assert_eq!(
+            decoded
+                .group_retry_policy
+                .unwrap()
+                .get_retry_policy()
+                .next_delay_duration(1),
+            3000
+        );
+    }
 }
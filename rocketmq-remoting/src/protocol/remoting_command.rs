@@ -18,6 +18,7 @@
 use std::collections::HashMap;
 use std::fmt;
 use std::hint;
+use std::io::Read;
 use std::sync::atomic::AtomicI32;
 use std::sync::atomic::Ordering;
 use std::sync::Arc;
@@ -29,6 +30,7 @@ use bytes::BufMut;
 use bytes::Bytes;
 use bytes::BytesMut;
 use cheetah_string::CheetahString;
+use flate2::read::ZlibDecoder;
 use lazy_static::lazy_static;
 use rocketmq_common::common::mq_version::RocketMqVersion;
 use rocketmq_common::utils::serde_json_utils::SerdeJsonUtils;
@@ -37,11 +39,13 @@ use rocketmq_rust::ArcMut;
 use serde::Deserialize;
 use serde::Serialize;
 use tracing::error;
+use tracing::warn;
 
 use super::RemotingCommandType;
 use super::RemotingSerializable;
 use super::SerializeType;
 use crate::code::response_code::RemotingSysResponseCode;
+use crate::codec::remoting_command_codec::CodecLimits;
 use crate::protocol::command_custom_header::CommandCustomHeader;
 use crate::protocol::command_custom_header::FromMap;
 use crate::protocol::LanguageCode;
@@ -69,7 +73,7 @@ lazy_static! {
 
 fn set_cmd_version(cmd: &mut RemotingCommand) {
     INIT.call_once(|| {
-        let v = match std::env::var("REMOTING_VERSION_KEY") {
+        let v = match std::env::var(REMOTING_VERSION_KEY) {
             Ok(value) => value
                 .parse::<i32>()
                 .unwrap_or(i32::from(RocketMqVersion::V500)),
@@ -82,7 +86,7 @@ fn set_cmd_version(cmd: &mut RemotingCommand) {
 
     if config_version >= 0 {
         cmd.set_version_ref(config_version);
-    } else if let Ok(v) = std::env::var("rocketmq.remoting.version") {
+    } else if let Ok(v) = std::env::var(REMOTING_VERSION_KEY) {
         if let Ok(value) = v.parse::<i32>() {
             cmd.set_version_ref(value);
             *CONFIG_VERSION.write().unwrap() = value;
@@ -176,6 +180,9 @@ impl Default for RemotingCommand {
 impl RemotingCommand {
     pub(crate) const RPC_ONEWAY: i32 = 1;
     pub(crate) const RPC_TYPE: i32 = 0;
+    pub(crate) const RPC_BODY_COMPRESSED: i32 = 2;
+    pub(crate) const RPC_BODY_ZSTD_COMPRESSED: i32 = 3;
+    pub(crate) const RPC_ACCEPT_COMPRESSED_BODY: i32 = 4;
 }
 
 impl RemotingCommand {
@@ -204,32 +211,52 @@ impl RemotingCommand {
     }
 
     pub fn create_response_command_with_code(code: impl Into<i32>) -> Self {
-        Self::default().set_code(code).mark_response_type()
+        Self::response_command().set_code(code)
     }
 
     pub fn create_response_command_with_code_remark(
         code: impl Into<i32>,
         remark: impl Into<CheetahString>,
     ) -> Self {
-        Self::default()
+        Self::response_command()
             .set_code(code)
             .set_remark_option(Some(remark.into()))
-            .mark_response_type()
+    }
+
+    /// Builds the response sent back when a request processor fails with `error`. Errors that
+    /// carry their own response code keep it; anything else is reported as a system error.
+    pub fn create_response_command_from_error(error: &RocketmqError) -> Self {
+        match error.response_code_remark() {
+            Some((code, remark)) => Self::create_response_command_with_code_remark(code, remark),
+            None => Self::create_response_command_with_code_remark(
+                RemotingSysResponseCode::SystemError,
+                error.to_string(),
+            ),
+        }
     }
 
     pub fn create_response_command() -> Self {
-        Self::default()
-            .set_code(RemotingSysResponseCode::Success)
-            .mark_response_type()
+        Self::response_command().set_code(RemotingSysResponseCode::Success)
     }
 
     pub fn create_response_command_with_header(
         header: impl CommandCustomHeader + Sync + Send + 'static,
     ) -> Self {
-        Self::default()
+        Self::response_command()
             .set_code(RemotingSysResponseCode::Success)
             .set_command_custom_header(header)
-            .mark_response_type()
+    }
+
+    // responses carry the version of this side too, peers gate features on it
+    fn response_command() -> Self {
+        let mut command = Self::default().mark_response_type();
+        set_cmd_version(&mut command);
+        command
+    }
+
+    /// Whether the peer that sent this command runs `version` or a later one.
+    pub fn is_version_at_least(&self, version: RocketMqVersion) -> bool {
+        self.version >= i32::from(version)
     }
 
     pub fn set_command_custom_header<T>(mut self, command_custom_header: T) -> Self
@@ -382,6 +409,57 @@ impl RemotingCommand {
         self.flag |= mark;
     }
 
+    /// Flags the body as zlib compressed, so a server that accepts compressed bodies inflates it
+    /// before processing.
+    #[inline]
+    pub fn mark_body_compressed(mut self) -> Self {
+        let mark = 1 << Self::RPC_BODY_COMPRESSED;
+        self.flag |= mark;
+        self
+    }
+
+    /// Flags a request whose sender inflates zstd compressed response bodies, so the server may
+    /// compress the body of the response.
+    #[inline]
+    pub fn mark_accept_compressed_body_ref(&mut self) {
+        let mark = 1 << Self::RPC_ACCEPT_COMPRESSED_BODY;
+        self.flag |= mark;
+    }
+
+    /// Compresses the body with zstd at `level` and flags it, so the codec of the receiver
+    /// inflates it. The body is left as it is when compressing does not make it smaller, returns
+    /// whether it was compressed.
+    pub fn compress_body_zstd(&mut self, level: i32) -> bool {
+        let Some(compressed) = self
+            .body
+            .as_ref()
+            .and_then(|body| Self::zstd_compress_body(body, level))
+        else {
+            return false;
+        };
+        self.set_zstd_compressed_body(compressed);
+        true
+    }
+
+    /// Compresses `body` with zstd at `level`, `None` when that does not make it smaller.
+    pub fn zstd_compress_body(body: &[u8], level: i32) -> Option<Bytes> {
+        match zstd::bulk::compress(body, level) {
+            Ok(compressed) if compressed.len() < body.len() => Some(Bytes::from(compressed)),
+            Ok(_) => None,
+            Err(err) => {
+                warn!("compress body of {} bytes failed: {}", body.len(), err);
+                None
+            }
+        }
+    }
+
+    /// Sets a body [`zstd_compress_body`](Self::zstd_compress_body) compressed and flags it, so
+    /// the codec of the receiver inflates it.
+    pub fn set_zstd_compressed_body(&mut self, body: Bytes) {
+        self.body = Some(body);
+        self.flag |= 1 << Self::RPC_BODY_ZSTD_COMPRESSED;
+    }
+
     #[inline]
     pub fn get_serialize_type(&self) -> SerializeType {
         self.serialize_type
@@ -450,70 +528,62 @@ impl RemotingCommand {
                 }
                 Some(ext) => {
                     if let Some(val) = option {
-                        for (key, value) in &val {
-                            ext.insert(key.clone(), value.clone());
-                        }
+                        ext.extend(val);
                     }
                 }
             }
         }
     }
 
+    /// Encodes the frame length, the header length and the header into `dst`, leaving the body
+    /// to the caller. Both formats are serialized in place, without an intermediate buffer.
     pub fn fast_header_encode(&mut self, dst: &mut BytesMut) {
-        match self.serialize_type {
+        let begin_index = dst.len();
+        dst.put_i64(0);
+        let header_length = match self.serialize_type {
             SerializeType::JSON => {
                 self.make_custom_header_to_net();
-                let header = match serde_json::to_vec(self) {
-                    Ok(value) => Some(value),
-                    Err(e) => {
-                        error!("Failed to encode generic: {}", e);
-                        None
-                    }
-                };
-                let header_length = header.as_ref().map_or(0, |h| h.len()) as i32;
-                let body_length = self.body.as_ref().map_or(0, |b| b.len()) as i32;
-                let total_length = 4 + header_length + body_length;
-
-                dst.reserve((total_length + 4) as usize);
-                dst.put_i32(total_length);
-                let serialize_type =
-                    RemotingCommand::mark_serialize_type(header_length, SerializeType::JSON);
-                dst.put_i32(serialize_type);
-
-                if let Some(header_inner) = header {
-                    dst.put(header_inner.as_slice());
-                }
-            }
-            SerializeType::ROCKETMQ => {
-                let begin_index = dst.len();
-                dst.put_i64(0);
-                if let Some(header) = self.command_custom_header_ref() {
-                    if !header.support_fast_codec() {
-                        self.make_custom_header_to_net();
-                    }
+                if let Err(e) = serde_json::to_writer((&mut *dst).writer(), self) {
+                    error!("Failed to encode generic: {}", e);
+                    dst.truncate(begin_index + 8);
                 }
-                let header_size = RocketMQSerializable::rocketmq_protocol_encode(self, dst);
-                let body_length = self.body.as_ref().map_or(0, |b| b.len()) as i32;
-                let serialize_type = RemotingCommand::mark_serialize_type(
-                    header_size as i32,
-                    SerializeType::ROCKETMQ,
-                );
-                dst[begin_index..begin_index + 4]
-                    .copy_from_slice(&(header_size as i32 + body_length).to_be_bytes());
-                dst[begin_index + 4..begin_index + 8]
-                    .copy_from_slice(&serialize_type.to_be_bytes());
+                dst.len() - begin_index - 8
             }
-        }
+            SerializeType::ROCKETMQ => RocketMQSerializable::rocketmq_protocol_encode(self, dst),
+        };
+        let body_length = self.body.as_ref().map_or(0, |b| b.len());
+        let serialize_type =
+            RemotingCommand::mark_serialize_type(header_length as i32, self.serialize_type);
+        dst[begin_index..begin_index + 4]
+            .copy_from_slice(&((4 + header_length + body_length) as i32).to_be_bytes());
+        dst[begin_index + 4..begin_index + 8].copy_from_slice(&serialize_type.to_be_bytes());
     }
 
     pub fn decode(src: &mut BytesMut) -> rocketmq_error::RocketMQResult<Option<RemotingCommand>> {
+        Self::decode_with_limits(src, &CodecLimits::default())
+    }
+
+    /// Decodes one frame from `src`, refusing frames and headers larger than `limits` allow.
+    /// A compressed body is inflated when `limits` ask for it and its inflated size stays within
+    /// the frame limit; otherwise it is kept as is and the command stays flagged as compressed.
+    pub fn decode_with_limits(
+        src: &mut BytesMut,
+        limits: &CodecLimits,
+    ) -> rocketmq_error::RocketMQResult<Option<RemotingCommand>> {
         let read_to = src.len();
         if read_to < 4 {
             // Wait for more data when there are less than 4 bytes.
             return Ok(None);
         }
         //Read the total size as a big-endian i32 from the first 4 bytes.
-        let total_size = i32::from_be_bytes([src[0], src[1], src[2], src[3]]) as usize;
+        let total_size = i32::from_be_bytes([src[0], src[1], src[2], src[3]]);
+        if total_size < 0 || total_size as usize > limits.max_frame_length {
+            return Err(RocketmqError::RemotingCommandDecoderError(format!(
+                "Frame length {total_size} is out of range, the max frame length is {}",
+                limits.max_frame_length
+            )));
+        }
+        let total_size = total_size as usize;
 
         if read_to < total_size + 4 {
             // Wait for more data when the available data is less than the total size.
@@ -534,6 +604,12 @@ impl RemotingCommand {
                 "Header length {header_length} is greater than total size {total_size}"
             )));
         }
+        if header_length > limits.max_header_length {
+            return Err(RocketmqError::RemotingCommandDecoderError(format!(
+                "Header length {header_length} exceeds the max header length {}",
+                limits.max_header_length
+            )));
+        }
         let protocol_type = parse_serialize_type(ori_header_length)?;
         // Assume the header is of i32 type and directly get it from the data.
         let mut header_data = cmd_data.split_to(header_length);
@@ -545,10 +621,49 @@ impl RemotingCommand {
             if total_size - 4 > header_length {
                 cmd.set_body_mut_ref(cmd_data.split_to(total_size - 4 - header_length).freeze());
             }
+            if limits.decompress_body && (cmd.is_body_compressed() || cmd.is_body_zstd_compressed())
+            {
+                cmd.inflate_body(limits.max_frame_length);
+            }
         }
         Ok(cmd)
     }
 
+    fn inflate_body(&mut self, max_length: usize) {
+        let compressed_flags =
+            (1 << Self::RPC_BODY_COMPRESSED) | (1 << Self::RPC_BODY_ZSTD_COMPRESSED);
+        let Some(body) = self.body.as_ref() else {
+            self.flag &= !compressed_flags;
+            return;
+        };
+        let mut inflated = Vec::new();
+        let inflate_result = if self.is_body_zstd_compressed() {
+            zstd::stream::read::Decoder::new(body.as_ref()).and_then(|decoder| {
+                decoder
+                    .take(max_length as u64 + 1)
+                    .read_to_end(&mut inflated)
+            })
+        } else {
+            ZlibDecoder::new(body.as_ref())
+                .take(max_length as u64 + 1)
+                .read_to_end(&mut inflated)
+        };
+        match inflate_result {
+            Ok(length) if length <= max_length => {
+                self.body = Some(Bytes::from(inflated));
+                self.flag &= !compressed_flags;
+            }
+            Ok(_) => warn!(
+                "compressed body of request {} inflates beyond {} bytes",
+                self.opaque, max_length
+            ),
+            Err(err) => warn!(
+                "compressed body of request {} can not be inflated: {}",
+                self.opaque, err
+            ),
+        }
+    }
+
     pub fn header_decode(
         src: &mut BytesMut,
         header_length: usize,
@@ -688,6 +803,24 @@ impl RemotingCommand {
         (self.flag & bits) == bits
     }
 
+    #[inline]
+    pub fn is_body_compressed(&self) -> bool {
+        let bits = 1 << Self::RPC_BODY_COMPRESSED;
+        (self.flag & bits) == bits
+    }
+
+    #[inline]
+    pub fn is_body_zstd_compressed(&self) -> bool {
+        let bits = 1 << Self::RPC_BODY_ZSTD_COMPRESSED;
+        (self.flag & bits) == bits
+    }
+
+    #[inline]
+    pub fn accepts_compressed_body(&self) -> bool {
+        let bits = 1 << Self::RPC_ACCEPT_COMPRESSED_BODY;
+        (self.flag & bits) == bits
+    }
+
     pub fn get_type(&self) -> RemotingCommandType {
         if self.is_response_type() {
             RemotingCommandType::RESPONSE
@@ -842,7 +975,11 @@ impl AsMut<RemotingCommand> for RemotingCommand {
 
 #[cfg(test)]
 mod tests {
+    use rocketmq_error::ResponseErr;
+
     use super::*;
+    use crate::code::request_code::RequestCode;
+    use crate::protocol::header::client_request_header::GetRouteInfoRequestHeader;
 
     #[test]
     fn test_remoting_command() {
@@ -874,4 +1011,62 @@ mod tests {
         println!("i={}", RemotingCommand::default().opaque);
         println!("i={}", RemotingCommand::default().opaque);
     }
+
+    #[test]
+    fn response_from_error_uses_carried_code() {
+        let error = RocketmqError::ResponseError(ResponseErr::new(
+            crate::code::response_code::ResponseCode::TopicNotExist,
+            "topic not exist",
+        ));
+        let response = RemotingCommand::create_response_command_from_error(&error);
+        assert_eq!(response.code(), 17);
+        assert_eq!(response.remark().unwrap().as_str(), "topic not exist");
+        assert!(response.is_response_type());
+    }
+
+    #[test]
+    fn response_from_plain_error_is_system_error() {
+        let error = RocketmqError::DeserializeHeaderError("missing field topic".to_string());
+        let response = RemotingCommand::create_response_command_from_error(&error);
+        assert_eq!(response.code(), RemotingSysResponseCode::SystemError as i32);
+        assert_eq!(response.remark().unwrap().as_str(), "missing field topic");
+    }
+
+    fn encode_and_decode(serialize_type: SerializeType) -> RemotingCommand {
+        let mut ext_fields = HashMap::new();
+        ext_fields.insert(CheetahString::from("traceOn"), CheetahString::from("true"));
+        let mut command = RemotingCommand::create_request_command(
+            RequestCode::GetRouteinfoByTopic,
+            GetRouteInfoRequestHeader::new("RoundTripTopic", Some(true)),
+        )
+        .set_ext_fields(ext_fields)
+        .set_remark("remark")
+        .set_body(Bytes::from_static(b"body"))
+        .set_serialize_type(serialize_type);
+
+        let mut frame = BytesMut::new();
+        command.fast_header_encode(&mut frame);
+        frame.put(command.take_body().unwrap());
+        let decoded = RemotingCommand::decode(&mut frame).unwrap().unwrap();
+        assert!(frame.is_empty());
+        decoded
+    }
+
+    #[test]
+    fn fast_header_encode_round_trips_both_formats() {
+        for serialize_type in [SerializeType::JSON, SerializeType::ROCKETMQ] {
+            let decoded = encode_and_decode(serialize_type);
+            assert_eq!(decoded.code(), RequestCode::GetRouteinfoByTopic as i32);
+            assert_eq!(decoded.remark().unwrap().as_str(), "remark");
+            assert_eq!(decoded.body().as_deref(), Some(&b"body"[..]));
+            let ext_fields = decoded.ext_fields().unwrap();
+            assert_eq!(ext_fields.get("topic").unwrap().as_str(), "RoundTripTopic");
+            assert_eq!(ext_fields.get("traceOn").unwrap().as_str(), "true");
+            let header = decoded
+                .decode_command_custom_header::<GetRouteInfoRequestHeader>()
+                .unwrap();
+            assert_eq!(header.topic.as_str(), "RoundTripTopic");
+            assert_eq!(header.accept_standard_json_only, Some(true));
+        }
+    }
 }
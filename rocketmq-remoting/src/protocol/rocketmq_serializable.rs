@@ -72,6 +72,10 @@ impl RocketMQSerializable {
         Ok(Some(CheetahString::from_bytes(bytes)))
     }
 
+    /// Writes the header of `cmd` to `buf` and returns the number of bytes written.
+    ///
+    /// Custom headers are written straight from the typed header instead of being merged into the
+    /// ext fields first; fields of the header win over ext fields of the same name on decode.
     pub fn rocketmq_protocol_encode(cmd: &mut RemotingCommand, buf: &mut BytesMut) -> usize {
         let begin_index = buf.len();
         buf.put_u16(cmd.code() as u16);
@@ -80,24 +84,21 @@ impl RocketMQSerializable {
         buf.put_i32(cmd.opaque());
         buf.put_i32(cmd.flag());
         if let Some(remark) = cmd.remark() {
-            Self::write_str(buf, true, remark.as_str());
+            Self::write_str(buf, false, remark.as_str());
         } else {
             buf.put_i32(0);
         }
         let map_len_index = buf.len();
         buf.put_i32(0);
-        let header = cmd.command_custom_header_mut().unwrap();
-        if header.support_fast_codec() {
-            header.encode_fast(buf);
-        }
         if let Some(ext_fields) = cmd.ext_fields() {
-            ext_fields.iter().for_each(|(k, v)| {
-                if k.is_empty() || v.is_empty() {
-                    return;
-                }
-                Self::write_str(buf, true, k.as_str());
-                Self::write_str(buf, true, v.as_str());
-            });
+            Self::write_fields(buf, ext_fields);
+        }
+        if let Some(header) = cmd.command_custom_header_mut() {
+            if header.support_fast_codec() {
+                header.encode_fast(buf);
+            } else if let Some(fields) = header.to_map() {
+                Self::write_fields(buf, &fields);
+            }
         }
         let current_length = buf.len();
         buf[map_len_index..map_len_index + 4]
@@ -105,6 +106,16 @@ impl RocketMQSerializable {
         buf.len() - begin_index
     }
 
+    fn write_fields(buf: &mut BytesMut, fields: &HashMap<CheetahString, CheetahString>) {
+        for (key, value) in fields {
+            if key.is_empty() || value.is_empty() {
+                continue;
+            }
+            Self::write_str(buf, true, key.as_str());
+            Self::write_str(buf, false, value.as_str());
+        }
+    }
+
     pub fn rocket_mq_protocol_encode_bytes(cmd: &RemotingCommand) -> Bytes {
         let remark_bytes = cmd.remark().map(|remark| remark.as_bytes().to_vec());
         let remark_len = remark_bytes.as_ref().map_or(0, |v| v.len());
@@ -211,7 +222,9 @@ impl RocketMQSerializable {
     ) -> rocketmq_error::RocketMQResult<RemotingCommand> {
         let cmd = RemotingCommand::default()
             .set_code(header_buffer.get_i16())
-            .set_language(LanguageCode::value_of(header_buffer.get_u8()).unwrap())
+            .set_language(
+                LanguageCode::value_of(header_buffer.get_u8()).unwrap_or(LanguageCode::OTHER),
+            )
             .set_version(header_buffer.get_i16() as i32)
             .set_opaque(header_buffer.get_i32())
             .set_flag(header_buffer.get_i32());
@@ -236,7 +249,8 @@ impl RocketMQSerializable {
         buffer: &mut BytesMut,
         len: usize,
     ) -> rocketmq_error::RocketMQResult<HashMap<CheetahString, CheetahString>> {
-        let mut map = HashMap::new();
+        // Every entry takes at least 8 bytes: two length prefixes and two non-empty strings.
+        let mut map = HashMap::with_capacity(len / 16);
         let end_index = buffer.len() - len;
 
         while buffer.remaining() > end_index {
@@ -19,7 +19,8 @@ use serde::Serialize;
 
 use crate::protocol::subscription::retry_policy::RetryPolicy;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct CustomizedRetryPolicy {
     next: Vec<i64>,
 }
@@ -21,7 +21,8 @@ use serde::Serialize;
 
 use crate::protocol::subscription::retry_policy::RetryPolicy;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ExponentialRetryPolicy {
     initial: u64,
     max: u64,
@@ -22,12 +22,14 @@ use crate::protocol::subscription::exponential_retry_policy::ExponentialRetryPol
 use crate::protocol::subscription::group_retry_policy_type::GroupRetryPolicyType;
 use crate::protocol::subscription::retry_policy::RetryPolicy;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
 pub struct GroupRetryPolicy {
+    #[serde(rename = "type")]
     type_: GroupRetryPolicyType,
     exponential_retry_policy: Option<ExponentialRetryPolicy>,
     customized_retry_policy: Option<CustomizedRetryPolicy>,
+    #[serde(skip)]
     default_retry_policy: CustomizedRetryPolicy,
 }
 
@@ -26,7 +26,7 @@ use crate::protocol::subscription::group_retry_policy::GroupRetryPolicy;
 use crate::protocol::subscription::simple_subscription_data::SimpleSubscriptionData;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase", default)]
 pub struct SubscriptionGroupConfig {
     group_name: CheetahString,
 
@@ -251,6 +251,7 @@ impl SubscriptionGroupConfig {
 #[cfg(test)]
 mod subscription_group_config_tests {
     use super::*;
+    use crate::protocol::subscription::group_retry_policy_type::GroupRetryPolicyType;
     //use crate::protocol::subscription::group_retry_policy::RetryPolicy;
 
     #[test]
@@ -314,4 +315,42 @@ mod subscription_group_config_tests {
             &HashMap::from([("key".into(), "value".into())])
         );
     }
+
+    #[test]
+    fn deserialize_partial_config_sent_by_admin_tools() {
+        let json = r#"{
+            "groupName": "group_a",
+            "consumeTimeoutMinute": 30,
+            "groupRetryPolicy": {
+                "type": "EXPONENTIAL",
+                "exponentialRetryPolicy": {"initial": 1000, "max": 60000, "multiplier": 2}
+            },
+            "attributes": {"key": "value"}
+        }"#;
+        let config: SubscriptionGroupConfig = serde_json::from_str(json).unwrap();
+
+        assert_eq!(config.group_name(), "group_a");
+        assert_eq!(config.consume_timeout_minute(), 30);
+        assert_eq!(config.retry_max_times(), 16);
+        assert!(config.consume_enable());
+        assert_eq!(
+            config.group_retry_policy().type_(),
+            GroupRetryPolicyType::Exponential
+        );
+        assert_eq!(
+            config
+                .group_retry_policy()
+                .get_retry_policy()
+                .next_delay_duration(0),
+            1000
+        );
+        assert_eq!(config.attributes().get("key").unwrap(), "value");
+    }
+
+    #[test]
+    fn serialize_group_retry_policy_type_as_type() {
+        let json = serde_json::to_string(&SubscriptionGroupConfig::default()).unwrap();
+        assert!(json.contains(r#""groupRetryPolicy":{"type":"CUSTOMIZED""#));
+        assert!(!json.contains("defaultRetryPolicy"));
+    }
 }
@@ -21,11 +21,13 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use rocketmq_common::common::server::config::ServerConfig;
+use rocketmq_common::utils::network_util::NetworkUtil;
 use rocketmq_error::RocketmqError;
 use rocketmq_rust::wait_for_signal;
 use rocketmq_rust::ArcMut;
 use tokio::net::TcpListener;
 use tokio::net::TcpStream;
+use tokio::runtime::Handle;
 use tokio::sync::broadcast;
 use tokio::sync::mpsc;
 use tokio::sync::Semaphore;
@@ -34,12 +36,17 @@ use tracing::error;
 use tracing::info;
 use tracing::warn;
 
+use crate::auth::authentication_provider::AuthenticationProvider;
+use crate::auth::authentication_rpc_hook::AuthenticationRpcHook;
 use crate::base::channel_event_listener::ChannelEventListener;
 use crate::base::connection_net_event::ConnectionNetEvent;
 use crate::base::response_future::ResponseFuture;
 use crate::base::tokio_event::TokioEvent;
+use crate::code::response_code::RemotingSysResponseCode;
 use crate::code::response_code::ResponseCode;
+use crate::codec::remoting_command_codec::CodecLimits;
 use crate::connection::Connection;
+use crate::connection::WriteBufferWaterMark;
 use crate::net::channel::Channel;
 use crate::net::channel::ChannelInner;
 use crate::protocol::remoting_command::RemotingCommand;
@@ -146,6 +153,32 @@ impl<RP: RequestProcessor + Sync + 'static> ConnectionHandler<RP> {
             }
             let opaque = cmd.opaque();
             let oneway_rpc = cmd.is_oneway_rpc();
+            // the codec inflates the compressed bodies it is allowed to
+            if cmd.is_body_compressed() || cmd.is_body_zstd_compressed() {
+                warn!(
+                    "reject request {} from {}, its body is still compressed",
+                    cmd.code(),
+                    self.channel_inner.1.remote_address()
+                );
+                if oneway_rpc {
+                    continue;
+                }
+                let response = RemotingCommand::create_response_command_with_code_remark(
+                    RemotingSysResponseCode::SystemError,
+                    "the compressed request body was not decompressed, it is invalid, inflates \
+                     beyond the max frame length or decompression is disabled",
+                );
+                if let Err(err) = self
+                    .channel_inner
+                    .0
+                    .connection
+                    .send_command(response.set_opaque(opaque))
+                    .await
+                {
+                    error!("send response failed: {}", err);
+                }
+                continue;
+            }
             //before handle request hooks
 
             let exception = self
@@ -164,9 +197,10 @@ impl<RP: RequestProcessor + Sync + 'static> ConnectionHandler<RP> {
                 tokio::select! {
                     result = self.request_processor.process_request(channel,ctx,cmd) =>  match result{
                         Ok(value) => value,
-                        Err(_err) => Some(RemotingCommand::create_response_command_with_code(
-                                        ResponseCode::SystemError,
-                                    )),
+                        Err(err) => {
+                            warn!("process request failed: {}", err);
+                            Some(RemotingCommand::create_response_command_from_error(&err))
+                        }
                     },
                 }
             };
@@ -294,34 +328,49 @@ struct ConnectionListener<RP> {
     rpc_hooks: Arc<Vec<Box<dyn RPCHook>>>,
 
     channel_event_listener: Option<Arc<dyn ChannelEventListener>>,
+
+    /// Bounds inbound frames of every accepted connection.
+    codec_limits: CodecLimits,
+
+    /// Bounds the outbound bytes every accepted connection may have pending.
+    write_buffer_water_mark: WriteBufferWaterMark,
+
+    /// Runtime the per-connection handlers are spawned on. `None` keeps them on the runtime
+    /// that runs the acceptor.
+    connection_runtime: Option<Handle>,
 }
 
 impl<RP: RequestProcessor + Sync + 'static + Clone> ConnectionListener<RP> {
     async fn run(&mut self) -> anyhow::Result<()> {
         info!("Prepare accepting connection");
         let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<TokioEvent>();
-        let listener = self.channel_event_listener.take().unwrap();
-
-        tokio::spawn(async move {
-            loop {
-                if let Some(event) = rx.recv().await {
-                    info!("Accepting connection event: {:?}", event);
-                    let addr = event.remote_addr();
-                    match event.type_() {
-                        ConnectionNetEvent::CONNECTED(_) => {
-                            listener.on_channel_connect(addr.to_string().as_str(), event.channel());
-                        }
-                        ConnectionNetEvent::DISCONNECTED => {
-                            listener.on_channel_close(addr.to_string().as_str(), event.channel());
-                        }
-                        ConnectionNetEvent::EXCEPTION => {
-                            listener
-                                .on_channel_exception(addr.to_string().as_str(), event.channel());
+        // connection events are only tracked for a listener that wants them
+        if let Some(listener) = self.channel_event_listener.take() {
+            tokio::spawn(async move {
+                loop {
+                    if let Some(event) = rx.recv().await {
+                        info!("Accepting connection event: {:?}", event);
+                        let addr = event.remote_addr();
+                        match event.type_() {
+                            ConnectionNetEvent::CONNECTED(_) => {
+                                listener
+                                    .on_channel_connect(addr.to_string().as_str(), event.channel());
+                            }
+                            ConnectionNetEvent::DISCONNECTED => {
+                                listener
+                                    .on_channel_close(addr.to_string().as_str(), event.channel());
+                            }
+                            ConnectionNetEvent::EXCEPTION => {
+                                listener.on_channel_exception(
+                                    addr.to_string().as_str(),
+                                    event.channel(),
+                                );
+                            }
                         }
                     }
                 }
-            }
-        });
+            });
+        }
 
         loop {
             let permit = self
@@ -340,7 +389,8 @@ impl<RP: RequestProcessor + Sync + 'static + Clone> ConnectionListener<RP> {
             let local_addr = socket.local_addr()?;
             let response_table = ArcMut::new(HashMap::with_capacity(128));
             let channel_inner = ArcMut::new(ChannelInner::new(
-                Connection::new(socket),
+                Connection::with_codec_limits(socket, self.codec_limits)
+                    .with_write_buffer_water_mark(self.write_buffer_water_mark),
                 response_table.clone(),
             ));
             //create per connection handler state
@@ -364,7 +414,7 @@ impl<RP: RequestProcessor + Sync + 'static + Clone> ConnectionListener<RP> {
                 response_table,
             };
             let sender = tx.clone();
-            tokio::spawn(async move {
+            let connection = async move {
                 if let Err(err) = handler.handle().await {
                     error!(cause = ?err, "connection error");
                 }
@@ -382,7 +432,11 @@ impl<RP: RequestProcessor + Sync + 'static + Clone> ConnectionListener<RP> {
                 }*/
                 drop(permit);
                 drop(handler);
-            });
+            };
+            match self.connection_runtime {
+                Some(ref runtime) => runtime.spawn(connection),
+                None => tokio::spawn(connection),
+            };
         }
     }
 
@@ -414,6 +468,8 @@ impl<RP: RequestProcessor + Sync + 'static + Clone> ConnectionListener<RP> {
 
 pub struct RocketMQServer<RP> {
     config: Arc<ServerConfig>,
+    connection_runtime: Option<Handle>,
+    authentication_provider: Option<Arc<dyn AuthenticationProvider>>,
     _phantom_data: std::marker::PhantomData<RP>,
 }
 
@@ -421,9 +477,27 @@ impl<RP> RocketMQServer<RP> {
     pub fn new(config: Arc<ServerConfig>) -> Self {
         Self {
             config,
+            connection_runtime: None,
+            authentication_provider: None,
             _phantom_data: std::marker::PhantomData,
         }
     }
+
+    /// Serves accepted connections, and with them request processing, on `runtime` while the
+    /// accept loop stays on the runtime that runs the server.
+    pub fn with_connection_runtime(mut self, runtime: Handle) -> Self {
+        self.connection_runtime = Some(runtime);
+        self
+    }
+
+    /// Authenticates every request with `authentication_provider` before it is processed.
+    pub fn with_authentication_provider(
+        mut self,
+        authentication_provider: Arc<dyn AuthenticationProvider>,
+    ) -> Self {
+        self.authentication_provider = Some(authentication_provider);
+        self
+    }
 }
 
 impl<RP: RequestProcessor + Sync + 'static + Clone> RocketMQServer<RP> {
@@ -432,24 +506,40 @@ impl<RP: RequestProcessor + Sync + 'static + Clone> RocketMQServer<RP> {
         request_processor: RP,
         channel_event_listener: Option<Arc<dyn ChannelEventListener>>,
     ) {
-        let listener = TcpListener::bind(&format!(
-            "{}:{}",
-            self.config.bind_address, self.config.listen_port
-        ))
-        .await
-        .unwrap();
-        info!(
-            "Bind local address: {}",
-            format!("{}:{}", self.config.bind_address, self.config.listen_port)
-        );
+        self.run_until(request_processor, channel_event_listener, wait_for_signal())
+            .await
+    }
+
+    /// Like [`run`](Self::run), but stops serving once `shutdown` completes instead of on the
+    /// process signal, so the server can be stopped independently of the process.
+    pub async fn run_until(
+        &self,
+        request_processor: RP,
+        channel_event_listener: Option<Arc<dyn ChannelEventListener>>,
+        shutdown: impl Future,
+    ) {
+        let bind_address =
+            NetworkUtil::format_address(&self.config.bind_address, self.config.listen_port);
+        let listener = TcpListener::bind(&bind_address).await.unwrap();
+        info!("Bind local address: {}", bind_address);
         let (notify_conn_disconnect, _) = broadcast::channel::<SocketAddr>(100);
-        run(
+        let rpc_hooks = self
+            .authentication_provider
+            .iter()
+            .map(|provider| {
+                Box::new(AuthenticationRpcHook::new(provider.clone())) as Box<dyn RPCHook>
+            })
+            .collect();
+        serve(
             listener,
-            wait_for_signal(),
+            shutdown,
             request_processor,
             Some(notify_conn_disconnect),
-            vec![],
+            rpc_hooks,
             channel_event_listener,
+            CodecLimits::from(self.config.as_ref()),
+            WriteBufferWaterMark::from(self.config.as_ref()),
+            self.connection_runtime.clone(),
         )
         .await;
     }
@@ -462,6 +552,31 @@ pub async fn run<RP: RequestProcessor + Sync + 'static + Clone>(
     conn_disconnect_notify: Option<broadcast::Sender<SocketAddr>>,
     rpc_hooks: Vec<Box<dyn RPCHook>>,
     channel_event_listener: Option<Arc<dyn ChannelEventListener>>,
+) {
+    serve(
+        listener,
+        shutdown,
+        request_processor,
+        conn_disconnect_notify,
+        rpc_hooks,
+        channel_event_listener,
+        CodecLimits::default(),
+        WriteBufferWaterMark::default(),
+        None,
+    )
+    .await
+}
+
+async fn serve<RP: RequestProcessor + Sync + 'static + Clone>(
+    listener: TcpListener,
+    shutdown: impl Future,
+    request_processor: RP,
+    conn_disconnect_notify: Option<broadcast::Sender<SocketAddr>>,
+    rpc_hooks: Vec<Box<dyn RPCHook>>,
+    channel_event_listener: Option<Arc<dyn ChannelEventListener>>,
+    codec_limits: CodecLimits,
+    write_buffer_water_mark: WriteBufferWaterMark,
+    connection_runtime: Option<Handle>,
 ) {
     let (notify_shutdown, _) = broadcast::channel(1);
     let (shutdown_complete_tx, mut shutdown_complete_rx) = mpsc::channel(1);
@@ -475,6 +590,9 @@ pub async fn run<RP: RequestProcessor + Sync + 'static + Clone>(
         request_processor,
         rpc_hooks: Arc::new(rpc_hooks),
         channel_event_listener,
+        codec_limits,
+        write_buffer_water_mark,
+        connection_runtime,
     };
 
     tokio::select! {
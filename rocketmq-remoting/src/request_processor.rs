@@ -15,3 +15,4 @@
  * limitations under the License.
  */
 pub mod default_request_processor;
+pub mod request_processor_table;
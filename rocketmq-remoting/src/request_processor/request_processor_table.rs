@@ -0,0 +1,229 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+
+use crate::code::response_code::RemotingSysResponseCode;
+use crate::net::channel::Channel;
+use crate::protocol::remoting_command::RemotingCommand;
+use crate::runtime::connection_handler_context::ConnectionHandlerContext;
+use crate::runtime::processor::RequestProcessor;
+
+type BoxedProcessor = Arc<
+    dyn Fn(
+            Channel,
+            ConnectionHandlerContext,
+            RemotingCommand,
+        ) -> BoxFuture<'static, rocketmq_error::RocketMQResult<Option<RemotingCommand>>>
+        + Send
+        + Sync,
+>;
+
+/// Routes requests to the processor registered for their request code, so a server built on
+/// [`RocketMQServer`](crate::remoting_server::server::RocketMQServer) can serve custom request
+/// codes without writing its own dispatch.
+///
+/// Requests without a processor go to the default processor when one is registered, otherwise
+/// they are answered with `REQUEST_CODE_NOT_SUPPORTED`.
+///
+/// ```no_run
+/// use std::sync::Arc;
+///
+/// use rocketmq_common::common::server::config::ServerConfig;
+/// use rocketmq_remoting::net::channel::Channel;
+/// use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+/// use rocketmq_remoting::remoting_server::server::RocketMQServer;
+/// use rocketmq_remoting::request_processor::request_processor_table::RequestProcessorTable;
+/// use rocketmq_remoting::runtime::connection_handler_context::ConnectionHandlerContext;
+/// use rocketmq_remoting::runtime::processor::RequestProcessor;
+///
+/// #[derive(Clone)]
+/// struct EchoProcessor;
+///
+/// impl RequestProcessor for EchoProcessor {
+///     async fn process_request(
+///         &mut self,
+///         _channel: Channel,
+///         _ctx: ConnectionHandlerContext,
+///         request: RemotingCommand,
+///     ) -> rocketmq_error::RocketMQResult<Option<RemotingCommand>> {
+///         let mut response = RemotingCommand::create_response_command();
+///         if let Some(body) = request.body() {
+///             response = response.set_body(body.clone());
+///         }
+///         Ok(Some(response))
+///     }
+/// }
+///
+/// # async fn serve(config: Arc<ServerConfig>) {
+/// let mut table = RequestProcessorTable::new();
+/// table.register_processor(10001, EchoProcessor);
+/// RocketMQServer::new(config).run(table, None).await;
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct RequestProcessorTable {
+    processors: HashMap<i32, BoxedProcessor>,
+    default_processor: Option<BoxedProcessor>,
+}
+
+impl RequestProcessorTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Serves requests with `request_code` by `processor`, replacing the processor registered for
+    /// the code before. Every request is processed by its own clone of `processor`.
+    pub fn register_processor<P>(&mut self, request_code: impl Into<i32>, processor: P)
+    where
+        P: RequestProcessor + Clone + Sync + 'static,
+    {
+        self.processors
+            .insert(request_code.into(), boxed_processor(processor));
+    }
+
+    /// Serves requests whose code has no processor registered by `processor`.
+    pub fn register_default_processor<P>(&mut self, processor: P)
+    where
+        P: RequestProcessor + Clone + Sync + 'static,
+    {
+        self.default_processor = Some(boxed_processor(processor));
+    }
+
+    pub fn contains_processor(&self, request_code: i32) -> bool {
+        self.processors.contains_key(&request_code)
+    }
+}
+
+impl RequestProcessor for RequestProcessorTable {
+    async fn process_request(
+        &mut self,
+        channel: Channel,
+        ctx: ConnectionHandlerContext,
+        request: RemotingCommand,
+    ) -> rocketmq_error::RocketMQResult<Option<RemotingCommand>> {
+        match self
+            .processors
+            .get(&request.code())
+            .or(self.default_processor.as_ref())
+        {
+            Some(processor) => processor(channel, ctx, request).await,
+            None => Ok(Some(
+                RemotingCommand::create_response_command_with_code_remark(
+                    RemotingSysResponseCode::RequestCodeNotSupported,
+                    format!(" request type {} not supported", request.code()),
+                ),
+            )),
+        }
+    }
+}
+
+fn boxed_processor<P>(processor: P) -> BoxedProcessor
+where
+    P: RequestProcessor + Clone + Sync + 'static,
+{
+    Arc::new(move |channel, ctx, request| {
+        let mut processor = processor.clone();
+        Box::pin(async move { processor.process_request(channel, ctx, request).await })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use cheetah_string::CheetahString;
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::clients::Client;
+    use crate::code::response_code::ResponseCode;
+    use crate::remoting_server::server::run;
+    use crate::request_processor::default_request_processor::DefaultRemotingRequestProcessor;
+
+    #[derive(Clone)]
+    struct RemarkProcessor(&'static str);
+
+    impl RequestProcessor for RemarkProcessor {
+        async fn process_request(
+            &mut self,
+            _channel: Channel,
+            _ctx: ConnectionHandlerContext,
+            _request: RemotingCommand,
+        ) -> rocketmq_error::RocketMQResult<Option<RemotingCommand>> {
+            Ok(Some(
+                RemotingCommand::create_response_command()
+                    .set_remark(CheetahString::from_static_str(self.0)),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn requests_are_routed_by_request_code() {
+        let mut table = RequestProcessorTable::new();
+        table.register_processor(10001, RemarkProcessor("custom"));
+        assert!(table.contains_processor(10001));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(run(
+            listener,
+            std::future::pending::<()>(),
+            table,
+            None,
+            vec![],
+            None,
+        ));
+        let mut client = Client::connect(addr, DefaultRemotingRequestProcessor, None)
+            .await
+            .unwrap();
+
+        let response = client
+            .send_read(RemotingCommand::create_remoting_command(10001), 3000)
+            .await
+            .unwrap();
+        assert_eq!(response.code(), ResponseCode::Success as i32);
+        assert_eq!(response.remark().unwrap().as_str(), "custom");
+
+        let response = client
+            .send_read(RemotingCommand::create_remoting_command(10002), 3000)
+            .await
+            .unwrap();
+        assert_eq!(
+            response.code(),
+            RemotingSysResponseCode::RequestCodeNotSupported as i32
+        );
+
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        let tx = parking_lot::Mutex::new(Some(tx));
+        client
+            .invoke_with_callback(
+                RemotingCommand::create_remoting_command(10001),
+                3000,
+                move |response: Option<RemotingCommand>, _, _| {
+                    if let Some(tx) = tx.lock().take() {
+                        let _ = tx.send(response);
+                    }
+                },
+            )
+            .await
+            .unwrap();
+        let response = rx.await.unwrap().unwrap();
+        assert_eq!(response.remark().unwrap().as_str(), "custom");
+    }
+}
@@ -290,6 +290,9 @@ pub trait MessageStoreInner: Sync + 'static {
     /// Get the total number of the messages in the specified queue.
     fn get_message_total_in_queue(&self, topic: &CheetahString, queue_id: i32) -> i64;
 
+    /// Get the bytes the readable messages of the topic take in the commit log.
+    fn get_topic_disk_usage(&self, topic: &CheetahString) -> i64;
+
     /// Get the raw commit log data starting from the given offset.
     fn get_commit_log_data(&self, offset: i64) -> Option<SelectMappedBufferResult>;
 
@@ -25,6 +25,9 @@ use serde::Serializer;
 pub enum StoreType {
     #[default]
     LocalFile,
+    /// Keeps the consume queues and the broker metadata (consumer offsets, topic configs and
+    /// subscription groups) in RocksDB. Not available, the workspace does not depend on the
+    /// `rocksdb` crate, a broker configured with it refuses to start.
     RocksDB,
 }
 
@@ -29,6 +29,7 @@ use rocketmq_common::common::broker::broker_config::BrokerIdentity;
 use rocketmq_common::common::system_clock::SystemClock;
 use rocketmq_common::TimeUtils::get_current_millis;
 use tracing::error;
+use tracing::info;
 
 const FREQUENCY_OF_SAMPLING: u64 = 1000;
 const MAX_RECORDS_OF_SAMPLING: usize = 60 * 10;
@@ -178,10 +179,42 @@ impl StoreStatsService {
         }
     }
 
+    /// Records the time one put took, in milliseconds, into its distribute time bucket and
+    /// keeps the largest one seen.
     #[inline]
-    pub fn set_put_message_entire_time_max(&self, _value: u64) {}
+    pub fn set_put_message_entire_time_max(&self, value: u64) {
+        self.put_message_distribute_time[put_message_distribute_time_index(value)]
+            .fetch_add(1, Ordering::Relaxed);
+        self.put_message_entire_time_max
+            .fetch_max(value as usize, Ordering::Relaxed);
+    }
 
-    // Add more methods as needed for functionality
+    /// Keeps the largest time, in milliseconds, a single get message call took.
+    #[inline]
+    pub fn set_get_message_entire_time_max(&self, value: u64) {
+        self.get_message_entire_time_max
+            .fetch_max(value as usize, Ordering::Relaxed);
+    }
+
+    /// Moves the put distribute times counted since the last call into the snapshot reported by
+    /// the runtime info and logs them, so the table covers one print interval.
+    pub fn print_put_message_distribute_time(&self) {
+        let mut total_put = 0;
+        let mut distribute_time = String::new();
+        for (i, time) in self.put_message_distribute_time.iter().enumerate() {
+            let value = time.swap(0, Ordering::Relaxed);
+            self.last_put_message_distribute_time[i].store(value, Ordering::Relaxed);
+            total_put += value;
+            distribute_time.push_str(&format!(
+                "{}:{} ",
+                PUT_MESSAGE_ENTIRE_TIME_MAX_DESC[i], value
+            ));
+        }
+        info!(
+            "[PAGECACHERT] TotalPut {}, PutMessageDistributeTime {}",
+            total_put, distribute_time
+        );
+    }
 
     #[inline]
     pub fn get_runtime_info(&self) -> HashMap<String, String> {
@@ -475,6 +508,13 @@ impl StoreStatsService {
         result
     }
 
+    /// Records messages of `topic` that were stored without going through the put path, e.g.
+    /// replicated to a slave.
+    pub fn add_single_put_message_topic_total(&self, topic: &str, times: usize, size: usize) {
+        add_topic_total(&self.put_message_topic_times_total, topic, times);
+        add_topic_total(&self.put_message_topic_size_total, topic, size);
+    }
+
     #[inline]
     pub fn get_put_message_size_total(&self) -> u64 {
         let map = self.put_message_topic_size_total.read();
@@ -553,6 +593,36 @@ impl fmt::Display for StoreStatsService {
     }
 }
 
+fn put_message_distribute_time_index(value: u64) -> usize {
+    match value {
+        0 => 0,
+        1..=9 => 1,
+        10..=49 => 2,
+        50..=99 => 3,
+        100..=199 => 4,
+        200..=499 => 5,
+        500..=999 => 6,
+        1000..=1999 => 7,
+        2000..=2999 => 8,
+        3000..=3999 => 9,
+        4000..=4999 => 10,
+        5000..=9999 => 11,
+        _ => 12,
+    }
+}
+
+fn add_topic_total(table: &RwLock<HashMap<String, AtomicUsize>>, topic: &str, value: usize) {
+    if let Some(total) = table.read().get(topic) {
+        total.fetch_add(value, Ordering::Relaxed);
+        return;
+    }
+    table
+        .write()
+        .entry(topic.to_string())
+        .or_default()
+        .fetch_add(value, Ordering::Relaxed);
+}
+
 pub struct CallSnapshot {
     pub timestamp: u64,
     pub call_times_total: u64,
@@ -623,3 +693,41 @@ mod call_snapshot_tests {
         assert!(tps < 0.0);
     }
 }
+
+#[cfg(test)]
+mod store_stats_service_tests {
+    use super::*;
+
+    #[test]
+    fn single_put_message_topic_total_accumulates() {
+        let stats = StoreStatsService::new(None);
+        stats.add_single_put_message_topic_total("TopicA", 1, 100);
+        stats.add_single_put_message_topic_total("TopicA", 2, 50);
+        stats.add_single_put_message_topic_total("TopicB", 1, 10);
+        assert_eq!(stats.get_put_message_times_total(), 4);
+        assert_eq!(stats.get_put_message_size_total(), 160);
+    }
+
+    #[test]
+    fn put_message_distribute_time_rotates_on_print() {
+        let stats = StoreStatsService::new(None);
+        stats.set_put_message_entire_time_max(0);
+        stats.set_put_message_entire_time_max(5);
+        stats.set_put_message_entire_time_max(7);
+        stats.set_put_message_entire_time_max(12_000);
+        stats.set_get_message_entire_time_max(30);
+        stats.set_get_message_entire_time_max(8);
+
+        stats.print_put_message_distribute_time();
+        let info = stats.get_runtime_info();
+        let distribute_time = info.get("putMessageDistributeTime").unwrap();
+        assert!(distribute_time.starts_with("[<=0ms]:1, [0~10ms]:2, [10~50ms]:0, "));
+        assert!(distribute_time.ends_with("[10s~]:1, "));
+        assert_eq!(info.get("putMessageEntireTimeMax").unwrap(), "12000");
+        assert_eq!(info.get("getMessageEntireTimeMax").unwrap(), "30");
+        assert!(stats
+            .put_message_distribute_time
+            .iter()
+            .all(|time| time.load(Ordering::Relaxed) == 0));
+    }
+}
@@ -22,6 +22,7 @@ use cheetah_string::CheetahString;
 use lazy_static::lazy_static;
 use rocketmq_common::common::broker::broker_role::BrokerRole;
 use serde::Deserialize;
+use serde::Serialize;
 
 use crate::base::store_enum::StoreType;
 use crate::config::flush_disk_type::FlushDiskType;
@@ -31,11 +32,15 @@ lazy_static! {
     static ref USER_HOME: PathBuf = dirs::home_dir().unwrap();
 }
 
-#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct MessageStoreConfig {
     pub store_path_root_dir: CheetahString,
     pub store_path_commit_log: Option<CheetahString>,
+    #[serde(
+        rename = "storePathDLedgerCommitLog",
+        alias = "storePathDledgerCommitLog"
+    )]
     pub store_path_dledger_commit_log: Option<CheetahString>,
     pub store_path_epoch_file: Option<CheetahString>,
     pub store_path_broker_identity: Option<CheetahString>,
@@ -78,6 +83,8 @@ pub struct MessageStoreConfig {
     pub max_recovery_commit_log_files: usize,
     pub disk_space_warning_level_ratio: usize,
     pub disk_space_clean_forcibly_ratio: usize,
+    /// Serializes appends with a fair async mutex, the default. `false` opts into a spin lock
+    /// that can raise produce throughput on machines with many cores.
     pub use_reentrant_lock_when_put_message: bool,
     pub flush_commit_log_timed: bool,
     pub flush_interval_consume_queue: usize,
@@ -134,9 +141,18 @@ pub struct MessageStoreConfig {
     pub transient_store_pool_enable: bool,
     pub transient_store_pool_size: usize,
     pub fast_fail_if_no_buffer_in_store_pool: bool,
+    #[serde(
+        rename = "enableDLegerCommitLog",
+        alias = "enableDledgerCommitLog",
+        alias = "enableDlegerCommitLog"
+    )]
     pub enable_dledger_commit_log: bool,
+    #[serde(rename = "dLegerGroup", alias = "dledgerGroup")]
     pub dledger_group: Option<String>,
+    /// The members of the group as `n0-127.0.0.1:40911;n1-127.0.0.1:40912`.
+    #[serde(rename = "dLegerPeers", alias = "dledgerPeers")]
     pub dledger_peers: Option<String>,
+    #[serde(rename = "dLegerSelfId", alias = "dledgerSelfId")]
     pub dledger_self_id: Option<String>,
     pub preferred_leader_id: Option<String>,
     pub enable_batch_push: bool,
@@ -206,9 +222,14 @@ pub struct MessageStoreConfig {
     pub enable_rocksdb_log: bool,
     pub topic_queue_lock_num: usize,
     pub max_filter_message_size: i32,
-    pub enable_dleger_commit_log: bool,
     pub rocksdb_cq_double_write_enable: bool,
     pub read_uncommitted: bool,
+    /// Bytes the consume queue dispatch may lag behind the commit log before puts are refused,
+    /// 0 disables the protection. Puts resume once the lag drops below half of it.
+    pub max_dispatch_behind_bytes: i64,
+    /// Unflushed commit log bytes tolerated before puts are refused, 0 disables the protection.
+    /// Puts resume once the backlog drops below half of it.
+    pub max_flush_behind_bytes: i64,
 }
 
 impl Default for MessageStoreConfig {
@@ -267,7 +288,7 @@ impl Default for MessageStoreConfig {
             max_recovery_commit_log_files: 0,
             disk_space_warning_level_ratio: 0,
             disk_space_clean_forcibly_ratio: 0,
-            use_reentrant_lock_when_put_message: false,
+            use_reentrant_lock_when_put_message: true,
             flush_commit_log_timed: true,
             flush_interval_consume_queue: 1000,
             clean_resource_interval: 10000,
@@ -396,9 +417,10 @@ impl Default for MessageStoreConfig {
             enable_rocksdb_log: false,
             topic_queue_lock_num: 32,
             max_filter_message_size: 16000,
-            enable_dleger_commit_log: false,
             rocksdb_cq_double_write_enable: false,
             read_uncommitted: false,
+            max_dispatch_behind_bytes: 16 * 1024 * 1024 * 1024,
+            max_flush_behind_bytes: 4 * 1024 * 1024 * 1024,
         }
     }
 }
@@ -414,6 +436,23 @@ impl MessageStoreConfig {
         self.store_path_commit_log.clone().unwrap().to_string()
     }
 
+    /// Data directory of the raft replicated commit log, `dledger-<selfId>/data` under
+    /// `storePathDLedgerCommitLog`, which defaults to the store root.
+    pub fn get_store_path_dledger_commit_log(&self) -> String {
+        let base_dir = self
+            .store_path_dledger_commit_log
+            .as_ref()
+            .unwrap_or(&self.store_path_root_dir);
+        PathBuf::from(base_dir.as_str())
+            .join(format!(
+                "dledger-{}",
+                self.dledger_self_id.as_deref().unwrap_or_default()
+            ))
+            .join("data")
+            .to_string_lossy()
+            .to_string()
+    }
+
     pub fn is_enable_rocksdb_store(&self) -> bool {
         self.store_type == StoreType::RocksDB
     }
@@ -441,7 +480,7 @@ impl MessageStoreConfig {
                 .to_string(),
         );
         properties.insert(
-            "storePathDledgerCommitLog".to_string(),
+            "storePathDLedgerCommitLog".to_string(),
             self.store_path_dledger_commit_log
                 .clone()
                 .unwrap_or_default()
@@ -833,19 +872,19 @@ impl MessageStoreConfig {
             self.fast_fail_if_no_buffer_in_store_pool.to_string(),
         );
         properties.insert(
-            "enableDledgerCommitLog".to_string(),
+            "enableDLegerCommitLog".to_string(),
             self.enable_dledger_commit_log.to_string(),
         );
         properties.insert(
-            "dledgerGroup".to_string(),
+            "dLegerGroup".to_string(),
             self.dledger_group.clone().unwrap_or_default(),
         );
         properties.insert(
-            "dledgerPeers".to_string(),
+            "dLegerPeers".to_string(),
             self.dledger_peers.clone().unwrap_or_default(),
         );
         properties.insert(
-            "dledgerSelfId".to_string(),
+            "dLegerSelfId".to_string(),
             self.dledger_self_id.clone().unwrap_or_default(),
         );
         properties.insert(
@@ -1109,6 +1148,14 @@ impl MessageStoreConfig {
             "maxFilterMessageSize".into(),
             self.max_filter_message_size.to_string(),
         );
+        properties.insert(
+            "maxDispatchBehindBytes".into(),
+            self.max_dispatch_behind_bytes.to_string(),
+        );
+        properties.insert(
+            "maxFlushBehindBytes".into(),
+            self.max_flush_behind_bytes.to_string(),
+        );
         properties
             .into_iter()
             .map(|(k, v)| (k.into(), v.into()))
@@ -14,11 +14,15 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+use bytes::Buf;
+use bytes::BufMut;
+use bytes::Bytes;
+use bytes::BytesMut;
 
-const MIN_EXT_UNIT_SIZE: i16 = 2  // size, 32k max
+pub(crate) const MIN_EXT_UNIT_SIZE: i16 = 2  // size, 32k max
  + 8 * 2 // msg time + tagCode
   + 2; // bitMapSize
-const MAX_EXT_UNIT_SIZE: i16 = i16::MAX;
+pub(crate) const MAX_EXT_UNIT_SIZE: i16 = i16::MAX;
 
 #[derive(Clone, Default)]
 pub struct CqExtUnit {
@@ -61,4 +65,79 @@ impl CqExtUnit {
     pub fn filter_bit_map(&self) -> &Option<Vec<u8>> {
         &self.filter_bit_map
     }
+
+    /// Reads a unit from the start of `buffer`, returns `false` if no unit was written there.
+    pub(crate) fn read(&mut self, mut buffer: &[u8]) -> bool {
+        if buffer.len() < 2 {
+            return false;
+        }
+        let size = buffer.get_i16();
+        if size < MIN_EXT_UNIT_SIZE || buffer.len() + 2 < size as usize {
+            return false;
+        }
+        let tags_code = buffer.get_i64();
+        let msg_store_time = buffer.get_i64();
+        let bit_map_size = buffer.get_i16();
+        if bit_map_size < 0 || buffer.len() < bit_map_size as usize {
+            return false;
+        }
+        self.size = size;
+        self.tags_code = tags_code;
+        self.msg_store_time = msg_store_time;
+        self.bit_map_size = bit_map_size;
+        self.filter_bit_map = if bit_map_size > 0 {
+            Some(buffer[..bit_map_size as usize].to_vec())
+        } else {
+            None
+        };
+        true
+    }
+
+    /// Reads only the size of the unit at the start of `buffer`, a non positive size marks the
+    /// end of the written data.
+    pub(crate) fn read_size(buffer: &[u8]) -> i16 {
+        if buffer.len() < 2 {
+            return 0;
+        }
+        (&buffer[..2]).get_i16()
+    }
+
+    pub(crate) fn encode(&self) -> Bytes {
+        let mut buffer = BytesMut::with_capacity(self.size as usize);
+        buffer.put_i16(self.size);
+        buffer.put_i64(self.tags_code);
+        buffer.put_i64(self.msg_store_time);
+        buffer.put_i16(self.bit_map_size);
+        if let Some(filter_bit_map) = self.filter_bit_map.as_ref() {
+            buffer.put_slice(filter_bit_map);
+        }
+        buffer.freeze()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_and_read_round_trip() {
+        let unit = CqExtUnit::new(42, 1_700_000_000_000, Some(vec![1, 2, 3]));
+        let encoded = unit.encode();
+        assert_eq!(encoded.len(), MIN_EXT_UNIT_SIZE as usize + 3);
+
+        let mut read = CqExtUnit::default();
+        assert!(read.read(&encoded));
+        assert_eq!(read.size(), unit.size());
+        assert_eq!(read.tags_code(), 42);
+        assert_eq!(read.msg_store_time(), 1_700_000_000_000);
+        assert_eq!(read.filter_bit_map(), &Some(vec![1, 2, 3]));
+        assert_eq!(CqExtUnit::read_size(&encoded), unit.size());
+    }
+
+    #[test]
+    fn read_returns_false_on_blank_data() {
+        let mut unit = CqExtUnit::default();
+        assert!(!unit.read(&[0u8; 32]));
+        assert!(!unit.read(&[0u8; 1]));
+    }
 }
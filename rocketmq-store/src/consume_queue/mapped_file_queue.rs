@@ -288,6 +288,13 @@ impl MappedFileQueue {
                 }
             }
         }
+        if !will_remove_files.is_empty() {
+            self.mapped_files.write().retain(|file| {
+                !will_remove_files
+                    .iter()
+                    .any(|removed| Arc::ptr_eq(removed, file))
+            });
+        }
     }
 
     #[inline]
@@ -314,10 +321,7 @@ impl MappedFileQueue {
 
     #[inline]
     pub(crate) fn delete_expired_file(&mut self, files: Vec<Arc<DefaultMappedFile>>) {
-        let mut files = files;
-        let read_guard = self.mapped_files.read();
         if !files.is_empty() {
-            files.retain(|mf| read_guard.contains(mf));
             self.mapped_files.write().retain(|mf| !files.contains(mf));
         }
     }
@@ -0,0 +1,30 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! A raft replicated commit log in the spirit of RocketMQ's DLedger.
+//!
+//! Every commit log message is wrapped in a [`dledger_entry::DLedgerEntry`] and only becomes
+//! visible to the consume queues once a majority of the group stored it. The leader of the group
+//! acts as the master broker; a newly elected leader takes over without a controller.
+
+pub mod dledger_commit_log;
+pub mod dledger_config;
+pub mod dledger_entry;
+pub(crate) mod dledger_mmap_store;
+pub(crate) mod dledger_protocol;
+pub mod dledger_server;
+pub mod member_state;
@@ -0,0 +1,389 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+use bytes::Buf;
+use bytes::Bytes;
+use rocketmq_common::common::message::message_batch::MessageExtBatch;
+use rocketmq_common::common::message::message_ext_broker_inner::MessageExtBrokerInner;
+use rocketmq_common::common::sys_flag::message_sys_flag::MessageSysFlag;
+use rocketmq_common::utils::message_utils;
+use rocketmq_common::CRC32Utils::crc32;
+use rocketmq_common::MessageDecoder::create_crc32;
+use rocketmq_common::MessageUtils::build_batch_message_id;
+use rocketmq_rust::ArcMut;
+use tokio::sync::watch;
+use tracing::warn;
+
+use crate::base::dispatch_request::DispatchRequest;
+use crate::base::message_result::AppendMessageResult;
+use crate::base::message_status_enum::AppendMessageStatus;
+use crate::base::message_status_enum::PutMessageStatus;
+use crate::base::put_message_context::PutMessageContext;
+use crate::config::message_store_config::MessageStoreConfig;
+use crate::consume_queue::mapped_file_queue::MappedFileQueue;
+use crate::dledger::dledger_config::DLedgerConfig;
+use crate::dledger::dledger_entry::EntryHeader;
+use crate::dledger::dledger_entry::BODY_OFFSET;
+use crate::dledger::dledger_server::DLedgerServer;
+use crate::dledger::member_state::MemberRole;
+use crate::dledger::member_state::RoleState;
+use crate::log_file::commit_log;
+use crate::log_file::commit_log::CRC32_RESERVED_LEN;
+use crate::store_error::DLedgerError;
+use crate::store_error::DLedgerResult;
+
+/// A message appended to the raft log, waiting for the quorum to store it.
+pub struct DLedgerAppendResult {
+    pub append_result: AppendMessageResult,
+    pub index: i64,
+    pub term: i64,
+}
+
+/// The commit log of `enableDLegerCommitLog`, which appends every message as a raft entry.
+///
+/// A message keeps the commit log layout inside its entry, so its physical offset is the entry
+/// position plus [`BODY_OFFSET`]. The consume queues store that offset and read the message
+/// straight from the data files, like with the plain commit log. Messages only become visible
+/// to the consume queues once they are committed.
+pub struct DLedgerCommitLog {
+    server: DLedgerServer,
+    message_store_config: Arc<MessageStoreConfig>,
+}
+
+impl DLedgerCommitLog {
+    pub fn new(
+        message_store_config: Arc<MessageStoreConfig>,
+        mapped_file_queue: ArcMut<MappedFileQueue>,
+    ) -> DLedgerResult<Self> {
+        let config = DLedgerConfig::new(&message_store_config)?;
+        Ok(DLedgerCommitLog {
+            server: DLedgerServer::new(config, mapped_file_queue),
+            message_store_config,
+        })
+    }
+
+    pub fn server(&self) -> &DLedgerServer {
+        &self.server
+    }
+
+    pub fn recover(&self) {
+        self.server.recover();
+    }
+
+    pub fn start(&self) -> DLedgerResult<()> {
+        self.server.start()
+    }
+
+    pub fn shutdown(&self) {
+        self.server.shutdown();
+    }
+
+    pub fn subscribe_role(&self) -> watch::Receiver<RoleState> {
+        self.server.subscribe_role()
+    }
+
+    /// The commit log is readable up to here, everything behind is not committed yet.
+    pub fn get_confirm_offset(&self) -> i64 {
+        self.server.committed_end_pos()
+    }
+
+    /// Appends one encoded message, filling in its queue offset, physical offset and store
+    /// timestamp on the way.
+    pub fn append_message(
+        &self,
+        msg: &mut MessageExtBrokerInner,
+        message_num: i16,
+    ) -> Result<DLedgerAppendResult, PutMessageStatus> {
+        let term = self.leader_term()?;
+        let Some(mut encoded) = msg.encoded_buff.take() else {
+            return Err(PutMessageStatus::UnknownError);
+        };
+        let msg_len = encoded.len();
+        let queue_offset = match MessageSysFlag::get_transaction_value(msg.sys_flag()) {
+            // Prepared and Rollback message is not consumed, will not enter the consume queue
+            MessageSysFlag::TRANSACTION_PREPARED_TYPE
+            | MessageSysFlag::TRANSACTION_ROLLBACK_TYPE => 0,
+            _ => msg.queue_offset(),
+        };
+        let sys_flag = msg.sys_flag();
+        let store_timestamp = msg.store_timestamp();
+        let begin = Instant::now();
+        let appended = self
+            .server
+            .append_as_leader(term, &[msg_len], |_, pos| {
+                let message = &mut encoded[..msg_len];
+                self.fill_message(
+                    message,
+                    sys_flag,
+                    queue_offset,
+                    pos + BODY_OFFSET as i64,
+                    store_timestamp,
+                );
+                Bytes::copy_from_slice(message)
+            })
+            .map_err(put_message_status)?;
+        let (index, pos) = appended[0];
+        let wrote_offset = pos + BODY_OFFSET as i64;
+        let addr = msg.message_ext_inner.store_host;
+        let msg_id_supplier =
+            move || -> String { message_utils::build_message_id(addr, wrote_offset) };
+        Ok(DLedgerAppendResult {
+            append_result: AppendMessageResult {
+                status: AppendMessageStatus::PutOk,
+                wrote_offset,
+                wrote_bytes: msg_len as i32,
+                store_timestamp,
+                logics_offset: queue_offset,
+                msg_num: message_num as i32,
+                msg_id_supplier: Some(Arc::new(Box::new(msg_id_supplier))),
+                page_cache_rt: begin.elapsed().as_millis() as i64,
+                ..Default::default()
+            },
+            index,
+            term,
+        })
+    }
+
+    /// Appends every message of an encoded batch as an entry of its own.
+    pub fn append_messages(
+        &self,
+        msg_batch: &mut MessageExtBatch,
+        put_message_context: &mut PutMessageContext,
+    ) -> Result<DLedgerAppendResult, PutMessageStatus> {
+        let term = self.leader_term()?;
+        let Some(mut encoded) = msg_batch.encoded_buff.take() else {
+            return Err(PutMessageStatus::UnknownError);
+        };
+        let mut starts = Vec::new();
+        let mut body_sizes = Vec::new();
+        let mut start = 0usize;
+        while start + 4 <= encoded.len() {
+            let msg_len = (&encoded[start..start + 4]).get_i32();
+            if msg_len <= 0 || start + msg_len as usize > encoded.len() {
+                return Err(PutMessageStatus::MessageIllegal);
+            }
+            starts.push(start);
+            body_sizes.push(msg_len as usize);
+            start += msg_len as usize;
+        }
+        let inner = &msg_batch.message_ext_broker_inner;
+        let sys_flag = inner.sys_flag();
+        let queue_offset = inner.queue_offset();
+        let store_timestamp = inner.store_timestamp();
+        let begin = Instant::now();
+        let mut phy_pos = Vec::with_capacity(starts.len());
+        let appended = self
+            .server
+            .append_as_leader(term, &body_sizes, |i, pos| {
+                let message = &mut encoded[starts[i]..starts[i] + body_sizes[i]];
+                let phy_offset = pos + BODY_OFFSET as i64;
+                self.fill_message(
+                    message,
+                    sys_flag,
+                    queue_offset + i as i64,
+                    phy_offset,
+                    store_timestamp,
+                );
+                phy_pos.push(phy_offset);
+                Bytes::copy_from_slice(message)
+            })
+            .map_err(put_message_status)?;
+        let (index, _) = *appended.last().ok_or(PutMessageStatus::MessageIllegal)?;
+        let wrote_offset = phy_pos[0];
+        put_message_context.set_phy_pos(phy_pos.clone());
+        let addr = inner.store_host();
+        let store_host_length = if sys_flag & MessageSysFlag::STOREHOSTADDRESS_V6_FLAG == 0 {
+            4 + 4
+        } else {
+            16 + 4
+        };
+        let batch_size = phy_pos.len();
+        let msg_id_supplier = move || -> String {
+            build_batch_message_id(addr, store_host_length, batch_size, &phy_pos)
+        };
+        Ok(DLedgerAppendResult {
+            append_result: AppendMessageResult {
+                status: AppendMessageStatus::PutOk,
+                wrote_offset,
+                wrote_bytes: body_sizes.iter().sum::<usize>() as i32,
+                store_timestamp,
+                logics_offset: queue_offset,
+                msg_num: batch_size as i32,
+                msg_id_supplier: Some(Arc::new(Box::new(msg_id_supplier))),
+                page_cache_rt: begin.elapsed().as_millis() as i64,
+                ..Default::default()
+            },
+            index,
+            term,
+        })
+    }
+
+    /// Waits until the quorum stored the entries up to `index`.
+    pub async fn wait_committed(&self, index: i64, term: i64) -> PutMessageStatus {
+        let timeout = self.server.config().max_wait_ack;
+        match self.server.wait_committed(index, term, timeout).await {
+            Ok(()) => PutMessageStatus::PutOk,
+            // Do not answer FlushSlaveTimeout, clients ignore it and would not retry
+            Err(DLedgerError::WaitAckTimeout) => PutMessageStatus::OsPageCacheBusy,
+            Err(e) => {
+                warn!("wait for entry {} of term {} failed: {}", index, term, e);
+                PutMessageStatus::ServiceNotAvailable
+            }
+        }
+    }
+
+    fn leader_term(&self) -> Result<i64, PutMessageStatus> {
+        let role = self.server.role_state();
+        if role.role != MemberRole::Leader {
+            return Err(PutMessageStatus::ServiceNotAvailable);
+        }
+        Ok(role.term)
+    }
+
+    fn fill_message(
+        &self,
+        message: &mut [u8],
+        sys_flag: i32,
+        queue_offset: i64,
+        phy_offset: i64,
+        store_timestamp: i64,
+    ) {
+        // 1 TOTALSIZE, 2 MAGICCODE, 3 BODYCRC, 4 QUEUEID, 5 FLAG
+        let mut pos = 4 + 4 + 4 + 4 + 4;
+        message[pos..pos + 8].copy_from_slice(&queue_offset.to_be_bytes());
+        pos += 8;
+        message[pos..pos + 8].copy_from_slice(&phy_offset.to_be_bytes());
+        let born_host_length = if sys_flag & MessageSysFlag::BORNHOST_V6_FLAG == 0 {
+            4 + 4
+        } else {
+            16 + 4
+        };
+        // 8 SYSFLAG, 9 BORNTIMESTAMP, 10 BORNHOST
+        pos += 8 + 4 + 8 + born_host_length;
+        message[pos..pos + 8].copy_from_slice(&store_timestamp.to_be_bytes());
+        if self.message_store_config.enabled_append_prop_crc {
+            let check_size = message.len() - CRC32_RESERVED_LEN as usize;
+            let crc32 = crc32(&message[..check_size]);
+            create_crc32(&mut message[check_size..], crc32);
+        }
+    }
+}
+
+fn put_message_status(error: DLedgerError) -> PutMessageStatus {
+    match error {
+        DLedgerError::NotLeader => PutMessageStatus::ServiceNotAvailable,
+        error => {
+            warn!("append to the dledger commit log failed: {}", error);
+            PutMessageStatus::UnknownError
+        }
+    }
+}
+
+/// Checks the message carried by the entry at the start of `bytes`. The request's buffer size
+/// covers the whole entry, so reading on from there reaches the next entry. An entry without a
+/// message, like the one a new leader appends, yields a request of size 0 whose buffer size
+/// skips the entry; the filler at the end of a data file yields size 0 without a buffer size.
+pub fn check_message_and_return_size(
+    bytes: &mut Bytes,
+    check_crc: bool,
+    check_dup_info: bool,
+    read_body: bool,
+    message_store_config: &Arc<MessageStoreConfig>,
+    max_delay_level: i32,
+    delay_level_table: &BTreeMap<i32, i64>,
+) -> DispatchRequest {
+    let header = EntryHeader::decode(&bytes[..bytes.len().min(BODY_OFFSET)]);
+    let Some(header) = header else {
+        return DispatchRequest {
+            msg_size: -1,
+            success: false,
+            ..Default::default()
+        };
+    };
+    if header.is_blank() {
+        return DispatchRequest {
+            msg_size: 0,
+            success: true,
+            ..Default::default()
+        };
+    }
+    bytes.advance(BODY_OFFSET);
+    if header.body_size == 0 {
+        return DispatchRequest {
+            msg_size: 0,
+            success: true,
+            buffer_size: BODY_OFFSET as i32,
+            ..Default::default()
+        };
+    }
+    let mut dispatch_request = commit_log::check_message_and_return_size(
+        bytes,
+        check_crc,
+        check_dup_info,
+        read_body,
+        message_store_config,
+        max_delay_level,
+        delay_level_table,
+    );
+    if dispatch_request.success || dispatch_request.msg_size > 0 {
+        dispatch_request.buffer_size = dispatch_request.msg_size + BODY_OFFSET as i32;
+    }
+    dispatch_request
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BytesMut;
+
+    use super::*;
+    use crate::dledger::dledger_entry::encode_blank;
+    use crate::dledger::dledger_entry::DLedgerEntry;
+
+    fn check(bytes: &mut Bytes) -> DispatchRequest {
+        check_message_and_return_size(
+            bytes,
+            false,
+            false,
+            false,
+            &Arc::new(MessageStoreConfig::default()),
+            0,
+            &BTreeMap::new(),
+        )
+    }
+
+    #[test]
+    fn entries_without_message_are_skipped() {
+        let mut no_op = DLedgerEntry::new(0, 1, 0, Bytes::new()).encode();
+        let request = check(&mut no_op);
+        assert!(request.success);
+        assert_eq!(request.msg_size, 0);
+        assert_eq!(request.buffer_size, BODY_OFFSET as i32);
+
+        let mut blank = Bytes::copy_from_slice(&encode_blank(100));
+        let request = check(&mut blank);
+        assert!(request.success);
+        assert_eq!(request.msg_size, 0);
+        assert_eq!(request.buffer_size, -1);
+
+        let mut garbage = Bytes::from(BytesMut::zeroed(BODY_OFFSET));
+        assert!(!check(&mut garbage).success);
+    }
+}
@@ -0,0 +1,158 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::config::message_store_config::MessageStoreConfig;
+use crate::store_error::DLedgerError;
+use crate::store_error::DLedgerResult;
+
+/// Settings of one member of a raft group, taken from the `dLeger*` items of the store config.
+#[derive(Debug, Clone)]
+pub struct DLedgerConfig {
+    pub group: String,
+    pub self_id: String,
+    /// Member id to the address its raft endpoint listens on, including this member.
+    pub peers: BTreeMap<String, String>,
+    pub store_path: String,
+    pub mapped_file_size: usize,
+    /// How often the leader heartbeats the followers when there is nothing to replicate.
+    pub heartbeat_interval: Duration,
+    /// Missed heartbeats after which a follower starts an election.
+    pub max_heartbeat_leak: u32,
+    /// How long an append waits for a majority to acknowledge it.
+    pub max_wait_ack: Duration,
+    pub rpc_timeout: Duration,
+    /// Upper bound of the entries sent to a follower in one append request.
+    pub max_batch_bytes: usize,
+}
+
+impl DLedgerConfig {
+    pub fn new(message_store_config: &MessageStoreConfig) -> DLedgerResult<Self> {
+        let self_id = message_store_config
+            .dledger_self_id
+            .clone()
+            .filter(|id| !id.is_empty())
+            .ok_or_else(|| DLedgerError::Config("dLegerSelfId is not set".to_string()))?;
+        let peers = parse_peers(
+            message_store_config
+                .dledger_peers
+                .as_deref()
+                .unwrap_or_default(),
+        )?;
+        if !peers.contains_key(&self_id) {
+            return Err(DLedgerError::Config(format!(
+                "dLegerSelfId {self_id} is not one of the dLegerPeers"
+            )));
+        }
+        Ok(DLedgerConfig {
+            group: message_store_config
+                .dledger_group
+                .clone()
+                .unwrap_or_default(),
+            self_id,
+            peers,
+            store_path: message_store_config.get_store_path_dledger_commit_log(),
+            mapped_file_size: message_store_config.mapped_file_size_commit_log,
+            heartbeat_interval: Duration::from_millis(2000),
+            max_heartbeat_leak: 3,
+            max_wait_ack: Duration::from_millis(2500),
+            rpc_timeout: Duration::from_millis(3000),
+            max_batch_bytes: 4 * 1024 * 1024,
+        })
+    }
+
+    pub fn self_addr(&self) -> &str {
+        self.peers[&self.self_id].as_str()
+    }
+
+    /// The number of members that must store an entry before it is committed.
+    pub fn quorum(&self) -> usize {
+        self.peers.len() / 2 + 1
+    }
+
+    /// The broker id serving as this member when it follows: `n0` becomes 1, `n1` becomes 2, ...
+    pub fn follower_broker_id(&self) -> u64 {
+        self.self_id
+            .trim_start_matches(|c: char| !c.is_ascii_digit())
+            .parse::<u64>()
+            .map_or(1, |id| id + 1)
+    }
+}
+
+/// Parses `n0-127.0.0.1:40911;n1-127.0.0.1:40912` into member ids and addresses.
+pub fn parse_peers(peers: &str) -> DLedgerResult<BTreeMap<String, String>> {
+    let mut parsed = BTreeMap::new();
+    for peer in peers.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+        let (id, addr) = peer
+            .split_once('-')
+            .filter(|(id, addr)| !id.is_empty() && !addr.is_empty())
+            .ok_or_else(|| DLedgerError::Config(format!("invalid dLegerPeers item `{peer}`")))?;
+        if parsed.insert(id.to_string(), addr.to_string()).is_some() {
+            return Err(DLedgerError::Config(format!(
+                "duplicate member {id} in dLegerPeers"
+            )));
+        }
+    }
+    if parsed.is_empty() {
+        return Err(DLedgerError::Config("dLegerPeers is empty".to_string()));
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peers_are_parsed() {
+        let peers =
+            parse_peers("n0-127.0.0.1:40911; n1-127.0.0.1:40912;n2-localhost:40913").unwrap();
+        assert_eq!(peers.len(), 3);
+        assert_eq!(peers["n1"], "127.0.0.1:40912");
+        assert_eq!(peers["n2"], "localhost:40913");
+
+        assert!(parse_peers("").is_err());
+        assert!(parse_peers("n0127.0.0.1:40911").is_err());
+        assert!(parse_peers("n0-127.0.0.1:40911;n0-127.0.0.1:40912").is_err());
+    }
+
+    #[test]
+    fn config_from_store_config() {
+        let store_config = MessageStoreConfig {
+            dledger_group: Some("broker-a".to_string()),
+            dledger_peers: Some(
+                "n0-127.0.0.1:40911;n1-127.0.0.1:40912;n2-127.0.0.1:40913".to_string(),
+            ),
+            dledger_self_id: Some("n2".to_string()),
+            ..Default::default()
+        };
+        let config = DLedgerConfig::new(&store_config).unwrap();
+        assert_eq!(config.self_addr(), "127.0.0.1:40913");
+        assert_eq!(config.quorum(), 2);
+        assert_eq!(config.follower_broker_id(), 3);
+        assert!(config.store_path.ends_with("data"));
+        assert!(config.store_path.contains("dledger-n2"));
+
+        let unknown_self = MessageStoreConfig {
+            dledger_self_id: Some("n3".to_string()),
+            ..store_config
+        };
+        assert!(DLedgerConfig::new(&unknown_self).is_err());
+    }
+}
@@ -0,0 +1,231 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use bytes::Buf;
+use bytes::BufMut;
+use bytes::Bytes;
+use bytes::BytesMut;
+use rocketmq_common::CRC32Utils::crc32;
+
+/// Magic code of a stored entry.
+pub const ENTRY_MAGIC: i32 = 1;
+
+/// Magic code of the filler that pads the tail of a data file the next entry does not fit in.
+pub const BLANK_MAGIC: i32 = -1;
+
+/// Offset of the `pos` field inside the header.
+pub const POS_OFFSET: usize = 4 + 4 + 8 + 8;
+
+/// Header length, from the magic code up to and including the body crc.
+pub const HEADER_SIZE: usize = POS_OFFSET + 8 + 4 + 4 + 4;
+
+/// Offset of the body, i.e. of the commit log message, relative to the start of the entry.
+pub const BODY_OFFSET: usize = HEADER_SIZE + 4;
+
+/// Length of the filler written at the end of a data file.
+pub const BLANK_SIZE: usize = 4 + 4;
+
+/// One raft log entry. The layout follows the Java DLedger entries:
+///
+/// | magic | size | index | term | pos | channel | chain crc | body crc | body size | body |
+/// |-------|------|-------|------|-----|---------|-----------|----------|-----------|------|
+/// | 4     | 4    | 8     | 8    | 8   | 4       | 4         | 4        | 4         | n    |
+///
+/// `pos` is the physical offset of the entry in the log, so the message carried in the body
+/// starts at `pos + BODY_OFFSET`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DLedgerEntry {
+    pub index: i64,
+    pub term: i64,
+    pub pos: i64,
+    pub channel: i32,
+    pub chain_crc: i32,
+    pub body_crc: i32,
+    pub body: Bytes,
+}
+
+/// The fixed size part of an entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EntryHeader {
+    pub magic: i32,
+    pub size: i32,
+    pub index: i64,
+    pub term: i64,
+    pub pos: i64,
+    pub body_crc: i32,
+    pub body_size: i32,
+}
+
+impl DLedgerEntry {
+    pub fn new(index: i64, term: i64, pos: i64, body: Bytes) -> Self {
+        DLedgerEntry {
+            index,
+            term,
+            pos,
+            body_crc: crc32(body.as_ref()) as i32,
+            body,
+            ..Default::default()
+        }
+    }
+
+    #[inline]
+    pub fn size(&self) -> usize {
+        BODY_OFFSET + self.body.len()
+    }
+
+    pub fn encode(&self) -> Bytes {
+        let mut buf = BytesMut::with_capacity(self.size());
+        self.encode_into(&mut buf);
+        buf.freeze()
+    }
+
+    pub fn encode_into(&self, buf: &mut BytesMut) {
+        buf.put_i32(ENTRY_MAGIC);
+        buf.put_i32(self.size() as i32);
+        buf.put_i64(self.index);
+        buf.put_i64(self.term);
+        buf.put_i64(self.pos);
+        buf.put_i32(self.channel);
+        buf.put_i32(self.chain_crc);
+        buf.put_i32(self.body_crc);
+        buf.put_i32(self.body.len() as i32);
+        buf.put_slice(self.body.as_ref());
+    }
+
+    /// Decodes a whole entry, returning `None` if the bytes are not a well formed entry or the
+    /// body does not match its crc.
+    pub fn decode(mut bytes: Bytes) -> Option<Self> {
+        let header = EntryHeader::decode(bytes.as_ref())?;
+        if header.magic != ENTRY_MAGIC || bytes.len() != header.size as usize {
+            return None;
+        }
+        bytes.advance(POS_OFFSET + 8);
+        let channel = bytes.get_i32();
+        let chain_crc = bytes.get_i32();
+        bytes.advance(8);
+        if crc32(bytes.as_ref()) as i32 != header.body_crc {
+            return None;
+        }
+        Some(DLedgerEntry {
+            index: header.index,
+            term: header.term,
+            pos: header.pos,
+            channel,
+            chain_crc,
+            body_crc: header.body_crc,
+            body: bytes,
+        })
+    }
+}
+
+impl EntryHeader {
+    /// Reads the header at the start of `bytes`. A blank filler only carries its magic code and
+    /// size, the other fields are zero then.
+    pub fn decode(mut bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < BLANK_SIZE {
+            return None;
+        }
+        let magic = bytes.get_i32();
+        let size = bytes.get_i32();
+        match magic {
+            BLANK_MAGIC => Some(EntryHeader {
+                magic,
+                size,
+                index: 0,
+                term: 0,
+                pos: 0,
+                body_crc: 0,
+                body_size: 0,
+            }),
+            ENTRY_MAGIC if bytes.len() >= BODY_OFFSET - BLANK_SIZE => {
+                let index = bytes.get_i64();
+                let term = bytes.get_i64();
+                let pos = bytes.get_i64();
+                bytes.advance(8);
+                let body_crc = bytes.get_i32();
+                let body_size = bytes.get_i32();
+                if size as usize != BODY_OFFSET + body_size.max(0) as usize {
+                    return None;
+                }
+                Some(EntryHeader {
+                    magic,
+                    size,
+                    index,
+                    term,
+                    pos,
+                    body_crc,
+                    body_size,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn is_blank(&self) -> bool {
+        self.magic == BLANK_MAGIC
+    }
+}
+
+/// The filler covering the `size` bytes left at the end of a data file.
+pub fn encode_blank(size: usize) -> [u8; BLANK_SIZE] {
+    let mut blank = [0u8; BLANK_SIZE];
+    blank[..4].copy_from_slice(&BLANK_MAGIC.to_be_bytes());
+    blank[4..].copy_from_slice(&(size as i32).to_be_bytes());
+    blank
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entry_round_trip() {
+        let entry = DLedgerEntry::new(3, 2, 4096, Bytes::from_static(b"message"));
+        let bytes = entry.encode();
+        assert_eq!(bytes.len(), BODY_OFFSET + 7);
+        assert_eq!(&bytes[BODY_OFFSET..], b"message");
+
+        let header = EntryHeader::decode(bytes.as_ref()).unwrap();
+        assert_eq!(header.size as usize, bytes.len());
+        assert_eq!((header.index, header.term, header.pos), (3, 2, 4096));
+        assert_eq!(header.body_size, 7);
+        assert!(!header.is_blank());
+
+        assert_eq!(DLedgerEntry::decode(bytes).unwrap(), entry);
+    }
+
+    #[test]
+    fn corrupted_body_is_rejected() {
+        let mut bytes = BytesMut::from(
+            DLedgerEntry::new(0, 1, 0, Bytes::from_static(b"body"))
+                .encode()
+                .as_ref(),
+        );
+        bytes[BODY_OFFSET] = b'B';
+        assert!(DLedgerEntry::decode(bytes.freeze()).is_none());
+    }
+
+    #[test]
+    fn blank_header() {
+        let blank = encode_blank(100);
+        let header = EntryHeader::decode(&blank).unwrap();
+        assert!(header.is_blank());
+        assert_eq!(header.size, 100);
+        assert!(EntryHeader::decode(&[0u8; BODY_OFFSET]).is_none());
+    }
+}
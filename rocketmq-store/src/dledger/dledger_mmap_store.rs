@@ -0,0 +1,462 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::sync::Arc;
+
+use bytes::Bytes;
+use bytes::BytesMut;
+use parking_lot::RwLock;
+use rocketmq_rust::ArcMut;
+use tracing::info;
+use tracing::warn;
+
+use crate::consume_queue::mapped_file_queue::MappedFileQueue;
+use crate::dledger::dledger_entry::encode_blank;
+use crate::dledger::dledger_entry::DLedgerEntry;
+use crate::dledger::dledger_entry::EntryHeader;
+use crate::dledger::dledger_entry::BLANK_SIZE;
+use crate::dledger::dledger_entry::BODY_OFFSET;
+use crate::log_file::mapped_file::default_mapped_file_impl::DefaultMappedFile;
+use crate::log_file::mapped_file::MappedFile;
+use crate::store_error::DLedgerError;
+use crate::store_error::DLedgerResult;
+
+#[derive(Debug, Clone, Copy)]
+struct IndexItem {
+    pos: i64,
+    size: i32,
+    term: i64,
+}
+
+#[derive(Default)]
+struct EntryIndex {
+    /// Raft index of `items[0]`.
+    first_index: i64,
+    items: Vec<IndexItem>,
+    /// Physical offset the next entry is appended at, unless it has to roll to the next file.
+    end_pos: i64,
+}
+
+impl EntryIndex {
+    fn get(&self, index: i64) -> Option<&IndexItem> {
+        if index < self.first_index {
+            return None;
+        }
+        self.items.get((index - self.first_index) as usize)
+    }
+
+    fn end_index(&self) -> i64 {
+        self.first_index + self.items.len() as i64 - 1
+    }
+}
+
+/// The raft log, stored in the data files of the commit log it replaces.
+///
+/// Entries are written back to back; an entry that does not fit in the rest of a data file is
+/// preceded by a blank filler and starts the next file, like commit log messages do. As leader
+/// and followers use the same file size, an entry lands at the same position on every member.
+pub(crate) struct DLedgerMmapFileStore {
+    mapped_file_queue: ArcMut<MappedFileQueue>,
+    mapped_file_size: i64,
+    index: RwLock<EntryIndex>,
+}
+
+impl DLedgerMmapFileStore {
+    pub fn new(mapped_file_queue: ArcMut<MappedFileQueue>, mapped_file_size: usize) -> Self {
+        DLedgerMmapFileStore {
+            mapped_file_queue,
+            mapped_file_size: mapped_file_size as i64,
+            index: RwLock::new(EntryIndex::default()),
+        }
+    }
+
+    /// Rebuilds the entry index from the loaded data files and cuts off the torn tail a crash may
+    /// have left behind.
+    pub fn recover(&self) {
+        let mut index = self.index.write();
+        *index = EntryIndex::default();
+        let mapped_files = self.mapped_file_queue.get_mapped_files();
+        let files = mapped_files.read().clone();
+        if let Some(first) = files.first() {
+            index.end_pos = first.get_file_from_offset() as i64;
+        }
+        'files: for file in files.iter() {
+            let file_from = file.get_file_from_offset() as i64;
+            if file_from != index.end_pos {
+                break;
+            }
+            let mut pos = 0i64;
+            while pos + BLANK_SIZE as i64 <= self.mapped_file_size {
+                let read = (self.mapped_file_size - pos).min(BODY_OFFSET as i64) as usize;
+                let Some(header) = file
+                    .get_bytes(pos as usize, read)
+                    .and_then(|bytes| EntryHeader::decode(bytes.as_ref()))
+                else {
+                    break 'files;
+                };
+                if header.is_blank() {
+                    if pos + header.size as i64 != self.mapped_file_size {
+                        break 'files;
+                    }
+                    index.end_pos = file_from + self.mapped_file_size;
+                    continue 'files;
+                }
+                let expected_index = if index.items.is_empty() {
+                    header.index
+                } else {
+                    index.end_index() + 1
+                };
+                if header.index != expected_index
+                    || header.pos != file_from + pos
+                    || pos + header.size as i64 > self.mapped_file_size
+                    || index
+                        .items
+                        .last()
+                        .is_some_and(|last| last.term > header.term)
+                {
+                    break 'files;
+                }
+                if index.items.is_empty() {
+                    index.first_index = header.index;
+                }
+                index.items.push(IndexItem {
+                    pos: header.pos,
+                    size: header.size,
+                    term: header.term,
+                });
+                pos += header.size as i64;
+                index.end_pos = file_from + pos;
+            }
+            break;
+        }
+        let end_pos = index.end_pos;
+        self.mapped_file_queue
+            .mut_from_ref()
+            .truncate_dirty_files(end_pos);
+        self.mapped_file_queue.set_flushed_where(end_pos);
+        self.mapped_file_queue.set_committed_where(end_pos);
+        info!(
+            "recover dledger store, entries [{}, {}], end pos {}",
+            index.first_index,
+            index.end_index(),
+            end_pos
+        );
+    }
+
+    /// Index of the last entry, -1 for an empty log.
+    pub fn ledger_end_index(&self) -> i64 {
+        self.index.read().end_index()
+    }
+
+    /// Term of the last entry, 0 for an empty log.
+    pub fn ledger_end_term(&self) -> i64 {
+        self.index.read().items.last().map_or(0, |item| item.term)
+    }
+
+    pub fn first_index(&self) -> i64 {
+        self.index.read().first_index
+    }
+
+    pub fn term_at(&self, index: i64) -> Option<i64> {
+        self.index.read().get(index).map(|item| item.term)
+    }
+
+    /// Physical offset right behind the entry at `index`.
+    pub fn entry_end_pos(&self, index: i64) -> Option<i64> {
+        self.index
+            .read()
+            .get(index)
+            .map(|item| item.pos + item.size as i64)
+    }
+
+    /// Physical offset of the first entry, or the end of the log if it is empty.
+    pub fn start_pos(&self) -> i64 {
+        let index = self.index.read();
+        index.items.first().map_or(index.end_pos, |item| item.pos)
+    }
+
+    pub fn end_pos(&self) -> i64 {
+        self.index.read().end_pos
+    }
+
+    /// Appends one entry per body length in `body_sizes`. `build_body` gets the position of each
+    /// entry and returns its body, which must be exactly as long as announced. Returns the index
+    /// and position of every entry.
+    pub fn append_as_leader<F>(
+        &self,
+        term: i64,
+        body_sizes: &[usize],
+        mut build_body: F,
+    ) -> DLedgerResult<Vec<(i64, i64)>>
+    where
+        F: FnMut(usize, i64) -> Bytes,
+    {
+        let mut index = self.index.write();
+        let mut appended = Vec::with_capacity(body_sizes.len());
+        let mut buf = BytesMut::new();
+        for (i, body_size) in body_sizes.iter().enumerate() {
+            let size = BODY_OFFSET + body_size;
+            let (file, pos) = self.reserve(&mut index, size)?;
+            let body = build_body(i, pos);
+            if body.len() != *body_size {
+                return Err(DLedgerError::Store(format!(
+                    "entry body has {} bytes instead of {}",
+                    body.len(),
+                    body_size
+                )));
+            }
+            let entry_index = index.end_index() + 1;
+            buf.clear();
+            DLedgerEntry::new(entry_index, term, pos, body).encode_into(&mut buf);
+            self.write(&mut index, &file, pos, term, buf.as_ref())?;
+            appended.push((entry_index, pos));
+        }
+        Ok(appended)
+    }
+
+    /// Stores an entry received from the leader, which must directly follow the last entry.
+    pub fn append_as_follower(&self, entry: &[u8]) -> DLedgerResult<()> {
+        let header = EntryHeader::decode(entry)
+            .filter(|header| !header.is_blank() && header.size as usize == entry.len())
+            .ok_or_else(|| DLedgerError::Protocol("malformed entry".to_string()))?;
+        let mut index = self.index.write();
+        let expected_index = index.end_index() + 1;
+        if header.index != expected_index {
+            return Err(DLedgerError::Store(format!(
+                "entry {} does not follow the last entry {}",
+                header.index,
+                expected_index - 1
+            )));
+        }
+        let (file, pos) = self.reserve(&mut index, entry.len())?;
+        if pos != header.pos {
+            return Err(DLedgerError::Store(format!(
+                "entry {} belongs at {} but the log ends at {}",
+                header.index, header.pos, pos
+            )));
+        }
+        self.write(&mut index, &file, pos, header.term, entry)
+    }
+
+    /// Drops the entry at `from_index` and all entries behind it.
+    pub fn truncate(&self, from_index: i64) {
+        let mut index = self.index.write();
+        let Some(item) = index.get(from_index).copied() else {
+            return;
+        };
+        warn!(
+            "truncate dledger entries [{}, {}] from pos {}",
+            from_index,
+            index.end_index(),
+            item.pos
+        );
+        let keep = (from_index - index.first_index) as usize;
+        index.items.truncate(keep);
+        index.end_pos = item.pos;
+        let mapped_file_queue = self.mapped_file_queue.mut_from_ref();
+        mapped_file_queue.truncate_dirty_files(item.pos);
+        if mapped_file_queue.get_flushed_where() > item.pos {
+            mapped_file_queue.set_flushed_where(item.pos);
+        }
+        if mapped_file_queue.get_committed_where() > item.pos {
+            mapped_file_queue.set_committed_where(item.pos);
+        }
+    }
+
+    /// The raw entry at `index`.
+    pub fn get_entry(&self, index: i64) -> Option<Bytes> {
+        let item = *self.index.read().get(index)?;
+        self.read(item)
+    }
+
+    /// Consecutive raw entries starting at `from_index`, at least one if it exists and as many
+    /// more as fit in `max_bytes`.
+    pub fn get_entries(&self, from_index: i64, max_bytes: usize) -> Vec<Bytes> {
+        let items: Vec<IndexItem> = {
+            let index = self.index.read();
+            let mut total = 0usize;
+            let mut items = Vec::new();
+            let mut next = from_index;
+            while let Some(item) = index.get(next) {
+                if !items.is_empty() && total + item.size as usize > max_bytes {
+                    break;
+                }
+                total += item.size as usize;
+                items.push(*item);
+                next += 1;
+            }
+            items
+        };
+        items
+            .into_iter()
+            .map_while(|item| self.read(item))
+            .collect()
+    }
+
+    fn read(&self, item: IndexItem) -> Option<Bytes> {
+        let file = self
+            .mapped_file_queue
+            .find_mapped_file_by_offset(item.pos, false)?;
+        file.get_bytes(
+            (item.pos % self.mapped_file_size) as usize,
+            item.size as usize,
+        )
+    }
+
+    /// Finds the file and position the next entry of `size` bytes goes to, closing the current
+    /// file with a blank filler if the entry does not fit in it.
+    fn reserve(
+        &self,
+        index: &mut EntryIndex,
+        size: usize,
+    ) -> DLedgerResult<(Arc<DefaultMappedFile>, i64)> {
+        if (size + BLANK_SIZE) as i64 > self.mapped_file_size {
+            return Err(DLedgerError::Store(format!(
+                "entry of {size} bytes exceeds the data file size"
+            )));
+        }
+        let mapped_file_queue = self.mapped_file_queue.mut_from_ref();
+        let mut file = mapped_file_queue
+            .get_last_mapped_file_mut_start_offset(index.end_pos as u64, true)
+            .ok_or_else(|| DLedgerError::Store("create mapped file failed".to_string()))?;
+        let wrote = file.get_wrote_position() as i64;
+        let remaining = self.mapped_file_size - wrote;
+        if (size + BLANK_SIZE) as i64 > remaining {
+            let blank = encode_blank(remaining as usize);
+            if !file.append_message_bytes(&blank) {
+                return Err(DLedgerError::Store("write blank filler failed".to_string()));
+            }
+            file.set_wrote_position(self.mapped_file_size as i32);
+            index.end_pos = file.get_file_from_offset() as i64 + self.mapped_file_size;
+            file = mapped_file_queue
+                .get_last_mapped_file_mut_start_offset(index.end_pos as u64, true)
+                .ok_or_else(|| DLedgerError::Store("create mapped file failed".to_string()))?;
+        }
+        let pos = file.get_file_from_offset() as i64 + file.get_wrote_position() as i64;
+        Ok((file, pos))
+    }
+
+    fn write(
+        &self,
+        index: &mut EntryIndex,
+        file: &DefaultMappedFile,
+        pos: i64,
+        term: i64,
+        entry: &[u8],
+    ) -> DLedgerResult<()> {
+        if !file.append_message_bytes(entry) {
+            return Err(DLedgerError::Store(format!("write entry at {pos} failed")));
+        }
+        index.items.push(IndexItem {
+            pos,
+            size: entry.len() as i32,
+            term,
+        });
+        index.end_pos = pos + entry.len() as i64;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_store(dir: &tempfile::TempDir, file_size: usize) -> DLedgerMmapFileStore {
+        let mut queue = MappedFileQueue::new(
+            dir.path().to_string_lossy().to_string(),
+            file_size as u64,
+            None,
+        );
+        queue.load();
+        let store = DLedgerMmapFileStore::new(ArcMut::new(queue), file_size);
+        store.recover();
+        store
+    }
+
+    fn append(store: &DLedgerMmapFileStore, term: i64, body: &'static [u8]) -> (i64, i64) {
+        store
+            .append_as_leader(term, &[body.len()], |_, _| Bytes::from_static(body))
+            .unwrap()[0]
+    }
+
+    #[test]
+    fn entries_roll_over_to_the_next_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = new_store(&dir, 160);
+        assert_eq!(store.ledger_end_index(), -1);
+        assert_eq!(store.ledger_end_term(), 0);
+
+        assert_eq!(append(&store, 1, b"0123456789"), (0, 0));
+        assert_eq!(append(&store, 1, b"0123456789"), (1, 58));
+        // 116 + 58 + 8 exceeds the file, so the third entry starts the second file
+        assert_eq!(append(&store, 2, b"0123456789"), (2, 160));
+        assert_eq!(store.ledger_end_index(), 2);
+        assert_eq!(store.ledger_end_term(), 2);
+        assert_eq!(store.entry_end_pos(2), Some(218));
+
+        let entry = DLedgerEntry::decode(store.get_entry(1).unwrap()).unwrap();
+        assert_eq!((entry.index, entry.term, entry.pos), (1, 1, 58));
+        assert_eq!(store.get_entries(0, 1).len(), 1);
+        assert_eq!(store.get_entries(1, usize::MAX).len(), 2);
+    }
+
+    #[test]
+    fn recover_rebuilds_the_index() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let store = new_store(&dir, 160);
+            for term in [1, 1, 2] {
+                append(&store, term, b"0123456789");
+            }
+            let mapped_files = store.mapped_file_queue.get_mapped_files();
+            for file in mapped_files.read().iter() {
+                file.flush(0);
+            }
+        }
+        let store = new_store(&dir, 160);
+        assert_eq!(store.ledger_end_index(), 2);
+        assert_eq!(store.term_at(1), Some(1));
+        assert_eq!(store.term_at(2), Some(2));
+        assert_eq!(store.end_pos(), 218);
+        assert_eq!(append(&store, 3, b"0123456789"), (3, 218));
+    }
+
+    #[test]
+    fn follower_truncates_and_appends() {
+        let leader_dir = tempfile::tempdir().unwrap();
+        let follower_dir = tempfile::tempdir().unwrap();
+        let leader = new_store(&leader_dir, 160);
+        let follower = new_store(&follower_dir, 160);
+        for term in [1, 1, 1] {
+            append(&leader, term, b"0123456789");
+        }
+        for entry in leader.get_entries(0, usize::MAX) {
+            follower.append_as_follower(entry.as_ref()).unwrap();
+        }
+        assert_eq!(follower.ledger_end_index(), 2);
+        assert_eq!(follower.end_pos(), leader.end_pos());
+        assert!(follower
+            .append_as_follower(leader.get_entry(1).unwrap().as_ref())
+            .is_err());
+
+        follower.truncate(1);
+        assert_eq!(follower.ledger_end_index(), 0);
+        assert_eq!(follower.end_pos(), 58);
+        assert_eq!(append(&follower, 2, b"abcdefghij"), (1, 58));
+        assert_eq!(follower.term_at(1), Some(2));
+    }
+}
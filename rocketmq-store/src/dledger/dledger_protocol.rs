@@ -0,0 +1,298 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! The messages members of a group exchange. Every message travels as one frame: a 4 byte length
+//! followed by a 1 byte message code and the fields in big endian order.
+
+use bytes::Buf;
+use bytes::BufMut;
+use bytes::Bytes;
+use bytes::BytesMut;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt;
+
+use crate::store_error::DLedgerError;
+use crate::store_error::DLedgerResult;
+
+const VOTE_REQUEST: u8 = 1;
+const VOTE_RESPONSE: u8 = 2;
+const APPEND_REQUEST: u8 = 3;
+const APPEND_RESPONSE: u8 = 4;
+
+/// Frames larger than this are rejected instead of being buffered.
+const MAX_FRAME_SIZE: usize = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct VoteRequest {
+    pub group: String,
+    pub candidate_id: String,
+    pub term: i64,
+    pub last_index: i64,
+    pub last_term: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct VoteResponse {
+    pub term: i64,
+    pub granted: bool,
+}
+
+/// Replicates `entries`, which directly follow the entry at `prev_index`. Without entries it
+/// only is a heartbeat carrying the commit index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct AppendRequest {
+    pub group: String,
+    pub leader_id: String,
+    pub term: i64,
+    pub prev_index: i64,
+    pub prev_term: i64,
+    pub commit_index: i64,
+    pub entries: Vec<Bytes>,
+}
+
+/// `end_index` is the last entry the follower holds in agreement with the leader; after a
+/// rejection the leader resumes replication behind it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct AppendResponse {
+    pub term: i64,
+    pub success: bool,
+    pub end_index: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum DLedgerMessage {
+    VoteRequest(VoteRequest),
+    VoteResponse(VoteResponse),
+    AppendRequest(AppendRequest),
+    AppendResponse(AppendResponse),
+}
+
+impl DLedgerMessage {
+    pub fn encode(&self) -> Bytes {
+        let mut buf = BytesMut::with_capacity(64);
+        buf.put_u32(0);
+        match self {
+            DLedgerMessage::VoteRequest(request) => {
+                buf.put_u8(VOTE_REQUEST);
+                put_string(&mut buf, &request.group);
+                put_string(&mut buf, &request.candidate_id);
+                buf.put_i64(request.term);
+                buf.put_i64(request.last_index);
+                buf.put_i64(request.last_term);
+            }
+            DLedgerMessage::VoteResponse(response) => {
+                buf.put_u8(VOTE_RESPONSE);
+                buf.put_i64(response.term);
+                buf.put_u8(response.granted as u8);
+            }
+            DLedgerMessage::AppendRequest(request) => {
+                buf.put_u8(APPEND_REQUEST);
+                put_string(&mut buf, &request.group);
+                put_string(&mut buf, &request.leader_id);
+                buf.put_i64(request.term);
+                buf.put_i64(request.prev_index);
+                buf.put_i64(request.prev_term);
+                buf.put_i64(request.commit_index);
+                buf.put_u32(request.entries.len() as u32);
+                for entry in &request.entries {
+                    buf.put_u32(entry.len() as u32);
+                    buf.put_slice(entry.as_ref());
+                }
+            }
+            DLedgerMessage::AppendResponse(response) => {
+                buf.put_u8(APPEND_RESPONSE);
+                buf.put_i64(response.term);
+                buf.put_u8(response.success as u8);
+                buf.put_i64(response.end_index);
+            }
+        }
+        let frame_len = (buf.len() - 4) as u32;
+        buf[..4].copy_from_slice(&frame_len.to_be_bytes());
+        buf.freeze()
+    }
+
+    /// Decodes a frame without its length prefix.
+    pub fn decode(mut frame: Bytes) -> DLedgerResult<Self> {
+        let code = get_u8(&mut frame)?;
+        let message = match code {
+            VOTE_REQUEST => DLedgerMessage::VoteRequest(VoteRequest {
+                group: get_string(&mut frame)?,
+                candidate_id: get_string(&mut frame)?,
+                term: get_i64(&mut frame)?,
+                last_index: get_i64(&mut frame)?,
+                last_term: get_i64(&mut frame)?,
+            }),
+            VOTE_RESPONSE => DLedgerMessage::VoteResponse(VoteResponse {
+                term: get_i64(&mut frame)?,
+                granted: get_u8(&mut frame)? != 0,
+            }),
+            APPEND_REQUEST => {
+                let group = get_string(&mut frame)?;
+                let leader_id = get_string(&mut frame)?;
+                let term = get_i64(&mut frame)?;
+                let prev_index = get_i64(&mut frame)?;
+                let prev_term = get_i64(&mut frame)?;
+                let commit_index = get_i64(&mut frame)?;
+                let count = get_u32(&mut frame)? as usize;
+                let mut entries = Vec::with_capacity(count.min(1024));
+                for _ in 0..count {
+                    let len = get_u32(&mut frame)? as usize;
+                    entries.push(get_bytes(&mut frame, len)?);
+                }
+                DLedgerMessage::AppendRequest(AppendRequest {
+                    group,
+                    leader_id,
+                    term,
+                    prev_index,
+                    prev_term,
+                    commit_index,
+                    entries,
+                })
+            }
+            APPEND_RESPONSE => DLedgerMessage::AppendResponse(AppendResponse {
+                term: get_i64(&mut frame)?,
+                success: get_u8(&mut frame)? != 0,
+                end_index: get_i64(&mut frame)?,
+            }),
+            code => {
+                return Err(DLedgerError::Protocol(format!(
+                    "unknown message code {code}"
+                )))
+            }
+        };
+        if frame.has_remaining() {
+            return Err(DLedgerError::Protocol(format!(
+                "{} trailing bytes behind message {code}",
+                frame.remaining()
+            )));
+        }
+        Ok(message)
+    }
+
+    pub async fn write_to<W: AsyncWrite + Unpin>(&self, writer: &mut W) -> DLedgerResult<()> {
+        writer.write_all(self.encode().as_ref()).await?;
+        writer.flush().await?;
+        Ok(())
+    }
+
+    pub async fn read_from<R: AsyncRead + Unpin>(reader: &mut R) -> DLedgerResult<Self> {
+        let len = reader.read_u32().await? as usize;
+        if len > MAX_FRAME_SIZE {
+            return Err(DLedgerError::Protocol(format!(
+                "frame of {len} bytes exceeds the limit of {MAX_FRAME_SIZE}"
+            )));
+        }
+        let mut frame = vec![0u8; len];
+        reader.read_exact(&mut frame).await?;
+        Self::decode(Bytes::from(frame))
+    }
+}
+
+fn put_string(buf: &mut BytesMut, value: &str) {
+    buf.put_u16(value.len() as u16);
+    buf.put_slice(value.as_bytes());
+}
+
+fn truncated() -> DLedgerError {
+    DLedgerError::Protocol("truncated message".to_string())
+}
+
+fn get_u8(buf: &mut Bytes) -> DLedgerResult<u8> {
+    buf.try_get_u8().map_err(|_| truncated())
+}
+
+fn get_u32(buf: &mut Bytes) -> DLedgerResult<u32> {
+    buf.try_get_u32().map_err(|_| truncated())
+}
+
+fn get_i64(buf: &mut Bytes) -> DLedgerResult<i64> {
+    buf.try_get_i64().map_err(|_| truncated())
+}
+
+fn get_bytes(buf: &mut Bytes, len: usize) -> DLedgerResult<Bytes> {
+    if buf.remaining() < len {
+        return Err(truncated());
+    }
+    Ok(buf.split_to(len))
+}
+
+fn get_string(buf: &mut Bytes) -> DLedgerResult<String> {
+    let len = buf.try_get_u16().map_err(|_| truncated())? as usize;
+    let bytes = get_bytes(buf, len)?;
+    String::from_utf8(bytes.to_vec())
+        .map_err(|_| DLedgerError::Protocol("string is not utf-8".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn round_trip(message: DLedgerMessage) {
+        let (mut client, mut server) = tokio::io::duplex(1024);
+        message.write_to(&mut client).await.unwrap();
+        assert_eq!(
+            DLedgerMessage::read_from(&mut server).await.unwrap(),
+            message
+        );
+    }
+
+    #[tokio::test]
+    async fn messages_round_trip() {
+        round_trip(DLedgerMessage::VoteRequest(VoteRequest {
+            group: "broker-a".to_string(),
+            candidate_id: "n1".to_string(),
+            term: 3,
+            last_index: 10,
+            last_term: 2,
+        }))
+        .await;
+        round_trip(DLedgerMessage::VoteResponse(VoteResponse {
+            term: 3,
+            granted: true,
+        }))
+        .await;
+        round_trip(DLedgerMessage::AppendRequest(AppendRequest {
+            group: "broker-a".to_string(),
+            leader_id: "n0".to_string(),
+            term: 3,
+            prev_index: 9,
+            prev_term: 2,
+            commit_index: 8,
+            entries: vec![Bytes::from_static(b"first"), Bytes::from_static(b"second")],
+        }))
+        .await;
+        round_trip(DLedgerMessage::AppendResponse(AppendResponse {
+            term: 3,
+            success: false,
+            end_index: 7,
+        }))
+        .await;
+    }
+
+    #[test]
+    fn truncated_frame_is_rejected() {
+        let frame = DLedgerMessage::VoteResponse(VoteResponse {
+            term: 1,
+            granted: false,
+        })
+        .encode();
+        assert!(DLedgerMessage::decode(frame.slice(4..frame.len() - 1)).is_err());
+        assert!(DLedgerMessage::decode(Bytes::from_static(&[9])).is_err());
+    }
+}
@@ -0,0 +1,966 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicI64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures_util::future::join_all;
+use parking_lot::Mutex;
+use rocketmq_common::common::mix_all::string_to_properties;
+use rocketmq_common::FileUtils::file_to_string;
+use rocketmq_common::FileUtils::string_to_file;
+use rocketmq_rust::ArcMut;
+use tokio::net::TcpListener;
+use tokio::net::TcpStream;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tokio::task::JoinSet;
+use tokio::time::Instant;
+use tracing::debug;
+use tracing::error;
+use tracing::info;
+use tracing::warn;
+
+use crate::consume_queue::mapped_file_queue::MappedFileQueue;
+use crate::dledger::dledger_config::DLedgerConfig;
+use crate::dledger::dledger_entry::EntryHeader;
+use crate::dledger::dledger_mmap_store::DLedgerMmapFileStore;
+use crate::dledger::dledger_protocol::AppendRequest;
+use crate::dledger::dledger_protocol::AppendResponse;
+use crate::dledger::dledger_protocol::DLedgerMessage;
+use crate::dledger::dledger_protocol::VoteRequest;
+use crate::dledger::dledger_protocol::VoteResponse;
+use crate::dledger::member_state::MemberRole;
+use crate::dledger::member_state::MemberState;
+use crate::dledger::member_state::RoleState;
+use crate::store_error::DLedgerError;
+use crate::store_error::DLedgerResult;
+
+const CHECKPOINT_FILE: &str = "checkpoint";
+const COMMITTED_INDEX_KEY: &str = "committedIndex";
+
+/// One member of a raft group replicating the commit log.
+///
+/// The server elects a leader among the members, lets the leader append entries and replicates
+/// them to the followers. An entry is committed once a majority stored it; followers learn the
+/// commit index from the heartbeats and appends of the leader.
+pub struct DLedgerServer {
+    inner: Arc<ServerInner>,
+}
+
+struct PeerProgress {
+    next_index: i64,
+    match_index: i64,
+    last_ack: Instant,
+}
+
+struct ServerInner {
+    config: DLedgerConfig,
+    store: DLedgerMmapFileStore,
+    meta_dir: PathBuf,
+    member_state: Mutex<MemberState>,
+    role_tx: watch::Sender<RoleState>,
+    /// Index of the last committed entry, -1 before anything got committed.
+    committed_tx: watch::Sender<i64>,
+    committed_end_pos: AtomicI64,
+    persisted_committed: AtomicI64,
+    /// Bumped on every leader append to wake the replicators.
+    ledger_end_tx: watch::Sender<i64>,
+    election_deadline: Mutex<Instant>,
+    peers: HashMap<String, PeerClient>,
+    progress: Mutex<HashMap<String, PeerProgress>>,
+    follower_append_lock: tokio::sync::Mutex<()>,
+    shutdown_tx: watch::Sender<bool>,
+    tasks: Mutex<Vec<JoinHandle<()>>>,
+}
+
+struct PeerClient {
+    addr: String,
+    stream: tokio::sync::Mutex<Option<TcpStream>>,
+}
+
+impl DLedgerServer {
+    pub fn new(config: DLedgerConfig, mapped_file_queue: ArcMut<MappedFileQueue>) -> Self {
+        let meta_dir = Path::new(&config.store_path)
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+        let member_state = MemberState::load(&meta_dir);
+        let peers = config
+            .peers
+            .iter()
+            .filter(|(id, _)| **id != config.self_id)
+            .map(|(id, addr)| {
+                (
+                    id.clone(),
+                    PeerClient {
+                        addr: addr.clone(),
+                        stream: tokio::sync::Mutex::new(None),
+                    },
+                )
+            })
+            .collect();
+        let (role_tx, _) = watch::channel(member_state.role_state());
+        let (committed_tx, _) = watch::channel(-1);
+        let (ledger_end_tx, _) = watch::channel(-1);
+        let (shutdown_tx, _) = watch::channel(false);
+        DLedgerServer {
+            inner: Arc::new(ServerInner {
+                store: DLedgerMmapFileStore::new(mapped_file_queue, config.mapped_file_size),
+                config,
+                meta_dir,
+                member_state: Mutex::new(member_state),
+                role_tx,
+                committed_tx,
+                committed_end_pos: AtomicI64::new(0),
+                persisted_committed: AtomicI64::new(-1),
+                ledger_end_tx,
+                election_deadline: Mutex::new(Instant::now()),
+                peers,
+                progress: Mutex::new(HashMap::new()),
+                follower_append_lock: tokio::sync::Mutex::new(()),
+                shutdown_tx,
+                tasks: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    pub fn config(&self) -> &DLedgerConfig {
+        &self.inner.config
+    }
+
+    /// Rebuilds the log from the data files loaded into the commit log's mapped file queue and
+    /// restores the commit index persisted at the last checkpoint.
+    pub fn recover(&self) {
+        let inner = &self.inner;
+        inner.store.recover();
+        let checkpoint = Path::new(&inner.meta_dir).join(CHECKPOINT_FILE);
+        let committed = file_to_string(&checkpoint.to_string_lossy())
+            .ok()
+            .and_then(|content| string_to_properties(&content))
+            .and_then(|properties| {
+                properties
+                    .get(COMMITTED_INDEX_KEY)
+                    .and_then(|index| index.parse::<i64>().ok())
+            })
+            .unwrap_or(-1)
+            .min(inner.store.ledger_end_index());
+        inner
+            .committed_end_pos
+            .store(inner.store.start_pos(), Ordering::Release);
+        inner.committed_tx.send_replace(-1);
+        inner.advance_commit(committed);
+        inner
+            .persisted_committed
+            .store(committed, Ordering::Release);
+        info!(
+            "recover dledger server {}, ledger end index {}, committed index {}",
+            inner.config.self_id,
+            inner.store.ledger_end_index(),
+            committed
+        );
+    }
+
+    /// Starts serving the other members and taking part in elections. Must be called from
+    /// within a tokio runtime.
+    pub fn start(&self) -> DLedgerResult<()> {
+        let self_addr = self.inner.config.self_addr();
+        let port = self_addr
+            .rsplit_once(':')
+            .and_then(|(_, port)| port.parse::<u16>().ok())
+            .ok_or_else(|| DLedgerError::Config(format!("invalid member address {self_addr}")))?;
+        let listener = std::net::TcpListener::bind(("0.0.0.0", port))?;
+        listener.set_nonblocking(true)?;
+        let listener = TcpListener::from_std(listener)?;
+        info!(
+            "dledger server {} of group {} listening on {}",
+            self.inner.config.self_id, self.inner.config.group, self_addr
+        );
+        // Give a running leader the chance to reach this member before it runs for leader itself.
+        self.inner
+            .reset_election_deadline(self.inner.leader_lease());
+        let mut tasks = self.inner.tasks.lock();
+        tasks.push(tokio::spawn(ServerInner::serve(
+            self.inner.clone(),
+            listener,
+        )));
+        tasks.push(tokio::spawn(ServerInner::run_ticker(self.inner.clone())));
+        Ok(())
+    }
+
+    pub fn shutdown(&self) {
+        self.inner.shutdown_tx.send_replace(true);
+        for task in self.inner.tasks.lock().drain(..) {
+            task.abort();
+        }
+        self.inner.persist_committed();
+    }
+
+    pub fn role_state(&self) -> RoleState {
+        self.inner.role_tx.borrow().clone()
+    }
+
+    /// Receives every role change of this member.
+    pub fn subscribe_role(&self) -> watch::Receiver<RoleState> {
+        self.inner.role_tx.subscribe()
+    }
+
+    pub fn is_leader(&self) -> bool {
+        self.inner.role_tx.borrow().role == MemberRole::Leader
+    }
+
+    pub fn committed_index(&self) -> i64 {
+        *self.inner.committed_tx.borrow()
+    }
+
+    /// Physical offset behind the last committed entry; the commit log is readable up to there.
+    pub fn committed_end_pos(&self) -> i64 {
+        self.inner.committed_end_pos.load(Ordering::Acquire)
+    }
+
+    pub fn ledger_end_index(&self) -> i64 {
+        self.inner.store.ledger_end_index()
+    }
+
+    pub fn ledger_end_term(&self) -> i64 {
+        self.inner.store.ledger_end_term()
+    }
+
+    /// Physical offset behind the last entry, committed or not.
+    pub fn ledger_end_pos(&self) -> i64 {
+        self.inner.store.end_pos()
+    }
+
+    /// Appends one entry per body size while this member leads `term`, see
+    /// [`DLedgerMmapFileStore::append_as_leader`].
+    pub fn append_as_leader<F>(
+        &self,
+        term: i64,
+        body_sizes: &[usize],
+        build_body: F,
+    ) -> DLedgerResult<Vec<(i64, i64)>>
+    where
+        F: FnMut(usize, i64) -> Bytes,
+    {
+        let inner = &self.inner;
+        let appended = {
+            let state = inner.member_state.lock();
+            if state.role != MemberRole::Leader || state.current_term != term {
+                return Err(DLedgerError::NotLeader);
+            }
+            inner.store.append_as_leader(term, body_sizes, build_body)?
+        };
+        inner
+            .ledger_end_tx
+            .send_replace(inner.store.ledger_end_index());
+        inner.update_commit(term);
+        Ok(appended)
+    }
+
+    /// Waits until the entry at `index`, appended in `term`, is committed.
+    pub async fn wait_committed(
+        &self,
+        index: i64,
+        term: i64,
+        timeout: Duration,
+    ) -> DLedgerResult<()> {
+        let inner = &self.inner;
+        let mut committed = inner.committed_tx.subscribe();
+        let mut role = inner.role_tx.subscribe();
+        let wait = async {
+            loop {
+                if *committed.borrow_and_update() >= index {
+                    return if inner.store.term_at(index) == Some(term) {
+                        Ok(())
+                    } else {
+                        Err(DLedgerError::NotLeader)
+                    };
+                }
+                {
+                    let role = role.borrow_and_update();
+                    if role.role != MemberRole::Leader || role.term != term {
+                        return Err(DLedgerError::NotLeader);
+                    }
+                }
+                tokio::select! {
+                    changed = committed.changed() => changed.map_err(|_| DLedgerError::NotLeader)?,
+                    changed = role.changed() => changed.map_err(|_| DLedgerError::NotLeader)?,
+                }
+            }
+        };
+        tokio::time::timeout(timeout, wait)
+            .await
+            .map_err(|_| DLedgerError::WaitAckTimeout)?
+    }
+}
+
+impl ServerInner {
+    async fn serve(inner: Arc<ServerInner>, listener: TcpListener) {
+        let mut connections = JoinSet::new();
+        loop {
+            match listener.accept().await {
+                Ok((stream, addr)) => {
+                    debug!("dledger connection from {}", addr);
+                    let _ = stream.set_nodelay(true);
+                    connections.spawn(ServerInner::serve_connection(inner.clone(), stream));
+                }
+                Err(e) => {
+                    warn!("dledger accept failed: {}", e);
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+            }
+            while connections.try_join_next().is_some() {}
+        }
+    }
+
+    async fn serve_connection(inner: Arc<ServerInner>, mut stream: TcpStream) {
+        loop {
+            let response = match DLedgerMessage::read_from(&mut stream).await {
+                Ok(DLedgerMessage::VoteRequest(request)) => {
+                    DLedgerMessage::VoteResponse(inner.handle_vote(request))
+                }
+                Ok(DLedgerMessage::AppendRequest(request)) => {
+                    DLedgerMessage::AppendResponse(inner.handle_append(request).await)
+                }
+                Ok(message) => {
+                    warn!("unexpected dledger message {:?}", message);
+                    return;
+                }
+                Err(_) => return,
+            };
+            if response.write_to(&mut stream).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    async fn run_ticker(inner: Arc<ServerInner>) {
+        let tick = (inner.config.heartbeat_interval / 5)
+            .clamp(Duration::from_millis(5), Duration::from_millis(100));
+        loop {
+            tokio::time::sleep(tick).await;
+            if *inner.shutdown_tx.borrow() {
+                return;
+            }
+            inner.persist_committed();
+            let role = inner.member_state.lock().role;
+            match role {
+                MemberRole::Leader => inner.check_leader_lease(),
+                MemberRole::Follower | MemberRole::Candidate => {
+                    if Instant::now() >= *inner.election_deadline.lock() {
+                        inner.start_election().await;
+                    }
+                }
+            }
+        }
+    }
+
+    fn leader_lease(&self) -> Duration {
+        self.config.heartbeat_interval * self.config.max_heartbeat_leak
+    }
+
+    /// Schedules the next election `base` plus a random part of a heartbeat interval from now, so
+    /// members rarely run for leader at the same time.
+    fn reset_election_deadline(&self, base: Duration) {
+        let interval = self.config.heartbeat_interval.as_millis().max(1) as u64;
+        let jitter = Duration::from_millis(rand::random_range(0..interval));
+        *self.election_deadline.lock() = Instant::now() + base + jitter;
+    }
+
+    fn publish_role(&self, state: &MemberState) {
+        let role_state = state.role_state();
+        self.role_tx.send_if_modified(|current| {
+            if *current == role_state {
+                return false;
+            }
+            info!(
+                "dledger member {} of group {} changes from {} to {} in term {}, leader {:?}",
+                self.config.self_id,
+                self.config.group,
+                current.role,
+                role_state.role,
+                role_state.term,
+                role_state.leader_id
+            );
+            *current = role_state;
+            true
+        });
+    }
+
+    fn step_down(&self, term: i64, leader_id: Option<String>) {
+        let mut state = self.member_state.lock();
+        state.advance_term(term);
+        if state.current_term == term {
+            state.role = MemberRole::Follower;
+            state.leader_id = leader_id;
+        }
+        self.publish_role(&state);
+        drop(state);
+        self.reset_election_deadline(self.leader_lease());
+    }
+
+    async fn start_election(self: &Arc<Self>) {
+        let request = {
+            let mut state = self.member_state.lock();
+            let term = state.current_term + 1;
+            state.advance_term(term);
+            state.vote(&self.config.self_id);
+            state.role = MemberRole::Candidate;
+            state.leader_id = None;
+            self.publish_role(&state);
+            VoteRequest {
+                group: self.config.group.clone(),
+                candidate_id: self.config.self_id.clone(),
+                term,
+                last_index: self.store.ledger_end_index(),
+                last_term: self.store.ledger_end_term(),
+            }
+        };
+        self.reset_election_deadline(self.leader_lease());
+        let message = DLedgerMessage::VoteRequest(request.clone());
+        let responses = join_all(
+            self.peers
+                .values()
+                .map(|peer| peer.call(&message, self.config.rpc_timeout)),
+        )
+        .await;
+        let mut votes = 1;
+        for response in responses {
+            if let Ok(DLedgerMessage::VoteResponse(response)) = response {
+                if response.term > request.term {
+                    self.step_down(response.term, None);
+                    return;
+                }
+                if response.granted {
+                    votes += 1;
+                }
+            }
+        }
+        if votes >= self.config.quorum() {
+            self.become_leader(request.term);
+        } else {
+            debug!(
+                "dledger member {} got {} of {} votes in term {}",
+                self.config.self_id,
+                votes,
+                self.config.peers.len(),
+                request.term
+            );
+        }
+    }
+
+    fn become_leader(self: &Arc<Self>, term: i64) {
+        {
+            let mut state = self.member_state.lock();
+            if state.role != MemberRole::Candidate || state.current_term != term {
+                return;
+            }
+            // Entries of earlier terms only commit together with one of the current term.
+            let no_op = match self.store.append_as_leader(term, &[0], |_, _| Bytes::new()) {
+                Ok(appended) => appended[0].0,
+                Err(e) => {
+                    error!("append the no-op entry of term {} failed: {}", term, e);
+                    return;
+                }
+            };
+            let now = Instant::now();
+            *self.progress.lock() = self
+                .peers
+                .keys()
+                .map(|id| {
+                    (
+                        id.clone(),
+                        PeerProgress {
+                            next_index: no_op,
+                            match_index: -1,
+                            last_ack: now,
+                        },
+                    )
+                })
+                .collect();
+            state.role = MemberRole::Leader;
+            state.leader_id = Some(self.config.self_id.clone());
+            self.publish_role(&state);
+        }
+        self.ledger_end_tx
+            .send_replace(self.store.ledger_end_index());
+        self.update_commit(term);
+        let mut tasks = self.tasks.lock();
+        tasks.retain(|task| !task.is_finished());
+        for peer_id in self.peers.keys() {
+            tasks.push(tokio::spawn(ServerInner::replicate(
+                self.clone(),
+                peer_id.clone(),
+                term,
+            )));
+        }
+    }
+
+    fn is_leader_of(&self, term: i64) -> bool {
+        let state = self.member_state.lock();
+        state.role == MemberRole::Leader && state.current_term == term
+    }
+
+    /// A leader that has not heard from a majority for a whole lease gives up, so a partitioned
+    /// leader stops taking writes it can never commit.
+    fn check_leader_lease(&self) {
+        let lease = self.leader_lease();
+        let acked = self
+            .progress
+            .lock()
+            .values()
+            .filter(|progress| progress.last_ack.elapsed() < lease)
+            .count();
+        if acked + 1 >= self.config.quorum() {
+            return;
+        }
+        let mut state = self.member_state.lock();
+        if state.role == MemberRole::Leader {
+            warn!(
+                "dledger leader {} lost the quorum in term {}",
+                self.config.self_id, state.current_term
+            );
+            state.role = MemberRole::Candidate;
+            state.leader_id = None;
+            self.publish_role(&state);
+            drop(state);
+            self.reset_election_deadline(Duration::ZERO);
+        }
+    }
+
+    async fn replicate(inner: Arc<ServerInner>, peer_id: String, term: i64) {
+        let peer = &inner.peers[&peer_id];
+        let mut ledger_end = inner.ledger_end_tx.subscribe();
+        let mut committed = inner.committed_tx.subscribe();
+        loop {
+            if !inner.is_leader_of(term) || *inner.shutdown_tx.borrow() {
+                return;
+            }
+            let next_index = match inner.progress.lock().get(&peer_id) {
+                Some(progress) => progress.next_index,
+                None => return,
+            };
+            let prev_index = next_index - 1;
+            ledger_end.borrow_and_update();
+            committed.borrow_and_update();
+            let entries = inner
+                .store
+                .get_entries(next_index, inner.config.max_batch_bytes);
+            let sent = entries.len() as i64;
+            let request = DLedgerMessage::AppendRequest(AppendRequest {
+                group: inner.config.group.clone(),
+                leader_id: inner.config.self_id.clone(),
+                term,
+                prev_index,
+                prev_term: inner.store.term_at(prev_index).unwrap_or(0),
+                commit_index: *inner.committed_tx.borrow(),
+                entries,
+            });
+            let response = match peer.call(&request, inner.config.rpc_timeout).await {
+                Ok(DLedgerMessage::AppendResponse(response)) => response,
+                Ok(message) => {
+                    warn!("unexpected dledger response {:?} from {}", message, peer_id);
+                    tokio::time::sleep(inner.config.heartbeat_interval).await;
+                    continue;
+                }
+                Err(e) => {
+                    debug!("replicate to {} failed: {}", peer_id, e);
+                    tokio::time::sleep(inner.config.heartbeat_interval).await;
+                    continue;
+                }
+            };
+            if response.term > term {
+                inner.step_down(response.term, None);
+                return;
+            }
+            let retry_at = {
+                let mut progress = inner.progress.lock();
+                let Some(progress) = progress.get_mut(&peer_id) else {
+                    return;
+                };
+                progress.last_ack = Instant::now();
+                if response.success {
+                    progress.match_index = prev_index + sent;
+                    progress.next_index = progress.match_index + 1;
+                    None
+                } else {
+                    progress.next_index = (response.end_index + 1)
+                        .min(prev_index)
+                        .max(inner.store.first_index());
+                    Some(progress.next_index)
+                }
+            };
+            match retry_at {
+                // The follower misses or disagrees on entries, go back and retry right away.
+                Some(retry_at) if retry_at < next_index => continue,
+                Some(_) => {
+                    tokio::time::sleep(inner.config.heartbeat_interval).await;
+                    continue;
+                }
+                None if sent > 0 => {
+                    inner.update_commit(term);
+                    continue;
+                }
+                None => {}
+            }
+            tokio::select! {
+                _ = ledger_end.changed() => {}
+                _ = committed.changed() => {}
+                _ = tokio::time::sleep(inner.config.heartbeat_interval) => {}
+            }
+        }
+    }
+
+    /// Commits the highest entry of `term` a majority stored.
+    fn update_commit(&self, term: i64) {
+        let mut matched: Vec<i64> = self
+            .progress
+            .lock()
+            .values()
+            .map(|progress| progress.match_index)
+            .collect();
+        matched.push(self.store.ledger_end_index());
+        matched.sort_unstable_by(|a, b| b.cmp(a));
+        let Some(&index) = matched.get(self.config.quorum() - 1) else {
+            return;
+        };
+        if self.store.term_at(index) == Some(term) {
+            self.advance_commit(index);
+        }
+    }
+
+    fn advance_commit(&self, index: i64) {
+        let Some(end_pos) = self.store.entry_end_pos(index) else {
+            return;
+        };
+        self.committed_tx.send_if_modified(|committed| {
+            if index <= *committed {
+                return false;
+            }
+            self.committed_end_pos.store(end_pos, Ordering::Release);
+            *committed = index;
+            true
+        });
+    }
+
+    fn persist_committed(&self) {
+        let committed = *self.committed_tx.borrow();
+        if self.persisted_committed.swap(committed, Ordering::AcqRel) == committed {
+            return;
+        }
+        let checkpoint = self.meta_dir.join(CHECKPOINT_FILE);
+        if let Err(e) = string_to_file(
+            &format!("{COMMITTED_INDEX_KEY}={committed}\n"),
+            &checkpoint.to_string_lossy(),
+        ) {
+            error!("persist dledger checkpoint failed: {}", e);
+        }
+    }
+
+    fn handle_vote(&self, request: VoteRequest) -> VoteResponse {
+        let mut state = self.member_state.lock();
+        let rejected = VoteResponse {
+            term: state.current_term,
+            granted: false,
+        };
+        if request.group != self.config.group || request.term < state.current_term {
+            return rejected;
+        }
+        if request.term > state.current_term {
+            state.advance_term(request.term);
+            state.role = MemberRole::Follower;
+            state.leader_id = None;
+            self.publish_role(&state);
+        }
+        let last_term = self.store.ledger_end_term();
+        let up_to_date = request.last_term > last_term
+            || (request.last_term == last_term
+                && request.last_index >= self.store.ledger_end_index());
+        let can_vote = state
+            .vote_for
+            .as_ref()
+            .map_or(true, |vote| *vote == request.candidate_id);
+        if !up_to_date || !can_vote {
+            return VoteResponse {
+                term: state.current_term,
+                granted: false,
+            };
+        }
+        state.vote(&request.candidate_id);
+        drop(state);
+        self.reset_election_deadline(self.leader_lease());
+        VoteResponse {
+            term: request.term,
+            granted: true,
+        }
+    }
+
+    async fn handle_append(&self, request: AppendRequest) -> AppendResponse {
+        {
+            let mut state = self.member_state.lock();
+            if request.group != self.config.group || request.term < state.current_term {
+                return AppendResponse {
+                    term: state.current_term,
+                    success: false,
+                    end_index: self.store.ledger_end_index(),
+                };
+            }
+            state.advance_term(request.term);
+            state.role = MemberRole::Follower;
+            state.leader_id = Some(request.leader_id.clone());
+            self.publish_role(&state);
+        }
+        self.reset_election_deadline(self.leader_lease());
+
+        let _append = self.follower_append_lock.lock().await;
+        let reject = |end_index: i64| AppendResponse {
+            term: request.term,
+            success: false,
+            end_index,
+        };
+        let committed = *self.committed_tx.borrow();
+        let prev_index = request.prev_index;
+        if prev_index > self.store.ledger_end_index() {
+            return reject(self.store.ledger_end_index());
+        }
+        if let Some(prev_term) = self.store.term_at(prev_index) {
+            if prev_term != request.prev_term {
+                if prev_index <= committed {
+                    error!(
+                        "leader {} conflicts with committed entry {}",
+                        request.leader_id, prev_index
+                    );
+                    return reject(committed);
+                }
+                self.store.truncate(prev_index);
+                return reject(prev_index - 1);
+            }
+        }
+        let mut last = prev_index;
+        for entry in &request.entries {
+            let Some(header) = EntryHeader::decode(entry.as_ref()) else {
+                return reject(last);
+            };
+            if header.index != last + 1 {
+                return reject(last);
+            }
+            if header.index <= self.store.ledger_end_index() {
+                if self.store.term_at(header.index) == Some(header.term) {
+                    last = header.index;
+                    continue;
+                }
+                if header.index <= committed {
+                    error!(
+                        "leader {} conflicts with committed entry {}",
+                        request.leader_id, header.index
+                    );
+                    return reject(committed);
+                }
+                self.store.truncate(header.index);
+            }
+            if let Err(e) = self.store.append_as_follower(entry.as_ref()) {
+                warn!("append entry {} failed: {}", header.index, e);
+                return reject(last.min(self.store.ledger_end_index()));
+            }
+            last = header.index;
+        }
+        let commit_index = request.commit_index.min(last);
+        if commit_index > committed {
+            self.advance_commit(commit_index);
+        }
+        AppendResponse {
+            term: request.term,
+            success: true,
+            end_index: last,
+        }
+    }
+}
+
+impl PeerClient {
+    async fn call(
+        &self,
+        message: &DLedgerMessage,
+        timeout: Duration,
+    ) -> DLedgerResult<DLedgerMessage> {
+        let mut stream = self.stream.lock().await;
+        let call = async {
+            if stream.is_none() {
+                let connected = TcpStream::connect(&self.addr).await?;
+                connected.set_nodelay(true)?;
+                *stream = Some(connected);
+            }
+            let connected = stream.as_mut().unwrap();
+            message.write_to(connected).await?;
+            DLedgerMessage::read_from(connected).await
+        };
+        let result = match tokio::time::timeout(timeout, call).await {
+            Ok(result) => result,
+            Err(_) => Err(DLedgerError::Protocol(format!(
+                "request to {} timed out",
+                self.addr
+            ))),
+        };
+        if result.is_err() {
+            *stream = None;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    const FILE_SIZE: usize = 64 * 1024;
+
+    struct Member {
+        server: DLedgerServer,
+        _dir: tempfile::TempDir,
+    }
+
+    fn free_port() -> u16 {
+        std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    fn start_member(self_id: &str, peers: &BTreeMap<String, String>) -> Member {
+        let dir = tempfile::tempdir().unwrap();
+        let store_path = dir.path().join(format!("dledger-{self_id}")).join("data");
+        let config = DLedgerConfig {
+            group: "broker-a".to_string(),
+            self_id: self_id.to_string(),
+            peers: peers.clone(),
+            store_path: store_path.to_string_lossy().to_string(),
+            mapped_file_size: FILE_SIZE,
+            heartbeat_interval: Duration::from_millis(50),
+            max_heartbeat_leak: 3,
+            max_wait_ack: Duration::from_secs(2),
+            rpc_timeout: Duration::from_millis(200),
+            max_batch_bytes: 1024,
+        };
+        let mut queue = MappedFileQueue::new(config.store_path.clone(), FILE_SIZE as u64, None);
+        queue.load();
+        let server = DLedgerServer::new(config, ArcMut::new(queue));
+        server.recover();
+        server.start().unwrap();
+        Member { server, _dir: dir }
+    }
+
+    async fn wait_for_leader(members: &[&Member]) -> usize {
+        for _ in 0..200 {
+            let leaders: Vec<usize> = (0..members.len())
+                .filter(|i| members[*i].server.is_leader())
+                .collect();
+            if leaders.len() == 1 {
+                return leaders[0];
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        panic!("no leader elected");
+    }
+
+    async fn append(server: &DLedgerServer, body: &'static [u8]) -> i64 {
+        let term = server.role_state().term;
+        let (index, _) = server
+            .append_as_leader(term, &[body.len()], |_, _| Bytes::from_static(body))
+            .unwrap()[0];
+        server
+            .wait_committed(index, term, Duration::from_secs(2))
+            .await
+            .unwrap();
+        index
+    }
+
+    async fn wait_committed_on(member: &Member, index: i64) {
+        for _ in 0..200 {
+            if member.server.committed_index() >= index {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!(
+            "entry {index} not committed on {}",
+            member.server.config().self_id
+        );
+    }
+
+    #[tokio::test]
+    async fn leader_replicates_and_fails_over() {
+        let peers: BTreeMap<String, String> = ["n0", "n1", "n2"]
+            .iter()
+            .map(|id| (id.to_string(), format!("127.0.0.1:{}", free_port())))
+            .collect();
+        let members: Vec<Member> = ["n0", "n1", "n2"]
+            .iter()
+            .map(|id| start_member(id, &peers))
+            .collect();
+        let all: Vec<&Member> = members.iter().collect();
+
+        let leader = wait_for_leader(&all).await;
+        let follower = all[(leader + 1) % 3].server.role_state();
+        assert_eq!(follower.role, MemberRole::Follower);
+        assert_eq!(
+            follower.leader_id.as_deref(),
+            Some(all[leader].server.config().self_id.as_str())
+        );
+
+        let mut last = 0;
+        for body in [b"first", b"other", b"third"] {
+            last = append(&all[leader].server, body).await;
+        }
+        for member in &all {
+            wait_committed_on(member, last).await;
+            assert_eq!(
+                member.server.committed_end_pos(),
+                all[leader].server.committed_end_pos()
+            );
+        }
+
+        all[leader].server.shutdown();
+        let survivors: Vec<&Member> = (0..3).filter(|i| *i != leader).map(|i| all[i]).collect();
+        let new_leader = wait_for_leader(&survivors).await;
+        let index = append(&survivors[new_leader].server, b"after failover").await;
+        assert!(index > last);
+        for member in &survivors {
+            wait_committed_on(member, index).await;
+        }
+        for member in &members {
+            member.server.shutdown();
+        }
+    }
+
+    #[tokio::test]
+    async fn single_member_commits_alone() {
+        let peers = BTreeMap::from([("n0".to_string(), format!("127.0.0.1:{}", free_port()))]);
+        let member = start_member("n0", &peers);
+        wait_for_leader(&[&member]).await;
+        let index = append(&member.server, b"alone").await;
+        assert_eq!(member.server.committed_index(), index);
+        member.server.shutdown();
+    }
+}
@@ -0,0 +1,143 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::fmt;
+use std::path::Path;
+
+use rocketmq_common::common::mix_all::string_to_properties;
+use rocketmq_common::FileUtils::file_to_string;
+use rocketmq_common::FileUtils::string_to_file;
+use tracing::error;
+
+const TERM_FILE: &str = "currterm";
+const CURR_TERM_KEY: &str = "currTerm";
+const VOTE_LEADER_KEY: &str = "voteLeader";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemberRole {
+    Follower,
+    Candidate,
+    Leader,
+}
+
+impl fmt::Display for MemberRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MemberRole::Follower => write!(f, "FOLLOWER"),
+            MemberRole::Candidate => write!(f, "CANDIDATE"),
+            MemberRole::Leader => write!(f, "LEADER"),
+        }
+    }
+}
+
+/// The role of a member as seen by the commit log and the broker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoleState {
+    pub role: MemberRole,
+    pub term: i64,
+    pub leader_id: Option<String>,
+}
+
+/// Term, vote and role of this member. Term and vote survive restarts so a member never votes
+/// twice in the same term.
+pub(crate) struct MemberState {
+    term_file: String,
+    pub current_term: i64,
+    pub vote_for: Option<String>,
+    pub role: MemberRole,
+    pub leader_id: Option<String>,
+}
+
+impl MemberState {
+    pub fn load(meta_dir: &Path) -> Self {
+        let term_file = meta_dir.join(TERM_FILE).to_string_lossy().to_string();
+        let properties = file_to_string(&term_file)
+            .ok()
+            .and_then(|content| string_to_properties(&content))
+            .unwrap_or_default();
+        MemberState {
+            term_file,
+            current_term: properties
+                .get(CURR_TERM_KEY)
+                .and_then(|term| term.parse().ok())
+                .unwrap_or(0),
+            vote_for: properties
+                .get(VOTE_LEADER_KEY)
+                .filter(|vote| !vote.is_empty())
+                .map(|vote| vote.to_string()),
+            role: MemberRole::Candidate,
+            leader_id: None,
+        }
+    }
+
+    pub fn role_state(&self) -> RoleState {
+        RoleState {
+            role: self.role,
+            term: self.current_term,
+            leader_id: self.leader_id.clone(),
+        }
+    }
+
+    /// Moves to a newer term, forgetting the vote of the old one.
+    pub fn advance_term(&mut self, term: i64) {
+        if term > self.current_term {
+            self.current_term = term;
+            self.vote_for = None;
+            self.persist();
+        }
+    }
+
+    pub fn vote(&mut self, candidate: &str) {
+        self.vote_for = Some(candidate.to_string());
+        self.persist();
+    }
+
+    fn persist(&self) {
+        let content = format!(
+            "{}={}\n{}={}\n",
+            CURR_TERM_KEY,
+            self.current_term,
+            VOTE_LEADER_KEY,
+            self.vote_for.as_deref().unwrap_or_default()
+        );
+        if let Err(e) = string_to_file(&content, &self.term_file) {
+            error!("persist dledger term to {} failed: {}", self.term_file, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn term_and_vote_survive_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut state = MemberState::load(dir.path());
+        assert_eq!(state.current_term, 0);
+        assert_eq!(state.role, MemberRole::Candidate);
+
+        state.advance_term(3);
+        state.vote("n1");
+        let reloaded = MemberState::load(dir.path());
+        assert_eq!(reloaded.current_term, 3);
+        assert_eq!(reloaded.vote_for.as_deref(), Some("n1"));
+
+        state.advance_term(4);
+        assert_eq!(MemberState::load(dir.path()).vote_for, None);
+    }
+}
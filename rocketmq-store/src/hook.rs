@@ -14,5 +14,7 @@
  * See the License for the specific language governing permissions and
  * limitations under the License.
  */
+pub mod delay_level_check_hook;
+pub mod message_size_check_hook;
 pub mod put_message_hook;
 pub mod send_message_back_hook;
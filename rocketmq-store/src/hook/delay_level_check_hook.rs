@@ -0,0 +1,84 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use rocketmq_common::common::message::message_ext_broker_inner::MessageExtBrokerInner;
+use rocketmq_common::common::message::MessageConst;
+use tracing::warn;
+
+use crate::base::message_result::PutMessageResult;
+use crate::base::message_status_enum::PutMessageStatus;
+use crate::hook::put_message_hook::PutMessageHook;
+
+/// Rejects messages whose delay level is not a non-negative number.
+///
+/// Levels above the highest configured one are legal, they are lowered to it when the message
+/// is scheduled.
+pub struct DelayLevelCheckHook;
+
+impl PutMessageHook for DelayLevelCheckHook {
+    fn hook_name(&self) -> String {
+        "delayLevelCheck".to_string()
+    }
+
+    fn execute_before_put_message(
+        &self,
+        msg: &mut MessageExtBrokerInner,
+    ) -> Option<PutMessageResult> {
+        let delay_level = msg.property(MessageConst::PROPERTY_DELAY_TIME_LEVEL)?;
+        if delay_level.parse::<i32>().is_ok_and(|level| level >= 0) {
+            return None;
+        }
+        warn!(
+            "putMessage message topic[{}] has illegal delay level {}",
+            msg.topic(),
+            delay_level
+        );
+        Some(PutMessageResult::new_default(
+            PutMessageStatus::MessageIllegal,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cheetah_string::CheetahString;
+
+    use super::*;
+
+    #[test]
+    fn only_non_negative_delay_levels_are_legal() {
+        let mut msg = MessageExtBrokerInner::default();
+        assert!(DelayLevelCheckHook
+            .execute_before_put_message(&mut msg)
+            .is_none());
+
+        for (delay_level, legal) in [
+            ("0", true),
+            ("3", true),
+            ("99", true),
+            ("-1", false),
+            ("1s", false),
+        ] {
+            msg.message_ext_inner.message.properties.insert(
+                CheetahString::from_static_str(MessageConst::PROPERTY_DELAY_TIME_LEVEL),
+                CheetahString::from_static_str(delay_level),
+            );
+            let result = DelayLevelCheckHook.execute_before_put_message(&mut msg);
+            assert_eq!(result.is_none(), legal, "delay level {delay_level}");
+        }
+    }
+}
@@ -0,0 +1,111 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use rocketmq_common::common::message::message_ext_broker_inner::MessageExtBrokerInner;
+use tracing::warn;
+
+use crate::base::message_result::PutMessageResult;
+use crate::base::message_status_enum::PutMessageStatus;
+use crate::config::message_store_config::MessageStoreConfig;
+use crate::hook::put_message_hook::PutMessageHook;
+
+/// Rejects messages too large to be stored before they reach the commit log.
+pub struct MessageSizeCheckHook {
+    max_message_size: usize,
+}
+
+impl MessageSizeCheckHook {
+    pub fn new(message_store_config: &MessageStoreConfig) -> Self {
+        Self {
+            max_message_size: message_store_config.max_message_size.max(0) as usize,
+        }
+    }
+}
+
+impl PutMessageHook for MessageSizeCheckHook {
+    fn hook_name(&self) -> String {
+        "messageSizeCheck".to_string()
+    }
+
+    fn execute_before_put_message(
+        &self,
+        msg: &mut MessageExtBrokerInner,
+    ) -> Option<PutMessageResult> {
+        let body_length = msg
+            .message_ext_inner
+            .message
+            .body
+            .as_ref()
+            .map_or(0, |body| body.len());
+        if body_length > self.max_message_size {
+            warn!(
+                "putMessage message topic[{}] body size {} exceeds maxMessageSize {}",
+                msg.topic(),
+                body_length,
+                self.max_message_size
+            );
+            return Some(PutMessageResult::new_default(
+                PutMessageStatus::MessageIllegal,
+            ));
+        }
+        if msg.properties_string.len() > i16::MAX as usize {
+            warn!(
+                "putMessage message topic[{}] properties length too long {}",
+                msg.topic(),
+                msg.properties_string.len()
+            );
+            return Some(PutMessageResult::new_default(
+                PutMessageStatus::PropertiesSizeExceeded,
+            ));
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+    use cheetah_string::CheetahString;
+
+    use super::*;
+
+    #[test]
+    fn oversized_body_or_properties_are_rejected() {
+        let hook = MessageSizeCheckHook::new(&MessageStoreConfig {
+            max_message_size: 4,
+            ..MessageStoreConfig::default()
+        });
+        let mut msg = MessageExtBrokerInner::default();
+        msg.message_ext_inner.message.body = Some(Bytes::from_static(b"1234"));
+        assert!(hook.execute_before_put_message(&mut msg).is_none());
+
+        msg.message_ext_inner.message.body = Some(Bytes::from_static(b"12345"));
+        let result = hook.execute_before_put_message(&mut msg).unwrap();
+        assert_eq!(
+            result.put_message_status(),
+            PutMessageStatus::MessageIllegal
+        );
+
+        msg.message_ext_inner.message.body = None;
+        msg.properties_string = CheetahString::from_string("k".repeat(i16::MAX as usize + 1));
+        let result = hook.execute_before_put_message(&mut msg).unwrap();
+        assert_eq!(
+            result.put_message_status(),
+            PutMessageStatus::PropertiesSizeExceeded
+        );
+    }
+}
@@ -21,6 +21,7 @@
 pub mod base;
 pub mod config;
 pub mod consume_queue;
+pub mod dledger;
 pub mod filter;
 pub mod ha;
 pub mod hook;
@@ -51,6 +51,7 @@ use rocketmq_common::MessageDecoder::SYSFLAG_POSITION;
 use rocketmq_common::TimeUtils::get_current_millis;
 use rocketmq_common::UtilAll::time_millis_to_human_string;
 use rocketmq_rust::ArcMut;
+use tokio::runtime::Handle;
 use tokio::time::Instant;
 use tracing::error;
 use tracing::info;
@@ -65,12 +66,14 @@ use crate::base::message_result::PutMessageResult;
 use crate::base::message_status_enum::AppendMessageStatus;
 use crate::base::message_status_enum::PutMessageStatus;
 use crate::base::put_message_context::PutMessageContext;
+use crate::base::put_message_lock::PutMessageLock;
 use crate::base::select_result::SelectMappedBufferResult;
 use crate::base::store_checkpoint::StoreCheckpoint;
 use crate::base::swappable::Swappable;
 use crate::base::topic_queue_lock::TopicQueueLock;
 use crate::config::message_store_config::MessageStoreConfig;
 use crate::consume_queue::mapped_file_queue::MappedFileQueue;
+use crate::dledger::dledger_commit_log::DLedgerCommitLog;
 use crate::log_file::cold_data_check_service::ColdDataCheckService;
 use crate::log_file::flush_manager_impl::defalut_flush_manager::DefaultFlushManager;
 use crate::log_file::mapped_file::default_mapped_file_impl::DefaultMappedFile;
@@ -92,6 +95,8 @@ pub const BLANK_MAGIC_CODE: i32 = -875286124;
 // PROPERTY_SEPARATOR]
 pub const CRC32_RESERVED_LEN: i32 = (MessageConst::PROPERTY_CRC32.len() + 1 + 10 + 1) as i32;
 
+const FLUSH_RETRY_TIMES_OVER: usize = 10;
+
 struct PutMessageThreadLocal {
     encoder: RefCell<Option<MessageExtEncoder>>,
     key: RefCell<String>,
@@ -195,13 +200,16 @@ pub struct CommitLog {
     confirm_offset: i64,
     store_checkpoint: Arc<StoreCheckpoint>,
     append_message_callback: Arc<DefaultAppendMessageCallback>,
-    put_message_lock: Arc<tokio::sync::Mutex<()>>,
+    put_message_lock: Arc<PutMessageLock>,
     topic_queue_lock: Arc<TopicQueueLock>,
     topic_config_table: Arc<parking_lot::Mutex<HashMap<CheetahString, TopicConfig>>>,
     consume_queue_store: ConsumeQueueStore,
     flush_manager: Arc<tokio::sync::Mutex<DefaultFlushManager>>,
     begin_time_in_lock: Arc<AtomicU64>,
     cold_data_check_service: Arc<ColdDataCheckService>,
+    flush_runtime: Option<Handle>,
+    // replicates the messages through a raft group when enableDLegerCommitLog is set
+    dledger_commit_log: Option<Arc<DLedgerCommitLog>>,
 }
 
 impl CommitLog {
@@ -214,7 +222,11 @@ impl CommitLog {
         consume_queue_store: ConsumeQueueStore,
     ) -> Self {
         let enabled_append_prop_crc = message_store_config.enabled_append_prop_crc;
-        let store_path = message_store_config.get_store_path_commit_log();
+        let store_path = if message_store_config.enable_dledger_commit_log {
+            message_store_config.get_store_path_dledger_commit_log()
+        } else {
+            message_store_config.get_store_path_commit_log()
+        };
         let mapped_file_size = message_store_config.mapped_file_size_commit_log;
         let mapped_file_queue = ArcMut::new(MappedFileQueue::new(
             store_path,
@@ -234,7 +246,9 @@ impl CommitLog {
                 message_store_config.clone(),
                 topic_config_table.clone(),
             )),
-            put_message_lock: Arc::new(Default::default()),
+            put_message_lock: Arc::new(PutMessageLock::new(
+                message_store_config.use_reentrant_lock_when_put_message,
+            )),
             topic_queue_lock: Arc::new(TopicQueueLock::new(
                 message_store_config.topic_queue_lock_num,
             )),
@@ -247,6 +261,8 @@ impl CommitLog {
             ))),
             begin_time_in_lock: Arc::new(AtomicU64::new(0)),
             cold_data_check_service: Arc::new(Default::default()),
+            flush_runtime: None,
+            dledger_commit_log: None,
         }
     }
 }
@@ -257,23 +273,75 @@ impl CommitLog {
         let result = self.mapped_file_queue.load();
         self.mapped_file_queue.check_self();
         info!("load commit log {}", if result { "OK" } else { "Failed" });
+        if result && self.message_store_config.enable_dledger_commit_log {
+            match DLedgerCommitLog::new(
+                self.message_store_config.clone(),
+                self.mapped_file_queue.clone(),
+            ) {
+                Ok(dledger_commit_log) => {
+                    self.dledger_commit_log = Some(Arc::new(dledger_commit_log));
+                }
+                Err(e) => {
+                    error!("load dledger commit log failed: {}", e);
+                    return false;
+                }
+            }
+        }
         result
     }
 
+    pub fn dledger_commit_log(&self) -> Option<&Arc<DLedgerCommitLog>> {
+        self.dledger_commit_log.as_ref()
+    }
+
+    /// Runs the flush and commit services on `runtime` instead of the runtime that starts the
+    /// commit log. Only takes effect for a commit log that has not been started yet.
+    pub fn set_flush_runtime(&mut self, runtime: Handle) {
+        self.flush_runtime = Some(runtime);
+    }
+
     pub fn start(&mut self) {
         let flush_manager = self.flush_manager.clone();
-        tokio::spawn(async move {
+        // The services spawn their loops from this task, so they end up on its runtime.
+        let start_flush_manager = async move {
             let flush_manager_weak = Arc::downgrade(&flush_manager);
             let mut guard = flush_manager.lock().await;
             if let Some(service) = guard.commit_real_time_service_mut() {
                 service.set_flush_manager(Some(flush_manager_weak))
             }
             guard.start();
-        });
+        };
+        match self.flush_runtime {
+            Some(ref runtime) => runtime.spawn(start_flush_manager),
+            None => tokio::spawn(start_flush_manager),
+        };
+        if let Some(dledger_commit_log) = self.dledger_commit_log.as_ref() {
+            if let Err(e) = dledger_commit_log.start() {
+                error!("start dledger commit log failed: {}", e);
+            }
+        }
     }
 
-    pub fn shutdown(&mut self) {
-        error!("shutdown commit log unimplemented");
+    /// Stops the commit log, flushing what the flush service has not written to disk yet.
+    /// Returns whether every written message reached the disk.
+    pub fn shutdown(&mut self) -> bool {
+        if let Some(dledger_commit_log) = self.dledger_commit_log.as_ref() {
+            dledger_commit_log.shutdown();
+            return true;
+        }
+        // a flush covers one mapped file, it reports true once nothing is left to flush
+        let _ = (0..FLUSH_RETRY_TIMES_OVER).any(|_| self.mapped_file_queue.flush(0));
+        self.mapped_file_queue.remain_how_many_data_to_flush() == 0
+    }
+
+    /// Flushes all written messages to disk, returns the offset flushed up to.
+    pub fn flush(&self) -> i64 {
+        self.mapped_file_queue.flush(0);
+        self.mapped_file_queue.get_flushed_where()
+    }
+
+    pub fn get_flushed_where(&self) -> i64 {
+        self.mapped_file_queue.get_flushed_where()
     }
 
     pub fn destroy(&mut self) {
@@ -392,6 +460,28 @@ impl CommitLog {
             .message_ext_inner
             .store_timestamp = time_utils::get_current_millis() as i64;
 
+        if let Some(dledger_commit_log) = self.dledger_commit_log.clone() {
+            let appended =
+                dledger_commit_log.append_messages(&mut msg_batch, &mut put_message_context);
+            drop(lock);
+            self.begin_time_in_lock
+                .store(0, std::sync::atomic::Ordering::Release);
+            return match appended {
+                Ok(appended) => {
+                    self.increase_offset(
+                        &msg_batch.message_ext_broker_inner,
+                        appended.append_result.msg_num as i16,
+                    );
+                    drop(topic_queue_lock);
+                    let status = dledger_commit_log
+                        .wait_committed(appended.index, appended.term)
+                        .await;
+                    PutMessageResult::new_append_result(status, Some(appended.append_result))
+                }
+                Err(status) => PutMessageResult::new_default(status),
+            };
+        }
+
         if mapped_file.is_none() || mapped_file.as_ref().unwrap().is_full() {
             mapped_file = self
                 .mapped_file_queue
@@ -592,6 +682,25 @@ impl CommitLog {
             msg.message_ext_inner.store_timestamp = begin_lock_timestamp as i64;
         }
 
+        if let Some(dledger_commit_log) = self.dledger_commit_log.clone() {
+            let message_num = get_message_num(&self.topic_config_table, &msg);
+            let appended = dledger_commit_log.append_message(&mut msg, message_num);
+            drop(lock);
+            self.begin_time_in_lock
+                .store(0, std::sync::atomic::Ordering::Release);
+            return match appended {
+                Ok(appended) => {
+                    self.increase_offset(&msg, message_num);
+                    drop(topic_queue_lock);
+                    let status = dledger_commit_log
+                        .wait_committed(appended.index, appended.term)
+                        .await;
+                    PutMessageResult::new_append_result(status, Some(appended.append_result))
+                }
+                Err(status) => PutMessageResult::new_default(status),
+            };
+        }
+
         if mapped_file.is_none() || mapped_file.as_ref().unwrap().is_full() {
             mapped_file = self
                 .mapped_file_queue
@@ -744,19 +853,14 @@ impl CommitLog {
             }
         });
 
-        match disk_flush_handle.await {
-            Ok(status) => {
-                put_message_result.set_put_message_status(status);
-                if status == PutMessageStatus::PutOk {
-                    if let Ok(replica_status) = replica_result_handle.await {
-                        put_message_result.set_put_message_status(replica_status);
-                    }
-                }
-            }
-            Err(error) => {
-                put_message_result.set_put_message_status(PutMessageStatus::FlushDiskTimeout);
-            }
-        }
+        let flush_status = disk_flush_handle
+            .await
+            .unwrap_or(PutMessageStatus::FlushDiskTimeout);
+        let replica_status = replica_result_handle
+            .await
+            .unwrap_or(PutMessageStatus::FlushSlaveTimeout);
+        put_message_result
+            .set_put_message_status(merge_flush_and_replica_status(flush_status, replica_status));
 
         put_message_result
     }
@@ -770,9 +874,14 @@ impl CommitLog {
             return PutMessageStatus::PutOk;
         }
 
-        //HA service to do unimplemented
-
-        PutMessageStatus::PutOk
+        // No HA service replicates the commit log yet, so no slave can acknowledge the message.
+        // Tell the producer it is only stored on the master instead of answering PutOk.
+        warn!(
+            "{} replicas required for offset {}, but no slave is available",
+            need_ack_nums,
+            put_message_result.wrote_offset + put_message_result.wrote_bytes as i64
+        );
+        PutMessageStatus::SlaveNotAvailable
     }
 
     async fn handle_disk_flush(
@@ -832,6 +941,10 @@ impl CommitLog {
         max_phy_offset_of_consume_queue: i64,
         mut message_store: ArcMut<LocalFileMessageStore>,
     ) {
+        if self.dledger_commit_log.is_some() {
+            self.recover_dledger(max_phy_offset_of_consume_queue, message_store);
+            return;
+        }
         let check_crc_on_recover = self.message_store_config.check_crc_on_recover;
         let check_dup_info = self.message_store_config.duplication_enable;
         let message_store_config = self.message_store_config.clone();
@@ -969,6 +1082,9 @@ impl CommitLog {
 
     //Fetch and compute the newest confirmOffset.
     pub fn get_confirm_offset(&self) -> i64 {
+        if let Some(dledger_commit_log) = self.dledger_commit_log.as_ref() {
+            return dledger_commit_log.get_confirm_offset();
+        }
         if self.broker_config.enable_controller_mode {
             unimplemented!()
         } else if self.broker_config.duplication_enable {
@@ -982,6 +1098,10 @@ impl CommitLog {
         max_phy_offset_of_consume_queue: i64,
         mut message_store: ArcMut<LocalFileMessageStore>,
     ) {
+        if self.dledger_commit_log.is_some() {
+            self.recover_dledger(max_phy_offset_of_consume_queue, message_store);
+            return;
+        }
         let check_crc_on_recover = self.message_store_config.check_crc_on_recover;
         let check_dup_info = self.message_store_config.duplication_enable;
         //let message_store_config = self.message_store_config.clone();
@@ -1136,6 +1256,31 @@ impl CommitLog {
         }
     }
 
+    /// The raft log rebuilds itself from the data files, uncommitted entries stay in place as the
+    /// next leader decides about them. Only the consume queues need to drop what is gone.
+    fn recover_dledger(
+        &mut self,
+        max_phy_offset_of_consume_queue: i64,
+        mut message_store: ArcMut<LocalFileMessageStore>,
+    ) {
+        let dledger_commit_log = self.dledger_commit_log.clone().unwrap();
+        dledger_commit_log.recover();
+        if self.mapped_file_queue.get_mapped_files().read().is_empty() {
+            warn!("The dledger commitlog files are deleted, and delete the consume queue files");
+            message_store.consume_queue_store_mut().destroy();
+            message_store.consume_queue_store_mut().load_after_destroy();
+            return;
+        }
+        let ledger_end_pos = dledger_commit_log.server().ledger_end_pos();
+        if max_phy_offset_of_consume_queue >= ledger_end_pos {
+            warn!(
+                "maxPhyOffsetOfConsumeQueue({}) >= ledgerEndPos({}), truncate dirty logic files",
+                max_phy_offset_of_consume_queue, ledger_end_pos
+            );
+            message_store.truncate_dirty_logic_files(ledger_end_pos);
+        }
+    }
+
     pub fn get_max_offset(&self) -> i64 {
         self.mapped_file_queue.get_max_offset()
     }
@@ -1493,6 +1638,19 @@ fn set_batch_size_if_needed(
     }
 }
 
+/// A message that failed to flush reports the flush status, otherwise it reports whether the
+/// slaves acknowledged it, so the producer can tell a partially stored message from a stored one.
+fn merge_flush_and_replica_status(
+    flush_status: PutMessageStatus,
+    replica_status: PutMessageStatus,
+) -> PutMessageStatus {
+    if flush_status != PutMessageStatus::PutOk {
+        flush_status
+    } else {
+        replica_status
+    }
+}
+
 fn is_mapped_file_matched_recover(
     message_store_config: &Arc<MessageStoreConfig>,
     mapped_file: &DefaultMappedFile,
@@ -1562,3 +1720,30 @@ impl Swappable for CommitLog {
         todo!()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flush_failure_wins_over_replica_status() {
+        assert_eq!(
+            merge_flush_and_replica_status(
+                PutMessageStatus::FlushDiskTimeout,
+                PutMessageStatus::SlaveNotAvailable
+            ),
+            PutMessageStatus::FlushDiskTimeout
+        );
+        assert_eq!(
+            merge_flush_and_replica_status(
+                PutMessageStatus::PutOk,
+                PutMessageStatus::FlushSlaveTimeout
+            ),
+            PutMessageStatus::FlushSlaveTimeout
+        );
+        assert_eq!(
+            merge_flush_and_replica_status(PutMessageStatus::PutOk, PutMessageStatus::PutOk),
+            PutMessageStatus::PutOk
+        );
+    }
+}
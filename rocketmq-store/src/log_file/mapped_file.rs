@@ -31,6 +31,7 @@ use crate::base::transient_store_pool::TransientStorePool;
 use crate::config::flush_disk_type::FlushDiskType;
 
 pub mod default_mapped_file_impl;
+pub mod platform;
 pub(crate) mod reference_resource;
 mod reference_resource_counter;
 
@@ -49,6 +49,7 @@ use crate::base::put_message_context::PutMessageContext;
 use crate::base::select_result::SelectMappedBufferResult;
 use crate::base::transient_store_pool::TransientStorePool;
 use crate::config::flush_disk_type::FlushDiskType;
+use crate::log_file::mapped_file::platform::MappedMemory;
 use crate::log_file::mapped_file::reference_resource::ReferenceResource;
 use crate::log_file::mapped_file::reference_resource_counter::ReferenceResourceCounter;
 use crate::log_file::mapped_file::MappedFile;
@@ -695,19 +696,67 @@ impl MappedFile for DefaultMappedFile {
         self.committed_position.load(Ordering::Acquire)
     }
 
-    #[inline]
     fn mlock(&self) {
-        todo!()
+        let begin_time = get_current_millis();
+        let lock_result = self.mmapped_file.lock();
+        let advise_result = self.mmapped_file.will_need(0, self.file_size as usize);
+        info!(
+            "mlock {} {} {} ret = {:?} advise ret = {:?} time consuming = {}",
+            self.file_name,
+            self.file_from_offset,
+            self.file_size,
+            lock_result,
+            advise_result,
+            get_current_millis() - begin_time
+        );
     }
 
-    #[inline]
     fn munlock(&self) {
-        todo!()
+        let begin_time = get_current_millis();
+        let unlock_result = self.mmapped_file.unlock();
+        info!(
+            "munlock {} {} {} ret = {:?} time consuming = {}",
+            self.file_name,
+            self.file_from_offset,
+            self.file_size,
+            unlock_result,
+            get_current_millis() - begin_time
+        );
     }
 
-    #[inline]
     fn warm_mapped_file(&self, flush_disk_type: FlushDiskType, pages: usize) {
-        todo!()
+        let begin_time = get_current_millis();
+        let mapped_file = self.get_mapped_file_mut();
+        let mut flush = 0;
+        // writing a byte to every page makes the OS allocate all of them up front
+        for (index, position) in (0..self.file_size as usize)
+            .step_by(OS_PAGE_SIZE as usize)
+            .enumerate()
+        {
+            mapped_file[position] = 0;
+            if flush_disk_type == FlushDiskType::SyncFlush && index - flush >= pages {
+                flush = index;
+                if let Err(e) = mapped_file.flush() {
+                    error!("warm mapped file {} flush error: {:?}", self.file_name, e);
+                }
+            }
+        }
+        if flush_disk_type == FlushDiskType::SyncFlush {
+            info!(
+                "mapped file warm-up done, force to disk, mappedFile={}, costTime={}",
+                self.file_name,
+                get_current_millis() - begin_time
+            );
+            if let Err(e) = mapped_file.flush() {
+                error!("warm mapped file {} flush error: {:?}", self.file_name, e);
+            }
+        }
+        info!(
+            "mapped file warm-up done. mappedFile={}, costTime={}",
+            self.file_name,
+            get_current_millis() - begin_time
+        );
+        MappedFile::mlock(self);
     }
 
     #[inline]
@@ -751,89 +800,11 @@ impl MappedFile for DefaultMappedFile {
     }
 
     #[inline]
-    #[cfg(target_os = "linux")]
-    fn is_loaded(&self, position: i64, size: usize) -> bool {
-        // use libc::c_void;
-        // use libc::mincore;
-        // use libc::EINVAL;
-        // let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize };
-        // let page_count = (length + page_size - 1) / page_size;
-
-        // let mut vec = vec![0u8; page_count];
-        // let ret = unsafe { mincore(address as *mut c_void, length, vec.as_mut_ptr()) };
-
-        // if ret == -1 {
-        //     return false;
-        // }
-
-        // !vec.iter().any(|&byte| byte & 1 == 0)
-        true
-    }
-
-    #[inline]
-    #[cfg(target_os = "windows")]
     fn is_loaded(&self, position: i64, size: usize) -> bool {
-        /*use windows::Win32::Foundation::{BOOL, HANDLE};
-        use windows::Win32::System::Memory::{VirtualQuery, MEMORY_BASIC_INFORMATION, MEM_COMMIT};
-
-        let address = self.mmapped_file.as_ptr().wrapping_add(position as usize);
-        let mut info: MEMORY_BASIC_INFORMATION = unsafe { std::mem::zeroed() };
-        let mut offset = 0;
-
-        while offset < length {
-            let result = unsafe {
-                VirtualQuery(
-                    address.add(offset) as *const _,
-                    &mut info,
-                    std::mem::size_of::<MEMORY_BASIC_INFORMATION>(),
-                )
-            };
-
-            if result == 0 {
-                return Err(std::io::Error::last_os_error());
-            }
-
-            if info.State != MEM_COMMIT {
-                return Ok(false);
-            }
-
-            offset += info.RegionSize;
-        }*/
-
-        true
-    }
-
-    #[inline]
-    #[cfg(target_os = "macos")]
-    fn is_loaded(&self, position: i64, size: usize) -> bool {
-        /*use windows::Win32::Foundation::{BOOL, HANDLE};
-        use windows::Win32::System::Memory::{VirtualQuery, MEMORY_BASIC_INFORMATION, MEM_COMMIT};
-
-        let address = self.mmapped_file.as_ptr().wrapping_add(position as usize);
-        let mut info: MEMORY_BASIC_INFORMATION = unsafe { std::mem::zeroed() };
-        let mut offset = 0;
-
-        while offset < length {
-            let result = unsafe {
-                VirtualQuery(
-                    address.add(offset) as *const _,
-                    &mut info,
-                    std::mem::size_of::<MEMORY_BASIC_INFORMATION>(),
-                )
-            };
-
-            if result == 0 {
-                return Err(std::io::Error::last_os_error());
-            }
-
-            if info.State != MEM_COMMIT {
-                return Ok(false);
-            }
-
-            offset += info.RegionSize;
-        }*/
-
-        true
+        if position < 0 {
+            return false;
+        }
+        self.mmapped_file.is_resident(position as usize, size)
     }
 
     fn select_mapped_buffer_with_position(&self, pos: i32) -> Option<SelectMappedBufferResult> {
@@ -0,0 +1,164 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Memory operations on mapped files that depend on the operating system.
+//!
+//! Mapping, flushing and unmapping go through `memmap2` and work everywhere. Locking pages,
+//! read-ahead hints and residency checks only exist on unix, elsewhere they do nothing and every
+//! page is reported as loaded.
+
+use std::io;
+
+use memmap2::MmapMut;
+
+/// Platform specific operations on the memory of a mapped file.
+pub trait MappedMemory {
+    /// Pins the mapped pages in physical memory so they are never swapped out.
+    fn lock(&self) -> io::Result<()>;
+
+    /// Lets the pages pinned by [`lock`](Self::lock) be swapped out again.
+    fn unlock(&self) -> io::Result<()>;
+
+    /// Hints that the `len` bytes at `offset` are about to be read.
+    fn will_need(&self, offset: usize, len: usize) -> io::Result<()>;
+
+    /// Returns whether every page holding the `len` bytes at `offset` is in physical memory.
+    /// Ranges outside the mapping are never loaded, platforms unable to tell report `true`.
+    fn is_resident(&self, offset: usize, len: usize) -> bool;
+}
+
+#[cfg(unix)]
+impl MappedMemory for MmapMut {
+    fn lock(&self) -> io::Result<()> {
+        MmapMut::lock(self)
+    }
+
+    fn unlock(&self) -> io::Result<()> {
+        MmapMut::unlock(self)
+    }
+
+    fn will_need(&self, offset: usize, len: usize) -> io::Result<()> {
+        if len == 0 {
+            return Ok(());
+        }
+        self.advise_range(memmap2::Advice::WillNeed, offset, len)
+    }
+
+    fn is_resident(&self, offset: usize, len: usize) -> bool {
+        if !in_bounds(self.len(), offset, len) {
+            return false;
+        }
+        pages_resident(self.as_ptr(), offset, len)
+    }
+}
+
+#[cfg(not(unix))]
+impl MappedMemory for MmapMut {
+    fn lock(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn unlock(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn will_need(&self, _offset: usize, _len: usize) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn is_resident(&self, offset: usize, len: usize) -> bool {
+        in_bounds(self.len(), offset, len)
+    }
+}
+
+#[inline]
+fn in_bounds(mapped_len: usize, offset: usize, len: usize) -> bool {
+    offset.checked_add(len).is_some_and(|end| end <= mapped_len)
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    target_os = "ios",
+    target_os = "freebsd"
+))]
+fn pages_resident(base: *const u8, offset: usize, len: usize) -> bool {
+    if len == 0 {
+        return true;
+    }
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+    // mincore wants a page aligned address, the mapping itself starts on a page boundary
+    let aligned_offset = offset - offset % page_size;
+    let aligned_len = len + offset % page_size;
+    let mut pages = vec![0u8; aligned_len.div_ceil(page_size)];
+    let ret = unsafe {
+        libc::mincore(
+            base.add(aligned_offset) as *mut libc::c_void,
+            aligned_len,
+            pages.as_mut_ptr().cast(),
+        )
+    };
+    ret == 0 && pages.iter().all(|page| page & 1 == 1)
+}
+
+#[cfg(all(
+    unix,
+    not(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd"
+    ))
+))]
+fn pages_resident(_base: *const u8, _offset: usize, _len: usize) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::OpenOptions;
+
+    use super::*;
+
+    #[test]
+    fn touched_pages_are_resident_and_out_of_range_is_not() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(dir.path().join("00000000000000000000"))
+            .unwrap();
+        file.set_len(64 * 1024).unwrap();
+        let mut mmap = unsafe { MmapMut::map_mut(&file).unwrap() };
+        mmap.fill(1);
+
+        mmap.will_need(0, mmap.len()).unwrap();
+        assert!(mmap.is_resident(0, mmap.len()));
+        assert!(mmap.is_resident(100, 5000));
+        assert!(!mmap.is_resident(mmap.len() - 10, 11));
+        assert!(!mmap.is_resident(usize::MAX, 2));
+
+        // pinning is limited by RLIMIT_MEMLOCK, only unlocking what was locked must succeed
+        if mmap.lock().is_ok() {
+            mmap.unlock().unwrap();
+        }
+    }
+}
@@ -16,3 +16,5 @@
  */
 #[cfg(feature = "local_file_store")]
 pub mod local_file_message_store;
+#[cfg(feature = "memory_store")]
+pub mod memory_message_store;
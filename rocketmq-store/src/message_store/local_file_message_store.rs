@@ -25,7 +25,6 @@ use std::collections::VecDeque;
 use std::error::Error;
 use std::fs;
 use std::future::Future;
-use std::net::IpAddr;
 use std::ops::Deref;
 use std::path::Path;
 use std::path::PathBuf;
@@ -43,6 +42,7 @@ use bytes::Bytes;
 use bytes::BytesMut;
 use cheetah_string::CheetahString;
 use rocketmq_common::common::attribute::cleanup_policy::CleanupPolicy;
+use rocketmq_common::common::attribute::cq_type::CQType;
 use rocketmq_common::common::boundary_type::BoundaryType;
 use rocketmq_common::common::broker::broker_config::BrokerConfig;
 use rocketmq_common::common::broker::broker_role::BrokerRole;
@@ -62,6 +62,7 @@ use rocketmq_common::common::system_clock::SystemClock;
 use rocketmq_common::utils::queue_type_utils::QueueTypeUtils;
 use rocketmq_common::utils::util_all;
 use rocketmq_common::CleanupPolicyUtils::get_delete_policy;
+use rocketmq_common::FileUtils::file_to_string;
 use rocketmq_common::FileUtils::string_to_file;
 use rocketmq_common::MessageDecoder;
 use rocketmq_common::TimeUtils::get_current_millis;
@@ -92,6 +93,9 @@ use crate::base::transient_store_pool::TransientStorePool;
 use crate::config::message_store_config::MessageStoreConfig;
 use crate::config::store_path_config_helper::get_store_path_batch_consume_queue;
 use crate::config::store_path_config_helper::get_store_path_consume_queue_ext;
+use crate::dledger::dledger_commit_log;
+use crate::dledger::dledger_commit_log::DLedgerCommitLog;
+use crate::dledger::dledger_entry::BODY_OFFSET;
 use crate::filter::MessageFilter;
 use crate::ha::general_ha_service::GeneralHAService;
 use crate::ha::ha_service::HAService;
@@ -109,11 +113,14 @@ use crate::log_file::MAX_PULL_MSG_SIZE;
 use crate::queue::build_consume_queue::CommitLogDispatcherBuildConsumeQueue;
 use crate::queue::consume_queue_store::ConsumeQueueStoreTrait;
 use crate::queue::local_file_consume_queue_store::ConsumeQueueStore;
+use crate::queue::topic_retention::QueueMessageSizes;
+use crate::queue::topic_retention::TopicRetention;
 use crate::queue::ArcConsumeQueue;
 use crate::stats::broker_stats_manager::BrokerStatsManager;
 use crate::store::running_flags::RunningFlags;
 use crate::store_error::StoreError;
 use crate::store_path_config_helper::get_abort_file;
+use crate::store_path_config_helper::get_retention_offset_store_path;
 use crate::store_path_config_helper::get_store_checkpoint;
 use crate::store_path_config_helper::get_store_path_consume_queue;
 use crate::timer::timer_message_store::TimerMessageStore;
@@ -202,6 +209,16 @@ impl LocalFileMessageStore {
             topic_config_table.clone(),
             consume_queue_store.clone(),
         ));
+        let clean_consume_queue_service = Arc::new(CleanConsumeQueueService {
+            topic_config_table: topic_config_table.clone(),
+            consume_queue_store: consume_queue_store.clone(),
+            commit_log: commit_log.clone(),
+            message_sizes: QueueMessageSizes::default(),
+            retained_offsets_path: get_retention_offset_store_path(
+                message_store_config.store_path_root_dir.as_str(),
+            ),
+            retained_offsets: parking_lot::Mutex::new(HashMap::new()),
+        });
 
         ensure_dir_ok(message_store_config.store_path_root_dir.as_str());
         ensure_dir_ok(Self::get_store_path_physic(&message_store_config).as_str());
@@ -238,7 +255,7 @@ impl LocalFileMessageStore {
             },
             clean_commit_log_service: Arc::new(CleanCommitLogService {}),
             correct_logic_offset_service: Arc::new(CorrectLogicOffsetService {}),
-            clean_consume_queue_service: Arc::new(CleanConsumeQueueService {}),
+            clean_consume_queue_service,
             broker_stats_manager,
             message_arriving_listener: None,
             notify_message_arrive_in_batch,
@@ -256,13 +273,16 @@ impl LocalFileMessageStore {
 
     pub fn get_store_path_physic(message_store_config: &Arc<MessageStoreConfig>) -> String {
         match message_store_config.enable_dledger_commit_log {
-            true => {
-                unimplemented!("dledger commit log is not supported yet")
-            }
+            true => message_store_config.get_store_path_dledger_commit_log(),
             false => message_store_config.get_store_path_commit_log(),
         }
     }
 
+    /// The raft replicated commit log, only present with `enableDLegerCommitLog` once loaded.
+    pub fn dledger_commit_log(&self) -> Option<&Arc<DLedgerCommitLog>> {
+        self.commit_log.dledger_commit_log()
+    }
+
     pub fn get_store_path_logic(message_store_config: &Arc<MessageStoreConfig>) -> String {
         get_store_path_consume_queue(message_store_config.store_path_root_dir.as_str())
     }
@@ -481,6 +501,26 @@ impl LocalFileMessageStore {
             }
         });
 
+        let message_store = self.message_store_arc.clone().unwrap();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(1));
+            loop {
+                interval.tick().await;
+                message_store.check_behind_protection();
+            }
+        });
+
+        // put message distribute time table, printed once a minute
+        let store_stats_service = self.store_stats_service.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(60));
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                store_stats_service.print_put_message_distribute_time();
+            }
+        });
+
         let correct_logic_offset_service_arc = self.correct_logic_offset_service.clone();
         let clean_consume_queue_service_arc = self.clean_consume_queue_service.clone();
         tokio::spawn(async move {
@@ -490,7 +530,13 @@ impl LocalFileMessageStore {
                 tokio::time::interval(Duration::from_millis(clean_resource_interval));
             loop {
                 correct_logic_offset_service_arc.run();
-                clean_consume_queue_service_arc.run();
+                // retention reads the consume queues and commit log, keep it off the async workers
+                let clean_consume_queue_service = clean_consume_queue_service_arc.clone();
+                if let Err(e) =
+                    tokio::task::spawn_blocking(move || clean_consume_queue_service.run()).await
+                {
+                    error!("clean consume queue service run failed: {}", e);
+                }
                 interval.tick().await;
             }
         });
@@ -501,6 +547,55 @@ impl LocalFileMessageStore {
         ConsumeQueueStoreTrait::check_self(&self.consume_queue_store);
     }
 
+    /// Unflushed bytes of the commit log.
+    pub fn flush_behind_bytes(&self) -> i64 {
+        self.commit_log.remain_how_many_data_to_flush()
+    }
+
+    /// Refuses puts while dispatch or flush lags too far behind the commit log, so the backlog
+    /// held in memory and page cache stays bounded, and accepts them again once the lag is back
+    /// below half of the limit.
+    fn check_behind_protection(&self) {
+        let dispatch_behind_bytes = self.dispatch_behind_bytes();
+        let max_dispatch_behind_bytes = self.message_store_config.max_dispatch_behind_bytes;
+        if max_dispatch_behind_bytes > 0 && dispatch_behind_bytes > max_dispatch_behind_bytes {
+            if self.running_flags.get_and_make_dispatch_behind() {
+                error!(
+                    "dispatch is {} bytes behind the commit log, more than {}, the store is not \
+                     writeable now",
+                    dispatch_behind_bytes, max_dispatch_behind_bytes
+                );
+            }
+        } else if dispatch_behind_bytes <= max_dispatch_behind_bytes / 2
+            && self.running_flags.get_and_make_dispatch_ok()
+        {
+            info!(
+                "dispatch caught up to {} bytes behind the commit log, the store is writeable \
+                 again",
+                dispatch_behind_bytes
+            );
+        }
+
+        let flush_behind_bytes = self.flush_behind_bytes();
+        let max_flush_behind_bytes = self.message_store_config.max_flush_behind_bytes;
+        if max_flush_behind_bytes > 0 && flush_behind_bytes > max_flush_behind_bytes {
+            if self.running_flags.get_and_make_flush_behind() {
+                error!(
+                    "commit log has {} bytes not flushed, more than {}, the store is not \
+                     writeable now",
+                    flush_behind_bytes, max_flush_behind_bytes
+                );
+            }
+        } else if flush_behind_bytes <= max_flush_behind_bytes / 2
+            && self.running_flags.get_and_make_flush_ok()
+        {
+            info!(
+                "commit log flush caught up to {} bytes behind, the store is writeable again",
+                flush_behind_bytes
+            );
+        }
+    }
+
     pub fn next_offset_correction(&self, old_offset: i64, new_offset: i64) -> i64 {
         let mut next_offset = old_offset;
         if self.message_store_config.broker_role != BrokerRole::Slave
@@ -519,6 +614,11 @@ impl LocalFileMessageStore {
         }
     }
 
+    /// See [`CommitLog::set_flush_runtime`].
+    pub fn set_flush_runtime(&mut self, runtime: Handle) {
+        self.commit_log.set_flush_runtime(runtime);
+    }
+
     pub fn set_message_arriving_listener(
         &mut self,
         message_arriving_listener: Option<
@@ -565,23 +665,24 @@ fn is_the_batch_full(
         return true;
     }
 
-    if is_in_mem {
-        if (buffer_total + size_py) as u64
-            > message_store_config.max_transfer_bytes_on_message_in_memory
-        {
-            return true;
-        }
-
-        message_total as u64 > message_store_config.max_transfer_count_on_message_in_memory - 1
+    // Messages still in the page cache are cheap to transfer, so larger batches are allowed
+    // than for messages that have to be read from disk.
+    let (max_transfer_bytes, max_transfer_count) = if is_in_mem {
+        (
+            message_store_config.max_transfer_bytes_on_message_in_memory,
+            message_store_config.max_transfer_count_on_message_in_memory,
+        )
     } else {
-        if (buffer_total + size_py) as u64
-            > message_store_config.max_transfer_bytes_on_message_in_disk
-        {
-            return true;
-        }
-
-        message_total as u64 > message_store_config.max_transfer_count_on_message_in_disk - 1
+        (
+            message_store_config.max_transfer_bytes_on_message_in_disk,
+            message_store_config.max_transfer_count_on_message_in_disk,
+        )
+    };
+    if (buffer_total as i64 + size_py as i64) as u64 > max_transfer_bytes {
+        return true;
     }
+
+    message_total as u64 >= max_transfer_count
 }
 
 #[allow(unused_variables)]
@@ -622,6 +723,7 @@ impl MessageStore for LocalFileMessageStore {
 
             //recover commit log and consume queue
             self.recover(last_exit_ok).await;
+            self.clean_consume_queue_service.load();
             info!(
                 "message store recover end, and the max phy offset = {}",
                 self.get_max_phy_offset()
@@ -639,7 +741,7 @@ impl MessageStore for LocalFileMessageStore {
     }
 
     fn start(&mut self) -> Result<(), StoreError> {
-        if !self.message_store_config.enable_dleger_commit_log
+        if !self.message_store_config.enable_dledger_commit_log
             && !self.message_store_config.duplication_enable
         {
             if let Some(ha_service) = self.ha_service.as_mut() {
@@ -660,8 +762,18 @@ impl MessageStore for LocalFileMessageStore {
 
         self.index_service.start();
 
+        let reput_from_offset = if self.message_store_config.enable_dledger_commit_log {
+            // committed entries may be ahead of the checkpointed commit index, continue
+            // behind what the consume queues already hold
+            self.consume_queue_store
+                .get_max_phy_offset_in_consume_queue_global()
+                .max(self.commit_log.get_min_offset())
+                .max(0)
+        } else {
+            self.commit_log.get_confirm_offset()
+        };
         self.reput_message_service
-            .set_reput_from_offset(self.commit_log.get_confirm_offset());
+            .set_reput_from_offset(reput_from_offset);
         self.reput_message_service.start(
             self.commit_log.clone(),
             self.message_store_config.clone(),
@@ -697,10 +809,10 @@ impl MessageStore for LocalFileMessageStore {
             }
 
             self.store_stats_service.shutdown();
-            self.commit_log.shutdown();
+            let commit_log_flushed = self.commit_log.shutdown();
 
             self.reput_message_service.shutdown();
-            self.consume_queue_store.shutdown();
+            let consume_queue_flushed = self.consume_queue_store.shutdown();
 
             // dispatch-related services must be shut down after reputMessageService
             self.index_service.shutdown();
@@ -717,11 +829,23 @@ impl MessageStore for LocalFileMessageStore {
             if let Some(store_checkpoint) = self.store_checkpoint.as_ref() {
                 let _ = store_checkpoint.shutdown();
             }
-            if self.running_flags.is_writeable() {
-                //delete abort file
+            // the abort file makes the next start recover, it may only go when nothing was lost
+            if self.running_flags.is_writeable()
+                && commit_log_flushed
+                && consume_queue_flushed
+                && self.dispatch_behind_bytes() == 0
+            {
                 self.delete_file(get_abort_file(
                     self.message_store_config.store_path_root_dir.as_str(),
                 ))
+            } else {
+                warn!(
+                    "message store shutdown unclean, commit log flushed: {}, consume queue \
+                     flushed: {}, dispatch behind: {} bytes, keep the abort file",
+                    commit_log_flushed,
+                    consume_queue_flushed,
+                    self.dispatch_behind_bytes()
+                );
             }
         }
 
@@ -1065,15 +1189,21 @@ impl MessageStore for LocalFileMessageStore {
                             );
                             status = GetMessageStatus::Found;
                             next_phy_file_start_offset = i64::MIN;
+                        } else {
+                            break;
                         }
                     }
                 }
                 if disk_fall_recorded {
-                    let fall_behind = max_offset_py - max_phy_offset_pulling;
-                    self.broker_stats_manager
-                        .as_ref()
-                        .unwrap()
-                        .record_disk_fall_behind_size(group, topic, queue_id, fall_behind);
+                    if let Some(broker_stats_manager) = self.broker_stats_manager.as_ref() {
+                        let fall_behind = max_offset_py - max_phy_offset_pulling;
+                        broker_stats_manager.record_disk_fall_behind_size(
+                            group,
+                            topic,
+                            queue_id,
+                            fall_behind,
+                        );
+                    }
                 }
                 let diff = max_offset_py - max_phy_offset_pulling;
                 let memory = ((*TOTAL_PHYSICAL_MEMORY_SIZE as f64)
@@ -1099,6 +1229,8 @@ impl MessageStore for LocalFileMessageStore {
                 .fetch_add(1, Ordering::Relaxed);
         }
         let elapsed_time = begin_time.elapsed().as_millis() as u64;
+        self.store_stats_service
+            .set_get_message_entire_time_max(elapsed_time);
         if get_result.is_none() {
             get_result = Some(GetMessageResult::new_result_size(0));
         }
@@ -1303,6 +1435,18 @@ impl MessageStore for LocalFileMessageStore {
             RunningStats::CommitLogMaxOffset.as_str().to_string(),
             self.get_max_phy_offset().to_string(),
         );
+        result.insert(
+            "dispatchBehindBytes".to_string(),
+            self.dispatch_behind_bytes().to_string(),
+        );
+        result.insert(
+            "flushBehindBytes".to_string(),
+            self.flush_behind_bytes().to_string(),
+        );
+        result.insert(
+            "storeRunningFlags".to_string(),
+            self.running_flags.get_flag_bits().to_string(),
+        );
 
         result
     }
@@ -1327,23 +1471,14 @@ impl MessageStore for LocalFileMessageStore {
     }
 
     fn get_earliest_message_time_store(&self) -> i64 {
-        let min_phy_offset = self.get_min_phy_offset();
-
-        //Rust not support DLedgerCommitLog
-        /*if (this.getCommitLog() instanceof DLedgerCommitLog) {
-            minPhyOffset += DLedgerEntry.BODY_OFFSET;
-        }*/
-
-        let mut size = MessageDecoder::MESSAGE_STORE_TIMESTAMP_POSITION + 8;
-        let result = self
-            .broker_config
-            .broker_ip1
-            .to_string()
-            .parse::<IpAddr>()
-            .unwrap();
-        if result.is_ipv6() {
-            size = MessageDecoder::MESSAGE_STORE_TIMESTAMP_POSITION + 20;
+        let mut min_phy_offset = self.get_min_phy_offset();
+        if self.message_store_config.enable_dledger_commit_log {
+            min_phy_offset += BODY_OFFSET as i64;
         }
+
+        // where the store timestamp sits depends on the born host of the message, which may be an
+        // IPv6 address whatever brokerIP1 is, read enough for both
+        let size = MessageDecoder::MESSAGE_STORE_TIMESTAMP_POSITION + 20;
         self.commit_log
             .pickup_store_timestamp(min_phy_offset, size as i32)
     }
@@ -1391,6 +1526,21 @@ impl MessageStore for LocalFileMessageStore {
         0
     }
 
+    fn get_topic_disk_usage(&self, topic: &CheetahString) -> i64 {
+        self.consume_queue_store
+            .find_consume_queue_map(topic)
+            .map_or(0, |queues| {
+                queues
+                    .values()
+                    .map(|consume_queue| {
+                        self.clean_consume_queue_service
+                            .message_sizes
+                            .message_size(&***consume_queue)
+                    })
+                    .sum()
+            })
+    }
+
     fn get_commit_log_data(&self, offset: i64) -> Option<SelectMappedBufferResult> {
         if self.shutdown.load(Ordering::Acquire) {
             return None;
@@ -1504,11 +1654,15 @@ impl MessageStore for LocalFileMessageStore {
     }*/
 
     fn update_ha_master_address(&self, new_addr: &CheetahString) {
-        todo!()
+        if let Some(ha_service) = self.ha_service.as_ref() {
+            ha_service.update_ha_master_address(new_addr.as_str());
+        }
     }
 
     fn update_master_address(&self, new_addr: &CheetahString) {
-        todo!()
+        if let Some(ha_service) = self.ha_service.as_ref() {
+            ha_service.update_master_address(new_addr.as_str());
+        }
     }
 
     fn slave_fall_behind_much(&self) -> i64 {
@@ -1610,16 +1764,15 @@ impl MessageStore for LocalFileMessageStore {
     }
 
     fn dispatch_behind_bytes(&self) -> i64 {
-        error!("DefaultMessageStore#dispatchBehindBytes: not implemented");
-        0
+        self.reput_message_service.behind()
     }
 
     fn flush(&self) -> i64 {
-        todo!()
+        self.commit_log.flush()
     }
 
     fn get_flushed_where(&self) -> i64 {
-        todo!()
+        self.commit_log.get_flushed_where()
     }
 
     fn reset_write_offset(&self, phy_offset: i64) -> bool {
@@ -1636,7 +1789,8 @@ impl MessageStore for LocalFileMessageStore {
 
     fn is_os_page_cache_busy(&self) -> bool {
         let begin = self.commit_log.begin_time_in_lock().load(Ordering::Relaxed);
-        let diff = get_current_millis() - begin;
+        // begin is 0 while nobody holds the lock, which the upper bound of diff filters out
+        let diff = get_current_millis().saturating_sub(begin);
         diff < 10000000 && diff > self.message_store_config.os_page_cache_busy_timeout_mills
     }
 
@@ -1743,7 +1897,7 @@ impl MessageStore for LocalFileMessageStore {
     }
 
     fn get_allocate_mapped_file_service(&self) -> Arc<AllocateMappedFileService> {
-        todo!()
+        self.allocate_mapped_file_service.clone()
     }
 
     fn truncate_dirty_logic_files(&self, phy_offset: i64) -> Result<(), StoreError> {
@@ -1970,58 +2124,17 @@ struct ReputMessageService {
 }
 
 impl ReputMessageService {
-    fn notify_message_arrive4multi_queue(&self, dispatch_request: &mut DispatchRequest) {
-        if dispatch_request.properties_map.is_none()
-            || dispatch_request
-                .topic
-                .as_str()
-                .starts_with(RETRY_GROUP_TOPIC_PREFIX)
-        {
-            return;
-        }
-        let prop = dispatch_request.properties_map.as_ref().unwrap();
-        let multi_dispatch_queue = prop.get(MessageConst::PROPERTY_INNER_MULTI_DISPATCH);
-        let multi_queue_offset = prop.get(MessageConst::PROPERTY_INNER_MULTI_QUEUE_OFFSET);
-        if multi_dispatch_queue.is_none()
-            || multi_queue_offset.is_none()
-            || multi_dispatch_queue.as_ref().unwrap().is_empty()
-            || multi_queue_offset.as_ref().unwrap().is_empty()
-        {
-            return;
-        }
-        let queues: Vec<&str> = multi_dispatch_queue
-            .unwrap()
-            .split(MULTI_DISPATCH_QUEUE_SPLITTER)
-            .collect();
-        let queue_offsets: Vec<&str> = multi_dispatch_queue
-            .unwrap()
-            .split(MULTI_DISPATCH_QUEUE_SPLITTER)
-            .collect();
-        if queues.len() != queue_offsets.len() {
-            return;
+    /// Bytes of the commit log that are readable but not dispatched yet.
+    fn behind(&self) -> i64 {
+        match self.inner.as_ref() {
+            Some(inner) => inner.get_reput_end_offset() - inner.reput_from_offset(),
+            None => 0,
         }
-        let reput_message_service_inner = self.inner.as_ref().unwrap();
-        for i in 0..queues.len() {
-            let queue_name = CheetahString::from_slice(queues[i]);
-            let queue_offset: i64 = queue_offsets[i].parse().unwrap();
-            let mut queue_id = dispatch_request.queue_id;
-            if self.message_store_config.enable_lmq && is_lmq(Some(queue_name.as_str())) {
-                queue_id = 0;
-            }
-            reput_message_service_inner
-                .message_store
-                .message_arriving_listener
-                .as_ref()
-                .unwrap()
-                .arriving(
-                    &queue_name,
-                    queue_id,
-                    queue_offset + 1,
-                    Some(dispatch_request.tags_code),
-                    dispatch_request.store_timestamp,
-                    dispatch_request.bit_map.clone(),
-                    dispatch_request.properties_map.as_ref(),
-                );
+    }
+
+    fn notify_message_arrive4multi_queue(&self, dispatch_request: &mut DispatchRequest) {
+        if let Some(inner) = self.inner.as_ref() {
+            inner.notify_message_arrive4multi_queue(dispatch_request);
         }
     }
 
@@ -2100,51 +2213,33 @@ struct ReputMessageServiceInner {
 
 impl ReputMessageServiceInner {
     fn notify_message_arrive4multi_queue(&self, dispatch_request: &mut DispatchRequest) {
-        let prop = dispatch_request.properties_map.as_ref();
-        if prop.is_none() || dispatch_request.topic.starts_with(RETRY_GROUP_TOPIC_PREFIX) {
+        if dispatch_request.topic.starts_with(RETRY_GROUP_TOPIC_PREFIX) {
             return;
         }
-        let prop = prop.unwrap();
-        let multi_dispatch_queue = prop.get(MessageConst::PROPERTY_INNER_MULTI_DISPATCH);
-        let multi_queue_offset = prop.get(MessageConst::PROPERTY_INNER_MULTI_QUEUE_OFFSET);
-        if multi_dispatch_queue.is_none()
-            || multi_queue_offset.is_none()
-            || multi_dispatch_queue.as_ref().unwrap().is_empty()
-            || multi_queue_offset.as_ref().unwrap().is_empty()
-        {
+        let Some(listener) = self.message_store.message_arriving_listener.as_ref() else {
             return;
-        }
-        let queues: Vec<&str> = multi_dispatch_queue
-            .unwrap()
-            .split(MULTI_DISPATCH_QUEUE_SPLITTER)
-            .collect();
-        let queue_offsets: Vec<&str> = multi_queue_offset
-            .unwrap()
-            .split(MULTI_DISPATCH_QUEUE_SPLITTER)
-            .collect();
-        if queues.len() != queue_offsets.len() {
+        };
+        let Some(queue_offsets) = dispatch_request
+            .properties_map
+            .as_ref()
+            .and_then(multi_dispatch_queue_offsets)
+        else {
             return;
-        }
-        for i in 0..queues.len() {
-            let queue_name = CheetahString::from_slice(queues[i]);
-            let queue_offset: i64 = queue_offsets[i].parse().unwrap();
+        };
+        for (queue_name, queue_offset) in queue_offsets {
             let mut queue_id = dispatch_request.queue_id;
             if self.message_store_config.enable_lmq && is_lmq(Some(queue_name.as_str())) {
                 queue_id = 0;
             }
-            self.message_store
-                .message_arriving_listener
-                .as_ref()
-                .unwrap()
-                .arriving(
-                    &queue_name,
-                    queue_id,
-                    queue_offset + 1,
-                    Some(dispatch_request.tags_code),
-                    dispatch_request.store_timestamp,
-                    dispatch_request.bit_map.clone(),
-                    dispatch_request.properties_map.as_ref(),
-                );
+            listener.arriving(
+                &queue_name,
+                queue_id,
+                queue_offset + 1,
+                Some(dispatch_request.tags_code),
+                dispatch_request.store_timestamp,
+                dispatch_request.bit_map.clone(),
+                dispatch_request.properties_map.as_ref(),
+            );
         }
     }
 
@@ -2160,6 +2255,7 @@ impl ReputMessageServiceInner {
             self.reput_from_offset
                 .store(self.commit_log.get_min_offset(), Ordering::Release);
         }
+        let mut arrived = Vec::new();
         let mut do_next = true;
         while do_next && self.is_commit_log_available() {
             let result = self
@@ -2176,7 +2272,13 @@ impl ReputMessageServiceInner {
                 && self.reput_from_offset.load(Ordering::Acquire) < self.get_reput_end_offset()
                 && do_next
             {
-                let mut dispatch_request = commit_log::check_message_and_return_size(
+                let check_message_and_return_size =
+                    if self.message_store_config.enable_dledger_commit_log {
+                        dledger_commit_log::check_message_and_return_size
+                    } else {
+                        commit_log::check_message_and_return_size
+                    };
+                let mut dispatch_request = check_message_and_return_size(
                     result.bytes.as_mut().unwrap(),
                     false,
                     false,
@@ -2200,19 +2302,33 @@ impl ReputMessageServiceInner {
                     match dispatch_request.msg_size.cmp(&0) {
                         std::cmp::Ordering::Greater => {
                             self.dispatcher.dispatch(&mut dispatch_request);
-                            if !self.notify_message_arrive_in_batch {
-                                self.message_store
-                                    .notify_message_arrive_if_necessary(&mut dispatch_request);
-                            }
                             self.reput_from_offset
                                 .fetch_add(size as i64, Ordering::AcqRel);
                             read_size += size;
                             if !self.message_store_config.duplication_enable
                                 && self.message_store_config.broker_role == BrokerRole::Slave
                             {
-                                unimplemented!()
+                                self.message_store
+                                    .store_stats_service
+                                    .add_single_put_message_topic_total(
+                                        dispatch_request.topic.as_str(),
+                                        dispatch_request.batch_size as usize,
+                                        dispatch_request.msg_size as usize,
+                                    );
+                            }
+                            if self.notify_message_arrive_in_batch {
+                                arrived.push(dispatch_request);
+                            } else {
+                                self.message_store
+                                    .notify_message_arrive_if_necessary(&mut dispatch_request);
                             }
                         }
+                        // a raft entry that carries no message
+                        std::cmp::Ordering::Equal if dispatch_request.buffer_size > 0 => {
+                            self.reput_from_offset
+                                .fetch_add(size as i64, Ordering::AcqRel);
+                            read_size += size;
+                        }
                         std::cmp::Ordering::Equal => {
                             self.reput_from_offset.store(
                                 self.commit_log
@@ -2232,13 +2348,28 @@ impl ReputMessageServiceInner {
                         .fetch_add(size as i64, Ordering::SeqCst);
                 } else {
                     do_next = false;
+                    // The entries are committed, so the leader will not replace them; skip the
+                    // rest of this buffer instead of getting stuck on it.
                     if self.message_store_config.enable_dledger_commit_log {
-                        unimplemented!()
+                        error!(
+                            "[BUG]dispatch message failed, skip the rest of the buffer. \
+                             reputFromOffset={}, readSize={}, bufferSize={}",
+                            self.reput_from_offset.load(Ordering::Relaxed),
+                            read_size,
+                            result.size
+                        );
+                        self.reput_from_offset
+                            .fetch_add((result.size - read_size) as i64, Ordering::SeqCst);
                     }
                 }
             }
             result.release();
         }
+        // waiting pull and pop requests are woken once the whole pass is dispatched
+        for mut dispatch_request in arrived {
+            self.message_store
+                .notify_message_arrive_if_necessary(&mut dispatch_request);
+        }
     }
 
     fn is_commit_log_available(&self) -> bool {
@@ -2274,14 +2405,124 @@ impl CleanCommitLogService {
     }
 }
 
-struct CleanConsumeQueueService {}
+/// Enforces the retention topics set with the `reserve.time` and `max.size` attributes on their
+/// consume queues.
+struct CleanConsumeQueueService {
+    topic_config_table: Arc<parking_lot::Mutex<HashMap<CheetahString, TopicConfig>>>,
+    consume_queue_store: ConsumeQueueStore,
+    commit_log: ArcMut<CommitLog>,
+    message_sizes: QueueMessageSizes,
+    /// The min offsets retention moved the queues to are lost on restart, they are kept here.
+    retained_offsets_path: String,
+    retained_offsets: parking_lot::Mutex<HashMap<String, i64>>,
+}
 
 impl CleanConsumeQueueService {
     fn run(&self) {
-        error!("clean consume queue service run unimplemented!")
+        let retentions = self
+            .topic_config_table
+            .lock()
+            .iter()
+            .filter(|(_, topic_config)| {
+                QueueTypeUtils::get_cq_type(&Some((*topic_config).clone())) == CQType::SimpleCQ
+            })
+            .filter_map(|(topic, topic_config)| {
+                TopicRetention::of(topic_config).map(|retention| (topic.clone(), retention))
+            })
+            .collect::<Vec<_>>();
+        let now = get_current_millis() as i64;
+        let mut retained_offsets = HashMap::new();
+        for (topic, retention) in retentions {
+            let Some(queues) = self.consume_queue_store.find_consume_queue_map(&topic) else {
+                continue;
+            };
+            for consume_queue in queues.values() {
+                retention.apply(
+                    &***consume_queue,
+                    queues.len(),
+                    now,
+                    &self.message_sizes,
+                    |unit| self.commit_log.pickup_store_timestamp(unit.pos, unit.size),
+                );
+                let min_offset = consume_queue.get_min_offset_in_queue();
+                if min_offset > 0 {
+                    retained_offsets.insert(
+                        retained_offset_key(&topic, consume_queue.get_queue_id()),
+                        min_offset,
+                    );
+                }
+            }
+        }
+        let topic_config_table = self.topic_config_table.lock();
+        self.message_sizes
+            .retain_topics(|topic| topic_config_table.contains_key(topic));
+        drop(topic_config_table);
+        self.persist(retained_offsets);
+    }
+
+    /// Moves the consume queues back to the min offsets retention left them at before the
+    /// restart.
+    fn load(&self) {
+        let content = match file_to_string(self.retained_offsets_path.as_str()) {
+            Ok(content) if !content.is_empty() => content,
+            _ => return,
+        };
+        let retained_offsets: HashMap<String, i64> = match serde_json::from_str(&content) {
+            Ok(retained_offsets) => retained_offsets,
+            Err(e) => {
+                error!(
+                    "load retention offsets from {} failed: {}",
+                    self.retained_offsets_path, e
+                );
+                return;
+            }
+        };
+        for (key, min_offset) in &retained_offsets {
+            let Some((topic, queue_id)) = parse_retained_offset_key(key) else {
+                continue;
+            };
+            let consume_queue = self
+                .consume_queue_store
+                .find_consume_queue_map(&CheetahString::from_slice(topic))
+                .and_then(|queues| queues.get(&queue_id).cloned());
+            if let Some(consume_queue) = consume_queue {
+                consume_queue.advance_min_offset(*min_offset);
+            }
+        }
+        *self.retained_offsets.lock() = retained_offsets;
+    }
+
+    fn persist(&self, retained_offsets: HashMap<String, i64>) {
+        let mut persisted = self.retained_offsets.lock();
+        if *persisted == retained_offsets {
+            return;
+        }
+        let content = match serde_json::to_string_pretty(&retained_offsets) {
+            Ok(content) => content,
+            Err(e) => {
+                error!("serialize retention offsets failed: {}", e);
+                return;
+            }
+        };
+        match string_to_file(content.as_str(), self.retained_offsets_path.as_str()) {
+            Ok(()) => *persisted = retained_offsets,
+            Err(e) => error!(
+                "persist retention offsets to {} failed: {}",
+                self.retained_offsets_path, e
+            ),
+        }
     }
 }
 
+fn retained_offset_key(topic: &str, queue_id: i32) -> String {
+    format!("{topic}@{queue_id}")
+}
+
+fn parse_retained_offset_key(key: &str) -> Option<(&str, i32)> {
+    let (topic, queue_id) = key.rsplit_once('@')?;
+    Some((topic, queue_id.parse().ok()?))
+}
+
 struct CorrectLogicOffsetService {}
 
 impl CorrectLogicOffsetService {
@@ -2341,3 +2582,106 @@ pub fn parse_delay_level(level_string: &str) -> (BTreeMap<i32, i64>, i32) {
 
     (delay_level_table, max_delay_level)
 }
+
+/// The queues a multi dispatched message was also written to, with its offset in each.
+fn multi_dispatch_queue_offsets(
+    properties: &HashMap<CheetahString, CheetahString>,
+) -> Option<Vec<(CheetahString, i64)>> {
+    let multi_dispatch_queue = properties
+        .get(MessageConst::PROPERTY_INNER_MULTI_DISPATCH)
+        .filter(|queues| !queues.is_empty())?;
+    let multi_queue_offset = properties
+        .get(MessageConst::PROPERTY_INNER_MULTI_QUEUE_OFFSET)
+        .filter(|offsets| !offsets.is_empty())?;
+    let queues: Vec<&str> = multi_dispatch_queue
+        .split(MULTI_DISPATCH_QUEUE_SPLITTER)
+        .collect();
+    let queue_offsets: Vec<&str> = multi_queue_offset
+        .split(MULTI_DISPATCH_QUEUE_SPLITTER)
+        .collect();
+    if queues.len() != queue_offsets.len() {
+        return None;
+    }
+    queues
+        .into_iter()
+        .zip(queue_offsets)
+        .map(|(queue, offset)| Some((CheetahString::from_slice(queue), offset.parse().ok()?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_batch_full(buffer_total: i32, message_total: i32, is_in_mem: bool) -> bool {
+        let message_store_config = Arc::new(MessageStoreConfig::default());
+        is_the_batch_full(
+            1024,
+            1,
+            64,
+            MAX_PULL_MSG_SIZE as i64,
+            buffer_total,
+            message_total,
+            is_in_mem,
+            &message_store_config,
+        )
+    }
+
+    #[test]
+    fn batch_is_never_full_before_first_message() {
+        assert!(!is_batch_full(0, 0, false));
+    }
+
+    #[test]
+    fn batch_count_limit_depends_on_memory() {
+        let config = MessageStoreConfig::default();
+        let disk_count = config.max_transfer_count_on_message_in_disk as i32;
+        let memory_count = config.max_transfer_count_on_message_in_memory as i32;
+        assert!(!is_batch_full(1024, disk_count - 1, false));
+        assert!(is_batch_full(1024, disk_count, false));
+        assert!(!is_batch_full(1024, disk_count, true));
+        assert!(is_batch_full(1024, memory_count, true));
+    }
+
+    #[test]
+    fn batch_bytes_limit_depends_on_memory() {
+        let config = MessageStoreConfig::default();
+        let disk_bytes = config.max_transfer_bytes_on_message_in_disk as i32;
+        assert!(is_batch_full(disk_bytes, 1, false));
+        assert!(!is_batch_full(disk_bytes, 1, true));
+    }
+
+    #[test]
+    fn multi_dispatch_queue_offsets_pairs_queues_with_offsets() {
+        let mut properties = HashMap::new();
+        properties.insert(
+            CheetahString::from_static_str(MessageConst::PROPERTY_INNER_MULTI_DISPATCH),
+            CheetahString::from_static_str("%LMQ%a,%LMQ%b"),
+        );
+        properties.insert(
+            CheetahString::from_static_str(MessageConst::PROPERTY_INNER_MULTI_QUEUE_OFFSET),
+            CheetahString::from_static_str("3,7"),
+        );
+        assert_eq!(
+            multi_dispatch_queue_offsets(&properties).unwrap(),
+            vec![
+                (CheetahString::from_static_str("%LMQ%a"), 3),
+                (CheetahString::from_static_str("%LMQ%b"), 7)
+            ]
+        );
+
+        properties.insert(
+            CheetahString::from_static_str(MessageConst::PROPERTY_INNER_MULTI_QUEUE_OFFSET),
+            CheetahString::from_static_str("3"),
+        );
+        assert!(multi_dispatch_queue_offsets(&properties).is_none());
+    }
+
+    #[test]
+    fn retained_offset_keys_round_trip() {
+        let key = retained_offset_key("retained", 3);
+        assert_eq!(parse_retained_offset_key(&key), Some(("retained", 3)));
+        assert_eq!(parse_retained_offset_key("retained"), None);
+        assert_eq!(parse_retained_offset_key("retained@x"), None);
+    }
+}
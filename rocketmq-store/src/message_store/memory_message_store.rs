@@ -0,0 +1,1453 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! A message store that keeps the commit log and consume queues in process memory.
+//!
+//! [`MemoryMessageStore`] implements [`MessageStore`] without mapped files, index files or
+//! background services, so broker processors and embedded single-process brokers can be exercised
+//! in tests without touching the disk. Messages are encoded in the regular commit log format, which
+//! keeps pulled data byte-compatible with what clients expect from a file based store.
+
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicI32;
+use std::sync::atomic::AtomicI64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use bytes::BytesMut;
+use cheetah_string::CheetahString;
+use parking_lot::RwLock;
+use rocketmq_common::common::boundary_type::BoundaryType;
+use rocketmq_common::common::broker::broker_role::BrokerRole;
+use rocketmq_common::common::message::message_batch::MessageExtBatch;
+use rocketmq_common::common::message::message_ext::MessageExt;
+use rocketmq_common::common::message::message_ext_broker_inner::MessageExtBrokerInner;
+use rocketmq_common::common::message::message_single::tags_string2tags_code;
+use rocketmq_common::common::message::MessageConst;
+use rocketmq_common::common::message::MessageTrait;
+use rocketmq_common::common::sys_flag::message_sys_flag::MessageSysFlag;
+use rocketmq_common::common::system_clock::SystemClock;
+use rocketmq_common::CRC32Utils::crc32;
+use rocketmq_common::MessageDecoder;
+use rocketmq_common::MessageUtils::build_message_id;
+use rocketmq_common::TimeUtils::get_current_millis;
+use tracing::warn;
+
+use crate::base::allocate_mapped_file_service::AllocateMappedFileService;
+use crate::base::commit_log_dispatcher::CommitLogDispatcher;
+use crate::base::dispatch_request::DispatchRequest;
+use crate::base::get_message_result::GetMessageResult;
+use crate::base::message_arriving_listener::MessageArrivingListener;
+use crate::base::message_result::AppendMessageResult;
+use crate::base::message_result::PutMessageResult;
+use crate::base::message_status_enum::AppendMessageStatus;
+use crate::base::message_status_enum::GetMessageStatus;
+use crate::base::message_status_enum::PutMessageStatus;
+use crate::base::message_store::MessageStore;
+use crate::base::query_message_result::QueryMessageResult;
+use crate::base::select_result::SelectMappedBufferResult;
+use crate::base::store_checkpoint::StoreCheckpoint;
+use crate::base::store_stats_service::StoreStatsService;
+use crate::base::transient_store_pool::TransientStorePool;
+use crate::config::flush_disk_type::FlushDiskType;
+use crate::config::message_store_config::MessageStoreConfig;
+use crate::filter::MessageFilter;
+use crate::hook::put_message_hook::BoxedPutMessageHook;
+use crate::hook::put_message_hook::PutMessageHook;
+use crate::hook::send_message_back_hook::SendMessageBackHook;
+use crate::log_file::commit_log::CommitLog;
+use crate::log_file::mapped_file::MappedFile;
+use crate::log_file::MAX_PULL_MSG_SIZE;
+use crate::queue::ArcConsumeQueue;
+use crate::stats::broker_stats_manager::BrokerStatsManager;
+use crate::store::running_flags::RunningFlags;
+use crate::store_error::StoreError;
+use crate::timer::timer_message_store::TimerMessageStore;
+
+/// One consume queue unit: where a message lives in the in-memory commit log.
+#[derive(Debug, Clone, Copy)]
+struct QueueUnit {
+    commit_log_offset: i64,
+    size: i32,
+    tags_code: i64,
+    store_timestamp: i64,
+}
+
+#[derive(Default)]
+struct MemoryStoreState {
+    /// Encoded messages keyed by their physical offset.
+    commit_log: BTreeMap<i64, Bytes>,
+    consume_queues: HashMap<CheetahString, HashMap<i32, Vec<QueueUnit>>>,
+    max_phy_offset: i64,
+}
+
+impl MemoryStoreState {
+    fn queue(&self, topic: &CheetahString, queue_id: i32) -> Option<&Vec<QueueUnit>> {
+        self.consume_queues
+            .get(topic)
+            .and_then(|queues| queues.get(&queue_id))
+    }
+
+    fn truncate(&mut self, phy_offset: i64) {
+        self.commit_log.split_off(&phy_offset);
+        for queues in self.consume_queues.values_mut() {
+            for units in queues.values_mut() {
+                units.retain(|unit| unit.commit_log_offset < phy_offset);
+            }
+        }
+        self.max_phy_offset = self.max_phy_offset.min(phy_offset);
+    }
+}
+
+/// A message encoded at its place in the commit log, waiting to be written to the state.
+struct PendingAppend {
+    msg: MessageExtBrokerInner,
+    consumable: bool,
+    wrote_offset: i64,
+    queue_offset: i64,
+    size: i32,
+    tags_code: i64,
+    encoded: Bytes,
+}
+
+/// In-memory implementation of [`MessageStore`] for tests and embedded brokers.
+///
+/// Queue offsets start at zero and are never reclaimed, and nothing survives a restart. Features
+/// that only make sense for file based stores (checkpoints, mapped files, HA replication) are not
+/// supported.
+pub struct MemoryMessageStore {
+    message_store_config: Arc<MessageStoreConfig>,
+    state: Arc<RwLock<MemoryStoreState>>,
+    running_flags: Arc<RunningFlags>,
+    dispatcher_list: Vec<Arc<dyn CommitLogDispatcher>>,
+    put_message_hook_list: Arc<RwLock<Vec<Arc<dyn PutMessageHook + Send + Sync>>>>,
+    store_stats_service: Arc<StoreStatsService>,
+    system_clock: Arc<SystemClock>,
+    timer_message_store: Option<Arc<TimerMessageStore>>,
+    broker_stats_manager: Option<Arc<BrokerStatsManager>>,
+    message_arriving_listener:
+        Option<Arc<Box<dyn MessageArrivingListener + Sync + Send + 'static>>>,
+    confirm_offset: i64,
+    broker_init_max_offset: i64,
+    master_flushed_offset: Arc<AtomicI64>,
+    alive_replica_num: Arc<AtomicI32>,
+    shutdown: Arc<AtomicBool>,
+}
+
+impl MemoryMessageStore {
+    pub fn new(message_store_config: Arc<MessageStoreConfig>) -> Self {
+        Self {
+            message_store_config,
+            state: Arc::new(RwLock::new(MemoryStoreState::default())),
+            running_flags: Arc::new(RunningFlags::new()),
+            dispatcher_list: Vec::new(),
+            put_message_hook_list: Arc::new(RwLock::new(Vec::new())),
+            store_stats_service: Arc::new(StoreStatsService::new(None)),
+            system_clock: Arc::new(SystemClock),
+            timer_message_store: None,
+            broker_stats_manager: None,
+            message_arriving_listener: None,
+            confirm_offset: -1,
+            broker_init_max_offset: -1,
+            master_flushed_offset: Arc::new(AtomicI64::new(-1)),
+            alive_replica_num: Arc::new(AtomicI32::new(1)),
+            shutdown: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn set_broker_stats_manager(&mut self, broker_stats_manager: Arc<BrokerStatsManager>) {
+        self.broker_stats_manager = Some(broker_stats_manager);
+    }
+
+    /// Sets the listener woken up for every consumable message, so held pull requests are answered
+    /// as soon as a message arrives.
+    pub fn set_message_arriving_listener(
+        &mut self,
+        message_arriving_listener: Option<
+            Arc<Box<dyn MessageArrivingListener + Sync + Send + 'static>>,
+        >,
+    ) {
+        self.message_arriving_listener = message_arriving_listener;
+    }
+
+    fn next_offset_correction(&self, old_offset: i64, new_offset: i64) -> i64 {
+        if self.message_store_config.broker_role != BrokerRole::Slave
+            || self.message_store_config.offset_check_in_slave
+        {
+            new_offset
+        } else {
+            old_offset
+        }
+    }
+
+    /// Appends `messages` as one unit: all of them are encoded first and then written under a
+    /// single lock, so their offsets are consecutive and either every message is stored or none.
+    fn append_all(
+        &self,
+        mut messages: Vec<MessageExtBrokerInner>,
+    ) -> Result<Vec<AppendMessageResult>, PutMessageResult> {
+        if self.shutdown.load(Ordering::Acquire) || !self.running_flags.is_writeable() {
+            warn!("message store is not writeable, so putMessage is forbidden");
+            return Err(PutMessageResult::new_default(
+                PutMessageStatus::ServiceNotAvailable,
+            ));
+        }
+        let store_timestamp = get_current_millis() as i64;
+        for msg in messages.iter_mut() {
+            if msg.message_ext_inner.message.body.is_none() {
+                msg.message_ext_inner.message.body = Some(Bytes::new());
+            }
+            msg.message_ext_inner.body_crc =
+                crc32(msg.message_ext_inner.message.body.as_ref().unwrap());
+            msg.message_ext_inner.store_timestamp = store_timestamp;
+            if msg.born_host().is_ipv6() {
+                msg.with_born_host_v6_flag();
+            }
+            if msg.store_host().is_ipv6() {
+                msg.with_store_host_v6_flag();
+            }
+        }
+
+        let mut state = self.state.write();
+        let mut wrote_offset = state.max_phy_offset;
+        // offsets handed out to earlier messages of this append, not yet in the state
+        let mut next_queue_offsets: HashMap<(CheetahString, i32), i64> = HashMap::new();
+        let mut appended = Vec::with_capacity(messages.len());
+        for mut msg in messages {
+            // Prepared and rolled back transaction messages never enter the consume queue.
+            let consumable = !matches!(
+                MessageSysFlag::get_transaction_value(msg.sys_flag()),
+                MessageSysFlag::TRANSACTION_PREPARED_TYPE
+                    | MessageSysFlag::TRANSACTION_ROLLBACK_TYPE
+            );
+            let topic = msg.topic().clone();
+            let queue_id = msg.queue_id();
+            let queue_offset = if consumable {
+                let next_queue_offset = next_queue_offsets
+                    .entry((topic.clone(), queue_id))
+                    .or_insert_with(|| {
+                        state
+                            .queue(&topic, queue_id)
+                            .map_or(0, |units| units.len() as i64)
+                    });
+                *next_queue_offset += 1;
+                *next_queue_offset - 1
+            } else {
+                0
+            };
+            msg.message_ext_inner.queue_offset = queue_offset;
+            msg.message_ext_inner.commit_log_offset = wrote_offset;
+            msg.message_ext_inner.store_size = 0;
+            let encoded = match MessageDecoder::encode(&msg.message_ext_inner, false) {
+                Ok(encoded) => encoded,
+                Err(e) => {
+                    warn!("encode message failed, topic: {}, {}", topic, e);
+                    return Err(PutMessageResult::new_default(
+                        PutMessageStatus::MessageIllegal,
+                    ));
+                }
+            };
+            let size = encoded.len() as i32;
+            let tags_code = if msg.tags_code != 0 {
+                msg.tags_code
+            } else {
+                tags_string2tags_code(msg.get_tags().as_ref())
+            };
+            appended.push(PendingAppend {
+                msg,
+                consumable,
+                wrote_offset,
+                queue_offset,
+                size,
+                tags_code,
+                encoded,
+            });
+            wrote_offset += size as i64;
+        }
+        for pending in appended.iter_mut() {
+            state
+                .commit_log
+                .insert(pending.wrote_offset, std::mem::take(&mut pending.encoded));
+            if pending.consumable {
+                state
+                    .consume_queues
+                    .entry(pending.msg.topic().clone())
+                    .or_default()
+                    .entry(pending.msg.queue_id())
+                    .or_default()
+                    .push(QueueUnit {
+                        commit_log_offset: pending.wrote_offset,
+                        size: pending.size,
+                        tags_code: pending.tags_code,
+                        store_timestamp,
+                    });
+            }
+        }
+        state.max_phy_offset = wrote_offset;
+        drop(state);
+
+        let results = appended
+            .into_iter()
+            .map(
+                |PendingAppend {
+                     msg,
+                     consumable,
+                     wrote_offset,
+                     queue_offset,
+                     size,
+                     tags_code,
+                     ..
+                 }| {
+                    let mut dispatch_request = DispatchRequest {
+                        topic: msg.topic().clone(),
+                        queue_id: msg.queue_id(),
+                        commit_log_offset: wrote_offset,
+                        msg_size: size,
+                        tags_code,
+                        store_timestamp,
+                        consume_queue_offset: queue_offset,
+                        keys: msg.get_keys().unwrap_or_default(),
+                        success: true,
+                        uniq_key: msg.get_property(&CheetahString::from_static_str(
+                            MessageConst::PROPERTY_UNIQ_CLIENT_MESSAGE_ID_KEYIDX,
+                        )),
+                        sys_flag: msg.sys_flag(),
+                        properties_map: Some(msg.get_properties().clone()),
+                        ..Default::default()
+                    };
+                    for dispatcher in self.dispatcher_list.iter() {
+                        dispatcher.dispatch(&mut dispatch_request);
+                    }
+                    if consumable {
+                        self.notify_message_arrive_if_necessary(&mut dispatch_request);
+                    }
+                    AppendMessageResult {
+                        status: AppendMessageStatus::PutOk,
+                        wrote_offset,
+                        wrote_bytes: size,
+                        msg_id: Some(build_message_id(msg.store_host(), wrote_offset)),
+                        store_timestamp,
+                        logics_offset: queue_offset,
+                        msg_num: 1,
+                        ..Default::default()
+                    }
+                },
+            )
+            .collect();
+        Ok(results)
+    }
+
+    fn select(&self, commit_log_offset: i64) -> Option<SelectMappedBufferResult> {
+        let state = self.state.read();
+        let bytes = state.commit_log.get(&commit_log_offset)?;
+        Some(SelectMappedBufferResult {
+            start_offset: commit_log_offset as u64,
+            size: bytes.len() as i32,
+            bytes: Some(bytes.clone()),
+            ..Default::default()
+        })
+    }
+}
+
+impl MessageStore for MemoryMessageStore {
+    async fn load(&mut self) -> bool {
+        true
+    }
+
+    fn start(&mut self) -> Result<(), StoreError> {
+        self.shutdown.store(false, Ordering::Release);
+        Ok(())
+    }
+
+    fn shutdown(&mut self) {
+        self.shutdown.store(true, Ordering::Release);
+    }
+
+    fn destroy(&mut self) {
+        *self.state.write() = MemoryStoreState::default();
+    }
+
+    async fn put_message(&mut self, mut msg: MessageExtBrokerInner) -> PutMessageResult {
+        for hook in self.put_message_hook_list.read().iter() {
+            if let Some(result) = hook.execute_before_put_message(&mut msg) {
+                return result;
+            }
+        }
+        match self.append_all(vec![msg]) {
+            Ok(results) => PutMessageResult::new_append_result(
+                PutMessageStatus::PutOk,
+                results.into_iter().next(),
+            ),
+            Err(result) => result,
+        }
+    }
+
+    async fn put_messages(&mut self, mut message_ext_batch: MessageExtBatch) -> PutMessageResult {
+        for hook in self.put_message_hook_list.read().iter() {
+            if let Some(result) =
+                hook.execute_before_put_message(&mut message_ext_batch.message_ext_broker_inner)
+            {
+                return result;
+            }
+        }
+        let batch = &message_ext_batch.message_ext_broker_inner;
+        let messages = MessageDecoder::decode_messages_batch(&batch.message_ext_inner);
+        let messages = messages
+            .into_iter()
+            .map(|message_ext| {
+                let mut msg = MessageExtBrokerInner {
+                    message_ext_inner: message_ext,
+                    ..Default::default()
+                };
+                msg.tags_code = tags_string2tags_code(msg.get_tags().as_ref());
+                msg
+            })
+            .collect::<Vec<_>>();
+        let results = match self.append_all(messages) {
+            Ok(results) => results,
+            Err(result) => return result,
+        };
+        let msg_num = results.len() as i32;
+        let wrote_bytes = results.iter().map(|result| result.wrote_bytes).sum();
+        match results.into_iter().next() {
+            None => PutMessageResult::new_default(PutMessageStatus::MessageIllegal),
+            Some(mut result) => {
+                result.wrote_bytes = wrote_bytes;
+                result.msg_num = msg_num;
+                PutMessageResult::new_append_result(PutMessageStatus::PutOk, Some(result))
+            }
+        }
+    }
+
+    async fn get_message(
+        &self,
+        group: &CheetahString,
+        topic: &CheetahString,
+        queue_id: i32,
+        offset: i64,
+        max_msg_nums: i32,
+        message_filter: Option<Arc<Box<dyn MessageFilter>>>,
+    ) -> Option<GetMessageResult> {
+        self.get_message_with_size_limit(
+            group,
+            topic,
+            queue_id,
+            offset,
+            max_msg_nums,
+            MAX_PULL_MSG_SIZE,
+            message_filter,
+        )
+        .await
+    }
+
+    async fn get_message_with_size_limit(
+        &self,
+        group: &CheetahString,
+        topic: &CheetahString,
+        queue_id: i32,
+        offset: i64,
+        max_msg_nums: i32,
+        max_total_msg_size: i32,
+        message_filter: Option<Arc<Box<dyn MessageFilter>>>,
+    ) -> Option<GetMessageResult> {
+        if self.shutdown.load(Ordering::Acquire) {
+            warn!("message store has shutdown, so getMessage is forbidden");
+            return None;
+        }
+        if !self.running_flags.is_readable() {
+            warn!("message store is not readable, so getMessage is forbidden");
+            return None;
+        }
+        let mut result = GetMessageResult::new();
+        let state = self.state.read();
+        let (status, next_begin_offset, max_offset) = match state.queue(topic, queue_id) {
+            None => (
+                GetMessageStatus::NoMatchedLogicQueue,
+                self.next_offset_correction(offset, 0),
+                0,
+            ),
+            Some(units) => {
+                let max_offset = units.len() as i64;
+                if max_offset == 0 {
+                    (
+                        GetMessageStatus::NoMessageInQueue,
+                        self.next_offset_correction(offset, 0),
+                        max_offset,
+                    )
+                } else if offset < 0 {
+                    (
+                        GetMessageStatus::OffsetTooSmall,
+                        self.next_offset_correction(offset, 0),
+                        max_offset,
+                    )
+                } else if offset == max_offset {
+                    (
+                        GetMessageStatus::OffsetOverflowOne,
+                        self.next_offset_correction(offset, offset),
+                        max_offset,
+                    )
+                } else if offset > max_offset {
+                    (
+                        GetMessageStatus::OffsetOverflowBadly,
+                        self.next_offset_correction(offset, max_offset),
+                        max_offset,
+                    )
+                } else {
+                    let max_pull_size = max_total_msg_size.clamp(100, MAX_PULL_MSG_SIZE);
+                    let mut next_begin_offset = offset;
+                    for unit in units[offset as usize..].iter() {
+                        if result.message_count() >= max_msg_nums
+                            || (result.buffer_total_size() > 0
+                                && result.buffer_total_size() + unit.size > max_pull_size)
+                        {
+                            break;
+                        }
+                        let queue_offset = next_begin_offset;
+                        next_begin_offset += 1;
+                        let bytes = &state.commit_log[&unit.commit_log_offset];
+                        if let Some(filter) = message_filter.as_ref() {
+                            if !filter.is_matched_by_consume_queue(Some(unit.tags_code), None)
+                                || !filter.is_matched_by_commit_log(Some(bytes.as_ref()), None)
+                            {
+                                continue;
+                            }
+                        }
+                        result.add_message(
+                            SelectMappedBufferResult {
+                                start_offset: unit.commit_log_offset as u64,
+                                size: unit.size,
+                                bytes: Some(bytes.clone()),
+                                ..Default::default()
+                            },
+                            queue_offset as u64,
+                            1,
+                        );
+                    }
+                    let status = if result.message_count() > 0 {
+                        GetMessageStatus::Found
+                    } else {
+                        GetMessageStatus::NoMatchedMessage
+                    };
+                    (status, next_begin_offset, max_offset)
+                }
+            }
+        };
+        result.set_status(Some(status));
+        result.set_next_begin_offset(next_begin_offset);
+        result.set_min_offset(0);
+        result.set_max_offset(max_offset);
+        Some(result)
+    }
+
+    fn get_max_offset_in_queue(&self, topic: &CheetahString, queue_id: i32) -> i64 {
+        self.state
+            .read()
+            .queue(topic, queue_id)
+            .map_or(0, |units| units.len() as i64)
+    }
+
+    fn get_max_offset_in_queue_committed(
+        &self,
+        topic: &CheetahString,
+        queue_id: i32,
+        committed: bool,
+    ) -> i64 {
+        self.get_max_offset_in_queue(topic, queue_id)
+    }
+
+    fn get_min_offset_in_queue(&self, topic: &CheetahString, queue_id: i32) -> i64 {
+        0
+    }
+
+    fn get_timer_message_store(&self) -> Option<&Arc<TimerMessageStore>> {
+        self.timer_message_store.as_ref()
+    }
+
+    fn set_timer_message_store(&mut self, timer_message_store: Arc<TimerMessageStore>) {
+        self.timer_message_store = Some(timer_message_store);
+    }
+
+    fn get_commit_log_offset_in_queue(
+        &self,
+        topic: &CheetahString,
+        queue_id: i32,
+        consume_queue_offset: i64,
+    ) -> i64 {
+        self.state
+            .read()
+            .queue(topic, queue_id)
+            .and_then(|units| units.get(consume_queue_offset as usize))
+            .map_or(0, |unit| unit.commit_log_offset)
+    }
+
+    fn get_offset_in_queue_by_time(
+        &self,
+        topic: &CheetahString,
+        queue_id: i32,
+        timestamp: i64,
+    ) -> i64 {
+        self.get_offset_in_queue_by_time_with_boundary(
+            topic,
+            queue_id,
+            timestamp,
+            BoundaryType::Lower,
+        )
+    }
+
+    fn get_offset_in_queue_by_time_with_boundary(
+        &self,
+        topic: &CheetahString,
+        queue_id: i32,
+        timestamp: i64,
+        boundary_type: BoundaryType,
+    ) -> i64 {
+        let state = self.state.read();
+        let Some(units) = state.queue(topic, queue_id) else {
+            return 0;
+        };
+        match boundary_type {
+            BoundaryType::Lower => units
+                .iter()
+                .position(|unit| unit.store_timestamp >= timestamp)
+                .unwrap_or(units.len()) as i64,
+            BoundaryType::Upper => units
+                .iter()
+                .rposition(|unit| unit.store_timestamp <= timestamp)
+                .map_or(0, |index| index as i64),
+        }
+    }
+
+    fn look_message_by_offset(&self, commit_log_offset: i64) -> Option<MessageExt> {
+        let mut bytes = self.select(commit_log_offset)?.bytes?;
+        MessageDecoder::decode(&mut bytes, true, false, false, false, false)
+    }
+
+    fn look_message_by_offset_with_size(
+        &self,
+        commit_log_offset: i64,
+        size: i32,
+    ) -> Option<MessageExt> {
+        self.look_message_by_offset(commit_log_offset)
+    }
+
+    fn select_one_message_by_offset(
+        &self,
+        commit_log_offset: i64,
+    ) -> Option<SelectMappedBufferResult> {
+        self.select(commit_log_offset)
+    }
+
+    fn select_one_message_by_offset_with_size(
+        &self,
+        commit_log_offset: i64,
+        msg_size: i32,
+    ) -> Option<SelectMappedBufferResult> {
+        self.select(commit_log_offset)
+    }
+
+    fn get_running_data_info(&self) -> String {
+        format!("{:?}", self.get_runtime_info())
+    }
+
+    fn get_timing_message_count(&self, topic: &CheetahString) -> i64 {
+        0
+    }
+
+    fn get_runtime_info(&self) -> HashMap<String, String> {
+        let state = self.state.read();
+        let mut info = HashMap::new();
+        info.insert(
+            "commitLogMaxOffset".to_string(),
+            state.max_phy_offset.to_string(),
+        );
+        info.insert("commitLogMinOffset".to_string(), "0".to_string());
+        info.insert(
+            "messageCount".to_string(),
+            state.commit_log.len().to_string(),
+        );
+        info
+    }
+
+    fn get_max_phy_offset(&self) -> i64 {
+        self.state.read().max_phy_offset
+    }
+
+    fn get_min_phy_offset(&self) -> i64 {
+        0
+    }
+
+    fn get_earliest_message_time(&self, topic: &CheetahString, queue_id: i32) -> i64 {
+        self.get_message_store_timestamp(topic, queue_id, 0)
+    }
+
+    fn get_earliest_message_time_store(&self) -> i64 {
+        self.state
+            .read()
+            .consume_queues
+            .values()
+            .flat_map(|queues| queues.values())
+            .filter_map(|units| units.first())
+            .map(|unit| unit.store_timestamp)
+            .min()
+            .unwrap_or(-1)
+    }
+
+    fn get_message_store_timestamp(
+        &self,
+        topic: &CheetahString,
+        queue_id: i32,
+        consume_queue_offset: i64,
+    ) -> i64 {
+        self.state
+            .read()
+            .queue(topic, queue_id)
+            .and_then(|units| units.get(consume_queue_offset as usize))
+            .map_or(-1, |unit| unit.store_timestamp)
+    }
+
+    async fn get_message_store_timestamp_async(
+        &self,
+        topic: &CheetahString,
+        queue_id: i32,
+        consume_queue_offset: i64,
+    ) -> Result<i64, StoreError> {
+        Ok(self.get_message_store_timestamp(topic, queue_id, consume_queue_offset))
+    }
+
+    fn get_message_total_in_queue(&self, topic: &CheetahString, queue_id: i32) -> i64 {
+        self.get_max_offset_in_queue(topic, queue_id)
+    }
+
+    fn get_topic_disk_usage(&self, topic: &CheetahString) -> i64 {
+        self.state
+            .read()
+            .consume_queues
+            .get(topic)
+            .map_or(0, |queues| {
+                queues.values().flatten().map(|unit| unit.size as i64).sum()
+            })
+    }
+
+    fn get_commit_log_data(&self, offset: i64) -> Option<SelectMappedBufferResult> {
+        let state = self.state.read();
+        if !state.commit_log.contains_key(&offset) {
+            return None;
+        }
+        let mut data = BytesMut::new();
+        for bytes in state.commit_log.range(offset..).map(|(_, bytes)| bytes) {
+            data.extend_from_slice(bytes);
+        }
+        Some(SelectMappedBufferResult {
+            start_offset: offset as u64,
+            size: data.len() as i32,
+            bytes: Some(data.freeze()),
+            ..Default::default()
+        })
+    }
+
+    fn get_bulk_commit_log_data(
+        &self,
+        offset: i64,
+        size: i32,
+    ) -> Option<Vec<SelectMappedBufferResult>> {
+        self.get_commit_log_data(offset).map(|result| vec![result])
+    }
+
+    fn append_to_commit_log(
+        &self,
+        start_offset: i64,
+        data: &[u8],
+        data_start: i32,
+        data_length: i32,
+    ) -> Result<bool, StoreError> {
+        warn!("MemoryMessageStore does not replicate, appendToCommitLog is ignored");
+        Ok(false)
+    }
+
+    fn execute_delete_files_manually(&self) {}
+
+    async fn query_message(
+        &self,
+        topic: &CheetahString,
+        key: &CheetahString,
+        max_num: i32,
+        begin: i64,
+        end: i64,
+    ) -> Option<QueryMessageResult> {
+        let mut result = QueryMessageResult::default();
+        let state = self.state.read();
+        for (offset, bytes) in state.commit_log.iter() {
+            if result.message_maped_list.len() >= max_num as usize {
+                break;
+            }
+            let Some(msg) =
+                MessageDecoder::decode(&mut bytes.clone(), false, false, false, false, false)
+            else {
+                continue;
+            };
+            if msg.get_topic() != topic || msg.store_timestamp < begin || msg.store_timestamp > end
+            {
+                continue;
+            }
+            let uniq_key = msg.get_property(&CheetahString::from_static_str(
+                MessageConst::PROPERTY_UNIQ_CLIENT_MESSAGE_ID_KEYIDX,
+            ));
+            let key_matched = msg
+                .get_keys()
+                .is_some_and(|keys| keys.split(MessageConst::KEY_SEPARATOR).any(|k| k == key))
+                || uniq_key.as_ref() == Some(key);
+            if key_matched {
+                result.add_message(SelectMappedBufferResult {
+                    start_offset: *offset as u64,
+                    size: bytes.len() as i32,
+                    bytes: Some(bytes.clone()),
+                    ..Default::default()
+                });
+            }
+        }
+        result.index_last_update_phyoffset = state.max_phy_offset;
+        result.index_last_update_timestamp = get_current_millis() as i64;
+        Some(result)
+    }
+
+    fn update_ha_master_address(&self, new_addr: &CheetahString) {}
+
+    fn update_master_address(&self, new_addr: &CheetahString) {}
+
+    fn slave_fall_behind_much(&self) -> i64 {
+        0
+    }
+
+    fn delete_topics(&mut self, delete_topics: Vec<&CheetahString>) -> i32 {
+        let mut state = self.state.write();
+        delete_topics
+            .into_iter()
+            .filter(|topic| state.consume_queues.remove(*topic).is_some())
+            .count() as i32
+    }
+
+    fn clean_unused_topic(&self, retain_topics: &HashSet<String>) -> i32 {
+        let mut state = self.state.write();
+        let before = state.consume_queues.len();
+        state
+            .consume_queues
+            .retain(|topic, _| retain_topics.contains(topic.as_str()));
+        (before - state.consume_queues.len()) as i32
+    }
+
+    fn clean_expired_consumer_queue(&self) {}
+
+    fn check_in_mem_by_consume_offset(
+        &self,
+        topic: &CheetahString,
+        queue_id: i32,
+        consume_offset: i64,
+        batch_size: i32,
+    ) -> bool {
+        self.check_in_store_by_consume_offset(topic, queue_id, consume_offset)
+    }
+
+    fn check_in_store_by_consume_offset(
+        &self,
+        topic: &CheetahString,
+        queue_id: i32,
+        consume_offset: i64,
+    ) -> bool {
+        consume_offset >= 0 && consume_offset < self.get_max_offset_in_queue(topic, queue_id)
+    }
+
+    fn dispatch_behind_bytes(&self) -> i64 {
+        0
+    }
+
+    fn flush(&self) -> i64 {
+        self.get_max_phy_offset()
+    }
+
+    fn get_flushed_where(&self) -> i64 {
+        self.get_max_phy_offset()
+    }
+
+    fn reset_write_offset(&self, phy_offset: i64) -> bool {
+        self.state.write().truncate(phy_offset);
+        true
+    }
+
+    fn get_confirm_offset(&self) -> i64 {
+        if self.confirm_offset < 0 {
+            self.get_max_phy_offset()
+        } else {
+            self.confirm_offset
+        }
+    }
+
+    fn set_confirm_offset(&mut self, phy_offset: i64) {
+        self.confirm_offset = phy_offset;
+    }
+
+    fn is_os_page_cache_busy(&self) -> bool {
+        false
+    }
+
+    fn lock_time_millis(&self) -> i64 {
+        0
+    }
+
+    fn is_transient_store_pool_deficient(&self) -> bool {
+        false
+    }
+
+    fn get_dispatcher_list(&self) -> &[Arc<dyn CommitLogDispatcher>] {
+        self.dispatcher_list.as_slice()
+    }
+
+    fn add_dispatcher(&mut self, dispatcher: Arc<dyn CommitLogDispatcher>) {
+        self.dispatcher_list.push(dispatcher);
+    }
+
+    fn add_first_dispatcher(&mut self, dispatcher: Arc<dyn CommitLogDispatcher>) {
+        self.dispatcher_list.insert(0, dispatcher);
+    }
+
+    fn get_consume_queue(&self, topic: &CheetahString, queue_id: i32) -> Option<ArcConsumeQueue> {
+        None
+    }
+
+    fn find_consume_queue(&self, topic: &CheetahString, queue_id: i32) -> Option<ArcConsumeQueue> {
+        None
+    }
+
+    fn get_broker_stats_manager(&self) -> Option<&Arc<BrokerStatsManager>> {
+        self.broker_stats_manager.as_ref()
+    }
+
+    fn on_commit_log_append<MF: MappedFile>(
+        &self,
+        msg: &MessageExtBrokerInner,
+        result: &AppendMessageResult,
+        commit_log_file: &MF,
+    ) {
+    }
+
+    fn on_commit_log_dispatch<MF: MappedFile>(
+        &self,
+        dispatch_request: &DispatchRequest,
+        do_dispatch: bool,
+        commit_log_file: &MF,
+        is_recover: bool,
+        is_file_end: bool,
+    ) -> Result<(), StoreError> {
+        Ok(())
+    }
+
+    fn finish_commit_log_dispatch(&self) {}
+
+    fn get_message_store_config(&self) -> &MessageStoreConfig {
+        self.message_store_config.as_ref()
+    }
+
+    fn get_store_stats_service(&self) -> Arc<StoreStatsService> {
+        self.store_stats_service.clone()
+    }
+
+    fn get_store_checkpoint(&self) -> &StoreCheckpoint {
+        unimplemented!("MemoryMessageStore has no store checkpoint")
+    }
+
+    fn get_store_checkpoint_arc(&self) -> Arc<StoreCheckpoint> {
+        unimplemented!("MemoryMessageStore has no store checkpoint")
+    }
+
+    fn get_system_clock(&self) -> Arc<SystemClock> {
+        self.system_clock.clone()
+    }
+
+    fn get_commit_log(&self) -> Arc<CommitLog> {
+        unimplemented!("MemoryMessageStore has no file based commit log")
+    }
+
+    fn get_running_flags(&self) -> &RunningFlags {
+        self.running_flags.as_ref()
+    }
+
+    fn get_running_flags_arc(&self) -> Arc<RunningFlags> {
+        self.running_flags.clone()
+    }
+
+    fn get_transient_store_pool(&self) -> Arc<TransientStorePool> {
+        unimplemented!("MemoryMessageStore has no transient store pool")
+    }
+
+    fn get_allocate_mapped_file_service(&self) -> Arc<AllocateMappedFileService> {
+        unimplemented!("MemoryMessageStore has no mapped files")
+    }
+
+    fn truncate_dirty_logic_files(&self, phy_offset: i64) -> Result<(), StoreError> {
+        self.state.write().truncate(phy_offset);
+        Ok(())
+    }
+
+    fn unlock_mapped_file<MF: MappedFile>(&self, unlock_mapped_file: &MF) {}
+
+    fn get_queue_store(&self) -> &dyn Any {
+        self
+    }
+
+    fn is_sync_disk_flush(&self) -> bool {
+        self.message_store_config.flush_disk_type == FlushDiskType::SyncFlush
+    }
+
+    fn is_sync_master(&self) -> bool {
+        self.message_store_config.broker_role == BrokerRole::SyncMaster
+    }
+
+    fn assign_offset(&self, msg: &mut MessageExtBrokerInner) -> Result<(), StoreError> {
+        let offset = self.get_max_offset_in_queue(msg.topic(), msg.queue_id());
+        msg.message_ext_inner.queue_offset = offset;
+        Ok(())
+    }
+
+    fn increase_offset(&self, msg: &MessageExtBrokerInner, message_num: i16) {}
+
+    fn get_master_store_in_process<M: MessageStore>(&self) -> Option<Arc<M>> {
+        None
+    }
+
+    fn set_master_store_in_process<M: MessageStore>(&self, master_store_in_process: Arc<M>) {}
+
+    fn get_data(&self, offset: i64, size: i32, byte_buffer: &mut BytesMut) -> bool {
+        match self
+            .get_commit_log_data(offset)
+            .and_then(|result| result.bytes)
+        {
+            Some(bytes) if bytes.len() >= size as usize => {
+                byte_buffer.extend_from_slice(&bytes[..size as usize]);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn set_alive_replica_num_in_group(&self, alive_replica_nums: i32) {
+        self.alive_replica_num
+            .store(alive_replica_nums, Ordering::Release);
+    }
+
+    fn get_alive_replica_num_in_group(&self) -> i32 {
+        self.alive_replica_num.load(Ordering::Acquire)
+    }
+
+    fn wakeup_ha_client(&self) {}
+
+    fn get_master_flushed_offset(&self) -> i64 {
+        self.master_flushed_offset.load(Ordering::Acquire)
+    }
+
+    fn get_broker_init_max_offset(&self) -> i64 {
+        self.broker_init_max_offset
+    }
+
+    fn set_master_flushed_offset(&self, master_flushed_offset: i64) {
+        self.master_flushed_offset
+            .store(master_flushed_offset, Ordering::Release);
+    }
+
+    fn set_broker_init_max_offset(&mut self, broker_init_max_offset: i64) {
+        self.broker_init_max_offset = broker_init_max_offset;
+    }
+
+    fn calc_delta_checksum(&self, from: i64, to: i64) -> Vec<u8> {
+        // Never part of an HA group, there is no replica to compare checksums with.
+        Vec::new()
+    }
+
+    fn truncate_files(&self, offset_to_truncate: i64) -> Result<bool, StoreError> {
+        self.state.write().truncate(offset_to_truncate);
+        Ok(true)
+    }
+
+    fn is_offset_aligned(&self, offset: i64) -> bool {
+        let state = self.state.read();
+        offset == state.max_phy_offset || state.commit_log.contains_key(&offset)
+    }
+
+    fn get_put_message_hook_list(&self) -> Vec<Arc<dyn PutMessageHook>> {
+        self.put_message_hook_list
+            .read()
+            .iter()
+            .map(|hook| hook.clone() as Arc<dyn PutMessageHook>)
+            .collect()
+    }
+
+    fn set_send_message_back_hook(&self, send_message_back_hook: Arc<dyn SendMessageBackHook>) {
+        warn!("MemoryMessageStore does not support send message back hooks");
+    }
+
+    fn get_send_message_back_hook(&self) -> Option<Arc<dyn SendMessageBackHook>> {
+        None
+    }
+
+    fn get_last_file_from_offset(&self) -> i64 {
+        0
+    }
+
+    fn get_last_mapped_file(&self, start_offset: i64) -> bool {
+        false
+    }
+
+    fn set_physical_offset(&self, phy_offset: i64) {
+        self.state.write().truncate(phy_offset);
+    }
+
+    fn is_mapped_files_empty(&self) -> bool {
+        self.state.read().commit_log.is_empty()
+    }
+
+    fn get_state_machine_version(&self) -> i64 {
+        0
+    }
+
+    fn check_message_and_return_size(
+        &self,
+        bytes: &mut Bytes,
+        check_crc: bool,
+        check_dup_info: bool,
+        read_body: bool,
+    ) -> DispatchRequest {
+        let msg_size = bytes.len() as i32;
+        match MessageDecoder::decode(bytes, read_body, false, false, false, check_crc) {
+            None => DispatchRequest {
+                msg_size: -1,
+                success: false,
+                ..Default::default()
+            },
+            Some(msg) => DispatchRequest {
+                topic: msg.get_topic().clone(),
+                queue_id: msg.queue_id,
+                commit_log_offset: msg.commit_log_offset,
+                msg_size,
+                tags_code: tags_string2tags_code(msg.get_tags().as_ref()),
+                store_timestamp: msg.store_timestamp,
+                consume_queue_offset: msg.queue_offset,
+                keys: msg.get_keys().unwrap_or_default(),
+                success: true,
+                sys_flag: msg.sys_flag,
+                prepared_transaction_offset: msg.prepared_transaction_offset,
+                properties_map: Some(msg.get_properties().clone()),
+                ..Default::default()
+            },
+        }
+    }
+
+    fn remain_transient_store_buffer_numbs(&self) -> i32 {
+        i32::MAX
+    }
+
+    fn remain_how_many_data_to_commit(&self) -> i64 {
+        0
+    }
+
+    fn remain_how_many_data_to_flush(&self) -> i64 {
+        0
+    }
+
+    fn is_shutdown(&self) -> bool {
+        self.shutdown.load(Ordering::Acquire)
+    }
+
+    fn estimate_message_count(
+        &self,
+        topic: &CheetahString,
+        queue_id: i32,
+        from: i64,
+        to: i64,
+        filter: &dyn MessageFilter,
+    ) -> i64 {
+        let state = self.state.read();
+        let Some(units) = state.queue(topic, queue_id) else {
+            return 0;
+        };
+        let from = from.clamp(0, units.len() as i64) as usize;
+        let to = to.clamp(from as i64, units.len() as i64) as usize;
+        units[from..to]
+            .iter()
+            .filter(|unit| filter.is_matched_by_consume_queue(Some(unit.tags_code), None))
+            .count() as i64
+    }
+
+    fn recover_topic_queue_table(&mut self) {}
+
+    fn notify_message_arrive_if_necessary(&self, dispatch_request: &mut DispatchRequest) {
+        if let Some(listener) = self.message_arriving_listener.as_ref() {
+            listener.arriving(
+                &dispatch_request.topic,
+                dispatch_request.queue_id,
+                dispatch_request.consume_queue_offset + 1,
+                Some(dispatch_request.tags_code),
+                dispatch_request.store_timestamp,
+                dispatch_request.bit_map.clone(),
+                dispatch_request.properties_map.as_ref(),
+            );
+        }
+    }
+
+    fn set_put_message_hook(&self, put_message_hook: BoxedPutMessageHook) {
+        self.put_message_hook_list
+            .write()
+            .push(Arc::from(put_message_hook));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rocketmq_common::common::message::message_single::Message;
+
+    use super::*;
+    use crate::consume_queue::consume_queue_ext::CqExtUnit;
+
+    fn new_store() -> MemoryMessageStore {
+        MemoryMessageStore::new(Arc::new(MessageStoreConfig::default()))
+    }
+
+    fn new_message(topic: &str, queue_id: i32, tags: &str, keys: &str) -> MessageExtBrokerInner {
+        let mut msg = MessageExtBrokerInner::default();
+        msg.set_topic(CheetahString::from_slice(topic));
+        msg.set_body(Bytes::from_static(b"hello memory store"));
+        msg.set_tags(CheetahString::from_slice(tags));
+        msg.set_keys(CheetahString::from_slice(keys));
+        msg.message_ext_inner.queue_id = queue_id;
+        msg
+    }
+
+    struct TagsCodeFilter(i64);
+
+    impl MessageFilter for TagsCodeFilter {
+        fn is_matched_by_consume_queue(
+            &self,
+            tags_code: Option<i64>,
+            _cq_ext_unit: Option<&CqExtUnit>,
+        ) -> bool {
+            tags_code == Some(self.0)
+        }
+
+        fn is_matched_by_commit_log(
+            &self,
+            _msg_buffer: Option<&[u8]>,
+            _properties: Option<&HashMap<CheetahString, CheetahString>>,
+        ) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn put_then_get_round_trips_messages() {
+        let mut store = new_store();
+        let topic = CheetahString::from_static_str("TopicTest");
+        for _ in 0..3 {
+            let result = store
+                .put_message(new_message("TopicTest", 1, "TagA", "key"))
+                .await;
+            assert_eq!(result.put_message_status(), PutMessageStatus::PutOk);
+        }
+        assert_eq!(store.get_max_offset_in_queue(&topic, 1), 3);
+        assert_eq!(store.get_max_offset_in_queue(&topic, 0), 0);
+
+        let group = CheetahString::from_static_str("group");
+        let result = store
+            .get_message(&group, &topic, 1, 1, 32, None)
+            .await
+            .unwrap();
+        assert_eq!(result.status(), Some(GetMessageStatus::Found));
+        assert_eq!(result.message_count(), 2);
+        assert_eq!(result.next_begin_offset(), 3);
+        assert_eq!(result.max_offset(), 3);
+
+        let mut bytes = result.message_mapped_list()[0].get_bytes().unwrap();
+        let msg = MessageDecoder::decode(&mut bytes, true, false, false, false, false).unwrap();
+        assert_eq!(msg.get_topic(), &topic);
+        assert_eq!(msg.queue_offset, 1);
+        assert_eq!(msg.get_body().unwrap().as_ref(), b"hello memory store");
+
+        let overflow = store
+            .get_message(&group, &topic, 1, 3, 32, None)
+            .await
+            .unwrap();
+        assert_eq!(overflow.status(), Some(GetMessageStatus::OffsetOverflowOne));
+        let missing = store
+            .get_message(&group, &topic, 7, 0, 32, None)
+            .await
+            .unwrap();
+        assert_eq!(
+            missing.status(),
+            Some(GetMessageStatus::NoMatchedLogicQueue)
+        );
+    }
+
+    #[tokio::test]
+    async fn topic_disk_usage_sums_the_message_sizes() {
+        let mut store = new_store();
+        let topic = CheetahString::from_static_str("TopicTest");
+        assert_eq!(store.get_topic_disk_usage(&topic), 0);
+
+        let mut wrote_bytes = 0;
+        for queue_id in 0..2 {
+            let result = store
+                .put_message(new_message("TopicTest", queue_id, "TagA", "key"))
+                .await;
+            wrote_bytes += result.append_message_result().unwrap().wrote_bytes as i64;
+        }
+        store
+            .put_message(new_message("OtherTopic", 0, "TagA", "key"))
+            .await;
+        assert_eq!(store.get_topic_disk_usage(&topic), wrote_bytes);
+    }
+
+    #[tokio::test]
+    async fn get_message_applies_filter_and_looks_up_by_offset() {
+        let mut store = new_store();
+        let topic = CheetahString::from_static_str("TopicTest");
+        let first = store
+            .put_message(new_message("TopicTest", 0, "TagA", "k1"))
+            .await;
+        store
+            .put_message(new_message("TopicTest", 0, "TagB", "k2"))
+            .await;
+
+        let tag_b = tags_string2tags_code(Some(&CheetahString::from_static_str("TagB")));
+        let filter: Arc<Box<dyn MessageFilter>> = Arc::new(Box::new(TagsCodeFilter(tag_b)));
+        let result = store
+            .get_message(
+                &CheetahString::from_static_str("group"),
+                &topic,
+                0,
+                0,
+                32,
+                Some(filter),
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.message_count(), 1);
+        assert_eq!(result.next_begin_offset(), 2);
+
+        let wrote_offset = first.append_message_result().unwrap().wrote_offset;
+        let msg = store.look_message_by_offset(wrote_offset).unwrap();
+        assert_eq!(msg.get_tags().unwrap().as_str(), "TagA");
+        assert_eq!(msg.commit_log_offset, wrote_offset);
+
+        let query = store
+            .query_message(
+                &topic,
+                &CheetahString::from_static_str("k2"),
+                32,
+                0,
+                i64::MAX,
+            )
+            .await
+            .unwrap();
+        assert_eq!(query.message_maped_list.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn prepared_transaction_message_is_not_consumable() {
+        let mut store = new_store();
+        let mut msg = new_message("TopicTest", 0, "TagA", "k1");
+        msg.message_ext_inner.sys_flag = MessageSysFlag::TRANSACTION_PREPARED_TYPE;
+        let result = store.put_message(msg).await;
+        assert_eq!(result.put_message_status(), PutMessageStatus::PutOk);
+        assert_eq!(
+            store.get_max_offset_in_queue(&CheetahString::from_static_str("TopicTest"), 0),
+            0
+        );
+        assert!(store.get_max_phy_offset() > 0);
+    }
+
+    #[tokio::test]
+    async fn put_messages_splits_batch() {
+        let mut store = new_store();
+        let messages = (0..3)
+            .map(|i| {
+                let mut message = Message::new("TopicTest", format!("body-{i}").as_bytes());
+                message.set_tags(CheetahString::from_static_str("TagA"));
+                message
+            })
+            .collect::<Vec<_>>();
+        let mut batch = MessageExtBatch::default();
+        batch
+            .message_ext_broker_inner
+            .set_topic(CheetahString::from_static_str("TopicTest"));
+        batch
+            .message_ext_broker_inner
+            .set_body(MessageDecoder::encode_messages(&messages));
+
+        let result = store.put_messages(batch).await;
+        assert_eq!(result.put_message_status(), PutMessageStatus::PutOk);
+        assert_eq!(result.append_message_result().unwrap().msg_num, 3);
+        assert_eq!(
+            store.get_max_offset_in_queue(&CheetahString::from_static_str("TopicTest"), 0),
+            3
+        );
+    }
+
+    #[tokio::test]
+    async fn put_messages_writes_the_batch_at_consecutive_offsets() {
+        let mut store = new_store();
+        let first = store
+            .put_message(new_message("TopicTest", 0, "TagA", "key"))
+            .await;
+        let first = first.append_message_result().unwrap().clone();
+
+        let messages = (0..3)
+            .map(|i| Message::new("TopicTest", format!("body-{i}").as_bytes()))
+            .collect::<Vec<_>>();
+        let mut batch = MessageExtBatch::default();
+        batch
+            .message_ext_broker_inner
+            .set_topic(CheetahString::from_static_str("TopicTest"));
+        batch
+            .message_ext_broker_inner
+            .set_body(MessageDecoder::encode_messages(&messages));
+        let result = store.put_messages(batch).await;
+        let result = result.append_message_result().unwrap();
+        assert_eq!(result.logics_offset, 1);
+        assert_eq!(
+            result.wrote_offset,
+            first.wrote_offset + first.wrote_bytes as i64
+        );
+        assert_eq!(
+            store.get_max_phy_offset(),
+            result.wrote_offset + result.wrote_bytes as i64
+        );
+
+        let topic = CheetahString::from_static_str("TopicTest");
+        let mut commit_log_offset = result.wrote_offset;
+        for (queue_offset, body) in (1..4).zip(["body-0", "body-1", "body-2"]) {
+            let message = store.look_message_by_offset(commit_log_offset).unwrap();
+            assert_eq!(message.queue_offset, queue_offset);
+            assert_eq!(message.get_body().unwrap().as_ref(), body.as_bytes());
+            assert_eq!(
+                store.get_commit_log_offset_in_queue(&topic, 0, queue_offset),
+                commit_log_offset
+            );
+            commit_log_offset += message.store_size as i64;
+        }
+    }
+}
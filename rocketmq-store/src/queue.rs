@@ -34,6 +34,7 @@ pub mod local_file_consume_queue_store;
 mod queue_offset_operator;
 pub mod referred_iterator;
 pub mod single_consume_queue;
+pub mod topic_retention;
 
 pub type ArcConsumeQueue = ArcMut<Box<dyn ConsumeQueueTrait>>;
 pub type ConsumeQueueTable =
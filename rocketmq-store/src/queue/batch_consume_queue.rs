@@ -18,6 +18,7 @@
 use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::sync::atomic::AtomicI64;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 use cheetah_string::CheetahString;
@@ -301,6 +302,12 @@ impl ConsumeQueueTrait for BatchConsumeQueue {
         todo!()
     }
 
+    #[inline]
+    fn advance_min_offset(&self, min_offset: i64) {
+        self.min_offset_in_queue
+            .fetch_max(min_offset, Ordering::SeqCst);
+    }
+
     #[inline]
     fn put_message_position_info_wrapper(&mut self, request: &DispatchRequest) {
         todo!()
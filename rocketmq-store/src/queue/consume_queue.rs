@@ -189,6 +189,14 @@ pub trait ConsumeQueueTrait: FileQueueLifeCycle {
     /// * `min_commit_log_offset` - The minimum commit log offset
     fn correct_min_offset(&self, min_commit_log_offset: i64);
 
+    /// Move the minimum offset forward, the entries before it can no longer be read
+    ///
+    /// Does nothing when the minimum offset is not before `min_offset` already.
+    ///
+    /// # Parameters
+    /// * `min_offset` - The new minimum offset in queue
+    fn advance_min_offset(&self, min_offset: i64);
+
     /// Process a dispatch request and update the consume queue
     ///
     /// # Parameters
@@ -15,13 +15,20 @@
  * limitations under the License.
  */
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use cheetah_string::CheetahString;
 use rocketmq_rust::ArcMut;
+use tracing::error;
 use tracing::info;
+use tracing::warn;
 
 use crate::consume_queue::consume_queue_ext::CqExtUnit;
+use crate::consume_queue::consume_queue_ext::MAX_EXT_UNIT_SIZE;
+use crate::consume_queue::consume_queue_ext::MIN_EXT_UNIT_SIZE;
 use crate::consume_queue::mapped_file_queue::MappedFileQueue;
+use crate::log_file::mapped_file::default_mapped_file_impl::DefaultMappedFile;
+use crate::log_file::mapped_file::MappedFile;
 
 const END_BLANK_DATA_LENGTH: usize = 4;
 
@@ -29,6 +36,14 @@ const END_BLANK_DATA_LENGTH: usize = 4;
 const MAX_ADDR: i64 = i32::MIN as i64 - 1;
 const MAX_REAL_OFFSET: i64 = MAX_ADDR - i64::MIN;
 
+const PUT_RETRY_TIMES: usize = 3;
+
+/// Extend of consume queue, stores the units which can not fit into the 8 bytes tags code of a
+/// consume queue unit, e.g. the filter bit map of a message.
+///
+/// The consume queue keeps the address of the unit in place of the tags code. Addresses are
+/// decorated offsets of the extend files: `offset + i64::MIN`, so they are all less than
+/// `i32::MIN` and never collide with a real tags code, see [`ConsumeQueueExt::is_ext_addr`].
 #[derive(Clone)]
 pub struct ConsumeQueueExt {
     mapped_file_queue: ArcMut<MappedFileQueue>,
@@ -63,15 +78,76 @@ impl ConsumeQueueExt {
         }
     }
 
+    /// Checks whether `address` is an address of the extend files rather than a tags code.
     pub fn is_ext_addr(address: i64) -> bool {
         address <= MAX_ADDR
     }
+
+    /// Transforms an offset of the extend files to an address.
+    pub fn decorate(offset: i64) -> i64 {
+        if !Self::is_ext_addr(offset) {
+            return offset.wrapping_add(i64::MIN);
+        }
+        offset
+    }
+
+    /// Transforms an address back to the offset of the extend files.
+    pub fn un_decorate(address: i64) -> i64 {
+        if Self::is_ext_addr(address) {
+            return address.wrapping_sub(i64::MIN);
+        }
+        address
+    }
 }
 
 impl ConsumeQueueExt {
-    pub fn truncate_by_max_address(&self, max_address: i64) {}
+    /// Deletes the files whose data all lie before `min_address`.
+    pub fn truncate_by_min_address(&self, min_address: i64) {
+        if !Self::is_ext_addr(min_address) {
+            return;
+        }
+        info!("Truncate consume queue ext by min {}.", min_address);
+        let real_offset = Self::un_decorate(min_address);
+        let mut will_remove_files = Vec::new();
+        for mapped_file in self.mapped_file_queue.get_mapped_files().read().iter() {
+            let file_tail_offset =
+                mapped_file.get_file_from_offset() as i64 + self.mapped_file_size as i64;
+            if file_tail_offset < real_offset {
+                info!(
+                    "Destroy consume queue ext by min: file={}, fileTailOffset={}, minOffset={}",
+                    mapped_file.get_file_name(),
+                    file_tail_offset,
+                    real_offset
+                );
+                if mapped_file.destroy(1000) {
+                    will_remove_files.push(mapped_file.clone());
+                }
+            }
+        }
+        self.mapped_file_queue
+            .mut_from_ref()
+            .delete_expired_file(will_remove_files);
+    }
 
-    pub fn truncate_by_min_address(&self, min_address: i64) {}
+    /// Drops the data after the unit at `max_address`.
+    pub fn truncate_by_max_address(&self, max_address: i64) {
+        if !Self::is_ext_addr(max_address) {
+            return;
+        }
+        info!("Truncate consume queue ext by max {}.", max_address);
+        let mut cq_ext_unit = CqExtUnit::default();
+        if !self.get(max_address, &mut cq_ext_unit) {
+            error!(
+                "[BUG] address {} of consume queue extend not found!",
+                max_address
+            );
+            return;
+        }
+        let real_offset = Self::un_decorate(max_address);
+        self.mapped_file_queue
+            .mut_from_ref()
+            .truncate_dirty_files(real_offset + cq_ext_unit.size() as i64);
+    }
 
     pub fn load(&mut self) -> bool {
         let result = self.mapped_file_queue.load();
@@ -85,17 +161,240 @@ impl ConsumeQueueExt {
         result
     }
 
-    pub fn recover(&mut self) {}
+    /// Finds the end of the written units. All files are kept, the consume queue truncates them
+    /// afterwards by its max address.
+    pub fn recover(&mut self) {
+        let mapped_files = self.mapped_file_queue.get_mapped_files().read().clone();
+        if mapped_files.is_empty() {
+            return;
+        }
+        let mut process_offset = 0i64;
+        for (index, mapped_file) in mapped_files.iter().enumerate() {
+            if index > 0 {
+                info!(
+                    "Recover next consume queue extend file, {}",
+                    mapped_file.get_file_name()
+                );
+            }
+            let buffer = mapped_file.get_mapped_file();
+            let mut mapped_file_offset = 0usize;
+            while mapped_file_offset < buffer.len() {
+                let size = CqExtUnit::read_size(&buffer[mapped_file_offset..]);
+                if size <= 0 {
+                    break;
+                }
+                mapped_file_offset += size as usize;
+            }
+            process_offset = mapped_file.get_file_from_offset() as i64 + mapped_file_offset as i64;
+        }
+        info!(
+            "All files of consume queue extend has been recovered over, last mapped file {}",
+            mapped_files.last().unwrap().get_file_name()
+        );
+        self.mapped_file_queue.set_flushed_where(process_offset);
+        self.mapped_file_queue.set_committed_where(process_offset);
+        self.mapped_file_queue.truncate_dirty_files(process_offset);
+    }
+
+    pub fn check_self(&self) {
+        self.mapped_file_queue.check_self();
+    }
 
+    /// Appends `cq_ext_unit` and returns its address, or `1` if it could not be stored.
     pub fn put(&self, cq_ext_unit: CqExtUnit) -> i64 {
-        unimplemented!()
+        let bit_map_size = cq_ext_unit
+            .filter_bit_map()
+            .as_ref()
+            .map_or(0, |bit_map| bit_map.len());
+        if MIN_EXT_UNIT_SIZE as usize + bit_map_size > MAX_EXT_UNIT_SIZE as usize {
+            error!(
+                "Size of cq ext unit is greater than {}, {}",
+                MAX_EXT_UNIT_SIZE,
+                MIN_EXT_UNIT_SIZE as usize + bit_map_size
+            );
+            return 1;
+        }
+        let size = cq_ext_unit.size() as usize;
+        if self.mapped_file_queue.get_max_offset() + size as i64 > MAX_REAL_OFFSET {
+            warn!(
+                "Capacity of ext is maximum!{}, {}",
+                self.mapped_file_queue.get_max_offset(),
+                size
+            );
+            return 1;
+        }
+        let data = cq_ext_unit.encode();
+        let mapped_file_queue = self.mapped_file_queue.mut_from_ref();
+        for _ in 0..PUT_RETRY_TIMES {
+            let mapped_file = match mapped_file_queue.get_last_mapped_file() {
+                Some(mapped_file) if !mapped_file.is_full() => Some(mapped_file),
+                _ => mapped_file_queue.get_last_mapped_file_mut_start_offset(0, true),
+            };
+            let Some(mapped_file) = mapped_file else {
+                error!(
+                    "Create mapped file when save consume queue extend, {}-{}",
+                    self.topic, self.queue_id
+                );
+                continue;
+            };
+            let wrote_position = mapped_file.get_wrote_position() as usize;
+            let blank_size =
+                self.mapped_file_size as usize - wrote_position - END_BLANK_DATA_LENGTH;
+            if size > blank_size {
+                self.full_fill_to_end(&mapped_file, wrote_position);
+                info!(
+                    "No enough space(need:{}, has:{}) of file {}, so fill to end",
+                    size,
+                    blank_size,
+                    mapped_file.get_file_name()
+                );
+                continue;
+            }
+            if mapped_file.append_message_bytes(&data) {
+                return Self::decorate(
+                    mapped_file.get_file_from_offset() as i64 + wrote_position as i64,
+                );
+            }
+        }
+        1
+    }
+
+    /// Marks the rest of `mapped_file` as unused, the next unit goes to a new file.
+    fn full_fill_to_end(&self, mapped_file: &Arc<DefaultMappedFile>, wrote_position: usize) {
+        mapped_file.get_mapped_file_mut()[wrote_position..wrote_position + 2]
+            .copy_from_slice(&(-1i16).to_be_bytes());
+        mapped_file.set_wrote_position(self.mapped_file_size);
+    }
+
+    pub fn flush(&self, flush_least_pages: i32) -> bool {
+        self.mapped_file_queue.flush(flush_least_pages)
     }
 
     pub fn destroy(&mut self) {
         self.mapped_file_queue.destroy();
     }
 
-    pub fn get(&self, address: i64, cq_ext_unit: &CqExtUnit) -> bool {
-        unimplemented!()
+    /// Reads the unit at `address` into `cq_ext_unit`, returns `false` if there is none.
+    pub fn get(&self, address: i64, cq_ext_unit: &mut CqExtUnit) -> bool {
+        if !Self::is_ext_addr(address) {
+            return false;
+        }
+        let real_offset = Self::un_decorate(address);
+        let Some(mapped_file) = self
+            .mapped_file_queue
+            .find_mapped_file_by_offset(real_offset, real_offset == 0)
+        else {
+            return false;
+        };
+        let pos = (real_offset % self.mapped_file_size as i64) as usize;
+        let read_position = mapped_file.get_read_position() as usize;
+        if pos >= read_position {
+            warn!(
+                "[BUG] Consume queue extend unit({}) is not found!",
+                real_offset
+            );
+            return false;
+        }
+        cq_ext_unit.read(&mapped_file.get_mapped_file()[pos..read_position])
+    }
+
+    /// Address of the end of the written units.
+    pub fn get_max_address(&self) -> i64 {
+        match self.mapped_file_queue.get_last_mapped_file() {
+            None => Self::decorate(0),
+            Some(mapped_file) => Self::decorate(
+                mapped_file.get_file_from_offset() as i64 + mapped_file.get_wrote_position() as i64,
+            ),
+        }
+    }
+
+    /// Address of the first unit still kept.
+    pub fn get_min_address(&self) -> i64 {
+        match self.mapped_file_queue.get_first_mapped_file() {
+            None => Self::decorate(0),
+            Some(mapped_file) => Self::decorate(mapped_file.get_file_from_offset() as i64),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn new_ext(dir: &TempDir) -> ConsumeQueueExt {
+        ConsumeQueueExt::new(
+            CheetahString::from_static_str("TopicTest"),
+            0,
+            CheetahString::from_string(dir.path().to_string_lossy().to_string()),
+            100,
+            64,
+        )
+    }
+
+    #[test]
+    fn decorate_and_un_decorate() {
+        assert!(!ConsumeQueueExt::is_ext_addr(0));
+        assert!(!ConsumeQueueExt::is_ext_addr(i32::MIN as i64));
+        let address = ConsumeQueueExt::decorate(1024);
+        assert!(ConsumeQueueExt::is_ext_addr(address));
+        assert_eq!(ConsumeQueueExt::decorate(address), address);
+        assert_eq!(ConsumeQueueExt::un_decorate(address), 1024);
+        assert_eq!(ConsumeQueueExt::un_decorate(1024), 1024);
+    }
+
+    #[test]
+    fn put_and_get_across_files() {
+        let dir = TempDir::new().unwrap();
+        let ext = new_ext(&dir);
+        let addresses: Vec<i64> = (0..6)
+            .map(|i| ext.put(CqExtUnit::new(i, 1000 + i, Some(vec![i as u8; 3]))))
+            .collect();
+        for (i, address) in addresses.iter().enumerate() {
+            assert!(ConsumeQueueExt::is_ext_addr(*address));
+            let mut unit = CqExtUnit::default();
+            assert!(ext.get(*address, &mut unit));
+            assert_eq!(unit.tags_code(), i as i64);
+            assert_eq!(unit.msg_store_time(), 1000 + i as i64);
+            assert_eq!(unit.filter_bit_map(), &Some(vec![i as u8; 3]));
+        }
+        // four units of 23 bytes fit into a file of 100 bytes
+        assert_eq!(ConsumeQueueExt::un_decorate(addresses[4]), 100);
+        assert_eq!(ext.get_min_address(), ConsumeQueueExt::decorate(0));
+        assert_eq!(ext.get_max_address(), ConsumeQueueExt::decorate(146));
+        assert!(!ext.get(42, &mut CqExtUnit::default()));
+    }
+
+    #[test]
+    fn recover_and_truncate() {
+        let dir = TempDir::new().unwrap();
+        let addresses: Vec<i64> = {
+            let ext = new_ext(&dir);
+            let addresses = (0..6)
+                .map(|i| ext.put(CqExtUnit::new(i, 1000 + i, None)))
+                .collect();
+            ext.flush(0);
+            addresses
+        };
+
+        let mut ext = new_ext(&dir);
+        assert!(ext.load());
+        ext.recover();
+        assert_eq!(
+            ext.get_max_address(),
+            ConsumeQueueExt::decorate(100 + 2 * 20)
+        );
+
+        ext.truncate_by_max_address(addresses[4]);
+        assert_eq!(ext.get_max_address(), ConsumeQueueExt::decorate(120));
+        let mut unit = CqExtUnit::default();
+        assert!(ext.get(addresses[4], &mut unit));
+        assert_eq!(unit.tags_code(), 4);
+        assert!(!ext.get(addresses[5], &mut unit));
+
+        ext.truncate_by_min_address(addresses[5]);
+        assert_eq!(ext.get_min_address(), ConsumeQueueExt::decorate(100));
+        assert!(!ext.get(addresses[1], &mut unit));
     }
 }
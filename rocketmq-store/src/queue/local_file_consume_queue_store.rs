@@ -49,6 +49,8 @@ use crate::queue::CqUnit;
 use crate::store_path_config_helper::get_store_path_batch_consume_queue;
 use crate::store_path_config_helper::get_store_path_consume_queue;
 
+const FLUSH_RETRY_TIMES_OVER: usize = 10;
+
 #[derive(Clone)]
 pub struct ConsumeQueueStore {
     inner: ArcMut<Inner>,
@@ -189,7 +191,20 @@ impl ConsumeQueueStoreTrait for ConsumeQueueStore {
     }
 
     fn shutdown(&self) -> bool {
-        true
+        let consume_queues = self
+            .inner
+            .consume_queue_table
+            .lock()
+            .values()
+            .flat_map(|queues| queues.values().cloned())
+            .collect::<Vec<_>>();
+        let mut flushed = true;
+        for consume_queue in consume_queues {
+            // a flush covers one mapped file, it reports true once nothing is left to flush
+            flushed &=
+                (0..FLUSH_RETRY_TIMES_OVER).any(|_| self.flush(&**consume_queue.as_ref(), 0));
+        }
+        flushed
     }
 
     fn destroy(&self) {
@@ -211,7 +226,7 @@ impl ConsumeQueueStoreTrait for ConsumeQueueStore {
     }
 
     fn flush(&self, consume_queue: &dyn ConsumeQueueTrait, flush_least_pages: i32) -> bool {
-        todo!()
+        consume_queue.flush(flush_least_pages)
     }
 
     async fn clean_expired(&self, min_phy_offset: i64) {
@@ -219,12 +234,17 @@ impl ConsumeQueueStoreTrait for ConsumeQueueStore {
     }
 
     fn check_self(&self) {
-        let consume_queue_table = self.inner.consume_queue_table.lock();
-        for consume_queue_table in consume_queue_table.values() {
-            for consume_queue in consume_queue_table.values() {
-                let consume_queue = &**consume_queue.as_ref();
-                self.check_self(consume_queue);
-            }
+        // Snapshot the queues first: checking a queue looks it up in the table again, and the
+        // table lock is not reentrant.
+        let consume_queues = self
+            .inner
+            .consume_queue_table
+            .lock()
+            .values()
+            .flat_map(|queues| queues.values().cloned())
+            .collect::<Vec<_>>();
+        for consume_queue in consume_queues {
+            self.check_self(&**consume_queue.as_ref());
         }
     }
 
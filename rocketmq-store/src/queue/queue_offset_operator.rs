@@ -16,15 +16,85 @@
  */
 
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::hash::BuildHasher;
+use std::hash::RandomState;
 
 use cheetah_string::CheetahString;
+use parking_lot::Mutex;
 use tracing::info;
 
+/// Number of independently locked shards of each offset table, a power of two.
+const OFFSET_TABLE_SHARDS: usize = 32;
+
+/// Next queue offset per `topic-queueId`, split into shards so that producers writing to
+/// different queues do not serialize on one table lock when offsets are assigned.
+struct OffsetTable {
+    hasher: RandomState,
+    shards: Box<[Mutex<HashMap<CheetahString, i64>>]>,
+}
+
+impl OffsetTable {
+    fn new() -> Self {
+        OffsetTable {
+            hasher: RandomState::new(),
+            shards: (0..OFFSET_TABLE_SHARDS)
+                .map(|_| Mutex::new(HashMap::new()))
+                .collect(),
+        }
+    }
+
+    #[inline]
+    fn shard(&self, key: &str) -> &Mutex<HashMap<CheetahString, i64>> {
+        let index = self.hasher.hash_one(key) as usize & (OFFSET_TABLE_SHARDS - 1);
+        &self.shards[index]
+    }
+
+    #[inline]
+    fn get(&self, key: &CheetahString) -> Option<i64> {
+        self.shard(key).lock().get(key).cloned()
+    }
+
+    #[inline]
+    fn get_or_init(&self, key: CheetahString) -> i64 {
+        *self.shard(&key).lock().entry(key).or_insert(0)
+    }
+
+    #[inline]
+    fn increase(&self, key: CheetahString, delta: i64) {
+        *self.shard(&key).lock().entry(key).or_insert(0) += delta;
+    }
+
+    #[inline]
+    fn insert(&self, key: CheetahString, offset: i64) {
+        self.shard(&key).lock().insert(key, offset);
+    }
+
+    #[inline]
+    fn remove(&self, key: &CheetahString) {
+        self.shard(key).lock().remove(key);
+    }
+
+    fn replace(&self, table: HashMap<CheetahString, i64>) {
+        let mut sharded: Vec<HashMap<CheetahString, i64>> =
+            (0..OFFSET_TABLE_SHARDS).map(|_| HashMap::new()).collect();
+        for (key, offset) in table {
+            let index = self.hasher.hash_one(key.as_str()) as usize & (OFFSET_TABLE_SHARDS - 1);
+            sharded[index].insert(key, offset);
+        }
+        for (shard, table) in self.shards.iter().zip(sharded) {
+            *shard.lock() = table;
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.shards.iter().all(|shard| shard.lock().is_empty())
+    }
+}
+
 pub struct QueueOffsetOperator {
-    topic_queue_table: Arc<parking_lot::Mutex<HashMap<CheetahString, i64>>>,
-    batch_topic_queue_table: Arc<parking_lot::Mutex<HashMap<CheetahString, i64>>>,
-    lmq_topic_queue_table: Arc<parking_lot::Mutex<HashMap<CheetahString, i64>>>,
+    topic_queue_table: OffsetTable,
+    batch_topic_queue_table: OffsetTable,
+    lmq_topic_queue_table: OffsetTable,
 }
 
 impl Default for QueueOffsetOperator {
@@ -38,96 +108,74 @@ impl QueueOffsetOperator {
     #[inline]
     pub fn new() -> Self {
         QueueOffsetOperator {
-            topic_queue_table: Arc::new(parking_lot::Mutex::new(HashMap::new())),
-            batch_topic_queue_table: Arc::new(parking_lot::Mutex::new(HashMap::new())),
-            lmq_topic_queue_table: Arc::new(parking_lot::Mutex::new(HashMap::new())),
+            topic_queue_table: OffsetTable::new(),
+            batch_topic_queue_table: OffsetTable::new(),
+            lmq_topic_queue_table: OffsetTable::new(),
         }
     }
 
     #[inline]
     pub fn get_queue_offset(&self, topic_queue_key: CheetahString) -> i64 {
-        let topic_queue_table = self.topic_queue_table.lock();
-        let mut table = topic_queue_table;
-        *table.entry(topic_queue_key).or_insert(0)
+        self.topic_queue_table.get_or_init(topic_queue_key)
     }
 
     #[inline]
     pub fn get_topic_queue_next_offset(&self, topic_queue_key: &CheetahString) -> Option<i64> {
-        let topic_queue_table = self.topic_queue_table.lock();
-        let table = topic_queue_table;
-        table.get(topic_queue_key).cloned()
+        self.topic_queue_table.get(topic_queue_key)
     }
 
     #[inline]
     pub fn increase_queue_offset(&self, topic_queue_key: CheetahString, message_num: i16) {
-        let topic_queue_table = self.topic_queue_table.lock();
-        let mut table = topic_queue_table;
-        let entry = table.entry(topic_queue_key).or_insert(0);
-        *entry += message_num as i64;
+        self.topic_queue_table
+            .increase(topic_queue_key, message_num as i64);
     }
 
     #[inline]
     pub fn update_queue_offset(&self, topic_queue_key: &CheetahString, offset: i64) {
-        let topic_queue_table = self.topic_queue_table.lock();
-        let mut table = topic_queue_table;
-        table.insert(topic_queue_key.clone(), offset);
+        self.topic_queue_table
+            .insert(topic_queue_key.clone(), offset);
     }
 
     #[inline]
     pub fn get_batch_queue_offset(&self, topic_queue_key: &CheetahString) -> i64 {
-        let batch_topic_queue_table = self.batch_topic_queue_table.lock();
-        let mut table = batch_topic_queue_table;
-        *table.entry(topic_queue_key.clone()).or_insert(0)
+        self.batch_topic_queue_table
+            .get_or_init(topic_queue_key.clone())
     }
 
     #[inline]
     pub fn increase_batch_queue_offset(&self, topic_queue_key: &CheetahString, message_num: i16) {
-        let batch_topic_queue_table = self.batch_topic_queue_table.lock();
-        let mut table = batch_topic_queue_table;
-        let entry = table.entry(topic_queue_key.clone()).or_insert(0);
-        *entry += message_num as i64;
+        self.batch_topic_queue_table
+            .increase(topic_queue_key.clone(), message_num as i64);
     }
 
     #[inline]
     pub fn get_lmq_offset(&self, topic_queue_key: &CheetahString) -> i64 {
-        let lmq_topic_queue_table = self.lmq_topic_queue_table.lock();
-        let mut table = lmq_topic_queue_table;
-        *table.entry(topic_queue_key.clone()).or_insert(0)
+        self.lmq_topic_queue_table
+            .get_or_init(topic_queue_key.clone())
     }
 
     #[inline]
     pub fn get_lmq_topic_queue_next_offset(&self, topic_queue_key: &CheetahString) -> Option<i64> {
-        let lmq_topic_queue_table = self.lmq_topic_queue_table.lock();
-        let table = lmq_topic_queue_table;
-        table.get(topic_queue_key).cloned()
+        self.lmq_topic_queue_table.get(topic_queue_key)
     }
 
     #[inline]
     pub fn increase_lmq_offset(&self, queue_key: &CheetahString, message_num: i16) {
-        let lmq_topic_queue_table = self.lmq_topic_queue_table.lock();
-        let mut table = lmq_topic_queue_table;
-        let entry = table.entry(queue_key.clone()).or_insert(0);
-        *entry += message_num as i64;
+        self.lmq_topic_queue_table
+            .increase(queue_key.clone(), message_num as i64);
     }
 
     #[inline]
     pub fn current_queue_offset(&self, topic_queue_key: &CheetahString) -> i64 {
-        let topic_queue_table = self.topic_queue_table.lock();
-        let table = topic_queue_table;
-        let current_queue_offset = table.get(topic_queue_key);
-        current_queue_offset.map_or(0, |offset| *offset)
+        self.topic_queue_table.get(topic_queue_key).unwrap_or(0)
     }
 
     #[inline]
     pub fn remove(&self, topic: &CheetahString, queue_id: i32) {
         let topic_queue_key = CheetahString::from(format!("{topic}-{queue_id}"));
-        // Beware of thread-safety
-        let mut topic_queue_table = self.topic_queue_table.lock();
-        topic_queue_table.remove(&topic_queue_key);
-        let mut batch_topic_queue_table = self.batch_topic_queue_table.lock();
-        batch_topic_queue_table.remove(&topic_queue_key);
-        let mut lmq_topic_queue_table = self.lmq_topic_queue_table.lock();
-        lmq_topic_queue_table.remove(&topic_queue_key);
+        self.topic_queue_table.remove(&topic_queue_key);
+        self.batch_topic_queue_table.remove(&topic_queue_key);
+        self.lmq_topic_queue_table.remove(&topic_queue_key);
 
         info!(
             "removeQueueFromTopicQueueTable OK Topic: {} QueueId: {}",
@@ -137,7 +185,7 @@ impl QueueOffsetOperator {
 
     #[inline]
     pub fn set_topic_queue_table(&self, topic_queue_table: HashMap<CheetahString, i64>) {
-        *self.topic_queue_table.lock() = topic_queue_table;
+        self.topic_queue_table.replace(topic_queue_table);
     }
 
     #[inline]
@@ -148,7 +196,7 @@ impl QueueOffsetOperator {
                 table.insert(key.clone(), *value);
             }
         }
-        *self.lmq_topic_queue_table.lock() = table;
+        self.lmq_topic_queue_table.replace(table);
     }
 
     #[inline]
@@ -156,7 +204,8 @@ impl QueueOffsetOperator {
         &self,
         batch_topic_queue_table: HashMap<CheetahString, i64>,
     ) {
-        *self.batch_topic_queue_table.lock() = batch_topic_queue_table;
+        self.batch_topic_queue_table
+            .replace(batch_topic_queue_table);
     }
 }
 
@@ -170,9 +219,9 @@ mod tests {
     fn queue_offset_operator_initializes_empty_tables() {
         let operator = QueueOffsetOperator::new();
 
-        assert!(operator.topic_queue_table.lock().is_empty());
-        assert!(operator.batch_topic_queue_table.lock().is_empty());
-        assert!(operator.lmq_topic_queue_table.lock().is_empty());
+        assert!(operator.topic_queue_table.is_empty());
+        assert!(operator.batch_topic_queue_table.is_empty());
+        assert!(operator.lmq_topic_queue_table.is_empty());
     }
 
     #[test]
@@ -457,12 +457,23 @@ impl<MS: MessageStore> FileQueueLifeCycle for ConsumeQueue<MS> {
 
     #[inline]
     fn check_self(&self) {
-        todo!()
+        self.mapped_file_queue.check_self();
+        if self.is_ext_read_enable() {
+            self.consume_queue_ext.as_ref().unwrap().check_self();
+        }
     }
 
     #[inline]
     fn flush(&self, flush_least_pages: i32) -> bool {
-        todo!()
+        let mut result = self.mapped_file_queue.flush(flush_least_pages);
+        if self.is_ext_read_enable() {
+            result &= self
+                .consume_queue_ext
+                .as_ref()
+                .unwrap()
+                .flush(flush_least_pages);
+        }
+        result
     }
 
     #[inline]
@@ -753,6 +764,20 @@ impl<MS: MessageStore> ConsumeQueueTrait for ConsumeQueue<MS> {
         }
     }
 
+    #[inline]
+    fn advance_min_offset(&self, min_offset: i64) {
+        let min_offset = min_offset.min(self.get_max_offset_in_queue());
+        let previous = self
+            .min_logic_offset
+            .fetch_max(min_offset * CQ_STORE_UNIT_SIZE as i64, Ordering::SeqCst);
+        if previous < min_offset * CQ_STORE_UNIT_SIZE as i64 {
+            info!(
+                "ConsumeQueue[topic={}, queue-id={}] min offset advanced to {}",
+                self.topic, self.queue_id, min_offset
+            );
+        }
+    }
+
     #[inline]
     fn put_message_position_info_wrapper(&mut self, request: &DispatchRequest) {
         let max_retries = 30i32;
@@ -785,12 +810,12 @@ impl<MS: MessageStore> ConsumeQueueTrait for ConsumeQueue<MS> {
                 request.consume_queue_offset,
             ) {
                 let message_store_config = self.message_store.get_message_store_config();
+                let store_checkpoint = self.message_store.get_store_checkpoint();
                 if message_store_config.broker_role == BrokerRole::Slave
                     || message_store_config.enable_dledger_commit_log
                 {
-                    unimplemented!("slave or dledger commit log not support")
+                    store_checkpoint.set_physic_msg_timestamp(request.store_timestamp as u64);
                 }
-                let store_checkpoint = self.message_store.get_store_checkpoint();
                 store_checkpoint.set_logics_msg_timestamp(request.store_timestamp as u64);
                 //if (MultiDispatchUtils.checkMultiDispatchQueue(this.messageStore.
                 // getMessageStoreConfig(), request)) {
@@ -881,7 +906,7 @@ struct ConsumeQueueIterator {
 }
 
 impl ConsumeQueueIterator {
-    fn get_ext(&self, offset: i64, cq_ext_unit: &CqExtUnit) -> bool {
+    fn get_ext(&self, offset: i64, cq_ext_unit: &mut CqExtUnit) -> bool {
         match self.consume_queue_ext.as_ref() {
             None => false,
             Some(value) => value.get(offset, cq_ext_unit),
@@ -931,8 +956,8 @@ impl Iterator for ConsumeQueueIterator {
                 };
 
                 if ConsumeQueueExt::is_ext_addr(cq_unit.tags_code) {
-                    let cq_ext_unit = CqExtUnit::default();
-                    let ext_ret = self.get_ext(cq_unit.tags_code, &cq_ext_unit);
+                    let mut cq_ext_unit = CqExtUnit::default();
+                    let ext_ret = self.get_ext(cq_unit.tags_code, &mut cq_ext_unit);
                     if ext_ret {
                         cq_unit.tags_code = cq_ext_unit.tags_code();
                         cq_unit.cq_ext_unit = Some(cq_ext_unit);
@@ -0,0 +1,376 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Per-topic retention set with the `reserve.time` and `max.size` topic attributes.
+//!
+//! The commit log is shared by all topics, so the retention of a topic is enforced on its consume
+//! queues: entries out of retention are skipped by moving the min offset of the queue forward.
+//! Their bytes are reclaimed once the commit log file holding them expires.
+
+use std::collections::HashMap;
+
+use cheetah_string::CheetahString;
+use parking_lot::Mutex;
+use rocketmq_common::common::attribute::topic_attributes::TopicAttributes;
+use rocketmq_common::common::attribute::Attribute;
+use rocketmq_common::common::config::TopicConfig;
+
+use crate::queue::consume_queue::ConsumeQueueTrait;
+use crate::queue::referred_iterator::ReferredIterator;
+use crate::queue::CqUnit;
+
+const MILLIS_PER_HOUR: i64 = 60 * 60 * 1000;
+
+/// The retention a topic overrides the broker wide file retention with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TopicRetention {
+    /// Hours the messages of the topic are kept.
+    pub reserve_time_hours: Option<i64>,
+    /// Bytes of messages the topic keeps at most.
+    pub max_size: Option<i64>,
+}
+
+impl TopicRetention {
+    /// Reads the retention attributes of `topic_config`, `None` when the topic sets neither.
+    pub fn of(topic_config: &TopicConfig) -> Option<Self> {
+        let positive_attribute = |name: &str| {
+            topic_config
+                .attributes
+                .get(name)
+                .and_then(|value| value.parse::<i64>().ok())
+                .filter(|value| *value > 0)
+        };
+        let retention = TopicRetention {
+            reserve_time_hours: positive_attribute(
+                TopicAttributes::topic_reserve_time_attribute().name(),
+            ),
+            max_size: positive_attribute(TopicAttributes::topic_max_size_attribute().name()),
+        };
+        if retention.reserve_time_hours.is_none() && retention.max_size.is_none() {
+            return None;
+        }
+        Some(retention)
+    }
+
+    /// Moves the min offset of `consume_queue` past the entries out of retention. The max size
+    /// of the topic is shared evenly by its `queue_nums` queues.
+    pub fn apply(
+        &self,
+        consume_queue: &dyn ConsumeQueueTrait,
+        queue_nums: usize,
+        now: i64,
+        message_sizes: &QueueMessageSizes,
+        store_timestamp: impl Fn(&CqUnit) -> i64,
+    ) {
+        let min_offset = consume_queue.get_min_offset_in_queue();
+        let max_offset = consume_queue.get_max_offset_in_queue();
+        if min_offset >= max_offset {
+            return;
+        }
+        let max_size = self
+            .max_size
+            .map(|max_size| max_size / queue_nums.max(1) as i64);
+        let total_size = if max_size.is_some() {
+            message_sizes.message_size(consume_queue)
+        } else {
+            0
+        };
+        let expire_before = self
+            .reserve_time_hours
+            .map(|hours| now - hours.saturating_mul(MILLIS_PER_HOUR));
+        let first_retained = first_retained_offset(
+            QueueUnits::new(consume_queue, min_offset, max_offset),
+            max_offset,
+            total_size,
+            max_size,
+            expire_before,
+            store_timestamp,
+        );
+        if first_retained > min_offset {
+            consume_queue.advance_min_offset(first_retained);
+        }
+    }
+}
+
+/// The bytes the messages readable from each consume queue take in the commit log.
+///
+/// A queue is counted once in full, later counts only read the entries appended to it and dropped
+/// from it since.
+#[derive(Default)]
+pub struct QueueMessageSizes {
+    counted: Mutex<HashMap<(CheetahString, i32), CountedSize>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CountedSize {
+    min_offset: i64,
+    max_offset: i64,
+    size: i64,
+}
+
+impl QueueMessageSizes {
+    /// Returns the bytes the messages readable from `consume_queue` take in the commit log.
+    pub fn message_size(&self, consume_queue: &dyn ConsumeQueueTrait) -> i64 {
+        let key = (
+            consume_queue.get_topic().clone(),
+            consume_queue.get_queue_id(),
+        );
+        let min_offset = consume_queue.get_min_offset_in_queue();
+        let max_offset = consume_queue.get_max_offset_in_queue();
+        let counted = self.counted.lock().get(&key).copied();
+        let size = count_message_size(counted, min_offset, max_offset, |from, to| {
+            unit_sizes(consume_queue, from, to)
+        });
+        let Some(size) = size else {
+            self.counted.lock().remove(&key);
+            return 0;
+        };
+        self.counted.lock().insert(
+            key,
+            CountedSize {
+                min_offset,
+                max_offset,
+                size,
+            },
+        );
+        size
+    }
+
+    /// Forgets the counts of the queues of the topics `keep` returns `false` for.
+    pub fn retain_topics(&self, keep: impl Fn(&CheetahString) -> bool) {
+        self.counted.lock().retain(|(topic, _), _| keep(topic));
+    }
+}
+
+/// Returns the message size of the queue holding the entries from `min_offset` up to
+/// `max_offset`, updating its last count when there is one.
+fn count_message_size(
+    counted: Option<CountedSize>,
+    min_offset: i64,
+    max_offset: i64,
+    unit_sizes: impl Fn(i64, i64) -> Option<i64>,
+) -> Option<i64> {
+    counted
+        .filter(|counted| {
+            counted.min_offset <= min_offset
+                && min_offset <= counted.max_offset
+                && counted.max_offset <= max_offset
+        })
+        .and_then(|counted| {
+            let dropped = unit_sizes(counted.min_offset, min_offset)?;
+            let appended = unit_sizes(counted.max_offset, max_offset)?;
+            Some(counted.size - dropped + appended)
+        })
+        // the queue was truncated or the dropped entries are gone already, count it again
+        .or_else(|| unit_sizes(min_offset, max_offset))
+}
+
+/// Sums the sizes of the entries of `consume_queue` from `from` up to `to`, `None` when some of
+/// them can no longer be read.
+fn unit_sizes(consume_queue: &dyn ConsumeQueueTrait, from: i64, to: i64) -> Option<i64> {
+    let mut next_offset = from;
+    let mut size = 0;
+    for (offset, unit) in QueueUnits::new(consume_queue, from, to) {
+        next_offset = offset + 1;
+        size += unit.size as i64;
+    }
+    (next_offset >= to).then_some(size)
+}
+
+/// Returns the offset of the first of `units` that is neither stored before `expire_before` nor
+/// needed to be dropped to get `total_size` down to `max_size`, or `max_offset` when every unit
+/// is out of retention.
+fn first_retained_offset(
+    units: impl Iterator<Item = (i64, CqUnit)>,
+    max_offset: i64,
+    mut total_size: i64,
+    max_size: Option<i64>,
+    expire_before: Option<i64>,
+    store_timestamp: impl Fn(&CqUnit) -> i64,
+) -> i64 {
+    for (offset, unit) in units {
+        let oversize = max_size.is_some_and(|max_size| total_size > max_size);
+        let expired = expire_before.is_some_and(|expire_before| {
+            let timestamp = store_timestamp(&unit);
+            timestamp >= 0 && timestamp < expire_before
+        });
+        if !oversize && !expired {
+            return offset;
+        }
+        total_size -= unit.size as i64;
+    }
+    max_offset
+}
+
+/// Iterates the entries of a consume queue with their queue offsets, across its files.
+struct QueueUnits<'a> {
+    consume_queue: &'a dyn ConsumeQueueTrait,
+    offset: i64,
+    max_offset: i64,
+    units: Option<Box<dyn ReferredIterator<CqUnit>>>,
+}
+
+impl<'a> QueueUnits<'a> {
+    fn new(consume_queue: &'a dyn ConsumeQueueTrait, start_offset: i64, end_offset: i64) -> Self {
+        QueueUnits {
+            consume_queue,
+            offset: start_offset,
+            max_offset: end_offset,
+            units: None,
+        }
+    }
+}
+
+impl Iterator for QueueUnits<'_> {
+    type Item = (i64, CqUnit);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.offset < self.max_offset {
+            let fresh = self.units.is_none();
+            if fresh {
+                // an iterator covers the rest of one file, start over at the next one
+                self.units = Some(self.consume_queue.iterate_from(self.offset)?);
+            }
+            if let Some(unit) = self.units.as_mut().and_then(|units| units.next()) {
+                let offset = self.offset;
+                self.offset += 1;
+                return Some((offset, unit));
+            }
+            if let Some(mut units) = self.units.take() {
+                units.release();
+            }
+            if fresh {
+                return None;
+            }
+        }
+        None
+    }
+}
+
+impl Drop for QueueUnits<'_> {
+    fn drop(&mut self) {
+        if let Some(units) = self.units.as_mut() {
+            units.release();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn units(sizes: &[i32]) -> impl Iterator<Item = (i64, CqUnit)> + '_ {
+        sizes.iter().enumerate().map(|(offset, size)| {
+            (
+                offset as i64,
+                CqUnit {
+                    size: *size,
+                    // the position doubles as the store timestamp in these tests
+                    pos: offset as i64 * 100,
+                    ..CqUnit::default()
+                },
+            )
+        })
+    }
+
+    #[test]
+    fn retention_is_read_from_topic_attributes() {
+        let mut topic_config = TopicConfig::new("retained");
+        assert_eq!(TopicRetention::of(&topic_config), None);
+
+        topic_config.attributes = HashMap::from([
+            (
+                CheetahString::from_static_str("reserve.time"),
+                CheetahString::from_static_str("24"),
+            ),
+            (
+                CheetahString::from_static_str("max.size"),
+                CheetahString::from_static_str("-1"),
+            ),
+        ]);
+        assert_eq!(
+            TopicRetention::of(&topic_config),
+            Some(TopicRetention {
+                reserve_time_hours: Some(24),
+                max_size: None,
+            })
+        );
+    }
+
+    #[test]
+    fn oldest_entries_are_dropped_until_the_queue_fits_max_size() {
+        let sizes = [10, 20, 30, 40];
+        let first = first_retained_offset(units(&sizes), 4, 100, Some(70), None, |unit| unit.pos);
+        assert_eq!(first, 2);
+
+        let first = first_retained_offset(units(&sizes), 4, 100, Some(100), None, |unit| unit.pos);
+        assert_eq!(first, 0);
+
+        let first = first_retained_offset(units(&sizes), 4, 100, Some(0), None, |unit| unit.pos);
+        assert_eq!(first, 4);
+    }
+
+    #[test]
+    fn entries_stored_before_the_reserve_time_are_dropped() {
+        let sizes = [10, 20, 30, 40];
+        let first = first_retained_offset(units(&sizes), 4, 0, None, Some(250), |unit| unit.pos);
+        assert_eq!(first, 3);
+
+        let first =
+            first_retained_offset(units(&sizes), 4, 100, Some(90), Some(150), |unit| unit.pos);
+        assert_eq!(first, 2);
+    }
+
+    #[test]
+    fn message_size_only_counts_the_entries_appended_and_dropped_since_the_last_count() {
+        let sizes = [10i64, 20, 30, 40, 50];
+        let first_readable = std::cell::Cell::new(0);
+        let reads = std::cell::Cell::new(0);
+        let unit_sizes = |from: i64, to: i64| {
+            if from < first_readable.get() {
+                return None;
+            }
+            reads.set(reads.get() + (to - from));
+            Some(sizes[from as usize..to as usize].iter().sum())
+        };
+
+        assert_eq!(count_message_size(None, 0, 3, unit_sizes), Some(60));
+        assert_eq!(reads.replace(0), 3);
+
+        let counted = CountedSize {
+            min_offset: 0,
+            max_offset: 3,
+            size: 60,
+        };
+        assert_eq!(
+            count_message_size(Some(counted), 1, 5, unit_sizes),
+            Some(140)
+        );
+        assert_eq!(reads.replace(0), 3);
+
+        // the dropped entries can no longer be read, the queue is counted again
+        first_readable.set(2);
+        assert_eq!(
+            count_message_size(Some(counted), 2, 5, unit_sizes),
+            Some(120)
+        );
+        assert_eq!(reads.replace(0), 3);
+
+        // the queue was truncated below the last count
+        assert_eq!(count_message_size(Some(counted), 2, 2, unit_sizes), Some(0));
+    }
+}
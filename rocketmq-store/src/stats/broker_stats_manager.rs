@@ -15,6 +15,8 @@
  * limitations under the License.
  */
 use std::collections::HashMap;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
 use cheetah_string::CheetahString;
@@ -42,6 +44,8 @@ pub struct BrokerStatsManager {
     producer_state_getter: Option<Arc<dyn StateGetter>>,
     consumer_state_getter: Option<Arc<dyn StateGetter>>,
     broker_config: Option<Arc<BrokerConfig>>,
+    schedule_deliver_failed_nums: AtomicU64,
+    pop_revive_failed_nums: AtomicU64,
 }
 
 impl BrokerStatsManager {
@@ -90,6 +94,8 @@ impl BrokerStatsManager {
     // Producer Register Time
     pub const PRODUCER_REGISTER_TIME: &'static str = "PRODUCER_REGISTER_TIME";
     pub const RT: &'static str = "RT";
+    pub const SCHEDULE_DELIVER_FAILED_NUMS: &'static str = "SCHEDULE_DELIVER_FAILED_NUMS";
+    pub const POP_REVIVE_FAILED_NUMS: &'static str = "POP_REVIVE_FAILED_NUMS";
     pub const SNDBCK2DLQ_TIMES: &'static str = "SNDBCK2DLQ_TIMES";
     pub const SUCCESS_MSG_NUM: &'static str = "SUCCESS_MSG_NUM";
     pub const SUCCESS_MSG_SIZE: &'static str = "SUCCESS_MSG_SIZE";
@@ -121,6 +127,8 @@ impl BrokerStatsManager {
             producer_state_getter: None,
             consumer_state_getter: None,
             broker_config: Some(broker_config),
+            schedule_deliver_failed_nums: AtomicU64::new(0),
+            pop_revive_failed_nums: AtomicU64::new(0),
         };
         broker_stats_manager.init();
         broker_stats_manager
@@ -147,6 +155,8 @@ impl BrokerStatsManager {
             producer_state_getter: None,
             consumer_state_getter: None,
             broker_config: Some(broker_config),
+            schedule_deliver_failed_nums: AtomicU64::new(0),
+            pop_revive_failed_nums: AtomicU64::new(0),
         };
         broker_stats_manager.init();
         broker_stats_manager
@@ -495,6 +505,13 @@ impl BrokerStatsManager {
         queue_id: i32,
         fall_behind: i64,
     ) {
+        if let Some(fall_size) = self.moment_stats_item_set_fall_size.as_ref() {
+            let stats_key = format!("{queue_id}@{topic}@{group}");
+            fall_size
+                .get_and_create_stats_item(stats_key)
+                .get_value()
+                .store(fall_behind, Ordering::Relaxed);
+        }
     }
 
     #[inline]
@@ -521,6 +538,20 @@ impl BrokerStatsManager {
     #[inline]
     pub fn on_topic_deleted(&self, topic: &CheetahString) {}
 
+    #[inline]
+    pub fn on_group_deleted(&self, group: &CheetahString) {
+        // the stats item sets keep no items yet, the fall behind items end with the group
+        for fall_behind in [
+            &self.moment_stats_item_set_fall_size,
+            &self.moment_stats_item_set_fall_time,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            fall_behind.del_value_by_suffix_key(group, "@");
+        }
+    }
+
     #[inline]
     pub fn inc_queue_put_nums(&self, topic: &str, queue_id: i32, num: i32, times: i32) {}
     #[inline]
@@ -553,6 +584,25 @@ impl BrokerStatsManager {
     pub fn inc_channel_close_num(&self) {}
 
     pub fn inc_channel_connect_num(&self) {}
+
+    /// Counts a scheduled message that could not be delivered to its real topic.
+    pub fn inc_schedule_deliver_failed_nums(&self) {
+        self.schedule_deliver_failed_nums
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn get_schedule_deliver_failed_nums(&self) -> u64 {
+        self.schedule_deliver_failed_nums.load(Ordering::Relaxed)
+    }
+
+    /// Counts a pop revive round or retry message that failed.
+    pub fn inc_pop_revive_failed_nums(&self) {
+        self.pop_revive_failed_nums.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn get_pop_revive_failed_nums(&self) -> u64 {
+        self.pop_revive_failed_nums.load(Ordering::Relaxed)
+    }
 }
 
 #[inline]
@@ -651,6 +701,22 @@ pub fn split_account_stat_key(account_stat_key: &str) -> Vec<&str> {
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn deleted_group_drops_its_fall_behind_stats() {
+        let broker_stats_manager = BrokerStatsManager::new(Arc::new(BrokerConfig::default()));
+        broker_stats_manager.record_disk_fall_behind_size("group1", "topic1", 0, 1024);
+        broker_stats_manager.record_disk_fall_behind_size("group2", "topic1", 0, 2048);
+        let fall_size = broker_stats_manager
+            .get_moment_stats_item_set_fall_size()
+            .unwrap()
+            .get_stats_item_table();
+        assert_eq!(fall_size.len(), 2);
+
+        broker_stats_manager.on_group_deleted(&CheetahString::from_static_str("group1"));
+        assert_eq!(fall_size.len(), 1);
+        assert!(fall_size.contains_key("0@topic1@group2"));
+    }
+
     #[test]
     fn build_commercial_stats_key_creates_correct_key() {
         let key = build_commercial_stats_key("owner1", "topic1", "group1", "type1");
@@ -685,6 +751,16 @@ mod tests {
         assert_eq!(key, "owner1|id1|topic1|group1|type1|limit1");
     }
 
+    #[tokio::test]
+    async fn failure_counters_count_up() {
+        let stats_manager = BrokerStatsManager::new(Arc::new(BrokerConfig::default()));
+        stats_manager.inc_schedule_deliver_failed_nums();
+        stats_manager.inc_schedule_deliver_failed_nums();
+        stats_manager.inc_pop_revive_failed_nums();
+        assert_eq!(stats_manager.get_schedule_deliver_failed_nums(), 2);
+        assert_eq!(stats_manager.get_pop_revive_failed_nums(), 1);
+    }
+
     #[test]
     fn split_account_stat_key_splits_correctly() {
         let parts = split_account_stat_key("part1|part2|part3|part4|part5");
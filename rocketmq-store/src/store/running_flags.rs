@@ -25,6 +25,8 @@ const WRITE_INDEX_FILE_ERROR_BIT: i32 = 1 << 3;
 const DISK_FULL_BIT: i32 = 1 << 4;
 const FENCED_BIT: i32 = 1 << 5;
 const LOGIC_DISK_FULL_BIT: i32 = 1 << 6;
+const DISPATCH_BEHIND_BIT: i32 = 1 << 7;
+const FLUSH_BEHIND_BIT: i32 = 1 << 8;
 
 /// `RunningFlags` is a structure to manage various states using bit flags.
 /// The state is represented by an `AtomicI32` to ensure thread safety.
@@ -105,7 +107,9 @@ impl RunningFlags {
                 | DISK_FULL_BIT
                 | WRITE_INDEX_FILE_ERROR_BIT
                 | FENCED_BIT
-                | LOGIC_DISK_FULL_BIT))
+                | LOGIC_DISK_FULL_BIT
+                | DISPATCH_BEHIND_BIT
+                | FLUSH_BEHIND_BIT))
             == 0
     }
 
@@ -192,6 +196,54 @@ impl RunningFlags {
             .fetch_and(!LOGIC_DISK_FULL_BIT, Ordering::SeqCst);
         result
     }
+
+    #[inline]
+    pub fn is_dispatch_behind(&self) -> bool {
+        (self.flag_bits.load(Ordering::SeqCst) & DISPATCH_BEHIND_BIT) != 0
+    }
+
+    /// Marks the commit log as too far ahead of the dispatched consume queues, returning whether
+    /// it was not marked before.
+    #[inline]
+    pub fn get_and_make_dispatch_behind(&self) -> bool {
+        (self
+            .flag_bits
+            .fetch_or(DISPATCH_BEHIND_BIT, Ordering::SeqCst)
+            & DISPATCH_BEHIND_BIT)
+            == 0
+    }
+
+    /// Clears the dispatch behind mark, returning whether it was set.
+    #[inline]
+    pub fn get_and_make_dispatch_ok(&self) -> bool {
+        (self
+            .flag_bits
+            .fetch_and(!DISPATCH_BEHIND_BIT, Ordering::SeqCst)
+            & DISPATCH_BEHIND_BIT)
+            != 0
+    }
+
+    #[inline]
+    pub fn is_flush_behind(&self) -> bool {
+        (self.flag_bits.load(Ordering::SeqCst) & FLUSH_BEHIND_BIT) != 0
+    }
+
+    /// Marks the commit log as holding too much unflushed data, returning whether it was not
+    /// marked before.
+    #[inline]
+    pub fn get_and_make_flush_behind(&self) -> bool {
+        (self.flag_bits.fetch_or(FLUSH_BEHIND_BIT, Ordering::SeqCst) & FLUSH_BEHIND_BIT) == 0
+    }
+
+    /// Clears the flush behind mark, returning whether it was set.
+    #[inline]
+    pub fn get_and_make_flush_ok(&self) -> bool {
+        (self
+            .flag_bits
+            .fetch_and(!FLUSH_BEHIND_BIT, Ordering::SeqCst)
+            & FLUSH_BEHIND_BIT)
+            != 0
+    }
 }
 
 #[cfg(test)]
@@ -354,4 +406,21 @@ mod tests {
         let running_flags = RunningFlags::new();
         assert_eq!(running_flags.get_and_make_logic_disk_ok(), true);
     }
+
+    #[test]
+    fn test_dispatch_and_flush_behind_block_writes_only() {
+        let running_flags = RunningFlags::new();
+        assert!(running_flags.get_and_make_dispatch_behind());
+        assert!(!running_flags.get_and_make_dispatch_behind());
+        assert!(running_flags.is_dispatch_behind());
+        assert!(!running_flags.is_writeable());
+        assert!(running_flags.is_cq_writeable());
+
+        assert!(running_flags.get_and_make_flush_behind());
+        assert!(running_flags.get_and_make_dispatch_ok());
+        assert!(!running_flags.get_and_make_dispatch_ok());
+        assert!(!running_flags.is_writeable());
+        assert!(running_flags.get_and_make_flush_ok());
+        assert!(running_flags.is_writeable());
+    }
 }
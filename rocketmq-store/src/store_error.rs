@@ -45,6 +45,29 @@ pub enum HAError {
 
 pub type HAResult<T> = std::result::Result<T, HAError>;
 
+#[derive(Debug, Error)]
+pub enum DLedgerError {
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("DLedger config error: {0}")]
+    Config(String),
+
+    #[error("This member is not the leader of the group")]
+    NotLeader,
+
+    #[error("Timed out waiting for the quorum to acknowledge")]
+    WaitAckTimeout,
+
+    #[error("DLedger store error: {0}")]
+    Store(String),
+
+    #[error("DLedger protocol error: {0}")]
+    Protocol(String),
+}
+
+pub type DLedgerResult<T> = std::result::Result<T, DLedgerError>;
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -74,6 +74,14 @@ pub fn get_delay_offset_store_path(root_dir: &str) -> String {
         .into_owned()
 }
 
+pub fn get_retention_offset_store_path(root_dir: &str) -> String {
+    PathBuf::from(root_dir)
+        .join("config")
+        .join("retentionOffset.json")
+        .to_string_lossy()
+        .into_owned()
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -140,5 +148,13 @@ mod tests {
                 .to_string_lossy()
                 .into_owned()
         );
+        assert_eq!(
+            get_retention_offset_store_path(root_dir),
+            PathBuf::from(root_dir)
+                .join("config")
+                .join("retentionOffset.json")
+                .to_string_lossy()
+                .into_owned()
+        );
     }
 }
@@ -0,0 +1,891 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use cheetah_string::CheetahString;
+use rocketmq_broker::Builder as BrokerBuilder;
+use rocketmq_common::common::broker::broker_config::BrokerConfig;
+use rocketmq_common::common::constant::PermName;
+use rocketmq_common::common::namesrv::namesrv_config::NamesrvConfig;
+use rocketmq_common::common::server::config::ServerConfig;
+use rocketmq_common::common::topic::TopicValidator;
+use rocketmq_common::common::TopicFilterType;
+use rocketmq_common::utils::network_util::NetworkUtil;
+use rocketmq_error::RocketMQResult;
+use rocketmq_error::RocketmqError;
+use rocketmq_namesrv::bootstrap::Builder as NameServerBuilder;
+use rocketmq_remoting::auth::acl_client_rpc_hook::AclClientRpcHook;
+use rocketmq_remoting::auth::acl_client_rpc_hook::SessionCredentials;
+use rocketmq_remoting::auth::authentication_provider::AuthenticationProvider;
+use rocketmq_remoting::clients::rocketmq_default_impl::RocketmqDefaultClient;
+use rocketmq_remoting::clients::RemotingClient;
+use rocketmq_remoting::code::request_code::RequestCode;
+use rocketmq_remoting::code::response_code::ResponseCode;
+use rocketmq_remoting::protocol::body::broker_body::cluster_info::ClusterInfo;
+use rocketmq_remoting::protocol::header::client_request_header::GetRouteInfoRequestHeader;
+use rocketmq_remoting::protocol::header::create_topic_request_header::CreateTopicRequestHeader;
+use rocketmq_remoting::protocol::remoting_command::RemotingCommand;
+use rocketmq_remoting::protocol::route::topic_route_data::TopicRouteData;
+use rocketmq_remoting::protocol::RemotingDeserializable;
+use rocketmq_remoting::remoting::RemotingService;
+use rocketmq_remoting::request_processor::default_request_processor::DefaultRemotingRequestProcessor;
+use rocketmq_remoting::runtime::config::client_config::TokioClientConfig;
+use rocketmq_rust::ArcMut;
+use rocketmq_store::config::message_store_config::MessageStoreConfig;
+use tempfile::TempDir;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tracing::info;
+use tracing::warn;
+
+use crate::port;
+
+const LOCALHOST: &str = "127.0.0.1";
+const REQUEST_TIMEOUT_MILLIS: u64 = 3_000;
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+type ConfigHook<T> = Box<dyn FnOnce(&mut T) + Send>;
+
+/// Builder for [`TestCluster`].
+///
+/// Everything that would otherwise touch well-known ports or the user's home directory is
+/// overridden by [`TestClusterBuilder::start`], so hooks only need to set what a test cares about.
+pub struct TestClusterBuilder {
+    cluster_name: CheetahString,
+    broker_name: CheetahString,
+    startup_timeout: Duration,
+    broker_config_hook: Option<ConfigHook<BrokerConfig>>,
+    message_store_config_hook: Option<ConfigHook<MessageStoreConfig>>,
+    broker_server_config_hook: Option<ConfigHook<ServerConfig>>,
+    authentication: Option<(Arc<dyn AuthenticationProvider>, SessionCredentials)>,
+}
+
+impl TestClusterBuilder {
+    pub fn new() -> Self {
+        TestClusterBuilder {
+            cluster_name: CheetahString::from_static_str("TestCluster"),
+            broker_name: CheetahString::from_static_str("test-broker-a"),
+            startup_timeout: Duration::from_secs(30),
+            broker_config_hook: None,
+            message_store_config_hook: None,
+            broker_server_config_hook: None,
+            authentication: None,
+        }
+    }
+
+    pub fn cluster_name(mut self, cluster_name: impl Into<CheetahString>) -> Self {
+        self.cluster_name = cluster_name.into();
+        self
+    }
+
+    pub fn broker_name(mut self, broker_name: impl Into<CheetahString>) -> Self {
+        self.broker_name = broker_name.into();
+        self
+    }
+
+    /// How long [`TestClusterBuilder::start`] waits for the broker to register with the name
+    /// server.
+    pub fn startup_timeout(mut self, startup_timeout: Duration) -> Self {
+        self.startup_timeout = startup_timeout;
+        self
+    }
+
+    /// Adjusts the broker configuration before the broker is built.
+    pub fn broker_config(mut self, hook: impl FnOnce(&mut BrokerConfig) + Send + 'static) -> Self {
+        self.broker_config_hook = Some(Box::new(hook));
+        self
+    }
+
+    /// Adjusts the message store configuration before the broker is built.
+    pub fn message_store_config(
+        mut self,
+        hook: impl FnOnce(&mut MessageStoreConfig) + Send + 'static,
+    ) -> Self {
+        self.message_store_config_hook = Some(Box::new(hook));
+        self
+    }
+
+    /// Adjusts the configuration of the broker's remoting server. The listen port and bind
+    /// address are picked by the cluster.
+    pub fn broker_server_config(
+        mut self,
+        hook: impl FnOnce(&mut ServerConfig) + Send + 'static,
+    ) -> Self {
+        self.broker_server_config_hook = Some(Box::new(hook));
+        self
+    }
+
+    /// Authenticates the requests of the broker with `authentication_provider`. The broker itself
+    /// and the admin requests of the cluster carry `credentials`.
+    pub fn authentication(
+        mut self,
+        authentication_provider: impl AuthenticationProvider,
+        credentials: SessionCredentials,
+    ) -> Self {
+        self.authentication = Some((Arc::new(authentication_provider), credentials));
+        self
+    }
+
+    /// Boots the name server and the broker on the current tokio runtime and waits until the
+    /// broker shows up in the name server's cluster info.
+    ///
+    /// The runtime must be multi-threaded, e.g. `#[tokio::test(flavor = "multi_thread")]`, since
+    /// the remoting clients block in place when they shut down.
+    pub async fn start(self) -> RocketMQResult<TestCluster> {
+        let data_dir = tempfile::Builder::new()
+            .prefix("rocketmq-test-cluster")
+            .tempdir()?;
+
+        let namesrv_port = port::pick_port()?;
+        let namesrv_addr =
+            CheetahString::from_string(NetworkUtil::format_address(LOCALHOST, namesrv_port));
+        let namesrv_dir = data_dir.path().join("namesrv");
+        let namesrv_config = NamesrvConfig {
+            kv_config_path: namesrv_dir
+                .join("kvConfig.json")
+                .to_string_lossy()
+                .into_owned(),
+            config_store_path: namesrv_dir
+                .join("namesrv.properties")
+                .to_string_lossy()
+                .into_owned(),
+            ..NamesrvConfig::default()
+        };
+        let namesrv = NameServerBuilder::new()
+            .set_name_server_config(namesrv_config)
+            .set_server_config(ServerConfig {
+                listen_port: namesrv_port,
+                bind_address: LOCALHOST.to_string(),
+                ..ServerConfig::default()
+            })
+            .build();
+        let (namesrv_shutdown_tx, namesrv_shutdown_rx) = oneshot::channel();
+        let namesrv_handle = tokio::spawn(namesrv.boot_until(shutdown_signal(namesrv_shutdown_rx)));
+
+        let mut admin_client = ArcMut::new(RocketmqDefaultClient::new(
+            Arc::new(TokioClientConfig::default()),
+            DefaultRemotingRequestProcessor,
+        ));
+        if let Some((_, credentials)) = &self.authentication {
+            admin_client.register_rpc_hook(Arc::new(Box::new(AclClientRpcHook::new(
+                credentials.clone(),
+            ))));
+        }
+        admin_client.start(ArcMut::downgrade(&admin_client)).await;
+
+        let mut cluster = TestCluster {
+            namesrv_addr: namesrv_addr.clone(),
+            broker_addr: CheetahString::default(),
+            cluster_name: self.cluster_name.clone(),
+            broker_name: self.broker_name.clone(),
+            admin_client,
+            namesrv: Some((namesrv_shutdown_tx, namesrv_handle)),
+            broker: None,
+            data_dir: Some(data_dir),
+        };
+
+        let broker_port = port::pick_broker_port()?;
+        let broker_root = cluster.data_dir().join("broker");
+        let store_root = CheetahString::from_string(broker_root.to_string_lossy().into_owned());
+
+        let mut broker_config = BrokerConfig::default();
+        broker_config.broker_identity.broker_cluster_name = self.cluster_name;
+        broker_config.broker_identity.broker_name = self.broker_name;
+        broker_config.broker_ip1 = CheetahString::from_static_str(LOCALHOST);
+        broker_config.listen_port = broker_port;
+        broker_config.namesrv_addr = Some(namesrv_addr);
+        broker_config.store_path_root_dir = store_root.clone();
+        if let Some((_, credentials)) = &self.authentication {
+            match credentials {
+                SessionCredentials::AccessKey {
+                    access_key,
+                    secret_key,
+                    ..
+                } => {
+                    broker_config.inner_access_key = Some(access_key.clone());
+                    broker_config.inner_secret_key = Some(secret_key.clone());
+                }
+                SessionCredentials::BearerToken(token) => {
+                    broker_config.inner_auth_token = Some(token.clone());
+                }
+            }
+        }
+        if let Some(hook) = self.broker_config_hook {
+            hook(&mut broker_config);
+        }
+        let mut message_store_config = MessageStoreConfig {
+            store_path_root_dir: store_root,
+            ..MessageStoreConfig::default()
+        };
+        if let Some(hook) = self.message_store_config_hook {
+            hook(&mut message_store_config);
+        }
+        cluster.broker_addr =
+            CheetahString::from_string(NetworkUtil::format_address(LOCALHOST, broker_port));
+
+        let mut broker_server_config = ServerConfig::default();
+        if let Some(hook) = self.broker_server_config_hook {
+            hook(&mut broker_server_config);
+        }
+        broker_server_config.listen_port = broker_port;
+        broker_server_config.bind_address = LOCALHOST.to_string();
+
+        let mut broker_builder = BrokerBuilder::new()
+            .set_broker_config(broker_config)
+            .set_message_store_config(message_store_config)
+            .set_server_config(broker_server_config);
+        if let Some((authentication_provider, _)) = self.authentication {
+            broker_builder = broker_builder.set_authentication_provider(authentication_provider);
+        }
+        let broker = broker_builder.build();
+        let (broker_shutdown_tx, broker_shutdown_rx) = oneshot::channel();
+        let broker_handle = tokio::spawn(broker.boot_until(shutdown_signal(broker_shutdown_rx)));
+        cluster.broker = Some((broker_shutdown_tx, broker_handle));
+
+        if let Err(e) = cluster.wait_for_broker(self.startup_timeout).await {
+            cluster.shutdown().await;
+            return Err(e);
+        }
+        info!(
+            "Test cluster started, namesrv: {}, broker: {}",
+            cluster.namesrv_addr, cluster.broker_addr
+        );
+        Ok(cluster)
+    }
+}
+
+impl Default for TestClusterBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A name server and a single master broker running in-process.
+///
+/// Call [`TestCluster::shutdown`] at the end of a test to stop both servers and remove the data
+/// directory. Dropping the cluster without it still signals the servers to stop, but does not
+/// wait for them.
+pub struct TestCluster {
+    namesrv_addr: CheetahString,
+    broker_addr: CheetahString,
+    cluster_name: CheetahString,
+    broker_name: CheetahString,
+    admin_client: ArcMut<RocketmqDefaultClient>,
+    namesrv: Option<(oneshot::Sender<()>, JoinHandle<()>)>,
+    broker: Option<(oneshot::Sender<()>, JoinHandle<()>)>,
+    data_dir: Option<TempDir>,
+}
+
+impl TestCluster {
+    pub fn builder() -> TestClusterBuilder {
+        TestClusterBuilder::new()
+    }
+
+    /// Starts a cluster with the default settings.
+    pub async fn start() -> RocketMQResult<TestCluster> {
+        TestClusterBuilder::new().start().await
+    }
+
+    #[inline]
+    pub fn namesrv_addr(&self) -> &CheetahString {
+        &self.namesrv_addr
+    }
+
+    #[inline]
+    pub fn broker_addr(&self) -> &CheetahString {
+        &self.broker_addr
+    }
+
+    #[inline]
+    pub fn cluster_name(&self) -> &CheetahString {
+        &self.cluster_name
+    }
+
+    #[inline]
+    pub fn broker_name(&self) -> &CheetahString {
+        &self.broker_name
+    }
+
+    /// Root of the temporary directory holding the name server and broker data.
+    #[inline]
+    pub fn data_dir(&self) -> &Path {
+        self.data_dir
+            .as_ref()
+            .expect("data dir is only released on shutdown")
+            .path()
+    }
+
+    /// Creates a readable and writable topic on the broker and waits until its route can be
+    /// fetched from the name server.
+    pub async fn create_topic(
+        &self,
+        topic: impl Into<CheetahString>,
+        queue_nums: i32,
+    ) -> RocketMQResult<TopicRouteData> {
+        let topic = topic.into();
+        let request_header = CreateTopicRequestHeader {
+            topic: topic.clone(),
+            default_topic: CheetahString::from_static_str(
+                TopicValidator::AUTO_CREATE_TOPIC_KEY_TOPIC,
+            ),
+            read_queue_nums: queue_nums,
+            write_queue_nums: queue_nums,
+            perm: (PermName::PERM_READ | PermName::PERM_WRITE) as i32,
+            topic_filter_type: CheetahString::from_string(TopicFilterType::SingleTag.to_string()),
+            topic_sys_flag: Some(0),
+            order: false,
+            attributes: None,
+            force: Some(false),
+            topic_request_header: None,
+        };
+        let request = RemotingCommand::create_request_command(
+            RequestCode::UpdateAndCreateTopic,
+            request_header,
+        );
+        let response = self
+            .admin_client
+            .invoke_async(Some(&self.broker_addr), request, REQUEST_TIMEOUT_MILLIS)
+            .await?;
+        if ResponseCode::from(response.code()) != ResponseCode::Success {
+            return Err(RocketmqError::RemoteError(format!(
+                "create topic {} failed, code: {}, remark: {:?}",
+                topic,
+                response.code(),
+                response.remark()
+            )));
+        }
+        self.wait_for_topic_route(&topic, Duration::from_secs(10))
+            .await
+    }
+
+    /// Polls the name server until it returns a route for `topic` that includes this cluster's
+    /// broker.
+    pub async fn wait_for_topic_route(
+        &self,
+        topic: &CheetahString,
+        timeout: Duration,
+    ) -> RocketMQResult<TopicRouteData> {
+        poll_until(timeout, format!("route of topic {topic}"), || async {
+            let request_header = GetRouteInfoRequestHeader {
+                topic: topic.clone(),
+                accept_standard_json_only: None,
+                topic_request_header: None,
+            };
+            let request = RemotingCommand::create_request_command(
+                RequestCode::GetRouteinfoByTopic,
+                request_header,
+            );
+            let response = self
+                .admin_client
+                .invoke_async(Some(&self.namesrv_addr), request, REQUEST_TIMEOUT_MILLIS)
+                .await
+                .ok()?;
+            if ResponseCode::from(response.code()) != ResponseCode::Success {
+                return None;
+            }
+            let route = TopicRouteData::decode(response.body().as_ref()?.as_ref()).ok()?;
+            route
+                .broker_datas
+                .iter()
+                .any(|broker_data| broker_data.broker_name() == &self.broker_name)
+                .then_some(route)
+        })
+        .await
+    }
+
+    async fn wait_for_broker(&self, timeout: Duration) -> RocketMQResult<()> {
+        poll_until(timeout, format!("broker {}", self.broker_name), || async {
+            if self
+                .broker
+                .as_ref()
+                .is_some_and(|(_, handle)| handle.is_finished())
+            {
+                return Some(Err(RocketmqError::RemoteError(format!(
+                    "broker {} stopped during startup",
+                    self.broker_name
+                ))));
+            }
+            let request =
+                RemotingCommand::create_remoting_command(RequestCode::GetBrokerClusterInfo);
+            let response = self
+                .admin_client
+                .invoke_async(Some(&self.namesrv_addr), request, REQUEST_TIMEOUT_MILLIS)
+                .await
+                .ok()?;
+            let cluster_info = ClusterInfo::decode(response.body().as_ref()?.as_ref()).ok()?;
+            cluster_info
+                .broker_addr_table
+                .as_ref()?
+                .contains_key(&self.broker_name)
+                .then_some(Ok(()))
+        })
+        .await?
+    }
+
+    /// Stops the broker, then the name server, and removes the data directory.
+    pub async fn shutdown(mut self) {
+        if let Some((shutdown_tx, handle)) = self.broker.take() {
+            let _ = shutdown_tx.send(());
+            if let Err(e) = handle.await {
+                warn!("Test broker task failed: {}", e);
+            }
+        }
+        if let Some((shutdown_tx, handle)) = self.namesrv.take() {
+            let _ = shutdown_tx.send(());
+            if let Err(e) = handle.await {
+                warn!("Test name server task failed: {}", e);
+            }
+        }
+        self.admin_client.shutdown();
+        if let Some(data_dir) = self.data_dir.take() {
+            if let Err(e) = data_dir.close() {
+                warn!("Failed to remove test cluster data dir: {}", e);
+            }
+        }
+    }
+}
+
+impl Drop for TestCluster {
+    fn drop(&mut self) {
+        for (shutdown_tx, _) in [self.broker.take(), self.namesrv.take()]
+            .into_iter()
+            .flatten()
+        {
+            let _ = shutdown_tx.send(());
+        }
+    }
+}
+
+async fn shutdown_signal(shutdown_rx: oneshot::Receiver<()>) {
+    // A dropped sender means the cluster is gone, which is just as much a reason to stop.
+    let _ = shutdown_rx.await;
+}
+
+async fn poll_until<T, F, Fut>(timeout: Duration, what: String, mut probe: F) -> RocketMQResult<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Option<T>>,
+{
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        if let Some(value) = probe().await {
+            return Ok(value);
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return Err(RocketmqError::RemoteError(format!(
+                "timed out after {timeout:?} waiting for {what}"
+            )));
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rocketmq_client_rust::base::client_config::ClientConfig;
+    use rocketmq_client_rust::producer::default_mq_producer::DefaultMQProducer;
+    use rocketmq_client_rust::producer::mq_producer::MQProducer;
+    use rocketmq_client_rust::producer::send_status::SendStatus;
+    use rocketmq_common::common::base::plain_access_config::PlainAccessConfig;
+    use rocketmq_common::common::message::message_single::Message;
+    use rocketmq_common::common::sys_flag::pull_sys_flag::PullSysFlag;
+    use rocketmq_remoting::auth::hmac_authentication_provider::HmacAuthenticationProvider;
+    use rocketmq_remoting::connection::Connection;
+    use rocketmq_remoting::protocol::body::topic::topic_list::TopicList;
+    use rocketmq_remoting::protocol::header::delete_topic_request_header::DeleteTopicRequestHeader;
+    use rocketmq_remoting::protocol::header::namesrv::topic_operation_header::DeleteTopicFromNamesrvRequestHeader;
+    use rocketmq_remoting::protocol::header::namesrv::topic_operation_header::GetTopicsByClusterRequestHeader;
+    use rocketmq_remoting::protocol::header::pull_message_request_header::PullMessageRequestHeader;
+    use rocketmq_remoting::protocol::header::pull_message_response_header::PullMessageResponseHeader;
+    use rocketmq_remoting::protocol::header::query_consumer_offset_request_header::QueryConsumerOffsetRequestHeader;
+    use rocketmq_remoting::protocol::header::query_consumer_offset_response_header::QueryConsumerOffsetResponseHeader;
+
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn boots_cluster_and_creates_topic() {
+        let cluster = TestCluster::start().await.unwrap();
+        let route = cluster.create_topic("TestClusterTopic", 4).await.unwrap();
+        let queue_data = route
+            .queue_datas
+            .iter()
+            .find(|queue_data| queue_data.broker_name() == cluster.broker_name())
+            .unwrap();
+        assert_eq!(queue_data.write_queue_nums(), 4);
+
+        let data_dir = cluster.data_dir().to_path_buf();
+        assert!(data_dir.join("broker").exists());
+        cluster.shutdown().await;
+        assert!(!data_dir.exists());
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn serves_requests_from_dedicated_broker_runtimes() {
+        let cluster = TestCluster::builder()
+            .broker_config(|broker_config| {
+                broker_config.network_runtime_threads = 1;
+                broker_config.processor_runtime_threads = 2;
+                broker_config.store_runtime_threads = 1;
+                broker_config.flush_on_dedicated_thread = true;
+            })
+            .start()
+            .await
+            .unwrap();
+        let route = cluster
+            .create_topic("DedicatedRuntimeTopic", 2)
+            .await
+            .unwrap();
+        assert!(!route.queue_datas.is_empty());
+        cluster.shutdown().await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn name_server_answers_cluster_and_topic_list_queries() {
+        let cluster = TestCluster::start().await.unwrap();
+        cluster.create_topic("TopicListTopic", 1).await.unwrap();
+
+        let query = |request: RemotingCommand| async {
+            let response = cluster
+                .admin_client
+                .invoke_async(
+                    Some(cluster.namesrv_addr()),
+                    request,
+                    REQUEST_TIMEOUT_MILLIS,
+                )
+                .await
+                .unwrap();
+            assert_eq!(ResponseCode::from(response.code()), ResponseCode::Success);
+            response.body().clone().unwrap()
+        };
+
+        let body = query(RemotingCommand::create_remoting_command(
+            RequestCode::GetBrokerClusterInfo,
+        ))
+        .await;
+        let cluster_info = ClusterInfo::decode(body.as_ref()).unwrap();
+        assert!(
+            cluster_info.cluster_addr_table.unwrap()[cluster.cluster_name()]
+                .contains(cluster.broker_name())
+        );
+
+        let topic = CheetahString::from_static_str("TopicListTopic");
+        let body = query(RemotingCommand::create_remoting_command(
+            RequestCode::GetAllTopicListFromNameserver,
+        ))
+        .await;
+        assert!(TopicList::decode(body.as_ref())
+            .unwrap()
+            .topic_list
+            .contains(&topic));
+
+        let body = query(RemotingCommand::create_request_command(
+            RequestCode::GetTopicsByCluster,
+            GetTopicsByClusterRequestHeader::new(cluster.cluster_name().clone()),
+        ))
+        .await;
+        let topics_by_cluster = TopicList::decode(body.as_ref()).unwrap().topic_list;
+        assert_eq!(topics_by_cluster.iter().filter(|t| **t == topic).count(), 1);
+
+        let body = query(RemotingCommand::create_remoting_command(
+            RequestCode::GetSystemTopicListFromNs,
+        ))
+        .await;
+        let system_topics = TopicList::decode(body.as_ref()).unwrap();
+        assert!(system_topics.topic_list.contains(cluster.cluster_name()));
+        assert!(system_topics.topic_list.contains(cluster.broker_name()));
+        assert_eq!(
+            system_topics.broker_addr.as_ref(),
+            Some(cluster.broker_addr())
+        );
+
+        cluster.shutdown().await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn deleted_topic_leaves_broker_and_name_server() {
+        let cluster = TestCluster::start().await.unwrap();
+        let topic = CheetahString::from_static_str("DeletedTopic");
+        cluster.create_topic(topic.clone(), 2).await.unwrap();
+
+        let invoke = |addr: CheetahString, request: RemotingCommand| {
+            let admin_client = cluster.admin_client.clone();
+            async move {
+                admin_client
+                    .invoke_async(Some(&addr), request, REQUEST_TIMEOUT_MILLIS)
+                    .await
+                    .unwrap()
+            }
+        };
+
+        let response = invoke(
+            cluster.broker_addr().clone(),
+            RemotingCommand::create_request_command(
+                RequestCode::DeleteTopicInBroker,
+                DeleteTopicRequestHeader {
+                    topic: topic.clone(),
+                    topic_request_header: None,
+                },
+            ),
+        )
+        .await;
+        assert_eq!(ResponseCode::from(response.code()), ResponseCode::Success);
+        let response = invoke(
+            cluster.namesrv_addr().clone(),
+            RemotingCommand::create_request_command(
+                RequestCode::DeleteTopicInNamesrv,
+                DeleteTopicFromNamesrvRequestHeader::new(
+                    topic.clone(),
+                    Some(cluster.cluster_name().clone()),
+                ),
+            ),
+        )
+        .await;
+        assert_eq!(ResponseCode::from(response.code()), ResponseCode::Success);
+
+        let response = invoke(
+            cluster.namesrv_addr().clone(),
+            RemotingCommand::create_request_command(
+                RequestCode::GetRouteinfoByTopic,
+                GetRouteInfoRequestHeader {
+                    topic: topic.clone(),
+                    accept_standard_json_only: None,
+                    topic_request_header: None,
+                },
+            ),
+        )
+        .await;
+        assert_eq!(
+            ResponseCode::from(response.code()),
+            ResponseCode::TopicNotExist
+        );
+
+        cluster.shutdown().await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn pull_request_persists_piggybacked_consumer_offset() {
+        let cluster = TestCluster::start().await.unwrap();
+        let topic = CheetahString::from_static_str("PiggybackOffsetTopic");
+        let group = CheetahString::from_static_str("PiggybackOffsetGroup");
+        cluster.create_topic(topic.clone(), 1).await.unwrap();
+
+        let pull_header = PullMessageRequestHeader {
+            consumer_group: group.clone(),
+            topic: topic.clone(),
+            queue_id: 0,
+            queue_offset: 0,
+            max_msg_nums: 32,
+            sys_flag: PullSysFlag::build_sys_flag(true, false, true, false) as i32,
+            commit_offset: 7,
+            suspend_timeout_millis: 0,
+            subscription: Some(CheetahString::from_static_str("*")),
+            sub_version: 0,
+            expression_type: Some(CheetahString::from_static_str("TAG")),
+            max_msg_bytes: None,
+            request_source: None,
+            proxy_forward_client_id: None,
+            topic_request: None,
+        };
+        cluster
+            .admin_client
+            .invoke_async(
+                Some(cluster.broker_addr()),
+                RemotingCommand::create_request_command(RequestCode::PullMessage, pull_header),
+                REQUEST_TIMEOUT_MILLIS,
+            )
+            .await
+            .unwrap();
+
+        let query_header = QueryConsumerOffsetRequestHeader {
+            consumer_group: group,
+            topic,
+            queue_id: 0,
+            set_zero_if_not_found: None,
+            topic_request_header: None,
+        };
+        let response = cluster
+            .admin_client
+            .invoke_async(
+                Some(cluster.broker_addr()),
+                RemotingCommand::create_request_command(
+                    RequestCode::QueryConsumerOffset,
+                    query_header,
+                ),
+                REQUEST_TIMEOUT_MILLIS,
+            )
+            .await
+            .unwrap();
+        assert_eq!(ResponseCode::from(response.code()), ResponseCode::Success);
+        let offset = response
+            .decode_command_custom_header::<QueryConsumerOffsetResponseHeader>()
+            .unwrap()
+            .offset;
+        assert_eq!(offset, Some(7));
+
+        cluster.shutdown().await;
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn consumer_not_reading_its_responses_is_asked_to_pull_again() {
+        let cluster = TestCluster::builder()
+            .broker_server_config(|server_config| {
+                server_config.write_buffer_low_water_mark = 64 * 1024;
+                server_config.write_buffer_high_water_mark = 128 * 1024;
+            })
+            .message_store_config(|message_store_config| {
+                message_store_config.max_transfer_count_on_message_in_memory = 64;
+                message_store_config.max_transfer_bytes_on_message_in_memory = 1024 * 1024;
+            })
+            .start()
+            .await
+            .unwrap();
+        let topic = CheetahString::from_static_str("SlowConsumerTopic");
+        cluster.create_topic(topic.clone(), 1).await.unwrap();
+
+        let mut producer = producer(&cluster, "slow_consumer", None);
+        producer.start().await.unwrap();
+        // stays below the body size the producer compresses
+        let body = vec![b'x'; 4000];
+        for _ in 0..64 {
+            let send_result = producer
+                .send_with_timeout(
+                    Message::new(topic.as_str(), body.as_slice()),
+                    REQUEST_TIMEOUT_MILLIS,
+                )
+                .await
+                .unwrap();
+            assert_eq!(send_result.send_status, SendStatus::SendOk);
+        }
+        producer.shutdown().await;
+
+        let pull = || {
+            RemotingCommand::create_request_command(
+                RequestCode::PullMessage,
+                PullMessageRequestHeader {
+                    consumer_group: CheetahString::from_static_str("SlowConsumerGroup"),
+                    topic: topic.clone(),
+                    queue_id: 0,
+                    queue_offset: 0,
+                    max_msg_nums: 64,
+                    sys_flag: PullSysFlag::build_sys_flag(false, false, true, false) as i32,
+                    commit_offset: 0,
+                    suspend_timeout_millis: 0,
+                    subscription: Some(CheetahString::from_static_str("*")),
+                    sub_version: 0,
+                    expression_type: Some(CheetahString::from_static_str("TAG")),
+                    max_msg_bytes: None,
+                    request_source: None,
+                    proxy_forward_client_id: None,
+                    topic_request: None,
+                },
+            )
+        };
+        let stream = tokio::net::TcpStream::connect(cluster.broker_addr().as_str())
+            .await
+            .unwrap();
+        let mut connection = Connection::new(stream);
+        // each response holds about 256KB, together far more than the socket buffers take
+        let pulls = 128;
+        for _ in 0..pulls {
+            connection.send_command(pull()).await.unwrap();
+        }
+        // let the broker answer every pull while nothing is read
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let mut codes = Vec::with_capacity(pulls);
+        for _ in 0..pulls {
+            let response = connection.receive_command().await.unwrap().unwrap();
+            let code = ResponseCode::from(response.code());
+            if code == ResponseCode::PullRetryImmediately {
+                let response_header = response
+                    .decode_command_custom_header::<PullMessageResponseHeader>()
+                    .unwrap();
+                assert_eq!(response_header.next_begin_offset, 0);
+            }
+            codes.push(code);
+        }
+        assert_eq!(codes[0], ResponseCode::Success);
+        assert!(codes.contains(&ResponseCode::PullRetryImmediately));
+
+        // every response has been read, the broker writes messages again
+        connection.send_command(pull()).await.unwrap();
+        let response = connection.receive_command().await.unwrap().unwrap();
+        assert_eq!(ResponseCode::from(response.code()), ResponseCode::Success);
+
+        cluster.shutdown().await;
+    }
+
+    fn producer(
+        cluster: &TestCluster,
+        instance_name: &str,
+        session_credentials: Option<SessionCredentials>,
+    ) -> DefaultMQProducer {
+        let client_config = ClientConfig {
+            instance_name: instance_name.into(),
+            session_credentials,
+            ..ClientConfig::default()
+        };
+        DefaultMQProducer::builder()
+            .client_config(client_config)
+            .name_server_addr(cluster.namesrv_addr().clone())
+            .producer_group(format!("{instance_name}_producer_group"))
+            .retry_times_when_send_failed(0)
+            .build()
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn only_producers_signing_their_requests_can_send() {
+        let credentials = SessionCredentials::access_key("rocketmq", "12345678");
+        let cluster = TestCluster::builder()
+            .authentication(
+                HmacAuthenticationProvider::new([PlainAccessConfig {
+                    access_key: Some("rocketmq".into()),
+                    secret_key: Some("12345678".into()),
+                    ..PlainAccessConfig::default()
+                }]),
+                credentials.clone(),
+            )
+            .start()
+            .await
+            .unwrap();
+        let topic = "AuthenticatedTopic";
+        cluster.create_topic(topic, 1).await.unwrap();
+
+        let mut signed = producer(&cluster, "signed", Some(credentials));
+        signed.start().await.unwrap();
+        let send_result = signed
+            .send_with_timeout(Message::new(topic, b"signed"), REQUEST_TIMEOUT_MILLIS)
+            .await
+            .unwrap();
+        assert_eq!(send_result.send_status, SendStatus::SendOk);
+        signed.shutdown().await;
+
+        let mut unsigned = producer(&cluster, "unsigned", None);
+        unsigned.start().await.unwrap();
+        let send_result = unsigned
+            .send_with_timeout(Message::new(topic, b"unsigned"), REQUEST_TIMEOUT_MILLIS)
+            .await;
+        assert!(send_result.is_err(), "{send_result:?}");
+        unsigned.shutdown().await;
+
+        cluster.shutdown().await;
+    }
+}
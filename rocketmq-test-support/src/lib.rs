@@ -0,0 +1,29 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! Test support for end-to-end RocketMQ Rust tests.
+//!
+//! [`TestCluster`] boots a name server and a single broker inside the current tokio runtime,
+//! each on an ephemeral port and backed by a temporary data directory, so that client features
+//! such as pop consumption, transactions and ordered sends can be exercised in CI without an
+//! external deployment.
+
+pub use cluster::TestCluster;
+pub use cluster::TestClusterBuilder;
+
+mod cluster;
+mod port;
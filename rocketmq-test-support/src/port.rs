@@ -0,0 +1,64 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one or more
+ * contributor license agreements.  See the NOTICE file distributed with
+ * this work for additional information regarding copyright ownership.
+ * The ASF licenses this file to You under the Apache License, Version 2.0
+ * (the "License"); you may not use this file except in compliance with
+ * the License.  You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::net::Ipv4Addr;
+use std::net::TcpListener;
+
+use rocketmq_error::RocketMQResult;
+use rocketmq_error::RocketmqError;
+
+const MAX_ATTEMPTS: usize = 64;
+
+/// Offset of the broker's fast remoting port below its main listen port.
+pub(crate) const FAST_PORT_OFFSET: u32 = 2;
+
+/// Picks a free loopback port for a name server.
+pub(crate) fn pick_port() -> RocketMQResult<u32> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    Ok(listener.local_addr()?.port() as u32)
+}
+
+/// Picks a free loopback port for a broker. The broker also listens on `port - 2` for its fast
+/// remoting server, so both ports have to be available.
+pub(crate) fn pick_broker_port() -> RocketMQResult<u32> {
+    for _ in 0..MAX_ATTEMPTS {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        let port = listener.local_addr()?.port() as u32;
+        if port <= FAST_PORT_OFFSET + 1024 {
+            continue;
+        }
+        if TcpListener::bind((Ipv4Addr::LOCALHOST, (port - FAST_PORT_OFFSET) as u16)).is_ok() {
+            return Ok(port);
+        }
+    }
+    Err(RocketmqError::RemoteError(
+        "no free port pair found for the test broker".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn broker_port_leaves_room_for_fast_port() {
+        let port = pick_broker_port().unwrap();
+        assert!(port > FAST_PORT_OFFSET + 1024);
+        TcpListener::bind((Ipv4Addr::LOCALHOST, port as u16)).unwrap();
+        TcpListener::bind((Ipv4Addr::LOCALHOST, (port - FAST_PORT_OFFSET) as u16)).unwrap();
+    }
+}
@@ -32,6 +32,8 @@ use rocketmq_common::common::message::message_enum::MessageRequestMode;
 use rocketmq_common::common::message::message_queue::MessageQueue;
 use rocketmq_common::common::topic::TopicValidator;
 use rocketmq_remoting::protocol::admin::consume_stats::ConsumeStats;
+use rocketmq_remoting::protocol::admin::pop_stats::PopStats;
+use rocketmq_remoting::protocol::admin::topic_disk_usage::TopicDiskUsage;
 use rocketmq_remoting::protocol::admin::topic_stats_table::TopicStatsTable;
 use rocketmq_remoting::protocol::body::broker_body::cluster_info::ClusterInfo;
 use rocketmq_remoting::protocol::body::consume_message_directly_result::ConsumeMessageDirectlyResult;
@@ -233,7 +235,9 @@ impl MQAdminExt for DefaultMQAdminExt {
         broker_addr: CheetahString,
         properties: HashMap<CheetahString, CheetahString>,
     ) -> rocketmq_error::RocketMQResult<()> {
-        todo!()
+        self.default_mqadmin_ext_impl
+            .update_broker_config(broker_addr, properties)
+            .await
     }
 
     async fn get_broker_config(
@@ -356,7 +360,36 @@ impl MQAdminExt for DefaultMQAdminExt {
         broker_addr: Option<CheetahString>,
         timeout_millis: Option<u64>,
     ) -> rocketmq_error::RocketMQResult<ConsumeStats> {
-        todo!()
+        self.default_mqadmin_ext_impl
+            .examine_consume_stats(
+                consumer_group,
+                topic,
+                cluster_name,
+                broker_addr,
+                timeout_millis,
+            )
+            .await
+    }
+
+    async fn examine_pop_stats(
+        &self,
+        consumer_group: CheetahString,
+        topic: Option<CheetahString>,
+        broker_addr: Option<CheetahString>,
+    ) -> rocketmq_error::RocketMQResult<PopStats> {
+        self.default_mqadmin_ext_impl
+            .examine_pop_stats(consumer_group, topic, broker_addr)
+            .await
+    }
+
+    async fn examine_topic_disk_usage(
+        &self,
+        broker_addr: CheetahString,
+        topic: Option<CheetahString>,
+    ) -> rocketmq_error::RocketMQResult<TopicDiskUsage> {
+        self.default_mqadmin_ext_impl
+            .examine_topic_disk_usage(broker_addr, topic)
+            .await
     }
 
     async fn examine_broker_cluster_info(&self) -> rocketmq_error::RocketMQResult<ClusterInfo> {